@@ -0,0 +1,82 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use routing_project::net_utils::{decrypt, encrypt};
+use routing_project::packet;
+use routing_project::types::{LSAMessage, Neighbor, Prefix, RouteState};
+use std::collections::HashMap;
+
+const KEY: [u8; 32] = [7; 32];
+
+/// LSA routeur de 8 voisins annonçant `prefixes` préfixes, scellé comme à l'émission
+fn lsa(prefixes: usize) -> LSAMessage {
+    let neighbors: Vec<Neighbor> = (0..8)
+        .map(|i| Neighbor {
+            neighbor_ip: format!("10.0.{}.2", i),
+            link_up: true,
+            capacity: 1000,
+            last_seen: 0,
+            cost: None,
+            rtt_us: Some(250),
+            jitter_us: Some(20),
+            hostname: Some(format!("R{}", i)),
+            interface: Some(format!("eth{}", i)),
+            router_id: Some(format!("10.255.0.{}", i).parse().unwrap()),
+            priority: 1,
+        })
+        .collect();
+    let routing_table: HashMap<Prefix, RouteState> = (0..prefixes)
+        .map(|i| (format!("172.{}.{}.0/24", 16 + i / 256, i % 256).parse().unwrap(), RouteState::Active(i as u32 % 64)))
+        .collect();
+    let mut lsa = LSAMessage {
+        message_type: 2,
+        router_ip: "10.0.0.1".to_string(),
+        last_hop: None,
+        originator: "10.0.0.1".to_string(),
+        router_id: Some("10.255.0.1".parse().unwrap()),
+        seq_num: 0x8000_0001,
+        neighbor_count: neighbors.len(),
+        neighbors,
+        routing_table,
+        external_routes: HashMap::new(),
+        external_metric_types: HashMap::new(),
+        route_tags: HashMap::new(),
+        path: Vec::new(),
+        ttl: routing_project::INITIAL_TTL,
+        stub_only: false,
+        base_seq: None,
+        withdrawn: Vec::new(),
+        opaque: Vec::new(),
+        flush: false,
+        checksum: None,
+        part: None,
+    };
+    lsa.seal();
+    lsa
+}
+
+/// Chaîne d'émission et de réception d'un LSA : sérialisation JSON, somme de contrôle,
+/// chiffrement AES-256, puis déchiffrement et analyse stricte
+fn lsa_codec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lsa");
+    for prefixes in [10, 100, 1000] {
+        let message = lsa(prefixes);
+        let serialized = serde_json::to_vec(&message).unwrap();
+        let encrypted = encrypt(&serialized, &KEY).unwrap();
+        group.throughput(Throughput::Bytes(serialized.len() as u64));
+        group.bench_with_input(BenchmarkId::new("serialize", prefixes), &message, |b, message| {
+            b.iter(|| serde_json::to_vec(message).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("seal", prefixes), &message, |b, message| {
+            b.iter(|| message.clone().seal())
+        });
+        group.bench_with_input(BenchmarkId::new("encrypt", prefixes), &serialized, |b, serialized| {
+            b.iter(|| encrypt(serialized, &KEY).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("decrypt_parse", prefixes), &encrypted, |b, encrypted| {
+            b.iter(|| packet::parse(&decrypt(encrypted, &KEY).unwrap()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lsa_codec);
+criterion_main!(benches);
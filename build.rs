@@ -0,0 +1,6 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // protoc embarqué : aucune dépendance système pour compiler les définitions protobuf
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    tonic_build::compile_protos("proto/telemetry.proto")?;
+    Ok(())
+}
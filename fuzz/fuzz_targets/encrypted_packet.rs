@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use routing_project::framing;
+
+const KEY: [u8; 32] = [7; 32];
+
+fuzz_target!(|data: &[u8]| {
+    let Ok((header, body)) = framing::split(data) else {
+        return;
+    };
+    if let Ok(decrypted) = routing_project::net_utils::decrypt(body, &KEY) {
+        let _ = routing_project::packet::parse_typed(header.message_type, &decrypted);
+        routing_project::packet::fuzz_parse(&decrypted);
+    }
+});
@@ -0,0 +1,365 @@
+use routing_project::transport::Socket;
+use tokio::net::UdpSocket;
+use tokio::time::{timeout, timeout_at, Duration, Instant};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use routing_project::read_config;
+use routing_project::net_utils;
+use routing_project::{clock, control, control_auth, framing};
+use routing_project::fragment::{FragmentMessage, Reassembler};
+use routing_project::types::ControlMessage;
+use std::io;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+/// Délai d'attente maximal d'une réponse du serveur
+const RESPONSE_TIMEOUT_SEC: u64 = 3;
+const HISTORY_FILE: &str = ".pospf_cli_history";
+/// Codes de sortie du mode non interactif
+const EXIT_REJECTED: i32 = 1;
+const EXIT_UNREACHABLE: i32 = 2;
+
+/// Commandes connues du CLI, utilisées pour l'aide et la complétion
+const COMMANDS: &[(&str, &str)] = &[
+    ("enable", "Active le protocole OSPF"),
+    ("disable", "Désactive le protocole OSPF"),
+    ("routing-table", "Affiche la table de routage"),
+    ("neighbors", "Affiche les voisins OSPF (adresse IP et nom système des routeurs voisins)"),
+    ("neighbor-detail", "Affiche les compteurs et l'historique d'un voisin (neighbor-detail <ip>)"),
+    ("pending-routes", "Affiche les installations de routes en attente ou en échec (tentatives, dernière erreur)"),
+    ("filtered-routes", "Affiche les routes calculées mais écartées par les seuils d'installation (capacité, sauts)"),
+    ("set-cost", "Impose le coût d'une interface sans modifier la configuration (set-cost <iface> <coût|auto>)"),
+    ("set-link", "Active ou coupe une interface sans modifier la configuration (set-link <iface> up|down)"),
+    ("lsdb", "Affiche la LSDB (originateur, séquence, âge, préfixes et voisins de chaque LSA)"),
+    ("path", "Affiche le chemin calculé, routeur par routeur, vers un préfixe (path <préfixe|adresse> [routeur], demandé à ce routeur)"),
+    ("paths", "Affiche les N meilleurs chemins vers un préfixe (paths <préfixe|adresse> [--k N], 3 par défaut) ou deux chemins aussi disjoints que possible (--disjoint)"),
+    ("spof", "Affiche les routeurs et les liens dont la panne couperait le réseau (points d'articulation et ponts de la topologie)"),
+    ("mtu", "Affiche la MTU mesurée vers chaque voisin et la taille des fragments envoyés"),
+    ("sessions", "Affiche les sessions TCP ouvertes avec les voisins pour l'échange des LSA"),
+    ("history", "Affiche les derniers changements de la LSDB et des routes (history [n]) ou l'état reconstruit à un instant (history at <HH:MM[:SS]|secondes>)"),
+    ("ping", "Envoie un écho authentifié à un routeur par les routes installées ; affiche le temps aller-retour et le chemin (ping <routeur>)"),
+    ("reload-config", "Recharge la configuration du routeur sans redémarrage (reload-config [fichier])"),
+    ("trace", "Affiche les derniers messages capturés (trace [n|on|off|clear|save <fichier>])"),
+    ("metrics", "Affiche les compteurs du routeur (authentification des voisins...)"),
+    #[cfg(feature = "fault-injection")]
+    ("fault", "Injecte des pertes, retards, doublons ou altérations (fault [<voisin|*> drop <%>|delay <%> <ms>|duplicate <%>|corrupt <%>|clear])"),
+    ("conflicts", "Affiche les collisions d'identifiant détectées (autre routeur annonçant nos adresses)"),
+    ("vrf", "Liste les VRF, ou exécute une commande dans l'une d'elles (vrf <nom> <commande>)"),
+    ("help", "Affiche cette aide"),
+    ("exit", "Quitte le CLI"),
+];
+
+/// Complète le premier mot de la ligne avec les noms de commandes connus
+struct CommandHelper;
+
+impl Completer for CommandHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let prefix = &line[..pos];
+        if prefix.contains(char::is_whitespace) {
+            return Ok((pos, Vec::new()));
+        }
+        let candidates = COMMANDS.iter()
+            .filter(|(name, _)| name.starts_with(prefix))
+            .map(|(name, _)| Pair { display: name.to_string(), replacement: name.to_string() })
+            .collect();
+        Ok((0, candidates))
+    }
+}
+
+impl Hinter for CommandHelper {
+    type Hint = String;
+}
+
+impl Highlighter for CommandHelper {}
+
+impl Validator for CommandHelper {}
+
+impl Helper for CommandHelper {}
+
+fn help() {
+    println!("Commandes disponibles:");
+    for (name, description) in COMMANDS {
+        println!("  {:<14} - {}", name, description);
+    }
+}
+
+fn history_path() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(HISTORY_FILE)
+}
+
+fn readline_error(e: ReadlineError) -> io::Error {
+    io::Error::other(format!("Erreur de saisie: {}", e))
+}
+
+/// Lit une valeur avec une valeur par défaut si l'utilisateur ne saisit rien
+fn prompt_with_default(editor: &mut Editor<CommandHelper, DefaultHistory>, prompt: &str, default: &str) -> io::Result<String> {
+    let input = editor.readline(prompt).map_err(readline_error)?;
+    let input = input.trim();
+    Ok(if input.is_empty() { default.to_string() } else { input.to_string() })
+}
+
+/// Utilisateur et secret du canal de contrôle
+struct Credentials {
+    user: String,
+    token: String,
+}
+
+/// Identifiant de la prochaine commande envoyée par UDP
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Envoie une commande (signée si des identifiants sont fournis) et attend la réponse
+/// chiffrée du serveur portant son identifiant, avec un délai maximal ; les réponses
+/// tardives à des commandes précédentes sont écartées, comme les datagrammes illisibles ou
+/// venus d'une autre adresse que le serveur
+async fn send_command(socket: &Socket, server_addr: &SocketAddr, command: &str, key: &net_utils::SegmentKey, credentials: Option<&Credentials>) -> io::Result<String> {
+    let mut message = match credentials {
+        Some(credentials) => control_auth::signed_command(&credentials.user, &credentials.token, command, clock::unix_secs())
+            .map_err(|e| io::Error::other(format!("Erreur de signature: {}", e)))?,
+        None => ControlMessage {
+            message_type: 3,
+            command: String::from(command),
+            user: None,
+            timestamp: None,
+            signature: None,
+            request_id: None,
+        },
+    };
+    let request_id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+    message.request_id = Some(request_id);
+
+    net_utils::send_message(socket, server_addr, &message, key, "[CLI]").await.map_err(|e| {
+        io::Error::other(format!("Erreur d'envoi: {}", e))
+    })?;
+
+    // Une réponse volumineuse arrive en plusieurs fragments
+    let mut buffer = vec![0; 65535];
+    let mut reassembler = Reassembler::new();
+    let deadline = Instant::now() + Duration::from_secs(RESPONSE_TIMEOUT_SEC);
+    loop {
+        let (size, source) = timeout_at(deadline, socket.recv_from(&mut buffer))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, format!("Pas de réponse du serveur après {} secondes", RESPONSE_TIMEOUT_SEC)))??;
+
+        // Datagramme d'un autre émetteur, ou trame illisible : ignoré, la réponse peut encore arriver
+        if source != *server_addr {
+            eprintln!("Message ignoré de {}: ce n'est pas le serveur", source);
+            continue;
+        }
+        let message = match open_response(&buffer[..size], source, key, &mut reassembler) {
+            Ok(Some(message)) => message,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("Message ignoré de {}: {}", source, e);
+                continue;
+            }
+        };
+        let response = control::response_for(request_id, &message).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("Réponse invalide: {}", e))
+        })?;
+        if let Some(response) = response {
+            return Ok(response);
+        }
+    }
+}
+
+/// Déchiffre un datagramme du serveur ; `None` tant qu'une réponse fragmentée est incomplète
+fn open_response(data: &[u8], source: SocketAddr, key: &net_utils::SegmentKey, reassembler: &mut Reassembler) -> Result<Option<Vec<u8>>, String> {
+    let (header, body) = framing::split(data).map_err(|e| format!("Trame invalide: {}", e))?;
+    let decrypted = key.open(body).map_err(|e| format!("Erreur de déchiffrement: {}", e))?;
+    if header.message_type != framing::FRAGMENT {
+        return Ok(Some(decrypted));
+    }
+    let fragment: FragmentMessage = serde_json::from_slice(&decrypted).map_err(|e| format!("Fragment invalide: {}", e))?;
+    Ok(reassembler.insert(source, &fragment))
+}
+
+/// Canal vers le démon : messages UDP chiffrés ou socket Unix locale
+enum Connection {
+    Udp {
+        socket: Socket,
+        server_addr: SocketAddr,
+        key: net_utils::SegmentKey,
+        credentials: Option<Credentials>,
+    },
+    #[cfg(unix)]
+    Local(PathBuf),
+}
+
+impl Connection {
+    async fn send(&self, command: &str) -> io::Result<String> {
+        match self {
+            Connection::Udp { socket, server_addr, key, credentials } => {
+                send_command(socket, server_addr, command, key, credentials.as_ref()).await
+            }
+            #[cfg(unix)]
+            Connection::Local(path) => timeout(Duration::from_secs(RESPONSE_TIMEOUT_SEC), routing_project::control_socket::send_command(path, command))
+                .await
+                .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, format!("Pas de réponse du serveur après {} secondes", RESPONSE_TIMEOUT_SEC)))?,
+        }
+    }
+}
+
+/// Commande adressée à la VRF donnée par `--vrf <nom>`, s'il y a lieu
+fn in_vrf(command: &str) -> String {
+    match read_config::option_from_args(std::env::args().skip(1), "--vrf") {
+        Some(vrf) => format!("vrf {} {}", vrf, command),
+        None => command.to_string(),
+    }
+}
+
+/// Vrai si le routeur a refusé la commande ou signalé un échec, en texte ou en JSON
+fn is_rejected(response: &str) -> bool {
+    match serde_json::from_str::<serde_json::Value>(response) {
+        Ok(serde_json::Value::Object(object)) => object.get("ok") == Some(&serde_json::Value::Bool(false)),
+        _ => control::is_error_response(response),
+    }
+}
+
+/// Connexion UDP au routeur donné par `--server <ip[:port]>`, saisi par l'utilisateur
+/// en mode interactif, 127.0.0.1 sinon, avec la clé de la configuration
+async fn connect_udp(editor: Option<&mut Editor<CommandHelper, DefaultHistory>>) -> io::Result<Connection> {
+    let config_path = read_config::config_path_from_args(std::env::args().skip(1));
+    let config = read_config::read_router_config(config_path.as_deref()).map_err(|e| {
+        io::Error::other(format!("Erreur de configuration: {}", e))
+    })?;
+    let key = net_utils::SegmentKey::from_global(config.decoded_key());
+    let credentials = match read_config::option_from_args(std::env::args().skip(1), "--user") {
+        Some(user) => {
+            let token = std::env::var(read_config::CONTROL_TOKEN_ENV).map_err(|_| {
+                io::Error::other(format!("--user {} requiert le secret dans {}", user, read_config::CONTROL_TOKEN_ENV))
+            })?;
+            Some(Credentials { user, token })
+        }
+        None => None,
+    };
+
+    let server = match (read_config::option_from_args(std::env::args().skip(1), "--server"), editor) {
+        (Some(server), _) => server,
+        (None, Some(editor)) => {
+            let ip = prompt_with_default(editor, "Entrez l'adresse IP du serveur [127.0.0.1]: ", "127.0.0.1")?;
+            let default_port = config.port.to_string();
+            let port = prompt_with_default(editor, &format!("Entrez le port du serveur [{}]: ", default_port), &default_port)?;
+            format!("{}:{}", ip, port.parse().unwrap_or(config.port))
+        }
+        (None, None) => "127.0.0.1".to_string(),
+    };
+    let server_addr: SocketAddr = match server.parse() {
+        Ok(address) => address,
+        Err(_) => format!("{}:{}", server, config.port).parse().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("Adresse serveur invalide: {}", server))
+        })?,
+    };
+
+    let socket = Socket::from(UdpSocket::bind("0.0.0.0:0").await?);
+    Ok(Connection::Udp { socket, server_addr, key, credentials })
+}
+
+/// `--socket <chemin>` : démon local, ni clé ni adresse à saisir
+async fn connect(editor: Option<&mut Editor<CommandHelper, DefaultHistory>>) -> io::Result<Connection> {
+    match read_config::option_from_args(std::env::args().skip(1), "--socket") {
+        #[cfg(unix)]
+        Some(path) => Ok(Connection::Local(PathBuf::from(path))),
+        #[cfg(not(unix))]
+        Some(_) => Err(io::Error::new(io::ErrorKind::Unsupported, "--socket n'est disponible que sous Unix")),
+        None => connect_udp(editor).await,
+    }
+}
+
+/// Mode non interactif : une commande, sa réponse sur la sortie standard et un code
+/// de sortie exploitable par les scripts (0 succès, 1 refus ou échec, 2 routeur injoignable)
+async fn run_batch(command: &str) -> i32 {
+    if command == "help" {
+        help();
+        return 0;
+    }
+    let connection = match connect(None).await {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("Erreur: {}", e);
+            return EXIT_UNREACHABLE;
+        }
+    };
+    match connection.send(&in_vrf(command)).await {
+        Ok(response) => {
+            println!("{}", response);
+            if is_rejected(&response) { EXIT_REJECTED } else { 0 }
+        }
+        Err(e) => {
+            eprintln!("Erreur: {}", e);
+            EXIT_UNREACHABLE
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    if let Some(command) = control::command_from_args(std::env::args().skip(1)) {
+        std::process::exit(run_batch(&command).await);
+    }
+
+    let mut editor: Editor<CommandHelper, DefaultHistory> = Editor::new().map_err(readline_error)?;
+    editor.set_helper(Some(CommandHelper));
+    let history = history_path();
+    let _ = editor.load_history(&history);
+
+    let connection = connect(Some(&mut editor)).await?;
+    match &connection {
+        Connection::Udp { server_addr, .. } => println!("Connexion au serveur {}...", server_addr),
+        #[cfg(unix)]
+        Connection::Local(path) => println!("Connexion à la socket {}...", path.display()),
+    }
+
+    match connection.send("connexion").await {
+        Ok(response) => println!("Réponse du serveur: {}", response),
+        Err(e) => println!("Avertissement: {}", e),
+    }
+
+    println!("\nBienvenue dans le CLI OSPF");
+    help();
+
+    loop {
+        let line = match editor.readline("\n> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(readline_error(e)),
+        };
+        let command = line.trim();
+        if command.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(command);
+
+        if command == "exit" {
+            break;
+        } else if command == "help" {
+            help();
+            continue;
+        }
+
+        match connection.send(&in_vrf(command)).await {
+            Ok(text) => {
+                println!("Réponse:");
+                println!("{}", text);
+            }
+            Err(e) => println!("Erreur: {}", e),
+        }
+    }
+
+    if let Err(e) = editor.save_history(&history) {
+        println!("Impossible d'enregistrer l'historique: {}", e);
+    }
+    println!("Au revoir!");
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use pospf_core::dijkstra::{NetworkTopology, PathSelection};
+
+/// Anneau de `n` routeurs avec deux cordes par routeur (degré moyen 6), capacités
+/// variées pour que les politiques de sélection divergent
+fn topology(n: usize) -> NetworkTopology {
+    let router = |i: usize| format!("10.{}.{}.1", i / 256, i % 256);
+    let mut topology = NetworkTopology::new();
+    for i in 0..n {
+        topology.add_router(router(i), Vec::new());
+    }
+    for i in 0..n {
+        let capacity = [10, 100, 1000][i % 3];
+        topology.add_link(router(i), router((i + 1) % n), capacity, true);
+        topology.add_link(router(i), router((i * 7 + 3) % n), capacity * 10, true);
+        topology.add_link(router(i), router((i + n / 2) % n), 100, i % 5 != 0);
+    }
+    topology
+}
+
+fn shortest_paths(c: &mut Criterion) {
+    let mut group = c.benchmark_group("calculate_shortest_paths");
+    for n in [50, 500, 5000] {
+        let topology = topology(n);
+        if n >= 5000 {
+            group.sample_size(10);
+        }
+        for policy in [PathSelection::MinCost, PathSelection::MaxBandwidth] {
+            group.bench_with_input(BenchmarkId::new(format!("{:?}", policy), n), &topology, |b, topology| {
+                b.iter(|| topology.calculate_shortest_paths("10.0.0.1", policy))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, shortest_paths);
+criterion_main!(benches);
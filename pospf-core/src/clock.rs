@@ -0,0 +1,19 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Heure murale et instant réel relevés au premier appel
+static ORIGIN: OnceLock<(Duration, Instant)> = OnceLock::new();
+
+/// Temps écoulé depuis l'époque Unix, avancé par l'horloge de tokio : monotone, et
+/// suspendu ou accéléré avec elle dans une simulation à temps contrôlé
+pub fn unix_time() -> Duration {
+    let (wall, origin) = ORIGIN.get_or_init(|| {
+        (SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default(), Instant::now())
+    });
+    *wall + tokio::time::Instant::now().into_std().saturating_duration_since(*origin)
+}
+
+/// Secondes depuis l'époque Unix (horodatage des voisins, numéros de séquence)
+pub fn unix_secs() -> u64 {
+    unix_time().as_secs()
+}
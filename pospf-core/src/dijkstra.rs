@@ -0,0 +1,419 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// Critère de choix des chemins dans le calcul SPF
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathSelection {
+    /// Coût OSPF cumulé minimal (nombre de sauts puis goulot en départage)
+    #[default]
+    MinCost,
+    /// Chemin dont le lien le plus lent est le plus rapide
+    MaxBandwidth,
+    /// Latence cumulée minimale, d'après le RTT mesuré sur les HELLO
+    MinLatency,
+}
+
+// Nœud dans le graphe
+#[derive(Debug, Clone)]
+pub struct NetworkNode {
+    pub router_id: String,
+    pub interfaces: Vec<InterfaceInfo>,
+    pub is_reachable: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct InterfaceInfo {
+    pub name: String,
+    pub network: String,
+    pub capacity_mbps: u32,
+    pub is_active: bool,
+    pub connected_to: Option<String>,
+}
+
+/// Représente un lien
+#[derive(Debug, Clone)]
+pub struct NetworkLink {
+    pub from: String,
+    pub to: String,
+    pub cost: u32,
+    pub capacity_mbps: u32,
+    pub is_active: bool,
+    pub hop_count: u32,
+    /// Latence mesurée du lien en microsecondes, si connue
+    pub latency_us: Option<u32>,
+}
+
+/// Latence attribuée à un lien dont le RTT n'a pas encore été mesuré
+pub const DEFAULT_LINK_LATENCY_US: u32 = 1000;
+
+/// Critères cumulés d'un chemin
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) struct PathMetrics {
+    pub(crate) total_cost: u32,
+    pub(crate) hop_count: u32,
+    pub(crate) bottleneck_capacity: u32,
+    pub(crate) total_latency_us: u32,
+}
+
+impl PathMetrics {
+    /// Chemin vide, au départ du routeur source
+    pub(crate) const ORIGIN: Self = Self {
+        total_cost: 0,
+        hop_count: 0,
+        bottleneck_capacity: u32::MAX,
+        total_latency_us: 0,
+    };
+
+    /// Critères d'un lien seul
+    pub(crate) fn of_link(link: &NetworkLink) -> Self {
+        Self {
+            total_cost: link.cost,
+            hop_count: link.hop_count,
+            bottleneck_capacity: link.capacity_mbps,
+            total_latency_us: link.latency_us.unwrap_or(DEFAULT_LINK_LATENCY_US),
+        }
+    }
+
+    /// Critères du chemin prolongé par `next`
+    pub(crate) fn then(&self, next: &Self) -> Self {
+        Self {
+            total_cost: self.total_cost.saturating_add(next.total_cost),
+            hop_count: self.hop_count + next.hop_count,
+            bottleneck_capacity: self.bottleneck_capacity.min(next.bottleneck_capacity),
+            total_latency_us: self.total_latency_us.saturating_add(next.total_latency_us),
+        }
+    }
+
+    /// Clé de tri selon la politique : la plus petite désigne le meilleur chemin
+    pub(crate) fn key(&self, policy: PathSelection) -> (u32, u32, u32) {
+        let narrowness = u32::MAX - self.bottleneck_capacity;
+        match policy {
+            // (1) coût OSPF, (2) nombre de sauts, (3) capacité du goulot d'étranglement
+            PathSelection::MinCost => (self.total_cost, self.hop_count, narrowness),
+            PathSelection::MaxBandwidth => (narrowness, self.total_cost, self.hop_count),
+            PathSelection::MinLatency => (self.total_latency_us, self.total_cost, self.hop_count),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct DijkstraNode {
+    router_id: String,
+    metrics: PathMetrics,
+    policy: PathSelection,
+    path: Vec<String>,
+}
+
+impl Ord for DijkstraNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Tas binaire max : on inverse pour extraire le meilleur chemin en premier
+        other.metrics.key(other.policy).cmp(&self.metrics.key(self.policy))
+    }
+}
+
+impl PartialOrd for DijkstraNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NetworkTopology {
+    pub nodes: HashMap<String, NetworkNode>,
+    pub links: Vec<NetworkLink>,
+}
+
+impl Default for NetworkTopology {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetworkTopology {
+    pub fn new() -> Self {
+        Self {
+            nodes: HashMap::new(),
+            links: Vec::new(),
+        }
+    }
+
+    pub fn add_router(&mut self, router_id: String, interfaces: Vec<InterfaceInfo>) {
+        let node = NetworkNode {
+            router_id: router_id.clone(),
+            interfaces,
+            is_reachable: true,
+        };
+        self.nodes.insert(router_id, node);
+    }
+
+    pub fn add_link(&mut self, from: String, to: String, capacity_mbps: u32, is_active: bool) {
+        let cost = calculate_ospf_cost(capacity_mbps, is_active);
+        // Lien direct
+        self.links.push(NetworkLink {
+            from: from.clone(),
+            to: to.clone(),
+            cost,
+            capacity_mbps,
+            is_active,
+            hop_count: 1,
+            latency_us: None,
+        });
+        // Lien de retour (bidirectionnel)
+        self.links.push(NetworkLink {
+            from: to,
+            to: from,
+            cost,
+            capacity_mbps,
+            is_active,
+            hop_count: 1,
+            latency_us: None,
+        });
+    }
+
+    pub fn add_link_with_min_capacity(&mut self, from: String, to: String, local_capacity: u32, neighbor_capacity: u32, is_active: bool) {
+        let min_capacity = local_capacity.min(neighbor_capacity);
+        let cost = calculate_ospf_cost(min_capacity, is_active);
+        // Lien direct
+        self.links.push(NetworkLink {
+            from: from.clone(),
+            to: to.clone(),
+            cost,
+            capacity_mbps: min_capacity,
+            is_active,
+            hop_count: 1,
+            latency_us: None,
+        });
+        // Lien de retour (bidirectionnel)
+        self.links.push(NetworkLink {
+            from: to,
+            to: from,
+            cost,
+            capacity_mbps: min_capacity,
+            is_active,
+            hop_count: 1,
+            latency_us: None,
+        });
+    }
+
+    /// Lien bidirectionnel dont le coût est imposé (coût statique d'interface)
+    pub fn add_link_with_cost(&mut self, from: String, to: String, capacity_mbps: u32, cost: u32, is_active: bool) {
+        let cost = if is_active { cost } else { u32::MAX };
+        for (from, to) in [(from.clone(), to.clone()), (to, from)] {
+            self.links.push(NetworkLink {
+                from,
+                to,
+                cost,
+                capacity_mbps,
+                is_active,
+                hop_count: 1,
+                latency_us: None,
+            });
+        }
+    }
+
+    /// Lien dans un seul sens, tel qu'annoncé par le routeur `from` dans son LSA
+    pub fn add_directed_link(&mut self, from: String, to: String, capacity_mbps: u32, cost: u32, latency_us: Option<u32>) {
+        self.links.push(NetworkLink {
+            from,
+            to,
+            cost,
+            capacity_mbps,
+            is_active: true,
+            hop_count: 1,
+            latency_us,
+        });
+    }
+
+    /// Rattache un routeur au pseudo-nœud d'un segment à accès multiple : lien de coût nul,
+    /// qui ne compte pas comme un saut
+    pub fn add_segment_member(&mut self, segment: String, router: String) {
+        self.links.push(NetworkLink {
+            from: segment,
+            to: router,
+            cost: 0,
+            capacity_mbps: u32::MAX,
+            is_active: true,
+            hop_count: 0,
+            latency_us: Some(0),
+        });
+    }
+
+    /// Renseigne la latence (dans les deux sens) d'un lien existant
+    pub fn set_link_latency(&mut self, a: &str, b: &str, latency_us: u32) {
+        for link in self.links.iter_mut() {
+            if (link.from == a && link.to == b) || (link.from == b && link.to == a) {
+                link.latency_us = Some(latency_us);
+            }
+        }
+    }
+
+    pub fn get_active_neighbors(&self, router_id: &str) -> Vec<&NetworkLink> {
+        self.links.iter()
+            .filter(|link| link.from == router_id && link.is_active)
+            .collect()
+    }
+
+    pub fn find_link(&self, from: &str, to: &str) -> Option<&NetworkLink> {
+        self.links.iter()
+            .find(|link| link.from == from && link.to == to)
+    }
+
+    /// Meilleurs chemins depuis `source` selon la politique de sélection
+    /// (coût OSPF, bande passante du goulot ou latence cumulée), liens actifs uniquement
+    pub fn calculate_shortest_paths(&self, source: &str, policy: PathSelection) -> HashMap<String, RouteInfo> {
+        let mut best: HashMap<String, PathMetrics> = HashMap::new();
+        let mut paths: HashMap<String, Vec<String>> = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut heap = BinaryHeap::new();
+
+        let origin = PathMetrics::ORIGIN;
+        best.insert(source.to_string(), origin);
+        paths.insert(source.to_string(), vec![source.to_string()]);
+
+        heap.push(DijkstraNode {
+            router_id: source.to_string(),
+            metrics: origin,
+            policy,
+            path: vec![source.to_string()],
+        });
+
+        // Dijkstra
+        while let Some(current) = heap.pop() {
+            if visited.contains(&current.router_id) {
+                continue;
+            }
+            visited.insert(current.router_id.clone());
+
+            // Explorer les voisins actifs uniquement
+            for link in self.get_active_neighbors(&current.router_id) {
+                if visited.contains(&link.to) {
+                    continue;
+                }
+
+                let total_cost = match current.metrics.total_cost.checked_add(link.cost) {
+                    Some(cost) => cost,
+                    None => continue,
+                };
+                let candidate = PathMetrics {
+                    total_cost,
+                    hop_count: current.metrics.hop_count + link.hop_count,
+                    bottleneck_capacity: current.metrics.bottleneck_capacity.min(link.capacity_mbps),
+                    total_latency_us: current.metrics.total_latency_us
+                        .saturating_add(link.latency_us.unwrap_or(DEFAULT_LINK_LATENCY_US)),
+                };
+
+                // Mettre à jour si on a trouvé un meilleur chemin selon la politique
+                let improves = best.get(&link.to)
+                    .is_none_or(|known| candidate.key(policy) < known.key(policy));
+                if improves {
+                    best.insert(link.to.clone(), candidate);
+                    
+                    let mut new_path = current.path.clone();
+                    new_path.push(link.to.clone());
+                    paths.insert(link.to.clone(), new_path.clone());
+
+                    heap.push(DijkstraNode {
+                        router_id: link.to.clone(),
+                        metrics: candidate,
+                        policy,
+                        path: new_path,
+                    });
+                }
+            }
+        }
+
+        let mut routes = HashMap::new();
+        for (dest, metrics) in best {
+            if dest != source {
+                let path = paths.get(&dest).cloned().unwrap_or_default();
+                let next_hop = if path.len() > 1 { path[1].clone() } else { dest.clone() };
+                
+                routes.insert(dest.clone(), RouteInfo {
+                    destination: dest.clone(),
+                    next_hop,
+                    total_cost: metrics.total_cost,
+                    hop_count: metrics.hop_count,
+                    bottleneck_capacity: metrics.bottleneck_capacity,
+                    total_latency_us: metrics.total_latency_us,
+                    policy,
+                    path,
+                    is_reachable: true,
+                });
+            }
+        }
+
+        routes
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RouteInfo {
+    pub destination: String,
+    pub next_hop: String,
+    pub total_cost: u32,
+    pub hop_count: u32,
+    pub bottleneck_capacity: u32,
+    /// Latence cumulée estimée du chemin (microsecondes)
+    pub total_latency_us: u32,
+    /// Politique ayant servi à choisir ce chemin
+    pub policy: PathSelection,
+    pub path: Vec<String>,
+    pub is_reachable: bool,
+}
+
+impl RouteInfo {
+    /// Clé de comparaison selon la politique qui a choisi ce chemin
+    pub fn key(&self) -> (u32, u32, u32) {
+        PathMetrics {
+            total_cost: self.total_cost,
+            hop_count: self.hop_count,
+            bottleneck_capacity: self.bottleneck_capacity,
+            total_latency_us: self.total_latency_us,
+        }.key(self.policy)
+    }
+}
+
+/// Adresses des voisins directs regroupées par identité (identifiant annoncé, ou adresse)
+pub fn neighbor_nodes(neighbors: &HashMap<String, crate::types::Neighbor>) -> HashMap<String, Vec<String>> {
+    let mut nodes: HashMap<String, Vec<String>> = HashMap::new();
+    for neighbor in neighbors.values() {
+        nodes.entry(neighbor.identity()).or_default().push(neighbor.neighbor_ip.clone());
+    }
+    nodes
+}
+
+/// Meilleur chemin vers l'originateur d'un LSA : par la meilleure des adjacences avec ce
+/// routeur (liens parallèles), ou directement s'il est identifié par son adresse
+pub fn route_to_originator<'a>(shortest_paths: &'a HashMap<String, RouteInfo>, nodes: &HashMap<String, Vec<String>>, originator: &str) -> Option<&'a RouteInfo> {
+    match nodes.get(originator) {
+        Some(addresses) => addresses.iter().filter_map(|address| shortest_paths.get(address)).min_by_key(|route| route.key()),
+        None => shortest_paths.get(originator),
+    }
+}
+
+pub fn calculate_ospf_cost(capacity_mbps: u32, is_active: bool) -> u32 {
+    if !is_active {
+        return u32::MAX;
+    }
+    
+    // Éviter la division par zéro
+    if capacity_mbps == 0 {
+        return u32::MAX;
+    }
+    
+    // Formule OSPF standard : référence de 100 Mbps
+    let reference_bandwidth = 100_000_000u64; // 100 Mbps en bps
+    let bandwidth_bps = capacity_mbps as u64 * 1_000_000;
+    
+    // Éviter la division par zéro
+    if bandwidth_bps == 0 {
+        return u32::MAX;
+    }
+    
+    let cost = (reference_bandwidth / bandwidth_bps) as u32;
+    
+    // Coût minimum de 1
+    cost.max(1)
+}
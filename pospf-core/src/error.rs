@@ -9,6 +9,7 @@ pub enum AppError {
     SerializationError(serde_json::Error),
     RouteError(String),
     CryptoError(String),
+    PacketError(String),
 }
 
 impl fmt::Display for AppError {
@@ -20,6 +21,7 @@ impl fmt::Display for AppError {
             AppError::SerializationError(err) => write!(f, "Serialization error: {}", err),
             AppError::RouteError(msg) => write!(f, "Route error: {}", msg),
             AppError::CryptoError(msg) => write!(f, "Crypto error: {}", msg),
+            AppError::PacketError(msg) => write!(f, "Invalid packet: {}", msg),
         }
     }
 }
@@ -0,0 +1,51 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Nombre d'événements conservés pour un abonné trop lent avant qu'il n'en perde
+const EVENT_CAPACITY: usize = 256;
+
+/// Changement d'état du protocole, diffusé à tous les abonnés
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RouterEvent {
+    NeighborUp { neighbor: String, interface: Option<String> },
+    NeighborDown { neighbor: String, reason: String },
+    /// Nouvelle instance de LSA installée dans la LSDB
+    LsaReceived { originator: String, seq_num: u32 },
+    SpfCompleted { routes: usize, updated: usize, duration_us: u64 },
+    RouteInstalled { prefix: String, next_hop: String },
+    /// Route retirée de la table du système
+    RouteRemoved { prefix: String },
+    RouteFailed { prefix: String, next_hop: String, error: String },
+    /// LSA d'un autre routeur annonçant sous un de nos identifiants
+    RouterIdConflict { originator: String, source: String },
+}
+
+/// Canal de diffusion des événements d'un routeur
+#[derive(Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<RouterEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CAPACITY);
+        Self { sender }
+    }
+
+    /// Publie un événement ; sans abonné, il est simplement ignoré
+    pub fn emit(&self, event: RouterEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Reçoit les événements publiés à partir de maintenant
+    pub fn subscribe(&self) -> broadcast::Receiver<RouterEvent> {
+        self.sender.subscribe()
+    }
+}
@@ -0,0 +1,141 @@
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Taille maximale d'un message envoyé en un seul datagramme ; au-delà il est découpé
+/// (fragments chiffrés sous la MTU Ethernet)
+pub const MAX_FRAGMENT_PAYLOAD: usize = 960;
+/// MTU supposée d'un lien dont la MTU n'a pas été mesurée (Ethernet)
+pub const DEFAULT_MTU: u16 = 1500;
+/// Plus petite MTU prise en charge (minimum IPv4 des hôtes)
+pub const MIN_MTU: u16 = 576;
+/// Nombre maximal de fragments par message (couvre `packet::MAX_PACKET_LEN` en fragments
+/// dimensionnés pour `MIN_MTU`)
+pub const MAX_FRAGMENTS: u16 = 256;
+/// Délai au-delà duquel un message incomplet est abandonné
+pub const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Nombre maximal de messages en cours de réassemblage
+const MAX_PENDING: usize = 256;
+
+/// Fragment d'un message trop grand pour un datagramme (message_type 7)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FragmentMessage {
+    pub message_type: u8,
+    /// Identifiant commun aux fragments d'un même message
+    pub message_id: u32,
+    pub index: u16,
+    pub count: u16,
+    /// Morceau du message sérialisé, encodé en base64
+    pub data: String,
+}
+
+static NEXT_MESSAGE_ID: AtomicU32 = AtomicU32::new(0);
+
+/// En-têtes IPv4 et UDP d'un datagramme
+const IP_UDP_HEADERS: usize = 28;
+/// Surcoût maximal de la protection d'une trame (IV et bourrage AES, ou HMAC)
+const PROTECTION_OVERHEAD: usize = 32;
+/// Champs JSON d'un fragment autour de ses données, valeurs maximales comprises
+const FRAGMENT_ENVELOPE: usize = 96;
+
+/// Taille de morceau dont les fragments, encodés, protégés et tramés, tiennent dans un
+/// datagramme de `mtu` octets ; jamais plus que `MAX_FRAGMENT_PAYLOAD`
+pub fn payload_for_mtu(mtu: u16) -> usize {
+    let overhead = IP_UDP_HEADERS + crate::framing::HEADER_LEN + PROTECTION_OVERHEAD + FRAGMENT_ENVELOPE;
+    // Encodage base64 : 4 caractères pour 3 octets
+    let body = (mtu.max(MIN_MTU) as usize).saturating_sub(overhead);
+    (body / 4 * 3).min(MAX_FRAGMENT_PAYLOAD)
+}
+
+/// Découpe un message sérialisé en fragments numérotés ; `None` s'il tient dans un datagramme
+pub fn split(serialized: &[u8]) -> Option<Vec<FragmentMessage>> {
+    split_to(serialized, MAX_FRAGMENT_PAYLOAD)
+}
+
+/// Comme `split`, avec des morceaux d'au plus `max_payload` octets (voir `payload_for_mtu`)
+pub fn split_to(serialized: &[u8], max_payload: usize) -> Option<Vec<FragmentMessage>> {
+    let max_payload = max_payload.clamp(payload_for_mtu(MIN_MTU), MAX_FRAGMENT_PAYLOAD);
+    if serialized.len() <= max_payload {
+        return None;
+    }
+    let message_id = NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed);
+    let chunks: Vec<&[u8]> = serialized.chunks(max_payload).collect();
+    let count = chunks.len() as u16;
+    Some(chunks.into_iter().enumerate().map(|(index, chunk)| FragmentMessage {
+        message_type: 7,
+        message_id,
+        index: index as u16,
+        count,
+        data: general_purpose::STANDARD.encode(chunk),
+    }).collect())
+}
+
+#[derive(Debug)]
+struct PendingMessage {
+    started: Instant,
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+/// Réassemblage des messages fragmentés, par émetteur et identifiant de message
+#[derive(Debug, Default)]
+pub struct Reassembler {
+    pending: HashMap<(SocketAddr, u32), PendingMessage>,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ajoute un fragment ; renvoie le message complet une fois tous les fragments reçus
+    pub fn insert(&mut self, source: SocketAddr, fragment: &FragmentMessage) -> Option<Vec<u8>> {
+        if fragment.count == 0 || fragment.count > MAX_FRAGMENTS || fragment.index >= fragment.count {
+            return None;
+        }
+        let data = general_purpose::STANDARD.decode(&fragment.data).ok()?;
+        if data.len() > MAX_FRAGMENT_PAYLOAD {
+            return None;
+        }
+        self.expire();
+        let key = (source, fragment.message_id);
+        if !self.pending.contains_key(&key) && self.pending.len() >= MAX_PENDING {
+            let oldest = self.pending.iter().min_by_key(|(_, pending)| pending.started).map(|(key, _)| *key);
+            if let Some(oldest) = oldest {
+                self.pending.remove(&oldest);
+            }
+        }
+        let pending = self.pending.entry(key).or_insert_with(|| PendingMessage {
+            started: Instant::now(),
+            chunks: vec![None; fragment.count as usize],
+        });
+        // Un identifiant réutilisé avec un autre nombre de fragments : recommencer
+        if pending.chunks.len() != fragment.count as usize {
+            *pending = PendingMessage {
+                started: Instant::now(),
+                chunks: vec![None; fragment.count as usize],
+            };
+        }
+        pending.chunks[fragment.index as usize] = Some(data);
+        if pending.chunks.iter().any(Option::is_none) {
+            return None;
+        }
+        let pending = self.pending.remove(&key)?;
+        Some(pending.chunks.into_iter().flatten().flatten().collect())
+    }
+
+    fn expire(&mut self) {
+        self.pending.retain(|_, pending| pending.started.elapsed() < REASSEMBLY_TIMEOUT);
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
@@ -0,0 +1,213 @@
+use crate::error::{AppError, Result};
+use crate::fragment::FragmentMessage;
+use crate::packet::MAX_PACKET_LEN;
+use crate::types::{AuthChallenge, AuthMode, AuthResponse, ControlMessage, ControlResponse, EchoMessage, HelloMessage, LSAMessage, LsaAckMessage, RouteRequest, RouteResponse};
+
+/// Version du format de trame
+pub const VERSION: u8 = 1;
+/// Taille de l'en-tête en clair : version, type, codec, protection et longueur du corps
+pub const HEADER_LEN: usize = 6;
+/// Taille maximale du corps d'une trame
+pub const MAX_BODY_LEN: usize = MAX_PACKET_LEN - HEADER_LEN;
+
+/// Type des HELLO, servis par leur propre file de réception
+pub const HELLO: u8 = 1;
+/// Type des commandes de contrôle, servies par leur propre file de réception
+pub const CONTROL: u8 = 3;
+/// Type des fragments d'un message plus grand que la MTU
+pub const FRAGMENT: u8 = 7;
+/// Type des réponses du routeur aux commandes de contrôle (texte)
+pub const CONTROL_RESPONSE: u8 = 9;
+/// Type des réponses aux requêtes de route, dernier type connu
+pub const ROUTE_RESPONSE: u8 = 11;
+
+/// Encodage du corps d'une trame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Json,
+}
+
+impl Codec {
+    fn to_byte(self) -> u8 {
+        match self {
+            Codec::Json => 0,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Codec::Json),
+            _ => None,
+        }
+    }
+}
+
+fn auth_to_byte(auth: AuthMode) -> u8 {
+    match auth {
+        AuthMode::None => 0,
+        AuthMode::Aes => 1,
+        AuthMode::Hmac => 2,
+    }
+}
+
+fn auth_from_byte(byte: u8) -> Option<AuthMode> {
+    match byte {
+        0 => Some(AuthMode::None),
+        1 => Some(AuthMode::Aes),
+        2 => Some(AuthMode::Hmac),
+        _ => None,
+    }
+}
+
+/// En-tête d'une trame, lisible sans la clé : le récepteur aiguille et rejette les trames
+/// avant tout déchiffrement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub message_type: u8,
+    pub codec: Codec,
+    /// Protection appliquée au corps
+    pub auth: AuthMode,
+    pub length: u16,
+}
+
+impl FrameHeader {
+    fn encode(&self) -> [u8; HEADER_LEN] {
+        let [high, low] = self.length.to_be_bytes();
+        [VERSION, self.message_type, self.codec.to_byte(), auth_to_byte(self.auth), high, low]
+    }
+}
+
+/// Trame d'un corps déjà protégé selon `auth`
+pub fn frame(message_type: u8, codec: Codec, auth: AuthMode, body: &[u8]) -> Result<Vec<u8>> {
+    if body.len() > MAX_BODY_LEN {
+        return Err(AppError::PacketError(format!("{} bytes frame body exceeds the {} bytes limit", body.len(), MAX_BODY_LEN)));
+    }
+    let header = FrameHeader { message_type, codec, auth, length: body.len() as u16 };
+    let mut datagram = Vec::with_capacity(HEADER_LEN + body.len());
+    datagram.extend_from_slice(&header.encode());
+    datagram.extend_from_slice(body);
+    Ok(datagram)
+}
+
+/// Sépare l'en-tête et le corps d'une trame reçue ; une version, un type, un codec ou une
+/// protection inconnus, ou une longueur ne correspondant pas au datagramme, sont rejetés
+pub fn split(datagram: &[u8]) -> Result<(FrameHeader, &[u8])> {
+    let invalid = |reason: String| AppError::PacketError(reason);
+    let Some((header, body)) = datagram.split_first_chunk::<HEADER_LEN>() else {
+        return Err(invalid(format!("{} bytes is too short for a frame header", datagram.len())));
+    };
+    let [version, message_type, codec, auth, high, low] = *header;
+    if version != VERSION {
+        return Err(invalid(format!("unsupported frame version {}", version)));
+    }
+    if !(1..=ROUTE_RESPONSE).contains(&message_type) {
+        return Err(invalid(format!("unknown message type {}", message_type)));
+    }
+    let codec = Codec::from_byte(codec).ok_or_else(|| invalid(format!("unknown codec {}", codec)))?;
+    let auth = auth_from_byte(auth).ok_or_else(|| invalid(format!("unknown auth mode {}", auth)))?;
+    let length = u16::from_be_bytes([high, low]);
+    if length as usize != body.len() {
+        return Err(invalid(format!("frame announces {} bytes but carries {}", length, body.len())));
+    }
+    Ok((FrameHeader { message_type, codec, auth, length }, body))
+}
+
+/// Type annoncé par une trame, sans la valider
+pub fn peek_type(datagram: &[u8]) -> Option<u8> {
+    match datagram {
+        [VERSION, message_type, ..] => Some(*message_type),
+        _ => None,
+    }
+}
+
+/// Message émis dans une trame, qui en porte le type dans son en-tête
+pub trait Framed: serde::Serialize {
+    fn message_type(&self) -> u8;
+
+    /// Messages complets, d'au plus `max_len` octets sérialisés, qui remplacent celui-ci
+    /// s'il est trop grand ; `None` s'il ne se découpe pas ainsi (il est alors fragmenté)
+    fn split_within(&self, _max_len: usize) -> Option<Vec<Self>>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+impl Framed for HelloMessage {
+    fn message_type(&self) -> u8 {
+        self.message_type
+    }
+}
+
+impl Framed for LSAMessage {
+    fn message_type(&self) -> u8 {
+        self.message_type
+    }
+
+    fn split_within(&self, max_len: usize) -> Option<Vec<Self>> {
+        crate::lsa_part::split(self, max_len)
+    }
+}
+
+impl Framed for ControlMessage {
+    fn message_type(&self) -> u8 {
+        self.message_type
+    }
+}
+
+impl Framed for LsaAckMessage {
+    fn message_type(&self) -> u8 {
+        self.message_type
+    }
+}
+
+impl Framed for AuthChallenge {
+    fn message_type(&self) -> u8 {
+        self.message_type
+    }
+}
+
+impl Framed for AuthResponse {
+    fn message_type(&self) -> u8 {
+        self.message_type
+    }
+}
+
+impl Framed for FragmentMessage {
+    fn message_type(&self) -> u8 {
+        self.message_type
+    }
+}
+
+impl Framed for EchoMessage {
+    fn message_type(&self) -> u8 {
+        self.message_type
+    }
+}
+
+impl Framed for RouteRequest {
+    fn message_type(&self) -> u8 {
+        self.message_type
+    }
+}
+
+impl Framed for RouteResponse {
+    fn message_type(&self) -> u8 {
+        self.message_type
+    }
+}
+
+/// Réponse à une commande de contrôle, associée à son identifiant
+impl Framed for ControlResponse {
+    fn message_type(&self) -> u8 {
+        CONTROL_RESPONSE
+    }
+}
+
+/// Réponse textuelle à une commande de contrôle
+impl Framed for String {
+    fn message_type(&self) -> u8 {
+        CONTROL_RESPONSE
+    }
+}
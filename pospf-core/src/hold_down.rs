@@ -0,0 +1,64 @@
+use crate::types::{Prefix, RouteState};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Route retenue après une annonce injoignable : prochain saut et métrique de la route
+/// que nous avions alors
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeldRoute {
+    pub next_hop: String,
+    pub metric: u32,
+    pub until: Instant,
+}
+
+/// Préfixes en retenue : pendant la période, une annonce active moins bonne que la route
+/// perdue est ignorée, pour ne pas rebondir entre des LSA périmés (comptage à l'infini)
+#[derive(Debug, Clone, Default)]
+pub struct HoldDown {
+    routes: HashMap<Prefix, HeldRoute>,
+}
+
+impl HoldDown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Met le préfixe en retenue ; une retenue en cours n'est ni prolongée ni modifiée,
+    /// pour qu'un routeur qui répète son annonce injoignable ne la rende pas permanente
+    pub fn start(&mut self, prefix: Prefix, next_hop: &str, metric: u32, duration: Duration, now: Instant) -> bool {
+        if self.routes.get(&prefix).is_some_and(|held| held.until > now) {
+            return false;
+        }
+        self.routes.insert(prefix, HeldRoute { next_hop: next_hop.to_string(), metric, until: now + duration });
+        true
+    }
+
+    /// Vrai si la route candidate peut être retenue par le SPF : préfixe hors retenue,
+    /// même prochain saut que la route perdue, ou métrique au moins aussi bonne
+    pub fn admits(&self, prefix: &Prefix, next_hop: &str, route_state: &RouteState, now: Instant) -> bool {
+        match self.routes.get(prefix) {
+            Some(held) if held.until > now => {
+                held.next_hop == next_hop || route_state.metric().is_some_and(|metric| metric <= held.metric)
+            }
+            _ => true,
+        }
+    }
+
+    /// Oublie les retenues échues
+    pub fn expire(&mut self, now: Instant) {
+        self.routes.retain(|_, held| held.until > now);
+    }
+
+    pub fn get(&self, prefix: &Prefix) -> Option<&HeldRoute> {
+        self.routes.get(prefix)
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+}
@@ -0,0 +1,131 @@
+use crate::dijkstra::{NetworkTopology, PathMetrics, PathSelection, RouteInfo};
+use std::collections::HashSet;
+
+/// Nombre maximal de chemins calculés par `k_shortest_paths`
+pub const MAX_PATHS: usize = 16;
+
+/// Séparation entre le meilleur chemin et son second de `disjoint_paths`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disjointness {
+    /// Aucun routeur intermédiaire ni lien commun
+    Nodes,
+    /// Aucun lien commun, mais des routeurs intermédiaires partagés
+    Links,
+    /// Nombre de liens partagés, le plus petit possible
+    Partial(usize),
+}
+
+/// Meilleur chemin et, s'il existe, le chemin le plus disjoint de celui-ci
+#[derive(Debug, Clone)]
+pub struct DisjointPaths {
+    pub primary: RouteInfo,
+    pub secondary: Option<(RouteInfo, Disjointness)>,
+}
+
+impl NetworkTopology {
+    /// Critères cumulés d'un chemin, par le meilleur lien actif de chaque saut
+    fn path_metrics(&self, path: &[String], policy: PathSelection) -> Option<PathMetrics> {
+        path.windows(2).try_fold(PathMetrics::ORIGIN, |metrics, hop| {
+            let link = self.links.iter()
+                .filter(|link| link.is_active && link.from == hop[0] && link.to == hop[1])
+                .min_by_key(|link| PathMetrics::of_link(link).key(policy))?;
+            Some(metrics.then(&PathMetrics::of_link(link)))
+        })
+    }
+
+    fn route_along(&self, path: Vec<String>, policy: PathSelection) -> Option<RouteInfo> {
+        let metrics = self.path_metrics(&path, policy)?;
+        Some(RouteInfo {
+            destination: path.last()?.clone(),
+            next_hop: path.get(1)?.clone(),
+            total_cost: metrics.total_cost,
+            hop_count: metrics.hop_count,
+            bottleneck_capacity: metrics.bottleneck_capacity,
+            total_latency_us: metrics.total_latency_us,
+            policy,
+            path,
+            is_reachable: true,
+        })
+    }
+
+    /// Copie de la topologie où les liens donnés, et ceux des routeurs donnés, sont inactifs
+    fn without(&self, links: &HashSet<(&str, &str)>, nodes: &HashSet<&str>) -> NetworkTopology {
+        let mut pruned = self.clone();
+        for link in &mut pruned.links {
+            if links.contains(&(link.from.as_str(), link.to.as_str())) || nodes.contains(link.from.as_str()) || nodes.contains(link.to.as_str()) {
+                link.is_active = false;
+            }
+        }
+        pruned
+    }
+
+    /// Les `k` meilleurs chemins sans boucle de `source` à `target` selon la politique
+    /// (algorithme de Yen), du meilleur au moins bon ; au plus `MAX_PATHS`
+    pub fn k_shortest_paths(&self, source: &str, target: &str, k: usize, policy: PathSelection) -> Vec<RouteInfo> {
+        let k = k.min(MAX_PATHS);
+        let Some(first) = self.calculate_shortest_paths(source, policy).remove(target) else {
+            return Vec::new();
+        };
+        let mut accepted = vec![first];
+        let mut candidates: Vec<RouteInfo> = Vec::new();
+        while accepted.len() < k {
+            let previous = accepted[accepted.len() - 1].path.clone();
+            // Chaque routeur du chemin précédent sert tour à tour de point de déviation : la
+            // racine commune est conservée, les liens déjà empruntés depuis elle sont écartés
+            for spur in 0..previous.len() - 1 {
+                let root = &previous[..=spur];
+                let used: HashSet<(&str, &str)> = accepted.iter()
+                    .filter(|route| route.path.len() > spur + 1 && route.path[..=spur] == *root)
+                    .map(|route| (route.path[spur].as_str(), route.path[spur + 1].as_str()))
+                    .collect();
+                let root_nodes: HashSet<&str> = root[..spur].iter().map(String::as_str).collect();
+                let Some(deviation) = self.without(&used, &root_nodes).calculate_shortest_paths(&root[spur], policy).remove(target) else {
+                    continue;
+                };
+                let path: Vec<String> = root[..spur].iter().cloned().chain(deviation.path).collect();
+                if accepted.iter().chain(&candidates).any(|route| route.path == path) {
+                    continue;
+                }
+                candidates.extend(self.route_along(path, policy));
+            }
+            let Some(best) = candidates.iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.key().cmp(&b.key()).then_with(|| a.path.cmp(&b.path)))
+                .map(|(index, _)| index)
+            else {
+                break;
+            };
+            accepted.push(candidates.swap_remove(best));
+        }
+        accepted.truncate(k);
+        accepted
+    }
+
+    /// Meilleur chemin de `source` à `target` et un second aussi disjoint que possible :
+    /// sans routeur intermédiaire ni lien du premier, à défaut sans lien commun, à défaut
+    /// celui des `MAX_PATHS` meilleurs chemins qui partage le moins de liens avec lui
+    pub fn disjoint_paths(&self, source: &str, target: &str, policy: PathSelection) -> Option<DisjointPaths> {
+        let primary = self.calculate_shortest_paths(source, policy).remove(target)?;
+        let links: HashSet<(&str, &str)> = primary.path.windows(2)
+            .flat_map(|hop| [(hop[0].as_str(), hop[1].as_str()), (hop[1].as_str(), hop[0].as_str())])
+            .collect();
+        let transit: HashSet<&str> = primary.path[1..primary.path.len() - 1].iter().map(String::as_str).collect();
+
+        let secondary = if let Some(route) = self.without(&links, &transit).calculate_shortest_paths(source, policy).remove(target) {
+            Some((route, Disjointness::Nodes))
+        } else if let Some(route) = self.without(&links, &HashSet::new()).calculate_shortest_paths(source, policy).remove(target) {
+            Some((route, Disjointness::Links))
+        } else {
+            let shared = |route: &RouteInfo| route.path.windows(2).filter(|hop| links.contains(&(hop[0].as_str(), hop[1].as_str()))).count();
+            self.k_shortest_paths(source, target, MAX_PATHS, policy)
+                .into_iter()
+                .skip(1)
+                .min_by_key(|route| (shared(route), route.key()))
+                .map(|route| {
+                    let shared = shared(&route);
+                    (route, Disjointness::Partial(shared))
+                })
+        };
+        Some(DisjointPaths { primary, secondary })
+    }
+}
@@ -0,0 +1,22 @@
+pub mod clock;
+pub mod dijkstra;
+pub mod error;
+pub mod events;
+pub mod fragment;
+pub mod framing;
+pub mod hold_down;
+pub mod k_paths;
+pub mod lsa_delta;
+pub mod lsa_part;
+pub mod lsdb;
+pub mod neighbor;
+pub mod ospfv2;
+pub mod packet;
+pub mod rip;
+pub mod sequence;
+pub mod spof;
+pub mod spf_engine;
+pub mod types;
+
+/// TTL initial des LSA émis
+pub const INITIAL_TTL: u8 = 15;
@@ -0,0 +1,171 @@
+use crate::types::{ExternalMetricType, LSAMessage, Prefix, RouteState};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Tables d'une instance complète, référence des LSA différentiels qui la suivent
+#[derive(Debug, Clone)]
+struct FullInstance {
+    seq_num: u32,
+    routing_table: HashMap<Prefix, RouteState>,
+    external_routes: HashMap<Prefix, u32>,
+    external_metric_types: HashMap<Prefix, ExternalMetricType>,
+    route_tags: HashMap<Prefix, u32>,
+    sent_at: Instant,
+    /// Voisins ayant acquitté cette instance (côté émetteur)
+    acked_by: HashSet<String>,
+}
+
+impl FullInstance {
+    fn from_lsa(lsa: &LSAMessage) -> Self {
+        Self {
+            seq_num: lsa.seq_num,
+            routing_table: lsa.routing_table.clone(),
+            external_routes: lsa.external_routes.clone(),
+            external_metric_types: lsa.external_metric_types.clone(),
+            route_tags: lsa.route_tags.clone(),
+            sent_at: Instant::now(),
+            acked_by: HashSet::new(),
+        }
+    }
+}
+
+/// LSA différentiels, encodés saut par saut : chaque émetteur (originateur ou relais)
+/// ne réduit un LSA qu'aux changements depuis la dernière instance complète qu'il a
+/// lui-même envoyée sur ce lien et que le destinataire a acquittée. Un différentiel est
+/// toujours relatif à cette instance complète, jamais au différentiel précédent : en
+/// perdre un ne rend pas les suivants inutilisables.
+#[derive(Debug, Default)]
+pub struct DeltaState {
+    /// Dernière instance complète envoyée, par (destination, originateur) ; la destination
+    /// est l'adresse de l'interface pour une diffusion, celle du voisin pour un relais
+    sent: HashMap<(String, String), FullInstance>,
+    /// Dernière instance complète reçue, par (voisin émetteur, originateur)
+    received: HashMap<(String, String), FullInstance>,
+}
+
+impl DeltaState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Réduit un LSA complet à envoyer vers `target` à ses changements depuis la dernière
+    /// instance complète, si tous les voisins `neighbors` du lien l'ont acquittée et
+    /// qu'elle date de moins de `full_refresh` ; sinon le LSA part complet et devient
+    /// la nouvelle référence. Renvoie vrai si le LSA a été réduit.
+    pub fn encode(&mut self, target: &str, lsa: &mut LSAMessage, neighbors: &[String], full_refresh: Duration) -> bool {
+        let key = (target.to_string(), lsa.originator.clone());
+        let usable = self.sent.get(&key).filter(|base| {
+            base.sent_at.elapsed() < full_refresh && neighbors.iter().all(|neighbor| base.acked_by.contains(neighbor))
+        });
+        if let Some(base) = usable {
+            let routing_table: HashMap<Prefix, RouteState> = lsa.routing_table.iter()
+                .filter(|(prefix, route_state)| {
+                    base.routing_table.get(prefix) != Some(route_state) || base.route_tags.get(prefix) != lsa.route_tags.get(prefix)
+                })
+                .map(|(prefix, route_state)| (*prefix, route_state.clone()))
+                .collect();
+            let external_routes: HashMap<Prefix, u32> = lsa.external_routes.iter()
+                .filter(|(prefix, metric)| {
+                    base.external_routes.get(prefix) != Some(metric)
+                        || base.external_metric_types.get(prefix) != lsa.external_metric_types.get(prefix)
+                        || base.route_tags.get(prefix) != lsa.route_tags.get(prefix)
+                })
+                .map(|(prefix, metric)| (*prefix, *metric))
+                .collect();
+            let mut withdrawn: Vec<Prefix> = base.routing_table.keys()
+                .filter(|prefix| !lsa.routing_table.contains_key(prefix))
+                .chain(base.external_routes.keys().filter(|prefix| !lsa.external_routes.contains_key(prefix)))
+                .copied()
+                .collect();
+            withdrawn.sort();
+            withdrawn.dedup();
+            // Un différentiel plus gros que l'instance complète n'a pas d'intérêt
+            if routing_table.len() + external_routes.len() + withdrawn.len() < lsa.routing_table.len() + lsa.external_routes.len() {
+                lsa.external_metric_types.retain(|prefix, _| external_routes.contains_key(prefix));
+                lsa.route_tags.retain(|prefix, _| routing_table.contains_key(prefix) || external_routes.contains_key(prefix));
+                lsa.routing_table = routing_table;
+                lsa.external_routes = external_routes;
+                lsa.withdrawn = withdrawn;
+                lsa.base_seq = Some(base.seq_num);
+                return true;
+            }
+        }
+        self.sent.insert(key, FullInstance::from_lsa(lsa));
+        false
+    }
+
+    /// Acquittement d'un voisin pour une instance que nous lui avons envoyée
+    pub fn acknowledge(&mut self, originator: &str, seq_num: u32, neighbor: &str) {
+        for ((_, sent_originator), base) in self.sent.iter_mut() {
+            if sent_originator == originator && base.seq_num == seq_num {
+                base.acked_by.insert(neighbor.to_string());
+            }
+        }
+    }
+
+    /// Mémorise un LSA complet reçu de `sender` comme référence de ses futurs différentiels
+    pub fn record_received(&mut self, sender: &str, lsa: &LSAMessage) {
+        if lsa.base_seq.is_some() {
+            return;
+        }
+        let key = (sender.to_string(), lsa.originator.clone());
+        let newer = self.received.get(&key)
+            .is_none_or(|base| crate::sequence::is_newer(lsa.seq_num, base.seq_num));
+        if newer {
+            self.received.insert(key, FullInstance::from_lsa(lsa));
+        }
+    }
+
+    /// LSA complet reconstitué à partir d'un différentiel reçu de `sender` et de son
+    /// instance de référence, ou `None` si celle-ci n'a pas été reçue
+    pub fn expand(&self, sender: &str, lsa: &LSAMessage) -> Option<LSAMessage> {
+        let base = self.received.get(&(sender.to_string(), lsa.originator.clone()))
+            .filter(|base| Some(base.seq_num) == lsa.base_seq)?;
+        let mut full = lsa.clone();
+        full.base_seq = None;
+        full.withdrawn.clear();
+        full.routing_table = base.routing_table.clone();
+        full.external_routes = base.external_routes.clone();
+        full.external_metric_types = base.external_metric_types.clone();
+        full.route_tags = base.route_tags.clone();
+        for prefix in &lsa.withdrawn {
+            full.routing_table.remove(prefix);
+            full.external_routes.remove(prefix);
+            full.external_metric_types.remove(prefix);
+            full.route_tags.remove(prefix);
+        }
+        // Un préfixe du différentiel porte sa nouvelle étiquette, ou n'en a plus
+        for prefix in lsa.routing_table.keys().chain(lsa.external_routes.keys()) {
+            match lsa.route_tags.get(prefix) {
+                Some(tag) => full.route_tags.insert(*prefix, *tag),
+                None => full.route_tags.remove(prefix),
+            };
+        }
+        full.routing_table.extend(lsa.routing_table.iter().map(|(prefix, route_state)| (*prefix, route_state.clone())));
+        for (prefix, metric) in &lsa.external_routes {
+            full.external_routes.insert(*prefix, *metric);
+            match lsa.external_metric_types.get(prefix) {
+                Some(metric_type) => full.external_metric_types.insert(*prefix, *metric_type),
+                None => full.external_metric_types.remove(prefix),
+            };
+        }
+        Some(full)
+    }
+
+    /// Oublie les instances de référence d'un originateur dont le LSA a été retiré : ses
+    /// numéros repartent de `INITIAL_SEQUENCE` et ne prolongent plus les anciens
+    pub fn forget_originator(&mut self, originator: &str) {
+        self.sent.retain(|(_, sent_originator), _| sent_originator != originator);
+        self.received.retain(|(_, received_originator), _| received_originator != originator);
+    }
+
+    /// Oublie les instances échangées avec les voisins qui ne sont plus adjacents : un
+    /// voisin qui revient repart d'une instance complète
+    pub fn retain_neighbors(&mut self, alive: impl Fn(&str) -> bool) {
+        self.received.retain(|(sender, _), _| alive(sender));
+        for base in self.sent.values_mut() {
+            base.acked_by.retain(|neighbor| alive(neighbor));
+        }
+    }
+}
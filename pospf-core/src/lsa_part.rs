@@ -0,0 +1,217 @@
+use crate::fragment::{payload_for_mtu, MAX_FRAGMENTS, MAX_FRAGMENT_PAYLOAD, MIN_MTU, REASSEMBLY_TIMEOUT};
+use crate::types::{LSAMessage, LsaPart, Prefix};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use tokio::time::Instant;
+
+/// Nombre maximal de parties d'un LSA, autant que de fragments d'un message
+pub const MAX_PARTS: u16 = MAX_FRAGMENTS;
+/// Nombre maximal de LSA en cours de réassemblage
+const MAX_PENDING: usize = 256;
+/// Noms des champs `route_tags` et `withdrawn`, absents d'un LSA tant qu'ils sont vides
+const OPTIONAL_FIELDS_LEN: usize = 32;
+
+/// Préfixe d'un LSA à répartir entre ses parties
+#[derive(Debug, Clone, Copy)]
+enum Entry {
+    Internal(Prefix),
+    External(Prefix),
+    Withdrawn(Prefix),
+}
+
+/// Taille sérialisée d'un message (nulle s'il ne se sérialise pas)
+fn serialized_len<T: serde::Serialize>(value: &T) -> usize {
+    serde_json::to_vec(value).map(|data| data.len()).unwrap_or(0)
+}
+
+/// Octets qu'ajoute une entrée à une partie, séparateurs compris
+fn entry_len(lsa: &LSAMessage, entry: Entry) -> usize {
+    let tag = |prefix: &Prefix| lsa.route_tags.get(prefix).map_or(0, |tag| serialized_len(&(prefix, tag)));
+    match entry {
+        Entry::Internal(prefix) => serialized_len(&(prefix, &lsa.routing_table[&prefix])) + tag(&prefix),
+        Entry::External(prefix) => {
+            serialized_len(&(prefix, lsa.external_routes[&prefix]))
+                + lsa.external_metric_types.get(&prefix).map_or(0, |metric_type| serialized_len(&(prefix, metric_type)))
+                + tag(&prefix)
+        }
+        Entry::Withdrawn(prefix) => serialized_len(&prefix) + 1,
+    }
+}
+
+/// Ajoute une entrée du LSA `lsa` à la partie `part`
+fn add(part: &mut LSAMessage, lsa: &LSAMessage, entry: Entry) {
+    let tag = |part: &mut LSAMessage, prefix: Prefix| {
+        if let Some(tag) = lsa.route_tags.get(&prefix) {
+            part.route_tags.insert(prefix, *tag);
+        }
+    };
+    match entry {
+        Entry::Internal(prefix) => {
+            part.routing_table.insert(prefix, lsa.routing_table[&prefix].clone());
+            tag(part, prefix);
+        }
+        Entry::External(prefix) => {
+            part.external_routes.insert(prefix, lsa.external_routes[&prefix]);
+            if let Some(metric_type) = lsa.external_metric_types.get(&prefix) {
+                part.external_metric_types.insert(prefix, *metric_type);
+            }
+            tag(part, prefix);
+        }
+        Entry::Withdrawn(prefix) => part.withdrawn.push(prefix),
+    }
+}
+
+/// Découpe un LSA dont la forme sérialisée dépasse `max_len` octets (borné comme la taille
+/// des fragments, voir `fragment::split_to`) en parties de même originateur et de même
+/// séquence, qui se partagent ses préfixes internes, externes et retirés ; voisins et TLV
+/// opaques restent dans la première, qui seule peut encore dépasser `max_len` (elle est
+/// alors fragmentée). Chaque partie est scellée si le LSA l'était. `None` si le LSA tient
+/// entier, ou si ses préfixes ne se répartissent pas en parties assez petites
+pub fn split(lsa: &LSAMessage, max_len: usize) -> Option<Vec<LSAMessage>> {
+    let max_len = max_len.clamp(payload_for_mtu(MIN_MTU), MAX_FRAGMENT_PAYLOAD);
+    if lsa.part.is_some() || serialized_len(lsa) <= max_len {
+        return None;
+    }
+    let mut entries: Vec<Entry> = lsa.routing_table.keys().copied().map(Entry::Internal)
+        .chain(lsa.external_routes.keys().copied().map(Entry::External))
+        .chain(lsa.withdrawn.iter().copied().map(Entry::Withdrawn))
+        .collect();
+    if entries.len() < 2 {
+        return None;
+    }
+    entries.sort_by_key(|entry| match entry {
+        Entry::Internal(prefix) => (0, *prefix),
+        Entry::External(prefix) => (1, *prefix),
+        Entry::Withdrawn(prefix) => (2, *prefix),
+    });
+
+    let mut first = lsa.clone();
+    first.routing_table.clear();
+    first.external_routes.clear();
+    first.external_metric_types.clear();
+    first.route_tags.clear();
+    first.withdrawn.clear();
+    // En-tête d'une partie au plus large : numéro, nombre et somme de contrôle maximaux
+    first.part = Some(LsaPart { index: MAX_PARTS, count: MAX_PARTS });
+    first.checksum = lsa.checksum.map(|_| u16::MAX);
+    let mut rest = first.clone();
+    rest.neighbors.clear();
+    rest.neighbor_count = 0;
+    rest.opaque.clear();
+
+    let rest_len = serialized_len(&rest) + OPTIONAL_FIELDS_LEN;
+    let lens: Vec<usize> = entries.iter().map(|entry| entry_len(lsa, *entry)).collect();
+    if lens.iter().any(|len| rest_len + len > max_len) {
+        return None;
+    }
+
+    let mut parts = Vec::new();
+    let mut current = first;
+    let mut used = serialized_len(&current) + OPTIONAL_FIELDS_LEN;
+    let mut added = 0;
+    for (entry, len) in entries.into_iter().zip(lens) {
+        if added > 0 && used + len > max_len {
+            parts.push(std::mem::replace(&mut current, rest.clone()));
+            used = rest_len;
+            added = 0;
+        }
+        add(&mut current, lsa, entry);
+        used += len;
+        added += 1;
+    }
+    parts.push(current);
+    let count = u16::try_from(parts.len()).ok().filter(|count| (2..=MAX_PARTS).contains(count))?;
+    for (index, part) in parts.iter_mut().enumerate() {
+        part.withdrawn.sort();
+        part.part = Some(LsaPart { index: index as u16, count });
+        if lsa.checksum.is_some() {
+            part.seal();
+        }
+    }
+    Some(parts)
+}
+
+/// Réunit les parties d'un LSA, dans l'ordre, en un seul LSA scellé si elles l'étaient
+fn merge(parts: Vec<LSAMessage>) -> Option<LSAMessage> {
+    let mut parts = parts.into_iter();
+    let mut lsa = parts.next()?;
+    for part in parts {
+        lsa.routing_table.extend(part.routing_table);
+        lsa.external_routes.extend(part.external_routes);
+        lsa.external_metric_types.extend(part.external_metric_types);
+        lsa.route_tags.extend(part.route_tags);
+        lsa.withdrawn.extend(part.withdrawn);
+    }
+    lsa.withdrawn.sort();
+    lsa.withdrawn.dedup();
+    lsa.part = None;
+    if lsa.checksum.is_some() {
+        lsa.seal();
+    }
+    Some(lsa)
+}
+
+#[derive(Debug)]
+struct PendingLsa {
+    started: Instant,
+    parts: Vec<Option<LSAMessage>>,
+}
+
+/// Réassemblage des LSA découpés, par émetteur, originateur, séquence et instance de
+/// référence (un différentiel et l'instance complète de même séquence ne se mélangent pas)
+#[derive(Debug, Default)]
+pub struct PartAssembler {
+    pending: HashMap<(SocketAddr, String, u32, Option<u32>), PendingLsa>,
+}
+
+impl PartAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ajoute une partie ; renvoie le LSA entier une fois toutes ses parties reçues (tout
+    /// de suite pour un LSA qui n'est pas découpé)
+    pub fn insert(&mut self, source: SocketAddr, lsa: LSAMessage) -> Option<LSAMessage> {
+        let Some(part) = lsa.part else {
+            return Some(lsa);
+        };
+        if part.count == 0 || part.count > MAX_PARTS || part.index >= part.count {
+            return None;
+        }
+        self.expire();
+        let key = (source, lsa.originator.clone(), lsa.seq_num, lsa.base_seq);
+        if !self.pending.contains_key(&key) && self.pending.len() >= MAX_PENDING {
+            let oldest = self.pending.iter().min_by_key(|(_, pending)| pending.started).map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.pending.remove(&oldest);
+            }
+        }
+        let fresh = || PendingLsa {
+            started: Instant::now(),
+            parts: vec![None; part.count as usize],
+        };
+        let pending = self.pending.entry(key.clone()).or_insert_with(fresh);
+        // Même instance découpée autrement (MTU mesurée entre-temps) : recommencer
+        if pending.parts.len() != part.count as usize {
+            *pending = fresh();
+        }
+        pending.parts[part.index as usize] = Some(lsa);
+        if pending.parts.iter().any(Option::is_none) {
+            return None;
+        }
+        let pending = self.pending.remove(&key)?;
+        merge(pending.parts.into_iter().flatten().collect())
+    }
+
+    fn expire(&mut self) {
+        self.pending.retain(|_, pending| pending.started.elapsed() < REASSEMBLY_TIMEOUT);
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
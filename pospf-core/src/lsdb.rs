@@ -0,0 +1,250 @@
+use crate::sequence::is_newer as is_newer_sequence;
+use crate::types::{ExternalMetricType, LSAMessage, Neighbor, OpaqueTlv, Prefix, RouteState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Type d'un enregistrement de la LSDB
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LsaType {
+    /// Voisins et réseaux connectés d'un routeur
+    Router,
+    /// Segment à accès multiple annoncé par son préfixe (LSA émis par le routeur désigné)
+    Network,
+    /// Routes redistribuées par un routeur
+    External,
+    /// TLV applicatifs d'un routeur, inondés sans être interprétés
+    Opaque,
+}
+
+/// LSA routeur : adjacences et réseaux connectés de l'originateur
+#[derive(Debug, Clone)]
+pub struct RouterLsa {
+    /// Identifiant du routeur d'origine (son adresse d'interface s'il n'en annonce pas)
+    pub originator: String,
+    pub seq_num: u32,
+    pub neighbors: Vec<Neighbor>,
+    pub networks: HashMap<Prefix, RouteState>,
+    /// Étiquettes des réseaux annoncés
+    pub tags: HashMap<Prefix, u32>,
+    pub stub_only: bool,
+    /// Horodatage (secondes Unix) de l'installation de cette instance
+    pub installed_at: u64,
+}
+
+/// LSA réseau : routeurs rattachés à un segment à accès multiple, émis par son routeur désigné
+#[derive(Debug, Clone)]
+pub struct NetworkLsa {
+    pub prefix: String,
+    pub seq_num: u32,
+    /// Routeur désigné ayant émis le LSA pour ce segment
+    pub advertising_router: String,
+    /// Identités des routeurs rattachés, routeur désigné compris
+    pub attached_routers: Vec<String>,
+    pub installed_at: u64,
+}
+
+/// LSA externe : routes redistribuées (préfixe -> métrique)
+#[derive(Debug, Clone)]
+pub struct ExternalLsa {
+    pub originator: String,
+    pub seq_num: u32,
+    pub routes: HashMap<Prefix, u32>,
+    pub metric_types: HashMap<Prefix, ExternalMetricType>,
+    /// Étiquettes des routes externes
+    pub tags: HashMap<Prefix, u32>,
+    pub installed_at: u64,
+}
+
+impl ExternalLsa {
+    /// Type de métrique annoncé pour un préfixe (E2 par défaut)
+    pub fn metric_type(&self, prefix: &Prefix) -> ExternalMetricType {
+        self.metric_types.get(prefix).copied().unwrap_or_default()
+    }
+}
+
+/// LSA opaque : TLV applicatifs annoncés par l'originateur
+#[derive(Debug, Clone)]
+pub struct OpaqueLsa {
+    pub originator: String,
+    pub seq_num: u32,
+    pub tlvs: Vec<OpaqueTlv>,
+    pub installed_at: u64,
+}
+
+impl OpaqueLsa {
+    /// Valeur du TLV d'un type donné
+    pub fn tlv(&self, tlv_type: u16) -> Option<&[u8]> {
+        self.tlvs.iter().find(|tlv| tlv.tlv_type == tlv_type).map(|tlv| tlv.value.as_slice())
+    }
+}
+
+/// Enregistrement typé renvoyé par une recherche
+#[derive(Debug, Clone, Copy)]
+pub enum LsaRecord<'a> {
+    Router(&'a RouterLsa),
+    Network(&'a NetworkLsa),
+    External(&'a ExternalLsa),
+    Opaque(&'a OpaqueLsa),
+}
+
+/// Base de données d'état des liens : dernière instance de chaque LSA, par type et originateur
+#[derive(Debug, Default)]
+pub struct Lsdb {
+    routers: HashMap<String, RouterLsa>,
+    networks: HashMap<String, NetworkLsa>,
+    externals: HashMap<String, ExternalLsa>,
+    opaques: HashMap<String, OpaqueLsa>,
+}
+
+impl Lsdb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Type des enregistrements issus d'un message : un originateur en notation CIDR
+    /// désigne un segment, sinon le message porte un LSA routeur (et externe ou opaque s'il en a)
+    pub fn classify(lsa: &LSAMessage) -> LsaType {
+        if lsa.originator.contains('/') {
+            LsaType::Network
+        } else {
+            LsaType::Router
+        }
+    }
+
+    /// Identité d'un LSA routeur ou externe : l'identifiant du routeur d'origine, de sorte
+    /// que ses LSA émis depuis chaque interface, ou après une renumérotation, n'en fassent qu'un
+    pub fn router_key(lsa: &LSAMessage) -> String {
+        lsa.router_id.map_or_else(|| lsa.originator.clone(), |router_id| router_id.to_string())
+    }
+
+    /// Installe les enregistrements d'un LSA reçu s'ils sont plus récents ; renvoie vrai si la base a changé
+    pub fn install(&mut self, lsa: &LSAMessage) -> bool {
+        self.install_at(lsa, crate::clock::unix_secs())
+    }
+
+    /// `install` avec une date d'installation donnée (rejeu d'un journal)
+    pub fn install_at(&mut self, lsa: &LSAMessage, installed_at: u64) -> bool {
+        match Self::classify(lsa) {
+            LsaType::Network => {
+                if self.networks.get(&lsa.originator).is_some_and(|old| !is_newer_sequence(lsa.seq_num, old.seq_num)) {
+                    return false;
+                }
+                self.networks.insert(lsa.originator.clone(), NetworkLsa {
+                    prefix: lsa.originator.clone(),
+                    seq_num: lsa.seq_num,
+                    advertising_router: lsa.router_id.map_or_else(|| lsa.router_ip.clone(), |router_id| router_id.to_string()),
+                    attached_routers: lsa.neighbors.iter().map(Neighbor::identity).collect(),
+                    installed_at,
+                });
+                true
+            }
+            _ => {
+                let originator = Self::router_key(lsa);
+                if self.routers.get(&originator).is_some_and(|old| !is_newer_sequence(lsa.seq_num, old.seq_num)) {
+                    return false;
+                }
+                self.routers.insert(originator.clone(), RouterLsa {
+                    originator: originator.clone(),
+                    seq_num: lsa.seq_num,
+                    neighbors: lsa.neighbors.clone(),
+                    networks: lsa.routing_table.clone(),
+                    tags: lsa.route_tags.iter().filter(|(prefix, _)| lsa.routing_table.contains_key(prefix)).map(|(prefix, tag)| (*prefix, *tag)).collect(),
+                    stub_only: lsa.stub_only,
+                    installed_at,
+                });
+                if lsa.external_routes.is_empty() {
+                    self.externals.remove(&originator);
+                } else {
+                    self.externals.insert(originator.clone(), ExternalLsa {
+                        originator: originator.clone(),
+                        seq_num: lsa.seq_num,
+                        routes: lsa.external_routes.clone(),
+                        metric_types: lsa.external_metric_types.clone(),
+                        tags: lsa.route_tags.iter().filter(|(prefix, _)| lsa.external_routes.contains_key(prefix)).map(|(prefix, tag)| (*prefix, *tag)).collect(),
+                        installed_at,
+                    });
+                }
+                if lsa.opaque.is_empty() {
+                    self.opaques.remove(&originator);
+                } else {
+                    self.opaques.insert(originator.clone(), OpaqueLsa {
+                        originator,
+                        seq_num: lsa.seq_num,
+                        tlvs: lsa.opaque.clone(),
+                        installed_at,
+                    });
+                }
+                true
+            }
+        }
+    }
+
+    /// Retire les enregistrements qu'un LSA de retrait (`flush`) remplace, sauf instance
+    /// installée plus récente ; renvoie vrai si la base a changé
+    pub fn flush(&mut self, lsa: &LSAMessage) -> bool {
+        let stale = |seq_num: u32| !is_newer_sequence(seq_num, lsa.seq_num);
+        match Self::classify(lsa) {
+            LsaType::Network => {
+                if !self.networks.get(&lsa.originator).is_some_and(|old| stale(old.seq_num)) {
+                    return false;
+                }
+                self.networks.remove(&lsa.originator);
+                true
+            }
+            _ => {
+                let originator = Self::router_key(lsa);
+                if !self.routers.get(&originator).is_some_and(|old| stale(old.seq_num)) {
+                    return false;
+                }
+                self.routers.remove(&originator);
+                self.externals.remove(&originator);
+                self.opaques.remove(&originator);
+                true
+            }
+        }
+    }
+
+    /// Recherche l'instance exacte (type, originateur, séquence)
+    pub fn get(&self, lsa_type: LsaType, originator: &str, seq_num: u32) -> Option<LsaRecord<'_>> {
+        match lsa_type {
+            LsaType::Router => self.routers.get(originator).filter(|lsa| lsa.seq_num == seq_num).map(LsaRecord::Router),
+            LsaType::Network => self.networks.get(originator).filter(|lsa| lsa.seq_num == seq_num).map(LsaRecord::Network),
+            LsaType::External => self.externals.get(originator).filter(|lsa| lsa.seq_num == seq_num).map(LsaRecord::External),
+            LsaType::Opaque => self.opaques.get(originator).filter(|lsa| lsa.seq_num == seq_num).map(LsaRecord::Opaque),
+        }
+    }
+
+    pub fn router_lsa(&self, originator: &str) -> Option<&RouterLsa> {
+        self.routers.get(originator)
+    }
+
+    pub fn router_lsas(&self) -> impl Iterator<Item = &RouterLsa> {
+        self.routers.values()
+    }
+
+    pub fn network_lsas(&self) -> impl Iterator<Item = &NetworkLsa> {
+        self.networks.values()
+    }
+
+    pub fn external_lsas(&self) -> impl Iterator<Item = &ExternalLsa> {
+        self.externals.values()
+    }
+
+    pub fn opaque_lsa(&self, originator: &str) -> Option<&OpaqueLsa> {
+        self.opaques.get(originator)
+    }
+
+    pub fn opaque_lsas(&self) -> impl Iterator<Item = &OpaqueLsa> {
+        self.opaques.values()
+    }
+
+    /// Nombre total d'enregistrements, tous types confondus
+    pub fn len(&self) -> usize {
+        self.routers.len() + self.networks.len() + self.externals.len() + self.opaques.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
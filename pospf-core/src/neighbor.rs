@@ -0,0 +1,144 @@
+use crate::events::RouterEvent;
+use crate::types::{default_router_priority, Neighbor};
+use std::collections::HashMap;
+
+/// État de l'interface locale sur laquelle un voisin est entendu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalLink {
+    pub interface: Option<String>,
+    pub capacity: u32,
+    pub active: bool,
+    pub cost: Option<u32>,
+}
+
+/// Effet d'un HELLO reçu sur la table des voisins
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HelloOutcome {
+    /// Changement d'état à publier
+    pub event: Option<RouterEvent>,
+    /// Le voisin déjà connu a changé d'état : bascule à amortir
+    pub flapped: bool,
+}
+
+/// Rafraîchit (ou crée) le voisin `neighbor_ip` entendu à l'instant `now` sur `link` ;
+/// son état suit celui de l'interface
+pub fn hear(neighbors: &mut HashMap<String, Neighbor>, neighbor_ip: &str, link: &LocalLink, now: u64) -> HelloOutcome {
+    let mut outcome = HelloOutcome::default();
+    let up = || RouterEvent::NeighborUp { neighbor: neighbor_ip.to_string(), interface: link.interface.clone() };
+    match neighbors.get_mut(neighbor_ip) {
+        Some(neighbor) => {
+            neighbor.last_seen = now;
+            neighbor.interface = link.interface.clone();
+            neighbor.capacity = link.capacity;
+            neighbor.cost = link.cost;
+            if neighbor.link_up != link.active {
+                outcome.event = Some(if link.active {
+                    up()
+                } else {
+                    RouterEvent::NeighborDown { neighbor: neighbor_ip.to_string(), reason: "interface inactive".to_string() }
+                });
+                neighbor.link_up = link.active;
+                outcome.flapped = true;
+            }
+        }
+        None => {
+            if link.active {
+                outcome.event = Some(up());
+            }
+            neighbors.insert(neighbor_ip.to_string(), Neighbor {
+                neighbor_ip: neighbor_ip.to_string(),
+                link_up: link.active,
+                capacity: link.capacity,
+                last_seen: now,
+                cost: link.cost,
+                rtt_us: None,
+                jitter_us: None,
+                hostname: None,
+                interface: link.interface.clone(),
+                router_id: None,
+                priority: default_router_priority(),
+            });
+        }
+    }
+    outcome
+}
+
+/// Intègre une mesure de RTT : moyenne lissée (1/8) et gigue (1/16) comme TCP et RTP
+pub fn record_rtt_sample(neighbor: &mut Neighbor, sample_us: u32) {
+    match neighbor.rtt_us {
+        Some(srtt) => {
+            let deviation = srtt.abs_diff(sample_us);
+            let jitter = neighbor.jitter_us.unwrap_or(0);
+            neighbor.rtt_us = Some(((srtt as u64 * 7 + sample_us as u64) / 8) as u32);
+            neighbor.jitter_us = Some(((jitter as u64 * 15 + deviation as u64) / 16) as u32);
+        }
+        None => {
+            neighbor.rtt_us = Some(sample_us);
+            neighbor.jitter_us = Some(0);
+        }
+    }
+}
+
+/// Passe inactifs les voisins muets depuis plus que leur intervalle de mort, donné par
+/// `dead_interval` selon le voisin ; un `NeighborDown` est émis pour chacun
+pub fn expire(neighbors: &mut HashMap<String, Neighbor>, now: u64, dead_interval: impl Fn(&str, &Neighbor) -> u64) -> Vec<RouterEvent> {
+    let mut events = Vec::new();
+    for (ip, neighbor) in neighbors.iter_mut() {
+        if neighbor.link_up && now.saturating_sub(neighbor.last_seen) > dead_interval(ip, neighbor) {
+            neighbor.link_up = false;
+            events.push(RouterEvent::NeighborDown { neighbor: ip.clone(), reason: "dead interval expired".to_string() });
+        }
+    }
+    events.sort_by(|a, b| neighbor_of(a).cmp(neighbor_of(b)));
+    events
+}
+
+/// Répercute l'état de l'interface `name` (active ou non, coût) sur ses voisins, situés
+/// par `interface_of` ; au rétablissement, seuls les voisins entendus depuis moins de
+/// `dead_interval` secondes repassent actifs
+pub fn apply_link_state(
+    neighbors: &mut HashMap<String, Neighbor>,
+    name: &str,
+    active: bool,
+    cost: Option<u32>,
+    now: u64,
+    dead_interval: u64,
+    interface_of: impl Fn(&str, &Neighbor) -> Option<String>,
+) -> Vec<RouterEvent> {
+    let mut events = Vec::new();
+    for (ip, neighbor) in neighbors.iter_mut() {
+        let interface = interface_of(ip, neighbor);
+        if interface.as_deref() != Some(name) {
+            continue;
+        }
+        neighbor.cost = cost;
+        let up = active && now.saturating_sub(neighbor.last_seen) <= dead_interval;
+        if neighbor.link_up != up {
+            neighbor.link_up = up;
+            events.push(if up {
+                RouterEvent::NeighborUp { neighbor: ip.clone(), interface }
+            } else {
+                RouterEvent::NeighborDown { neighbor: ip.clone(), reason: "interface shut down".to_string() }
+            });
+        }
+    }
+    events.sort_by(|a, b| neighbor_of(a).cmp(neighbor_of(b)));
+    events
+}
+
+/// Voisins perdus d'après une suite d'événements
+pub fn lost(events: &[RouterEvent]) -> Vec<String> {
+    events.iter()
+        .filter_map(|event| match event {
+            RouterEvent::NeighborDown { neighbor, .. } => Some(neighbor.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn neighbor_of(event: &RouterEvent) -> &str {
+    match event {
+        RouterEvent::NeighborUp { neighbor, .. } | RouterEvent::NeighborDown { neighbor, .. } => neighbor,
+        _ => "",
+    }
+}
@@ -0,0 +1,519 @@
+use crate::error::{AppError, Result};
+use std::net::Ipv4Addr;
+
+/// Numéro de protocole IP d'OSPF
+pub const IP_PROTOCOL: u8 = 89;
+/// Groupe multicast AllSPFRouters
+pub const ALL_SPF_ROUTERS: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 5);
+pub const VERSION: u8 = 2;
+pub const HEADER_LEN: usize = 24;
+pub const LSA_HEADER_LEN: usize = 20;
+/// Premier numéro de séquence d'un LSA (RFC 2328, 12.1.6)
+pub const INITIAL_SEQUENCE: i32 = 0x8000_0001_u32 as i32;
+pub const MAX_SEQUENCE: i32 = 0x7fff_ffff;
+/// Âge au-delà duquel un LSA est retiré du domaine
+pub const MAX_AGE: u16 = 3600;
+/// Écart d'âge significatif entre deux instances de même séquence
+const MAX_AGE_DIFF: u16 = 900;
+/// Option E : la zone accepte les LSA externes
+pub const OPTION_E: u8 = 0x02;
+
+/// Drapeaux des paquets Database Description
+pub const DD_INIT: u8 = 0x04;
+pub const DD_MORE: u8 = 0x02;
+pub const DD_MASTER: u8 = 0x01;
+
+/// Types de LSA
+pub const ROUTER_LSA: u8 = 1;
+pub const NETWORK_LSA: u8 = 2;
+pub const EXTERNAL_LSA: u8 = 5;
+
+/// Types de liens d'un LSA routeur
+pub const LINK_POINT_TO_POINT: u8 = 1;
+pub const LINK_TRANSIT: u8 = 2;
+pub const LINK_STUB: u8 = 3;
+pub const LINK_VIRTUAL: u8 = 4;
+
+fn invalid(message: impl Into<String>) -> AppError {
+    AppError::PacketError(format!("OSPFv2: {}", message.into()))
+}
+
+/// Paquet OSPFv2 (en-tête commun et contenu), sans authentification (AuType 0)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub router_id: Ipv4Addr,
+    pub area_id: Ipv4Addr,
+    pub body: Body,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Body {
+    Hello(Hello),
+    DatabaseDescription(DatabaseDescription),
+    LinkStateRequest(Vec<LsaKey>),
+    LinkStateUpdate(Vec<Lsa>),
+    LinkStateAck(Vec<LsaHeader>),
+}
+
+impl Body {
+    fn packet_type(&self) -> u8 {
+        match self {
+            Body::Hello(_) => 1,
+            Body::DatabaseDescription(_) => 2,
+            Body::LinkStateRequest(_) => 3,
+            Body::LinkStateUpdate(_) => 4,
+            Body::LinkStateAck(_) => 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hello {
+    pub network_mask: Ipv4Addr,
+    pub hello_interval: u16,
+    pub options: u8,
+    pub priority: u8,
+    pub dead_interval: u32,
+    pub designated_router: Ipv4Addr,
+    pub backup_designated_router: Ipv4Addr,
+    /// Routeurs dont un HELLO a été reçu récemment sur le lien
+    pub neighbors: Vec<Ipv4Addr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseDescription {
+    pub interface_mtu: u16,
+    pub options: u8,
+    pub flags: u8,
+    pub sequence: u32,
+    pub headers: Vec<LsaHeader>,
+}
+
+/// Identité d'un LSA dans le domaine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LsaKey {
+    pub ls_type: u8,
+    pub link_state_id: Ipv4Addr,
+    pub advertising_router: Ipv4Addr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LsaHeader {
+    pub age: u16,
+    pub options: u8,
+    pub ls_type: u8,
+    pub link_state_id: Ipv4Addr,
+    pub advertising_router: Ipv4Addr,
+    pub sequence: i32,
+    pub checksum: u16,
+    pub length: u16,
+}
+
+impl LsaHeader {
+    pub fn key(&self) -> LsaKey {
+        LsaKey {
+            ls_type: self.ls_type,
+            link_state_id: self.link_state_id,
+            advertising_router: self.advertising_router,
+        }
+    }
+
+    /// Vrai si cette instance est plus récente que `other` (RFC 2328, 13.1)
+    pub fn is_newer_than(&self, other: &LsaHeader) -> bool {
+        if self.sequence != other.sequence {
+            return self.sequence > other.sequence;
+        }
+        if self.checksum != other.checksum {
+            return self.checksum > other.checksum;
+        }
+        if (self.age >= MAX_AGE) != (other.age >= MAX_AGE) {
+            return self.age >= MAX_AGE;
+        }
+        self.age.abs_diff(other.age) > MAX_AGE_DIFF && self.age < other.age
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.age.to_be_bytes());
+        out.push(self.options);
+        out.push(self.ls_type);
+        out.extend_from_slice(&self.link_state_id.octets());
+        out.extend_from_slice(&self.advertising_router.octets());
+        out.extend_from_slice(&self.sequence.to_be_bytes());
+        out.extend_from_slice(&self.checksum.to_be_bytes());
+        out.extend_from_slice(&self.length.to_be_bytes());
+    }
+
+    fn decode(reader: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            age: reader.u16()?,
+            options: reader.u8()?,
+            ls_type: reader.u8()?,
+            link_state_id: reader.address()?,
+            advertising_router: reader.address()?,
+            sequence: reader.u32()? as i32,
+            checksum: reader.u16()?,
+            length: reader.u16()?,
+        })
+    }
+}
+
+/// Lien annoncé dans un LSA routeur (métrique TOS 0 seule)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RouterLink {
+    pub link_id: Ipv4Addr,
+    pub link_data: Ipv4Addr,
+    pub link_type: u8,
+    pub metric: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LsaBody {
+    Router { flags: u8, links: Vec<RouterLink> },
+    Network { network_mask: Ipv4Addr, attached_routers: Vec<Ipv4Addr> },
+    External { network_mask: Ipv4Addr, type2: bool, metric: u32, forwarding_address: Ipv4Addr, route_tag: u32 },
+    /// Type non interprété, relayé tel quel
+    Other(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lsa {
+    pub header: LsaHeader,
+    pub body: LsaBody,
+}
+
+impl Lsa {
+    /// Nouvelle instance d'âge nul, longueur et somme de contrôle calculées
+    pub fn originate(ls_type: u8, link_state_id: Ipv4Addr, advertising_router: Ipv4Addr, sequence: i32, body: LsaBody) -> Self {
+        let mut lsa = Self {
+            header: LsaHeader {
+                age: 0,
+                options: OPTION_E,
+                ls_type,
+                link_state_id,
+                advertising_router,
+                sequence,
+                checksum: 0,
+                length: 0,
+            },
+            body,
+        };
+        let encoded = lsa.encode();
+        lsa.header.length = encoded.len() as u16;
+        lsa.header.checksum = u16::from_be_bytes([encoded[16], encoded[17]]);
+        lsa
+    }
+
+    /// Encodage complet ; la somme de contrôle (âge exclu) est recalculée
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(LSA_HEADER_LEN + 64);
+        self.header.encode(&mut out);
+        match &self.body {
+            LsaBody::Router { flags, links } => {
+                out.push(*flags);
+                out.push(0);
+                out.extend_from_slice(&(links.len() as u16).to_be_bytes());
+                for link in links {
+                    out.extend_from_slice(&link.link_id.octets());
+                    out.extend_from_slice(&link.link_data.octets());
+                    out.push(link.link_type);
+                    out.push(0);
+                    out.extend_from_slice(&link.metric.to_be_bytes());
+                }
+            }
+            LsaBody::Network { network_mask, attached_routers } => {
+                out.extend_from_slice(&network_mask.octets());
+                for router in attached_routers {
+                    out.extend_from_slice(&router.octets());
+                }
+            }
+            LsaBody::External { network_mask, type2, metric, forwarding_address, route_tag } => {
+                out.extend_from_slice(&network_mask.octets());
+                out.extend_from_slice(&((u32::from(*type2) << 31) | (metric & 0x00ff_ffff)).to_be_bytes());
+                out.extend_from_slice(&forwarding_address.octets());
+                out.extend_from_slice(&route_tag.to_be_bytes());
+            }
+            LsaBody::Other(data) => out.extend_from_slice(data),
+        }
+        let length = out.len() as u16;
+        out[18..20].copy_from_slice(&length.to_be_bytes());
+        out[16..18].fill(0);
+        let checksum = fletcher_checksum(&out[2..], 14);
+        out[16..18].copy_from_slice(&checksum.to_be_bytes());
+        out
+    }
+
+    fn decode(reader: &mut Reader) -> Result<Self> {
+        let start = reader.position;
+        let header = LsaHeader::decode(reader)?;
+        let length = header.length as usize;
+        if length < LSA_HEADER_LEN {
+            return Err(invalid(format!("LSA length {} below header size", length)));
+        }
+        let raw = reader.data.get(start..start + length).ok_or_else(|| invalid("truncated LSA"))?;
+        if !fletcher_valid(&raw[2..]) {
+            return Err(invalid(format!("bad checksum on LSA {:?}", header.key())));
+        }
+        let mut body = Reader { data: &raw[..length], position: LSA_HEADER_LEN };
+        let body = match header.ls_type {
+            ROUTER_LSA => {
+                let flags = body.u8()?;
+                body.u8()?;
+                let count = body.u16()?;
+                let mut links = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let link_id = body.address()?;
+                    let link_data = body.address()?;
+                    let link_type = body.u8()?;
+                    let tos_count = body.u8()?;
+                    let metric = body.u16()?;
+                    body.skip(tos_count as usize * 4)?;
+                    links.push(RouterLink { link_id, link_data, link_type, metric });
+                }
+                LsaBody::Router { flags, links }
+            }
+            NETWORK_LSA => {
+                let network_mask = body.address()?;
+                let mut attached_routers = Vec::new();
+                while body.remaining() >= 4 {
+                    attached_routers.push(body.address()?);
+                }
+                LsaBody::Network { network_mask, attached_routers }
+            }
+            EXTERNAL_LSA => {
+                let network_mask = body.address()?;
+                let metric = body.u32()?;
+                LsaBody::External {
+                    network_mask,
+                    type2: metric & 0x8000_0000 != 0,
+                    metric: metric & 0x00ff_ffff,
+                    forwarding_address: body.address()?,
+                    route_tag: body.u32()?,
+                }
+            }
+            _ => LsaBody::Other(raw[LSA_HEADER_LEN..].to_vec()),
+        };
+        reader.position = start + length;
+        Ok(Self { header, body })
+    }
+}
+
+impl Packet {
+    /// Encodage avec longueur et somme de contrôle IP
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = vec![0u8; HEADER_LEN];
+        out[0] = VERSION;
+        out[1] = self.body.packet_type();
+        out[4..8].copy_from_slice(&self.router_id.octets());
+        out[8..12].copy_from_slice(&self.area_id.octets());
+        match &self.body {
+            Body::Hello(hello) => {
+                out.extend_from_slice(&hello.network_mask.octets());
+                out.extend_from_slice(&hello.hello_interval.to_be_bytes());
+                out.push(hello.options);
+                out.push(hello.priority);
+                out.extend_from_slice(&hello.dead_interval.to_be_bytes());
+                out.extend_from_slice(&hello.designated_router.octets());
+                out.extend_from_slice(&hello.backup_designated_router.octets());
+                for neighbor in &hello.neighbors {
+                    out.extend_from_slice(&neighbor.octets());
+                }
+            }
+            Body::DatabaseDescription(dd) => {
+                out.extend_from_slice(&dd.interface_mtu.to_be_bytes());
+                out.push(dd.options);
+                out.push(dd.flags);
+                out.extend_from_slice(&dd.sequence.to_be_bytes());
+                for header in &dd.headers {
+                    header.encode(&mut out);
+                }
+            }
+            Body::LinkStateRequest(keys) => {
+                for key in keys {
+                    out.extend_from_slice(&u32::from(key.ls_type).to_be_bytes());
+                    out.extend_from_slice(&key.link_state_id.octets());
+                    out.extend_from_slice(&key.advertising_router.octets());
+                }
+            }
+            Body::LinkStateUpdate(lsas) => {
+                out.extend_from_slice(&(lsas.len() as u32).to_be_bytes());
+                for lsa in lsas {
+                    out.extend_from_slice(&lsa.encode());
+                }
+            }
+            Body::LinkStateAck(headers) => {
+                for header in headers {
+                    header.encode(&mut out);
+                }
+            }
+        }
+        let length = out.len() as u16;
+        out[2..4].copy_from_slice(&length.to_be_bytes());
+        let checksum = internet_checksum(&out);
+        out[12..14].copy_from_slice(&checksum.to_be_bytes());
+        out
+    }
+
+    /// Décode un paquet OSPFv2 (sans l'en-tête IP) et vérifie sa somme de contrôle
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < HEADER_LEN {
+            return Err(invalid(format!("{} bytes is shorter than the header", data.len())));
+        }
+        if data[0] != VERSION {
+            return Err(invalid(format!("unsupported version {}", data[0])));
+        }
+        let length = u16::from_be_bytes([data[2], data[3]]) as usize;
+        if length < HEADER_LEN || length > data.len() {
+            return Err(invalid(format!("length {} does not match {} received bytes", length, data.len())));
+        }
+        let data = &data[..length];
+        let autype = u16::from_be_bytes([data[14], data[15]]);
+        if autype != 0 {
+            return Err(invalid(format!("authentication type {} is not supported", autype)));
+        }
+        if internet_checksum(data) != 0 {
+            return Err(invalid("bad packet checksum"));
+        }
+        let mut reader = Reader { data, position: 4 };
+        let router_id = reader.address()?;
+        let area_id = reader.address()?;
+        reader.position = HEADER_LEN;
+        let body = match data[1] {
+            1 => {
+                let mut hello = Hello {
+                    network_mask: reader.address()?,
+                    hello_interval: reader.u16()?,
+                    options: reader.u8()?,
+                    priority: reader.u8()?,
+                    dead_interval: reader.u32()?,
+                    designated_router: reader.address()?,
+                    backup_designated_router: reader.address()?,
+                    neighbors: Vec::new(),
+                };
+                while reader.remaining() >= 4 {
+                    hello.neighbors.push(reader.address()?);
+                }
+                Body::Hello(hello)
+            }
+            2 => {
+                let mut dd = DatabaseDescription {
+                    interface_mtu: reader.u16()?,
+                    options: reader.u8()?,
+                    flags: reader.u8()?,
+                    sequence: reader.u32()?,
+                    headers: Vec::new(),
+                };
+                while reader.remaining() >= LSA_HEADER_LEN {
+                    dd.headers.push(LsaHeader::decode(&mut reader)?);
+                }
+                Body::DatabaseDescription(dd)
+            }
+            3 => {
+                let mut keys = Vec::new();
+                while reader.remaining() >= 12 {
+                    let ls_type = reader.u32()?;
+                    keys.push(LsaKey {
+                        ls_type: u8::try_from(ls_type).map_err(|_| invalid(format!("LS type {}", ls_type)))?,
+                        link_state_id: reader.address()?,
+                        advertising_router: reader.address()?,
+                    });
+                }
+                Body::LinkStateRequest(keys)
+            }
+            4 => {
+                let count = reader.u32()?;
+                let mut lsas = Vec::new();
+                for _ in 0..count {
+                    lsas.push(Lsa::decode(&mut reader)?);
+                }
+                Body::LinkStateUpdate(lsas)
+            }
+            5 => {
+                let mut headers = Vec::new();
+                while reader.remaining() >= LSA_HEADER_LEN {
+                    headers.push(LsaHeader::decode(&mut reader)?);
+                }
+                Body::LinkStateAck(headers)
+            }
+            other => return Err(invalid(format!("unknown packet type {}", other))),
+        };
+        Ok(Self { router_id, area_id, body })
+    }
+}
+
+/// Somme de contrôle IP (complément à un) ; nulle sur un paquet valide
+pub fn internet_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data.chunks(2)
+        .map(|chunk| u32::from(u16::from_be_bytes([chunk[0], chunk.get(1).copied().unwrap_or(0)])))
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// Somme de Fletcher d'un LSA sans son champ âge, le champ somme à `offset` étant à zéro
+/// (RFC 2328, 12.1.7 ; ISO 8473)
+pub fn fletcher_checksum(data: &[u8], offset: usize) -> u16 {
+    let (c0, c1) = fletcher_sums(data);
+    let mut x = ((data.len() - offset - 1) as i64 * c0 - c1) % 255;
+    if x <= 0 {
+        x += 255;
+    }
+    let mut y = 510 - c0 - x;
+    if y > 255 {
+        y -= 255;
+    }
+    ((x as u16) << 8) | (y as u16 & 0xff)
+}
+
+/// Un LSA (sans son âge) est intègre si ses deux sommes de Fletcher sont nulles
+pub fn fletcher_valid(data: &[u8]) -> bool {
+    fletcher_sums(data) == (0, 0)
+}
+
+fn fletcher_sums(data: &[u8]) -> (i64, i64) {
+    data.iter().fold((0i64, 0i64), |(c0, c1), byte| {
+        let c0 = (c0 + i64::from(*byte)) % 255;
+        (c0, (c1 + c0) % 255)
+    })
+}
+
+/// Lecture séquentielle en ordre réseau
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.position)
+    }
+
+    fn take(&mut self, count: usize) -> Result<&[u8]> {
+        let bytes = self.data.get(self.position..self.position + count).ok_or_else(|| invalid("truncated packet"))?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<()> {
+        self.take(count).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn address(&mut self) -> Result<Ipv4Addr> {
+        Ok(Ipv4Addr::from(self.u32()?))
+    }
+}
@@ -0,0 +1,292 @@
+use crate::error::{AppError, Result};
+use crate::fragment::{FragmentMessage, MAX_FRAGMENTS, MAX_FRAGMENT_PAYLOAD};
+use crate::types::{AuthChallenge, AuthResponse, ControlMessage, EchoMessage, HelloMessage, LSAMessage, LsaAckMessage, RouteRequest, RouteResponse};
+use ipnetwork::Ipv4Network;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::net::Ipv4Addr;
+
+/// Taille maximale d'un message déchiffré (charge utile UDP maximale)
+pub const MAX_PACKET_LEN: usize = 65_507;
+/// Nombre maximal de préfixes par LSA (table de routage ou routes externes)
+pub const MAX_PREFIXES: usize = 1024;
+/// Nombre maximal de voisins annoncés par LSA, et d'échos par HELLO
+pub const MAX_NEIGHBORS: usize = 256;
+/// Longueur maximale du chemin parcouru par un LSA
+pub const MAX_PATH_LEN: usize = 64;
+/// Nombre maximal d'identités par acquittement
+pub const MAX_ACKS: usize = 256;
+/// Longueur maximale d'une commande de contrôle
+pub const MAX_COMMAND_LEN: usize = 1024;
+/// Longueur maximale d'un nom d'hôte annoncé
+pub const MAX_HOSTNAME_LEN: usize = 63;
+/// Longueur maximale d'un nonce ou d'une signature (encodés en base64)
+pub const MAX_TOKEN_LEN: usize = 256;
+/// Nombre maximal de TLV opaques par LSA
+pub const MAX_OPAQUE_TLVS: usize = 64;
+/// Taille maximale de la valeur d'un TLV opaque
+pub const MAX_OPAQUE_LEN: usize = 1024;
+
+/// Message protocolaire validé, prêt à être traité
+#[derive(Debug, Clone)]
+pub enum Packet {
+    Hello(HelloMessage),
+    Lsa(Box<LSAMessage>),
+    Control(ControlMessage),
+    LsaAck(LsaAckMessage),
+    AuthChallenge(AuthChallenge),
+    AuthResponse(AuthResponse),
+    Fragment(FragmentMessage),
+    Echo(EchoMessage),
+    RouteRequest(RouteRequest),
+    RouteResponse(RouteResponse),
+}
+
+impl Packet {
+    pub fn message_type(&self) -> u8 {
+        match self {
+            Packet::Hello(_) => 1,
+            Packet::Lsa(_) => 2,
+            Packet::Control(_) => 3,
+            Packet::LsaAck(_) => 4,
+            Packet::AuthChallenge(_) => 5,
+            Packet::AuthResponse(_) => 6,
+            Packet::Fragment(_) => 7,
+            Packet::Echo(_) => 8,
+            Packet::RouteRequest(_) => 10,
+            Packet::RouteResponse(_) => 11,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Header {
+    message_type: u8,
+}
+
+/// Décode et valide un message déchiffré ; tout message mal formé, hors limites
+/// ou de type inconnu est rejeté avant d'atteindre l'état du routeur
+pub fn parse(data: &[u8]) -> Result<Packet> {
+    if data.len() > MAX_PACKET_LEN {
+        return Err(invalid(format!("{} bytes exceeds the {} bytes limit", data.len(), MAX_PACKET_LEN)));
+    }
+    let header: Header = decode(data)?;
+    parse_typed(header.message_type, data)
+}
+
+/// Décode et valide un message dont le type est connu par l'en-tête de sa trame ; le type
+/// déclaré dans le message doit être le même
+pub fn parse_typed(message_type: u8, data: &[u8]) -> Result<Packet> {
+    if data.len() > MAX_PACKET_LEN {
+        return Err(invalid(format!("{} bytes exceeds the {} bytes limit", data.len(), MAX_PACKET_LEN)));
+    }
+    let packet = match message_type {
+        1 => Packet::Hello(decode(data)?),
+        2 => Packet::Lsa(decode(data)?),
+        3 => Packet::Control(decode(data)?),
+        4 => Packet::LsaAck(decode(data)?),
+        5 => Packet::AuthChallenge(decode(data)?),
+        6 => Packet::AuthResponse(decode(data)?),
+        7 => Packet::Fragment(decode(data)?),
+        8 => Packet::Echo(decode(data)?),
+        10 => Packet::RouteRequest(decode(data)?),
+        11 => Packet::RouteResponse(decode(data)?),
+        other => return Err(invalid(format!("unknown message type {}", other))),
+    };
+    let declared = match &packet {
+        Packet::Hello(message) => message.message_type,
+        Packet::Lsa(message) => message.message_type,
+        Packet::Control(message) => message.message_type,
+        Packet::LsaAck(message) => message.message_type,
+        Packet::AuthChallenge(message) => message.message_type,
+        Packet::AuthResponse(message) => message.message_type,
+        Packet::Fragment(message) => message.message_type,
+        Packet::Echo(message) => message.message_type,
+        Packet::RouteRequest(message) => message.message_type,
+        Packet::RouteResponse(message) => message.message_type,
+    };
+    if declared != message_type {
+        return Err(invalid(format!("message of type {} framed as type {}", declared, message_type)));
+    }
+    validate(&packet)?;
+    Ok(packet)
+}
+
+fn decode<T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    serde_json::from_slice(data).map_err(|e| invalid(format!("malformed message: {}", e)))
+}
+
+fn invalid(reason: String) -> AppError {
+    AppError::PacketError(reason)
+}
+
+/// Valide un message déjà décodé, par exemple un LSA réuni à partir de ses parties
+pub fn validate(packet: &Packet) -> Result<()> {
+    match packet {
+        Packet::Hello(hello) => {
+            check_router_id("router_ip", &hello.router_ip)?;
+            if let Some(hostname) = &hello.hostname {
+                check_hostname(hostname)?;
+            }
+            check_count("echoes", hello.echoes.len(), MAX_NEIGHBORS)?;
+            for echo in &hello.echoes {
+                check_router_id("echo router_ip", &echo.router_ip)?;
+            }
+        }
+        Packet::Lsa(lsa) => validate_lsa(lsa)?,
+        Packet::Control(control) => {
+            check_count("command length", control.command.len(), MAX_COMMAND_LEN)?;
+            if let Some(user) = &control.user {
+                check_count("user name length", user.len(), MAX_HOSTNAME_LEN)?;
+            }
+            if let Some(signature) = &control.signature {
+                check_count("signature length", signature.len(), MAX_TOKEN_LEN)?;
+            }
+        }
+        Packet::LsaAck(ack) => {
+            check_router_id("router_ip", &ack.router_ip)?;
+            check_count("acks", ack.acks.len(), MAX_ACKS)?;
+            for identity in &ack.acks {
+                check_router_id("ack originator", &identity.originator)?;
+            }
+        }
+        Packet::AuthChallenge(challenge) => {
+            check_router_id("router_ip", &challenge.router_ip)?;
+            check_count("nonce length", challenge.nonce.len(), MAX_TOKEN_LEN)?;
+        }
+        Packet::AuthResponse(response) => {
+            check_router_id("router_ip", &response.router_ip)?;
+            check_count("nonce length", response.nonce.len(), MAX_TOKEN_LEN)?;
+            check_count("signature length", response.signature.len(), MAX_TOKEN_LEN)?;
+        }
+        Packet::Fragment(fragment) => {
+            check_count("fragment count", fragment.count as usize, MAX_FRAGMENTS as usize)?;
+            if fragment.count == 0 || fragment.index >= fragment.count {
+                return Err(invalid(format!("fragment {} of {}", fragment.index, fragment.count)));
+            }
+            // Encodage base64 : 4 caractères pour 3 octets
+            check_count("fragment length", fragment.data.len(), MAX_FRAGMENT_PAYLOAD.div_ceil(3) * 4)?;
+        }
+        Packet::Echo(echo) => {
+            check_router_id("source", &echo.source)?;
+            check_router_id("target", &echo.target)?;
+            if echo.ttl > crate::INITIAL_TTL {
+                return Err(invalid(format!("TTL {} above {}", echo.ttl, crate::INITIAL_TTL)));
+            }
+            check_count("path", echo.path.len(), MAX_PATH_LEN)?;
+            for hop in &echo.path {
+                check_router_id("path", hop)?;
+            }
+            check_count("signature length", echo.signature.len(), MAX_TOKEN_LEN)?;
+            check_count("padding length", echo.padding.len(), crate::fragment::DEFAULT_MTU as usize)?;
+        }
+        Packet::RouteRequest(_) => {}
+        Packet::RouteResponse(response) => {
+            check_router_id("router_id", &response.router_id)?;
+            if let Some(next_hop) = &response.next_hop {
+                check_router_id("next_hop", next_hop)?;
+            }
+            check_count("path", response.path.len(), MAX_PATH_LEN)?;
+            for hop in &response.path {
+                check_router_id("path", hop)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_lsa(lsa: &LSAMessage) -> Result<()> {
+    check_router_id("router_ip", &lsa.router_ip)?;
+    check_router_id("originator", &lsa.originator)?;
+    if let Some(last_hop) = &lsa.last_hop {
+        check_router_id("last_hop", last_hop)?;
+    }
+    if lsa.ttl > crate::INITIAL_TTL {
+        return Err(invalid(format!("TTL {} above {}", lsa.ttl, crate::INITIAL_TTL)));
+    }
+    check_count("neighbors", lsa.neighbors.len(), MAX_NEIGHBORS)?;
+    if lsa.neighbor_count != lsa.neighbors.len() {
+        return Err(invalid(format!("neighbor_count {} but {} neighbors", lsa.neighbor_count, lsa.neighbors.len())));
+    }
+    for neighbor in &lsa.neighbors {
+        check_router_id("neighbor_ip", &neighbor.neighbor_ip)?;
+        if let Some(hostname) = &neighbor.hostname {
+            check_hostname(hostname)?;
+        }
+    }
+    check_count("path", lsa.path.len(), MAX_PATH_LEN)?;
+    for hop in &lsa.path {
+        check_router_id("path", hop)?;
+    }
+    check_count("routing_table", lsa.routing_table.len(), MAX_PREFIXES)?;
+    check_count("external_routes", lsa.external_routes.len(), MAX_PREFIXES)?;
+    check_count("external_metric_types", lsa.external_metric_types.len(), MAX_PREFIXES)?;
+    // Une étiquette par route interne ou externe
+    check_count("route_tags", lsa.route_tags.len(), 2 * MAX_PREFIXES)?;
+    check_count("withdrawn", lsa.withdrawn.len(), MAX_PREFIXES)?;
+    check_count("opaque", lsa.opaque.len(), MAX_OPAQUE_TLVS)?;
+    if let Some(part) = lsa.part {
+        if part.count == 0 || part.count > crate::lsa_part::MAX_PARTS || part.index >= part.count {
+            return Err(invalid(format!("part {} of {} is out of range", part.index, part.count)));
+        }
+    }
+    for tlv in &lsa.opaque {
+        check_count("opaque value length", tlv.value.len(), MAX_OPAQUE_LEN)?;
+    }
+    Ok(())
+}
+
+fn check_count(field: &str, count: usize, max: usize) -> Result<()> {
+    if count > max {
+        return Err(invalid(format!("{}: {} exceeds the limit of {}", field, count, max)));
+    }
+    Ok(())
+}
+
+/// Un identifiant de routeur est une adresse IPv4, ou un préfixe pour les LSA d'un segment
+fn check_router_id(field: &str, value: &str) -> Result<()> {
+    if value.parse::<Ipv4Addr>().is_ok() || value.parse::<Ipv4Network>().is_ok() {
+        return Ok(());
+    }
+    Err(invalid(format!("{}: '{}' is not an IPv4 address", field, truncate(value))))
+}
+
+/// Nom d'hôte : 1 à 63 caractères alphanumériques, '-', '_' ou '.'
+pub fn is_valid_hostname(hostname: &str) -> bool {
+    (1..=MAX_HOSTNAME_LEN).contains(&hostname.len())
+        && hostname.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn check_hostname(hostname: &str) -> Result<()> {
+    if !is_valid_hostname(hostname) {
+        return Err(invalid(format!("'{}' is not a valid hostname", truncate(hostname))));
+    }
+    Ok(())
+}
+
+/// Tronque une valeur reçue avant de l'inclure dans un message d'erreur
+fn truncate(value: &str) -> String {
+    value.chars().take(64).collect()
+}
+
+/// Point d'entrée des cibles de fuzzing : ne doit jamais paniquer, et tout message
+/// accepté doit se réencoder puis se relire à l'identique
+pub fn fuzz_parse(data: &[u8]) {
+    if let Ok(packet) = parse(data) {
+        let encoded = match &packet {
+            Packet::Hello(message) => serde_json::to_vec(message),
+            Packet::Lsa(message) => serde_json::to_vec(message),
+            Packet::Control(message) => serde_json::to_vec(message),
+            Packet::LsaAck(message) => serde_json::to_vec(message),
+            Packet::AuthChallenge(message) => serde_json::to_vec(message),
+            Packet::AuthResponse(message) => serde_json::to_vec(message),
+            Packet::Fragment(message) => serde_json::to_vec(message),
+            Packet::Echo(message) => serde_json::to_vec(message),
+            Packet::RouteRequest(message) => serde_json::to_vec(message),
+            Packet::RouteResponse(message) => serde_json::to_vec(message),
+        }
+        .expect("a parsed packet must serialize");
+        let reparsed = parse(&encoded).expect("a re-encoded packet must parse");
+        assert_eq!(reparsed.message_type(), packet.message_type());
+    }
+}
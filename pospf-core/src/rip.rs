@@ -0,0 +1,123 @@
+use crate::error::{AppError, Result};
+use crate::types::Prefix;
+use ipnetwork::Ipv4Network;
+use std::net::Ipv4Addr;
+
+/// Port UDP de RIP
+pub const PORT: u16 = 520;
+/// Groupe multicast des routeurs RIPv2
+pub const RIP_ROUTERS: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 9);
+pub const VERSION: u8 = 2;
+/// Métrique d'une destination injoignable
+pub const INFINITY: u32 = 16;
+/// Entrées au plus par message (RFC 2453, 3.6)
+pub const MAX_ENTRIES: usize = 25;
+const HEADER_LEN: usize = 4;
+const ENTRY_LEN: usize = 20;
+const AFI_INET: u16 = 2;
+const AFI_AUTHENTICATION: u16 = 0xffff;
+
+fn invalid(message: impl Into<String>) -> AppError {
+    AppError::PacketError(format!("RIPv2: {}", message.into()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Request,
+    Response,
+}
+
+/// Route annoncée par un message RIPv2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RipEntry {
+    pub prefix: Prefix,
+    pub tag: u16,
+    /// Prochain saut explicite, 0.0.0.0 pour l'émetteur du message
+    pub next_hop: Ipv4Addr,
+    pub metric: u32,
+}
+
+/// Message RIPv2 ; une requête sans entrée demande la table complète
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RipPacket {
+    pub command: Command,
+    pub entries: Vec<RipEntry>,
+}
+
+impl RipPacket {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + ENTRY_LEN * self.entries.len().max(1));
+        out.push(match self.command {
+            Command::Request => 1,
+            Command::Response => 2,
+        });
+        out.push(VERSION);
+        out.extend_from_slice(&[0, 0]);
+        if self.command == Command::Request && self.entries.is_empty() {
+            // Demande de la table complète : AFI 0 et métrique infinie
+            out.extend_from_slice(&[0; 16]);
+            out.extend_from_slice(&INFINITY.to_be_bytes());
+            return out;
+        }
+        for entry in &self.entries {
+            let network = entry.prefix.network();
+            out.extend_from_slice(&AFI_INET.to_be_bytes());
+            out.extend_from_slice(&entry.tag.to_be_bytes());
+            out.extend_from_slice(&network.network().octets());
+            out.extend_from_slice(&network.mask().octets());
+            out.extend_from_slice(&entry.next_hop.octets());
+            out.extend_from_slice(&entry.metric.to_be_bytes());
+        }
+        out
+    }
+
+    /// Décode un message RIPv2 ; les entrées d'une autre famille d'adresses, de masque
+    /// non contigu ou de métrique hors de 1..=16 sont ignorées
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < HEADER_LEN || !(data.len() - HEADER_LEN).is_multiple_of(ENTRY_LEN) {
+            return Err(invalid(format!("length {} is not a whole number of entries", data.len())));
+        }
+        let command = match data[0] {
+            1 => Command::Request,
+            2 => Command::Response,
+            other => return Err(invalid(format!("unknown command {}", other))),
+        };
+        if data[1] != VERSION {
+            return Err(invalid(format!("unsupported version {}", data[1])));
+        }
+        let count = (data.len() - HEADER_LEN) / ENTRY_LEN;
+        if count > MAX_ENTRIES {
+            return Err(invalid(format!("{} entries exceed the limit of {}", count, MAX_ENTRIES)));
+        }
+        let u32_at = |offset: usize| u32::from_be_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]);
+        let mut entries = Vec::new();
+        for index in 0..count {
+            let offset = HEADER_LEN + index * ENTRY_LEN;
+            let afi = u16::from_be_bytes([data[offset], data[offset + 1]]);
+            let metric = u32_at(offset + 16);
+            match afi {
+                AFI_AUTHENTICATION => return Err(invalid("authentication is not supported")),
+                0 if command == Command::Request && count == 1 && metric == INFINITY => {
+                    return Ok(Self { command, entries: Vec::new() });
+                }
+                AFI_INET => {}
+                _ => continue,
+            }
+            let address = Ipv4Addr::from(u32_at(offset + 4));
+            let mask = Ipv4Addr::from(u32_at(offset + 8));
+            let Ok(network) = Ipv4Network::with_netmask(address, mask) else {
+                continue;
+            };
+            if !(1..=INFINITY).contains(&metric) {
+                continue;
+            }
+            entries.push(RipEntry {
+                prefix: Prefix::new(network),
+                tag: u16::from_be_bytes([data[offset + 2], data[offset + 3]]),
+                next_hop: Ipv4Addr::from(u32_at(offset + 12)),
+                metric,
+            });
+        }
+        Ok(Self { command, entries })
+    }
+}
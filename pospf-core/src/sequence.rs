@@ -0,0 +1,114 @@
+use std::sync::Mutex;
+
+/// Premier numéro de séquence (InitialSequenceNumber d'OSPF, 0x80000001)
+pub const INITIAL_SEQUENCE: u32 = 0x8000_0001;
+/// Dernier numéro (MaxSequenceNumber d'OSPF) : l'instance qui le porte est retirée avant
+/// de repartir de `INITIAL_SEQUENCE`
+pub const MAX_SEQUENCE: u32 = 0x7FFF_FFFF;
+/// Numéros réservés à chaque écriture du fichier d'état, pour ne pas l'écrire à chaque LSA
+const RESERVATION: i32 = 1000;
+
+/// Compare deux numéros dans l'espace signé linéaire d'OSPF (INITIAL < … < MAX) ; INITIAL
+/// n'est accepté après MAX qu'une fois l'instance MAX retirée chez le destinataire
+pub fn is_newer(candidate: u32, current: u32) -> bool {
+    (candidate as i32) > (current as i32)
+}
+
+#[derive(Debug)]
+struct Counter {
+    current: u32,
+    /// Plus grand numéro couvert par le fichier d'état
+    reserved: u32,
+    /// Retrait de l'instance MAX en cours
+    wrapping: bool,
+}
+
+/// Compteur des LSA émis par ce routeur, indépendant de l'horloge une fois enregistré
+#[derive(Debug)]
+pub struct SequenceCounter {
+    counter: Mutex<Counter>,
+}
+
+impl SequenceCounter {
+    /// Compteur partant de `start` ; rien n'est encore réservé
+    pub fn starting_at(start: u32) -> Self {
+        let start = if is_newer(start, INITIAL_SEQUENCE) { start } else { INITIAL_SEQUENCE };
+        Self { counter: Mutex::new(Counter { current: start, reserved: start, wrapping: false }) }
+    }
+
+    /// Sans compteur enregistré, l'horloge fournit un point de départ supérieur aux
+    /// numéros que les voisins ont pu retenir d'une exécution précédente
+    pub fn from_clock() -> Self {
+        Self::starting_at(crate::clock::unix_secs() as u32)
+    }
+
+    fn counter(&self) -> std::sync::MutexGuard<'_, Counter> {
+        self.counter.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Numéro suivant, et vrai s'il faut enregistrer une nouvelle réservation ; `None` une
+    /// fois MAX émis, jusqu'à ce que `restart` suive le retrait de cette instance
+    pub fn advance(&self) -> Option<(u32, bool)> {
+        let mut counter = self.counter();
+        if counter.current == MAX_SEQUENCE {
+            return None;
+        }
+        counter.current = counter.current.wrapping_add(1);
+        let reserve = !is_newer(counter.reserved, counter.current);
+        if reserve {
+            counter.reserved = (counter.current as i32).saturating_add(RESERVATION) as u32;
+        }
+        Some((counter.current, reserve))
+    }
+
+    /// Vrai pour le seul appelant qui doit retirer l'instance MAX ; faux avant MAX ou si
+    /// le retrait est déjà en cours
+    pub fn begin_wrap(&self) -> bool {
+        let mut counter = self.counter();
+        if counter.current != MAX_SEQUENCE || counter.wrapping {
+            return false;
+        }
+        counter.wrapping = true;
+        true
+    }
+
+    /// Instance MAX retirée : le prochain numéro est `INITIAL_SEQUENCE`, et la réservation
+    /// repart de là
+    pub fn restart(&self) {
+        let mut counter = self.counter();
+        counter.current = INITIAL_SEQUENCE.wrapping_sub(1);
+        counter.reserved = counter.current;
+        counter.wrapping = false;
+    }
+
+    /// Dernier numéro émis
+    pub fn current(&self) -> u32 {
+        self.counter().current
+    }
+
+    /// Numéro à enregistrer : le prochain démarrage reprendra au-delà
+    pub fn reserved(&self) -> u32 {
+        self.counter().reserved
+    }
+
+    /// Reprend après le numéro enregistré au dernier arrêt
+    pub fn resume_after(&self, saved: u32) {
+        let mut counter = self.counter();
+        counter.current = saved;
+        counter.reserved = saved;
+    }
+
+    /// Une instance de nos propres LSA plus récente que le compteur circule encore
+    /// (compteur perdu) : les prochains numéros doivent la dépasser
+    pub fn observe(&self, seq_num: u32) -> bool {
+        let mut counter = self.counter();
+        if !is_newer(seq_num, counter.current) {
+            return false;
+        }
+        counter.current = seq_num;
+        if is_newer(seq_num, counter.reserved) {
+            counter.reserved = seq_num;
+        }
+        true
+    }
+}
@@ -0,0 +1,173 @@
+use crate::dijkstra::{NetworkLink, NetworkTopology, PathMetrics, PathSelection, RouteInfo};
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Add;
+
+/// Implémentation du calcul des plus courts chemins depuis un routeur
+pub trait SpfEngine: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Meilleurs chemins depuis `source` selon la politique, liens actifs uniquement
+    fn shortest_paths(&self, topology: &NetworkTopology, source: &str, policy: PathSelection) -> HashMap<String, RouteInfo>;
+}
+
+/// Dijkstra propre à `NetworkTopology`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NativeEngine;
+
+impl SpfEngine for NativeEngine {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn shortest_paths(&self, topology: &NetworkTopology, source: &str, policy: PathSelection) -> HashMap<String, RouteInfo> {
+        topology.calculate_shortest_paths(source, policy)
+    }
+}
+
+/// Dijkstra de petgraph, sur le graphe orienté des liens actifs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PetgraphEngine;
+
+/// Moteur SPF choisi par la configuration
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SpfEngineKind {
+    #[default]
+    Native,
+    Petgraph,
+}
+
+impl SpfEngineKind {
+    pub fn engine(self) -> &'static dyn SpfEngine {
+        match self {
+            SpfEngineKind::Native => &NativeEngine,
+            SpfEngineKind::Petgraph => &PetgraphEngine,
+        }
+    }
+}
+
+/// Graphe orienté des liens actifs d'une topologie, et index de chaque routeur ; les
+/// routeurs sont ajoutés par ordre d'identifiant pour un graphe reproductible
+pub fn active_graph(topology: &NetworkTopology) -> (DiGraph<String, &NetworkLink>, HashMap<String, NodeIndex>) {
+    let names: BTreeSet<&str> = topology.nodes.keys()
+        .map(String::as_str)
+        .chain(topology.links.iter().flat_map(|link| [link.from.as_str(), link.to.as_str()]))
+        .collect();
+    let mut graph = DiGraph::new();
+    let indices: HashMap<String, NodeIndex> = names.into_iter()
+        .map(|name| (name.to_string(), graph.add_node(name.to_string())))
+        .collect();
+    for link in topology.links.iter().filter(|link| link.is_active) {
+        graph.add_edge(indices[&link.from], indices[&link.to], link);
+    }
+    (graph, indices)
+}
+
+/// Score d'un chemin pour petgraph : critères cumulés, comparés selon la politique. Le
+/// score nul (`Default`) n'est jamais comparé, seule la politique des liens compte
+#[derive(Debug, Clone, Copy)]
+struct Score {
+    metrics: PathMetrics,
+    policy: PathSelection,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Score { metrics: PathMetrics::ORIGIN, policy: PathSelection::default() }
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, link: Score) -> Score {
+        Score { metrics: self.metrics.then(&link.metrics), policy: link.policy }
+    }
+}
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.metrics.key(self.policy) == other.metrics.key(other.policy)
+    }
+}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.metrics.key(self.policy).cmp(&other.metrics.key(other.policy)))
+    }
+}
+
+impl SpfEngine for PetgraphEngine {
+    fn name(&self) -> &'static str {
+        "petgraph"
+    }
+
+    fn shortest_paths(&self, topology: &NetworkTopology, source: &str, policy: PathSelection) -> HashMap<String, RouteInfo> {
+        let (graph, indices) = active_graph(topology);
+        let Some(&start) = indices.get(source) else {
+            return HashMap::new();
+        };
+        let scores = petgraph::algo::dijkstra(&graph, start, None, |edge| Score {
+            metrics: PathMetrics::of_link(edge.weight()),
+            policy,
+        });
+
+        // petgraph ne donne que les scores : chaque routeur est rattaché, par ordre de score,
+        // à un prédécesseur déjà placé dont le chemin prolongé par le lien atteint ce score
+        let mut pending: Vec<NodeIndex> = scores.keys().copied().filter(|node| *node != start).collect();
+        pending.sort_by(|a, b| scores[a].metrics.key(policy).cmp(&scores[b].metrics.key(policy)).then_with(|| graph[*a].cmp(&graph[*b])));
+        let mut placed: HashMap<NodeIndex, (Vec<String>, PathMetrics)> = HashMap::from([(start, (vec![source.to_string()], PathMetrics::ORIGIN))]);
+        while !pending.is_empty() {
+            let mut progress = false;
+            let mut index = 0;
+            while index < pending.len() {
+                let node = pending[index];
+                let target = scores[&node].metrics.key(policy);
+                let best = graph.edges_directed(node, Direction::Incoming)
+                    .filter_map(|edge| {
+                        let (path, metrics) = placed.get(&edge.source())?;
+                        let extended = metrics.then(&PathMetrics::of_link(edge.weight()));
+                        (extended.key(policy) == target).then_some((path, extended))
+                    })
+                    .min_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| a.0.cmp(b.0)));
+                match best {
+                    Some((path, metrics)) => {
+                        let mut path = path.clone();
+                        path.push(graph[node].clone());
+                        placed.insert(node, (path, metrics));
+                        pending.remove(index);
+                        progress = true;
+                    }
+                    None => index += 1,
+                }
+            }
+            if !progress {
+                break;
+            }
+        }
+
+        placed.into_iter()
+            .filter(|(node, _)| *node != start)
+            .map(|(node, (path, metrics))| {
+                let destination = graph[node].clone();
+                let route = RouteInfo {
+                    destination: destination.clone(),
+                    next_hop: path[1].clone(),
+                    total_cost: metrics.total_cost,
+                    hop_count: metrics.hop_count,
+                    bottleneck_capacity: metrics.bottleneck_capacity,
+                    total_latency_us: metrics.total_latency_us,
+                    policy,
+                    path,
+                    is_reachable: true,
+                };
+                (destination, route)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,55 @@
+use crate::dijkstra::NetworkTopology;
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::visit::EdgeRef;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// Points uniques de défaillance d'une topologie : éléments dont la panne isolerait une
+/// partie des routeurs du reste du réseau
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SinglePointsOfFailure {
+    /// Routeurs ou pseudo-nœuds de segment (points d'articulation), triés
+    pub routers: Vec<String>,
+    /// Liens (ponts), extrémités dans l'ordre, triés
+    pub links: Vec<(String, String)>,
+}
+
+impl SinglePointsOfFailure {
+    pub fn is_empty(&self) -> bool {
+        self.routers.is_empty() && self.links.is_empty()
+    }
+}
+
+/// Points d'articulation et ponts du graphe non orienté des liens actifs. Deux liens
+/// parallèles entre les mêmes routeurs se secourent : aucun des deux n'est un pont
+pub fn analyze(topology: &NetworkTopology) -> SinglePointsOfFailure {
+    let pairs: BTreeSet<(&str, &str)> = topology.links.iter()
+        .filter(|link| link.is_active && link.from != link.to)
+        .map(|link| (link.from.as_str().min(link.to.as_str()), link.from.as_str().max(link.to.as_str())))
+        .collect();
+    // Chaque lien figure dans les deux sens : plusieurs liens dans un même sens sont parallèles
+    let parallel = |(a, b): (&str, &str)| {
+        let directed = |from: &str, to: &str| topology.links.iter().filter(|link| link.is_active && link.from == from && link.to == to).count();
+        directed(a, b).max(directed(b, a)) > 1
+    };
+
+    let names: BTreeSet<&str> = pairs.iter().flat_map(|(a, b)| [*a, *b]).collect();
+    let mut graph: UnGraph<&str, ()> = UnGraph::new_undirected();
+    let indices: HashMap<&str, NodeIndex> = names.into_iter().map(|name| (name, graph.add_node(name))).collect();
+    for (a, b) in &pairs {
+        graph.add_edge(indices[a], indices[b], ());
+    }
+
+    let mut routers: Vec<String> = petgraph::algo::articulation_points::articulation_points(&graph).into_iter().map(|node| graph[node].to_string()).collect();
+    routers.sort();
+    let mut links: Vec<(String, String)> = petgraph::algo::bridges::bridges(&graph)
+        .map(|edge| {
+            let (a, b) = (graph[edge.source()], graph[edge.target()]);
+            (a.min(b), a.max(b))
+        })
+        .filter(|pair| !parallel(*pair))
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+        .collect();
+    links.sort();
+    SinglePointsOfFailure { routers, links }
+}
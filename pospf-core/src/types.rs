@@ -0,0 +1,619 @@
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Serialize, Deserialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use ipnetwork::Ipv4Network;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Préfixe IPv4 en notation CIDR, tel qu'annoncé : les réseaux connectés gardent l'adresse
+/// de l'interface (« 192.168.1.1/24 »), `network` en donne la forme normalisée
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Prefix(Ipv4Network);
+
+impl Prefix {
+    pub fn new(network: Ipv4Network) -> Self {
+        Self(network)
+    }
+
+    /// Route par défaut 0.0.0.0/0
+    pub fn default_route() -> Self {
+        Self(Ipv4Network::new(Ipv4Addr::UNSPECIFIED, 0).expect("/0 is a valid prefix length"))
+    }
+
+    /// Réseau sans les bits d'hôte (« 192.168.1.0/24 »), tel qu'installé dans le noyau
+    pub fn network(&self) -> Ipv4Network {
+        Ipv4Network::new(self.0.network(), self.0.prefix()).unwrap_or(self.0)
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.0.prefix()
+    }
+
+    pub fn contains(&self, address: Ipv4Addr) -> bool {
+        self.0.contains(address)
+    }
+
+    /// Vrai si `other` est inclus dans ce préfixe (ou lui est égal)
+    pub fn covers(&self, other: &Prefix) -> bool {
+        self.prefix_len() <= other.prefix_len() && self.contains(other.0.network())
+    }
+}
+
+impl From<Ipv4Network> for Prefix {
+    fn from(network: Ipv4Network) -> Self {
+        Self(network)
+    }
+}
+
+impl std::fmt::Display for Prefix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::str::FromStr for Prefix {
+    type Err = crate::error::AppError;
+
+    /// La longueur est obligatoire : « 10.0.0.1 » seul n'est pas un préfixe
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.contains('/')
+            .then(|| s.parse::<Ipv4Network>().ok())
+            .flatten()
+            .map(Self)
+            .ok_or_else(|| crate::error::AppError::RouteError(format!("'{}' is not an IPv4 prefix in CIDR notation", s)))
+    }
+}
+
+impl Serialize for Prefix {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Prefix {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Identifiant 32 bits d'un routeur, affiché en notation pointée ; il ne dépend pas des
+/// adresses des interfaces, si bien qu'une renumérotation ne change pas l'identité du routeur
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RouterId(pub u32);
+
+impl RouterId {
+    /// Identifiant automatique, comme OSPF : la plus grande adresse IPv4 des interfaces
+    pub fn from_addresses(addresses: impl IntoIterator<Item = Ipv4Addr>) -> Option<Self> {
+        addresses.into_iter().max().map(Self::from)
+    }
+}
+
+impl From<Ipv4Addr> for RouterId {
+    fn from(address: Ipv4Addr) -> Self {
+        Self(u32::from(address))
+    }
+}
+
+impl std::fmt::Display for RouterId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Ipv4Addr::from(self.0).fmt(f)
+    }
+}
+
+impl std::str::FromStr for RouterId {
+    type Err = std::net::AddrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<Ipv4Addr>().map(Self::from)
+    }
+}
+
+impl Serialize for RouterId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RouterId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(|_| serde::de::Error::custom(format!("invalid router id '{}', expected a dotted quad", text)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RouteState {
+    Active(u32),
+    Unreachable,
+    /// Route externe : métrique annoncée par le routeur qui la redistribue et coût interne pour le joindre
+    External { metric_type: ExternalMetricType, metric: u32, cost: u32 },
+}
+
+impl RouteState {
+    /// Métrique totale ; le coût interne ne s'ajoute qu'aux routes externes de type 1
+    pub fn metric(&self) -> Option<u32> {
+        match self {
+            RouteState::Active(metric) => Some(*metric),
+            RouteState::External { metric_type: ExternalMetricType::Type1, metric, cost } => Some(metric.saturating_add(*cost)),
+            RouteState::External { metric_type: ExternalMetricType::Type2, metric, .. } => Some(*metric),
+            RouteState::Unreachable => None,
+        }
+    }
+
+    /// Clé de comparaison (la plus petite l'emporte) : route interne, puis externe E1,
+    /// puis E2 départagées par le coût interne, puis route inaccessible
+    pub fn preference(&self) -> (u8, u32, u32) {
+        match self {
+            RouteState::Active(metric) => (0, *metric, 0),
+            RouteState::External { metric_type: ExternalMetricType::Type1, .. } => (1, self.metric().unwrap_or(u32::MAX), 0),
+            RouteState::External { metric_type: ExternalMetricType::Type2, metric, cost } => (2, *metric, *cost),
+            RouteState::Unreachable => (3, u32::MAX, u32::MAX),
+        }
+    }
+}
+
+/// Type de métrique d'une route externe, comme les LSA externes OSPF
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum ExternalMetricType {
+    /// E1 : la métrique externe s'ajoute au coût interne jusqu'au routeur qui l'annonce
+    #[serde(rename = "type-1")]
+    Type1,
+    /// E2 : seule la métrique externe compte, le coût interne départage les ex æquo
+    #[default]
+    #[serde(rename = "type-2")]
+    Type2,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HelloMessage {
+    pub message_type: u8,
+    pub router_ip: String,
+    /// Identifiant de l'émetteur (absent chez les anciens routeurs)
+    #[serde(default)]
+    pub router_id: Option<RouterId>,
+    /// Horodatage d'émission (microsecondes, horloge de l'émetteur)
+    #[serde(default)]
+    pub timestamp_us: u64,
+    /// Horodatages des derniers HELLO reçus des voisins, renvoyés pour la mesure du RTT
+    #[serde(default)]
+    pub echoes: Vec<HelloEcho>,
+    /// Nom système de l'émetteur
+    #[serde(default)]
+    pub hostname: Option<String>,
+    /// Priorité de l'émetteur pour l'élection du routeur désigné (0 : jamais élu)
+    #[serde(default = "default_router_priority")]
+    pub priority: u8,
+    /// Protection des messages sur le segment d'émission (absente chez les anciens routeurs)
+    #[serde(default)]
+    pub auth: Option<AuthMode>,
+}
+
+/// Protection des messages d'un segment ; deux routeurs n'y deviennent voisins que s'ils
+/// utilisent la même
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthMode {
+    /// En clair, sans clé (laboratoire)
+    None,
+    /// Chiffrement AES-256-CBC
+    Aes,
+    /// En clair, suivi d'un HMAC-SHA256 : intègre et authentifié, mais lisible
+    Hmac,
+}
+
+impl AuthMode {
+    /// Vrai si le mode requiert une clé partagée
+    pub fn needs_key(self) -> bool {
+        self != AuthMode::None
+    }
+}
+
+impl std::fmt::Display for AuthMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AuthMode::None => "none",
+            AuthMode::Aes => "aes",
+            AuthMode::Hmac => "hmac",
+        })
+    }
+}
+
+/// Priorité d'un routeur n'annonçant pas la sienne
+pub fn default_router_priority() -> u8 {
+    1
+}
+
+/// Écho du dernier HELLO reçu d'un voisin
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HelloEcho {
+    pub router_ip: String,
+    pub timestamp_us: u64,
+    /// Temps écoulé entre la réception et le renvoi, à retrancher du RTT
+    pub held_us: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Neighbor {
+    pub neighbor_ip: String,
+    pub link_up: bool,
+    pub capacity: u32,
+    pub last_seen: u64,
+    /// Coût statique de l'interface vers ce voisin, annoncé dans les LSA
+    #[serde(default)]
+    pub cost: Option<u32>,
+    /// RTT lissé mesuré via les HELLO (microsecondes)
+    #[serde(default)]
+    pub rtt_us: Option<u32>,
+    /// Gigue du RTT (microsecondes)
+    #[serde(default)]
+    pub jitter_us: Option<u32>,
+    /// Nom système annoncé dans les HELLO du voisin
+    #[serde(default)]
+    pub hostname: Option<String>,
+    /// Interface locale sur laquelle le voisin est entendu
+    #[serde(default)]
+    pub interface: Option<String>,
+    /// Identifiant annoncé dans les HELLO du voisin
+    #[serde(default)]
+    pub router_id: Option<RouterId>,
+    /// Priorité annoncée dans les HELLO du voisin (élection du routeur désigné)
+    #[serde(default = "default_router_priority")]
+    pub priority: u8,
+}
+
+impl Neighbor {
+    /// Nom affichable : « R3 (10.1.0.3) », ou l'adresse seule si le nom est inconnu
+    pub fn display_name(&self) -> String {
+        display_name(&self.neighbor_ip, self.hostname.as_deref())
+    }
+
+    /// Identité du voisin dans la LSDB : son identifiant, ou son adresse s'il n'en annonce pas
+    pub fn identity(&self) -> String {
+        self.router_id.map_or_else(|| self.neighbor_ip.clone(), |id| id.to_string())
+    }
+}
+
+/// Origine d'une route de la table : LSA qui l'annonce, date d'installation et chemin calculé
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RouteOrigin {
+    pub lsa_type: crate::lsdb::LsaType,
+    pub originator: String,
+    pub seq_num: u32,
+    /// Horodatage (secondes Unix) depuis lequel la route pointe vers ce prochain saut
+    pub installed_at: u64,
+    /// Routeurs traversés, de ce routeur jusqu'à l'originateur
+    pub path: Vec<String>,
+    /// Étiquette annoncée avec la route
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<u32>,
+}
+
+/// TLV applicatif transporté par un LSA opaque : inondé et conservé dans la LSDB,
+/// mais jamais interprété par le protocole (la valeur est encodée en base64)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OpaqueTlv {
+    pub tlv_type: u16,
+    #[serde(with = "base64_bytes")]
+    pub value: Vec<u8>,
+}
+
+mod base64_bytes {
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        base64::engine::general_purpose::STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Nom affichable d'un routeur à partir de son adresse et de son nom éventuel
+pub fn display_name(ip: &str, hostname: Option<&str>) -> String {
+    match hostname {
+        Some(hostname) => format!("{} ({})", hostname, ip),
+        None => ip.to_string(),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LSAMessage {
+    pub message_type: u8,
+    pub router_ip: String,
+    pub last_hop: Option<String>,
+    pub originator: String,
+    /// Identifiant du routeur d'origine, conservé lors du relais (absent chez les anciens routeurs)
+    #[serde(default)]
+    pub router_id: Option<RouterId>,
+    pub seq_num: u32,
+    pub neighbor_count: usize,
+    pub neighbors: Vec<Neighbor>,
+    pub routing_table: HashMap<Prefix, RouteState>,
+    /// Routes externes redistribuées par l'originateur (préfixe -> métrique)
+    #[serde(default)]
+    pub external_routes: HashMap<Prefix, u32>,
+    /// Type de métrique des routes externes (E2 pour un préfixe absent)
+    #[serde(default)]
+    pub external_metric_types: HashMap<Prefix, ExternalMetricType>,
+    /// Étiquettes des routes annoncées, internes comme externes (aucune pour un préfixe
+    /// absent) ; un routeur qui réannonce une route conserve son étiquette
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub route_tags: HashMap<Prefix, u32>,
+    /// Conservé pour la compatibilité avec les anciens routeurs ; la suppression
+    /// des boucles repose désormais sur l'état d'inondation par voisin
+    #[serde(default)]
+    pub path: Vec<String>,
+    pub ttl: u8,
+    /// LSA résumé d'un routeur de bordure (route par défaut seule), limité à la zone stub
+    #[serde(default)]
+    pub stub_only: bool,
+    /// LSA différentiel : séquence de l'instance complète de même originateur à laquelle
+    /// s'appliquent les préfixes ajoutés ou modifiés et `withdrawn`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_seq: Option<u32>,
+    /// Préfixes (internes ou externes) retirés depuis l'instance `base_seq`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub withdrawn: Vec<Prefix>,
+    /// TLV opaques de l'originateur, relayés tels quels
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub opaque: Vec<OpaqueTlv>,
+    /// Instance retirée avant le retour du compteur de séquence à `INITIAL_SEQUENCE` (MaxAge
+    /// d'OSPF) : ses destinataires oublient l'originateur et acceptent sa prochaine instance
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub flush: bool,
+    /// Somme de contrôle Fletcher-16 du contenu, recalculée à chaque émission ; un LSA reçu
+    /// sans elle est rejeté
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<u16>,
+    /// Partie d'un LSA découpé pour la MTU du lien (voir `lsa_part`), absente pour un LSA
+    /// entier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part: Option<LsaPart>,
+}
+
+/// Place d'une partie dans un LSA découpé : ses parties partagent originateur et séquence
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct LsaPart {
+    pub index: u16,
+    pub count: u16,
+}
+
+impl LSAMessage {
+    /// Fletcher-16 sur la forme JSON canonique du message (clés triées), hors `checksum`
+    pub fn compute_checksum(&self) -> u16 {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("checksum");
+        }
+        fletcher16(&serde_json::to_vec(&value).unwrap_or_default())
+    }
+
+    /// Calcule la somme de contrôle juste avant l'envoi
+    pub fn seal(&mut self) {
+        self.checksum = Some(self.compute_checksum());
+    }
+
+    /// Faux si la somme de contrôle manque ou ne correspond pas au contenu : tout LSA reçu
+    /// doit avoir été scellé par son émetteur
+    pub fn checksum_ok(&self) -> bool {
+        self.checksum.is_some_and(|checksum| checksum == self.compute_checksum())
+    }
+}
+
+fn fletcher16(data: &[u8]) -> u16 {
+    let (mut sum1, mut sum2) = (0u16, 0u16);
+    for byte in data {
+        sum1 = (sum1 + u16::from(*byte)) % 255;
+        sum2 = (sum2 + sum1) % 255;
+    }
+    (sum2 << 8) | sum1
+}
+
+/// Défi envoyé à un routeur inconnu avant de l'accepter comme voisin (message_type 5)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthChallenge {
+    pub message_type: u8,
+    pub router_ip: String,
+    pub nonce: String,
+}
+
+/// Réponse au défi : HMAC-SHA256 du nonce et de l'adresse du répondant (message_type 6)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuthResponse {
+    pub message_type: u8,
+    pub router_ip: String,
+    pub nonce: String,
+    pub signature: String,
+}
+
+/// Commande envoyée par le CLI (message_type 3)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ControlMessage {
+    pub message_type: u8,
+    pub command: String,
+    /// Utilisateur déclaré dans `[control]` ; absent pour une commande anonyme
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Horodatage Unix de la commande, couvert par la signature (anti-rejeu)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    /// HMAC-SHA256 (base64) de l'utilisateur, de l'horodatage et de la commande
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Identifiant choisi par le CLI et renvoyé avec la réponse : une réponse tardive à une
+    /// commande précédente n'est pas prise pour celle de la suivante
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<u64>,
+}
+
+/// Réponse à une commande de contrôle portant un `request_id` ; sans identifiant, la
+/// réponse est le texte seul (anciens CLI)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ControlResponse {
+    pub request_id: u64,
+    pub response: String,
+}
+
+/// Identité d'une instance de LSA
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct LsaIdentity {
+    pub originator: String,
+    pub seq_num: u32,
+}
+
+/// Acquittement des LSA reçus d'un voisin (message_type 4)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LsaAckMessage {
+    pub message_type: u8,
+    pub router_ip: String,
+    pub acks: Vec<LsaIdentity>,
+}
+
+/// Question « quelle route vers ce préfixe ? » posée à un routeur par un hôte, un outil ou
+/// un autre routeur (message_type 10)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RouteRequest {
+    pub message_type: u8,
+    /// Identifiant aléatoire, repris par la réponse
+    pub id: u64,
+    /// Préfixe ou adresse (/32) recherché
+    pub destination: Prefix,
+}
+
+/// Réponse à une `RouteRequest`, tirée de la table de routage du routeur interrogé
+/// (message_type 11)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RouteResponse {
+    pub message_type: u8,
+    pub id: u64,
+    /// Identifiant du routeur qui répond
+    pub router_id: String,
+    pub destination: Prefix,
+    /// Préfixe le plus spécifique couvrant la destination, absent sans route
+    pub prefix: Option<Prefix>,
+    /// Prochain saut, absent pour un réseau directement connecté
+    pub next_hop: Option<String>,
+    pub metric: Option<u32>,
+    /// Routeurs traversés, du routeur interrogé jusqu'à celui qui annonce le préfixe
+    pub path: Vec<String>,
+    pub reachable: bool,
+}
+
+/// Écho entre routeurs (message_type 8) : la requête est relayée de proche en proche par
+/// les routes installées jusqu'au routeur visé, qui renvoie la réponse de la même façon
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EchoMessage {
+    pub message_type: u8,
+    /// Identifiant aléatoire, qui associe la réponse à la requête
+    pub id: u64,
+    /// Identifiant du routeur qui a lancé la sonde
+    pub source: String,
+    /// Identifiant (ou adresse) du routeur visé
+    pub target: String,
+    pub reply: bool,
+    /// Routeurs traversés par la requête, de la source au routeur visé
+    pub path: Vec<String>,
+    pub ttl: u8,
+    /// HMAC-SHA256 des champs précédents avec la clé partagée, recalculé à chaque saut
+    pub signature: String,
+    /// Bourrage des sondes de MTU, qui fixe la taille du datagramme ; absent des réponses
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub padding: String,
+}
+
+impl EchoMessage {
+    fn mac(&self, key: &[u8]) -> crate::error::Result<HmacSha256> {
+        let mut mac = HmacSha256::new_from_slice(key)
+            .map_err(|e| crate::error::AppError::CryptoError(format!("Invalid HMAC key: {}", e)))?;
+        mac.update(format!("{}|{}|{}|{}|{}|{}", self.id, self.source, self.target, self.reply, self.path.join(","), self.ttl).as_bytes());
+        Ok(mac)
+    }
+
+    pub fn sign(&mut self, key: &[u8]) -> crate::error::Result<()> {
+        let signature = self.mac(key)?.finalize().into_bytes();
+        self.signature = base64::engine::general_purpose::STANDARD.encode(signature);
+        Ok(())
+    }
+
+    /// Vérifie la signature en temps constant
+    pub fn verify(&self, key: &[u8]) -> bool {
+        let Ok(signature) = base64::engine::general_purpose::STANDARD.decode(&self.signature) else {
+            return false;
+        };
+        self.mac(key).is_ok_and(|mac| mac.verify_slice(&signature).is_ok())
+    }
+}
+
+/// État courant d'une interface configurée, modifiable à chaud (`set-cost`, `set-link`)
+#[derive(Debug, Clone)]
+pub struct InterfaceState {
+    pub name: String,
+    pub capacity_mbps: u32,
+    pub link_active: bool,
+    /// Coût imposé, prioritaire sur le coût dérivé de la capacité
+    pub cost: Option<u32>,
+    pub ip_address: Option<String>,
+    pub network: Option<String>,
+    pub last_state_change: u64,
+}
+
+impl InterfaceState {
+    pub fn new(name: String, capacity_mbps: u32, link_active: bool) -> Self {
+        Self {
+            name,
+            capacity_mbps,
+            link_active,
+            cost: None,
+            ip_address: None,
+            network: None,
+            last_state_change: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_else(|_| std::time::Duration::from_secs(0))
+                .as_secs(),
+        }
+    }
+
+    /// Coût OSPF annoncé : coût imposé si le lien est actif, sinon dérivé de la capacité
+    pub fn effective_cost(&self) -> u32 {
+        match self.cost {
+            Some(cost) if self.link_active => cost,
+            _ => crate::dijkstra::calculate_ospf_cost(self.capacity_mbps, self.link_active),
+        }
+    }
+    
+    pub fn set_link_state(&mut self, active: bool) {
+        if self.link_active != active {
+            self.link_active = active;
+            self.last_state_change = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_else(|_| std::time::Duration::from_secs(0))
+                .as_secs();
+        }
+    }
+    
+    pub fn is_link_up(&self) -> bool {
+        self.link_active
+    }
+    
+    /// Obtient le coût OSPF basé sur la capacité
+    pub fn get_ospf_cost(&self) -> u32 {
+        if !self.link_active {
+            return u32::MAX; // Coût infini pour les liens inactifs
+        }
+        
+        if self.capacity_mbps == 0 {
+            return u32::MAX;
+        }
+        
+        let reference_bandwidth = 100_000_000; // 100 Mbps en bps
+        let bandwidth_bps = self.capacity_mbps * 1_000_000;
+        let cost = reference_bandwidth / bandwidth_bps;
+        cost.max(1) // Le coût minimum est 1
+    }
+}
@@ -0,0 +1,135 @@
+use pospf_core::dijkstra::{NetworkTopology, PathSelection};
+use pospf_core::events::RouterEvent;
+use pospf_core::fragment;
+use pospf_core::lsa_part;
+use pospf_core::lsdb::Lsdb;
+use pospf_core::neighbor::{self, LocalLink};
+use pospf_core::types::{LSAMessage, RouteState};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn lsa(originator: &str, seq_num: u32) -> LSAMessage {
+    serde_json::from_value(json!({
+        "message_type": 2,
+        "router_ip": originator,
+        "last_hop": null,
+        "originator": originator,
+        "seq_num": seq_num,
+        "neighbor_count": 0,
+        "neighbors": [],
+        "routing_table": { "192.168.1.1/24": { "Active": 0 } },
+        "path": [originator],
+        "ttl": 15
+    }))
+    .unwrap()
+}
+
+// Calcul SPF et LSDB sans socket ni runtime : a joint c par b (1 Gb/s) plutôt que par
+// le lien direct à 10 Mb/s, et la LSDB n'accepte que les instances plus récentes
+#[test]
+fn protocol_logic_runs_without_a_network() {
+    let mut topology = NetworkTopology::new();
+    for router in ["a", "b", "c"] {
+        topology.add_router(router.to_string(), Vec::new());
+    }
+    topology.add_link("a".to_string(), "b".to_string(), 1000, true);
+    topology.add_link("b".to_string(), "c".to_string(), 1000, true);
+    topology.add_link("a".to_string(), "c".to_string(), 10, true);
+    let paths = topology.calculate_shortest_paths("a", PathSelection::MinCost);
+    assert_eq!(paths["c"].next_hop, "b");
+    assert_eq!(paths["c"].total_cost, 2);
+    assert_eq!(paths["c"].path, vec!["a", "b", "c"]);
+
+    let mut lsdb = Lsdb::new();
+    assert!(lsdb.install(&lsa("10.0.12.1", 7)));
+    assert!(!lsdb.install(&lsa("10.0.12.1", 6)));
+    assert!(lsdb.install(&lsa("10.0.12.1", 8)));
+    assert_eq!(lsdb.len(), 1);
+}
+
+// Automate des voisins sans socket : un HELLO sur une interface active crée le voisin
+// actif, une interface coupée le rend inactif, son rétablissement le réactive tant qu'il
+// a été entendu récemment
+#[test]
+fn neighbor_state_follows_hellos_and_links() {
+    let mut neighbors = HashMap::new();
+    let eth0 = LocalLink { interface: Some("eth0".to_string()), capacity: 1000, active: true, cost: None };
+    let heard = neighbor::hear(&mut neighbors, "10.0.12.2", &eth0, 100);
+    assert_eq!(heard.event, Some(RouterEvent::NeighborUp { neighbor: "10.0.12.2".to_string(), interface: Some("eth0".to_string()) }));
+    assert!(!heard.flapped);
+    assert_eq!(neighbor::hear(&mut neighbors, "10.0.12.2", &eth0, 110), Default::default());
+    assert_eq!(neighbors["10.0.12.2"].last_seen, 110);
+
+    let interface_of = |_: &str, n: &pospf_core::types::Neighbor| n.interface.clone();
+    let down = neighbor::apply_link_state(&mut neighbors, "eth0", false, Some(50), 115, 40, interface_of);
+    assert_eq!(neighbor::lost(&down), vec!["10.0.12.2".to_string()]);
+    assert_eq!(neighbors["10.0.12.2"].cost, Some(50));
+    assert!(neighbor::apply_link_state(&mut neighbors, "eth1", true, None, 115, 40, interface_of).is_empty());
+    let up = neighbor::apply_link_state(&mut neighbors, "eth0", true, None, 120, 40, interface_of);
+    assert!(matches!(up.as_slice(), [RouterEvent::NeighborUp { .. }]));
+
+    neighbor::record_rtt_sample(neighbors.get_mut("10.0.12.2").unwrap(), 800);
+    neighbor::record_rtt_sample(neighbors.get_mut("10.0.12.2").unwrap(), 1600);
+    assert_eq!(neighbors["10.0.12.2"].rtt_us, Some(900));
+    assert_eq!(neighbors["10.0.12.2"].jitter_us, Some(50));
+}
+
+// Un voisin muet au-delà de son intervalle de mort est signalé perdu par un
+// `NeighborDown`, une seule fois : l'appelant retire alors les routes apprises par lui
+#[test]
+fn silent_neighbors_expire_with_an_event() {
+    let mut neighbors = HashMap::new();
+    let link = LocalLink { interface: Some("eth0".to_string()), capacity: 100, active: true, cost: None };
+    neighbor::hear(&mut neighbors, "10.0.12.2", &link, 100);
+    neighbor::hear(&mut neighbors, "10.0.13.3", &link, 130);
+    let dead_interval = |_: &str, _: &pospf_core::types::Neighbor| 40;
+
+    assert!(neighbor::expire(&mut neighbors, 140, dead_interval).is_empty());
+    let expired = neighbor::expire(&mut neighbors, 141, dead_interval);
+    assert_eq!(expired, vec![RouterEvent::NeighborDown { neighbor: "10.0.12.2".to_string(), reason: "dead interval expired".to_string() }]);
+    assert!(!neighbors["10.0.12.2"].link_up);
+    assert!(neighbors["10.0.13.3"].link_up);
+    assert!(neighbor::expire(&mut neighbors, 150, dead_interval).is_empty());
+
+    // Entendu à nouveau, il redevient actif et la bascule est signalée
+    let heard = neighbor::hear(&mut neighbors, "10.0.12.2", &link, 150);
+    assert!(heard.flapped);
+    assert!(matches!(heard.event, Some(RouterEvent::NeighborUp { .. })));
+}
+
+// Un LSA trop grand pour la MTU se découpe en LSA plus petits, scellés un à un, qui se
+// partagent ses préfixes ; réunis dans n'importe quel ordre, ils redonnent le LSA entier
+#[test]
+fn oversized_lsas_split_into_parts_and_merge_back() {
+    let mut whole = lsa("10.0.12.1", 9);
+    whole.routing_table = (0..200u32)
+        .map(|i| (format!("172.16.{}.0/24", i).parse().unwrap(), RouteState::Active(i % 7 + 1)))
+        .collect();
+    whole.external_routes.insert("198.51.100.0/24".parse().unwrap(), 20);
+    whole.route_tags.insert("172.16.3.0/24".parse().unwrap(), 42);
+    whole.seal();
+    let max_len = fragment::payload_for_mtu(1280);
+    assert!(lsa_part::split(&lsa("10.0.12.1", 9), max_len).is_none());
+
+    let parts = lsa_part::split(&whole, max_len).unwrap();
+    assert!(parts.len() > 2);
+    for part in &parts {
+        assert!(serde_json::to_vec(part).unwrap().len() <= max_len);
+        assert!(part.checksum_ok());
+        assert_eq!((part.originator.as_str(), part.seq_num), ("10.0.12.1", 9));
+    }
+
+    let source = "10.0.12.1:5000".parse().unwrap();
+    let mut assembler = lsa_part::PartAssembler::new();
+    let (last, others) = parts.split_first().unwrap();
+    for part in others.iter().rev() {
+        assert!(assembler.insert(source, part.clone()).is_none());
+    }
+    let merged = assembler.insert(source, last.clone()).unwrap();
+    assert!(assembler.is_empty());
+    assert_eq!(merged.part, None);
+    assert!(merged.checksum_ok());
+    assert_eq!(merged.routing_table, whole.routing_table);
+    assert_eq!(merged.external_routes, whole.external_routes);
+    assert_eq!(merged.route_tags, whole.route_tags);
+}
@@ -0,0 +1,194 @@
+use pospf_core::dijkstra::{calculate_ospf_cost, NetworkTopology, PathSelection};
+use pospf_core::k_paths::{DisjointPaths, Disjointness};
+use pospf_core::spf_engine::SpfEngineKind;
+use proptest::prelude::*;
+use proptest::sample::Index;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Lien généré : extrémités, capacité et état
+type Edge = (usize, usize, u32, bool);
+
+fn router(i: usize) -> String {
+    format!("10.0.0.{}", i + 1)
+}
+
+/// Graphe connexe de 2 à 16 routeurs : un arbre couvrant plus quelques liens
+/// supplémentaires, chaque paire n'étant reliée qu'une fois ; environ un lien sur six est
+/// inactif, ce qui peut rendre une partie des routeurs injoignable
+fn connected_graph() -> impl Strategy<Value = (usize, Vec<Edge>)> {
+    (2usize..=16).prop_flat_map(|n| {
+        let link = || (1u32..=10_000, prop::bool::weighted(0.85));
+        let tree = prop::collection::vec((any::<Index>(), link()), n - 1);
+        let extra = prop::collection::vec((any::<Index>(), any::<Index>(), link()), 0..=2 * n);
+        (Just(n), tree, extra).prop_map(|(n, tree, extra)| {
+            let mut pairs = HashSet::new();
+            let mut edges = Vec::new();
+            // Le routeur i + 1 se rattache à un routeur déjà placé
+            for (i, (parent, (capacity, active))) in tree.into_iter().enumerate() {
+                let parent = parent.index(i + 1);
+                pairs.insert((parent.min(i + 1), parent.max(i + 1)));
+                edges.push((parent, i + 1, capacity, active));
+            }
+            for (a, b, (capacity, active)) in extra {
+                let (a, b) = (a.index(n), b.index(n));
+                if a != b && pairs.insert((a.min(b), a.max(b))) {
+                    edges.push((a, b, capacity, active));
+                }
+            }
+            (n, edges)
+        })
+    })
+}
+
+fn topology(n: usize, edges: &[Edge]) -> NetworkTopology {
+    let mut topology = NetworkTopology::new();
+    for i in 0..n {
+        topology.add_router(router(i), Vec::new());
+    }
+    for &(a, b, capacity, active) in edges {
+        topology.add_link(router(a), router(b), capacity, active);
+    }
+    topology
+}
+
+/// Routeurs joignables depuis `source` par des liens actifs
+fn reachable(topology: &NetworkTopology, source: &str) -> HashSet<String> {
+    let mut seen = HashSet::from([source.to_string()]);
+    let mut queue = VecDeque::from([source.to_string()]);
+    while let Some(node) = queue.pop_front() {
+        for link in topology.get_active_neighbors(&node) {
+            if seen.insert(link.to.clone()) {
+                queue.push_back(link.to.clone());
+            }
+        }
+    }
+    seen
+}
+
+proptest! {
+    #[test]
+    fn shortest_paths_respect_the_topology((n, edges) in connected_graph(), source in any::<Index>()) {
+        let topology = topology(n, &edges);
+        let source = router(source.index(n));
+        let routes = topology.calculate_shortest_paths(&source, PathSelection::MinCost);
+
+        // Exactement les routeurs joignables par des liens actifs
+        let mut expected = reachable(&topology, &source);
+        expected.remove(&source);
+        prop_assert_eq!(routes.keys().cloned().collect::<HashSet<_>>(), expected);
+
+        let costs: HashMap<&str, u32> = routes.iter().map(|(dest, route)| (dest.as_str(), route.total_cost)).collect();
+        for (dest, route) in &routes {
+            prop_assert_eq!(route.path.first(), Some(&source));
+            prop_assert_eq!(route.path.last(), Some(dest));
+            // Le prochain saut est un voisin direct, joint par un lien actif
+            prop_assert!(topology.find_link(&source, &route.next_hop).is_some_and(|link| link.is_active));
+            prop_assert_eq!(&route.path[1], &route.next_hop);
+
+            let mut cost = 0;
+            for hop in route.path.windows(2) {
+                let link = topology.find_link(&hop[0], &hop[1]);
+                // Aucun chemin n'emprunte un lien inactif
+                prop_assert!(link.is_some_and(|link| link.is_active), "{:?} uses {} -> {}", route.path, hop[0], hop[1]);
+                let link = link.unwrap();
+                prop_assert!(route.bottleneck_capacity <= link.capacity_mbps);
+                cost += link.cost;
+            }
+            prop_assert_eq!(route.total_cost, cost);
+            prop_assert_eq!(route.hop_count as usize, route.path.len() - 1);
+        }
+
+        // Inégalité triangulaire : aucun lien actif ne raccourcit un chemin calculé
+        for link in topology.links.iter().filter(|link| link.is_active) {
+            let from = if link.from == source { Some(0) } else { costs.get(link.from.as_str()).copied() };
+            if let (Some(from), Some(to)) = (from, costs.get(link.to.as_str())) {
+                prop_assert!(*to <= from + link.cost, "{} -> {}: {} > {} + {}", link.from, link.to, to, from, link.cost);
+            }
+        }
+    }
+
+    #[test]
+    fn widest_path_bottleneck_is_never_beaten((n, edges) in connected_graph(), source in any::<Index>()) {
+        let topology = topology(n, &edges);
+        let source = router(source.index(n));
+        let widest = topology.calculate_shortest_paths(&source, PathSelection::MaxBandwidth);
+        let cheapest = topology.calculate_shortest_paths(&source, PathSelection::MinCost);
+        prop_assert_eq!(widest.len(), cheapest.len());
+        for (dest, route) in &widest {
+            prop_assert!(route.bottleneck_capacity >= cheapest[dest].bottleneck_capacity);
+            prop_assert!(route.total_cost >= cheapest[dest].total_cost);
+        }
+    }
+
+    #[test]
+    fn petgraph_engine_agrees_with_the_native_one((n, edges) in connected_graph(), source in any::<Index>(), latency in 1u32..=5_000) {
+        let mut topology = topology(n, &edges);
+        // Une latence mesurée sur un lien sur deux, pour que "min-latency" ne soit pas un simple compte de sauts
+        for (a, b, ..) in edges.iter().step_by(2) {
+            topology.set_link_latency(&router(*a), &router(*b), latency * (*b as u32 + 1));
+        }
+        let source = router(source.index(n));
+        for policy in [PathSelection::MinCost, PathSelection::MaxBandwidth, PathSelection::MinLatency] {
+            let native = SpfEngineKind::Native.engine().shortest_paths(&topology, &source, policy);
+            let petgraph = SpfEngineKind::Petgraph.engine().shortest_paths(&topology, &source, policy);
+            prop_assert_eq!(native.keys().collect::<HashSet<_>>(), petgraph.keys().collect::<HashSet<_>>());
+            for (dest, route) in &petgraph {
+                let expected = &native[dest];
+                // Mêmes critères départagés par la politique ; à égalité, le chemin peut différer
+                let key = |route: &pospf_core::dijkstra::RouteInfo| match policy {
+                    PathSelection::MinCost => (route.total_cost, route.hop_count, route.bottleneck_capacity),
+                    PathSelection::MaxBandwidth => (route.bottleneck_capacity, route.total_cost, route.hop_count),
+                    PathSelection::MinLatency => (route.total_latency_us, route.total_cost, route.hop_count),
+                };
+                prop_assert_eq!(key(route), key(expected), "{:?} {} -> {}", policy, source, dest);
+                prop_assert_eq!(route.path.first(), Some(&source));
+                prop_assert_eq!(route.path.last(), Some(dest));
+                prop_assert_eq!(&route.path[1], &route.next_hop);
+            }
+        }
+    }
+
+    #[test]
+    fn k_shortest_paths_are_ordered_distinct_and_loopless((n, edges) in connected_graph(), source in any::<Index>(), target in any::<Index>(), k in 1usize..=6) {
+        let topology = topology(n, &edges);
+        let (source, target) = (router(source.index(n)), router(target.index(n)));
+        let best = topology.calculate_shortest_paths(&source, PathSelection::MinCost).remove(&target);
+        let paths = topology.k_shortest_paths(&source, &target, k, PathSelection::MinCost);
+        prop_assert_eq!(paths.first().map(|route| route.key()), best.as_ref().map(|route| route.key()));
+        prop_assert!(paths.len() <= k);
+        prop_assert!(paths.windows(2).all(|pair| pair[0].key() <= pair[1].key()));
+        prop_assert_eq!(paths.iter().map(|route| &route.path).collect::<HashSet<_>>().len(), paths.len());
+        for route in &paths {
+            prop_assert_eq!(route.path.first(), Some(&source));
+            prop_assert_eq!(route.path.last(), Some(&target));
+            prop_assert_eq!(route.path.iter().collect::<HashSet<_>>().len(), route.path.len());
+            prop_assert!(route.path.windows(2).all(|hop| topology.get_active_neighbors(&hop[0]).iter().any(|link| link.to == hop[1])));
+        }
+
+        let disjoint = topology.disjoint_paths(&source, &target, PathSelection::MinCost);
+        prop_assert_eq!(disjoint.as_ref().map(|paths| paths.primary.key()), best.as_ref().map(|route| route.key()));
+        if let Some(DisjointPaths { primary, secondary: Some((backup, separation)) }) = disjoint {
+            let links = |route: &pospf_core::dijkstra::RouteInfo| route.path.windows(2)
+                .map(|hop| (hop[0].clone().min(hop[1].clone()), hop[0].clone().max(hop[1].clone())))
+                .collect::<HashSet<_>>();
+            let shared_links = links(&primary).intersection(&links(&backup)).count();
+            let transit = |route: &pospf_core::dijkstra::RouteInfo| route.path[1..route.path.len() - 1].to_vec();
+            let shared_routers = transit(&primary).iter().filter(|router| transit(&backup).contains(router)).count();
+            match separation {
+                Disjointness::Nodes => prop_assert_eq!((shared_links, shared_routers), (0, 0)),
+                Disjointness::Links => prop_assert_eq!(shared_links, 0),
+                Disjointness::Partial(shared) => prop_assert_eq!(shared, shared_links),
+            }
+        }
+    }
+
+    #[test]
+    fn cost_decreases_with_capacity(low in 1u32..=1_000_000, extra in 0u32..=1_000_000) {
+        let high = low.saturating_add(extra);
+        let cost = calculate_ospf_cost(low, true);
+        prop_assert!(cost >= 1);
+        prop_assert!(calculate_ospf_cost(high, true) <= cost);
+        prop_assert_eq!(calculate_ospf_cost(low, false), u32::MAX);
+        prop_assert_eq!(calculate_ospf_cost(0, true), u32::MAX);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::neighbor_stats::NeighborStats;
+use crate::read_config::{AdaptiveHelloConfig, RouterConfig};
+use crate::AppState;
+
+/// Intervalle des HELLO souhaité pour un voisin : rapide s'il a basculé depuis moins de
+/// `flap_window_sec`, sinon l'intervalle configuré doublé à chaque période de stabilité
+/// `stable_after_sec`, dans la limite de `max_interval_sec`
+pub fn neighbor_interval(adaptive: &AdaptiveHelloConfig, base: u64, stats: Option<&NeighborStats>, now: u64) -> u64 {
+    if !adaptive.enabled {
+        return base;
+    }
+    let Some(last_change) = stats.and_then(|stats| stats.state_changes.back()) else {
+        return base;
+    };
+    let stable_for = now.saturating_sub(last_change.at);
+    // La première montée d'un voisin n'est pas une bascule
+    let flapped = stats.is_some_and(|stats| stats.state_change_count > 1);
+    if flapped && stable_for < adaptive.flap_window_sec {
+        return adaptive.fast_interval_sec.min(base);
+    }
+    let doublings = (stable_for / adaptive.stable_after_sec.max(1)).min(16) as u32;
+    base.saturating_mul(1 << doublings).min(adaptive.max_interval_sec.max(base))
+}
+
+/// Intervalle des HELLO émis sur une interface : le plus court souhaité par ses voisins,
+/// l'intervalle configuré s'il n'en a aucun
+pub async fn interface_interval(state: &AppState, config: &RouterConfig, interface: Option<&str>) -> u64 {
+    let base = config.hello_interval_for(interface);
+    if !config.adaptive_hello.enabled {
+        return base;
+    }
+    let neighbors: Vec<String> = state.neighbors.read().await.values()
+        .filter(|neighbor| interface.is_some() && neighbor.interface.as_deref() == interface)
+        .map(|neighbor| neighbor.neighbor_ip.clone())
+        .collect();
+    let now = crate::clock::unix_secs();
+    let stats = state.neighbor_stats.lock().await;
+    neighbors.iter()
+        .map(|neighbor| neighbor_interval(&config.adaptive_hello, base, stats.get(neighbor), now))
+        .min()
+        .unwrap_or(base)
+}
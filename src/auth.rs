@@ -0,0 +1,145 @@
+use crate::error::{AppError, Result};
+use crate::types::{AuthChallenge, AuthResponse, HelloMessage};
+use crate::AppState;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use tracing::{debug, info, warn};
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use crate::transport::Socket;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Durée de validité d'un défi ; un nouveau défi est émis au-delà
+const CHALLENGE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Nombre maximal de défis en cours : des HELLO aux adresses inventées n'en créent pas plus
+const MAX_PENDING: usize = 256;
+
+/// Défis en cours, par adresse de routeur candidat, avec leur date d'émission
+#[derive(Debug, Default)]
+pub struct NeighborAuth {
+    pending: HashMap<String, (String, Instant)>,
+}
+
+impl NeighborAuth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Crée un nonce pour le routeur, sauf si un défi récent est déjà en attente ; les défis
+    /// expirés sont oubliés, et le plus ancien cède sa place au-delà de `MAX_PENDING`
+    fn issue(&mut self, router_ip: &str) -> Option<String> {
+        self.pending.retain(|_, (_, issued)| issued.elapsed() < CHALLENGE_TIMEOUT);
+        if self.pending.contains_key(router_ip) {
+            return None;
+        }
+        if self.pending.len() >= MAX_PENDING {
+            let oldest = self.pending.iter().min_by_key(|(_, (_, issued))| *issued).map(|(ip, _)| ip.clone());
+            if let Some(oldest) = oldest {
+                self.pending.remove(&oldest);
+            }
+        }
+        let mut bytes = [0u8; 16];
+        OsRng.fill_bytes(&mut bytes);
+        let nonce = base64::engine::general_purpose::STANDARD.encode(bytes);
+        self.pending.insert(router_ip.to_string(), (nonce.clone(), Instant::now()));
+        Some(nonce)
+    }
+
+    /// Consomme le défi du routeur s'il correspond au nonce et n'a pas expiré
+    fn take(&mut self, router_ip: &str, nonce: &str) -> bool {
+        match self.pending.get(router_ip) {
+            Some((expected, issued)) if expected == nonce && issued.elapsed() < CHALLENGE_TIMEOUT => {
+                self.pending.remove(router_ip);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+fn mac(key: &[u8], nonce: &str, router_ip: &str) -> Result<HmacSha256> {
+    let mut mac = HmacSha256::new_from_slice(key)
+        .map_err(|e| AppError::CryptoError(format!("Invalid HMAC key: {}", e)))?;
+    mac.update(nonce.as_bytes());
+    mac.update(router_ip.as_bytes());
+    Ok(mac)
+}
+
+/// Signe le nonce reçu avec la clé partagée
+pub fn sign(key: &[u8], nonce: &str, router_ip: &str) -> Result<String> {
+    let signature = mac(key, nonce, router_ip)?.finalize().into_bytes();
+    Ok(base64::engine::general_purpose::STANDARD.encode(signature))
+}
+
+/// Vérifie la signature d'une réponse en temps constant
+pub fn verify(key: &[u8], nonce: &str, router_ip: &str, signature: &str) -> bool {
+    let Ok(signature) = base64::engine::general_purpose::STANDARD.decode(signature) else {
+        return false;
+    };
+    mac(key, nonce, router_ip).is_ok_and(|mac| mac.verify_slice(&signature).is_ok())
+}
+
+/// Un HELLO d'un voisin adjacent rafraîchit l'adjacence ; celui d'un routeur
+/// inconnu (ou d'un voisin tombé) déclenche un défi
+pub async fn handle_hello(state: &Arc<AppState>, socket: &Socket, hello: &HelloMessage, local_ip: &str) -> Result<()> {
+    let adjacent = state.neighbors.read().await
+        .get(&hello.router_ip)
+        .is_some_and(|neighbor| neighbor.link_up);
+    if adjacent || !state.config().await.neighbor_auth {
+        crate::neighbor::update_neighbor(state, &hello.router_ip, local_ip).await;
+        return Ok(());
+    }
+
+    let Some(nonce) = state.neighbor_auth.lock().await.issue(&hello.router_ip) else {
+        return Ok(());
+    };
+    debug!("[AUTH] Challenging {} before adjacency", hello.router_ip);
+    let challenge = AuthChallenge {
+        message_type: 5,
+        router_ip: local_ip.to_string(),
+        nonce,
+    };
+    let addr = crate::discovery::neighbor_endpoint(state, &hello.router_ip).await?;
+    crate::net_utils::send_message(socket, &addr, &challenge, &state.key_for(local_ip).await, "[AUTH]").await
+}
+
+/// Répond au défi d'un voisin en signant son nonce
+pub async fn handle_challenge(state: &Arc<AppState>, socket: &Socket, challenge: &AuthChallenge, local_ip: &str) -> Result<()> {
+    let key = state.key_for(local_ip).await;
+    let response = AuthResponse {
+        message_type: 6,
+        router_ip: local_ip.to_string(),
+        nonce: challenge.nonce.clone(),
+        signature: sign(&key.key, &challenge.nonce, local_ip)?,
+    };
+    let addr = crate::discovery::neighbor_endpoint(state, &challenge.router_ip).await?;
+    crate::net_utils::send_message(socket, &addr, &response, &key, "[AUTH]").await
+}
+
+/// Accepte le voisin si la réponse correspond à un défi en cours et que la signature est valide
+pub async fn handle_response(state: &Arc<AppState>, response: &AuthResponse, local_ip: &str) {
+    let key = state.key_for(local_ip).await;
+    let expected = state.neighbor_auth.lock().await.take(&response.router_ip, &response.nonce);
+    if expected && verify(&key.key, &response.nonce, &response.router_ip, &response.signature) {
+        info!("[AUTH] Neighbor {} authenticated", response.router_ip);
+        state.metrics.neighbors_authenticated.fetch_add(1, Ordering::Relaxed);
+        crate::neighbor::update_neighbor(state, &response.router_ip, local_ip).await;
+    } else {
+        warn!("[AUTH] Rejected neighbor {}: invalid or unexpected challenge response", response.router_ip);
+        state.metrics.neighbors_rejected.fetch_add(1, Ordering::Relaxed);
+    }
+}
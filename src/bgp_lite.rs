@@ -0,0 +1,315 @@
+use crate::error::{AppError, Result};
+use crate::read_config::BgpPeerConfig;
+use crate::types::{Prefix, RouteState};
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tracing::{debug, info, warn};
+
+/// Longueur maximale d'un message (une ligne JSON)
+const MAX_MESSAGE_LEN: u64 = 256 * 1024;
+/// Chemin d'AS au-delà duquel une annonce est écartée
+const MAX_AS_PATH: usize = 64;
+
+/// Message d'une session de la passerelle : une ligne JSON par message, à la manière
+/// des messages OPEN, UPDATE, KEEPALIVE et NOTIFICATION de BGP
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BgpMessage {
+    Open { asn: u32, router_id: String, hold_time_sec: u64 },
+    Update {
+        #[serde(default)]
+        announce: Vec<BgpRoute>,
+        #[serde(default)]
+        withdraw: Vec<Prefix>,
+    },
+    Keepalive,
+    Notification { reason: String },
+}
+
+/// Préfixe annoncé et systèmes autonomes traversés, le plus proche en tête
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BgpRoute {
+    pub prefix: Prefix,
+    pub as_path: Vec<u32>,
+}
+
+/// Routes acceptées de chaque pair externe
+#[derive(Debug, Default)]
+pub struct BgpRib {
+    established: BTreeSet<String>,
+    routes: BTreeMap<String, HashMap<Prefix, Vec<u32>>>,
+}
+
+impl BgpRib {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enregistre une session établie ; faux si le pair en a déjà une
+    pub fn open_session(&mut self, peer: &str) -> bool {
+        self.established.insert(peer.to_string())
+    }
+
+    /// Ferme la session d'un pair et oublie ses routes
+    pub fn close_session(&mut self, peer: &str) {
+        self.established.remove(peer);
+        self.routes.remove(peer);
+    }
+
+    pub fn sessions(&self) -> usize {
+        self.established.len()
+    }
+
+    /// Applique une mise à jour d'un pair. Une annonce dont le chemin contient notre AS
+    /// (boucle), ne commence pas par l'AS du pair ou est trop longue est écartée et retire
+    /// la route précédente du préfixe ; renvoie le nombre d'annonces écartées.
+    pub fn update(&mut self, peer: &str, remote_as: u32, local_as: u32, announce: Vec<BgpRoute>, withdraw: &[Prefix]) -> usize {
+        let routes = self.routes.entry(peer.to_string()).or_default();
+        for prefix in withdraw {
+            routes.remove(&Prefix::new(prefix.network()));
+        }
+        let mut rejected = 0;
+        for route in announce {
+            let prefix = Prefix::new(route.prefix.network());
+            let valid = route.as_path.first() == Some(&remote_as)
+                && route.as_path.len() <= MAX_AS_PATH
+                && !route.as_path.contains(&local_as);
+            if valid {
+                routes.insert(prefix, route.as_path);
+            } else {
+                debug!("BGP peer {}: rejecting {} with AS path {:?}", peer, prefix, route.as_path);
+                routes.remove(&prefix);
+                rejected += 1;
+            }
+        }
+        rejected
+    }
+
+    /// Meilleure route de chaque préfixe : chemin d'AS le plus court, puis pair de plus
+    /// petite adresse
+    pub fn best(&self) -> BTreeMap<Prefix, (&str, &[u32])> {
+        let mut best: BTreeMap<Prefix, (&str, &[u32])> = BTreeMap::new();
+        for (peer, routes) in &self.routes {
+            for (prefix, as_path) in routes {
+                let better = best.get(prefix).is_none_or(|(_, current)| as_path.len() < current.len());
+                if better {
+                    best.insert(*prefix, (peer.as_str(), as_path.as_slice()));
+                }
+            }
+        }
+        best
+    }
+
+    /// Routes à redistribuer dans nos LSA : métrique de base augmentée de la longueur
+    /// du chemin d'AS
+    pub fn external_routes(&self, metric: u32) -> HashMap<Prefix, u32> {
+        self.best().into_iter()
+            .map(|(prefix, (_, as_path))| (prefix, metric.saturating_add(as_path.len() as u32)))
+            .collect()
+    }
+}
+
+/// Préfixes de `bgp.export` présents dans notre domaine : réseaux connectés, ou routes
+/// internes joignables (jamais les routes externes, pour ne pas servir de transit)
+pub async fn exported_prefixes(state: &AppState) -> BTreeMap<Prefix, Vec<u32>> {
+    let router_config = state.config().await;
+    let mut known: Vec<Prefix> = crate::lsa::originated_networks(&router_config, &state.interfaces, &*state.interface_states.read().await, &state.local_ip).into_keys().collect();
+    let config = router_config.bgp;
+    known.extend(state.routing_table.read().await.iter()
+        .filter(|(_, (_, route_state))| matches!(route_state, RouteState::Active(_)))
+        .map(|(prefix, _)| *prefix));
+    config.export.iter()
+        .filter_map(|entry| entry.parse::<Prefix>().ok())
+        .filter(|prefix| known.iter().any(|known| known.network() == prefix.network()))
+        .map(|prefix| (Prefix::new(prefix.network()), vec![config.local_as]))
+        .collect()
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &BgpMessage) -> Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    Ok(())
+}
+
+async fn read_message<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> Result<BgpMessage> {
+    let mut line = String::new();
+    let read = reader.take(MAX_MESSAGE_LEN).read_line(&mut line).await?;
+    if read == 0 {
+        return Err(AppError::NetworkError("session closed by peer".to_string()));
+    }
+    if !line.ends_with('\n') {
+        return Err(AppError::PacketError(format!("message longer than {} bytes", MAX_MESSAGE_LEN)));
+    }
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// Déroule une session avec un pair sur un flux déjà connecté, jusqu'à sa fermeture
+pub async fn run_session<S: AsyncRead + AsyncWrite>(state: &Arc<AppState>, peer: &BgpPeerConfig, stream: S) -> Result<()> {
+    let config = state.config().await.bgp;
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+
+    write_message(&mut writer, &BgpMessage::Open {
+        asn: config.local_as,
+        router_id: state.router_id.to_string(),
+        hold_time_sec: config.hold_time_sec,
+    }).await?;
+    let open = tokio::time::timeout(Duration::from_secs(config.hold_time_sec), read_message(&mut reader)).await
+        .map_err(|_| AppError::NetworkError("no OPEN received".to_string()))??;
+    let hold_time = match open {
+        BgpMessage::Open { asn, hold_time_sec, .. } if asn == peer.remote_as && hold_time_sec >= 3 => {
+            config.hold_time_sec.min(hold_time_sec)
+        }
+        BgpMessage::Open { asn, hold_time_sec, .. } => {
+            let reason = format!("unexpected AS {} or hold time {}", asn, hold_time_sec);
+            let _ = write_message(&mut writer, &BgpMessage::Notification { reason: reason.clone() }).await;
+            return Err(AppError::NetworkError(reason));
+        }
+        other => return Err(AppError::NetworkError(format!("expected OPEN, received {:?}", other))),
+    };
+    if !state.bgp.write().await.open_session(&peer.address) {
+        let _ = write_message(&mut writer, &BgpMessage::Notification { reason: "session already established".to_string() }).await;
+        return Err(AppError::NetworkError("session already established".to_string()));
+    }
+    state.metrics.bgp_sessions.fetch_add(1, Ordering::Relaxed);
+    info!("BGP session with {} (AS {}) established, hold time {}s", peer.address, peer.remote_as, hold_time);
+
+    let result = established(state, peer, config.local_as, hold_time, &mut reader, &mut writer).await;
+
+    state.bgp.write().await.close_session(&peer.address);
+    state.metrics.bgp_sessions.fetch_sub(1, Ordering::Relaxed);
+    result
+}
+
+async fn established<R, W>(state: &Arc<AppState>, peer: &BgpPeerConfig, local_as: u32, hold_time: u64, reader: &mut R, writer: &mut W) -> Result<()>
+where
+    R: AsyncBufReadExt + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    // Lecture dans une boucle distincte : une ligne à moitié lue n'est jamais abandonnée
+    // quand un KEEPALIVE est dû
+    let (received, mut messages) = tokio::sync::mpsc::channel(16);
+    let receive = async move {
+        loop {
+            let message = read_message(reader).await;
+            let failed = message.is_err();
+            if received.send(message).await.is_err() || failed {
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    let process = async {
+        let mut advertised: BTreeMap<Prefix, Vec<u32>> = BTreeMap::new();
+        let mut keepalive = tokio::time::interval(Duration::from_secs((hold_time / 3).max(1)));
+        let hold = Duration::from_secs(hold_time);
+        let mut deadline = tokio::time::Instant::now() + hold;
+        loop {
+            tokio::select! {
+                _ = keepalive.tick() => {
+                    // Annonces et retraits depuis le dernier UPDATE, puis KEEPALIVE
+                    let exported = exported_prefixes(state).await;
+                    let withdraw: Vec<Prefix> = advertised.keys().filter(|prefix| !exported.contains_key(prefix)).copied().collect();
+                    let announce: Vec<BgpRoute> = exported.iter()
+                        .filter(|(prefix, as_path)| advertised.get(prefix) != Some(as_path))
+                        .map(|(prefix, as_path)| BgpRoute { prefix: *prefix, as_path: as_path.clone() })
+                        .collect();
+                    if !announce.is_empty() || !withdraw.is_empty() {
+                        write_message(writer, &BgpMessage::Update { announce, withdraw }).await?;
+                        advertised = exported;
+                    }
+                    write_message(writer, &BgpMessage::Keepalive).await?;
+                }
+                _ = tokio::time::sleep_until(deadline) => {
+                    let _ = write_message(writer, &BgpMessage::Notification { reason: "hold timer expired".to_string() }).await;
+                    return Err(AppError::NetworkError("hold timer expired".to_string()));
+                }
+                Some(message) = messages.recv() => {
+                    deadline = tokio::time::Instant::now() + hold;
+                    match message? {
+                        BgpMessage::Update { announce, withdraw } => {
+                            let rejected = state.bgp.write().await.update(&peer.address, peer.remote_as, local_as, announce, &withdraw);
+                            if rejected > 0 {
+                                state.metrics.bgp_routes_rejected.fetch_add(rejected as u64, Ordering::Relaxed);
+                            }
+                        }
+                        BgpMessage::Keepalive => {}
+                        BgpMessage::Notification { reason } => {
+                            return Err(AppError::NetworkError(format!("closed by peer: {}", reason)));
+                        }
+                        BgpMessage::Open { .. } => {
+                            return Err(AppError::NetworkError("unexpected OPEN in an established session".to_string()));
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    tokio::select! {
+        _ = receive => Ok(()),
+        result = process => result,
+    }
+}
+
+/// Établit et rétablit la session d'un pair actif
+async fn connect_loop(state: Arc<AppState>, peer: BgpPeerConfig) {
+    loop {
+        let retry = Duration::from_secs(state.config().await.bgp.connect_retry_sec.max(1));
+        match tokio::time::timeout(retry, tokio::net::TcpStream::connect(&peer.address)).await {
+            Ok(Ok(stream)) => {
+                if let Err(e) = run_session(&state, &peer, stream).await {
+                    warn!("BGP session with {} closed: {}", peer.address, e);
+                }
+            }
+            Ok(Err(e)) => debug!("BGP connection to {} failed: {}", peer.address, e),
+            Err(_) => debug!("BGP connection to {} timed out", peer.address),
+        }
+        tokio::time::sleep(retry).await;
+    }
+}
+
+/// Accepte les sessions des pairs passifs, reconnus à leur adresse
+async fn listen_loop(state: Arc<AppState>, listen: String, sessions: &mut tokio::task::JoinSet<()>) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(&listen).await?;
+    info!("BGP gateway listening on {}", listen);
+    loop {
+        let (stream, remote) = listener.accept().await?;
+        while sessions.try_join_next().is_some() {}
+        let peer = state.config().await.bgp.peers.into_iter()
+            .find(|peer| peer.passive && peer.address.parse::<std::net::SocketAddr>().is_ok_and(|address| address.ip() == remote.ip()));
+        let Some(peer) = peer else {
+            warn!("BGP connection from unknown peer {} refused", remote);
+            continue;
+        };
+        let state = Arc::clone(&state);
+        sessions.spawn(async move {
+            if let Err(e) = run_session(&state, &peer, stream).await {
+                warn!("BGP session with {} closed: {}", peer.address, e);
+            }
+        });
+    }
+}
+
+/// Lance les sessions de la passerelle ; l'arrêt de la tâche arrête toutes les sessions
+pub fn spawn_bgp_task(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let config = state.config().await.bgp;
+        let mut sessions = tokio::task::JoinSet::new();
+        for peer in config.peers.into_iter().filter(|peer| !peer.passive) {
+            sessions.spawn(connect_loop(Arc::clone(&state), peer));
+        }
+        if let Some(listen) = config.listen {
+            if let Err(e) = listen_loop(Arc::clone(&state), listen.clone(), &mut sessions).await {
+                tracing::error!("BGP listener on {} failed: {}", listen, e);
+            }
+        }
+        while sessions.join_next().await.is_some() {}
+    })
+}
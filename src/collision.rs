@@ -0,0 +1,176 @@
+use crate::types::LSAMessage;
+use crate::AppState;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use tracing::{error, warn};
+
+/// Instances émises retenues par originateur pour reconnaître leurs copies
+const SENT_HISTORY: usize = 64;
+/// Fenêtre pendant laquelle les sauts de séquence d'un de nos originateurs sont comptés
+const FIGHT_WINDOW_SEC: u64 = 60;
+/// Sauts de séquence dans la fenêtre au-delà desquels un autre routeur utilise notre identifiant
+const FIGHT_THRESHOLD: usize = 3;
+
+/// Empreinte du contenu d'un LSA, indépendante des champs modifiés par le relais
+/// (émetteur, dernier saut, chemin, TTL) et de l'ordre des tables
+pub fn fingerprint(lsa: &LSAMessage) -> u64 {
+    let mut neighbors: Vec<_> = lsa.neighbors.iter()
+        .map(|neighbor| (&neighbor.neighbor_ip, neighbor.link_up, neighbor.capacity, neighbor.cost))
+        .collect();
+    neighbors.sort();
+    let routing_table: BTreeMap<_, _> = lsa.routing_table.iter()
+        .map(|(prefix, route_state)| (prefix, serde_json::to_string(route_state).unwrap_or_default()))
+        .collect();
+    let external_routes: BTreeMap<_, _> = lsa.external_routes.iter().collect();
+    let external_metric_types: BTreeMap<_, _> = lsa.external_metric_types.iter()
+        .map(|(prefix, metric_type)| (prefix, format!("{:?}", metric_type)))
+        .collect();
+    let route_tags: BTreeMap<_, _> = lsa.route_tags.iter().collect();
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (&lsa.originator, lsa.seq_num, neighbors, routing_table, external_routes, external_metric_types, route_tags, lsa.stub_only, &lsa.opaque).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Collision détectée sur un de nos originateurs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Conflict {
+    pub originator: String,
+    /// Dernier voisin nous ayant transmis l'instance concurrente
+    pub source: String,
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub count: u64,
+}
+
+/// Issue de la vérification d'une copie de nos propres LSA
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Instance émise par ce routeur, ou copie ancienne
+    Consistent,
+    /// Instance plus récente que notre compteur, sans encore justifier une alarme
+    Ahead,
+    /// Un autre routeur annonce sous notre identifiant
+    Conflict,
+}
+
+/// Instances émises par ce routeur et collisions observées sur ses originateurs
+#[derive(Debug, Default)]
+pub struct RouterIdGuard {
+    sent: HashMap<String, VecDeque<(u32, u64)>>,
+    jumps: HashMap<String, VecDeque<u64>>,
+    conflicts: BTreeMap<String, Conflict>,
+}
+
+impl RouterIdGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retient une instance émise (un même numéro peut être envoyé avec des contenus
+    /// différents selon les filtres de sortie de chaque voisin)
+    pub fn record_sent(&mut self, lsa: &LSAMessage) {
+        let sent = self.sent.entry(lsa.originator.clone()).or_default();
+        let entry = (lsa.seq_num, fingerprint(lsa));
+        if sent.contains(&entry) {
+            return;
+        }
+        if sent.len() == SENT_HISTORY {
+            sent.pop_front();
+        }
+        sent.push_back(entry);
+    }
+
+    /// Compare une copie reçue de nos propres LSA à ce que nous avons émis :
+    /// même numéro et contenu différent, ou numéros plus récents que notre compteur
+    /// à répétition, trahissent un autre routeur utilisant le même identifiant
+    pub fn check(&mut self, lsa: &LSAMessage, own_sequence: u32, now: u64) -> Verdict {
+        if let Some(sent) = self.sent.get(&lsa.originator) {
+            let mut same_seq = sent.iter().filter(|(seq_num, _)| *seq_num == lsa.seq_num).peekable();
+            if same_seq.peek().is_some() {
+                let print = fingerprint(lsa);
+                return if same_seq.any(|(_, sent_print)| *sent_print == print) {
+                    Verdict::Consistent
+                } else {
+                    Verdict::Conflict
+                };
+            }
+        }
+        if !crate::sequence::is_newer(lsa.seq_num, own_sequence) {
+            return Verdict::Consistent;
+        }
+        let jumps = self.jumps.entry(lsa.originator.clone()).or_default();
+        jumps.retain(|seen| now.saturating_sub(*seen) <= FIGHT_WINDOW_SEC);
+        jumps.push_back(now);
+        if jumps.len() >= FIGHT_THRESHOLD {
+            Verdict::Conflict
+        } else {
+            Verdict::Ahead
+        }
+    }
+
+    fn record_conflict(&mut self, originator: &str, source: &str, now: u64) -> &Conflict {
+        let conflict = self.conflicts.entry(originator.to_string()).or_insert_with(|| Conflict {
+            originator: originator.to_string(),
+            source: source.to_string(),
+            first_seen: now,
+            last_seen: now,
+            count: 0,
+        });
+        conflict.source = source.to_string();
+        conflict.last_seen = now;
+        conflict.count += 1;
+        conflict
+    }
+
+    /// Collisions observées, triées par originateur
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        self.conflicts.values().cloned().collect()
+    }
+}
+
+/// Vrai si le LSA porte un de nos originateurs. Le LSA réseau d'un de nos segments n'est
+/// le nôtre que si nous l'avons émis comme routeur désigné
+pub fn is_own_lsa(state: &AppState, lsa: &LSAMessage) -> bool {
+    crate::sequence::is_own_originator(state, &lsa.originator)
+        && (crate::lsdb::Lsdb::classify(lsa) == crate::lsdb::LsaType::Router || lsa.router_id == Some(state.router_id))
+}
+
+/// Contrôle un LSA reçu portant notre identifiant ou un de nos originateurs ; renvoie vrai
+/// s'il vient d'un autre routeur utilisant le même identifiant et doit être écarté
+pub async fn reject_foreign_own_lsa(state: &AppState, lsa: &LSAMessage, source: &str) -> bool {
+    let own_originator = is_own_lsa(state, lsa);
+    if !own_originator && lsa.router_id != Some(state.router_id) {
+        return false;
+    }
+    // Nos propres diffusions reçues en retour sur un segment
+    if state.interfaces.interfaces().iter().any(|iface| iface.ip().to_string() == source) {
+        return false;
+    }
+    let now = crate::clock::unix_secs();
+    let mut guard = state.router_id_guard.lock().await;
+    // Notre identifiant annoncé depuis une adresse qui n'est pas la nôtre
+    let verdict = if own_originator { guard.check(lsa, state.own_sequence.current(), now) } else { Verdict::Conflict };
+    match verdict {
+        Verdict::Consistent => false,
+        Verdict::Ahead => {
+            if state.own_sequence.observe(lsa.seq_num) {
+                warn!("Own LSA {} with seq {} is newer than our counter, skipping ahead", lsa.originator, lsa.seq_num);
+            }
+            false
+        }
+        Verdict::Conflict => {
+            let count = guard.record_conflict(&lsa.originator, source, now).count;
+            drop(guard);
+            error!("[CONFLICT] Router-id {} is also used by another router (LSA seq {} relayed by {}, {} occurrence(s)); check the addressing of the network",
+                   lsa.originator, lsa.seq_num, source, count);
+            state.metrics.router_id_conflicts.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            state.events.emit(crate::events::RouterEvent::RouterIdConflict {
+                originator: lsa.originator.clone(),
+                source: source.to_string(),
+            });
+            true
+        }
+    }
+}
+
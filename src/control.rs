@@ -0,0 +1,621 @@
+use crate::AppState;
+use crate::types::Prefix;
+use pnet::ipnetwork::Ipv4Network;
+use tracing::{error, info, warn};
+use std::sync::Arc;
+use crate::transport::Socket;
+
+/// Suffixe demandant une réponse JSON (`routing-table --json`)
+pub const JSON_FLAG: &str = "--json";
+
+/// Début des réponses signalant un refus ou un échec de la commande
+const ERROR_PREFIXES: &[&str] = &["Accès refusé", "Commande inconnue", "Échec", "Argument invalide", "Usage"];
+
+/// Sépare le suffixe `--json` de la commande
+pub fn split_json_flag(command: &str) -> (&str, bool) {
+    match command.strip_suffix(JSON_FLAG) {
+        Some(rest) if rest.is_empty() || rest.ends_with(char::is_whitespace) => (rest.trim_end(), true),
+        _ => (command, false),
+    }
+}
+
+/// Options du CLI suivies d'une valeur, à ne pas confondre avec les mots de la commande
+pub const CLI_VALUE_OPTIONS: &[&str] = &["--config", "--user", "--socket", "--server", "--vrf"];
+
+/// Commande passée en arguments au CLI (mode non interactif) : ses propres options sont
+/// retirées, `--json` est replacé en suffixe et tout le reste, options de la commande
+/// comprises (`paths <dest> --k 3`), est transmis tel quel
+pub fn command_from_args<I: IntoIterator<Item = String>>(args: I) -> Option<String> {
+    let mut words = Vec::new();
+    let mut json = false;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if CLI_VALUE_OPTIONS.contains(&arg.as_str()) {
+            args.next();
+        } else if arg == JSON_FLAG {
+            json = true;
+        } else if !CLI_VALUE_OPTIONS.iter().any(|option| arg.strip_prefix(option).is_some_and(|rest| rest.starts_with('='))) {
+            words.push(arg);
+        }
+    }
+    if words.is_empty() {
+        return None;
+    }
+    if json {
+        words.push(JSON_FLAG.to_string());
+    }
+    Some(words.join(" "))
+}
+
+/// Texte d'une réponse de contrôle reçue (message réassemblé) si elle répond à la commande
+/// `request_id` ; `None` pour la réponse tardive d'une commande précédente. Une réponse sans
+/// identifiant (ancien démon) est acceptée telle quelle
+pub fn response_for(request_id: u64, message: &[u8]) -> crate::error::Result<Option<String>> {
+    if let Ok(response) = serde_json::from_slice::<crate::types::ControlResponse>(message) {
+        return Ok((response.request_id == request_id).then_some(response.response));
+    }
+    serde_json::from_slice::<String>(message).map(Some).map_err(crate::error::AppError::SerializationError)
+}
+
+/// Vrai si la réponse textuelle signale que la commande a été refusée ou a échoué
+pub fn is_error_response(response: &str) -> bool {
+    ERROR_PREFIXES.iter().any(|prefix| response.starts_with(prefix))
+}
+
+/// Exécute une commande de contrôle (CLI ou API embarquée) et renvoie la réponse,
+/// textuelle ou JSON si la commande se termine par `--json`
+pub async fn execute_command(state: &Arc<AppState>, socket: &Arc<Socket>, command: &str) -> String {
+    // `vrf <nom> <commande>` : la commande, `--json` compris, est confiée à l'instance de la VRF
+    if let Some(arguments) = command.strip_prefix("vrf ").filter(|arguments| !split_json_flag(arguments).0.trim().is_empty()) {
+        return crate::vrf::execute(state, arguments).await;
+    }
+    match split_json_flag(command) {
+        (command, true) => execute_json(state, socket, command).await.to_string(),
+        (command, false) => execute_text(state, socket, command).await,
+    }
+}
+
+/// Variante JSON : tables structurées pour `routing-table`, `neighbors` et `conflicts`,
+/// `{"ok", "message"}` pour les autres commandes
+async fn execute_json(state: &Arc<AppState>, socket: &Arc<Socket>, command: &str) -> serde_json::Value {
+    match command {
+        "routing-table" => {
+            let routing_table = state.routing_table.read().await;
+            let origins = state.route_origins.read().await;
+            let mut routes: Vec<_> = routing_table.iter().collect();
+            routes.sort_by(|a, b| a.0.cmp(b.0));
+            routes.into_iter()
+                .map(|(prefix, (next_hop, route_state))| serde_json::json!({
+                    "prefix": prefix,
+                    "next_hop": next_hop,
+                    "state": route_state,
+                    "metric": route_state.metric(),
+                    "origin": origins.get(prefix),
+                }))
+                .collect()
+        }
+        "conflicts" => serde_json::json!(state.router_id_guard.lock().await.conflicts()),
+        "filtered-routes" => serde_json::json!(*state.filtered_routes.lock().await),
+        "spof" => serde_json::json!(crate::spof::analyze(&crate::dijkstra::build_network_topology(Arc::clone(state)).await)),
+        "pending-routes" => {
+            let now = tokio::time::Instant::now();
+            state.route_ops.queue().await.entries()
+                .map(|(prefix, pending)| {
+                    let (op, next_hop) = match &pending.op {
+                        crate::route_queue::RouteOp::Install { next_hop } => ("install", Some(next_hop.to_string())),
+                        crate::route_queue::RouteOp::Remove => ("remove", None),
+                    };
+                    serde_json::json!({
+                        "prefix": prefix,
+                        "op": op,
+                        "next_hop": next_hop,
+                        "attempts": pending.attempts,
+                        "last_error": pending.last_error,
+                        "retry_in_ms": pending.next_attempt.map(|at| at.saturating_duration_since(now).as_millis() as u64),
+                    })
+                })
+                .collect()
+        }
+        detail_command if detail_command.starts_with("neighbor-detail ") => {
+            let ip = detail_command["neighbor-detail ".len()..].trim();
+            let Some(neighbor) = state.neighbors.read().await.get(ip).cloned() else {
+                return serde_json::json!({ "ok": false, "message": format!("Échec: voisin '{}' inconnu", ip) });
+            };
+            let stats = state.neighbor_stats.lock().await.get(ip).cloned().unwrap_or_default();
+            let config = state.config().await;
+            let damping = state.damping.lock().await.status(ip, &config.flap_damping);
+            let hello_interval = crate::adaptive_hello::interface_interval(state, &config, neighbor.interface.as_deref()).await;
+            serde_json::json!({
+                "router_id": neighbor.identity(),
+                "address": neighbor.neighbor_ip,
+                "hostname": neighbor.hostname,
+                "interface": neighbor.interface,
+                "link_up": neighbor.link_up,
+                "hello_interval_sec": hello_interval,
+                "damping": damping,
+                "stats": stats,
+            })
+        }
+        "neighbors" => {
+            let current_time = crate::clock::unix_secs();
+            let neighbors = state.neighbors.read().await;
+            let mut neighbors: Vec<_> = neighbors.values().collect();
+            neighbors.sort_by(|a, b| a.neighbor_ip.cmp(&b.neighbor_ip));
+            neighbors.into_iter()
+                .map(|neighbor| serde_json::json!({
+                    "router_id": neighbor.identity(),
+                    "address": neighbor.neighbor_ip,
+                    "hostname": neighbor.hostname,
+                    "interface": neighbor.interface,
+                    "link_up": neighbor.link_up,
+                    "last_seen_sec": current_time.saturating_sub(neighbor.last_seen),
+                    "rtt_us": neighbor.rtt_us,
+                    "jitter_us": neighbor.jitter_us,
+                }))
+                .collect()
+        }
+        _ => {
+            let message = execute_text(state, socket, command).await;
+            serde_json::json!({ "ok": !is_error_response(&message), "message": message })
+        }
+    }
+}
+
+async fn execute_text(state: &Arc<AppState>, socket: &Arc<Socket>, command: &str) -> String {
+    match command {
+        "connexion" => {
+            info!("[CLI] New connection");
+            "Connexion établie avec succès".to_string()
+        }
+        "enable" => {
+            state.enable().await;
+            crate::persist::save(state).await;
+            info!("[CLI] Protocole activé via commande réseau");
+            "Protocole OSPF activé".to_string()
+        }
+        "disable" => {
+            state.disable().await;
+            crate::persist::save(state).await;
+            info!("[CLI] Protocole désactivé via commande réseau");
+            "Protocole OSPF désactivé".to_string()
+        }
+        reload_command if reload_command == "reload-config" || reload_command.starts_with("reload-config ") => {
+            // `reload-config <fichier>` : recharger depuis un fichier explicite
+            let override_path = reload_command.split_whitespace().nth(1).map(std::path::PathBuf::from);
+            match crate::reload::reload_config(state, socket, override_path).await {
+                Ok(summary) => summary,
+                Err(e) => {
+                    error!("[CLI] Configuration reload failed: {}", e);
+                    format!("Échec du rechargement de la configuration: {}", e)
+                }
+            }
+        }
+        trace_command if trace_command == "trace" || trace_command.starts_with("trace ") => {
+            handle_trace_command(trace_command)
+        }
+        #[cfg(feature = "fault-injection")]
+        fault_command if fault_command == "fault" || fault_command.starts_with("fault ") => {
+            info!("[CLI] Fault injection: {}", fault_command);
+            crate::fault::handle_command(fault_command)
+        }
+        "metrics" => state.metrics.summary(),
+        "vrf" => crate::vrf::execute(state, "").await,
+        "conflicts" => conflicts_report(&state.router_id_guard.lock().await.conflicts(), crate::clock::unix_secs()),
+        detail_command if detail_command.starts_with("neighbor-detail ") || detail_command == "neighbor-detail" => {
+            let [_, ip] = detail_command.split_whitespace().collect::<Vec<_>>()[..] else {
+                return "Usage: neighbor-detail <ip>".to_string();
+            };
+            let Some(neighbor) = state.neighbors.read().await.get(ip).cloned() else {
+                return format!("Échec: voisin '{}' inconnu", ip);
+            };
+            let stats = state.neighbor_stats.lock().await.get(ip).cloned();
+            let config = state.config().await;
+            let damping = state.damping.lock().await.status(ip, &config.flap_damping);
+            let hello_interval = crate::adaptive_hello::interface_interval(state, &config, neighbor.interface.as_deref()).await;
+            crate::neighbor_stats::detail_report(&neighbor, stats.as_ref(), damping.as_ref(), hello_interval, crate::clock::unix_secs())
+        }
+        "filtered-routes" => crate::install_policy::report(&state.filtered_routes.lock().await),
+        "pending-routes" => crate::route_queue::pending_report(&*state.route_ops.queue().await, tokio::time::Instant::now()),
+        ping_command if ["ping", "probe"].contains(&ping_command.split_whitespace().next().unwrap_or_default()) => {
+            match ping_command.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [_, target] => crate::probe::ping(state, target).await,
+                _ => "Usage: ping <routeur>".to_string(),
+            }
+        }
+        path_command if path_command.starts_with("path ") || path_command == "path" => {
+            match path_command.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [_, destination] => describe_path(state, destination).await,
+                [_, destination, router] => query_path(state, destination, router).await,
+                _ => "Usage: path <préfixe|adresse> [routeur]".to_string(),
+            }
+        }
+        paths_command if paths_command.starts_with("paths ") || paths_command == "paths" => {
+            describe_paths(state, &paths_command.split_whitespace().skip(1).collect::<Vec<_>>()).await
+        }
+        set_command if set_command.starts_with("set-cost ") || set_command.starts_with("set-link ") => {
+            set_interface(state, set_command).await
+        }
+        history_command if history_command == "history" || history_command.starts_with("history ") => {
+            crate::history::execute(state, &history_command["history".len()..]).await
+        }
+        "mtu" => crate::path_mtu::report(state).await,
+        "sessions" => crate::tcp_session::report(state).await,
+        "spof" => spof_report(&crate::spof::analyze(&crate::dijkstra::build_network_topology(Arc::clone(state)).await)),
+        "lsdb" => {
+            info!("[CLI] LSDB requested");
+            lsdb_table(&*state.lsdb.read().await, crate::clock::unix_secs())
+        }
+        "routing-table" => {
+            info!("[CLI] Routing table requested");
+            let routing_table = state.routing_table.read().await;
+            let origins = state.route_origins.read().await;
+            if routing_table.is_empty() {
+                "Table de routage vide".to_string()
+            } else {
+                let now = crate::clock::unix_secs();
+                let mut routes: Vec<_> = routing_table.iter().collect();
+                routes.sort_by(|a, b| a.0.cmp(b.0));
+                routes.into_iter()
+                    .map(|(key, (next_hop, state))| {
+                        let origin = origins.get(key)
+                            .map(|origin| format!(
+                                " [LSA {:?} de {} (seq {}), installée il y a {} s, chemin: {}{}]",
+                                origin.lsa_type, origin.originator, origin.seq_num,
+                                now.saturating_sub(origin.installed_at), origin.path.join(" -> "),
+                                origin.tag.map(|tag| format!(", étiquette {}", tag)).unwrap_or_default(),
+                            ))
+                            .unwrap_or_default();
+                        format!("{} -> {} ({:?}){}", key, next_hop, state, origin)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        }
+        "neighbors" => {
+            info!("[CLI] Neighbors list requested");
+            let neighbors = state.neighbors.read().await;
+            if neighbors.is_empty() {
+                "Aucun voisin détecté".to_string()
+            } else {
+                let current_time = crate::clock::unix_secs();
+                neighbors.values()
+                    .map(|neighbor| {
+                        let age = current_time.saturating_sub(neighbor.last_seen);
+                        let rtt = match (neighbor.rtt_us, neighbor.jitter_us) {
+                            (Some(rtt), Some(jitter)) => format!(", RTT: {:.2} ms, gigue: {:.2} ms", rtt as f64 / 1000.0, jitter as f64 / 1000.0),
+                            _ => String::new(),
+                        };
+                        let interface = neighbor.interface.as_deref().map(|name| format!(" sur {}", name)).unwrap_or_default();
+                        let router_id = neighbor.router_id.map(|router_id| format!(" [id {}]", router_id)).unwrap_or_default();
+                        format!("{}{}{} (dernière activité: il y a {} secondes{})", neighbor.display_name(), router_id, interface, age, rtt)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        }
+        _ => {
+            warn!("[CLI] Commande de contrôle inconnue: {}", command);
+            format!("Commande inconnue: '{}'. Utilisez 'help' pour voir les commandes disponibles.", command)
+        }
+    }
+}
+
+/// `set-cost <iface> <coût|auto>` et `set-link <iface> up|down` : modifient l'état
+/// courant de l'interface (pas la configuration), puis annoncent le changement
+async fn set_interface(state: &Arc<AppState>, command: &str) -> String {
+    const USAGE: &str = "Usage: set-cost <interface> <coût|auto> | set-link <interface> up|down";
+    let args: Vec<&str> = command.split_whitespace().collect();
+    let [verb, name, value] = args.as_slice() else {
+        return USAGE.to_string();
+    };
+    let summary = {
+        let mut states = state.interface_states.write().await;
+        let Some(iface) = states.get_mut(*name) else {
+            return format!("Échec: interface '{}' non configurée", name);
+        };
+        match (*verb, *value) {
+            ("set-cost", "auto") => iface.cost = None,
+            ("set-cost", cost) => match cost.parse::<u32>() {
+                Ok(cost) if cost > 0 && cost < u32::MAX => iface.cost = Some(cost),
+                _ => return format!("Argument invalide: '{}'. {}", cost, USAGE),
+            },
+            ("set-link", "up") => iface.set_link_state(true),
+            ("set-link", "down") => iface.set_link_state(false),
+            _ => return USAGE.to_string(),
+        }
+        format!(
+            "Interface {}: lien {}, coût {}",
+            name,
+            if iface.link_active { "actif" } else { "inactif" },
+            match iface.effective_cost() { u32::MAX => "∞".to_string(), cost => cost.to_string() },
+        )
+    };
+    info!("[CLI] {}", summary);
+    crate::persist::save(state).await;
+    crate::neighbor::apply_interface_change(state, name).await;
+    crate::neighbor::display_interface_report(state).await;
+    summary
+}
+
+/// Préfixe le plus spécifique couvrant la destination et l'originateur qui l'annonce ;
+/// l'erreur est le message à afficher (argument invalide, réseau connecté, pas de route)
+async fn route_originator(state: &Arc<AppState>, destination: &str, usage: &str) -> std::result::Result<(Prefix, String), String> {
+    let Ok(target) = destination.parse::<Ipv4Network>().map(Prefix::new) else {
+        return Err(format!("Argument invalide: '{}'. Usage: {}", destination, usage));
+    };
+    if let Some(iface) = state.interfaces.interfaces().into_iter().find(|iface| Prefix::new(iface.network).covers(&target)) {
+        return Err(format!("{} est directement connecté à ce routeur ({}, interface {})", iface.network, state.local_ip, iface.name));
+    }
+    let routing_table = state.routing_table.read().await;
+    let origins = state.route_origins.read().await;
+    routing_table.keys()
+        .filter(|prefix| prefix.covers(&target))
+        .filter_map(|prefix| Some((prefix.prefix_len(), *prefix, origins.get(prefix)?.originator.clone())))
+        .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+        .map(|(_, prefix, originator)| (prefix, originator))
+        .ok_or_else(|| format!("Aucune route vers {}", destination))
+}
+
+/// Chemin saut par saut vers l'originateur de la route retenue pour la destination
+/// (préfixe le plus spécifique), recalculé sur la topologie courante
+async fn describe_path(state: &Arc<AppState>, destination: &str) -> String {
+    let (prefix, originator) = match route_originator(state, destination, "path <préfixe|adresse>").await {
+        Ok(found) => found,
+        Err(message) => return message,
+    };
+
+    let topology = crate::dijkstra::build_network_topology(Arc::clone(state)).await;
+    let config = state.config().await;
+    let shortest_paths = config.spf_engine.engine().shortest_paths(&topology, &state.local_ip, config.path_selection);
+    let nodes = crate::dijkstra::neighbor_nodes(&*state.neighbors.read().await);
+    let Some(route) = crate::dijkstra::route_to_originator(&shortest_paths, &nodes, &originator) else {
+        return format!("{} est annoncé par {}, injoignable dans la topologie courante", prefix, originator);
+    };
+    let mut lines = vec![format!(
+        "Chemin vers {} (annoncé par {}, coût {}, {} sauts):",
+        prefix, originator, route.total_cost, route.hop_count,
+    )];
+    lines.push(format!("  1. {}", state.local_ip));
+    for (index, hop) in route.path.windows(2).enumerate() {
+        let cost = topology.find_link(&hop[0], &hop[1]).map_or("?".to_string(), |link| link.cost.to_string());
+        lines.push(format!("  {}. {} (coût du lien {})", index + 2, hop[1], cost));
+    }
+    lines.join("\n")
+}
+
+/// Une ligne par chemin : coût, sauts et routeurs traversés
+fn path_line(label: &str, route: &crate::dijkstra::RouteInfo) -> String {
+    format!("  {} : coût {}, {} sauts, {}", label, route.total_cost, route.hop_count, route.path.join(" -> "))
+}
+
+/// Commande `paths <destination> [--k N | --disjoint]` : les N meilleurs chemins vers
+/// l'originateur de la route (3 par défaut), ou deux chemins aussi disjoints que possible
+async fn describe_paths(state: &Arc<AppState>, arguments: &[&str]) -> String {
+    const USAGE: &str = "paths <préfixe|adresse> [--k N | --disjoint]";
+    let (destination, k, disjoint) = match arguments {
+        [destination] => (*destination, 3, false),
+        [destination, "--k", k] => match k.parse::<usize>() {
+            Ok(k) if (1..=crate::k_paths::MAX_PATHS).contains(&k) => (*destination, k, false),
+            _ => return format!("Argument invalide: '{}' (1 à {} chemins). Usage: {}", k, crate::k_paths::MAX_PATHS, USAGE),
+        },
+        [destination, "--disjoint"] => (*destination, 0, true),
+        _ => return format!("Usage: {}", USAGE),
+    };
+    let (prefix, originator) = match route_originator(state, destination, USAGE).await {
+        Ok(found) => found,
+        Err(message) => return message,
+    };
+
+    let topology = crate::dijkstra::build_network_topology(Arc::clone(state)).await;
+    let policy = state.config().await.path_selection;
+    // Un voisin direct figure dans la topologie sous ses adresses, pas sous son identifiant
+    let targets = crate::dijkstra::neighbor_nodes(&*state.neighbors.read().await)
+        .remove(&originator)
+        .unwrap_or_else(|| vec![originator.clone()]);
+    let unreachable = || format!("{} est annoncé par {}, injoignable dans la topologie courante", prefix, originator);
+
+    if disjoint {
+        let Some(paths) = targets.iter()
+            .filter_map(|target| topology.disjoint_paths(&state.local_ip, target, policy))
+            .min_by_key(|paths| paths.primary.key())
+        else {
+            return unreachable();
+        };
+        let mut lines = vec![format!("Chemins disjoints vers {} (annoncé par {}):", prefix, originator), path_line("principal", &paths.primary)];
+        match &paths.secondary {
+            Some((route, disjointness)) => {
+                let separation = match disjointness {
+                    crate::k_paths::Disjointness::Nodes => "aucun routeur commun".to_string(),
+                    crate::k_paths::Disjointness::Links => "aucun lien commun".to_string(),
+                    crate::k_paths::Disjointness::Partial(shared) => format!("{} lien(s) commun(s)", shared),
+                };
+                lines.push(format!("{} ({})", path_line("secours", route), separation));
+            }
+            None => lines.push("  secours : aucun autre chemin".to_string()),
+        }
+        return lines.join("\n");
+    }
+
+    let mut routes: Vec<_> = targets.iter()
+        .flat_map(|target| topology.k_shortest_paths(&state.local_ip, target, k, policy))
+        .collect();
+    routes.sort_by(|a, b| a.key().cmp(&b.key()).then_with(|| a.path.cmp(&b.path)));
+    routes.truncate(k);
+    if routes.is_empty() {
+        return unreachable();
+    }
+    let mut lines = vec![format!("{} meilleur(s) chemin(s) vers {} (annoncé par {}):", routes.len(), prefix, originator)];
+    lines.extend(routes.iter().enumerate().map(|(index, route)| path_line(&(index + 1).to_string(), route)));
+    lines.join("\n")
+}
+
+/// Route d'un autre routeur vers la destination, demandée par une `RouteRequest`
+async fn query_path(state: &Arc<AppState>, destination: &str, router: &str) -> String {
+    let Ok(target) = destination.parse::<Ipv4Network>().map(Prefix::new) else {
+        return format!("Argument invalide: '{}'. Usage: path <préfixe|adresse> [routeur]", destination);
+    };
+    let Ok(router) = router.parse::<std::net::Ipv4Addr>() else {
+        return format!("Argument invalide: '{}' n'est pas une adresse de routeur", router);
+    };
+    match crate::route_query::query(state, router, target).await {
+        Ok(response) => crate::route_query::describe(&response),
+        Err(e) => format!("Échec: {}", e),
+    }
+}
+
+/// Routeurs et liens dont la panne couperait le réseau ; les pseudo-nœuds des segments à
+/// accès multiple sont désignés par leur préfixe
+fn spof_report(analysis: &crate::spof::SinglePointsOfFailure) -> String {
+    if analysis.is_empty() {
+        return "Aucun point unique de défaillance : chaque routeur et chaque lien a un secours".to_string();
+    }
+    let node = |name: &String| if name.contains('/') { format!("segment {}", name) } else { name.clone() };
+    let mut lines = vec!["Points uniques de défaillance:".to_string()];
+    if !analysis.routers.is_empty() {
+        lines.push(format!("  Routeurs : {}", analysis.routers.iter().map(node).collect::<Vec<_>>().join(", ")));
+    }
+    if !analysis.links.is_empty() {
+        lines.push(format!("  Liens : {}", analysis.links.iter().map(|(a, b)| format!("{} - {}", node(a), node(b))).collect::<Vec<_>>().join(", ")));
+    }
+    lines.join("\n")
+}
+
+/// Collisions d'identifiant détectées sur les originateurs de ce routeur
+fn conflicts_report(conflicts: &[crate::collision::Conflict], now: u64) -> String {
+    if conflicts.is_empty() {
+        return "Aucune collision d'identifiant détectée".to_string();
+    }
+    conflicts.iter()
+        .map(|conflict| format!("Collision sur {} : LSA concurrents relayés par {}, {} fois (première il y a {} s, dernière il y a {} s)",
+            conflict.originator, conflict.source, conflict.count,
+            now.saturating_sub(conflict.first_seen), now.saturating_sub(conflict.last_seen)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Contenu de la LSDB en tableau : une ligne par LSA, triées par type puis originateur
+pub fn lsdb_table(lsdb: &crate::lsdb::Lsdb, now: u64) -> String {
+    if lsdb.is_empty() {
+        return "LSDB vide".to_string();
+    }
+    let sorted = |mut items: Vec<String>| {
+        items.sort();
+        if items.is_empty() { "-".to_string() } else { items.join(", ") }
+    };
+    let mut rows: Vec<[String; 6]> = Vec::new();
+    let mut routers: Vec<_> = lsdb.router_lsas().collect();
+    routers.sort_by(|a, b| a.originator.cmp(&b.originator));
+    for lsa in routers {
+        rows.push([
+            if lsa.stub_only { "router (stub)" } else { "router" }.to_string(),
+            lsa.originator.clone(),
+            lsa.seq_num.to_string(),
+            format!("{}s", now.saturating_sub(lsa.installed_at)),
+            sorted(lsa.networks.keys().map(Prefix::to_string).collect()),
+            sorted(lsa.neighbors.iter().map(|neighbor| neighbor.display_name()).collect()),
+        ]);
+    }
+    let mut networks: Vec<_> = lsdb.network_lsas().collect();
+    networks.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+    for lsa in networks {
+        rows.push([
+            "network".to_string(),
+            lsa.advertising_router.clone(),
+            lsa.seq_num.to_string(),
+            format!("{}s", now.saturating_sub(lsa.installed_at)),
+            lsa.prefix.clone(),
+            sorted(lsa.attached_routers.clone()),
+        ]);
+    }
+    let mut externals: Vec<_> = lsdb.external_lsas().collect();
+    externals.sort_by(|a, b| a.originator.cmp(&b.originator));
+    for lsa in externals {
+        rows.push([
+            "external".to_string(),
+            lsa.originator.clone(),
+            lsa.seq_num.to_string(),
+            format!("{}s", now.saturating_sub(lsa.installed_at)),
+            sorted(lsa.routes.iter().map(|(prefix, metric)| format!("{} ({})", prefix, metric)).collect()),
+            "-".to_string(),
+        ]);
+    }
+    let mut opaques: Vec<_> = lsdb.opaque_lsas().collect();
+    opaques.sort_by(|a, b| a.originator.cmp(&b.originator));
+    for lsa in opaques {
+        rows.push([
+            "opaque".to_string(),
+            lsa.originator.clone(),
+            lsa.seq_num.to_string(),
+            format!("{}s", now.saturating_sub(lsa.installed_at)),
+            lsa.tlvs.iter().map(|tlv| format!("TLV {} ({} octets)", tlv.tlv_type, tlv.value.len())).collect::<Vec<_>>().join(", "),
+            "-".to_string(),
+        ]);
+    }
+
+    let header = ["Type", "Originateur", "Seq", "Âge", "Préfixes annoncés", "Voisins"].map(String::from);
+    let mut widths = header.clone().map(|column| column.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            row.iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Nombre d'entrées renvoyées par défaut par la commande `trace`
+const DEFAULT_TRACE_LINES: usize = 20;
+
+/// Commande `trace [n] | trace on | trace off | trace clear | trace save <fichier>`
+fn handle_trace_command(command: &str) -> String {
+    use crate::trace;
+
+    let args: Vec<&str> = command.split_whitespace().skip(1).collect();
+    match args.as_slice() {
+        ["on"] => {
+            trace::set_enabled(true);
+            "Capture des messages activée".to_string()
+        }
+        ["off"] => {
+            trace::set_enabled(false);
+            "Capture des messages désactivée".to_string()
+        }
+        ["clear"] => {
+            trace::clear();
+            "Tampon de capture vidé".to_string()
+        }
+        ["save", path] => match trace::save(path) {
+            Ok(count) => format!("{} messages enregistrés dans {}", count, path),
+            Err(e) => format!("Échec de l'enregistrement de la capture: {}", e),
+        },
+        [] | [_] => {
+            let limit = match args.first() {
+                Some(n) => match n.parse::<usize>() {
+                    Ok(n) => n,
+                    Err(_) => return format!("Argument invalide: '{}'. Usage: trace [n|on|off|clear|save <fichier>]", n),
+                },
+                None => DEFAULT_TRACE_LINES,
+            };
+            let entries = trace::recent(limit);
+            let status = if trace::is_enabled() { "active" } else { "inactive" };
+            if entries.is_empty() {
+                format!("Capture {} - aucun message enregistré", status)
+            } else {
+                let lines: Vec<String> = entries.iter().map(|entry| entry.to_string()).collect();
+                format!("Capture {} - {} derniers messages:\n{}", status, entries.len(), lines.join("\n"))
+            }
+        }
+        _ => "Usage: trace [n|on|off|clear|save <fichier>]".to_string(),
+    }
+}
@@ -0,0 +1,166 @@
+use crate::error::{AppError, Result};
+use crate::read_config::ControlRole;
+use crate::transport::Socket;
+use crate::types::ControlMessage;
+use crate::AppState;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Écart toléré entre l'horodatage d'une commande signée et l'horloge du routeur
+const CONTROL_WINDOW_SEC: u64 = 30;
+
+/// Signatures déjà acceptées pendant la fenêtre de validité, pour refuser leur rejeu
+#[derive(Debug, Default)]
+pub struct ControlReplay {
+    seen: HashMap<String, u64>,
+}
+
+impl ControlReplay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Vrai si la signature n'a pas encore été vue ; l'enregistre dans ce cas
+    fn accept(&mut self, signature: &str, timestamp: u64, now: u64) -> bool {
+        self.seen.retain(|_, seen| now.abs_diff(*seen) <= CONTROL_WINDOW_SEC);
+        self.seen.insert(signature.to_string(), timestamp).is_none()
+    }
+}
+
+/// Auteur authentifié d'une commande
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caller {
+    pub user: Option<String>,
+    pub role: ControlRole,
+}
+
+impl Caller {
+    pub fn name(&self) -> &str {
+        self.user.as_deref().unwrap_or("anonymous")
+    }
+}
+
+/// Droits nécessaires : les commandes qui modifient l'état du routeur sont réservées aux admins
+pub fn required_role(command: &str) -> ControlRole {
+    let mut words = crate::control::split_json_flag(command).0.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some("connexion" | "routing-table" | "neighbors" | "neighbor-detail" | "pending-routes" | "filtered-routes" | "lsdb" | "path" | "paths" | "spof" | "mtu" | "sessions" | "history" | "ping" | "probe" | "metrics" | "conflicts"), _) => ControlRole::ReadOnly,
+        // `trace [n]` consulte le tampon ; on/off/clear/save le modifient
+        (Some("trace"), None) => ControlRole::ReadOnly,
+        (Some("trace"), Some(argument)) if argument.parse::<usize>().is_ok() => ControlRole::ReadOnly,
+        // `vrf <nom> <commande>` : les droits de la commande exécutée dans la VRF
+        (Some("vrf"), None) => ControlRole::ReadOnly,
+        (Some("vrf"), Some(_)) => required_role(&words.collect::<Vec<_>>().join(" ")),
+        _ => ControlRole::Admin,
+    }
+}
+
+fn mac(token: &str, user: &str, timestamp: u64, command: &str) -> Result<HmacSha256> {
+    let mut mac = HmacSha256::new_from_slice(token.as_bytes())
+        .map_err(|e| AppError::CryptoError(format!("Invalid HMAC key: {}", e)))?;
+    mac.update(user.as_bytes());
+    mac.update(b"\n");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b"\n");
+    mac.update(command.as_bytes());
+    Ok(mac)
+}
+
+/// Construit une commande signée avec le secret de l'utilisateur
+pub fn signed_command(user: &str, token: &str, command: &str, timestamp: u64) -> Result<ControlMessage> {
+    let signature = mac(token, user, timestamp, command)?.finalize().into_bytes();
+    Ok(ControlMessage {
+        message_type: 3,
+        command: command.to_string(),
+        user: Some(user.to_string()),
+        timestamp: Some(timestamp),
+        signature: Some(base64::engine::general_purpose::STANDARD.encode(signature)),
+        request_id: None,
+    })
+}
+
+/// Identifie l'auteur d'une commande : signature valide, récente et non rejouée d'un
+/// utilisateur déclaré, ou droits anonymes de la configuration
+pub async fn authenticate(state: &AppState, message: &ControlMessage) -> std::result::Result<Caller, String> {
+    let control = state.config().await.control;
+    let Some(user_name) = &message.user else {
+        return control.anonymous_role()
+            .map(|role| Caller { user: None, role })
+            .ok_or_else(|| "commande non signée".to_string());
+    };
+    let user = control.user(user_name).ok_or_else(|| format!("utilisateur inconnu '{}'", user_name))?;
+    let (Some(timestamp), Some(signature)) = (message.timestamp, &message.signature) else {
+        return Err("signature manquante".to_string());
+    };
+    let now = crate::clock::unix_secs();
+    if now.abs_diff(timestamp) > CONTROL_WINDOW_SEC {
+        return Err("horodatage hors de la fenêtre de validité".to_string());
+    }
+    let valid = base64::engine::general_purpose::STANDARD.decode(signature).ok()
+        .is_some_and(|signature| {
+            mac(&user.token, user_name, timestamp, &message.command).is_ok_and(|mac| mac.verify_slice(&signature).is_ok())
+        });
+    if !valid {
+        return Err("signature invalide".to_string());
+    }
+    if !state.control_replay.lock().await.accept(signature, timestamp, now) {
+        return Err("commande rejouée".to_string());
+    }
+    Ok(Caller { user: Some(user.name.clone()), role: user.role })
+}
+
+/// Consigne une commande exécutée ou refusée dans les journaux et le fichier d'audit
+pub async fn audit(state: &AppState, user: &str, source: &str, command: &str, outcome: &str) {
+    info!(target: "audit", "[AUDIT] {} from {} ran '{}': {}", user, source, command, outcome);
+    let Some(path) = state.config().await.control.audit_log else {
+        return;
+    };
+    let entry = serde_json::json!({
+        "timestamp": crate::clock::unix_secs(),
+        "user": user,
+        "source": source,
+        "command": command,
+        "outcome": outcome,
+    });
+    let written = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", entry));
+    if let Err(e) = written {
+        warn!("Failed to write audit log {}: {}", path.display(), e);
+    }
+}
+
+/// Exécute une commande reçue du réseau si son auteur en a le droit
+pub async fn execute_authorized(state: &Arc<AppState>, socket: &Arc<Socket>, message: &ControlMessage, source: SocketAddr) -> String {
+    let source = source.to_string();
+    let caller = match authenticate(state, message).await {
+        Ok(caller) => caller,
+        Err(reason) => {
+            let user = message.user.as_deref().unwrap_or("anonymous");
+            audit(state, user, &source, &message.command, &format!("denied ({})", reason)).await;
+            return format!("Accès refusé: {}", reason);
+        }
+    };
+    execute_as(state, socket, &caller, &source, &message.command).await
+}
+
+/// Exécute une commande pour un auteur déjà identifié, si son rôle le permet
+pub async fn execute_as(state: &Arc<AppState>, socket: &Arc<Socket>, caller: &Caller, source: &str, command: &str) -> String {
+    if caller.role < required_role(command) {
+        audit(state, caller.name(), source, command, "denied (admin role required)").await;
+        return "Accès refusé: droits administrateur requis".to_string();
+    }
+    let response = crate::control::execute_command(state, socket, command).await;
+    audit(state, caller.name(), source, command, "allowed").await;
+    response
+}
@@ -0,0 +1,134 @@
+use crate::control_auth::{execute_as, Caller};
+use crate::read_config::ControlRole;
+use crate::transport::Socket;
+use crate::AppState;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tracing::{debug, error, info, warn};
+
+/// Droits d'un client local : admin pour root ou l'utilisateur du démon (propriétaire
+/// de la socket), consultation seule pour les autres
+fn role_for(uid: u32, owner: Option<u32>) -> ControlRole {
+    if uid == 0 || Some(uid) == owner {
+        ControlRole::Admin
+    } else {
+        ControlRole::ReadOnly
+    }
+}
+
+/// Une connexion = une commande terminée par un saut de ligne ; la réponse est
+/// renvoyée telle quelle puis la connexion est fermée
+async fn serve(stream: UnixStream, state: &Arc<AppState>, socket: &Arc<Socket>, owner: Option<u32>, path: &Path) -> std::io::Result<()> {
+    let uid = stream.peer_cred()?.uid();
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(reader.take(crate::packet::MAX_COMMAND_LEN as u64 + 1)).read_line(&mut line).await?;
+    let command = line.trim();
+    if command.is_empty() {
+        return Ok(());
+    }
+    let caller = Caller { user: Some(format!("uid {}", uid)), role: role_for(uid, owner) };
+    let source = format!("unix:{}", path.display());
+    let response = execute_as(state, socket, &caller, &source, command).await;
+    writer.write_all(response.as_bytes()).await?;
+    writer.shutdown().await
+}
+
+/// Identifiant d'un groupe donné par son numéro ou son nom (`/etc/group`)
+fn group_id(group: &str) -> Option<u32> {
+    if let Ok(gid) = group.parse() {
+        return Some(gid);
+    }
+    std::fs::read_to_string("/etc/group").ok()?
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+        .find(|fields| fields.first() == Some(&group))
+        .and_then(|fields| fields.get(2)?.parse().ok())
+}
+
+/// Groupe `socket_group` puis mode 0660 : la socket, créée en 0600 (voir `bind`), n'est
+/// ouverte qu'au démon, à root et aux membres du groupe, et non à tout utilisateur de l'hôte
+fn restrict(path: &Path, group: Option<&str>) {
+    if let Some(group) = group {
+        match group_id(group) {
+            Some(gid) => {
+                if let Err(e) = std::os::unix::fs::chown(path, None, Some(gid)) {
+                    warn!("Failed to give {} to group {}: {}", path.display(), group, e);
+                }
+            }
+            None => warn!("Unknown group {} for control socket {}", group, path.display()),
+        }
+    }
+    if let Err(e) = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o660)) {
+        warn!("Failed to set permissions on {}: {}", path.display(), e);
+    }
+}
+
+/// Crée la socket sous un umask 0177 : elle n'est jamais, même un instant, accessible à
+/// d'autres que le démon avant `restrict`
+fn bind(path: &Path) -> std::io::Result<UnixListener> {
+    // SAFETY : umask ne fait que remplacer le masque du processus, rétabli juste après
+    let previous = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(path);
+    unsafe { libc::umask(previous) };
+    listener
+}
+
+/// Ouvre la socket de contrôle locale (voir `restrict`) ; seuls root et l'utilisateur du
+/// démon obtiennent les commandes d'administration, les membres du groupe la consultation
+pub fn spawn_control_socket(state: Arc<AppState>, socket: Arc<Socket>, path: PathBuf) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        // Socket laissée par une exécution précédente ; tout autre fichier est laissé en place
+        match std::fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_socket() => {
+                let _ = std::fs::remove_file(&path);
+            }
+            Ok(_) => {
+                error!("Control socket path {} exists and is not a socket, not replacing it", path.display());
+                return;
+            }
+            Err(_) => {}
+        }
+        let listener = match bind(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to bind control socket {}: {}", path.display(), e);
+                return;
+            }
+        };
+        restrict(&path, state.config().await.control.socket_group.as_deref());
+        let owner = std::fs::metadata(&path).map(|metadata| metadata.uid()).ok();
+        info!("Control socket listening on {}", path.display());
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("Control socket accept failed: {}", e);
+                    continue;
+                }
+            };
+            let (state, socket, path) = (Arc::clone(&state), Arc::clone(&socket), path.clone());
+            tokio::spawn(async move {
+                if let Err(e) = serve(stream, &state, &socket, owner, &path).await {
+                    debug!("Control socket connection failed: {}", e);
+                }
+            });
+        }
+    })
+}
+
+/// Envoie une commande au démon par sa socket locale et renvoie la réponse (CLI)
+pub async fn send_command(path: &Path, command: &str) -> std::io::Result<String> {
+    let mut stream = UnixStream::connect(path).await?;
+    stream.write_all(format!("{}\n", command).as_bytes()).await?;
+    stream.shutdown().await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(response)
+}
@@ -0,0 +1,229 @@
+use crate::error::Result;
+use crate::interfaces::InterfaceProvider;
+use crate::read_config::{RouterConfig, VrfConfig};
+use crate::route_backend::RouteBackend;
+use crate::AppState;
+use tracing::{info, warn};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::transport::Socket;
+use tokio::task::JoinHandle;
+
+/// Instance de routeur embarquable : sockets, état partagé et tâches de fond
+pub struct RouterDaemon {
+    state: Arc<AppState>,
+    socket: Arc<Socket>,
+    tasks: Vec<JoinHandle<()>>,
+    main_loop: Option<JoinHandle<Result<()>>>,
+}
+
+/// Accès à un routeur en cours d'exécution, clonable et utilisable depuis d'autres tâches
+#[derive(Clone)]
+pub struct RouterHandle {
+    state: Arc<AppState>,
+    socket: Arc<Socket>,
+}
+
+impl RouterDaemon {
+    /// Ouvre les sockets et prépare l'état du routeur sans démarrer le protocole ; les
+    /// interfaces rattachées à une VRF sont laissées à l'instance de celle-ci
+    pub async fn new(config: RouterConfig, config_path: Option<PathBuf>) -> Result<Self> {
+        Self::open(config.for_vrf(None), config_path, None).await
+    }
+
+    /// Prépare l'instance de la VRF `vrf` : sockets liées à son périphérique, ses seules
+    /// interfaces et routes installées dans sa table du noyau
+    pub async fn new_in_vrf(config: &RouterConfig, config_path: Option<PathBuf>, vrf: &VrfConfig) -> Result<Self> {
+        Self::open(config.for_vrf(Some(&vrf.name)), config_path, Some(&vrf.name)).await
+    }
+
+    async fn open(config: RouterConfig, config_path: Option<PathBuf>, vrf: Option<&str>) -> Result<Self> {
+        // Les threads du runtime doivent déjà être dans l'espace de noms (voir `netns::enter`)
+        if let Some(netns) = &config.netns {
+            if !crate::netns::is_current(netns)? {
+                return Err(crate::error::AppError::ConfigError(format!(
+                    "Network namespace {} must be entered before the runtime starts", netns,
+                )));
+            }
+        }
+        crate::trace::configure(config.trace.enabled, config.trace.capacity);
+
+        // Une VRF ne voit que ses interfaces, relevées à l'ouverture
+        let interfaces = match vrf {
+            None => InterfaceProvider::System,
+            Some(_) => InterfaceProvider::Static(InterfaceProvider::System.interfaces()
+                .into_iter()
+                .filter(|iface| config.interface(&iface.name).is_some())
+                .collect()),
+        };
+        let socket = crate::init::init_socket(&config, vrf).await?;
+        let flood_sockets = crate::init::init_flood_sockets(config.transport, config.qos.dscp, interfaces.local_ipv4_addresses(), vrf)?;
+        let table = config.route_install.table;
+        let routes: Arc<dyn RouteBackend> = if config.route_install.dry_run {
+            Arc::new(crate::route_backend::DryRunBackend::over(crate::route_backend::system_backend(table)))
+        } else {
+            crate::route_backend::system_backend(table)
+        };
+        info!("Using {} route backend (table {})", routes.name(), table);
+        Self::build(config, config_path, socket, interfaces, routes, flood_sockets, vrf.map(str::to_string))
+    }
+
+    /// Prépare un routeur sur une socket et des interfaces fournies (routeur simulé, tests)
+    pub fn with_socket(
+        config: RouterConfig,
+        config_path: Option<PathBuf>,
+        socket: Arc<Socket>,
+        interfaces: InterfaceProvider,
+        routes: Arc<dyn RouteBackend>,
+        flood_sockets: HashMap<String, Arc<Socket>>,
+    ) -> Result<Self> {
+        Self::build(config, config_path, socket, interfaces, routes, flood_sockets, None)
+    }
+
+    fn build(
+        config: RouterConfig,
+        config_path: Option<PathBuf>,
+        socket: Arc<Socket>,
+        interfaces: InterfaceProvider,
+        routes: Arc<dyn RouteBackend>,
+        flood_sockets: HashMap<String, Arc<Socket>>,
+        vrf: Option<String>,
+    ) -> Result<Self> {
+        let router_ip = interfaces.router_ip()?;
+        let key = config.decoded_key();
+        let state = crate::init::init_state(router_ip, config, config_path, key, Arc::clone(&socket), interfaces, routes, flood_sockets, vrf);
+        Ok(Self {
+            state,
+            socket,
+            tasks: Vec::new(),
+            main_loop: None,
+        })
+    }
+
+    /// Calcule les routes initiales puis lance les tâches périodiques et la boucle de réception
+    pub async fn start(&mut self) -> Result<()> {
+        if self.main_loop.is_some() {
+            return Ok(());
+        }
+        crate::persist::restore(&self.state).await;
+        if let Err(e) = crate::dijkstra::calculate_and_update_optimal_routes(Arc::clone(&self.state)).await {
+            warn!("Échec du calcul initial des routes: {}", e);
+        }
+
+        let socket = &self.socket;
+        let state = &self.state;
+        self.tasks.push(crate::tasks::spawn_hello_and_lsa_tasks(Arc::clone(socket), Arc::clone(state)));
+        self.tasks.push(crate::tasks::spawn_neighbor_timeout_task(Arc::clone(state)));
+        self.tasks.push(crate::tasks::spawn_redistribution_task(Arc::clone(state)));
+        self.tasks.push(crate::tasks::spawn_retransmission_task(Arc::clone(socket), Arc::clone(state)));
+        self.tasks.push(crate::tasks::spawn_route_reconciliation_task(Arc::clone(state)));
+        self.tasks.push(crate::spf::spawn_spf_task(Arc::clone(state)));
+        self.tasks.push(crate::route_queue::spawn_route_install_task(Arc::clone(state)));
+        self.tasks.push(crate::path_mtu::spawn_mtu_discovery_task(Arc::clone(state)));
+        if state.config().await.interfaces.iter().any(|iface| iface.transport != crate::read_config::LsaTransport::Udp) {
+            self.tasks.push(crate::tcp_session::spawn_tcp_session_task(Arc::clone(state)));
+        }
+        let http = state.config().await.http;
+        if http.enabled {
+            self.tasks.push(crate::http_api::spawn_http_server(Arc::clone(state), http.listen));
+        }
+        if state.config().await.interfaces.iter().any(|iface| iface.protocol == crate::read_config::InterfaceProtocol::Ospfv2) {
+            #[cfg(unix)]
+            self.tasks.push(crate::ospfv2_interop::spawn_interop_task(Arc::clone(state)));
+            #[cfg(not(unix))]
+            warn!("OSPFv2 interfaces ignored: raw sockets are not supported on this platform");
+        }
+        if state.config().await.interfaces.iter().any(|iface| iface.protocol == crate::read_config::InterfaceProtocol::Rip) {
+            self.tasks.push(crate::rip_gateway::spawn_rip_task(Arc::clone(state)));
+        }
+        if state.config().await.mdns.enabled {
+            self.tasks.push(crate::discovery::spawn_mdns_task(Arc::clone(state)));
+        }
+        if state.config().await.bgp.enabled {
+            self.tasks.push(crate::bgp_lite::spawn_bgp_task(Arc::clone(state)));
+        }
+        let grpc = state.config().await.grpc;
+        if grpc.enabled {
+            self.tasks.push(crate::grpc_api::spawn_grpc_server(Arc::clone(state), grpc.listen));
+        }
+        #[cfg(unix)]
+        self.tasks.push(crate::tasks::spawn_sighup_task(Arc::clone(socket), Arc::clone(state)));
+        if let Some(path) = state.config().await.control.socket {
+            #[cfg(unix)]
+            self.tasks.push(crate::control_socket::spawn_control_socket(Arc::clone(state), Arc::clone(socket), path));
+            #[cfg(not(unix))]
+            warn!("Control socket {} ignored: Unix domain sockets are not supported on this platform", path.display());
+        }
+
+        self.main_loop = Some(tokio::spawn(crate::packet_loop::main_loop(Arc::clone(socket), Arc::clone(state))));
+        info!("Router {} started", state.local_ip);
+        Ok(())
+    }
+
+    /// Attend la fin de la boucle de réception (erreur de socket) ; retourne immédiatement si le routeur est arrêté
+    pub async fn wait(&mut self) -> Result<()> {
+        match self.main_loop.as_mut() {
+            Some(main_loop) => match main_loop.await {
+                Ok(result) => result,
+                Err(e) if e.is_cancelled() => Ok(()),
+                Err(e) => Err(crate::error::AppError::NetworkError(format!("Main loop panicked: {}", e))),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Arrêt propre : empoisonne les routes annoncées, retire les routes du noyau et stoppe les tâches
+    pub async fn stop(&mut self) {
+        let Some(main_loop) = self.main_loop.take() else {
+            return;
+        };
+        main_loop.abort();
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+        crate::shutdown::graceful_shutdown(&self.socket, &self.state).await;
+    }
+
+    pub fn handle(&self) -> RouterHandle {
+        RouterHandle {
+            state: Arc::clone(&self.state),
+            socket: Arc::clone(&self.socket),
+        }
+    }
+
+    pub fn state(&self) -> &Arc<AppState> {
+        &self.state
+    }
+}
+
+impl RouterHandle {
+    pub fn state(&self) -> &Arc<AppState> {
+        &self.state
+    }
+
+    /// Abonnement aux changements d'état du protocole (voisins, LSA, SPF, routes)
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<crate::events::RouterEvent> {
+        self.state.events.subscribe()
+    }
+
+    /// Exécute une commande de contrôle, comme si elle venait du CLI
+    pub async fn command(&self, command: &str) -> String {
+        crate::control::execute_command(&self.state, &self.socket, command).await
+    }
+
+    /// Copie de la table de routage (destination -> prochain saut, état)
+    pub async fn routing_table(&self) -> std::collections::HashMap<crate::types::Prefix, (String, crate::types::RouteState)> {
+        self.state.routing_table.read().await.clone()
+    }
+
+    /// Rend l'instance `vrf` joignable par les commandes `vrf <nom> ...` de ce routeur
+    pub async fn attach_vrf(&self, vrf: &str, instance: RouterHandle) {
+        self.state.vrf_instances.write().await.insert(vrf.to_string(), instance);
+    }
+
+    /// Copie de la table des voisins
+    pub async fn neighbors(&self) -> std::collections::HashMap<String, crate::types::Neighbor> {
+        self.state.neighbors.read().await.clone()
+    }
+}
@@ -0,0 +1,142 @@
+use crate::read_config::{FlapDampingConfig, LsaThrottleConfig};
+use serde::Serialize;
+use tracing::{info, warn};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Pénalité d'un voisin, décroissant exponentiellement depuis `updated`
+#[derive(Debug, Clone)]
+struct FlapState {
+    penalty: f64,
+    updated: Instant,
+    suppressed: bool,
+    /// Adjacence perdue pendant la quarantaine : son rétablissement n'est pas pénalisé
+    rejoin_pending: bool,
+}
+
+impl FlapState {
+    fn decay(&mut self, half_life_sec: u64) {
+        let elapsed = self.updated.elapsed().as_secs_f64();
+        self.penalty *= 0.5f64.powf(elapsed / half_life_sec.max(1) as f64);
+        self.updated = Instant::now();
+    }
+}
+
+/// État d'amortissement d'un voisin, pour `neighbor-detail`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DampingStatus {
+    pub penalty: u64,
+    pub suppressed: bool,
+    /// Supprimé avec la quarantaine activée : ses HELLO sont ignorés
+    pub quarantined: bool,
+}
+
+/// Limitation du débit des LSA et amortissement des voisins instables
+#[derive(Debug, Default)]
+pub struct LsaDamping {
+    /// Dernier LSA accepté, par originateur
+    last_arrival: HashMap<String, Instant>,
+    /// Dernier LSA déclenché émis, par adresse d'interface locale
+    last_origination: HashMap<String, Instant>,
+    flaps: HashMap<String, FlapState>,
+}
+
+impl LsaDamping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepte un LSA de l'originateur si le précédent est assez ancien (MinLSArrival)
+    pub fn accept_arrival(&mut self, originator: &str, config: &LsaThrottleConfig) -> bool {
+        Self::throttle(&mut self.last_arrival, originator, config.min_arrival_ms)
+    }
+
+    /// Autorise l'émission d'un LSA déclenché sur l'interface (MinLSInterval)
+    pub fn allow_origination(&mut self, local_ip: &str, config: &LsaThrottleConfig) -> bool {
+        Self::throttle(&mut self.last_origination, local_ip, config.min_origination_ms)
+    }
+
+    /// Note l'émission d'un LSA périodique, qui repousse les LSA déclenchés
+    pub fn record_origination(&mut self, local_ip: &str) {
+        self.last_origination.insert(local_ip.to_string(), Instant::now());
+    }
+
+    fn throttle(last: &mut HashMap<String, Instant>, key: &str, min_interval_ms: u64) -> bool {
+        let min_interval = Duration::from_millis(min_interval_ms);
+        if last.get(key).is_some_and(|previous| previous.elapsed() < min_interval) {
+            return false;
+        }
+        last.insert(key.to_string(), Instant::now());
+        true
+    }
+
+    /// Pénalise un changement d'état du voisin ; renvoie vrai s'il vient d'être supprimé
+    pub fn record_flap(&mut self, neighbor_ip: &str, config: &FlapDampingConfig) -> bool {
+        if !config.enabled {
+            return false;
+        }
+        let flap = self.flaps.entry(neighbor_ip.to_string()).or_insert_with(|| FlapState {
+            penalty: 0.0,
+            updated: Instant::now(),
+            suppressed: false,
+            rejoin_pending: false,
+        });
+        flap.decay(config.half_life_sec);
+        // En quarantaine, l'expiration de l'adjacence vient de nous : ni elle ni le
+        // rétablissement qui suit ne prolongent la peine
+        if flap.suppressed && config.quarantine {
+            flap.rejoin_pending = true;
+            return false;
+        }
+        if std::mem::take(&mut flap.rejoin_pending) {
+            return false;
+        }
+        // Plafond : la pénalité retombe au seuil de réutilisation en au plus max_suppress_sec
+        let ceiling = config.reuse_threshold as f64
+            * 2f64.powf(config.max_suppress_sec as f64 / config.half_life_sec.max(1) as f64);
+        flap.penalty = (flap.penalty + config.penalty as f64).min(ceiling);
+        if !flap.suppressed && flap.penalty >= config.suppress_threshold as f64 {
+            flap.suppressed = true;
+            warn!("[DAMPING] Neighbor {} suppressed (penalty {:.0})", neighbor_ip, flap.penalty);
+            return true;
+        }
+        false
+    }
+
+    /// Indique si le voisin est supprimé, en le réutilisant si sa pénalité a assez décru
+    pub fn is_suppressed(&mut self, neighbor_ip: &str, config: &FlapDampingConfig) -> bool {
+        if !config.enabled {
+            return false;
+        }
+        let Some(flap) = self.flaps.get_mut(neighbor_ip) else {
+            return false;
+        };
+        flap.decay(config.half_life_sec);
+        if flap.suppressed && flap.penalty < config.reuse_threshold as f64 {
+            flap.suppressed = false;
+            info!("[DAMPING] Neighbor {} reused (penalty {:.0})", neighbor_ip, flap.penalty);
+        }
+        if !flap.suppressed && !flap.rejoin_pending && flap.penalty < 1.0 {
+            self.flaps.remove(neighbor_ip);
+            return false;
+        }
+        flap.suppressed
+    }
+
+    /// Vrai si les HELLO du voisin doivent être ignorés (supprimé, quarantaine activée)
+    pub fn is_quarantined(&mut self, neighbor_ip: &str, config: &FlapDampingConfig) -> bool {
+        config.quarantine && self.is_suppressed(neighbor_ip, config)
+    }
+
+    /// Pénalité courante du voisin, s'il en a une
+    pub fn status(&mut self, neighbor_ip: &str, config: &FlapDampingConfig) -> Option<DampingStatus> {
+        let suppressed = self.is_suppressed(neighbor_ip, config);
+        let flap = self.flaps.get(neighbor_ip)?;
+        Some(DampingStatus {
+            penalty: flap.penalty as u64,
+            suppressed,
+            quarantined: suppressed && config.quarantine,
+        })
+    }
+}
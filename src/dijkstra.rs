@@ -1,368 +1,289 @@
-use std::collections::{HashMap, BinaryHeap, HashSet};
-use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use log::{info, debug, warn, error};
-use crate::types::{RouteState, Neighbor};
-use crate::error::{AppError, Result};
+use tracing::{info, debug, warn};
+use crate::types::{Prefix, RouteOrigin, RouteState};
+use crate::lsdb::LsaType;
+use crate::error::Result;
 use crate::AppState;
-use futures::stream::TryStreamExt;
+use crate::events::RouterEvent;
+use crate::route_diff::RouteDiff;
+use crate::route_queue::RouteOp;
 
-// Nœud dans le graphe
-#[derive(Debug, Clone)]
-pub struct NetworkNode {
-    pub router_id: String,
-    pub interfaces: Vec<InterfaceInfo>,
-    pub is_reachable: bool,
-}
-
-#[derive(Debug, Clone)]
-pub struct InterfaceInfo {
-    pub name: String,
-    pub network: String,
-    pub capacity_mbps: u32,
-    pub is_active: bool,
-    pub connected_to: Option<String>,
-}
-
-/// Représente un lien
-#[derive(Debug, Clone)]
-pub struct NetworkLink {
-    pub from: String,
-    pub to: String,
-    pub cost: u32,
-    pub capacity_mbps: u32,
-    pub is_active: bool,
-    pub hop_count: u32,
-}
-
-#[derive(Debug, Clone, Eq, PartialEq)]
-struct DijkstraNode {
-    router_id: String,
-    total_cost: u32,
-    hop_count: u32,
-    bottleneck_capacity: u32,
-    path: Vec<String>,
-}
-
-impl Ord for DijkstraNode {
-    fn cmp(&self, other: &Self) -> Ordering {
-        // (1) coût OSPF, (2) nombre de sauts, (3) capacité du goulot d'étranglement
-        other.total_cost.cmp(&self.total_cost)
-            .then_with(|| other.hop_count.cmp(&self.hop_count)) 
-            .then_with(|| self.bottleneck_capacity.cmp(&other.bottleneck_capacity))
-    }
-}
-
-impl PartialOrd for DijkstraNode {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct NetworkTopology {
-    pub nodes: HashMap<String, NetworkNode>,
-    pub links: Vec<NetworkLink>,
-}
-
-impl NetworkTopology {
-    pub fn new() -> Self {
-        Self {
-            nodes: HashMap::new(),
-            links: Vec::new(),
-        }
-    }
-
-    pub fn add_router(&mut self, router_id: String, interfaces: Vec<InterfaceInfo>) {
-        let node = NetworkNode {
-            router_id: router_id.clone(),
-            interfaces,
-            is_reachable: true,
-        };
-        self.nodes.insert(router_id, node);
-    }
-
-    pub fn add_link(&mut self, from: String, to: String, capacity_mbps: u32, is_active: bool) {
-        let cost = calculate_ospf_cost(capacity_mbps, is_active);
-        // Lien direct
-        self.links.push(NetworkLink {
-            from: from.clone(),
-            to: to.clone(),
-            cost,
-            capacity_mbps,
-            is_active,
-            hop_count: 1,
-        });
-        // Lien de retour (bidirectionnel)
-        self.links.push(NetworkLink {
-            from: to,
-            to: from,
-            cost,
-            capacity_mbps,
-            is_active,
-            hop_count: 1,
-        });
-    }
-
-    pub fn add_link_with_min_capacity(&mut self, from: String, to: String, local_capacity: u32, neighbor_capacity: u32, is_active: bool) {
-        let min_capacity = local_capacity.min(neighbor_capacity);
-        let cost = calculate_ospf_cost(min_capacity, is_active);
-        // Lien direct
-        self.links.push(NetworkLink {
-            from: from.clone(),
-            to: to.clone(),
-            cost,
-            capacity_mbps: min_capacity,
-            is_active,
-            hop_count: 1,
-        });
-        // Lien de retour (bidirectionnel)
-        self.links.push(NetworkLink {
-            from: to,
-            to: from,
-            cost,
-            capacity_mbps: min_capacity,
-            is_active,
-            hop_count: 1,
-        });
-    }
-
-    pub fn get_active_neighbors(&self, router_id: &str) -> Vec<&NetworkLink> {
-        self.links.iter()
-            .filter(|link| link.from == router_id && link.is_active)
-            .collect()
-    }
-
-    pub fn find_link(&self, from: &str, to: &str) -> Option<&NetworkLink> {
-        self.links.iter()
-            .find(|link| link.from == from && link.to == to)
-    }
-
-    /// 1) Plus court chemin (nombre de sauts), 2) Capacité goulot, 3) État des liens
-    pub fn calculate_shortest_paths(&self, source: &str) -> HashMap<String, RouteInfo> {
-        let mut costs: HashMap<String, u32> = HashMap::new();
-        let mut hop_counts: HashMap<String, u32> = HashMap::new();
-        let mut bottleneck_capacities: HashMap<String, u32> = HashMap::new();
-        let mut paths: HashMap<String, Vec<String>> = HashMap::new();
-        let mut visited = HashSet::new();
-        let mut heap = BinaryHeap::new();
-
-        // Initialisation avec des valeurs infinies
-        for node_id in self.nodes.keys() {
-            costs.insert(node_id.clone(), u32::MAX);
-            hop_counts.insert(node_id.clone(), u32::MAX);
-            bottleneck_capacities.insert(node_id.clone(), 0);
-            paths.insert(node_id.clone(), Vec::new());
-        }
-
-        // Nœud source
-        costs.insert(source.to_string(), 0);
-        hop_counts.insert(source.to_string(), 0);
-        bottleneck_capacities.insert(source.to_string(), u32::MAX);
-        paths.insert(source.to_string(), vec![source.to_string()]);
-
-        heap.push(DijkstraNode {
-            router_id: source.to_string(),
-            total_cost: 0,
-            hop_count: 0,
-            bottleneck_capacity: u32::MAX,
-            path: vec![source.to_string()],
-        });
-
-        // Dijkstra
-        while let Some(current) = heap.pop() {
-            if visited.contains(&current.router_id) {
-                continue;
-            }
-            visited.insert(current.router_id.clone());
-
-            // Explorer les voisins actifs uniquement
-            for link in self.get_active_neighbors(&current.router_id) {
-                if visited.contains(&link.to) {
-                    continue;
-                }
-
-                let new_cost = match current.total_cost.checked_add(link.cost) {
-                    Some(cost) => cost,
-                    None => continue,
-                };
-                
-                let new_hop_count = current.hop_count + 1;
-                let new_bottleneck_capacity = current.bottleneck_capacity.min(link.capacity_mbps);
-                
-                let current_best_cost = *costs.get(&link.to).unwrap_or(&u32::MAX);
-
-                // Mettre à jour si on a trouvé un chemin avec un meilleur coût OSPF
-                if new_cost < current_best_cost {
-                    costs.insert(link.to.clone(), new_cost);
-                    hop_counts.insert(link.to.clone(), new_hop_count);
-                    bottleneck_capacities.insert(link.to.clone(), new_bottleneck_capacity);
-                    
-                    let mut new_path = current.path.clone();
-                    new_path.push(link.to.clone());
-                    paths.insert(link.to.clone(), new_path.clone());
-
-                    heap.push(DijkstraNode {
-                        router_id: link.to.clone(),
-                        total_cost: new_cost,
-                        hop_count: new_hop_count,
-                        bottleneck_capacity: new_bottleneck_capacity,
-                        path: new_path,
-                    });
-                }
-            }
-        }
-
-        let mut routes = HashMap::new();
-        for (dest, cost) in costs {
-            if dest != source && cost != u32::MAX {
-                let path = paths.get(&dest).unwrap_or(&Vec::new()).clone();
-                let next_hop = if path.len() > 1 { path[1].clone() } else { dest.clone() };
-                
-                routes.insert(dest.clone(), RouteInfo {
-                    destination: dest.clone(),
-                    next_hop,
-                    total_cost: cost,
-                    hop_count: *hop_counts.get(&dest).unwrap_or(&0),
-                    bottleneck_capacity: *bottleneck_capacities.get(&dest).unwrap_or(&0),
-                    path,
-                    is_reachable: true,
-                });
-            }
-        }
-
-        routes
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct RouteInfo {
-    pub destination: String,
-    pub next_hop: String,
-    pub total_cost: u32,
-    pub hop_count: u32,
-    pub bottleneck_capacity: u32,
-    pub path: Vec<String>,
-    pub is_reachable: bool,
-}
-
-pub fn calculate_ospf_cost(capacity_mbps: u32, is_active: bool) -> u32 {
-    if !is_active {
-        return u32::MAX;
-    }
-    
-    // Éviter la division par zéro
-    if capacity_mbps == 0 {
-        return u32::MAX;
-    }
-    
-    // Formule OSPF standard : référence de 100 Mbps
-    let reference_bandwidth = 100_000_000u64; // 100 Mbps en bps
-    let bandwidth_bps = capacity_mbps as u64 * 1_000_000;
-    
-    // Éviter la division par zéro
-    if bandwidth_bps == 0 {
-        return u32::MAX;
-    }
-    
-    let cost = (reference_bandwidth / bandwidth_bps) as u32;
-    
-    // Coût minimum de 1
-    cost.max(1)
-}
+pub use pospf_core::dijkstra::*;
 
 pub async fn build_network_topology(state: Arc<AppState>) -> NetworkTopology {
     let mut topology = NetworkTopology::new();
     
-    let local_interfaces = state.config.interfaces.iter().map(|iface| {
+    let config = state.config().await;
+    let interface_states = state.interface_states.read().await.clone();
+    let local_interfaces = config.interfaces.iter().map(|iface| {
         InterfaceInfo {
             name: iface.name.clone(),
             network: format!("network_{}", iface.name),
             capacity_mbps: iface.capacity_mbps,
-            is_active: iface.link_active,
+            is_active: interface_states.get(&iface.name).map_or(iface.link_active, |runtime| runtime.link_active),
             connected_to: None,
         }
     }).collect();
     
     topology.add_router(state.local_ip.clone(), local_interfaces);
     
-    let neighbors = state.neighbors.lock().await;
+    let neighbors = state.neighbors.read().await.clone();
     for (neighbor_ip, neighbor) in neighbors.iter() {
         if !topology.nodes.contains_key(neighbor_ip) {
             topology.add_router(neighbor_ip.clone(), Vec::new());
         }
         
-        if neighbor.link_up {
-            topology.add_link_with_min_capacity(
-                state.local_ip.clone(),
-                neighbor_ip.clone(),
-                neighbor.capacity,
-                neighbor.capacity,
-                true,
-            );
+        if crate::neighbor::is_usable(&state, neighbor).await {
+            match neighbor.cost {
+                Some(cost) => topology.add_link_with_cost(
+                    state.local_ip.clone(),
+                    neighbor_ip.clone(),
+                    neighbor.capacity,
+                    cost,
+                    true,
+                ),
+                None => topology.add_link_with_min_capacity(
+                    state.local_ip.clone(),
+                    neighbor_ip.clone(),
+                    neighbor.capacity,
+                    neighbor.capacity,
+                    true,
+                ),
+            }
+            // Latence unidirectionnelle estimée à la moitié du RTT
+            if let Some(rtt) = neighbor.rtt_us {
+                topology.set_link_latency(&state.local_ip, neighbor_ip, rtt / 2);
+            }
         }
     }
+    
+    // Au-delà des voisins directs : adjacences annoncées dans les LSA routeur de la LSDB.
+    // Un voisin direct est représenté par ses adresses, un routeur distant par son identité
+    let local_identity = state.router_id.to_string();
+    let node_keys = |identity: &str| -> Vec<String> {
+        match neighbors.values().filter(|neighbor| neighbor.identity() == identity).map(|neighbor| neighbor.neighbor_ip.clone()).collect::<Vec<_>>() {
+            addresses if addresses.is_empty() => vec![identity.to_string()],
+            addresses => addresses,
+        }
+    };
+    let lsdb = state.lsdb.read().await;
+    // Segments à accès multiple décrits par leur routeur désigné : une étoile autour d'un
+    // pseudo-nœud (le préfixe du segment) remplace le maillage complet des adjacences
+    let segments: Vec<(Prefix, &Vec<String>)> = lsdb.network_lsas()
+        .filter(|lsa| lsa.attached_routers.len() > 1)
+        .filter_map(|lsa| Some((lsa.prefix.parse::<Prefix>().ok()?, &lsa.attached_routers)))
+        .collect();
+    let segment_of = |originator: &str, advertised: &crate::types::Neighbor| {
+        let address = advertised.neighbor_ip.parse().ok()?;
+        let identity = advertised.identity();
+        segments.iter()
+            .find(|(prefix, attached)| prefix.contains(address) && attached.iter().any(|router| router == originator) && attached.contains(&identity))
+            .map(|(prefix, _)| prefix.to_string())
+    };
+    let mut joined: HashSet<(String, String)> = HashSet::new();
+    for lsa in lsdb.router_lsas() {
+        if lsa.originator == local_identity || lsa.originator == state.local_ip {
+            continue;
+        }
+        let from_keys = node_keys(&lsa.originator);
+        for key in &from_keys {
+            if !topology.nodes.contains_key(key) {
+                topology.add_router(key.clone(), Vec::new());
+            }
+        }
+        for advertised in lsa.neighbors.iter().filter(|neighbor| neighbor.link_up) {
+            let target = advertised.identity();
+            if target == local_identity || target == state.local_ip {
+                continue;
+            }
+            // Vérification bidirectionnelle : le voisin annoncé, s'il a un LSA, doit nous citer
+            let two_way = lsdb.router_lsa(&target).is_none_or(|reverse| {
+                reverse.neighbors.iter().any(|neighbor| neighbor.link_up && neighbor.identity() == lsa.originator)
+            });
+            if !two_way {
+                continue;
+            }
+            let cost = advertised.cost.unwrap_or_else(|| calculate_ospf_cost(advertised.capacity, true));
+            let latency = advertised.rtt_us.map(|rtt| rtt / 2);
+            if let Some(segment) = segment_of(&lsa.originator, advertised) {
+                if joined.insert((lsa.originator.clone(), segment.clone())) {
+                    if !topology.nodes.contains_key(&segment) {
+                        topology.add_router(segment.clone(), Vec::new());
+                    }
+                    for from in &from_keys {
+                        topology.add_directed_link(from.clone(), segment.clone(), advertised.capacity, cost, latency);
+                    }
+                }
+                continue;
+            }
+            for to in node_keys(&target) {
+                if !topology.nodes.contains_key(&to) {
+                    topology.add_router(to.clone(), Vec::new());
+                }
+                for from in &from_keys {
+                    topology.add_directed_link(from.clone(), to.clone(), advertised.capacity, cost, latency);
+                }
+            }
+        }
+    }
+    for (prefix, attached) in &segments {
+        let segment = prefix.to_string();
+        if !topology.nodes.contains_key(&segment) {
+            continue;
+        }
+        for router in attached.iter().filter(|router| **router != local_identity) {
+            for key in node_keys(router) {
+                if !topology.nodes.contains_key(&key) {
+                    topology.add_router(key.clone(), Vec::new());
+                }
+                topology.add_segment_member(segment.clone(), key);
+            }
+        }
+    }
+    drop(lsdb);
     drop(neighbors);
     
     topology
 }
 
+/// Vrai si `candidate` est meilleure que la route déjà retenue pour le préfixe
+fn is_preferred(table: &HashMap<Prefix, (String, RouteState)>, prefix: &Prefix, candidate: &RouteState) -> bool {
+    table.get(prefix).is_none_or(|(_, current)| candidate.preference() < current.preference())
+}
+
+#[tracing::instrument(name = "spf", skip_all, fields(router = %state.local_ip))]
 pub async fn calculate_and_update_optimal_routes(state: Arc<AppState>) -> Result<()> {
     debug!("Calcul des routes optimales en cours...");
+    let started = tokio::time::Instant::now();
     
     let topology = build_network_topology(Arc::clone(&state)).await;
     
-    let shortest_paths = topology.calculate_shortest_paths(&state.local_ip);
+    let config = state.config().await;
+    let shortest_paths = config.spf_engine.engine().shortest_paths(&topology, &state.local_ip, config.path_selection);
     
     if shortest_paths.is_empty() {
         warn!("Aucune route calculée - routeur probablement isolé");
         return Ok(());
     }
     
+    let route_install = config.route_install.clone();
     let mut new_routing_table = HashMap::new();
-    let mut routes_updated = 0;
-    let lsdb = state.topology.lock().await;
+    let mut new_origins: HashMap<Prefix, RouteOrigin> = HashMap::new();
+    let now = crate::clock::unix_secs();
+    // Chemins enregistrés par identifiant de routeur plutôt que par adresse d'interface
+    let identities: HashMap<String, String> = state.neighbors.read().await.values()
+        .map(|neighbor| (neighbor.neighbor_ip.clone(), neighbor.identity()))
+        .chain(std::iter::once((state.local_ip.clone(), state.router_id.to_string())))
+        .collect();
+    let origin = |lsa_type: LsaType, originator: &str, seq_num: u32, route_info: &RouteInfo, tag: Option<u32>| RouteOrigin {
+        lsa_type,
+        originator: originator.to_string(),
+        seq_num,
+        installed_at: now,
+        path: route_info.path.iter().map(|hop| identities.get(hop).cloned().unwrap_or_else(|| hop.clone())).collect(),
+        tag,
+    };
+    // Prochains sauts résolus en adresses directement joignables, avant de verrouiller la LSDB
+    let mut next_hops: HashMap<String, Option<crate::nexthop::NextHop>> = HashMap::new();
+    for route_info in shortest_paths.values() {
+        if !next_hops.contains_key(&route_info.next_hop) {
+            let resolved = crate::nexthop::resolve(&state, &route_info.next_hop).await;
+            next_hops.insert(route_info.next_hop.clone(), resolved);
+        }
+    }
+    // Retenues en cours, copiées avant de verrouiller la LSDB
+    let hold_down = {
+        let mut hold_down = state.hold_down.lock().await;
+        hold_down.expire(tokio::time::Instant::now());
+        state.metrics.routes_held_down.store(hold_down.len() as u64, std::sync::atomic::Ordering::Relaxed);
+        hold_down.clone()
+    };
+    let held_now = tokio::time::Instant::now();
+    let not_held = |next_hop: &str, prefix: &Prefix, candidate: &RouteState| {
+        let admitted = hold_down.admits(prefix, next_hop, candidate, held_now);
+        if !admitted {
+            debug!("Route {} via {} ignorée pendant la retenue du préfixe", prefix, next_hop);
+            state.metrics.hold_down_ignored.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+        admitted
+    };
+    let lsdb = state.lsdb.read().await;
+    let neighbors = state.neighbors.read().await;
+    let nodes = neighbor_nodes(&neighbors);
+    // Interface de chaque voisin, pour les filtres d'entrée
+    let neighbor_interfaces: HashMap<String, Option<String>> = neighbors.iter()
+        .map(|(ip, neighbor)| (ip.clone(), neighbor.interface.clone().or_else(|| state.interfaces.name_for_address(ip))))
+        .collect();
+    drop(neighbors);
+    let accepts = |next_hop: &str, prefix: &Prefix, tag: Option<u32>| {
+        let interface = neighbor_interfaces.get(next_hop).cloned().flatten();
+        let accepted = crate::filter::permits(&config, crate::filter::Direction::In, Some(next_hop), interface.as_deref(), prefix, tag);
+        if !accepted {
+            debug!("Route {} via {} rejetée par le filtre d'entrée", prefix, next_hop);
+        }
+        accepted
+    };
+    // Seuils d'installation, évalués en dernier : seules les routes qui auraient été
+    // retenues sont rapportées par `filtered-routes`
+    let mut filtered = Vec::new();
+    let mut within_policy = |prefix: &Prefix, route_info: &RouteInfo| {
+        let Some(reason) = crate::install_policy::violation(&config.install_policy, route_info) else {
+            return true;
+        };
+        debug!("Route {} via {} écartée: {}", prefix, route_info.next_hop, reason);
+        filtered.push(crate::install_policy::FilteredRoute {
+            prefix: *prefix,
+            next_hop: route_info.next_hop.clone(),
+            hop_count: route_info.hop_count,
+            bottleneck_mbps: route_info.bottleneck_capacity,
+            reason,
+        });
+        false
+    };
 
-    // Parcourir la LSDB pour trouver les réseaux annoncés
-    for (originator, router_state) in lsdb.iter() {
-        if let Some(lsa) = &router_state.last_lsa {
-            if let Some(route_info) = shortest_paths.get(originator) {
-                if route_info.is_reachable && route_info.total_cost < u32::MAX {
-                    for (network_prefix, route_state) in &lsa.routing_table {
-                        if let RouteState::Active(metric) = route_state {
-                            // Calculer le coût total (coût local + métrique distante)
-                            let total_metric = if *metric == u32::MAX || route_info.total_cost == u32::MAX {
-                                u32::MAX
-                            } else {
-                                route_info.total_cost.saturating_add(*metric)
-                            };
-                            
-                            let should_update = match new_routing_table.get(network_prefix) {
-                                Some((_, RouteState::Active(current_metric))) => total_metric < *current_metric,
-                                Some((_, RouteState::Unreachable)) => true,
-                                None => true,
-                            };
-                            
-                            if should_update {
-                                routes_updated += 1;
-                                new_routing_table.insert(
-                                    network_prefix.clone(),
-                                    (route_info.next_hop.clone(), RouteState::Active(total_metric)),
-                                );
-                                
-                                // Ne mettre à jour la table système que si le préfixe est valide
-                                if network_prefix.contains('/') {
-                                    if let Err(e) = crate::lsa::update_routing_table_safe(network_prefix, &route_info.next_hop).await {
-                                        warn!("Échec de la mise à jour de la route système vers {} via {}: {}", 
-                                              network_prefix, &route_info.next_hop, e);
-                                    } else {
-                                        info!("Route mise à jour: {} via {} (coût: {})", 
-                                              network_prefix, &route_info.next_hop, total_metric);
-                                    }
-                                } else {
-                                    debug!("Préfixe invalide ignoré: {}", network_prefix);
-                                }
-                            }
+    // Routeur d'accès : une seule route par défaut, vers le voisin direct le plus proche
+    let access = config.role == crate::read_config::RouterRole::Access;
+    let upstream = shortest_paths.values()
+        .filter(|route_info| access && route_info.is_reachable && route_info.hop_count == 1 && route_info.total_cost < u32::MAX)
+        .min_by(|a, b| (a.total_cost, &a.next_hop).cmp(&(b.total_cost, &b.next_hop)));
+    if let Some(route_info) = upstream {
+        let default_route = Prefix::default_route();
+        let candidate = RouteState::Active(route_info.total_cost);
+        if accepts(&route_info.next_hop, &default_route, None)
+            && not_held(&route_info.next_hop, &default_route, &candidate)
+            && within_policy(&default_route, route_info) {
+            let originator = identities.get(&route_info.next_hop).cloned().unwrap_or_else(|| route_info.next_hop.clone());
+            new_routing_table.insert(default_route, (route_info.next_hop.clone(), candidate));
+            new_origins.insert(default_route, origin(LsaType::Router, &originator, 0, route_info, None));
+        }
+    }
+
+    // Parcourir les LSA routeur pour trouver les réseaux annoncés
+    for lsa in lsdb.router_lsas().filter(|_| !access) {
+        if let Some(route_info) = route_to_originator(&shortest_paths, &nodes, &lsa.originator) {
+            if route_info.is_reachable && route_info.total_cost < u32::MAX {
+                for (network_prefix, route_state) in &lsa.networks {
+                    if let RouteState::Active(metric) = route_state {
+                        let tag = lsa.tags.get(network_prefix).copied();
+                        // Calculer le coût total (coût local + métrique distante)
+                        let total_metric = if *metric == u32::MAX || route_info.total_cost == u32::MAX {
+                            u32::MAX
+                        } else {
+                            route_info.total_cost.saturating_add(*metric)
+                        };
+                        
+                        let should_update = is_preferred(&new_routing_table, network_prefix, &RouteState::Active(total_metric))
+                            && accepts(&route_info.next_hop, network_prefix, tag)
+                            && not_held(&route_info.next_hop, network_prefix, &RouteState::Active(total_metric))
+                            && within_policy(network_prefix, route_info);
+                        
+                        if should_update {
+                            new_routing_table.insert(
+                                *network_prefix,
+                                (route_info.next_hop.clone(), RouteState::Active(total_metric)),
+                            );
+                            new_origins.insert(*network_prefix, origin(LsaType::Router, &lsa.originator, lsa.seq_num, route_info, tag));
                         }
                     }
                 }
@@ -370,79 +291,100 @@ pub async fn calculate_and_update_optimal_routes(state: Arc<AppState>) -> Result
         }
     }
 
-    // Mise à jour complète de la table de routage
-    let mut routing_table = state.routing_table.lock().await;
-    *routing_table = new_routing_table;
-    
-    info!("Calcul des routes terminé. {} routes dans la table de routage ({} mises à jour).", 
-          routing_table.len(), routes_updated);
-    Ok(())
-}
-
-async fn update_system_route(destination: &str, gateway: &str) -> Result<()> {
-    use rtnetlink::{new_connection, IpVersion};
-    use std::net::Ipv4Addr;
-    use tokio::time::{timeout, Duration};
-    use pnet::ipnetwork::IpNetwork;
-
-    // Vérifier le préfixe
-    if !destination.contains('/') {
-        return Err(AppError::RouteError(format!("Format de destination invalide (CIDR attendu): {}", destination)));
-    }
-
-    let network: IpNetwork = destination.parse()
-        .map_err(|e| AppError::RouteError(format!("Analyse du réseau destination échouée {}: {}", destination, e)))?;
-
-    let (dest_ip, prefix_len) = match network {
-        IpNetwork::V4(ipv4) => (ipv4.network(), ipv4.prefix()),
-        IpNetwork::V6(_) => return Err(AppError::RouteError("IPv6 non supporté".to_string())),
-    };
-
-    let gw_ip: Ipv4Addr = gateway.parse()
-        .map_err(|e| AppError::RouteError(format!("Passerelle IPv4 invalide {}: {}", gateway, e)))?;
-
-    if gw_ip.is_unspecified() || gw_ip.is_broadcast() || gw_ip.is_loopback() {
-        return Err(AppError::RouteError(format!("Adresse de passerelle invalide: {}", gw_ip)));
+    // Routes externes : une route interne l'emporte toujours, puis E1 sur E2 (voir `RouteState::preference`)
+    for lsa in lsdb.external_lsas().filter(|_| !access) {
+        let Some(route_info) = route_to_originator(&shortest_paths, &nodes, &lsa.originator) else {
+            continue;
+        };
+        if !route_info.is_reachable || route_info.total_cost == u32::MAX {
+            continue;
+        }
+        for (network_prefix, metric) in &lsa.routes {
+            let tag = lsa.tags.get(network_prefix).copied();
+            let candidate = RouteState::External {
+                metric_type: lsa.metric_type(network_prefix),
+                metric: *metric,
+                cost: route_info.total_cost,
+            };
+            if !is_preferred(&new_routing_table, network_prefix, &candidate)
+                || !accepts(&route_info.next_hop, network_prefix, tag)
+                || !not_held(&route_info.next_hop, network_prefix, &candidate)
+                || !within_policy(network_prefix, route_info) {
+                continue;
+            }
+            new_routing_table.insert(*network_prefix, (route_info.next_hop.clone(), candidate));
+            new_origins.insert(*network_prefix, origin(LsaType::External, &lsa.originator, lsa.seq_num, route_info, tag));
+        }
     }
-
-    let (connection, handle, _) = match new_connection() {
-        Ok(conn) => conn,
-        Err(e) => return Err(AppError::RouteError(format!("Échec de connexion netlink: {}", e))),
-    };
-    tokio::spawn(connection);
-
-    let mut routes = handle.route().get(IpVersion::V4).execute();
-    let mut route_existed = false;
-    
-    while let Ok(Ok(Some(route))) = timeout(Duration::from_secs(1), routes.try_next()).await {
-        if route.destination_prefix() == Some((std::net::IpAddr::V4(dest_ip), prefix_len as u8)) {
-            route_existed = true;
-            match handle.route().del(route).execute().await {
-                Ok(_) => debug!("Route existante supprimée: {} via {}", destination, gateway),
-                Err(e) => debug!("Erreur lors de la suppression de la route existante: {}", e),
+    // La LSDB n'est pas verrouillée pendant l'installation des routes : un noyau lent
+    // ne retarde pas la réception des LSA
+    drop(lsdb);
+    // Un préfixe écarté sur un chemin mais installé par un autre n'est pas rapporté
+    filtered.retain(|route| !new_routing_table.contains_key(&route.prefix));
+    filtered.sort_by_key(|route| route.prefix);
+    filtered.dedup_by(|a, b| a.prefix == b.prefix);
+    *state.filtered_routes.lock().await = filtered;
+
+    // Seules les routes dont le prochain saut résolu a changé sont confiées à la tâche
+    // d'installation (voir `route_queue`), qui programme le noyau et retente les échecs
+    let diff = RouteDiff::between(&*state.routing_table.read().await, &new_routing_table);
+    let programmed = state.programmed_routes.lock().await.snapshot(&route_install);
+    for (network_prefix, (next_hop, route_state)) in &new_routing_table {
+        let total_metric = route_state.metric().unwrap_or(u32::MAX);
+        let kind = if matches!(route_state, RouteState::External { .. }) { "externe " } else { "" };
+        let Some(resolved) = next_hops.get(next_hop).cloned().flatten() else {
+            if route_install.enabled {
+                let error = format!("{} is not reachable through a directly connected neighbor", next_hop);
+                warn!("Échec de la mise à jour de la route {}vers {} via {}: {}", kind, network_prefix, next_hop, error);
+                state.events.emit(RouterEvent::RouteFailed { prefix: network_prefix.to_string(), next_hop: next_hop.clone(), error });
             }
+            continue;
+        };
+        if programmed.get(network_prefix) == Some(&resolved) {
+            state.route_ops.cancel(network_prefix).await;
+            continue;
         }
+        info!("Route {}mise à jour: {} via {} (coût: {})", kind, network_prefix, next_hop, total_metric);
+        state.route_ops.push(*network_prefix, RouteOp::Install { next_hop: resolved }).await;
     }
 
-    let add_route = handle.route().add()
-        .v4()
-        .destination_prefix(dest_ip, prefix_len as u8)
-        .gateway(gw_ip)
-        .execute();
+    // Retirer du noyau les routes qui ont disparu de la table
+    let mut withdrawn: Vec<Prefix> = programmed.keys()
+        .chain(diff.removed.iter())
+        .filter(|prefix| !new_routing_table.contains_key(*prefix))
+        .copied()
+        .collect();
+    withdrawn.sort();
+    withdrawn.dedup();
+    for prefix in withdrawn {
+        state.route_ops.push(prefix, RouteOp::Remove).await;
+    }
+    if !diff.is_empty() {
+        info!("Routing table changes: {}", diff.summary());
+        crate::history::record_routes(&state, &new_routing_table, &diff).await;
+    }
 
-    match timeout(Duration::from_secs(2), add_route).await {
-        Ok(Ok(_)) => {
-            let action = if route_existed { "mise à jour" } else { "ajoutée" };
-            info!("Route système {}: {} via {}", action, destination, gateway);
-            Ok(())
-        }
-        Ok(Err(e)) => {
-            error!("Erreur netlink lors de l'ajout de la route: {}", e);
-            Err(AppError::RouteError(format!("Erreur netlink: {}", e)))
-        }
-        Err(_) => {
-            error!("Timeout netlink lors de l'ajout de la route");
-            Err(AppError::RouteError("Timeout netlink".into()))
+    // Mise à jour complète de la table de routage ; une route inchangée garde sa date d'installation
+    let mut routing_table = state.routing_table.write().await;
+    let mut origins = state.route_origins.write().await;
+    for (prefix, origin) in new_origins.iter_mut() {
+        if let (Some(previous), Some(old_route)) = (origins.get(prefix), routing_table.get(prefix)) {
+            if new_routing_table.get(prefix) == Some(old_route) {
+                origin.installed_at = previous.installed_at;
+            }
         }
     }
-}
\ No newline at end of file
+    *origins = new_origins;
+    drop(origins);
+    *routing_table = new_routing_table;
+    
+    info!("Calcul des routes terminé. {} routes dans la table de routage ({} mises à jour).", 
+          routing_table.len(), diff.len());
+    state.events.emit(RouterEvent::SpfCompleted {
+        routes: routing_table.len(),
+        updated: diff.len(),
+        duration_us: started.elapsed().as_micros() as u64,
+    });
+    Ok(())
+}
+
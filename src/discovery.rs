@@ -0,0 +1,266 @@
+use crate::read_config::{InterfaceProtocol, RouterConfig};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Voisin joint en unicast, hors de portée de nos HELLO diffusés
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnicastPeer {
+    pub address: Ipv4Addr,
+    pub port: u16,
+    /// Interface locale à laquelle ses messages sont rattachés
+    pub interface: String,
+}
+
+impl UnicastPeer {
+    pub fn endpoint(&self) -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(self.address), self.port)
+    }
+}
+
+/// Voisins découverts par mDNS, jusqu'à l'expiration de leur annonce
+#[derive(Debug, Default)]
+pub struct DiscoveredPeers {
+    peers: HashMap<Ipv4Addr, (u16, String, Instant)>,
+}
+
+impl DiscoveredPeers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enregistre ou prolonge un voisin ; renvoie vrai s'il est nouveau
+    pub fn insert(&mut self, address: Ipv4Addr, port: u16, interface: &str, ttl: Duration) -> bool {
+        self.peers.insert(address, (port, interface.to_string(), Instant::now() + ttl)).is_none()
+    }
+
+    pub fn remove(&mut self, address: Ipv4Addr) -> bool {
+        self.peers.remove(&address).is_some()
+    }
+
+    /// Oublie les voisins dont l'annonce a expiré
+    pub fn expire(&mut self) -> Vec<Ipv4Addr> {
+        let now = Instant::now();
+        let expired: Vec<Ipv4Addr> = self.peers.iter()
+            .filter(|(_, (_, _, expires))| *expires <= now)
+            .map(|(address, _)| *address)
+            .collect();
+        for address in &expired {
+            self.peers.remove(address);
+        }
+        expired
+    }
+
+    pub fn peers(&self) -> Vec<UnicastPeer> {
+        self.peers.iter()
+            .map(|(address, (port, interface, _))| UnicastPeer { address: *address, port: *port, interface: interface.clone() })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+}
+
+/// Voisins unicast : configurés, puis découverts
+pub async fn unicast_peers(state: &crate::AppState, config: &RouterConfig) -> Vec<UnicastPeer> {
+    let mut peers: Vec<UnicastPeer> = config.static_neighbors.iter()
+        .map(|neighbor| UnicastPeer {
+            address: neighbor.address,
+            port: neighbor.port.unwrap_or(state.port),
+            interface: neighbor.interface.clone(),
+        })
+        .collect();
+    for peer in state.discovered_peers.lock().await.peers() {
+        if !peers.iter().any(|known| known.address == peer.address) {
+            peers.push(peer);
+        }
+    }
+    peers
+}
+
+/// Interface locale rattachée à un voisin unicast
+pub async fn peer_interface(state: &crate::AppState, config: &RouterConfig, address: Ipv4Addr) -> Option<String> {
+    unicast_peers(state, config).await
+        .into_iter()
+        .find(|peer| peer.address == address)
+        .map(|peer| peer.interface)
+}
+
+/// Point d'accès d'un voisin : celui observé à la réception de ses HELLO (port traduit par
+/// un NAT), à défaut son adresse sur le port du protocole
+pub async fn neighbor_endpoint(state: &crate::AppState, neighbor_ip: &str) -> crate::error::Result<SocketAddr> {
+    if let Some(endpoint) = state.neighbor_endpoints.lock().await.get(neighbor_ip) {
+        return Ok(*endpoint);
+    }
+    format!("{}:{}", neighbor_ip, state.port)
+        .parse::<SocketAddr>()
+        .map_err(|e| crate::error::AppError::NetworkError(format!("Invalid neighbor addr: {}", e)))
+}
+
+/// Destinations des HELLO et LSA : adresses de diffusion des interfaces (sauf en overlay),
+/// puis chaque voisin unicast depuis l'adresse de son interface
+pub async fn flood_targets(state: &crate::AppState, config: &RouterConfig) -> Vec<(String, SocketAddr)> {
+    let mut targets = if config.overlay.enabled {
+        Vec::new()
+    } else {
+        state.interfaces.configured_flood_addresses(config, state.port)
+    };
+    let observed: Vec<SocketAddr> = state.neighbor_endpoints.lock().await.values().copied().collect();
+    for peer in unicast_peers(state, config).await {
+        if !config.is_interface_configured(&peer.interface) || config.interface_protocol(Some(&peer.interface)) != InterfaceProtocol::Pospf {
+            continue;
+        }
+        let Some(local_ip) = state.interfaces.addresses_of(&peer.interface).first().copied() else {
+            continue;
+        };
+        // Derrière un NAT, le voisin est joint là d'où viennent ses HELLO
+        let endpoint = observed.iter()
+            .find(|endpoint| endpoint.ip() == IpAddr::V4(peer.address))
+            .copied()
+            .unwrap_or_else(|| peer.endpoint());
+        targets.push((local_ip.to_string(), endpoint));
+    }
+    targets
+}
+
+/// Nom d'instance mDNS d'un routeur, sans point pour rester un seul label
+fn instance_name(router_id: crate::types::RouterId) -> String {
+    format!("pospf-{}", router_id.to_string().replace('.', "-"))
+}
+
+/// Adresses des interfaces P-OSPF configurées, avec leur nom et leur sous-réseau
+fn pospf_interfaces(state: &crate::AppState, config: &RouterConfig) -> Vec<crate::interfaces::LocalInterface> {
+    state.interfaces.interfaces()
+        .into_iter()
+        .filter(|iface| config.is_interface_configured(&iface.name) && config.interface_protocol(Some(&iface.name)) == InterfaceProtocol::Pospf)
+        .collect()
+}
+
+fn open_socket(interfaces: &[crate::interfaces::LocalInterface]) -> crate::error::Result<tokio::net::UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, crate::mdns::PORT)).into())?;
+    socket.set_multicast_ttl_v4(255)?;
+    socket.set_multicast_loop_v4(false)?;
+    for iface in interfaces {
+        socket.join_multicast_v4(&crate::mdns::GROUP, &iface.ip())?;
+    }
+    socket.set_nonblocking(true)?;
+    Ok(tokio::net::UdpSocket::from_std(std::net::UdpSocket::from(socket))?)
+}
+
+/// Retient les routeurs annoncés hors de nos sous-réseaux (via un relais mDNS), rattachés
+/// à l'interface dont le sous-réseau contient l'émetteur de l'annonce
+async fn learn(state: &crate::AppState, interfaces: &[crate::interfaces::LocalInterface], source: Ipv4Addr, message: crate::mdns::MdnsMessage) {
+    let Some(receiving) = interfaces.iter().find(|iface| iface.network.contains(source)) else {
+        return;
+    };
+    let mut discovered = state.discovered_peers.lock().await;
+    for announcement in message.announcements {
+        for address in announcement.addresses {
+            if state.interfaces.interfaces().iter().any(|iface| iface.ip() == address || iface.network.contains(address)) {
+                continue;
+            }
+            if announcement.ttl == 0 {
+                if discovered.remove(address) {
+                    info!("[MDNS] Peer {} withdrew its announcement", address);
+                }
+            } else if discovered.insert(address, announcement.port, &receiving.name, Duration::from_secs(announcement.ttl as u64)) {
+                info!("[MDNS] Discovered peer {} ({}) via {}", address, announcement.instance, receiving.name);
+            }
+        }
+    }
+    state.metrics.discovered_peers.store(discovered.len() as u64, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Découverte mDNS : interroge périodiquement le service, répond aux requêtes des
+/// autres routeurs et retient ceux qui ne sont pas sur un sous-réseau local
+pub fn spawn_mdns_task(state: Arc<crate::AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let config = state.config().await;
+        let interfaces = pospf_interfaces(&state, &config);
+        if interfaces.is_empty() {
+            warn!("No P-OSPF interface to run mDNS discovery on");
+            return;
+        }
+        let socket = match open_socket(&interfaces) {
+            Ok(socket) => socket,
+            Err(e) => {
+                tracing::error!("Failed to open the mDNS socket on port {}: {}", crate::mdns::PORT, e);
+                return;
+            }
+        };
+        let socket2 = socket2::SockRef::from(&socket);
+        let group = SocketAddr::new(IpAddr::V4(crate::mdns::GROUP), crate::mdns::PORT);
+        let interval = Duration::from_secs(config.mdns.interval_sec.max(1));
+        let announcement = crate::mdns::Announcement {
+            instance: instance_name(state.router_id),
+            port: state.port,
+            addresses: interfaces.iter().map(|iface| iface.ip()).collect(),
+            ttl: (config.mdns.interval_sec * 3).min(u32::MAX as u64) as u32,
+        };
+        let mut query = tokio::time::interval(interval);
+        let mut buffer = vec![0u8; 9_000];
+        loop {
+            let (outgoing, interfaces_out) = tokio::select! {
+                _ = query.tick() => {
+                    let mut discovered = state.discovered_peers.lock().await;
+                    for address in discovered.expire() {
+                        info!("[MDNS] Peer {} expired", address);
+                    }
+                    state.metrics.discovered_peers.store(discovered.len() as u64, std::sync::atomic::Ordering::Relaxed);
+                    drop(discovered);
+                    (crate::mdns::encode_query(crate::mdns::SERVICE), interfaces.clone())
+                }
+                received = socket.recv_from(&mut buffer) => {
+                    let (len, source) = match received {
+                        Ok(received) => received,
+                        Err(e) => {
+                            warn!("mDNS receive error: {}", e);
+                            continue;
+                        }
+                    };
+                    let SocketAddr::V4(source) = source else {
+                        continue;
+                    };
+                    let message = match crate::mdns::decode(&buffer[..len], crate::mdns::SERVICE) {
+                        Ok(message) => message,
+                        Err(e) => {
+                            debug!("Dropping mDNS message from {}: {}", source, e);
+                            continue;
+                        }
+                    };
+                    let asked = message.queries.iter().any(|name| name == crate::mdns::SERVICE);
+                    learn(&state, &interfaces, *source.ip(), message).await;
+                    if !asked {
+                        continue;
+                    }
+                    // Réponse sur l'interface de la requête seulement
+                    let Some(receiving) = interfaces.iter().find(|iface| iface.network.contains(*source.ip())) else {
+                        continue;
+                    };
+                    (crate::mdns::encode_response(crate::mdns::SERVICE, &announcement), vec![receiving.clone()])
+                }
+            };
+            for iface in interfaces_out {
+                let sent = match socket2.set_multicast_if_v4(&iface.ip()) {
+                    Ok(()) => socket.send_to(&outgoing, group).await.map(|_| ()),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = sent {
+                    warn!("Failed to send mDNS message on {}: {}", iface.name, e);
+                }
+            }
+        }
+    })
+}
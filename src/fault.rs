@@ -0,0 +1,195 @@
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Clé des règles appliquées aux voisins sans règle propre
+pub const ANY_NEIGHBOR: &str = "*";
+
+static RULES: Mutex<BTreeMap<String, FaultRule>> = Mutex::new(BTreeMap::new());
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+static DELAYED: AtomicU64 = AtomicU64::new(0);
+static DUPLICATED: AtomicU64 = AtomicU64::new(0);
+static CORRUPTED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    In,
+    Out,
+}
+
+/// Perturbations appliquées aux datagrammes échangés avec un voisin, en pourcentages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultRule {
+    pub drop_pct: u8,
+    pub delay_pct: u8,
+    pub delay_ms: u64,
+    pub duplicate_pct: u8,
+    pub corrupt_pct: u8,
+    /// Sens perturbé ; les deux si `None`
+    pub direction: Option<Direction>,
+}
+
+/// Sort d'un datagramme
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Verdict {
+    pub drop: bool,
+    pub delay: Option<Duration>,
+    pub duplicate: bool,
+    pub corrupt: bool,
+}
+
+impl FaultRule {
+    pub fn is_empty(&self) -> bool {
+        self.drop_pct == 0 && self.delay_pct == 0 && self.duplicate_pct == 0 && self.corrupt_pct == 0
+    }
+
+    pub fn applies_to(&self, direction: Direction) -> bool {
+        self.direction.is_none_or(|only| only == direction)
+    }
+
+    /// Tire le sort d'un datagramme ; un datagramme perdu n'est ni retardé, ni dupliqué,
+    /// ni altéré
+    pub fn decide<R: Rng>(&self, rng: &mut R) -> Verdict {
+        let mut hit = |pct: u8| pct > 0 && rng.gen_range(0..100u8) < pct;
+        if hit(self.drop_pct) {
+            return Verdict { drop: true, ..Verdict::default() };
+        }
+        Verdict {
+            drop: false,
+            delay: hit(self.delay_pct).then(|| Duration::from_millis(self.delay_ms)),
+            duplicate: hit(self.duplicate_pct),
+            corrupt: hit(self.corrupt_pct),
+        }
+    }
+}
+
+impl std::fmt::Display for FaultRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let direction = match self.direction {
+            Some(Direction::In) => "in",
+            Some(Direction::Out) => "out",
+            None => "both",
+        };
+        write!(
+            f,
+            "{} drop {}% delay {}% {} ms duplicate {}% corrupt {}%",
+            direction, self.drop_pct, self.delay_pct, self.delay_ms, self.duplicate_pct, self.corrupt_pct,
+        )
+    }
+}
+
+/// Règle du voisin, à défaut celle de `*`
+pub fn rule_for(peer: IpAddr) -> Option<FaultRule> {
+    let rules = RULES.lock().unwrap_or_else(|e| e.into_inner());
+    rules.get(&peer.to_string()).or_else(|| rules.get(ANY_NEIGHBOR)).copied()
+}
+
+/// Datagrammes à transmettre à la place de `data`, chacun avec son retard : aucun s'il
+/// est perdu, deux s'il est dupliqué
+pub fn apply(direction: Direction, peer: IpAddr, data: &[u8]) -> Vec<(Vec<u8>, Option<Duration>)> {
+    let Some(rule) = rule_for(peer).filter(|rule| rule.applies_to(direction)) else {
+        return vec![(data.to_vec(), None)];
+    };
+    let mut rng = rand::thread_rng();
+    let verdict = rule.decide(&mut rng);
+    if verdict.drop {
+        DROPPED.fetch_add(1, Ordering::Relaxed);
+        return Vec::new();
+    }
+    let mut datagram = data.to_vec();
+    if verdict.corrupt && !datagram.is_empty() {
+        let index = rng.gen_range(0..datagram.len());
+        datagram[index] ^= 1 << rng.gen_range(0..8);
+        CORRUPTED.fetch_add(1, Ordering::Relaxed);
+    }
+    if verdict.delay.is_some() {
+        DELAYED.fetch_add(1, Ordering::Relaxed);
+    }
+    if verdict.duplicate {
+        DUPLICATED.fetch_add(1, Ordering::Relaxed);
+        return vec![(datagram.clone(), verdict.delay), (datagram, verdict.delay)];
+    }
+    vec![(datagram, verdict.delay)]
+}
+
+fn percentage(value: Option<&&str>) -> std::result::Result<u8, String> {
+    value.and_then(|value| value.parse::<u8>().ok())
+        .filter(|pct| *pct <= 100)
+        .ok_or_else(|| "Argument invalide: pourcentage attendu (0 à 100)".to_string())
+}
+
+/// Commande `fault` : liste des règles, `fault clear`, ou
+/// `fault <voisin|*> [in|out|both] [drop <%>] [delay <%> <ms>] [duplicate <%>] [corrupt <%>] [clear]`
+pub fn handle_command(command: &str) -> String {
+    let args: Vec<&str> = command.split_whitespace().skip(1).collect();
+    let mut rules = RULES.lock().unwrap_or_else(|e| e.into_inner());
+    match args.as_slice() {
+        [] => {
+            let mut out = format!(
+                "Injection de fautes: {} perdus, {} retardés, {} dupliqués, {} altérés",
+                DROPPED.load(Ordering::Relaxed),
+                DELAYED.load(Ordering::Relaxed),
+                DUPLICATED.load(Ordering::Relaxed),
+                CORRUPTED.load(Ordering::Relaxed),
+            );
+            if rules.is_empty() {
+                out.push_str("\nAucune règle");
+            }
+            for (neighbor, rule) in rules.iter() {
+                let _ = write!(out, "\n  {}: {}", neighbor, rule);
+            }
+            out
+        }
+        ["clear"] => {
+            rules.clear();
+            "Règles d'injection de fautes supprimées".to_string()
+        }
+        [neighbor, options @ ..] => {
+            if *neighbor != ANY_NEIGHBOR && neighbor.parse::<IpAddr>().is_err() {
+                return format!("Argument invalide: '{}' n'est ni une adresse ni *", neighbor);
+            }
+            let mut rule = rules.get(*neighbor).copied().unwrap_or_default();
+            let mut words = options.iter();
+            while let Some(word) = words.next() {
+                let parsed = match *word {
+                    "in" | "out" | "both" => {
+                        rule.direction = match *word {
+                            "in" => Some(Direction::In),
+                            "out" => Some(Direction::Out),
+                            _ => None,
+                        };
+                        Ok(())
+                    }
+                    "drop" => percentage(words.next()).map(|pct| rule.drop_pct = pct),
+                    "duplicate" => percentage(words.next()).map(|pct| rule.duplicate_pct = pct),
+                    "corrupt" => percentage(words.next()).map(|pct| rule.corrupt_pct = pct),
+                    "delay" => percentage(words.next()).and_then(|pct| {
+                        let ms = words.next().and_then(|ms| ms.parse::<u64>().ok())
+                            .ok_or_else(|| "Argument invalide: délai en millisecondes attendu".to_string())?;
+                        rule.delay_pct = pct;
+                        rule.delay_ms = ms;
+                        Ok(())
+                    }),
+                    "clear" => {
+                        rule = FaultRule::default();
+                        Ok(())
+                    }
+                    other => Err(format!("Argument invalide: '{}'", other)),
+                };
+                if let Err(message) = parsed {
+                    return message;
+                }
+            }
+            if rule.is_empty() {
+                rules.remove(*neighbor);
+                return format!("Aucune faute injectée pour {}", neighbor);
+            }
+            rules.insert(neighbor.to_string(), rule);
+            format!("Fautes injectées pour {}: {}", neighbor, rule)
+        }
+    }
+}
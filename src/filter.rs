@@ -0,0 +1,64 @@
+use crate::read_config::{FilterAction, PrefixListEntry, RouterConfig};
+use crate::types::Prefix;
+use pnet::ipnetwork::Ipv4Network;
+
+/// Sens d'application d'un filtre de routes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Routes apprises des LSA reçus
+    In,
+    /// Routes annoncées dans nos propres LSA
+    Out,
+}
+
+impl PrefixListEntry {
+    fn matches(&self, prefix: &Ipv4Network, tag: Option<u32>) -> bool {
+        if self.tag.is_some_and(|wanted| tag != Some(wanted)) {
+            return false;
+        }
+        if self.prefix.is_empty() {
+            return self.tag.is_some();
+        }
+        let Ok(entry) = self.prefix.parse::<Ipv4Network>() else {
+            return false;
+        };
+        if self.ge.is_none() && self.le.is_none() {
+            return entry.network() == prefix.network() && entry.prefix() == prefix.prefix();
+        }
+        let ge = self.ge.unwrap_or(entry.prefix());
+        let le = self.le.unwrap_or(32);
+        entry.prefix() <= prefix.prefix()
+            && entry.contains(prefix.network())
+            && (ge..=le).contains(&prefix.prefix())
+    }
+}
+
+/// Évalue une liste de préfixes pour une route d'étiquette `tag` : la première entrée
+/// correspondante décide, refus implicite sinon
+pub fn evaluate(entries: &[PrefixListEntry], prefix: &Prefix, tag: Option<u32>) -> bool {
+    // Les clés de la table portent l'adresse de l'interface (« 192.168.1.1/24 ») : comparer le réseau
+    let prefix = prefix.network();
+    entries.iter()
+        .find(|entry| entry.matches(&prefix, tag))
+        .is_some_and(|entry| entry.action == FilterAction::Permit)
+}
+
+/// Vrai si les listes de l'interface et du voisin acceptent toutes le préfixe, annoncé
+/// avec l'étiquette `tag`
+pub fn permits(config: &RouterConfig, direction: Direction, neighbor: Option<&str>, interface: Option<&str>, prefix: &Prefix, tag: Option<u32>) -> bool {
+    let select = |filter_in: &Option<String>, filter_out: &Option<String>| match direction {
+        Direction::In => filter_in.clone(),
+        Direction::Out => filter_out.clone(),
+    };
+    let interface_list = interface
+        .and_then(|name| config.interface(name))
+        .and_then(|iface| select(&iface.filter_in, &iface.filter_out));
+    let neighbor_list = neighbor
+        .and_then(|ip| config.neighbor_filters.iter().find(|filter| filter.neighbor == ip))
+        .and_then(|filter| select(&filter.filter_in, &filter.filter_out));
+    [interface_list, neighbor_list]
+        .into_iter()
+        .flatten()
+        .filter_map(|name| config.prefix_lists.get(&name))
+        .all(|entries| evaluate(entries, prefix, tag))
+}
@@ -0,0 +1,172 @@
+use crate::error::Result;
+use crate::types::{LSAMessage, LsaAckMessage, LsaIdentity};
+use crate::AppState;
+use tracing::{debug, info};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::time::Instant;
+use crate::transport::Socket;
+
+/// Voisin vu sur une interface donnée : une même adresse peut apparaître sur plusieurs liens
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NeighborKey {
+    pub interface: String,
+    pub neighbor_ip: String,
+}
+
+impl NeighborKey {
+    pub fn new(interface: Option<String>, neighbor_ip: &str) -> Self {
+        Self {
+            interface: interface.unwrap_or_default(),
+            neighbor_ip: neighbor_ip.to_string(),
+        }
+    }
+
+    /// Clé d'un voisin : interface sur laquelle il est entendu, à défaut déduite de son adresse
+    pub fn for_neighbor(interfaces: &crate::interfaces::InterfaceProvider, neighbor: &crate::types::Neighbor) -> Self {
+        let interface = neighbor.interface.clone().or_else(|| interfaces.name_for_address(&neighbor.neighbor_ip));
+        Self::new(interface, &neighbor.neighbor_ip)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PendingLsa {
+    lsa: LSAMessage,
+    last_sent: Instant,
+}
+
+/// Listes de retransmission par voisin, indexées par originateur : seule la
+/// dernière instance d'un LSA reste en attente d'acquittement
+#[derive(Debug, Default)]
+pub struct FloodingState {
+    retransmissions: HashMap<NeighborKey, HashMap<String, PendingLsa>>,
+}
+
+impl FloodingState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enregistre un LSA envoyé à un voisin, en remplaçant une instance plus ancienne
+    pub fn push(&mut self, neighbor: NeighborKey, lsa: LSAMessage) {
+        self.retransmissions
+            .entry(neighbor)
+            .or_default()
+            .insert(lsa.originator.clone(), PendingLsa { lsa, last_sent: Instant::now() });
+    }
+
+    /// Retire une instance acquittée (explicitement ou implicitement) par le voisin
+    pub fn acknowledge(&mut self, neighbor: &NeighborKey, id: &LsaIdentity) -> bool {
+        let Some(list) = self.retransmissions.get_mut(neighbor) else {
+            return false;
+        };
+        let acked = list.get(&id.originator).is_some_and(|pending| {
+            pending.lsa.seq_num == id.seq_num || crate::lsa::is_newer_sequence(id.seq_num, pending.lsa.seq_num)
+        });
+        if acked {
+            list.remove(&id.originator);
+            if list.is_empty() {
+                self.retransmissions.remove(neighbor);
+            }
+        }
+        acked
+    }
+
+    /// Le voisin connaît-il déjà cette instance (ou une plus récente) en attente ?
+    pub fn is_pending(&self, neighbor: &NeighborKey, id: &LsaIdentity) -> bool {
+        self.retransmissions
+            .get(neighbor)
+            .and_then(|list| list.get(&id.originator))
+            .is_some_and(|pending| pending.lsa.seq_num == id.seq_num)
+    }
+
+    /// Abandonne les instances d'un originateur en attente vers tous les voisins : retiré,
+    /// son LSA ne doit plus être réémis
+    pub fn forget_originator(&mut self, originator: &str) {
+        for list in self.retransmissions.values_mut() {
+            list.remove(originator);
+        }
+        self.retransmissions.retain(|_, list| !list.is_empty());
+    }
+
+    /// LSA dont le délai de retransmission est écoulé, marqués comme réémis
+    pub fn due(&mut self, interval: Duration) -> Vec<(NeighborKey, LSAMessage)> {
+        let now = Instant::now();
+        let mut due = Vec::new();
+        for (neighbor, list) in self.retransmissions.iter_mut() {
+            for pending in list.values_mut() {
+                if now.duration_since(pending.last_sent) >= interval {
+                    pending.last_sent = now;
+                    due.push((neighbor.clone(), pending.lsa.clone()));
+                }
+            }
+        }
+        due
+    }
+
+    /// Oublie les voisins qui ne sont plus adjacents
+    pub fn retain_neighbors(&mut self, alive: impl Fn(&str) -> bool) {
+        self.retransmissions.retain(|neighbor, _| alive(&neighbor.neighbor_ip));
+    }
+}
+
+/// Acquitte un LSA auprès du voisin qui nous l'a transmis, par sa session TCP s'il en a une
+pub async fn send_ack(state: &AppState, socket: &Socket, addr: &SocketAddr, router_ip: &str, lsa: &LSAMessage, key: &crate::net_utils::SegmentKey) -> Result<()> {
+    let message = LsaAckMessage {
+        message_type: 4,
+        router_ip: router_ip.to_string(),
+        acks: vec![LsaIdentity { originator: lsa.originator.clone(), seq_num: lsa.seq_num }],
+    };
+    crate::tcp_session::send_message(state, socket, router_ip, addr, &message, key, "[ACK]").await
+}
+
+/// Traite un acquittement reçu d'un voisin sur l'interface d'adresse `local_ip`
+pub async fn handle_ack(state: &AppState, src_ip: &str, local_ip: &str, ack: &LsaAckMessage) {
+    let neighbor = NeighborKey::new(state.interfaces.name_for_address(local_ip), src_ip);
+    let mut flooding = state.flooding.lock().await;
+    let mut deltas = state.lsa_deltas.lock().await;
+    for id in &ack.acks {
+        if flooding.acknowledge(&neighbor, id) {
+            debug!("[ACK] {} acknowledged LSA (originator: {}, seq: {})", src_ip, id.originator, id.seq_num);
+        }
+        deltas.acknowledge(&id.originator, id.seq_num, src_ip);
+    }
+}
+
+/// Réémet les LSA non acquittés et purge les listes des voisins disparus
+pub async fn retransmit_pending(socket: &Socket, state: &AppState) {
+    let interval = Duration::from_secs(state.config().await.retransmit_interval_sec);
+    let due = {
+        let neighbors = state.neighbors.read().await;
+        let mut flooding = state.flooding.lock().await;
+        flooding.retain_neighbors(|ip| neighbors.get(ip).is_some_and(|n| n.link_up));
+        state.lsa_deltas.lock().await.retain_neighbors(|ip| neighbors.get(ip).is_some_and(|n| n.link_up));
+        flooding.due(interval)
+    };
+    if due.is_empty() {
+        return;
+    }
+    for (neighbor, lsa) in due {
+        let addr = match crate::discovery::neighbor_endpoint(state, &neighbor.neighbor_ip).await {
+            Ok(addr) => addr,
+            Err(e) => {
+                tracing::warn!("{}", e);
+                continue;
+            }
+        };
+        info!("[RETRANSMIT] LSA (originator: {}, seq: {}) to {} on {}",
+              lsa.originator, lsa.seq_num, neighbor.neighbor_ip, neighbor.interface);
+        let key = state.interface_key(Some(&neighbor.interface)).await;
+        // Pertes constatées : une interface `auto` passe ce voisin en TCP
+        if let std::net::IpAddr::V4(peer) = addr.ip() {
+            if state.stream_sessions.lock().await.record_retransmit(peer) {
+                debug!("[RETRANSMIT] Repeated losses towards {}", peer);
+            }
+        }
+        let local_ip = state.interfaces.addresses_of(&neighbor.interface).first().map(|ip| ip.to_string()).unwrap_or_default();
+        if let Err(e) = crate::tcp_session::send_message(state, socket, &local_ip, &addr, &lsa, &key, "[RETRANSMIT]").await {
+            tracing::warn!("{}", e);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use crate::dijkstra::calculate_ospf_cost;
+use crate::read_config::LinkType;
+use crate::types::{ExternalMetricType, RouteState};
+use crate::AppState;
+use tracing::{error, info};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+
+/// Types générés depuis proto/telemetry.proto
+pub mod proto {
+    tonic::include_proto!("pospf.telemetry");
+}
+
+use proto::telemetry_server::{Telemetry, TelemetryServer};
+
+/// Implémentation du service `Telemetry` sur l'état partagé du routeur
+#[derive(Clone)]
+pub struct TelemetryService {
+    state: Arc<AppState>,
+}
+
+impl TelemetryService {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+}
+
+fn metric_type_name(metric_type: ExternalMetricType) -> &'static str {
+    match metric_type {
+        ExternalMetricType::Type1 => "type-1",
+        ExternalMetricType::Type2 => "type-2",
+    }
+}
+
+#[tonic::async_trait]
+impl Telemetry for TelemetryService {
+    async fn get_neighbors(&self, _request: Request<proto::Empty>) -> Result<Response<proto::NeighborTable>, Status> {
+        let now = crate::clock::unix_secs();
+        let mut neighbors: Vec<proto::Neighbor> = self.state.neighbors.read().await.values().map(|neighbor| proto::Neighbor {
+            router_id: neighbor.neighbor_ip.clone(),
+            hostname: neighbor.hostname.clone(),
+            link_up: neighbor.link_up,
+            capacity_mbps: neighbor.capacity,
+            cost: neighbor.cost.unwrap_or_else(|| calculate_ospf_cost(neighbor.capacity, neighbor.link_up)),
+            last_seen_sec: now.saturating_sub(neighbor.last_seen),
+            rtt_us: neighbor.rtt_us,
+            jitter_us: neighbor.jitter_us,
+            interface: neighbor.interface.clone(),
+        }).collect();
+        neighbors.sort_by(|a, b| a.router_id.cmp(&b.router_id));
+        Ok(Response::new(proto::NeighborTable {
+            router_id: self.state.router_id.to_string(),
+            neighbors,
+        }))
+    }
+
+    async fn get_lsdb(&self, _request: Request<proto::Empty>) -> Result<Response<proto::LsdbContents>, Status> {
+        let lsdb = self.state.lsdb.read().await;
+        let router_lsas = lsdb.router_lsas().map(|lsa| {
+            let mut networks: Vec<proto::AdvertisedNetwork> = lsa.networks.iter().map(|(prefix, route_state)| proto::AdvertisedNetwork {
+                prefix: prefix.to_string(),
+                cost: route_state.metric(),
+            }).collect();
+            networks.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+            proto::RouterLsa {
+                originator: lsa.originator.clone(),
+                seq_num: lsa.seq_num,
+                stub_only: lsa.stub_only,
+                links: lsa.neighbors.iter().map(|neighbor| proto::RouterLink {
+                    neighbor: neighbor.neighbor_ip.clone(),
+                    link_up: neighbor.link_up,
+                    capacity_mbps: neighbor.capacity,
+                    cost: neighbor.cost.unwrap_or_else(|| calculate_ospf_cost(neighbor.capacity, neighbor.link_up)),
+                }).collect(),
+                networks,
+            }
+        }).collect();
+        let network_lsas = lsdb.network_lsas().map(|lsa| proto::NetworkLsa {
+            prefix: lsa.prefix.clone(),
+            seq_num: lsa.seq_num,
+            advertising_router: lsa.advertising_router.clone(),
+            attached_routers: lsa.attached_routers.clone(),
+        }).collect();
+        let external_lsas = lsdb.external_lsas().map(|lsa| {
+            let mut routes: Vec<proto::ExternalRoute> = lsa.routes.iter().map(|(prefix, metric)| proto::ExternalRoute {
+                prefix: prefix.to_string(),
+                metric: *metric,
+                metric_type: metric_type_name(lsa.metric_type(prefix)).to_string(),
+            }).collect();
+            routes.sort_by(|a, b| a.prefix.cmp(&b.prefix));
+            proto::ExternalLsa {
+                originator: lsa.originator.clone(),
+                seq_num: lsa.seq_num,
+                routes,
+            }
+        }).collect();
+        Ok(Response::new(proto::LsdbContents { router_lsas, network_lsas, external_lsas }))
+    }
+
+    async fn get_spf_stats(&self, _request: Request<proto::Empty>) -> Result<Response<proto::SpfStats>, Status> {
+        let (routes, unreachable_routes) = {
+            let table = self.state.routing_table.read().await;
+            let unreachable = table.values().filter(|(_, route_state)| matches!(route_state, RouteState::Unreachable)).count();
+            (table.len() as u32, unreachable as u32)
+        };
+        Ok(Response::new(proto::SpfStats {
+            runs: self.state.metrics.spf_runs.load(Ordering::Relaxed),
+            requests: self.state.metrics.spf_requests.load(Ordering::Relaxed),
+            routes,
+            unreachable_routes,
+            lsdb_entries: self.state.lsdb.read().await.len() as u32,
+        }))
+    }
+
+    async fn get_interfaces(&self, _request: Request<proto::Empty>) -> Result<Response<proto::InterfaceStates>, Status> {
+        let config = self.state.config().await;
+        let states = self.state.interface_states.read().await.clone();
+        let neighbors = self.state.neighbors.read().await;
+        let interfaces = self.state.interfaces.interfaces().into_iter().map(|iface| {
+            let configured = config.interface(&iface.name);
+            let runtime = states.get(&iface.name);
+            proto::InterfaceState {
+                address: iface.network.to_string(),
+                link_active: runtime.is_none_or(|r| r.link_active),
+                capacity_mbps: configured.map_or(0, |c| c.capacity_mbps),
+                cost: runtime.and_then(|r| r.cost),
+                link_type: match configured.map(|c| c.link_type).unwrap_or_default() {
+                    LinkType::Broadcast => "broadcast",
+                    LinkType::PointToPoint => "point-to-point",
+                }.to_string(),
+                stub: configured.is_some_and(|c| c.stub),
+                neighbors: neighbors.values().filter(|neighbor| neighbor.interface.as_deref() == Some(&iface.name)).count() as u32,
+                name: iface.name,
+            }
+        }).collect();
+        Ok(Response::new(proto::InterfaceStates { interfaces }))
+    }
+}
+
+/// Démarre le service gRPC de télémétrie si activé dans la configuration
+pub fn spawn_grpc_server(state: Arc<AppState>, listen: String) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let address = match listen.parse() {
+            Ok(address) => address,
+            Err(e) => {
+                error!("Invalid gRPC listen address {}: {}", listen, e);
+                return;
+            }
+        };
+        info!("gRPC telemetry listening on {}", listen);
+        let server = tonic::transport::Server::builder()
+            .add_service(TelemetryServer::new(TelemetryService::new(state)))
+            .serve(address);
+        if let Err(e) = server.await {
+            error!("gRPC server error: {}", e);
+        }
+    })
+}
@@ -1,13 +1,55 @@
-use crate::types::HelloMessage;
-use crate::error::{AppError, Result};
-use tokio::net::UdpSocket;
-use log::info;
+use crate::types::{HelloEcho, HelloMessage, RouterId};
+use crate::error::Result;
+use crate::transport::Socket;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use crate::AppState;
 
-pub async fn send_hello(socket: &UdpSocket, addr: &SocketAddr, router_ip: &str, key: &[u8]) -> Result<()> {
+/// Horloge locale en microsecondes, utilisée pour les horodatages des HELLO
+pub fn now_us() -> u64 {
+    crate::clock::unix_time().as_micros() as u64
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn send_hello(socket: &Socket, addr: &SocketAddr, router_ip: &str, router_id: RouterId, hostname: Option<&str>, priority: u8, echoes: Vec<HelloEcho>, key: &crate::net_utils::SegmentKey) -> Result<()> {
     let message = HelloMessage {
         message_type: 1,
         router_ip: router_ip.to_string(),
+        router_id: Some(router_id),
+        timestamp_us: now_us(),
+        echoes,
+        hostname: hostname.map(str::to_string),
+        priority,
+        auth: Some(key.auth),
     };
     crate::net_utils::send_message(socket, addr, &message, key, "[SEND] HELLO").await
 }
+
+/// Échos à joindre au HELLO émis sur l'interface `local_ip` (voisins du même sous-réseau)
+pub async fn hello_echoes(state: &Arc<AppState>, local_ip: &str) -> Vec<HelloEcho> {
+    let interface = state.interfaces.name_for_address(local_ip);
+    let received = state.hello_timestamps.lock().await;
+    received.iter()
+        .filter(|(router_ip, _)| state.interfaces.name_for_address(router_ip) == interface)
+        .map(|(router_ip, (timestamp_us, received_at))| HelloEcho {
+            router_ip: router_ip.clone(),
+            timestamp_us: *timestamp_us,
+            held_us: received_at.elapsed().as_micros() as u64,
+        })
+        .collect()
+}
+
+/// Mémorise l'horodatage d'un HELLO reçu et mesure le RTT si le voisin nous renvoie le nôtre
+pub async fn process_hello_timestamps(state: &Arc<AppState>, hello: &HelloMessage, local_ip: &str) {
+    if hello.timestamp_us != 0 {
+        state.hello_timestamps.lock().await
+            .insert(hello.router_ip.clone(), (hello.timestamp_us, tokio::time::Instant::now()));
+    }
+    let Some(echo) = hello.echoes.iter().find(|echo| echo.router_ip == local_ip) else {
+        return;
+    };
+    let rtt_us = now_us()
+        .saturating_sub(echo.timestamp_us)
+        .saturating_sub(echo.held_us);
+    crate::neighbor::record_rtt_sample(state, &hello.router_ip, rtt_us.min(u32::MAX as u64) as u32).await;
+}
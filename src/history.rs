@@ -0,0 +1,294 @@
+use crate::error::Result;
+use crate::lsdb::Lsdb;
+use crate::read_config::HistoryConfig;
+use crate::route_diff::RouteDiff;
+use crate::types::{LSAMessage, Prefix, RouteState};
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tracing::warn;
+
+/// Taille par défaut d'un fichier du journal avant rotation
+pub const DEFAULT_HISTORY_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Nombre d'entrées affichées par `history` sans argument
+const DEFAULT_SHOWN: usize = 20;
+
+const MS_PER_DAY: u64 = 86_400_000;
+
+/// Entrée du journal, écrite sur une ligne JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HistoryRecord {
+    /// Nouvelle instance de LSA installée dans la LSDB
+    Lsa { at_ms: u64, lsa: Box<LSAMessage> },
+    /// Route ajoutée ou modifiée, ou retirée (`route` vide) de la table de routage
+    Route { at_ms: u64, prefix: Prefix, route: Option<(String, RouteState)> },
+}
+
+impl HistoryRecord {
+    /// Horodatage en millisecondes depuis l'epoch
+    pub fn at_ms(&self) -> u64 {
+        match self {
+            HistoryRecord::Lsa { at_ms, .. } | HistoryRecord::Route { at_ms, .. } => *at_ms,
+        }
+    }
+}
+
+impl fmt::Display for HistoryRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", clock_time(self.at_ms()))?;
+        match self {
+            HistoryRecord::Lsa { lsa, .. } => write!(
+                f,
+                "LSA {:?} {} seq {} ({} préfixes, {} voisins)",
+                Lsdb::classify(lsa),
+                lsa.originator,
+                lsa.seq_num,
+                lsa.routing_table.len(),
+                lsa.neighbors.len(),
+            ),
+            HistoryRecord::Route { prefix, route: Some((next_hop, route_state)), .. } => match route_state.metric() {
+                Some(metric) => write!(f, "route {} via {} (métrique {})", prefix, next_hop, metric),
+                None => write!(f, "route {} via {} injoignable", prefix, next_hop),
+            },
+            HistoryRecord::Route { prefix, route: None, .. } => write!(f, "route {} retirée", prefix),
+        }
+    }
+}
+
+/// Heure UTC `HH:MM:SS.mmm` d'un horodatage
+pub fn clock_time(at_ms: u64) -> String {
+    let in_day = at_ms % MS_PER_DAY;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        in_day / 3_600_000,
+        in_day / 60_000 % 60,
+        in_day / 1000 % 60,
+        in_day % 1000,
+    )
+}
+
+/// Fichier précédent du journal, remplacé à chaque rotation
+pub fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+/// Journal sur disque, borné : au-delà de `max_bytes`, le fichier courant devient
+/// `<fichier>.1` (remplaçant le précédent) et un nouveau fichier commence. Le fichier reste
+/// ouvert et s'écrit par `tokio::fs` : une écriture lente ne bloque pas les threads du runtime
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    max_bytes: u64,
+    written: u64,
+    file: tokio::fs::File,
+}
+
+impl Journal {
+    pub fn open(path: &Path, max_bytes: u64) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let written = file.metadata()?.len();
+        Ok(Self { path: path.to_path_buf(), max_bytes, written, file: tokio::fs::File::from_std(file) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub async fn append(&mut self, records: &[HistoryRecord]) -> Result<()> {
+        let mut lines = Vec::new();
+        for record in records {
+            serde_json::to_writer(&mut lines, record)?;
+            lines.push(b'\n');
+        }
+        if self.written > 0 && self.written + lines.len() as u64 > self.max_bytes {
+            tokio::fs::rename(&self.path, rotated_path(&self.path)).await?;
+            self.file = tokio::fs::OpenOptions::new().create(true).append(true).open(&self.path).await?;
+            self.written = 0;
+        }
+        self.file.write_all(&lines).await?;
+        // Entrées visibles de `history` dès leur ajout
+        self.file.flush().await?;
+        self.written += lines.len() as u64;
+        Ok(())
+    }
+}
+
+/// Ouvre le journal configuré ; sans fichier, ou s'il ne peut être ouvert, rien n'est journalisé
+pub fn open(config: &HistoryConfig) -> Option<Journal> {
+    let path = config.path.as_ref()?;
+    match Journal::open(path, config.max_bytes) {
+        Ok(journal) => Some(journal),
+        Err(e) => {
+            warn!("[HISTORY] Cannot open journal {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Lit le journal, fichier précédent compris, du plus ancien au plus récent ; une ligne
+/// illisible (écriture interrompue) est ignorée
+pub fn read(path: &Path) -> Result<Vec<HistoryRecord>> {
+    let mut records = Vec::new();
+    for file in [rotated_path(path), path.to_path_buf()] {
+        let file = match std::fs::File::open(&file) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for line in std::io::BufReader::new(file).lines() {
+            if let Ok(record) = serde_json::from_str(&line?) {
+                records.push(record);
+            }
+        }
+    }
+    records.sort_by_key(HistoryRecord::at_ms);
+    Ok(records)
+}
+
+/// LSDB et table de routage reconstruites à un instant donné
+#[derive(Debug)]
+pub struct Snapshot {
+    pub at_ms: u64,
+    pub lsdb: Lsdb,
+    pub routes: BTreeMap<Prefix, (String, RouteState)>,
+    /// Entrées du journal rejouées
+    pub replayed: usize,
+}
+
+/// Rejoue les entrées antérieures ou égales à `at_ms`
+pub fn replay(records: &[HistoryRecord], at_ms: u64) -> Snapshot {
+    let mut snapshot = Snapshot { at_ms, lsdb: Lsdb::new(), routes: BTreeMap::new(), replayed: 0 };
+    for record in records.iter().take_while(|record| record.at_ms() <= at_ms) {
+        match record {
+            HistoryRecord::Lsa { at_ms, lsa } => {
+                snapshot.lsdb.install_at(lsa, at_ms / 1000);
+            }
+            HistoryRecord::Route { prefix, route: Some(route), .. } => {
+                snapshot.routes.insert(*prefix, route.clone());
+            }
+            HistoryRecord::Route { prefix, route: None, .. } => {
+                snapshot.routes.remove(prefix);
+            }
+        }
+        snapshot.replayed += 1;
+    }
+    snapshot
+}
+
+/// État reconstruit présenté en texte : LSDB (âges relatifs à l'instant) et table de routage
+pub fn snapshot_report(snapshot: &Snapshot) -> String {
+    let mut lines = vec![
+        format!("État à {} UTC ({} entrées rejouées)", clock_time(snapshot.at_ms), snapshot.replayed),
+        crate::control::lsdb_table(&snapshot.lsdb, snapshot.at_ms / 1000),
+        "Table de routage:".to_string(),
+    ];
+    if snapshot.routes.is_empty() {
+        lines.push("  (vide)".to_string());
+    }
+    for (prefix, (next_hop, route_state)) in &snapshot.routes {
+        lines.push(match route_state.metric() {
+            Some(metric) => format!("  {} via {} (métrique {})", prefix, next_hop, metric),
+            None => format!("  {} via {} (injoignable)", prefix, next_hop),
+        });
+    }
+    lines.join("\n")
+}
+
+/// Instant désigné par l'utilisateur : secondes depuis l'epoch, ou heure UTC `HH:MM[:SS]`
+/// du jour de la dernière entrée du journal. L'instant renvoyé est la fin de la seconde
+/// désignée, pour inclure les changements survenus pendant celle-ci
+pub fn parse_time(text: &str, records: &[HistoryRecord]) -> Option<u64> {
+    if let Ok(secs) = text.parse::<u64>() {
+        return secs.checked_mul(1000)?.checked_add(999);
+    }
+    let fields: Vec<u64> = text.split(':').map(|field| field.parse().ok()).collect::<Option<_>>()?;
+    let (hours, minutes, seconds) = match fields[..] {
+        [hours, minutes] => (hours, minutes, 0),
+        [hours, minutes, seconds] => (hours, minutes, seconds),
+        _ => return None,
+    };
+    if hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    let day = records.last()?.at_ms() / MS_PER_DAY * MS_PER_DAY;
+    Some(day + (hours * 3600 + minutes * 60 + seconds) * 1000 + 999)
+}
+
+/// Ajoute des entrées au journal du routeur, s'il en a un
+async fn record(state: &AppState, records: &[HistoryRecord]) {
+    if records.is_empty() {
+        return;
+    }
+    let mut journal = state.history.lock().await;
+    let Some(journal) = journal.as_mut() else {
+        return;
+    };
+    if let Err(e) = journal.append(records).await {
+        warn!("[HISTORY] Failed to append to {}: {}", journal.path().display(), e);
+    }
+}
+
+fn now_ms() -> u64 {
+    crate::clock::unix_time().as_millis() as u64
+}
+
+/// Journalise une instance de LSA qui vient d'entrer dans la LSDB
+pub async fn record_lsa(state: &AppState, lsa: &LSAMessage) {
+    record(state, &[HistoryRecord::Lsa { at_ms: now_ms(), lsa: Box::new(lsa.clone()) }]).await;
+}
+
+/// Journalise les routes ajoutées, modifiées et retirées par un calcul SPF
+pub async fn record_routes(state: &AppState, new_routing_table: &HashMap<Prefix, (String, RouteState)>, diff: &RouteDiff) {
+    let at_ms = now_ms();
+    let records: Vec<HistoryRecord> = diff.added.iter()
+        .chain(&diff.modified)
+        .map(|prefix| HistoryRecord::Route { at_ms, prefix: *prefix, route: new_routing_table.get(prefix).cloned() })
+        .chain(diff.removed.iter().map(|prefix| HistoryRecord::Route { at_ms, prefix: *prefix, route: None }))
+        .collect();
+    record(state, &records).await;
+}
+
+/// Commande `history` : `history [n]` liste les n dernières entrées du journal,
+/// `history at <instant>` reconstruit la LSDB et la table de routage à cet instant
+pub async fn execute(state: &Arc<AppState>, arguments: &str) -> String {
+    let Some(path) = state.history.lock().await.as_ref().map(|journal| journal.path().to_path_buf()) else {
+        return "Échec: aucun journal configuré (section [history])".to_string();
+    };
+    let records = match read(&path) {
+        Ok(records) => records,
+        Err(e) => return format!("Échec: lecture du journal {}: {}", path.display(), e),
+    };
+    match arguments.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [] | [_] if records.is_empty() => "Journal vide".to_string(),
+        [] => recent_report(&records, DEFAULT_SHOWN),
+        [count] => match count.parse::<usize>() {
+            Ok(count) => recent_report(&records, count),
+            Err(_) => format!("Argument invalide: '{}'. Usage: history [n] | history at <instant>", count),
+        },
+        ["at", instant] => match parse_time(instant, &records) {
+            Some(at_ms) => snapshot_report(&replay(&records, at_ms)),
+            None => format!("Argument invalide: '{}' (secondes Unix ou HH:MM[:SS] UTC)", instant),
+        },
+        _ => "Usage: history [n] | history at <instant>".to_string(),
+    }
+}
+
+/// Les `count` dernières entrées, de la plus ancienne à la plus récente
+pub fn recent_report(records: &[HistoryRecord], count: usize) -> String {
+    records[records.len().saturating_sub(count)..].iter()
+        .map(HistoryRecord::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
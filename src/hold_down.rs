@@ -0,0 +1,48 @@
+use crate::types::Prefix;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::info;
+
+pub use pospf_core::hold_down::*;
+
+/// Met en retenue les routes de la table perdues (`prefixes`), d'après le prochain saut et
+/// la métrique qu'elles avaient, puis relance le SPF à l'échéance pour accepter un chemin
+/// moins bon s'il n'en reste pas d'autre
+pub async fn hold(state: &Arc<crate::AppState>, prefixes: &[Prefix], reason: &str) {
+    let config = state.config().await.hold_down;
+    if !config.enabled || prefixes.is_empty() {
+        return;
+    }
+    let lost: Vec<(Prefix, String, u32)> = {
+        let routing_table = state.routing_table.read().await;
+        prefixes.iter()
+            .filter_map(|prefix| {
+                let (next_hop, route_state) = routing_table.get(prefix)?;
+                Some((*prefix, next_hop.clone(), route_state.metric().unwrap_or(u32::MAX)))
+            })
+            .collect()
+    };
+    let duration = Duration::from_secs(config.duration_sec);
+    let now = Instant::now();
+    let mut held = Vec::new();
+    {
+        let mut hold_down = state.hold_down.lock().await;
+        for (prefix, next_hop, metric) in lost {
+            if hold_down.start(prefix, &next_hop, metric, duration, now) {
+                held.push(prefix);
+            }
+        }
+        state.metrics.routes_held_down.store(hold_down.len() as u64, std::sync::atomic::Ordering::Relaxed);
+    }
+    if held.is_empty() {
+        return;
+    }
+    info!("[HOLD-DOWN] {} route(s) held for {}s after {}: {:?}", held.len(), config.duration_sec, reason, held);
+    let state = Arc::clone(state);
+    tokio::spawn(async move {
+        tokio::time::sleep(duration).await;
+        state.metrics.spf_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        state.spf.schedule();
+    });
+}
@@ -0,0 +1,223 @@
+use crate::dijkstra::calculate_ospf_cost;
+use crate::AppState;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{Html, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use tracing::{debug, error, info};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+/// Tableau de bord embarqué dans le binaire
+const DASHBOARD_HTML: &str = include_str!("web/dashboard.html");
+
+#[derive(Debug, Serialize)]
+pub struct TopologyNode {
+    pub id: String,
+    /// Nom système du routeur, s'il a été annoncé
+    pub hostname: Option<String>,
+    /// « R3 (10.1.0.3) », ou l'adresse seule
+    pub label: String,
+    pub local: bool,
+    /// Le routeur a annoncé un LSA (présent dans la LSDB)
+    pub advertised: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TopologyLink {
+    pub source: String,
+    pub target: String,
+    pub capacity_mbps: u32,
+    pub cost: u32,
+    pub up: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TopologyView {
+    pub router_id: String,
+    pub enabled: bool,
+    pub nodes: Vec<TopologyNode>,
+    pub links: Vec<TopologyLink>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RouteView {
+    pub destination: String,
+    pub next_hop: String,
+    pub cost: Option<u32>,
+}
+
+/// Construit la vue du graphe à partir des voisins directs et des LSA de la LSDB ;
+/// les routeurs y sont identifiés par leur identifiant (leur adresse s'ils n'en annoncent pas)
+pub async fn topology_view(state: &AppState) -> TopologyView {
+    let local_id = state.router_id.to_string();
+    let mut advertised = BTreeSet::new();
+    let mut hostnames: BTreeMap<String, String> = BTreeMap::new();
+    if let Some(hostname) = &state.hostname {
+        hostnames.insert(local_id.clone(), hostname.clone());
+    }
+    let mut links: BTreeMap<(String, String), TopologyLink> = BTreeMap::new();
+    let mut add_link = |from: &str, to: &str, capacity: u32, cost: Option<u32>, up: bool| {
+        let link_cost = |capacity: u32, up: bool| match cost {
+            Some(cost) if up => cost,
+            _ => calculate_ospf_cost(capacity, up),
+        };
+        // Liens non orientés : une seule arête par paire, l'état « down » l'emporte
+        let key = if from < to { (from.to_string(), to.to_string()) } else { (to.to_string(), from.to_string()) };
+        let link = links.entry(key.clone()).or_insert_with(|| TopologyLink {
+            source: key.0,
+            target: key.1,
+            capacity_mbps: capacity,
+            cost: link_cost(capacity, up),
+            up,
+        });
+        link.up &= up;
+        link.capacity_mbps = link.capacity_mbps.min(capacity);
+        link.cost = link.cost.max(link_cost(link.capacity_mbps, link.up));
+    };
+
+    for neighbor in state.neighbors.read().await.values() {
+        add_link(&local_id, &neighbor.identity(), neighbor.capacity, neighbor.cost, neighbor.link_up);
+        if let Some(hostname) = &neighbor.hostname {
+            hostnames.insert(neighbor.identity(), hostname.clone());
+        }
+    }
+    for lsa in state.lsdb.read().await.router_lsas() {
+        advertised.insert(lsa.originator.clone());
+        for neighbor in &lsa.neighbors {
+            add_link(&lsa.originator, &neighbor.identity(), neighbor.capacity, neighbor.cost, neighbor.link_up);
+            if let Some(hostname) = &neighbor.hostname {
+                hostnames.entry(neighbor.identity()).or_insert_with(|| hostname.clone());
+            }
+        }
+    }
+
+    let mut ids: BTreeSet<String> = advertised.clone();
+    ids.insert(local_id.clone());
+    for link in links.values() {
+        ids.insert(link.source.clone());
+        ids.insert(link.target.clone());
+    }
+    let nodes = ids.into_iter().map(|id| {
+        let hostname = hostnames.get(&id).cloned();
+        TopologyNode {
+            label: crate::types::display_name(&id, hostname.as_deref()),
+            local: id == local_id,
+            advertised: id == local_id || advertised.contains(&id),
+            hostname,
+            id,
+        }
+    }).collect();
+
+    TopologyView {
+        router_id: local_id,
+        enabled: state.is_enabled().await,
+        nodes,
+        links: links.into_values().collect(),
+    }
+}
+
+async fn dashboard() -> Html<&'static str> {
+    Html(DASHBOARD_HTML)
+}
+
+async fn topology(State(state): State<Arc<AppState>>) -> Json<TopologyView> {
+    Json(topology_view(&state).await)
+}
+
+async fn routes(State(state): State<Arc<AppState>>) -> Json<Vec<RouteView>> {
+    let table = state.routing_table.read().await;
+    let mut routes: Vec<RouteView> = table.iter().map(|(destination, (next_hop, route_state))| RouteView {
+        destination: destination.to_string(),
+        next_hop: next_hop.clone(),
+        cost: route_state.metric(),
+    }).collect();
+    routes.sort_by(|a, b| a.destination.cmp(&b.destination));
+    Json(routes)
+}
+
+/// Flux Server-Sent Events des changements d'état du protocole (voir `events::RouterEvent`)
+async fn events(State(state): State<Arc<AppState>>) -> Sse<impl futures::Stream<Item = std::result::Result<Event, std::convert::Infallible>>> {
+    let stream = futures::stream::unfold(state.events.subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let event = Event::default().json_data(&event).unwrap_or_else(|_| Event::default());
+                    return Some((Ok(event), receiver));
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Les mêmes événements sur une WebSocket, un message texte JSON par événement, pour
+/// animer la convergence en direct ; les messages du client sont ignorés
+async fn events_ws(ws: WebSocketUpgrade, State(state): State<Arc<AppState>>) -> Response {
+    let receiver = state.events.subscribe();
+    ws.on_upgrade(move |socket| stream_events(socket, receiver))
+}
+
+async fn stream_events(mut socket: WebSocket, mut receiver: tokio::sync::broadcast::Receiver<crate::events::RouterEvent>) {
+    loop {
+        tokio::select! {
+            event = receiver.recv() => match event {
+                Ok(event) => {
+                    let Ok(text) = serde_json::to_string(&event) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        return;
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!("WebSocket client lagging, {} events skipped", skipped);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn metrics(State(state): State<Arc<AppState>>) -> String {
+    let mut out = state.metrics.render();
+    out.push_str(&state.neighbor_stats.lock().await.render());
+    out
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(dashboard))
+        .route("/topology", get(topology))
+        .route("/routes", get(routes))
+        .route("/metrics", get(metrics))
+        .route("/events", get(events))
+        .route("/ws", get(events_ws))
+        .with_state(state)
+}
+
+/// Démarre le serveur HTTP de supervision si activé dans la configuration
+pub fn spawn_http_server(state: Arc<AppState>, listen: String) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(&listen).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to bind HTTP server on {}: {}", listen, e);
+                return;
+            }
+        };
+        info!("HTTP dashboard listening on http://{}", listen);
+        if let Err(e) = axum::serve(listener, router(state)).await {
+            error!("HTTP server error: {}", e);
+        }
+    })
+}
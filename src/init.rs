@@ -1,25 +1,126 @@
+/// Journalisation structurée : niveau selon RUST_LOG (info par défaut), sortie JSON
+/// avec les spans courants si `ROUTER_LOG_FORMAT=json` (ingestion ELK/Loki)
 pub fn init_logging_and_env() {
-    if std::env::var("RUST_LOG").is_err() {
-        std::env::set_var("RUST_LOG", "info");
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
+    let json = std::env::var(crate::read_config::LOG_FORMAT_ENV)
+        .is_ok_and(|format| format.trim().eq_ignore_ascii_case("json"));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    if json {
+        subscriber.json().with_current_span(true).with_span_list(true).init();
+    } else {
+        subscriber.init();
     }
-    env_logger::init();
 }
 
-pub async fn init_socket(port: u16) -> crate::error::Result<std::sync::Arc<tokio::net::UdpSocket>> {
-    let socket = std::sync::Arc::new(tokio::net::UdpSocket::bind(format!("0.0.0.0:{}", port)).await?);
+/// Socket principale du protocole, liée au périphérique de la VRF `vrf` s'il y a lieu ;
+/// le port est partagé entre les instances quand des VRF sont configurées
+pub async fn init_socket(config: &crate::read_config::RouterConfig, vrf: Option<&str>) -> crate::error::Result<std::sync::Arc<crate::transport::Socket>> {
+    let socket = crate::net_utils::bind_protocol_socket(config.port, vrf, !config.vrfs.is_empty())?;
     socket.set_broadcast(true)?;
-    Ok(socket)
+    crate::net_utils::set_dscp(&socket, config.qos.dscp)?;
+    match (config.transport, vrf) {
+        (crate::read_config::TransportMode::Multicast, None) => crate::net_utils::join_multicast_group(&socket)?,
+        (crate::read_config::TransportMode::Multicast, Some(_)) => {
+            socket.set_multicast_loop_v4(false)?;
+            for ip in config.interfaces.iter().flat_map(|iface| crate::net_utils::get_interface_ipv4_addresses(&iface.name)) {
+                crate::net_utils::join_multicast_on(&socket, ip)?;
+            }
+        }
+        (crate::read_config::TransportMode::Broadcast, _) => {}
+    }
+    Ok(std::sync::Arc::new(socket.into()))
+}
+
+/// Crée une socket d'émission par adresse locale en mode multicast (aucune en mode broadcast)
+pub fn init_flood_sockets(
+    mode: crate::read_config::TransportMode,
+    dscp: u8,
+    addresses: Vec<std::net::Ipv4Addr>,
+    vrf: Option<&str>,
+) -> crate::error::Result<std::collections::HashMap<String, std::sync::Arc<crate::transport::Socket>>> {
+    let mut sockets = std::collections::HashMap::new();
+    if mode == crate::read_config::TransportMode::Multicast {
+        for ip in addresses {
+            let socket = crate::net_utils::create_multicast_sender(ip, dscp, vrf)?;
+            sockets.insert(ip.to_string(), std::sync::Arc::new(socket.into()));
+        }
+    }
+    Ok(sockets)
 }
 
-pub fn init_state(router_ip: String, config: crate::read_config::RouterConfig, key: Vec<u8>) -> std::sync::Arc<crate::AppState> {
+#[allow(clippy::too_many_arguments)]
+pub fn init_state(
+    router_ip: String,
+    config: crate::read_config::RouterConfig,
+    config_path: Option<std::path::PathBuf>,
+    key: Vec<u8>,
+    socket: std::sync::Arc<crate::transport::Socket>,
+    interfaces: crate::interfaces::InterfaceProvider,
+    routes: std::sync::Arc<dyn crate::route_backend::RouteBackend>,
+    flood_sockets: std::collections::HashMap<String, std::sync::Arc<crate::transport::Socket>>,
+    vrf: Option<String>,
+) -> std::sync::Arc<crate::AppState> {
+    let interface_keys = config.interface_keys();
+    let history = crate::history::open(&config.history);
     std::sync::Arc::new(crate::AppState {
-        topology: tokio::sync::Mutex::new(std::collections::HashMap::new()),
-        neighbors: tokio::sync::Mutex::new(std::collections::HashMap::new()),
-        routing_table: tokio::sync::Mutex::new(std::collections::HashMap::new()),
-        processed_lsa: tokio::sync::Mutex::new(std::collections::HashSet::new()),
+        lsdb: tokio::sync::RwLock::new(crate::lsdb::Lsdb::new()),
+        neighbors: tokio::sync::RwLock::new(std::collections::HashMap::new()),
+        routing_table: tokio::sync::RwLock::new(std::collections::HashMap::new()),
+        route_origins: tokio::sync::RwLock::new(std::collections::HashMap::new()),
+        programmed_routes: tokio::sync::Mutex::new(crate::route_diff::ProgrammedRoutes::new()),
+        route_ops: crate::route_queue::RouteOps::new(),
+        filtered_routes: tokio::sync::Mutex::new(Vec::new()),
+        hold_down: tokio::sync::Mutex::new(crate::hold_down::HoldDown::new()),
+        processed_lsa: tokio::sync::Mutex::new(crate::lsa_cache::ProcessedLsaCache::new()),
+        reassembly: tokio::sync::Mutex::new(crate::fragment::Reassembler::new()),
+        lsa_parts: tokio::sync::Mutex::new(crate::lsa_part::PartAssembler::new()),
+        lsa_sequences: tokio::sync::Mutex::new(std::collections::HashMap::new()),
+        own_sequence: crate::sequence::SequenceCounter::from_clock(),
+        router_id_guard: tokio::sync::Mutex::new(crate::collision::RouterIdGuard::new()),
+        redistributed_routes: tokio::sync::RwLock::new(std::collections::HashMap::new()),
+        bgp: tokio::sync::RwLock::new(crate::bgp_lite::BgpRib::new()),
+        router_id: config.router_id
+            .or_else(|| crate::types::RouterId::from_addresses(interfaces.interfaces().iter().map(|iface| iface.ip())))
+            .unwrap_or_else(|| router_ip.parse().unwrap_or(crate::types::RouterId(0))),
         local_ip: router_ip,
-        enabled: tokio::sync::Mutex::new(true),
-        config,
-        key: key,
+        hostname: config.hostname.clone()
+            // Plusieurs instances d'une même machine se distinguent par leur espace de noms
+            .or_else(|| config.netns.clone().filter(|name| crate::packet::is_valid_hostname(name)))
+            .or_else(|| crate::read_config::get_hostname().ok().filter(|name| crate::packet::is_valid_hostname(name))),
+        vrf,
+        socket,
+        interfaces,
+        routes,
+        port: config.port,
+        enabled: std::sync::atomic::AtomicBool::new(true),
+        interface_states: tokio::sync::RwLock::new(config.interfaces.iter()
+            .map(|iface| (iface.name.clone(), iface.initial_state()))
+            .collect()),
+        config: tokio::sync::RwLock::new(config),
+        config_path: tokio::sync::Mutex::new(config_path),
+        key: tokio::sync::RwLock::new(key),
+        interface_keys: tokio::sync::RwLock::new(interface_keys),
+        flood_sockets: tokio::sync::RwLock::new(flood_sockets),
+        flooding: tokio::sync::Mutex::new(crate::flooding::FloodingState::new()),
+        lsa_deltas: tokio::sync::Mutex::new(crate::lsa_delta::DeltaState::new()),
+        neighbor_auth: tokio::sync::Mutex::new(crate::auth::NeighborAuth::new()),
+        control_replay: tokio::sync::Mutex::new(crate::control_auth::ControlReplay::new()),
+        probes: tokio::sync::Mutex::new(crate::probe::PendingProbes::new()),
+        path_mtu: tokio::sync::Mutex::new(crate::path_mtu::PathMtuTable::new()),
+        stream_sessions: tokio::sync::Mutex::new(crate::tcp_session::StreamSessions::new()),
+        stream_inbound: crate::tcp_session::Inbound::new(),
+        route_queries: tokio::sync::Mutex::new(crate::route_query::PendingQueries::new()),
+        damping: tokio::sync::Mutex::new(crate::damping::LsaDamping::new()),
+        history: tokio::sync::Mutex::new(history),
+        spf: crate::spf::SpfScheduler::new(),
+        hello_timestamps: tokio::sync::Mutex::new(std::collections::HashMap::new()),
+        neighbor_addresses: tokio::sync::Mutex::new(std::collections::HashMap::new()),
+        discovered_peers: tokio::sync::Mutex::new(crate::discovery::DiscoveredPeers::new()),
+        neighbor_endpoints: tokio::sync::Mutex::new(std::collections::HashMap::new()),
+        neighbor_stats: tokio::sync::Mutex::new(crate::neighbor_stats::NeighborStatsTable::new()),
+        vrf_instances: tokio::sync::RwLock::new(std::collections::HashMap::new()),
+        metrics: crate::metrics::Metrics::new(),
+        events: crate::events::EventBus::new(),
     })
 }
\ No newline at end of file
@@ -0,0 +1,37 @@
+use crate::dijkstra::RouteInfo;
+use crate::read_config::InstallPolicyConfig;
+use crate::types::Prefix;
+use serde::Serialize;
+
+/// Route calculée mais écartée par les seuils d'installation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FilteredRoute {
+    pub prefix: Prefix,
+    pub next_hop: String,
+    pub hop_count: u32,
+    pub bottleneck_mbps: u32,
+    pub reason: String,
+}
+
+/// Seuil que le chemin `route` ne respecte pas, le cas échéant
+pub fn violation(policy: &InstallPolicyConfig, route: &RouteInfo) -> Option<String> {
+    if let Some(min) = policy.min_bottleneck_mbps.filter(|min| route.bottleneck_capacity < *min) {
+        return Some(format!("capacité du chemin {} Mbps < {} Mbps", route.bottleneck_capacity, min));
+    }
+    if let Some(max) = policy.max_hop_count.filter(|max| route.hop_count > *max) {
+        return Some(format!("{} sauts > {}", route.hop_count, max));
+    }
+    None
+}
+
+/// Rapport de la commande `filtered-routes`
+pub fn report(filtered: &[FilteredRoute]) -> String {
+    if filtered.is_empty() {
+        return "Aucune route écartée par les seuils d'installation".to_string();
+    }
+    let mut lines = vec![format!("{} route(s) écartée(s):", filtered.len())];
+    for route in filtered {
+        lines.push(format!("  {} via {} : {}", route.prefix, route.next_hop, route.reason));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,132 @@
+use crate::error::{AppError, Result};
+use crate::read_config::{RouterConfig, TransportMode};
+use pnet::datalink;
+use pnet::ipnetwork::{IpNetwork, Ipv4Network};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+/// Interface IPv4 locale : nom et adresse avec son préfixe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalInterface {
+    pub name: String,
+    pub network: Ipv4Network,
+}
+
+impl LocalInterface {
+    pub fn new(name: &str, network: Ipv4Network) -> Self {
+        Self { name: name.to_string(), network }
+    }
+
+    pub fn ip(&self) -> Ipv4Addr {
+        self.network.ip()
+    }
+}
+
+/// Source des interfaces d'un routeur : celles du système, ou une liste fixe
+/// pour les routeurs simulés qui partagent le même processus
+#[derive(Debug, Clone, Default)]
+pub enum InterfaceProvider {
+    #[default]
+    System,
+    Static(Vec<LocalInterface>),
+}
+
+impl InterfaceProvider {
+    /// Interfaces IPv4 hors loopback
+    pub fn interfaces(&self) -> Vec<LocalInterface> {
+        match self {
+            Self::System => datalink::interfaces()
+                .into_iter()
+                .flat_map(|iface| {
+                    iface.ips.into_iter().filter_map(move |ip_network| match ip_network {
+                        IpNetwork::V4(network) if !network.ip().is_loopback() && !network.ip().is_unspecified() => {
+                            Some(LocalInterface::new(&iface.name, network))
+                        }
+                        _ => None,
+                    })
+                })
+                .collect(),
+            Self::Static(interfaces) => interfaces.clone(),
+        }
+    }
+
+    /// Adresses IPv4 locales hors loopback
+    pub fn local_ipv4_addresses(&self) -> Vec<Ipv4Addr> {
+        self.interfaces().iter().map(LocalInterface::ip).collect()
+    }
+
+    /// Nom de l'interface locale dont le sous-réseau contient `ip` (adresse locale ou voisin)
+    pub fn name_for_address(&self, ip: &str) -> Option<String> {
+        let ip: Ipv4Addr = ip.parse().ok()?;
+        self.interfaces()
+            .into_iter()
+            .find(|iface| iface.network.contains(ip))
+            .map(|iface| iface.name)
+    }
+
+    /// Adresses IPv4 portées par une interface nommée
+    pub fn addresses_of(&self, name: &str) -> Vec<Ipv4Addr> {
+        self.interfaces().iter().filter(|iface| iface.name == name).map(LocalInterface::ip).collect()
+    }
+
+    /// Index d'une interface nommée : celui du système, ou pour une liste fixe son rang
+    /// (à partir de 1) parmi les noms distincts
+    pub fn index_of(&self, name: &str) -> Option<u32> {
+        match self {
+            Self::System => datalink::interfaces().into_iter().find(|iface| iface.name == name).map(|iface| iface.index),
+            Self::Static(interfaces) => {
+                let mut names: Vec<&str> = Vec::new();
+                for iface in interfaces {
+                    if !names.contains(&iface.name.as_str()) {
+                        names.push(&iface.name);
+                    }
+                }
+                names.iter().position(|known| *known == name).map(|position| position as u32 + 1)
+            }
+        }
+    }
+
+    /// Identifiant du routeur : première adresse IPv4 locale
+    pub fn router_ip(&self) -> Result<String> {
+        self.local_ipv4_addresses()
+            .first()
+            .map(|ip| ip.to_string())
+            .ok_or_else(|| AppError::ConfigError("No valid IP address found".to_string()))
+    }
+
+    /// Adresses de diffusion (broadcast ou multicast selon le mode) pour chaque interface locale
+    pub fn flood_addresses(&self, mode: TransportMode, port: u16) -> Vec<(String, SocketAddr)> {
+        self.interfaces()
+            .into_iter()
+            .map(|iface| {
+                let target = match mode {
+                    TransportMode::Broadcast => iface.network.broadcast(),
+                    TransportMode::Multicast => crate::net_utils::OSPF_ALL_ROUTERS,
+                };
+                (iface.ip().to_string(), SocketAddr::new(IpAddr::V4(target), port))
+            })
+            .collect()
+    }
+
+    /// Adresses de diffusion limitées aux interfaces déclarées dans la configuration ;
+    /// une interface point à point vise son voisin configuré en unicast
+    pub fn configured_flood_addresses(&self, config: &RouterConfig, port: u16) -> Vec<(String, SocketAddr)> {
+        self.flood_addresses(config.transport, port)
+            .into_iter()
+            .filter_map(|(local_ip, addr)| {
+                let name = self.name_for_address(&local_ip);
+                if name.as_ref().is_some_and(|name| !config.is_interface_configured(name)) {
+                    return None;
+                }
+                // Les interfaces OSPFv2 ou RIP n'échangent pas de messages P-OSPF
+                if config.interface_protocol(name.as_deref()) != crate::read_config::InterfaceProtocol::Pospf {
+                    return None;
+                }
+                let addr = match config.point_to_point_peer(name.as_deref()) {
+                    Some(peer) => SocketAddr::new(IpAddr::V4(peer), port),
+                    None => addr,
+                };
+                Some((local_ip, addr))
+            })
+            .collect()
+    }
+}
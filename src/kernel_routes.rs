@@ -0,0 +1,133 @@
+use std::net::{IpAddr, Ipv4Addr};
+use futures::future::BoxFuture;
+use futures::stream::TryStreamExt;
+use netlink_packet_route::nlas::route::Nla;
+use netlink_packet_route::RouteMessage;
+use pnet::ipnetwork::Ipv4Network;
+use rtnetlink::{new_connection, Handle, IpVersion};
+use crate::error::{AppError, Result};
+use crate::route_backend::{KernelRoute, RouteBackend};
+
+pub const RT_TABLE_MAIN: u8 = netlink_packet_route::RT_TABLE_MAIN;
+
+/// Table de routage Linux, via netlink : la table principale ou celle d'une VRF
+#[derive(Debug, Clone, Copy)]
+pub struct NetlinkBackend {
+    table: u32,
+}
+
+impl Default for NetlinkBackend {
+    fn default() -> Self {
+        Self::new(u32::from(RT_TABLE_MAIN))
+    }
+}
+
+impl NetlinkBackend {
+    pub fn new(table: u32) -> Self {
+        Self { table }
+    }
+}
+
+impl RouteBackend for NetlinkBackend {
+    fn name(&self) -> &'static str {
+        "netlink"
+    }
+
+    fn list_routes(&self) -> BoxFuture<'_, Result<Vec<KernelRoute>>> {
+        Box::pin(async {
+            Ok(list_route_messages(self.table).await?.into_iter().map(|(route, _)| route).collect())
+        })
+    }
+
+    fn install_route(&self, route: KernelRoute) -> BoxFuture<'_, Result<()>> {
+        Box::pin(install_route(self.table, route))
+    }
+
+    fn delete_route(&self, route: KernelRoute) -> BoxFuture<'_, Result<()>> {
+        Box::pin(delete_route(self.table, route))
+    }
+}
+
+fn connect() -> Result<Handle> {
+    let (connection, handle, _) = new_connection()
+        .map_err(|e| AppError::RouteError(format!("Erreur netlink: {}", e)))?;
+    tokio::spawn(connection);
+    Ok(handle)
+}
+
+/// Routes IPv4 de la table `table`, avec le message netlink qui permet de les supprimer
+async fn list_route_messages(table: u32) -> Result<Vec<(KernelRoute, RouteMessage)>> {
+    let handle = connect()?;
+    let mut stream = handle.route().get(IpVersion::V4).execute();
+    let mut routes = Vec::new();
+    while let Some(message) = stream.try_next().await
+        .map_err(|e| AppError::RouteError(format!("Erreur netlink: {}", e)))?
+    {
+        // Au-delà de 255, l'identifiant n'est porté que par l'attribut RTA_TABLE
+        let message_table = message.nlas.iter().find_map(|nla| match nla {
+            Nla::Table(table) => Some(*table),
+            _ => None,
+        });
+        if message_table.unwrap_or(u32::from(message.header.table)) != table {
+            continue;
+        }
+        let (destination, prefix_len) = match message.destination_prefix() {
+            Some((IpAddr::V4(ip), len)) => (ip, len),
+            Some(_) => continue,
+            None => (Ipv4Addr::UNSPECIFIED, message.header.destination_prefix_length),
+        };
+        let Ok(prefix) = Ipv4Network::new(destination, prefix_len) else {
+            continue;
+        };
+        let gateway = match message.gateway() {
+            Some(IpAddr::V4(gw)) => Some(gw),
+            _ => None,
+        };
+        let priority = message.nlas.iter().find_map(|nla| match nla {
+            Nla::Priority(priority) => Some(*priority),
+            _ => None,
+        });
+        let output_interface = message.nlas.iter().find_map(|nla| match nla {
+            Nla::Oif(index) => Some(*index),
+            _ => None,
+        });
+        let route = KernelRoute { prefix, gateway, output_interface, protocol: message.header.protocol, priority };
+        routes.push((route, message));
+    }
+    Ok(routes)
+}
+
+/// Installe (ou remplace) une route, par une passerelle et/ou une interface de sortie
+/// (route de périphérique sans passerelle)
+async fn install_route(table: u32, route: KernelRoute) -> Result<()> {
+    let handle = connect()?;
+    let mut request = handle.route().add()
+        .v4()
+        .destination_prefix(route.prefix.network(), route.prefix.prefix())
+        .protocol(route.protocol)
+        .table_id(table)
+        .replace();
+    if let Some(gateway) = route.gateway {
+        request = request.gateway(gateway);
+    }
+    if let Some(index) = route.output_interface {
+        request = request.output_interface(index);
+    }
+    if let Some(priority) = route.priority {
+        request.message_mut().nlas.push(Nla::Priority(priority));
+    }
+    request.execute().await
+        .map_err(|e| AppError::RouteError(format!("Erreur netlink lors de l'ajout de {} via {:?}: {}", route.prefix, route.gateway, e)))
+}
+
+/// Supprime une route du noyau précédemment listée
+async fn delete_route(table: u32, route: KernelRoute) -> Result<()> {
+    let message = list_route_messages(table).await?
+        .into_iter()
+        .find(|(listed, _)| *listed == route)
+        .map(|(_, message)| message)
+        .ok_or_else(|| AppError::RouteError(format!("Route vers {} introuvable", route.prefix)))?;
+    let handle = connect()?;
+    handle.route().del(message).execute().await
+        .map_err(|e| AppError::RouteError(format!("Erreur netlink lors de la suppression de {}: {}", route.prefix, e)))
+}
@@ -1,3 +1,72 @@
-pub mod error;
 pub mod net_utils;
-pub mod read_config;
\ No newline at end of file
+pub mod read_config;
+pub mod trace;
+
+// Logique du protocole sans réseau, partagée avec le simulateur et le CLI
+pub use pospf_core::{clock, error, events, fragment, framing, k_paths, lsa_delta, lsa_part, lsdb, ospfv2, packet, rip, spf_engine, spof, types};
+
+pub mod adaptive_hello;
+pub mod auth;
+pub mod bgp_lite;
+pub mod collision;
+pub mod control;
+pub mod control_auth;
+#[cfg(unix)]
+pub mod control_socket;
+pub mod daemon;
+pub mod damping;
+pub mod dijkstra;
+pub mod discovery;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+pub mod filter;
+pub mod flooding;
+pub mod grpc_api;
+pub mod hello;
+pub mod history;
+pub mod hold_down;
+pub mod http_api;
+pub mod interfaces;
+pub mod init;
+pub mod install_policy;
+#[cfg(target_os = "linux")]
+pub mod kernel_routes;
+pub mod lsa;
+pub mod lsa_cache;
+pub mod mdns;
+pub mod metrics;
+pub mod neighbor;
+pub mod neighbor_stats;
+pub mod netns;
+pub mod network_lsa;
+pub mod nexthop;
+pub mod ospfv2_interop;
+pub mod packet_loop;
+pub mod path_mtu;
+pub mod persist;
+pub mod portable_routes;
+pub mod probe;
+pub mod quic;
+pub mod reconcile;
+pub mod redistribute;
+pub mod reload;
+pub mod rip_gateway;
+pub mod route_backend;
+pub mod route_diff;
+pub mod route_query;
+pub mod route_queue;
+pub mod sequence;
+pub mod shutdown;
+pub mod sim;
+pub mod spf;
+pub mod state;
+pub mod tasks;
+pub mod tcp_session;
+pub mod topology;
+pub mod transport;
+pub mod vrf;
+
+pub use daemon::{RouterDaemon, RouterHandle};
+pub use state::AppState;
+
+pub use pospf_core::INITIAL_TTL;
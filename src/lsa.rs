@@ -1,26 +1,121 @@
 use std::collections::HashMap;
-use std::net::{IpAddr, Ipv4Addr};
 use std::sync::Arc;
-use log::{info, warn, error, debug};
-use crate::types::{LSAMessage, RouteState};
+use tracing::{info, warn, debug};
+use crate::types::{Prefix, RouteState};
 use crate::error::{AppError, Result};
 
-pub async fn update_topology(state: Arc<crate::AppState>, lsa: &crate::types::LSAMessage) -> Result<()> {
-    let mut topology = state.topology.lock().await;
+/// Vrai si `candidate` est une instance plus récente que `current` (voir `sequence::is_newer`)
+pub fn is_newer_sequence(candidate: u32, current: u32) -> bool {
+    crate::sequence::is_newer(candidate, current)
+}
 
-    let router_state = topology.entry(lsa.originator.clone()).or_insert_with(crate::types::Router::new);
+/// Vérifie que le numéro de séquence d'un LSA progresse strictement pour son originateur
+/// et enregistre la nouvelle valeur. Un LSA dont la séquence régresse est un rejeu.
+pub async fn accept_lsa_sequence(state: &crate::AppState, originator: &str, seq_num: u32) -> bool {
+    let mut sequences = state.lsa_sequences.lock().await;
+    match sequences.get(originator) {
+        Some(&highest) if !is_newer_sequence(seq_num, highest) => {
+            warn!("[REPLAY] Rejected LSA from originator {} with seq {} (highest seen: {})", originator, seq_num, highest);
+            false
+        }
+        _ => {
+            sequences.insert(originator.to_string(), seq_num);
+            true
+        }
+    }
+}
 
-    // Met à jour si le nouveau LSA est plus récent
-    if router_state.last_lsa.as_ref().map_or(true, |old_lsa| lsa.seq_num > old_lsa.seq_num) {
-        router_state.last_lsa = Some(lsa.clone());
-        debug!("Updated topology for originator {}", lsa.originator);
+pub async fn update_topology(state: Arc<crate::AppState>, lsa: &crate::types::LSAMessage) -> Result<()> {
+    // N'installe que les instances plus récentes que celles de la LSDB
+    if state.lsdb.write().await.install(lsa) {
+        debug!("Updated LSDB for originator {} ({:?})", lsa.originator, crate::lsdb::Lsdb::classify(lsa));
+        state.events.emit(crate::events::RouterEvent::LsaReceived { originator: lsa.originator.clone(), seq_num: lsa.seq_num });
+        crate::history::record_lsa(&state, lsa).await;
+        // Préfixes annoncés injoignables : retenue de la route que nous en avions
+        let unreachable: Vec<Prefix> = lsa.routing_table.iter()
+            .filter(|(_, route_state)| matches!(route_state, RouteState::Unreachable))
+            .map(|(prefix, _)| *prefix)
+            .collect();
+        let reason = format!("unreachable advertisement from {}", lsa.originator);
+        crate::hold_down::hold(&state, &unreachable, &reason).await;
     }
     
     Ok(())
 }
 
+/// Réseaux annoncés par ce routeur : sous-réseau de chaque interface présente, sauf
+/// `redistribute_connected = false`, et préfixes de son option `advertise` ; chacun avec le
+/// coût de son interface pour métrique, sauf métrique imposée par `network_metrics`
+pub fn originated_networks(
+    config: &crate::read_config::RouterConfig,
+    interfaces: &crate::interfaces::InterfaceProvider,
+    interface_states: &HashMap<String, crate::types::InterfaceState>,
+    router_ip: &str,
+) -> HashMap<Prefix, RouteState> {
+    let mut route_states = HashMap::new();
+
+    for iface in interfaces.interfaces() {
+        let configured = config.interface(&iface.name);
+        // Coût de l'interface (imposé ou dérivé de sa capacité), même si le lien est coupé
+        let cost = interface_states.get(&iface.name)
+            .map_or(0, |state| state.cost.unwrap_or_else(|| crate::dijkstra::calculate_ospf_cost(state.capacity_mbps, true)));
+        let metric = |prefix: &Prefix| config.network_metric(prefix).unwrap_or(cost);
+        if configured.is_none_or(|configured| configured.redistribute_connected) {
+            let network_cidr = Prefix::new(iface.network);
+            route_states.insert(network_cidr, RouteState::Active(metric(&network_cidr)));
+            debug!("Router {} advertising connected network {} ({})", router_ip, network_cidr, iface.name);
+        }
+        for prefix in configured.into_iter().flat_map(|configured| &configured.advertise) {
+            if let Ok(prefix) = prefix.parse::<Prefix>() {
+                route_states.insert(prefix, RouteState::Active(metric(&prefix)));
+                debug!("Router {} advertising configured network {} ({})", router_ip, prefix, iface.name);
+            }
+        }
+    }
+
+    route_states
+}
+
+/// Étiquette d'un réseau annoncé par ce routeur : `route_tag` de l'interface qui l'annonce
+pub fn originated_tag(config: &crate::read_config::RouterConfig, interfaces: &crate::interfaces::InterfaceProvider, prefix: &Prefix) -> Option<u32> {
+    interfaces.interfaces().iter()
+        .filter_map(|iface| Some((Prefix::new(iface.network), config.interface(&iface.name)?)))
+        .find(|(network, configured)| {
+            (configured.redistribute_connected && network == prefix)
+                || configured.advertise.iter().any(|advertised| advertised.parse::<Prefix>().is_ok_and(|advertised| advertised == *prefix))
+        })
+        .and_then(|(_, configured)| configured.route_tag)
+}
+
+/// Métrique de la route par défaut à annoncer, si `originate_default` est actif et sa condition remplie
+pub async fn default_route_origination(config: &crate::read_config::RouterConfig, interfaces: &crate::interfaces::InterfaceProvider, routes: &dyn crate::route_backend::RouteBackend) -> Option<u32> {
+    use crate::read_config::DefaultOriginateCondition;
+
+    let originate = &config.originate_default;
+    if !originate.enabled {
+        return None;
+    }
+    let condition_met = match originate.condition {
+        DefaultOriginateCondition::Always => true,
+        DefaultOriginateCondition::AccessNetwork => interfaces.local_ipv4_addresses()
+            .iter()
+            .any(|ip| ip.octets()[0] == 192 && ip.octets()[1] == 168),
+        DefaultOriginateCondition::DefaultRoute => match routes.list_routes().await {
+            Ok(routes) => routes.iter().any(|route| {
+                route.prefix.prefix() == 0 && route.protocol != config.route_install.protocol
+            }),
+            Err(e) => {
+                warn!("Failed to read kernel routes for default origination: {}", e);
+                false
+            }
+        },
+    };
+    condition_met.then_some(originate.metric)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn send_lsa(
-    socket: &tokio::net::UdpSocket,
+    socket: &crate::transport::Socket,
     addr: &std::net::SocketAddr,
     router_ip: &str,
     last_hop: Option<&str>,
@@ -29,286 +124,410 @@ pub async fn send_lsa(
     seq_num: u32,
     path: Vec<String>
 ) -> Result<()> {
-    let neighbors_guard = state.neighbors.lock().await;
-    let neighbors_vec = neighbors_guard.values().cloned().collect::<Vec<_>>();
+    let neighbors_guard = state.neighbors.read().await;
+    let mut neighbors_vec = neighbors_guard.values().cloned().collect::<Vec<_>>();
     drop(neighbors_guard);
+    // Un voisin supprimé par l'amortissement est annoncé comme inactif
+    for neighbor in neighbors_vec.iter_mut().filter(|neighbor| neighbor.link_up) {
+        neighbor.link_up = crate::neighbor::is_usable(&state, neighbor).await;
+    }
 
-    let routing_table_guard = state.routing_table.lock().await;
+    let config = state.config().await;
+    let routing_table_guard = state.routing_table.read().await;
+    let origins = state.route_origins.read().await;
     let mut route_states = HashMap::new();
+    let mut route_tags = HashMap::new();
+    // Les routes externes restent annoncées par le seul routeur qui les redistribue ; un
+    // routeur d'accès n'annonce que ses propres réseaux, pas la route par défaut apprise
     for (dest, (_, state)) in routing_table_guard.iter() {
-        route_states.insert(dest.clone(), state.clone());
+        if !matches!(state, RouteState::External { .. }) && config.role != crate::read_config::RouterRole::Access {
+            route_states.insert(*dest, state.clone());
+            if let Some(tag) = origins.get(dest).and_then(|origin| origin.tag) {
+                route_tags.insert(*dest, tag);
+            }
+        }
     }
+    drop(origins);
     drop(routing_table_guard);
     
-    use pnet::datalink;
-    use pnet::ipnetwork::IpNetwork;
-    let interfaces = datalink::interfaces();
-    let mut has_access_network = false;
-    
-    for iface in interfaces {
-        for ip_network in iface.ips {
-            if let IpNetwork::V4(ipv4_network) = ip_network {
-                let ip = ipv4_network.ip();
-                if !ip.is_loopback() && !ip.is_unspecified() {
-                    let network_cidr = ipv4_network.to_string();
-                    
-                    if ip.octets()[0] == 10 {
-                        route_states.insert(network_cidr.clone(), crate::types::RouteState::Active(0));
-                        debug!("Router {} advertising backbone network {}", router_ip, network_cidr);
-                    } else if ip.octets()[0] == 192 && ip.octets()[1] == 168 {
-                        route_states.insert(network_cidr.clone(), crate::types::RouteState::Active(0));
-                        has_access_network = true;
-                        debug!("Router {} advertising access network {} (academic demo)", router_ip, network_cidr);
-                    }
+    let interface = state.interfaces.name_for_address(router_ip);
+    // Vers une zone stub, un routeur de bordure n'annonce qu'une route par défaut
+    let stub_only = config.is_stub_border() && config.is_stub_interface(interface.as_deref());
+    let mut external_routes: HashMap<Prefix, u32> = HashMap::new();
+    let mut external_metric_types = HashMap::new();
+    if stub_only {
+        route_states.clear();
+        route_tags.clear();
+        route_states.insert(Prefix::default_route(), RouteState::Active(config.originate_default.metric));
+    } else {
+        let originated = originated_networks(&config, &state.interfaces, &*state.interface_states.read().await, router_ip);
+        for prefix in originated.keys() {
+            match originated_tag(&config, &state.interfaces, prefix) {
+                Some(tag) => route_tags.insert(*prefix, tag),
+                None => route_tags.remove(prefix),
+            };
+        }
+        route_states.extend(originated);
+        for (prefix, metric) in state.redistributed_routes.read().await.iter() {
+            external_routes.insert(*prefix, *metric);
+            external_metric_types.insert(*prefix, config.redistribute.metric_type);
+            if let Some(tag) = config.redistribute.tag {
+                route_tags.insert(*prefix, tag);
+            }
+        }
+        if config.bgp.enabled {
+            for (prefix, metric) in state.bgp.read().await.external_routes(config.bgp.metric) {
+                if let std::collections::hash_map::Entry::Vacant(entry) = external_routes.entry(prefix) {
+                    entry.insert(metric);
+                    external_metric_types.insert(prefix, config.bgp.metric_type);
                 }
             }
         }
+        if let Some(metric) = default_route_origination(&config, &state.interfaces, state.routes.as_ref()).await {
+            debug!("Router {} advertising default route (metric {}, {:?})", router_ip, metric, config.originate_default.metric_type);
+            external_routes.insert(Prefix::default_route(), metric);
+            external_metric_types.insert(Prefix::default_route(), config.originate_default.metric_type);
+        }
     }
-    
-    if has_access_network {
-        route_states.insert("0.0.0.0/0".to_string(), crate::types::RouteState::Active(20));
-        debug!("Access router {} advertising default route", router_ip);
-    }
+    // Filtres de sortie de l'interface, et du voisin pour un envoi unicast
+    let neighbor = addr.ip().to_string();
+    let permits = |prefix: &Prefix| {
+        let tag = route_tags.get(prefix).copied();
+        crate::filter::permits(&config, crate::filter::Direction::Out, Some(&neighbor), interface.as_deref(), prefix, tag)
+    };
+    route_states.retain(|prefix, _| permits(prefix));
+    external_routes.retain(|prefix, _| permits(prefix));
+    external_metric_types.retain(|prefix, _| external_routes.contains_key(prefix));
+    route_tags.retain(|prefix, _| route_states.contains_key(prefix) || external_routes.contains_key(prefix));
 
-    let message = crate::types::LSAMessage {
+    let mut message = crate::types::LSAMessage {
         message_type: 2,
         router_ip: router_ip.to_string(),
         last_hop: last_hop.map(|s| s.to_string()),
         originator: originator.to_string(),
+        router_id: Some(state.router_id),
         seq_num,
         neighbor_count: neighbors_vec.len(),
         neighbors: neighbors_vec,
         routing_table: route_states,
+        external_routes,
+        external_metric_types,
+        route_tags,
         path,
-        ttl: super::INITIAL_TTL,
+        ttl: crate::INITIAL_TTL,
+        stub_only,
+        base_seq: None,
+        withdrawn: Vec::new(),
+        opaque: config.opaque.clone(),
+        flush: false,
+        checksum: None,
+        part: None,
     };
+    // Instance retenue complète, telle que ses copies relayées seront reconstituées
+    state.router_id_guard.lock().await.record_sent(&message);
+    if config.lsa_delta.enabled && crate::lsdb::Lsdb::classify(&message) == crate::lsdb::LsaType::Router {
+        // Voisins de l'interface qui doivent détenir l'instance complète de référence
+        let on_interface: Vec<String> = message.neighbors.iter()
+            .filter(|neighbor| neighbor.link_up)
+            .filter(|neighbor| crate::flooding::NeighborKey::for_neighbor(&state.interfaces, neighbor).interface == interface.as_deref().unwrap_or_default())
+            .map(|neighbor| neighbor.neighbor_ip.clone())
+            .collect();
+        let full_refresh = std::time::Duration::from_secs(config.lsa_delta.full_refresh_sec);
+        if state.lsa_deltas.lock().await.encode(router_ip, &mut message, &on_interface, full_refresh) {
+            debug!("LSA seq {} on {} sent as a delta of seq {:?} ({} changed, {} withdrawn)",
+                   seq_num, router_ip, message.base_seq, message.routing_table.len() + message.external_routes.len(), message.withdrawn.len());
+            state.metrics.lsa_deltas_sent.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
 
-    crate::net_utils::send_message(socket, addr, &message, state.key.as_slice(),"[SEND] LSA").await
+    message.seal();
+    let key = state.key_for(router_ip).await;
+    crate::tcp_session::send_message(&state, socket, router_ip, addr, &message, &key, "[SEND] LSA").await
 }
 
+/// Relaie un LSA vers chaque voisin actif, sauf celui qui nous l'a transmis (split horizon)
+/// et ceux pour lesquels cette instance est déjà en attente d'acquittement
 pub async fn forward_lsa(
-    socket: &tokio::net::UdpSocket,
-    _broadcast_addr: &std::net::SocketAddr,
+    socket: &crate::transport::Socket,
     local_ip: &str,
     original_lsa: &crate::types::LSAMessage,
-    mut path: Vec<String>,
+    received_from: &crate::flooding::NeighborKey,
     state: &std::sync::Arc<crate::AppState>,
 ) -> Result<()> {
     if original_lsa.ttl <= 1 {
         return Ok(());
     }
 
+    let mut path = original_lsa.path.clone();
     if !path.contains(&local_ip.to_string()) {
         path.push(local_ip.to_string());
     }
 
-    let neighbors = state.neighbors.lock().await;
-    for (neighbor_ip, neighbor) in neighbors.iter() {
-        if neighbor_ip == local_ip {
-            continue;
-        }
-        if let Some(last_hop) = &original_lsa.last_hop {
-            if neighbor_ip == last_hop {
-                continue;
-            }
-        }
-        if !neighbor.link_up {
-            continue;
-        }
+    let message = crate::types::LSAMessage {
+        message_type: 2,
+        router_ip: local_ip.to_string(),
+        last_hop: Some(local_ip.to_string()),
+        originator: original_lsa.originator.clone(),
+        router_id: original_lsa.router_id,
+        seq_num: original_lsa.seq_num,
+        neighbor_count: original_lsa.neighbor_count,
+        neighbors: original_lsa.neighbors.clone(),
+        routing_table: original_lsa.routing_table.clone(),
+        external_routes: original_lsa.external_routes.clone(),
+        external_metric_types: original_lsa.external_metric_types.clone(),
+        route_tags: original_lsa.route_tags.clone(),
+        path,
+        ttl: original_lsa.ttl - 1,
+        stub_only: original_lsa.stub_only,
+        base_seq: None,
+        withdrawn: Vec::new(),
+        opaque: original_lsa.opaque.clone(),
+        flush: original_lsa.flush,
+        checksum: None,
+        part: None,
+    };
+    let identity = crate::types::LsaIdentity {
+        originator: original_lsa.originator.clone(),
+        seq_num: original_lsa.seq_num,
+    };
 
-        if path.contains(neighbor_ip) {
-            continue;
+    // Portée des zones stub : les LSA du backbone n'y entrent pas, les résumés stub n'en sortent pas
+    let config = state.config().await;
+    let from_stub = config.is_stub_interface(Some(&received_from.interface));
+    let in_scope = |neighbor: &crate::flooding::NeighborKey| {
+        if config.interface_protocol(Some(&neighbor.interface)) != crate::read_config::InterfaceProtocol::Pospf {
+            return false;
         }
+        let to_stub = config.is_stub_interface(Some(&neighbor.interface));
+        if to_stub { from_stub } else { !original_lsa.stub_only }
+    };
 
-        let addr = format!("{}:{}", neighbor_ip, crate::PORT)
-            .parse::<std::net::SocketAddr>()
-            .map_err(|e| AppError::NetworkError(format!("Invalid neighbor addr: {}", e)))?;
+    let targets: Vec<crate::flooding::NeighborKey> = {
+        let neighbors = state.neighbors.read().await;
+        let flooding = state.flooding.lock().await;
+        neighbors.iter()
+            .filter(|(neighbor_ip, neighbor)| {
+                neighbor.link_up && *neighbor_ip != local_ip && **neighbor_ip != original_lsa.originator
+            })
+            .map(|(_, neighbor)| crate::flooding::NeighborKey::for_neighbor(&state.interfaces, neighbor))
+            .filter(|neighbor| neighbor != received_from && in_scope(neighbor) && !flooding.is_pending(neighbor, &identity))
+            .collect()
+    };
 
-        let message = crate::types::LSAMessage {
-            message_type: 2,
-            router_ip: local_ip.to_string(),
-            last_hop: Some(local_ip.to_string()),
-            originator: original_lsa.originator.clone(),
-            seq_num: original_lsa.seq_num,
-            neighbor_count: original_lsa.neighbor_count,
-            neighbors: original_lsa.neighbors.clone(),
-            routing_table: original_lsa.routing_table.clone(),
-            path: path.clone(),
-            ttl: original_lsa.ttl - 1,
-        };
+    let full_refresh = std::time::Duration::from_secs(config.lsa_delta.full_refresh_sec);
+    for neighbor in targets {
+        let addr = crate::discovery::neighbor_endpoint(state, &neighbor.neighbor_ip).await?;
+        let key = state.interface_key(Some(&neighbor.interface)).await;
 
-        crate::net_utils::send_message(socket, &addr, &message, state.key.as_slice(), "[FORWARD]").await?;
+        // Relais différentiel : relatif à la dernière instance complète acquittée par ce voisin
+        let mut message = message.clone();
+        if config.lsa_delta.enabled && !message.flush && crate::lsdb::Lsdb::classify(&message) == crate::lsdb::LsaType::Router {
+            let target = std::slice::from_ref(&neighbor.neighbor_ip);
+            if state.lsa_deltas.lock().await.encode(&neighbor.neighbor_ip, &mut message, target, full_refresh) {
+                state.metrics.lsa_deltas_sent.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+        message.seal();
+        crate::tcp_session::send_message(state, socket, local_ip, &addr, &message, &key, "[FORWARD]").await?;
+        state.neighbor_stats.lock().await.entry(&neighbor.neighbor_ip).lsas_forwarded += 1;
         info!("[FORWARD] LSA from {} (originator: {}, seq: {}) to {}", 
               local_ip, original_lsa.originator, original_lsa.seq_num, addr);
+        state.flooding.lock().await.push(neighbor, message);
     }
     Ok(())
 }
 
+/// Retrait de l'instance MAX d'un originateur qui repart de `INITIAL_SEQUENCE` : ses
+/// séquences, instances de référence et enregistrements sont oubliés, puis le retrait est
+/// relayé. Un retrait déjà appliqué (originateur inconnu), ou d'une autre instance que MAX,
+/// n'est ni appliqué ni relayé
+pub async fn apply_flush(
+    state: &Arc<crate::AppState>,
+    socket: &crate::transport::Socket,
+    local_ip: &str,
+    lsa: &crate::types::LSAMessage,
+    received_from: &crate::flooding::NeighborKey,
+) -> Result<()> {
+    // Nos propres retraits revenus par une autre interface
+    if crate::collision::is_own_lsa(state, lsa) {
+        return Ok(());
+    }
+    if lsa.seq_num != crate::sequence::MAX_SEQUENCE {
+        warn!("Ignoring flush of {} (seq: {:#x}): only the instance {:#x} is flushed", lsa.originator, lsa.seq_num, crate::sequence::MAX_SEQUENCE);
+        return Ok(());
+    }
+    {
+        let mut sequences = state.lsa_sequences.lock().await;
+        if sequences.get(&lsa.originator).is_none_or(|highest| is_newer_sequence(*highest, lsa.seq_num)) {
+            debug!("Ignoring flush of {} (seq: {}): already applied", lsa.originator, lsa.seq_num);
+            return Ok(());
+        }
+        sequences.remove(&lsa.originator);
+    }
+    state.processed_lsa.lock().await.forget(&lsa.originator);
+    state.lsa_deltas.lock().await.forget_originator(&lsa.originator);
+    // Une instance plus ancienne encore en attente ne doit plus être réémise après le retrait
+    state.flooding.lock().await.forget_originator(&lsa.originator);
+    info!("[FLUSH] LSA of {} withdrawn (seq: {})", lsa.originator, lsa.seq_num);
+    if state.lsdb.write().await.flush(lsa) {
+        state.metrics.spf_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        state.spf.schedule();
+    }
+    if state.config().await.role == crate::read_config::RouterRole::Access {
+        return Ok(());
+    }
+    forward_lsa(socket, local_ip, lsa, received_from, state).await
+}
+
 pub async fn update_routing_from_lsa(
     state: std::sync::Arc<crate::AppState>,
-    lsa: &crate::types::LSAMessage,
+    _lsa: &crate::types::LSAMessage,
     _sender_ip: &str,
-    _socket: &tokio::net::UdpSocket
+    _socket: &crate::transport::Socket
 ) -> Result<()> {
-    crate::dijkstra::calculate_and_update_optimal_routes(std::sync::Arc::clone(&state)).await
+    state.metrics.spf_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    state.spf.schedule();
+    Ok(())
 }
 
 pub async fn send_poisoned_route(
-    socket: &tokio::net::UdpSocket,
+    socket: &crate::transport::Socket,
     addr: &std::net::SocketAddr,
     router_ip: &str,
-    poisoned_route: &str,
+    poisoned_routes: &[Prefix],
     seq_num: u32,
     path: Vec<String>,
     state: &std::sync::Arc<crate::AppState>,
 ) -> Result<()> {
-    let mut routing_table = HashMap::new();
-    routing_table.insert(poisoned_route.to_string(), crate::types::RouteState::Unreachable);
-    let message = crate::types::LSAMessage {
+    let routing_table: HashMap<Prefix, RouteState> = poisoned_routes.iter()
+        .map(|route| (*route, RouteState::Unreachable))
+        .collect();
+    let mut message = crate::types::LSAMessage {
         message_type: 2,
         router_ip: router_ip.to_string(),
         last_hop: None,
         originator: router_ip.to_string(),
+        router_id: Some(state.router_id),
         seq_num,
         neighbor_count: 0,
         neighbors: Vec::new(),
         routing_table,
+        external_routes: HashMap::new(),
+        external_metric_types: HashMap::new(),
+        route_tags: HashMap::new(),
         path,
-        ttl: super::INITIAL_TTL,
+        ttl: crate::INITIAL_TTL,
+        stub_only: false,
+        base_seq: None,
+        withdrawn: Vec::new(),
+        opaque: state.config().await.opaque.clone(),
+        flush: false,
+        checksum: None,
+        part: None,
     };
     
-    crate::net_utils::send_message(socket, addr, &message, state.key.as_slice(), "[POISON]").await?;
-    info!("[SEND] POISON ROUTE for {} from {} to {}", poisoned_route, router_ip, addr);
+    state.router_id_guard.lock().await.record_sent(&message);
+    message.seal();
+    crate::tcp_session::send_message(state, socket, router_ip, addr, &message, &state.key_for(router_ip).await, "[POISON]").await?;
+    info!("[SEND] POISON ROUTES {:?} from {} to {}", poisoned_routes, router_ip, addr);
     Ok(())
 }
 
-pub async fn update_routing_table_safe(destination: &str, gateway: &str) -> Result<()> {
-    use pnet::ipnetwork::IpNetwork;
-    use pnet::datalink;
-    
-    if !destination.contains('/') {
-        debug!("Skipping route to individual IP (not a network): {}", destination);
-        return Ok(());
+/// Installe la route vers `destination` par un prochain saut résolu (voir `nexthop::resolve`) ;
+/// les interfaces de sortie et réseaux locaux sont ceux de `interfaces`. Renvoie vrai si la
+/// table du système a été modifiée
+#[tracing::instrument(name = "route_install", skip(routes, interfaces, install), fields(next_hop = %next_hop))]
+pub async fn update_routing_table_safe(
+    routes: &dyn crate::route_backend::RouteBackend,
+    interfaces: &crate::interfaces::InterfaceProvider,
+    destination: &Prefix,
+    next_hop: &crate::nexthop::NextHop,
+    install: &crate::read_config::RouteInstallConfig,
+) -> Result<bool> {
+    if !install.enabled {
+        return Ok(false);
     }
+    let dest_net = destination.network();
     
-    let network: IpNetwork = destination.parse()
-        .map_err(|e| AppError::RouteError(format!("Invalid destination network {}: {}", destination, e)))?;
-    
-    let prefix_len = match network {
-        IpNetwork::V4(ipv4) => ipv4.prefix(),
-        IpNetwork::V6(ipv6) => ipv6.prefix(),
-    };
-    
-    let gateway_ip: Ipv4Addr = gateway.parse()
-        .map_err(|e| AppError::RouteError(format!("Invalid gateway IP {}: {}", gateway, e)))?;
-    
-    if gateway_ip.is_loopback() || gateway_ip.is_unspecified() {
-        debug!("Skipping route to invalid gateway: {} via {}", destination, gateway);
-        return Ok(());
+    if next_hop.gateway.is_some_and(|gateway| gateway.is_loopback() || gateway.is_unspecified()) {
+        debug!("Skipping route to invalid gateway: {} via {:?}", destination, next_hop.gateway);
+        return Ok(false);
     }
-    
-    let interfaces = datalink::interfaces();
-    let mut gateway_is_local = false;
-    let mut local_networks = Vec::new();
-    
-    for iface in interfaces {
-        for ip_network in iface.ips {
-            if let IpNetwork::V4(ipv4_network) = ip_network {
-                local_networks.push(ipv4_network.to_string());
-                if ipv4_network.contains(gateway_ip) {
-                    debug!("Gateway {} found in local network {}", gateway_ip, ipv4_network);
-                    gateway_is_local = true;
-                    break;
-                }
-            }
-        }
-        if gateway_is_local { break; }
+    if next_hop.gateway.is_none() && next_hop.interface.is_none() {
+        return Err(AppError::RouteError(format!("No gateway nor interface for {}", destination)));
     }
-    
-    if !gateway_is_local {
-        debug!("Gateway {} is not in any local networks: {:?}", gateway, local_networks);
-        debug!("Skipping route to {} via non-local gateway {}", destination, gateway);
-        return Ok(());
+    let via = next_hop.to_string();
+    let output_interface = next_hop.interface.as_deref().and_then(|name| interfaces.index_of(name));
+    if next_hop.gateway.is_none() && output_interface.is_none() {
+        return Err(AppError::RouteError(format!("Interface {:?} not found for {}", next_hop.interface, destination)));
     }
     
-    if let IpNetwork::V4(dest_net) = network {
-        for iface in datalink::interfaces() {
-            for ip_network in iface.ips {
-                if let IpNetwork::V4(local_net) = ip_network {
-                    if dest_net.network() == local_net.network() && dest_net.prefix() == local_net.prefix() {
-                        debug!("Skipping route to local network {} via {}", destination, gateway);
-                        return Ok(());
-                    }
-                }
-            }
-        }
+    let local = interfaces.interfaces().into_iter()
+        .any(|iface| dest_net.network() == iface.network.network() && dest_net.prefix() == iface.network.prefix());
+    if local {
+        debug!("Skipping route to local network {} via {}", destination, via);
+        return Ok(false);
     }
-    let handle = net_route::Handle::new()
-        .map_err(|e| AppError::RouteError(format!("Cannot create routing handle (permissions?): {}", e)))?;
-    let (ip, prefix) = match network {
-        IpNetwork::V4(net) => (IpAddr::V4(net.network()), net.prefix()),
-        IpNetwork::V6(_) => {
-            return Err(AppError::RouteError("IPv6 not supported".to_string()));
+
+    // Ne jamais écraser une route installée par l'opérateur ou un autre démon
+    let existing = routes.list_routes().await?;
+    let foreign: Vec<_> = existing.iter()
+        .filter(|route| route.prefix == dest_net && route.protocol != install.protocol)
+        .collect();
+    if let Some(route) = foreign.first() {
+        if install.protect_foreign {
+            info!("Route {} already installed by protocol {} (via {:?}), leaving it untouched",
+                  destination, route.protocol, route.gateway);
+            return Ok(false);
         }
+        debug!("Route {} also present from protocol {} (metric {:?}), installing ours with metric {}",
+               destination, route.protocol, route.priority, install.distance);
+    }
+
+    let already_installed = existing.iter().any(|route| {
+        route.prefix == dest_net
+            && route.protocol == install.protocol
+            && route.gateway == next_hop.gateway
+            && route.priority == Some(install.distance)
+    });
+    if already_installed {
+        debug!("Route {} via {} already up to date", destination, via);
+        return Ok(false);
+    }
+
+    let route = crate::route_backend::KernelRoute {
+        prefix: dest_net,
+        gateway: next_hop.gateway,
+        output_interface,
+        protocol: install.protocol,
+        priority: Some(install.distance),
     };
-    let route = net_route::Route::new(ip, prefix as u8)
-        .with_gateway(IpAddr::V4(gateway_ip));
-    match handle.add(&route).await {
+    match routes.install_route(route).await {
         Ok(_) => {
-            info!("Successfully added network route to {} via {}", destination, gateway_ip);
-            Ok(())
+            info!("Successfully installed network route to {} via {}", destination, via);
+            Ok(true)
         },
         Err(e) => {
-            debug!("Route add failed, trying to update: {}", e);
-            let _ = handle.delete(&route).await;
-            match handle.add(&route).await {
-                Ok(_) => {
-                    info!("Successfully updated network route to {} via {}", destination, gateway_ip);
-                    Ok(())
-                },
-                Err(e2) => {
-                    warn!("Failed to add/update route to {} via {}: {}", destination, gateway_ip, e2);
-                    Err(AppError::RouteError(format!("Routing update failed: {}", e2)))
-                }
-            }
+            warn!("Failed to add/update route to {} via {}: {}", destination, via, e);
+            Err(e)
         }
     }
 }
 
-async fn update_system_route(destination: &str, gateway: &str, prefix_len: u8) -> Result<()> {
-    use rtnetlink::{new_connection, IpVersion};
-    use std::net::Ipv4Addr;
-    use tokio::time::{timeout, Duration};
-
-    let parts: Vec<&str> = destination.split('/').collect();
-    let dest_ip: Ipv4Addr = parts[0].parse()
-        .map_err(|e| AppError::RouteError(format!("Destination IPv4 invalide: {}", e)))?;
-    
-    let gw_ip: Ipv4Addr = gateway.parse()
-        .map_err(|e| AppError::RouteError(format!("Gateway IPv4 invalide: {}", e)))?;
-
-    let (connection, handle, _) = new_connection()
-        .map_err(|e| AppError::RouteError(format!("Erreur netlink: {}", e)))?;
-    tokio::spawn(connection);
-
-    let fut = handle.route().add()
-        .v4()
-        .destination_prefix(dest_ip, prefix_len)
-        .gateway(gw_ip)
-        .execute();
-
-    match timeout(Duration::from_secs(2), fut).await {
-        Ok(Ok(_)) => {
-            debug!("Route système mise à jour: {} via {}", destination, gateway);
-            Ok(())
-        }
-        Ok(Err(e)) => {
-            warn!("Erreur netlink lors de la mise à jour de la route: {}", e);
-            Err(AppError::RouteError(format!("Erreur netlink: {}", e)))
-        }
-        Err(_) => {
-            warn!("Timeout netlink lors de la mise à jour de la route");
-            Err(AppError::RouteError("Timeout netlink".into()))
-        }
+/// Retire du noyau les routes vers `destination` portant notre numéro de protocole,
+/// sans jamais toucher aux routes statiques ou installées par d'autres démons
+#[tracing::instrument(name = "route_remove", skip(routes, install))]
+pub async fn remove_own_route(routes: &dyn crate::route_backend::RouteBackend, destination: &Prefix, install: &crate::read_config::RouteInstallConfig) -> Result<()> {
+    if !install.enabled {
+        return Ok(());
     }
+    let dest_net = destination.network();
+    let installed = routes.list_routes().await?;
+    for route in installed.into_iter().filter(|route| route.prefix == dest_net && route.protocol == install.protocol) {
+        let gateway = route.gateway;
+        routes.delete_route(route).await?;
+        info!("Removed network route to {} via {:?}", destination, gateway);
+    }
+    Ok(())
 }
+
@@ -0,0 +1,95 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Nombre d'instances récentes mémorisées par originateur
+const RECENT_WINDOW: usize = 16;
+/// Un originateur silencieux pendant cette durée est oublié
+const ORIGINATOR_TTL: Duration = Duration::from_secs(600);
+/// Intervalle entre deux passes d'éviction
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+struct OriginatorWindow {
+    highest: u32,
+    recent: VecDeque<u32>,
+    last_seen: Instant,
+}
+
+/// Instances de LSA déjà traitées : plus haute séquence et fenêtre récente par originateur,
+/// à taille bornée contrairement à un ensemble de toutes les paires (originateur, séquence)
+#[derive(Debug)]
+pub struct ProcessedLsaCache {
+    originators: HashMap<String, OriginatorWindow>,
+    last_eviction: Instant,
+}
+
+impl Default for ProcessedLsaCache {
+    fn default() -> Self {
+        Self {
+            originators: HashMap::new(),
+            last_eviction: Instant::now(),
+        }
+    }
+}
+
+impl ProcessedLsaCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enregistre l'instance et renvoie vrai si elle n'avait pas encore été traitée.
+    /// Une séquence plus ancienne que la fenêtre récente est considérée comme déjà vue.
+    pub fn insert(&mut self, originator: &str, seq_num: u32) -> bool {
+        self.evict_expired();
+        let now = Instant::now();
+        let Some(window) = self.originators.get_mut(originator) else {
+            self.originators.insert(originator.to_string(), OriginatorWindow {
+                highest: seq_num,
+                recent: VecDeque::from([seq_num]),
+                last_seen: now,
+            });
+            return true;
+        };
+        if window.recent.contains(&seq_num) {
+            return false;
+        }
+        let oldest = window.recent.front().copied().unwrap_or(window.highest);
+        if crate::lsa::is_newer_sequence(oldest, seq_num) {
+            return false;
+        }
+        window.last_seen = now;
+        if crate::lsa::is_newer_sequence(seq_num, window.highest) {
+            window.highest = seq_num;
+        }
+        window.recent.push_back(seq_num);
+        if window.recent.len() > RECENT_WINDOW {
+            window.recent.pop_front();
+        }
+        true
+    }
+
+    /// Oublie un originateur dont le LSA a été retiré : sa prochaine instance repart de
+    /// `INITIAL_SEQUENCE`, plus ancienne que sa fenêtre récente
+    pub fn forget(&mut self, originator: &str) {
+        self.originators.remove(originator);
+    }
+
+    /// Oublie les originateurs silencieux depuis plus de `ORIGINATOR_TTL`
+    fn evict_expired(&mut self) {
+        if self.last_eviction.elapsed() < EVICTION_INTERVAL {
+            return;
+        }
+        self.last_eviction = Instant::now();
+        self.originators.retain(|_, window| window.last_seen.elapsed() < ORIGINATOR_TTL);
+    }
+
+    /// Nombre d'instances mémorisées
+    pub fn len(&self) -> usize {
+        self.originators.values().map(|window| window.recent.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.originators.is_empty()
+    }
+}
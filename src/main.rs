@@ -1,93 +1,44 @@
-use routing_project::*;
+use tracing::info;
+use routing_project::{read_config, shutdown, RouterDaemon};
 
-mod types;
-mod neighbor;
-mod lsa;
-mod init;
-mod tasks;
-mod packet_loop;
-mod hello;
-mod dijkstra;
+fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    routing_project::init::init_logging_and_env();
 
-use error::*;
-use lsa::*;
-use net_utils::*;
-use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-use std::net::{IpAddr, SocketAddr};
-use tokio::net::UdpSocket;
-use tokio::sync::Mutex;
-use tokio::time::{self, Duration};
-use std::sync::Arc;
-use log::{debug, error, info, warn};
-use pnet::ipnetwork::IpNetwork;
-use std::error::Error as StdError;
-use std::fmt;
-use crate::types::{Neighbor, Router, LSAMessage, RouteState, HelloMessage};
-use crate::neighbor::{update_neighbor, check_neighbor_timeouts};
-use init::{init_logging_and_env, init_socket, init_state};
-use tasks::{spawn_hello_and_lsa_tasks, spawn_neighbor_timeout_task};
-use packet_loop::main_loop;
-
-pub use hello::send_hello;
-
-pub struct AppState {
-    pub topology: Mutex<HashMap<String, Router>>,
-    pub neighbors: Mutex<HashMap<String, Neighbor>>,
-    pub routing_table: Mutex<HashMap<String, (String, RouteState)>>,
-    pub processed_lsa: Mutex<HashSet<(String, u32)>>,
-    pub local_ip: String,
-    pub enabled: Mutex<bool>,
-    pub config: read_config::RouterConfig,
-    pub key: Vec<u8>,
-}
-
-impl AppState {
-    pub async fn enable(&self) {
-        let mut enabled = self.enabled.lock().await;
-        *enabled = true;
+    // Charger la configuration basée sur le hostname
+    let config_path = read_config::config_path_from_args(std::env::args().skip(1));
+    let mut config = read_config::read_router_config(config_path.as_deref())?;
+    if read_config::dry_run_from_args(std::env::args().skip(1)) {
+        config.route_install.dry_run = true;
     }
-    
-    pub async fn disable(&self) {
-        let mut enabled = self.enabled.lock().await;
-        *enabled = false;
+    if let Some(netns) = read_config::option_from_args(std::env::args().skip(1), "--netns") {
+        config.netns = Some(netns);
+        config.validate()?;
     }
-    
-    pub async fn is_enabled(&self) -> bool {
-        *self.enabled.lock().await
+    info!("Configuration chargée pour le routeur avec {} interfaces", config.interfaces.len());
+    info!("Hostname: {}", hostname::get()?.to_string_lossy());
+
+    // Avant le runtime : ses threads héritent de l'espace de noms réseau
+    if let Some(netns) = &config.netns {
+        routing_project::netns::enter(netns)?;
+        info!("Running in network namespace {}", netns);
     }
+    tokio::runtime::Runtime::new()?.block_on(run(config, config_path))
 }
 
-const PORT: u16 = 5000;
-const HELLO_INTERVAL_SEC: u64 = 5;
-const LSA_INTERVAL_SEC: u64 = 10;
-const NEIGHBOR_TIMEOUT_SEC: u64 = 22;
-const INITIAL_TTL: u8 = 15;
+async fn run(config: read_config::RouterConfig, config_path: Option<std::path::PathBuf>) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let mut daemon = RouterDaemon::new(config.clone(), config_path.clone()).await?;
+    daemon.start().await?;
+    let mut vrfs = routing_project::vrf::start_instances(&config, config_path, &daemon).await?;
 
-#[tokio::main]
-async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    init_logging_and_env();
-    
-    // Charger la configuration basée sur le hostname
-    let config = read_config::read_router_config()?;
-    info!("Configuration chargée pour le routeur avec {} interfaces", config.interfaces.len());
-    
-    let router_ip = get_local_ip()?;
-    info!("Hostname: {}", hostname::get()?.to_string_lossy());
-    let socket = init_socket(PORT).await?;
-    let key = config.key
-        .as_ref()
-        .map(|k| base64::decode(k).unwrap_or_else(|_| k.as_bytes().to_vec()))
-        .unwrap_or_else(|| vec![0u8; 32]); // fallback si pas de clé
-    let state = init_state(router_ip.clone(), config, key);
-    
-    if let Err(e) = dijkstra::calculate_and_update_optimal_routes(Arc::clone(&state)).await {
-        warn!("Échec du calcul initial des routes: {}", e);
+    let result = tokio::select! {
+        result = daemon.wait() => result,
+        _ = shutdown::wait_for_shutdown_signal() => {
+            daemon.stop().await;
+            Ok(())
+        }
+    };
+    for vrf in &mut vrfs {
+        vrf.stop().await;
     }
-    
-    spawn_hello_and_lsa_tasks(Arc::clone(&socket), Arc::clone(&state));
-    spawn_neighbor_timeout_task(Arc::clone(&state));
-    
-    main_loop(socket, state).await?;
-    Ok(())
-}
\ No newline at end of file
+    Ok(result?)
+}
@@ -0,0 +1,205 @@
+use crate::error::{AppError, Result};
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+
+/// Port UDP de mDNS
+pub const PORT: u16 = 5353;
+/// Groupe multicast mDNS
+pub const GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+/// Service sous lequel les routeurs P-OSPF s'annoncent
+pub const SERVICE: &str = "_pospf._udp.local";
+const HEADER_LEN: usize = 12;
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_SRV: u16 = 33;
+const CLASS_IN: u16 = 1;
+/// Bit « cache flush » des enregistrements uniques (RFC 6762, 10.2)
+const CACHE_FLUSH: u16 = 0x8000;
+const FLAGS_RESPONSE: u16 = 0x8400;
+/// Sauts de compression suivis au plus par nom, contre les boucles
+const MAX_POINTERS: usize = 16;
+
+fn invalid(message: impl Into<String>) -> AppError {
+    AppError::PacketError(format!("mDNS: {}", message.into()))
+}
+
+/// Instance du service annoncée par un routeur : port du protocole et adresses joignables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Announcement {
+    /// Nom de l'instance, sans le suffixe du service
+    pub instance: String,
+    pub port: u16,
+    pub addresses: Vec<Ipv4Addr>,
+    /// Durée de validité en secondes ; 0 retire l'annonce
+    pub ttl: u32,
+}
+
+/// Message mDNS réduit à ce qu'en utilise la découverte
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MdnsMessage {
+    /// Services demandés (questions PTR)
+    pub queries: Vec<String>,
+    /// Instances annoncées pour `SERVICE`
+    pub announcements: Vec<Announcement>,
+}
+
+fn push_name(out: &mut Vec<u8>, name: &str) {
+    for label in name.split('.').filter(|label| !label.is_empty()) {
+        out.push(label.len().min(63) as u8);
+        out.extend_from_slice(&label.as_bytes()[..label.len().min(63)]);
+    }
+    out.push(0);
+}
+
+fn push_record(out: &mut Vec<u8>, name: &str, kind: u16, class: u16, ttl: u32, rdata: &[u8]) {
+    push_name(out, name);
+    out.extend_from_slice(&kind.to_be_bytes());
+    out.extend_from_slice(&class.to_be_bytes());
+    out.extend_from_slice(&ttl.to_be_bytes());
+    out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+    out.extend_from_slice(rdata);
+}
+
+fn header(flags: u16, questions: u16, answers: u16, additional: u16) -> Vec<u8> {
+    let mut out = Vec::with_capacity(512);
+    out.extend_from_slice(&[0, 0]);
+    for field in [flags, questions, answers, 0, additional] {
+        out.extend_from_slice(&field.to_be_bytes());
+    }
+    out
+}
+
+/// Question PTR demandant les instances du service
+pub fn encode_query(service: &str) -> Vec<u8> {
+    let mut out = header(0, 1, 0, 0);
+    push_name(&mut out, service);
+    out.extend_from_slice(&TYPE_PTR.to_be_bytes());
+    out.extend_from_slice(&CLASS_IN.to_be_bytes());
+    out
+}
+
+/// Réponse annonçant une instance : PTR du service, puis SRV et A en données additionnelles
+pub fn encode_response(service: &str, announcement: &Announcement) -> Vec<u8> {
+    let instance = format!("{}.{}", announcement.instance, service);
+    let target = format!("{}.local", announcement.instance);
+    let mut out = header(FLAGS_RESPONSE, 0, 1, 1 + announcement.addresses.len() as u16);
+
+    let mut ptr = Vec::new();
+    push_name(&mut ptr, &instance);
+    push_record(&mut out, service, TYPE_PTR, CLASS_IN, announcement.ttl, &ptr);
+
+    let mut srv = vec![0, 0, 0, 0];
+    srv.extend_from_slice(&announcement.port.to_be_bytes());
+    push_name(&mut srv, &target);
+    push_record(&mut out, &instance, TYPE_SRV, CLASS_IN | CACHE_FLUSH, announcement.ttl, &srv);
+
+    for address in &announcement.addresses {
+        push_record(&mut out, &target, TYPE_A, CLASS_IN | CACHE_FLUSH, announcement.ttl, &address.octets());
+    }
+    out
+}
+
+/// Lit un nom à `offset`, en suivant les pointeurs de compression ; renvoie le nom
+/// (en minuscules) et la position qui suit le nom dans le message
+fn read_name(data: &[u8], mut offset: usize) -> Result<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    let mut pointers = 0;
+    loop {
+        let len = *data.get(offset).ok_or_else(|| invalid("truncated name"))? as usize;
+        match len & 0xc0 {
+            0x00 if len == 0 => {
+                let end = end.unwrap_or(offset + 1);
+                return Ok((labels.join("."), end));
+            }
+            0x00 => {
+                let label = data.get(offset + 1..offset + 1 + len).ok_or_else(|| invalid("truncated label"))?;
+                labels.push(String::from_utf8_lossy(label).to_ascii_lowercase());
+                offset += 1 + len;
+            }
+            0xc0 => {
+                let low = *data.get(offset + 1).ok_or_else(|| invalid("truncated pointer"))? as usize;
+                pointers += 1;
+                if pointers > MAX_POINTERS {
+                    return Err(invalid("too many compression pointers"));
+                }
+                end.get_or_insert(offset + 2);
+                offset = ((len & 0x3f) << 8) | low;
+            }
+            _ => return Err(invalid("unsupported label type")),
+        }
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| invalid("truncated record"))
+}
+
+/// Décode un message mDNS : questions PTR et instances de `service` annoncées ; les
+/// autres enregistrements sont ignorés
+pub fn decode(data: &[u8], service: &str) -> Result<MdnsMessage> {
+    if data.len() < HEADER_LEN {
+        return Err(invalid(format!("message of {} bytes is shorter than its header", data.len())));
+    }
+    let flags = read_u16(data, 2)?;
+    let questions = read_u16(data, 4)?;
+    let records = read_u16(data, 6)? as usize + read_u16(data, 8)? as usize + read_u16(data, 10)? as usize;
+    let service = service.to_ascii_lowercase();
+
+    let mut message = MdnsMessage::default();
+    let mut offset = HEADER_LEN;
+    for _ in 0..questions {
+        let (name, next) = read_name(data, offset)?;
+        if read_u16(data, next)? == TYPE_PTR && flags & 0x8000 == 0 {
+            message.queries.push(name);
+        }
+        offset = next + 4;
+    }
+
+    let mut instances: Vec<(String, u32)> = Vec::new();
+    let mut services: HashMap<String, (u16, String)> = HashMap::new();
+    let mut addresses: HashMap<String, Vec<Ipv4Addr>> = HashMap::new();
+    for _ in 0..records {
+        let (name, next) = read_name(data, offset)?;
+        let kind = read_u16(data, next)?;
+        let ttl = ((read_u16(data, next + 4)? as u32) << 16) | read_u16(data, next + 6)? as u32;
+        let rdlength = read_u16(data, next + 8)? as usize;
+        let rdata = next + 10;
+        if data.len() < rdata + rdlength {
+            return Err(invalid("truncated record data"));
+        }
+        match kind {
+            TYPE_PTR if name == service => {
+                let (instance, _) = read_name(data, rdata)?;
+                instances.push((instance, ttl));
+            }
+            TYPE_SRV if rdlength >= 7 => {
+                let port = read_u16(data, rdata + 4)?;
+                let (target, _) = read_name(data, rdata + 6)?;
+                services.insert(name, (port, target));
+            }
+            TYPE_A if rdlength == 4 => {
+                let octets = [data[rdata], data[rdata + 1], data[rdata + 2], data[rdata + 3]];
+                addresses.entry(name).or_default().push(Ipv4Addr::from(octets));
+            }
+            _ => {}
+        }
+        offset = rdata + rdlength;
+    }
+
+    let suffix = format!(".{}", service);
+    for (instance, ttl) in instances {
+        let Some((port, target)) = services.get(&instance) else {
+            continue;
+        };
+        message.announcements.push(Announcement {
+            instance: instance.strip_suffix(&suffix).unwrap_or(&instance).to_string(),
+            port: *port,
+            addresses: addresses.get(target).cloned().unwrap_or_default(),
+            ttl,
+        });
+    }
+    Ok(message)
+}
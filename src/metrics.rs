@@ -0,0 +1,136 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Compteurs du démon, exposés au format Prometheus sur /metrics et via la commande `metrics`
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Voisins ayant réussi le défi d'authentification
+    pub neighbors_authenticated: AtomicU64,
+    /// Routeurs refusés (réponse au défi invalide ou inattendue)
+    pub neighbors_rejected: AtomicU64,
+    /// LSA ignorés car arrivés trop tôt après l'instance précédente du même originateur
+    pub lsa_arrivals_throttled: AtomicU64,
+    /// LSA déclenchés non émis (intervalle minimal d'origination)
+    pub lsa_originations_throttled: AtomicU64,
+    /// Voisins supprimés par l'amortissement des bascules
+    pub neighbors_suppressed: AtomicU64,
+    /// Recalculs SPF effectués par l'ordonnanceur
+    pub spf_runs: AtomicU64,
+    /// Demandes de recalcul SPF (LSA, changement de voisin)
+    pub spf_requests: AtomicU64,
+    /// Messages rejetés par l'analyseur (mal formés, hors limites ou de type inconnu)
+    pub packets_rejected: AtomicU64,
+    /// LSA écartés car leur somme de contrôle ne correspond pas au contenu
+    pub lsa_checksum_failures: AtomicU64,
+    /// Corrections de la table du noyau par la réconciliation (routes retirées ou réinstallées)
+    pub route_repairs: AtomicU64,
+    /// Routes empoisonnées à la perte du voisin par lequel elles étaient apprises
+    pub routes_poisoned: AtomicU64,
+    /// Routes candidates écartées par le SPF car moins bonnes qu'une route en retenue
+    pub hold_down_ignored: AtomicU64,
+    /// LSA écartés car un autre routeur annonce sous un de nos identifiants
+    pub router_id_conflicts: AtomicU64,
+    /// LSA émis sous forme différentielle
+    pub lsa_deltas_sent: AtomicU64,
+    /// LSA différentiels reçus sans leur instance complète de référence
+    pub lsa_deltas_unusable: AtomicU64,
+    /// Échos `ping` écartés car leur signature ne correspond pas à la clé partagée
+    pub probes_rejected: AtomicU64,
+    /// Datagrammes impossibles à déchiffrer ou dont le HMAC est invalide
+    pub decrypt_failures: AtomicU64,
+    /// Messages de routeurs dont le mode de protection (`auth`) diffère de celui du segment
+    pub auth_mode_mismatches: AtomicU64,
+    /// HELLO ignorés d'un voisin en quarantaine
+    pub quarantined_hellos: AtomicU64,
+    /// Annonces de la passerelle externe écartées (boucle d'AS ou chemin invalide)
+    pub bgp_routes_rejected: AtomicU64,
+    /// Datagrammes abandonnés car la file de la tâche de traitement était pleine
+    pub receive_queue_dropped: AtomicU64,
+    /// Trames de session TCP ou QUIC abandonnées, file d'écriture ou de réception pleine
+    pub stream_frames_dropped: AtomicU64,
+    /// Taille du cache des LSA déjà traités (jauge)
+    pub processed_lsa_entries: AtomicU64,
+    /// Datagrammes reçus en attente de traitement (jauge)
+    pub receive_queue_depth: AtomicU64,
+    /// Adjacences OSPFv2 en Full (jauge)
+    pub ospfv2_adjacencies: AtomicU64,
+    /// Routeurs RIP entendus sur les interfaces RIP (jauge)
+    pub rip_neighbors: AtomicU64,
+    /// Sessions établies de la passerelle externe (jauge)
+    pub bgp_sessions: AtomicU64,
+    /// Voisins découverts par mDNS et joints en unicast (jauge)
+    pub discovered_peers: AtomicU64,
+    /// Opérations de routes en attente ou abandonnées (jauge)
+    pub pending_route_ops: AtomicU64,
+    /// Préfixes en retenue (jauge)
+    pub routes_held_down: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn counters(&self) -> Vec<(&'static str, &'static str, u64)> {
+        vec![
+            ("pospf_neighbor_auth_success_total", "Neighbors that passed the authentication handshake", self.neighbors_authenticated.load(Ordering::Relaxed)),
+            ("pospf_neighbor_auth_rejected_total", "Peers rejected by the authentication handshake", self.neighbors_rejected.load(Ordering::Relaxed)),
+            ("pospf_lsa_arrival_throttled_total", "LSAs dropped by the minimum arrival interval", self.lsa_arrivals_throttled.load(Ordering::Relaxed)),
+            ("pospf_lsa_origination_throttled_total", "Triggered LSAs skipped by the minimum origination interval", self.lsa_originations_throttled.load(Ordering::Relaxed)),
+            ("pospf_neighbor_flap_suppressed_total", "Neighbors suppressed by flap damping", self.neighbors_suppressed.load(Ordering::Relaxed)),
+            ("pospf_spf_runs_total", "SPF computations run by the scheduler", self.spf_runs.load(Ordering::Relaxed)),
+            ("pospf_spf_requests_total", "SPF computations requested before coalescing", self.spf_requests.load(Ordering::Relaxed)),
+            ("pospf_packets_rejected_total", "Received messages rejected by the packet parser", self.packets_rejected.load(Ordering::Relaxed)),
+            ("pospf_lsa_checksum_failures_total", "LSAs dropped because their checksum was missing or did not match their contents", self.lsa_checksum_failures.load(Ordering::Relaxed)),
+            ("pospf_route_repairs_total", "Kernel routes removed or reinstalled by reconciliation", self.route_repairs.load(Ordering::Relaxed)),
+            ("pospf_routes_poisoned_total", "Routes poisoned when the neighbor they were learned through was lost", self.routes_poisoned.load(Ordering::Relaxed)),
+            ("pospf_hold_down_ignored_total", "Route candidates ignored by SPF because a better route is held down", self.hold_down_ignored.load(Ordering::Relaxed)),
+            ("pospf_router_id_conflicts_total", "LSAs dropped because another router uses one of our router ids", self.router_id_conflicts.load(Ordering::Relaxed)),
+            ("pospf_lsa_delta_sent_total", "LSAs sent as differential updates", self.lsa_deltas_sent.load(Ordering::Relaxed)),
+            ("pospf_lsa_delta_unusable_total", "Differential LSAs received without their full base instance", self.lsa_deltas_unusable.load(Ordering::Relaxed)),
+            ("pospf_probe_rejected_total", "Echo probes dropped because of a bad signature", self.probes_rejected.load(Ordering::Relaxed)),
+            ("pospf_decrypt_failures_total", "Received datagrams that could not be decrypted or failed HMAC verification", self.decrypt_failures.load(Ordering::Relaxed)),
+            ("pospf_auth_mode_mismatch_total", "Messages from routers using another auth mode than the receiving segment", self.auth_mode_mismatches.load(Ordering::Relaxed)),
+            ("pospf_quarantined_hellos_total", "HELLO messages ignored from quarantined neighbors", self.quarantined_hellos.load(Ordering::Relaxed)),
+            ("pospf_bgp_routes_rejected_total", "External gateway announcements rejected by AS path checks", self.bgp_routes_rejected.load(Ordering::Relaxed)),
+            ("pospf_receive_queue_dropped_total", "Received datagrams dropped because the worker queue was full", self.receive_queue_dropped.load(Ordering::Relaxed)),
+            ("pospf_stream_frames_dropped_total", "TCP or QUIC session frames dropped because the write or receive queue was full", self.stream_frames_dropped.load(Ordering::Relaxed)),
+        ]
+    }
+
+    fn gauges(&self) -> Vec<(&'static str, &'static str, u64)> {
+        vec![
+            ("pospf_processed_lsa_cache_entries", "LSA instances remembered for duplicate detection", self.processed_lsa_entries.load(Ordering::Relaxed)),
+            ("pospf_receive_queue_depth", "Received datagrams waiting for a worker", self.receive_queue_depth.load(Ordering::Relaxed)),
+            ("pospf_ospfv2_adjacencies", "OSPFv2 neighbors in the Full state", self.ospfv2_adjacencies.load(Ordering::Relaxed)),
+            ("pospf_rip_neighbors", "RIP routers heard on RIP interfaces", self.rip_neighbors.load(Ordering::Relaxed)),
+            ("pospf_bgp_sessions", "Established external gateway sessions", self.bgp_sessions.load(Ordering::Relaxed)),
+            ("pospf_discovered_peers", "Peers discovered through mDNS and reached over unicast", self.discovered_peers.load(Ordering::Relaxed)),
+            ("pospf_pending_route_operations", "Kernel route operations waiting for a retry or given up", self.pending_route_ops.load(Ordering::Relaxed)),
+            ("pospf_routes_held_down", "Prefixes in a hold-down period after an unreachable advertisement", self.routes_held_down.load(Ordering::Relaxed)),
+        ]
+    }
+
+    /// Exposition au format texte Prometheus
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let counters = self.counters().into_iter().map(|metric| (metric, "counter"));
+        let gauges = self.gauges().into_iter().map(|metric| (metric, "gauge"));
+        for ((name, help, value), kind) in counters.chain(gauges) {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+        out
+    }
+
+    /// Résumé lisible pour le CLI
+    pub fn summary(&self) -> String {
+        self.counters()
+            .into_iter()
+            .chain(self.gauges())
+            .map(|(name, _, value)| format!("{} {}", name, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
@@ -1,123 +1,218 @@
 use std::sync::Arc;
-use tokio::net::UdpSocket;
-use log::{info, warn, error};
+use tracing::{info, warn, error};
 use crate::AppState;
-use std::time::Duration;
-use crate::dijkstra::{self, calculate_ospf_cost};
+use crate::events::RouterEvent;
 
-use crate::net_utils::get_broadcast_addresses;
+pub use pospf_core::neighbor::*;
 
-pub async fn update_neighbor(state: &Arc<crate::AppState>, neighbor_ip: &str) {
-    let current_time = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_else(|_| Duration::from_secs(0))
-        .as_secs();
+
+/// Rafraîchit un voisin entendu sur l'interface d'adresse `local_ip`
+pub async fn update_neighbor(state: &Arc<crate::AppState>, neighbor_ip: &str, local_ip: &str) {
+    let current_time = crate::clock::unix_secs();
     
-    let (capacity, link_active) = get_interface_info_for_neighbor(state, neighbor_ip).await;
+    let interface = state.interfaces.name_for_address(local_ip);
+    let (capacity, active, cost) = get_interface_info(state, interface.as_deref()).await;
+    let flap_damping = state.config().await.flap_damping;
+    let link = LocalLink { interface, capacity, active, cost };
     
-    let mut neighbors = state.neighbors.lock().await;
-    neighbors.entry(neighbor_ip.to_string())
-        .and_modify(|n| {
-            n.last_seen = current_time;
-            n.capacity = capacity;
-            let should_be_up = link_active;
-            if n.link_up != should_be_up {
-                if should_be_up {
-                    info!("Neighbor {} is now UP (capacity: {} Mbps)", neighbor_ip, capacity);
-                } else {
-                    warn!("Neighbor {} is now DOWN (interface inactive)", neighbor_ip);
-                }
-                n.link_up = should_be_up;
-            }
-        })
-        .or_insert_with(|| {
-            let should_be_up = link_active;
-            if should_be_up {
-                info!("New neighbor discovered: {} (capacity: {} Mbps)", neighbor_ip, capacity);
-            } else {
-                warn!("New neighbor discovered but interface is DOWN: {}", neighbor_ip);
-            }
-            crate::types::Neighbor {
-                neighbor_ip: neighbor_ip.to_string(),
-                link_up: should_be_up,
-                capacity,
-                last_seen: current_time,
-            }
-        });
+    let outcome = hear(&mut *state.neighbors.write().await, neighbor_ip, &link, current_time);
+    match &outcome.event {
+        Some(RouterEvent::NeighborUp { .. }) => info!("Neighbor {} is now UP (capacity: {} Mbps)", neighbor_ip, capacity),
+        Some(RouterEvent::NeighborDown { .. }) => warn!("Neighbor {} is now DOWN (interface inactive)", neighbor_ip),
+        _ => {}
+    }
+    if let Some(event) = outcome.event {
+        crate::neighbor_stats::publish(state, event).await;
+    }
+    if outcome.flapped {
+        record_flap(state, neighbor_ip, &flap_damping).await;
+    }
     
     // Déclencher un recalcul des routes si c'est un nouveau voisin ou un changement d'état
-    let state_clone = Arc::clone(state);
-    tokio::spawn(async move {
-        // Attendre un peu pour que les changements se stabilisent
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        if let Err(e) = crate::dijkstra::calculate_and_update_optimal_routes(Arc::clone(&state_clone)).await {
-            warn!("Échec du calcul initial des routes: {}", e);
-        }
-    });
+    state.metrics.spf_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    state.spf.schedule();
 }
 
+/// Pénalise un changement d'état du voisin (amortissement des bascules)
+async fn record_flap(state: &Arc<AppState>, neighbor_ip: &str, config: &crate::read_config::FlapDampingConfig) {
+    if state.damping.lock().await.record_flap(neighbor_ip, config) {
+        state.metrics.neighbors_suppressed.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+/// Voisin actif et non supprimé par l'amortissement des bascules
+pub async fn is_usable(state: &Arc<AppState>, neighbor: &crate::types::Neighbor) -> bool {
+    let flap_damping = state.config().await.flap_damping;
+    neighbor.link_up && !state.damping.lock().await.is_suppressed(&neighbor.neighbor_ip, &flap_damping)
+}
+
+/// Intègre une mesure de RTT au voisin, s'il est connu
+pub async fn record_rtt_sample(state: &Arc<AppState>, neighbor_ip: &str, sample_us: u32) {
+    if let Some(neighbor) = state.neighbors.write().await.get_mut(neighbor_ip) {
+        pospf_core::neighbor::record_rtt_sample(neighbor, sample_us);
+    }
+}
+
+/// Passe inactifs les voisins muets depuis plus que l'intervalle de mort de leur interface,
+/// puis empoisonne les routes apprises par eux et annonce le changement
 pub async fn check_neighbor_timeouts(state: &Arc<AppState>) {
-    let current_time = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_else(|_| Duration::from_secs(0))
-        .as_secs();
-    let mut neighbors = state.neighbors.lock().await;
-    let mut changed = false;
-    for (ip, neighbor) in neighbors.iter_mut() {
-        if neighbor.link_up && current_time - neighbor.last_seen > super::NEIGHBOR_TIMEOUT_SEC {
-            warn!("Neighbor {} is DOWN (timeout)", ip);
-            neighbor.link_up = false;
-            changed = true;
+    let current_time = crate::clock::unix_secs();
+    let config = state.config().await;
+    let events = expire(&mut *state.neighbors.write().await, current_time, |ip, neighbor| {
+        let interface = neighbor.interface.clone().or_else(|| state.interfaces.name_for_address(ip));
+        config.dead_interval_for(interface.as_deref())
+    });
+    let changed = lost(&events);
+    for (ip, event) in changed.iter().zip(events) {
+        warn!("Neighbor {} is DOWN (timeout)", ip);
+        crate::neighbor_stats::publish(state, event).await;
+        record_flap(state, ip, &config.flap_damping).await;
+    }
+    if !changed.is_empty() {
+        poison_routes_via(state, &changed).await;
+        originate_lsas(state, "neighbor timeout").await;
+        state.metrics.spf_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        state.spf.schedule();
+    }
+}
+
+/// Empoisonne les routes apprises par des voisins perdus : marquées `Unreachable` dans la
+/// table (donc annoncées comme telles par le prochain LSA) et retirées du noyau, sans
+/// attendre que le reste du réseau les fasse expirer. Le recalcul SPF suivant les remplace
+/// par un autre chemin s'il en existe un
+pub async fn poison_routes_via(state: &Arc<AppState>, lost: &[String]) -> Vec<crate::types::Prefix> {
+    let is_poisoned = |next_hop: &String, route_state: &crate::types::RouteState| {
+        lost.contains(next_hop) && !matches!(route_state, crate::types::RouteState::Unreachable)
+    };
+    let mut poisoned: Vec<crate::types::Prefix> = state.routing_table.read().await.iter()
+        .filter(|(_, (next_hop, route_state))| is_poisoned(next_hop, route_state))
+        .map(|(prefix, _)| *prefix)
+        .collect();
+    poisoned.sort();
+    // Retenue d'après la route d'avant l'empoisonnement
+    crate::hold_down::hold(state, &poisoned, "neighbor loss").await;
+    let mut routing_table = state.routing_table.write().await;
+    for prefix in &poisoned {
+        if let Some((_, route_state)) = routing_table.get_mut(prefix) {
+            *route_state = crate::types::RouteState::Unreachable;
         }
     }
-    drop(neighbors);
-    if changed {
-        let broadcast_addrs = get_broadcast_addresses(super::PORT);
-        let socket = UdpSocket::bind("0.0.0.0:0").await.unwrap_or_else(|_| panic!("Failed to create socket"));
-        socket.set_broadcast(true).unwrap_or_else(|_| panic!("Failed to set broadcast"));
-        for (local_ip, addr) in &broadcast_addrs {
-            let seq_num = current_time as u32;
-            if let Err(e) = super::send_lsa(&socket, addr, local_ip, None, local_ip, Arc::clone(&state), seq_num, vec![]).await {
-                error!("Failed to send LSA after neighbor timeout: {}", e);
-            }
+    drop(routing_table);
+    for prefix in &poisoned {
+        state.route_ops.push(*prefix, crate::route_queue::RouteOp::Remove).await;
+    }
+    if !poisoned.is_empty() {
+        warn!("Poisoned {} route(s) learned through {}: {:?}", poisoned.len(), lost.join(", "), poisoned);
+        state.metrics.routes_poisoned.fetch_add(poisoned.len() as u64, std::sync::atomic::Ordering::Relaxed);
+    }
+    poisoned
+}
+
+/// Annonce immédiatement un nouveau LSA sur chaque interface après un changement local
+pub async fn originate_lsas(state: &Arc<AppState>, reason: &str) {
+    let config = state.config().await;
+    let flood_addrs = crate::discovery::flood_targets(state, &config).await;
+    for (local_ip, addr) in &flood_addrs {
+        state.damping.lock().await.record_origination(local_ip);
+        let sender = state.sender_for(local_ip, addr, &state.socket).await;
+        let Some(seq_num) = crate::sequence::next_sequence(state).await else {
+            return;
+        };
+        match crate::lsa::send_lsa(&sender, addr, local_ip, None, local_ip, Arc::clone(state), seq_num, vec![]).await {
+            Ok(()) => crate::neighbor_stats::record_sent(state, local_ip, addr, false).await,
+            Err(e) => error!("Failed to send LSA after {}: {}", reason, e),
         }
     }
 }
 
-/// Détermine la capacité et l'état d'une interface pour un voisin donné
-async fn get_interface_info_for_neighbor(state: &Arc<AppState>, neighbor_ip: &str) -> (u32, bool) {
-    
-    for interface in &state.config.interfaces {
-        if interface.link_active {
-            return (interface.capacity_mbps, true);
+/// Répercute l'état courant d'une interface sur ses voisins, puis annonce le changement
+/// (nouveaux LSA et recalcul SPF)
+pub async fn apply_interface_change(state: &Arc<AppState>, name: &str) {
+    let Some(iface) = state.interface_state(name).await else {
+        return;
+    };
+    let current_time = crate::clock::unix_secs();
+    let dead_interval = state.config().await.dead_interval_for(Some(name));
+    let events = apply_link_state(&mut *state.neighbors.write().await, name, iface.link_active, iface.cost, current_time, dead_interval, |ip, neighbor| {
+        neighbor.interface.clone().or_else(|| state.interfaces.name_for_address(ip))
+    });
+    let lost = lost(&events);
+    for event in events {
+        crate::neighbor_stats::publish(state, event).await;
+    }
+    poison_routes_via(state, &lost).await;
+    originate_lsas(state, "interface change").await;
+    state.metrics.spf_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    state.spf.schedule();
+}
+
+/// Réaligne les états d'interface sur une configuration rechargée : les interfaces
+/// ajoutées ou modifiées repartent de la configuration, les autres gardent leur état courant
+pub async fn sync_interface_states(state: &Arc<AppState>, config: &crate::read_config::RouterConfig, diff: &crate::reload::ConfigDiff) {
+    let mut states = state.interface_states.write().await;
+    states.retain(|name, _| config.interface(name).is_some());
+    for iface in &config.interfaces {
+        if diff.added.contains(&iface.name) || diff.modified.contains(&iface.name) || !states.contains_key(&iface.name) {
+            states.insert(iface.name.clone(), iface.initial_state());
         }
     }
-    
-    // Si aucune interface active, utiliser la première interface disponible
-    if let Some(interface) = state.config.interfaces.first() {
-        (interface.capacity_mbps, interface.link_active)
-    } else {
-        (100, false)
+}
+
+/// Mémorise le nom et l'identifiant annoncés par un voisin dans son HELLO
+pub async fn set_identity(state: &Arc<AppState>, hello: &crate::types::HelloMessage) {
+    if let Some(neighbor) = state.neighbors.write().await.get_mut(&hello.router_ip) {
+        // Les LSA du voisin sont rattachés à son identifiant : recalculer s'il change
+        if neighbor.router_id != hello.router_id {
+            info!("Neighbor {} announces router id {:?}", hello.router_ip, hello.router_id);
+            state.metrics.spf_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            state.spf.schedule();
+        }
+        neighbor.hostname = hello.hostname.clone();
+        neighbor.router_id = hello.router_id;
+        neighbor.priority = hello.priority;
+    }
+}
+
+/// Capacité par défaut d'une interface absente de la configuration
+const DEFAULT_CAPACITY_MBPS: u32 = 100;
+
+/// Détermine la capacité, l'état et le coût courants de l'interface sur laquelle un voisin est entendu
+async fn get_interface_info(state: &Arc<AppState>, interface: Option<&str>) -> (u32, bool, Option<u32>) {
+    let runtime = match interface {
+        Some(name) => state.interface_state(name).await,
+        None => None,
+    };
+    if let Some(iface) = runtime {
+        return (iface.capacity_mbps, iface.link_active, iface.cost);
     }
+    let config = state.config().await;
+    // Interface non déclarée : active seulement si la configuration n'en restreint pas la liste
+    (DEFAULT_CAPACITY_MBPS, config.interfaces.is_empty(), None)
 }
 
-/// Affiche un rapport détaillé de l'état des interfaces
+/// Affiche un rapport détaillé de l'état courant des interfaces
 pub async fn display_interface_report(state: &Arc<AppState>) {
-    use log::info;
+    use tracing::info;
     
     info!("=== RAPPORT D'ÉTAT DES INTERFACES ===");
     
-    if state.config.interfaces.is_empty() {
+    let config = state.config().await;
+    if config.interfaces.is_empty() {
         info!("Aucune interface configurée");
         return;
     }
+    let states = state.interface_states.read().await;
+    let interfaces: Vec<crate::types::InterfaceState> = config.interfaces.iter()
+        .map(|iface| states.get(&iface.name).cloned().unwrap_or_else(|| iface.initial_state()))
+        .collect();
+    drop(states);
     
     info!("{:<10} {:<12} {:<8} {:<10}", "Interface", "Capacité", "État", "Coût OSPF");
     info!("{}", "-".repeat(45));
     
-    for interface in &state.config.interfaces {
+    for interface in &interfaces {
         let status = if interface.link_active { "ACTIF" } else { "INACTIF" };
-        let cost = calculate_ospf_cost(interface.capacity_mbps, interface.link_active);
+        let cost = interface.effective_cost();
         
         let cost_str = if cost == u32::MAX {
             "∞".to_string()
@@ -133,15 +228,15 @@ pub async fn display_interface_report(state: &Arc<AppState>) {
     }
     
     // Statistiques générales
-    let total_interfaces = state.config.interfaces.len();
-    let active_interfaces = state.config.interfaces.iter()
+    let total_interfaces = interfaces.len();
+    let active_interfaces = interfaces.iter()
         .filter(|iface| iface.link_active)
         .count();
     
     info!("Total interfaces: {} (actives: {})", total_interfaces, active_interfaces);
     
     // Capacité totale disponible
-    let total_capacity: u32 = state.config.interfaces.iter()
+    let total_capacity: u32 = interfaces.iter()
         .filter(|iface| iface.link_active)
         .map(|iface| iface.capacity_mbps)
         .sum();
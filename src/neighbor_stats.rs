@@ -0,0 +1,191 @@
+use crate::events::RouterEvent;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::net::{IpAddr, SocketAddr};
+
+/// Changements d'état conservés par voisin
+const STATE_HISTORY: usize = 16;
+
+/// Série Prometheus : nom, description et compteur lu dans les statistiques
+type Series = (&'static str, &'static str, fn(&NeighborStats) -> u64);
+
+/// Changement d'état d'un voisin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StateChange {
+    /// Horodatage Unix en secondes
+    pub at: u64,
+    pub up: bool,
+    pub reason: String,
+}
+
+/// Compteurs de trafic et de protocole d'un voisin
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct NeighborStats {
+    pub hellos_rx: u64,
+    pub hellos_tx: u64,
+    pub lsas_rx: u64,
+    pub lsas_tx: u64,
+    pub lsas_forwarded: u64,
+    /// Numéro de séquence du dernier LSA reçu de ce voisin
+    pub last_seq: Option<u32>,
+    /// Messages de ce voisin impossibles à déchiffrer
+    pub decrypt_failures: u64,
+    /// Changements d'état depuis le démarrage
+    pub state_change_count: u64,
+    /// Derniers changements d'état, du plus ancien au plus récent
+    pub state_changes: VecDeque<StateChange>,
+}
+
+impl NeighborStats {
+    pub fn record_state_change(&mut self, up: bool, reason: &str) {
+        self.state_change_count += 1;
+        if self.state_changes.len() == STATE_HISTORY {
+            self.state_changes.pop_front();
+        }
+        self.state_changes.push_back(StateChange { at: crate::clock::unix_secs(), up, reason: reason.to_string() });
+    }
+}
+
+/// Statistiques de tous les voisins, indexées comme `AppState::neighbors`
+#[derive(Debug, Default)]
+pub struct NeighborStatsTable {
+    stats: HashMap<String, NeighborStats>,
+}
+
+impl NeighborStatsTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entry(&mut self, neighbor: &str) -> &mut NeighborStats {
+        self.stats.entry(neighbor.to_string()).or_default()
+    }
+
+    pub fn get(&self, neighbor: &str) -> Option<&NeighborStats> {
+        self.stats.get(neighbor)
+    }
+
+    /// Exposition Prometheus, une série par voisin
+    pub fn render(&self) -> String {
+        let mut neighbors: Vec<_> = self.stats.iter().collect();
+        neighbors.sort_by(|a, b| a.0.cmp(b.0));
+        let series: [Series; 7] = [
+            ("pospf_neighbor_hellos_received_total", "HELLO messages received from the neighbor", |stats| stats.hellos_rx),
+            ("pospf_neighbor_hellos_sent_total", "HELLO messages sent towards the neighbor", |stats| stats.hellos_tx),
+            ("pospf_neighbor_lsas_received_total", "LSAs received from the neighbor", |stats| stats.lsas_rx),
+            ("pospf_neighbor_lsas_sent_total", "LSAs originated towards the neighbor", |stats| stats.lsas_tx),
+            ("pospf_neighbor_lsas_forwarded_total", "LSAs forwarded to the neighbor", |stats| stats.lsas_forwarded),
+            ("pospf_neighbor_decrypt_failures_total", "Messages from the neighbor that could not be decrypted", |stats| stats.decrypt_failures),
+            ("pospf_neighbor_state_changes_total", "Up and down transitions of the neighbor", |stats| stats.state_change_count),
+        ];
+        let mut out = String::new();
+        for (name, help, value) in series {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for (neighbor, stats) in &neighbors {
+                let _ = writeln!(out, "{}{{neighbor=\"{}\"}} {}", name, neighbor, value(stats));
+            }
+        }
+        out
+    }
+}
+
+/// Voisin à l'origine d'un datagramme : celui dont les HELLO viennent de cette adresse,
+/// à défaut l'adresse elle-même
+pub async fn neighbor_for_source(state: &crate::AppState, source: IpAddr) -> String {
+    let IpAddr::V4(source) = source else {
+        return source.to_string();
+    };
+    state.neighbor_addresses.lock().await
+        .iter()
+        .find(|(_, address)| **address == source)
+        .map(|(neighbor, _)| neighbor.clone())
+        .unwrap_or_else(|| source.to_string())
+}
+
+/// Voisins atteints par un envoi depuis l'interface `local_ip` vers `addr` : celui qui
+/// a cette adresse s'il est connu, sinon tous ceux de l'interface (diffusion)
+pub async fn neighbors_reached(state: &crate::AppState, local_ip: &str, addr: &SocketAddr) -> Vec<String> {
+    let target = addr.ip().to_string();
+    let neighbors = state.neighbors.read().await;
+    if neighbors.contains_key(&target) {
+        return vec![target];
+    }
+    let unicast = neighbor_for_source(state, addr.ip()).await;
+    if neighbors.contains_key(&unicast) {
+        return vec![unicast];
+    }
+    let interface = state.interfaces.name_for_address(local_ip);
+    neighbors.values()
+        .filter(|neighbor| interface.is_some() && neighbor.interface == interface)
+        .map(|neighbor| neighbor.neighbor_ip.clone())
+        .collect()
+}
+
+/// Publie un changement d'état de voisin après l'avoir noté dans ses statistiques
+pub async fn publish(state: &crate::AppState, event: RouterEvent) {
+    match &event {
+        RouterEvent::NeighborUp { neighbor, interface } => {
+            let reason = interface.as_deref().map(|name| format!("up on {}", name)).unwrap_or_else(|| "up".to_string());
+            state.neighbor_stats.lock().await.entry(neighbor).record_state_change(true, &reason);
+        }
+        RouterEvent::NeighborDown { neighbor, reason } => {
+            state.neighbor_stats.lock().await.entry(neighbor).record_state_change(false, reason);
+        }
+        _ => {}
+    }
+    state.events.emit(event);
+}
+
+/// Compte un envoi de HELLO ou de LSA depuis `local_ip` vers `addr`
+pub async fn record_sent(state: &crate::AppState, local_ip: &str, addr: &SocketAddr, hello: bool) {
+    let reached = neighbors_reached(state, local_ip, addr).await;
+    let mut stats = state.neighbor_stats.lock().await;
+    for neighbor in reached {
+        let stats = stats.entry(&neighbor);
+        if hello {
+            stats.hellos_tx += 1;
+        } else {
+            stats.lsas_tx += 1;
+        }
+    }
+}
+
+/// Rapport `neighbor-detail <ip>`, avec l'intervalle des HELLO émis sur l'interface du voisin
+pub fn detail_report(
+    neighbor: &crate::types::Neighbor,
+    stats: Option<&NeighborStats>,
+    damping: Option<&crate::damping::DampingStatus>,
+    hello_interval: u64,
+    now: u64,
+) -> String {
+    let stats = stats.cloned().unwrap_or_default();
+    let mut lines = vec![
+        format!("Voisin {}", neighbor.display_name()),
+        format!("  Interface: {}", neighbor.interface.as_deref().unwrap_or("?")),
+        format!("  État: {} (dernière activité il y a {} s)", if neighbor.link_up { "actif" } else { "inactif" }, now.saturating_sub(neighbor.last_seen)),
+        format!("  HELLO reçus/émis: {}/{} (intervalle effectif: {} s)", stats.hellos_rx, stats.hellos_tx, hello_interval),
+        format!("  LSA reçus/émis/relayés: {}/{}/{}", stats.lsas_rx, stats.lsas_tx, stats.lsas_forwarded),
+        format!("  Dernière séquence reçue: {}", stats.last_seq.map(|seq| seq.to_string()).unwrap_or_else(|| "-".to_string())),
+        format!("  Échecs de déchiffrement: {}", stats.decrypt_failures),
+    ];
+    if let Some(damping) = damping {
+        let status = if damping.quarantined { "en quarantaine" } else if damping.suppressed { "supprimé" } else { "actif" };
+        lines.push(format!("  Amortissement: pénalité {} ({})", damping.penalty, status));
+    }
+    if stats.state_changes.is_empty() {
+        lines.push("  Aucun changement d'état".to_string());
+    } else {
+        lines.push("  Changements d'état:".to_string());
+        for change in &stats.state_changes {
+            lines.push(format!(
+                "    il y a {} s: {} ({})",
+                now.saturating_sub(change.at),
+                if change.up { "actif" } else { "inactif" },
+                change.reason,
+            ));
+        }
+    }
+    lines.join("\n")
+}
@@ -1,50 +1,164 @@
 use std::collections::HashMap;
-use std::net::{IpAddr, SocketAddr};
-use pnet::datalink::{self, NetworkInterface};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use crate::interfaces::InterfaceProvider;
 use pnet::ipnetwork::IpNetwork;
 use crate::error::{AppError, Result};
+use crate::read_config::TransportMode;
+use crate::types::AuthMode;
 use aes::Aes256;
 use cbc::{Encryptor, Decryptor};
 use cipher::{KeyIvInit, block_padding::Pkcs7, BlockEncryptMut, BlockDecryptMut};
+use hmac::{Hmac, Mac};
 use rand::{RngCore, rngs::OsRng};
+use sha2::Sha256;
 
 pub fn get_broadcast_addresses(port: u16) -> Vec<(String, SocketAddr)> {
-    let interfaces = datalink::interfaces();
-    interfaces
-        .into_iter()
-        .flat_map(|iface: NetworkInterface| {
-            iface.ips.into_iter().filter_map(move |ip_network| {
-                if let IpAddr::V4(ip) = ip_network.ip() {
-                    if !ip.is_loopback() {
-                        if let IpNetwork::V4(ipv4_network) = ip_network {
-                            let broadcast = ipv4_network.broadcast();
-                            Some((ip.to_string(), SocketAddr::new(IpAddr::V4(broadcast), port)))
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
-        })
-        .collect()
+    InterfaceProvider::System.flood_addresses(TransportMode::Broadcast, port)
 }
 
-pub fn get_local_ip() -> Result<String> {
-    let interfaces = datalink::interfaces();
-    for interface in interfaces {
-        for ip_network in interface.ips {
-            if let IpAddr::V4(ipv4) = ip_network.ip() {
-                if !ipv4.is_loopback() && !ipv4.is_unspecified() {
-                    return Ok(ipv4.to_string());
-                }
-            }
+/// Groupe multicast AllSPFRouters utilisé par OSPF
+pub const OSPF_ALL_ROUTERS: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 5);
+
+/// Adresses de diffusion (broadcast ou multicast selon le mode) pour chaque interface locale
+pub fn get_flood_addresses(mode: TransportMode, port: u16) -> Vec<(String, SocketAddr)> {
+    InterfaceProvider::System.flood_addresses(mode, port)
+}
+
+/// Adresses de diffusion limitées aux interfaces déclarées dans la configuration
+pub fn get_configured_flood_addresses(config: &crate::read_config::RouterConfig, port: u16) -> Vec<(String, SocketAddr)> {
+    InterfaceProvider::System.configured_flood_addresses(config, port)
+}
+
+/// Adresse de diffusion à utiliser pour répondre sur l'interface de réception
+pub fn flood_address_for_interface(mode: TransportMode, interface_ip: &str, ip_network: &IpNetwork, port: u16) -> Result<SocketAddr> {
+    match mode {
+        TransportMode::Broadcast => calculate_broadcast_for_interface(interface_ip, ip_network, port),
+        TransportMode::Multicast => Ok(SocketAddr::new(IpAddr::V4(OSPF_ALL_ROUTERS), port)),
+    }
+}
+
+/// Adresses IPv4 locales hors loopback
+pub fn get_local_ipv4_addresses() -> Vec<Ipv4Addr> {
+    InterfaceProvider::System.local_ipv4_addresses()
+}
+
+/// Nom de l'interface locale dont le sous-réseau contient `ip` (adresse locale ou voisin)
+pub fn interface_name_for_address(ip: &str) -> Option<String> {
+    InterfaceProvider::System.name_for_address(ip)
+}
+
+/// Abonne la socket de réception au groupe AllSPFRouters sur chaque interface locale
+pub fn join_multicast_group(socket: &tokio::net::UdpSocket) -> Result<()> {
+    socket.set_multicast_loop_v4(false)?;
+    for ip in get_local_ipv4_addresses() {
+        join_multicast_on(socket, ip)?;
+    }
+    Ok(())
+}
+
+/// Abonne la socket de réception au groupe AllSPFRouters sur une interface
+pub fn join_multicast_on(socket: &tokio::net::UdpSocket, ip: Ipv4Addr) -> Result<()> {
+    socket.join_multicast_v4(OSPF_ALL_ROUTERS, ip)
+        .map_err(|e| AppError::NetworkError(format!("Failed to join {} on {}: {}", OSPF_ALL_ROUTERS, ip, e)))?;
+    tracing::info!("Joined multicast group {} on interface {}", OSPF_ALL_ROUTERS, ip);
+    Ok(())
+}
+
+/// Adresses IPv4 portées par une interface nommée
+pub fn get_interface_ipv4_addresses(name: &str) -> Vec<Ipv4Addr> {
+    InterfaceProvider::System.addresses_of(name)
+}
+
+/// Marque les datagrammes émis par la socket avec la valeur DSCP `dscp` (champ TOS)
+pub fn set_dscp(socket: &tokio::net::UdpSocket, dscp: u8) -> Result<()> {
+    socket2::SockRef::from(socket).set_tos(u32::from(dscp) << 2)
+        .map_err(|e| AppError::NetworkError(format!("Failed to set DSCP {}: {}", dscp, e)))
+}
+
+/// Lie la socket principale du protocole sur `port`, dans la VRF `vrf` (SO_BINDTODEVICE sur
+/// son périphérique) ; avec `shared`, les instances de plusieurs VRF partagent le port
+pub fn bind_protocol_socket(port: u16, vrf: Option<&str>, shared: bool) -> Result<tokio::net::UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(shared)?;
+    bind_to_vrf(&socket, vrf)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port).into())
+        .map_err(|e| AppError::NetworkError(format!("Failed to bind UDP port {}: {}", port, e)))?;
+    Ok(tokio::net::UdpSocket::from_std(socket.into())?)
+}
+
+/// Restreint une socket au périphérique de la VRF `vrf` (Linux seulement)
+fn bind_to_vrf(socket: &socket2::Socket, vrf: Option<&str>) -> Result<()> {
+    let Some(vrf) = vrf else {
+        return Ok(());
+    };
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    {
+        socket.bind_device(Some(vrf.as_bytes()))
+            .map_err(|e| AppError::NetworkError(format!("Failed to bind to vrf {}: {}", vrf, e)))
+    }
+    #[cfg(not(any(target_os = "android", target_os = "fuchsia", target_os = "linux")))]
+    {
+        let _ = socket;
+        Err(AppError::NetworkError(format!("vrf {}: VRF devices are only supported on Linux", vrf)))
+    }
+}
+
+/// Crée une socket d'émission multicast liée à une interface (IP_MULTICAST_IF, TTL 1),
+/// dans la VRF `vrf` s'il y a lieu
+pub fn create_multicast_sender(local_ip: Ipv4Addr, dscp: u8, vrf: Option<&str>) -> Result<tokio::net::UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_tos(u32::from(dscp) << 2)?;
+    bind_to_vrf(&socket, vrf)?;
+    socket.set_multicast_if_v4(&local_ip)?;
+    socket.set_multicast_ttl_v4(1)?;
+    socket.set_multicast_loop_v4(false)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::new(IpAddr::V4(local_ip), 0).into())?;
+    Ok(tokio::net::UdpSocket::from_std(socket.into())?)
+}
+
+/// Crée une socket d'émission des sondes de MTU liée à `local_ip`, dans la VRF `vrf` s'il y
+/// a lieu : IP_PMTUDISC_PROBE pose le bit DF et ignore la MTU de chemin mise en cache par
+/// le noyau, si bien qu'une sonde trop grande est refusée (EMSGSIZE) ou perdue en route au
+/// lieu d'être fragmentée (Linux seulement)
+pub fn create_probe_sender(local_ip: Ipv4Addr, vrf: Option<&str>) -> Result<tokio::net::UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    bind_to_vrf(&socket, vrf)?;
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        use std::os::fd::AsRawFd;
+        let value: libc::c_int = libc::IP_PMTUDISC_PROBE;
+        let set = unsafe {
+            libc::setsockopt(
+                socket.as_raw_fd(),
+                libc::IPPROTO_IP,
+                libc::IP_MTU_DISCOVER,
+                &value as *const libc::c_int as *const libc::c_void,
+                std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+            )
+        };
+        if set != 0 {
+            return Err(AppError::NetworkError(format!("Failed to set IP_MTU_DISCOVER: {}", std::io::Error::last_os_error())));
         }
     }
-    Err(AppError::ConfigError("No valid IP address found".to_string()))
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    {
+        return Err(AppError::NetworkError("Path MTU probes need IP_MTU_DISCOVER, only supported on Linux".to_string()));
+    }
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::new(IpAddr::V4(local_ip), 0).into())?;
+    Ok(tokio::net::UdpSocket::from_std(socket.into())?)
+}
+
+pub fn get_local_ip() -> Result<String> {
+    InterfaceProvider::System.router_ip()
 }
 
 pub fn determine_receiving_interface(
@@ -52,10 +166,10 @@ pub fn determine_receiving_interface(
     local_ips: &HashMap<IpAddr, (String, IpNetwork)>,
 ) -> Result<(String, IpNetwork)> {
     if let IpAddr::V4(sender_ipv4) = sender_ip {
-        for (local_ip, (local_ip_str, ip_network)) in local_ips {
+        for (local_ip_str, ip_network) in local_ips.values() {
             if let IpNetwork::V4(ipv4_network) = ip_network {
                 if ipv4_network.contains(*sender_ipv4) {
-                    return Ok((local_ip_str.clone(), ip_network.clone()));
+                    return Ok((local_ip_str.clone(), *ip_network));
                 }
             }
         }
@@ -63,14 +177,14 @@ pub fn determine_receiving_interface(
     for (local_ip, (local_ip_str, ip_network)) in local_ips {
         if let IpAddr::V4(ipv4) = local_ip {
             if !ipv4.is_loopback() && !ipv4.is_unspecified() {
-                return Ok((local_ip_str.clone(), ip_network.clone()));
+                return Ok((local_ip_str.clone(), *ip_network));
             }
         }
     }
     Err(AppError::NetworkError("No valid receiving interface found".to_string()))
 }
 
-pub fn calculate_broadcast_for_interface(interface_ip: &str, ip_network: &IpNetwork, port: u16) -> Result<SocketAddr> {
+pub fn calculate_broadcast_for_interface(_interface_ip: &str, ip_network: &IpNetwork, port: u16) -> Result<SocketAddr> {
     if let IpNetwork::V4(ipv4_network) = ip_network {
         let broadcast_addr = ipv4_network.broadcast();
         Ok(SocketAddr::new(IpAddr::V4(broadcast_addr), port))
@@ -79,27 +193,158 @@ pub fn calculate_broadcast_for_interface(interface_ip: &str, ip_network: &IpNetw
     }
 }
 
-pub async fn send_message<T: serde::Serialize>(
-    socket: &tokio::net::UdpSocket,
+pub async fn send_message<T: crate::framing::Framed>(
+    socket: &crate::transport::Socket,
     addr: &std::net::SocketAddr,
     message: &T,
-    key: &[u8],
+    key: &SegmentKey,
+    log_prefix: &str
+) -> Result<()> {
+    send_message_within(socket, addr, message, key, crate::fragment::MAX_FRAGMENT_PAYLOAD, log_prefix).await
+}
+
+/// Comme `send_message`, découpé en morceaux d'au plus `max_payload` octets : les LSA,
+/// potentiellement grands, suivent la MTU mesurée vers le voisin (`AppState::max_payload`).
+/// Un LSA trop grand part d'abord en plusieurs LSA plus petits (voir `lsa_part`) ; seul ce
+/// qui dépasse encore est fragmenté
+pub async fn send_message_within<T: crate::framing::Framed>(
+    socket: &crate::transport::Socket,
+    addr: &std::net::SocketAddr,
+    message: &T,
+    key: &SegmentKey,
+    max_payload: usize,
     log_prefix: &str
 ) -> Result<()> {
     let serialized = serde_json::to_vec(message)
-        .map_err(|e| AppError::SerializationError(e))?;
+        .map_err(AppError::SerializationError)?;
 
-    let encrypted = encrypt(&serialized, key)?;
+    match message.split_within(max_payload) {
+        Some(parts) => {
+            tracing::debug!("{} Message of {} bytes split into {} parts", log_prefix, serialized.len(), parts.len());
+            for part in &parts {
+                let part = serde_json::to_vec(part).map_err(AppError::SerializationError)?;
+                send_serialized_within(socket, addr, message.message_type(), &part, key, max_payload, log_prefix).await?;
+            }
+        }
+        None => send_serialized_within(socket, addr, message.message_type(), &serialized, key, max_payload, log_prefix).await?,
+    }
 
-    socket.send_to(&encrypted, addr).await
-        .map_err(|e| AppError::NetworkError(format!("Failed to send message: {}", e)))?;
+    if crate::trace::is_enabled() {
+        let interface = interface_name_for_address(&addr.ip().to_string())
+            .or_else(|| socket.local_addr().ok().map(|local| local.ip().to_string()));
+        crate::trace::record(crate::trace::Direction::Sent, *addr, interface, &serialized);
+    }
 
-    log::info!("{} Encrypted message sent to {}", log_prefix, addr);
+    tracing::info!("{} Encrypted message sent to {}", log_prefix, addr);
+    Ok(())
+}
+
+/// Émet un message sérialisé, en fragments d'au plus `max_payload` octets s'il le faut
+async fn send_serialized_within(
+    socket: &crate::transport::Socket,
+    addr: &std::net::SocketAddr,
+    message_type: u8,
+    serialized: &[u8],
+    key: &SegmentKey,
+    max_payload: usize,
+    log_prefix: &str
+) -> Result<()> {
+    match crate::fragment::split_to(serialized, max_payload) {
+        Some(fragments) => {
+            tracing::debug!("{} Message of {} bytes split into {} fragments", log_prefix, serialized.len(), fragments.len());
+            for fragment in &fragments {
+                let chunk = serde_json::to_vec(fragment).map_err(AppError::SerializationError)?;
+                send_datagram(socket, addr, crate::framing::FRAGMENT, &chunk, key).await?;
+            }
+            Ok(())
+        }
+        None => send_datagram(socket, addr, message_type, serialized, key).await,
+    }
+}
+
+/// Émet un message déjà sérialisé en un seul datagramme, sans le découper
+pub async fn send_datagram(socket: &crate::transport::Socket, addr: &SocketAddr, message_type: u8, data: &[u8], key: &SegmentKey) -> Result<()> {
+    let encrypted = crate::framing::frame(message_type, crate::framing::Codec::Json, key.auth, &key.seal(data)?)?;
+    #[cfg(feature = "fault-injection")]
+    let datagrams = crate::fault::apply(crate::fault::Direction::Out, addr.ip(), &encrypted);
+    #[cfg(not(feature = "fault-injection"))]
+    let datagrams = [(encrypted, None::<std::time::Duration>)];
+    for (datagram, delay) in datagrams {
+        // Un envoi retardé retient aussi les envois suivants de la même tâche
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+        socket.send_to(&datagram, *addr).await
+            .map_err(|e| AppError::NetworkError(format!("Failed to send message: {}", e)))?;
+    }
     Ok(())
 }
 
+/// Longueur du HMAC-SHA256 ajouté aux messages en mode `hmac`
+pub const HMAC_TAG_LEN: usize = 32;
+
+/// Protection des messages d'un segment : mode et clé partagée
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentKey {
+    pub auth: AuthMode,
+    pub key: Vec<u8>,
+}
+
+impl SegmentKey {
+    /// Messages en clair
+    pub fn plaintext() -> Self {
+        Self { auth: AuthMode::None, key: Vec::new() }
+    }
+
+    /// Clé globale : chiffrement AES si elle est définie, en clair sinon
+    pub fn from_global(key: Vec<u8>) -> Self {
+        let auth = if key.is_empty() { AuthMode::None } else { AuthMode::Aes };
+        Self { auth, key }
+    }
+
+    /// Corps de trame à émettre pour `data`
+    pub fn seal(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self.auth {
+            AuthMode::None => Ok(data.to_vec()),
+            AuthMode::Aes => encrypt(data, &self.key),
+            AuthMode::Hmac => {
+                let mut datagram = hmac_over(data, &self.key)?.finalize().into_bytes().to_vec();
+                datagram.extend_from_slice(data);
+                Ok(datagram)
+            }
+        }
+    }
+
+    /// Contenu d'un corps de trame reçu ; erreur s'il n'est pas protégé selon ce mode et cette clé
+    pub fn open(&self, datagram: &[u8]) -> Result<Vec<u8>> {
+        match self.auth {
+            AuthMode::None => Ok(datagram.to_vec()),
+            AuthMode::Aes => decrypt(datagram, &self.key),
+            AuthMode::Hmac => {
+                if datagram.len() < HMAC_TAG_LEN {
+                    return Err(AppError::CryptoError("Message trop court pour contenir le HMAC".to_string()));
+                }
+                let (tag, data) = datagram.split_at(HMAC_TAG_LEN);
+                hmac_over(data, &self.key)?.verify_slice(tag)
+                    .map_err(|_| AppError::CryptoError("HMAC invalide".to_string()))?;
+                Ok(data.to_vec())
+            }
+        }
+    }
+}
+
+fn hmac_over(data: &[u8], key: &[u8]) -> Result<Hmac<Sha256>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)
+        .map_err(|e| AppError::CryptoError(format!("Clé HMAC invalide: {}", e)))?;
+    mac.update(data);
+    Ok(mac)
+}
 
 pub fn encrypt(data: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+    // Clé vide : chiffrement désactivé dans la configuration
+    if key.is_empty() {
+        return Ok(data.to_vec());
+    }
     if key.len() != 32 {
         return Err(AppError::CryptoError("La clé doit faire 32 octets".to_string()));
     }
@@ -135,6 +380,9 @@ pub fn encrypt(data: &[u8], key: &[u8]) -> Result<Vec<u8>> {
 
 
 pub fn decrypt(ciphertext: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+    if key.is_empty() {
+        return Ok(ciphertext.to_vec());
+    }
     // Vérifier que la clé fait 32 octets (256 bits)
     if key.len() != 32 {
         return Err(AppError::CryptoError("La clé doit faire 32 octets".to_string()));
@@ -0,0 +1,66 @@
+use crate::error::{AppError, Result};
+use std::path::{Path, PathBuf};
+
+/// Répertoire des espaces de noms nommés créés par `ip netns add`
+pub const NETNS_RUN_DIR: &str = "/run/netns";
+
+/// Vrai pour un nom d'espace (`r1`) ou un chemin absolu (`/proc/1234/ns/net`)
+pub fn is_valid_name(netns: &str) -> bool {
+    if netns.starts_with('/') {
+        return true;
+    }
+    !netns.is_empty() && netns != "." && netns != ".." && !netns.contains('/')
+}
+
+/// Fichier désignant l'espace de noms
+pub fn path(netns: &str) -> PathBuf {
+    if netns.starts_with('/') {
+        PathBuf::from(netns)
+    } else {
+        Path::new(NETNS_RUN_DIR).join(netns)
+    }
+}
+
+fn open(netns: &str) -> Result<std::fs::File> {
+    std::fs::File::open(path(netns))
+        .map_err(|e| AppError::ConfigError(format!("Network namespace {} ({}): {}", netns, path(netns).display(), e)))
+}
+
+/// Fait entrer le thread courant dans l'espace de noms réseau ; appelé avant de créer le
+/// runtime tokio, pour que ses threads, les sockets du protocole et les connexions netlink
+/// y soient tous créés
+#[cfg(target_os = "linux")]
+pub fn enter(netns: &str) -> Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let file = open(netns)?;
+    // SAFETY : descripteur valide pendant l'appel, setns ne le conserve pas
+    if unsafe { libc::setns(file.as_raw_fd(), libc::CLONE_NEWNET) } != 0 {
+        return Err(AppError::ConfigError(format!(
+            "Failed to enter network namespace {}: {}",
+            netns,
+            std::io::Error::last_os_error(),
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn enter(netns: &str) -> Result<()> {
+    Err(AppError::ConfigError(format!("Network namespace {}: only supported on Linux", netns)))
+}
+
+/// Vrai si le thread courant est déjà dans l'espace de noms
+#[cfg(target_os = "linux")]
+pub fn is_current(netns: &str) -> Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let target = open(netns)?.metadata()?;
+    let current = std::fs::metadata("/proc/thread-self/ns/net")?;
+    Ok(target.dev() == current.dev() && target.ino() == current.ino())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn is_current(netns: &str) -> Result<bool> {
+    enter(netns).map(|()| false)
+}
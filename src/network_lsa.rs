@@ -0,0 +1,124 @@
+use crate::error::Result;
+use crate::types::{LSAMessage, Neighbor};
+use crate::AppState;
+use pnet::ipnetwork::{IpNetwork, Ipv4Network};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Routeur désigné parmi les candidats (priorité, identité) : plus grande priorité, puis plus
+/// grand identifiant ; un routeur de priorité 0 n'est jamais élu
+pub fn elect<'a>(candidates: impl IntoIterator<Item = (u8, &'a str)>) -> Option<&'a str> {
+    candidates.into_iter()
+        .filter(|(priority, _)| *priority > 0)
+        .max_by_key(|(priority, identity)| (*priority, identity.parse::<Ipv4Addr>().unwrap_or(Ipv4Addr::UNSPECIFIED), *identity))
+        .map(|(_, identity)| identity)
+}
+
+/// Routeurs rattachés au segment de l'interface `local_ip`, vus par ce routeur : nous-mêmes,
+/// puis les voisins utilisables entendus sur l'interface
+pub async fn attached_routers(state: &Arc<AppState>, local_ip: &str) -> Vec<Neighbor> {
+    let config = state.config().await;
+    let interface = state.interfaces.name_for_address(local_ip);
+    let iface = interface.as_deref().and_then(|name| config.interface(name));
+    let mut attached = vec![Neighbor {
+        neighbor_ip: local_ip.to_string(),
+        link_up: true,
+        capacity: iface.map_or(0, |iface| iface.capacity_mbps),
+        last_seen: crate::clock::unix_secs(),
+        cost: config.cost_for(interface.as_deref()),
+        rtt_us: None,
+        jitter_us: None,
+        hostname: state.hostname.clone(),
+        interface: interface.clone(),
+        router_id: Some(state.router_id),
+        priority: config.priority_for(interface.as_deref()),
+    }];
+    let neighbors: Vec<Neighbor> = state.neighbors.read().await.values()
+        .filter(|neighbor| neighbor.interface.clone().or_else(|| state.interfaces.name_for_address(&neighbor.neighbor_ip)) == interface)
+        .cloned()
+        .collect();
+    for neighbor in neighbors {
+        if crate::neighbor::is_usable(state, &neighbor).await {
+            attached.push(neighbor);
+        }
+    }
+    attached
+}
+
+/// Identifiant du LSA réseau d'un segment : son préfixe, sans la partie hôte
+pub fn segment_id(network: Ipv4Network) -> String {
+    format!("{}/{}", network.network(), network.prefix())
+}
+
+fn designated_among(attached: &[Neighbor]) -> Option<String> {
+    let identities: Vec<(u8, String)> = attached.iter().map(|router| (router.priority, router.identity())).collect();
+    elect(identities.iter().map(|(priority, identity)| (*priority, identity.as_str()))).map(str::to_string)
+}
+
+/// Identité du routeur désigné du segment de l'interface `local_ip`
+pub async fn designated_router(state: &Arc<AppState>, local_ip: &str) -> Option<String> {
+    designated_among(&attached_routers(state, local_ip).await)
+}
+
+/// Émet le LSA réseau du segment `network` si ce routeur en est le routeur désigné et qu'un
+/// autre routeur y est rattaché ; le LSA est aussi installé dans notre propre LSDB
+pub async fn originate(
+    state: &Arc<AppState>,
+    socket: &crate::transport::Socket,
+    addr: &SocketAddr,
+    local_ip: &str,
+    network: IpNetwork,
+) -> Result<bool> {
+    let IpNetwork::V4(network) = network else {
+        return Ok(false);
+    };
+    let attached = attached_routers(state, local_ip).await;
+    if attached.len() < 2 {
+        return Ok(false);
+    }
+    let designated = designated_among(&attached);
+    if designated != Some(state.router_id.to_string()) {
+        debug!("Not the designated router of {} ({:?}), no network LSA", network, designated);
+        return Ok(false);
+    }
+
+    let segment = segment_id(network);
+    let Some(seq_num) = crate::sequence::next_sequence(state).await else {
+        return Ok(false);
+    };
+    let mut message = LSAMessage {
+        message_type: 2,
+        router_ip: local_ip.to_string(),
+        last_hop: None,
+        originator: segment.clone(),
+        router_id: Some(state.router_id),
+        seq_num,
+        neighbor_count: attached.len(),
+        neighbors: attached,
+        routing_table: std::collections::HashMap::new(),
+        external_routes: std::collections::HashMap::new(),
+        external_metric_types: std::collections::HashMap::new(),
+        route_tags: std::collections::HashMap::new(),
+        path: Vec::new(),
+        ttl: crate::INITIAL_TTL,
+        stub_only: false,
+        base_seq: None,
+        withdrawn: Vec::new(),
+        opaque: Vec::new(),
+        flush: false,
+        checksum: None,
+        part: None,
+    };
+    state.router_id_guard.lock().await.record_sent(&message);
+    message.seal();
+    if state.lsdb.write().await.install(&message) {
+        crate::history::record_lsa(state, &message).await;
+        state.metrics.spf_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        state.spf.schedule();
+    }
+    let key = state.key_for(local_ip).await;
+    crate::tcp_session::send_message(state, socket, local_ip, addr, &message, &key, "[SEND] NETWORK LSA").await?;
+    info!("[SEND] Network LSA for {} ({} attached routers) to {}", segment, message.neighbor_count, addr);
+    Ok(true)
+}
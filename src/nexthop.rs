@@ -0,0 +1,67 @@
+use crate::AppState;
+use std::net::Ipv4Addr;
+
+/// Prochain saut prêt à être installé dans le noyau
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NextHop {
+    /// Passerelle sur un sous-réseau directement connecté ; absente pour une route
+    /// de périphérique sur un lien point à point non numéroté
+    pub gateway: Option<Ipv4Addr>,
+    /// Interface de sortie
+    pub interface: Option<String>,
+}
+
+impl std::fmt::Display for NextHop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.gateway, self.interface.as_deref()) {
+            (Some(gateway), _) => write!(f, "{}", gateway),
+            (None, Some(interface)) => write!(f, "dev {}", interface),
+            (None, None) => write!(f, "?"),
+        }
+    }
+}
+
+/// Associe le premier routeur du chemin (identifiant annoncé dans ses HELLO) à une
+/// adresse directement joignable : l'identifiant lui-même s'il est sur un sous-réseau
+/// local, sinon l'adresse source de ses HELLO, sinon l'interface point à point qui y mène ;
+/// un voisin unicast au-delà d'un saut IP est joint par la route du noyau qui y mène
+pub async fn resolve(state: &AppState, router_id: &str) -> Option<NextHop> {
+    let on_link = |ip: Ipv4Addr| {
+        state.interfaces.interfaces()
+            .into_iter()
+            .find(|iface| iface.network.contains(ip))
+            .map(|iface| NextHop { gateway: Some(ip), interface: Some(iface.name) })
+    };
+
+    if let Some(next_hop) = router_id.parse().ok().and_then(on_link) {
+        return Some(next_hop);
+    }
+    let source = state.neighbor_addresses.lock().await.get(router_id).copied();
+    if let Some(next_hop) = source.and_then(on_link) {
+        return Some(next_hop);
+    }
+    let interface = state.neighbors.read().await.get(router_id).and_then(|neighbor| neighbor.interface.clone())?;
+    let config = state.config().await;
+    if config.point_to_point_peer(Some(&interface)).is_some() {
+        return Some(NextHop { gateway: None, interface: Some(interface) });
+    }
+    let peer = source?;
+    crate::discovery::peer_interface(state, &config, peer).await?;
+    recursive(state, &config, peer).await.and_then(on_link)
+}
+
+/// Passerelle de la route du noyau la plus spécifique vers `address`, hors routes
+/// installées par le démon (qui dépendent elles-mêmes de ce voisin)
+async fn recursive(state: &AppState, config: &crate::read_config::RouterConfig, address: Ipv4Addr) -> Option<Ipv4Addr> {
+    let routes = match state.routes.list_routes().await {
+        Ok(routes) => routes,
+        Err(e) => {
+            tracing::warn!("Failed to read kernel routes to resolve {}: {}", address, e);
+            return None;
+        }
+    };
+    routes.into_iter()
+        .filter(|route| route.protocol != config.route_install.protocol && route.prefix.contains(address))
+        .max_by_key(|route| route.prefix.prefix())
+        .and_then(|route| route.gateway)
+}
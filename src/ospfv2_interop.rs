@@ -0,0 +1,1121 @@
+use crate::error::{AppError, Result};
+use crate::ospfv2::{
+    Body, DatabaseDescription, Hello, Lsa, LsaBody, LsaHeader, LsaKey, Packet, RouterLink, DD_INIT, DD_MASTER, DD_MORE,
+    EXTERNAL_LSA, INITIAL_SEQUENCE, LINK_POINT_TO_POINT, LINK_STUB, LINK_TRANSIT, LSA_HEADER_LEN, MAX_AGE,
+    NETWORK_LSA, OPTION_E, ROUTER_LSA,
+};
+use crate::types::{ExternalMetricType, Prefix};
+use pnet::ipnetwork::Ipv4Network;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+use std::net::Ipv4Addr;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Zone des adjacences OSPFv2 : le backbone
+pub const BACKBONE: Ipv4Addr = Ipv4Addr::UNSPECIFIED;
+/// Délai de retransmission des DBD, requêtes et LSA non acquittés (RxmtInterval)
+const RETRANSMIT_INTERVAL: Duration = Duration::from_secs(5);
+/// Période de rafraîchissement de notre LSA routeur (LSRefreshTime)
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1800);
+/// Délai minimal entre deux instances de notre LSA routeur (MinLSInterval)
+const MIN_LS_INTERVAL: Duration = Duration::from_secs(5);
+/// En-têtes IP, OSPF et DBD retirés de la MTU pour dimensionner les paquets
+const PACKET_OVERHEAD: usize = 20 + crate::ospfv2::HEADER_LEN + 8;
+
+/// Interface parlant OSPFv2, traitée en point à point (un seul voisin)
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteropInterface {
+    pub name: String,
+    pub address: Ipv4Addr,
+    pub network: Ipv4Network,
+    pub hello_interval: u16,
+    pub dead_interval: u32,
+    pub cost: u16,
+    pub mtu: u16,
+}
+
+/// États d'un voisin OSPFv2 (RFC 2328, 10.1) ; un lien point à point passe
+/// directement de 2-Way à ExStart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NeighborState {
+    Init,
+    ExStart,
+    Exchange,
+    Loading,
+    Full,
+}
+
+/// Paquet à émettre vers AllSPFRouters sur une interface
+#[derive(Debug, Clone)]
+pub struct Outgoing {
+    pub interface: usize,
+    pub packet: Packet,
+}
+
+/// Changement à répercuter sur l'état du routeur
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InteropEvent {
+    AdjacencyUp { interface: String, address: Ipv4Addr, router_id: Ipv4Addr },
+    AdjacencyDown { interface: String, address: Ipv4Addr, router_id: Ipv4Addr, reason: String },
+    /// HELLO d'un voisin en Full : il reste vivant côté P-OSPF
+    Refresh { interface: String, address: Ipv4Addr },
+    LsdbChanged,
+}
+
+/// Routes apprises du domaine OSPFv2 par un voisin adjacent, métriques relatives à ce voisin
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LearnedRoutes {
+    pub networks: HashMap<Prefix, u32>,
+    pub externals: HashMap<Prefix, (u32, ExternalMetricType)>,
+}
+
+#[derive(Debug)]
+struct Adjacency {
+    router_id: Ipv4Addr,
+    address: Ipv4Addr,
+    state: NeighborState,
+    last_hello: Instant,
+    master: bool,
+    dd_sequence: u32,
+    /// En-têtes de notre LSDB restant à décrire
+    summary: VecDeque<LsaHeader>,
+    /// Dernier DBD émis : retransmis par le maître, renvoyé par l'esclave sur doublon
+    last_dd: Option<DatabaseDescription>,
+    last_dd_sent: Instant,
+    requests: BTreeMap<LsaKey, LsaHeader>,
+    requests_sent: Instant,
+    /// Instances inondées vers ce voisin en attente d'acquittement
+    retransmit: BTreeMap<LsaKey, LsaHeader>,
+    retransmit_sent: Instant,
+}
+
+#[derive(Debug)]
+struct Port {
+    params: InteropInterface,
+    last_hello: Option<Instant>,
+    neighbor: Option<Adjacency>,
+}
+
+#[derive(Debug, Clone)]
+struct StoredLsa {
+    lsa: Lsa,
+    received_at: Instant,
+}
+
+impl StoredLsa {
+    fn age(&self, now: Instant) -> u16 {
+        let elapsed = now.saturating_duration_since(self.received_at).as_secs();
+        (u64::from(self.lsa.header.age) + elapsed).min(u64::from(MAX_AGE)) as u16
+    }
+
+    fn header(&self, now: Instant) -> LsaHeader {
+        LsaHeader { age: self.age(now), ..self.lsa.header }
+    }
+
+    /// Copie à transmettre, vieillie du délai de transmission
+    fn aged_copy(&self, now: Instant) -> Lsa {
+        let mut lsa = self.lsa.clone();
+        lsa.header.age = (self.age(now) + 1).min(MAX_AGE);
+        lsa
+    }
+}
+
+/// Meilleure route externe d'un préfixe : préférence, métrique annoncée, type et premier saut
+type ExternalChoice = ((u8, u32, u32), u32, ExternalMetricType, Ipv4Addr);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Vertex {
+    Router(Ipv4Addr),
+    Network(Ipv4Addr),
+}
+
+/// Adjacences OSPFv2 et LSDB du domaine : machine à états des voisins, échange de bases,
+/// inondation et SPF, sans entrées/sorties (la tâche `spawn_interop_task` les assure)
+#[derive(Debug)]
+pub struct Engine {
+    router_id: Ipv4Addr,
+    ports: Vec<Port>,
+    lsdb: BTreeMap<LsaKey, StoredLsa>,
+    own_sequence: i32,
+    /// Liens de bout de notre LSA routeur en plus des interfaces OSPFv2
+    stubs: Vec<RouterLink>,
+    last_origination: Option<Instant>,
+    origination_pending: bool,
+    outbox: Vec<Outgoing>,
+    events: Vec<InteropEvent>,
+}
+
+impl Engine {
+    pub fn new(router_id: Ipv4Addr, interfaces: Vec<InteropInterface>) -> Self {
+        Self {
+            router_id,
+            ports: interfaces.into_iter().map(|params| Port { params, last_hello: None, neighbor: None }).collect(),
+            lsdb: BTreeMap::new(),
+            own_sequence: INITIAL_SEQUENCE,
+            stubs: Vec::new(),
+            last_origination: None,
+            origination_pending: true,
+            outbox: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn interfaces(&self) -> impl Iterator<Item = &InteropInterface> {
+        self.ports.iter().map(|port| &port.params)
+    }
+
+    /// Voisins adjacents (interface, identifiant, adresse, état)
+    pub fn neighbors(&self) -> Vec<(String, Ipv4Addr, Ipv4Addr, NeighborState)> {
+        self.ports.iter()
+            .filter_map(|port| port.neighbor.as_ref().map(|adj| (port.params.name.clone(), adj.router_id, adj.address, adj.state)))
+            .collect()
+    }
+
+    pub fn lsdb_len(&self) -> usize {
+        self.lsdb.len()
+    }
+
+    /// Paquets à émettre et événements produits depuis le dernier appel
+    pub fn take_output(&mut self) -> (Vec<Outgoing>, Vec<InteropEvent>) {
+        (std::mem::take(&mut self.outbox), std::mem::take(&mut self.events))
+    }
+
+    /// Remplace les liens de bout annoncés (réseaux connectés et routes du domaine P-OSPF)
+    pub fn set_stubs(&mut self, mut stubs: Vec<RouterLink>, now: Instant) {
+        stubs.sort_by_key(|link| (link.link_id, link.link_data));
+        stubs.dedup_by_key(|link| (link.link_id, link.link_data));
+        if stubs != self.stubs {
+            self.stubs = stubs;
+            self.request_origination(now);
+        }
+    }
+
+    /// Traite un paquet reçu sur une interface
+    pub fn receive(&mut self, interface: usize, source: Ipv4Addr, data: &[u8], now: Instant) -> Result<()> {
+        let packet = Packet::decode(data)?;
+        if interface >= self.ports.len() {
+            return Err(AppError::NetworkError(format!("unknown OSPFv2 interface {}", interface)));
+        }
+        if packet.area_id != BACKBONE {
+            return Err(AppError::PacketError(format!("OSPFv2: area {} is not the backbone", packet.area_id)));
+        }
+        if packet.router_id == self.router_id {
+            return Ok(());
+        }
+        match packet.body {
+            Body::Hello(hello) => self.receive_hello(interface, source, packet.router_id, &hello, now),
+            body => {
+                let known = self.ports[interface].neighbor.as_ref()
+                    .is_some_and(|adj| adj.router_id == packet.router_id);
+                if !known {
+                    debug!("OSPFv2 packet from unknown neighbor {} on {}", packet.router_id, self.ports[interface].params.name);
+                    return Ok(());
+                }
+                match body {
+                    Body::DatabaseDescription(dd) => self.receive_dd(interface, dd, now),
+                    Body::LinkStateRequest(keys) => self.receive_request(interface, &keys, now),
+                    Body::LinkStateUpdate(lsas) => self.receive_update(interface, lsas, now),
+                    Body::LinkStateAck(headers) => self.receive_ack(interface, &headers),
+                    Body::Hello(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Temporisations : HELLO, expiration des voisins, retransmissions, vieillissement et rafraîchissement
+    pub fn tick(&mut self, now: Instant) {
+        let own_key = self.own_key();
+        let refresh_due = self.lsdb.get(&own_key)
+            .is_some_and(|own| now.saturating_duration_since(own.received_at) >= REFRESH_INTERVAL);
+        let interval_elapsed = self.last_origination.is_none_or(|last| now.saturating_duration_since(last) >= MIN_LS_INTERVAL);
+        if (self.origination_pending && interval_elapsed) || refresh_due {
+            self.originate(now);
+        }
+
+        for index in 0..self.ports.len() {
+            let port = &self.ports[index];
+            let hello_due = port.last_hello
+                .is_none_or(|sent| now.saturating_duration_since(sent) >= Duration::from_secs(u64::from(port.params.hello_interval)));
+            if hello_due {
+                self.send_hello(index, now);
+            }
+
+            let dead_interval = Duration::from_secs(u64::from(self.ports[index].params.dead_interval));
+            let expired = self.ports[index].neighbor.as_ref()
+                .is_some_and(|adj| now.saturating_duration_since(adj.last_hello) > dead_interval);
+            if expired {
+                self.drop_neighbor(index, "dead interval expired", now);
+                continue;
+            }
+            self.retransmit(index, now);
+        }
+
+        let before = self.lsdb.len();
+        let retransmitting: Vec<LsaKey> = self.ports.iter()
+            .filter_map(|port| port.neighbor.as_ref())
+            .flat_map(|adj| adj.retransmit.keys().copied())
+            .collect();
+        self.lsdb.retain(|key, stored| stored.age(now) < MAX_AGE || retransmitting.contains(key));
+        if self.lsdb.len() != before {
+            self.events.push(InteropEvent::LsdbChanged);
+        }
+    }
+
+    fn own_key(&self) -> LsaKey {
+        LsaKey { ls_type: ROUTER_LSA, link_state_id: self.router_id, advertising_router: self.router_id }
+    }
+
+    fn request_origination(&mut self, now: Instant) {
+        self.origination_pending = true;
+        if self.last_origination.is_none_or(|last| now.saturating_duration_since(last) >= MIN_LS_INTERVAL) {
+            self.originate(now);
+        }
+    }
+
+    /// Nouvelle instance de notre LSA routeur : liens vers les voisins en Full, réseaux
+    /// des interfaces OSPFv2 et liens de bout fournis par `set_stubs`
+    fn originate(&mut self, now: Instant) {
+        let mut links = Vec::new();
+        for port in &self.ports {
+            if let Some(adj) = port.neighbor.as_ref().filter(|adj| adj.state == NeighborState::Full) {
+                links.push(RouterLink {
+                    link_id: adj.router_id,
+                    link_data: port.params.address,
+                    link_type: LINK_POINT_TO_POINT,
+                    metric: port.params.cost,
+                });
+            }
+            links.push(RouterLink {
+                link_id: port.params.network.network(),
+                link_data: port.params.network.mask(),
+                link_type: LINK_STUB,
+                metric: port.params.cost,
+            });
+        }
+        for stub in &self.stubs {
+            if !links.iter().any(|link| link.link_type == LINK_STUB && link.link_id == stub.link_id && link.link_data == stub.link_data) {
+                links.push(stub.clone());
+            }
+        }
+        let sequence = self.own_sequence;
+        self.own_sequence = self.own_sequence.saturating_add(1);
+        let lsa = Lsa::originate(ROUTER_LSA, self.router_id, self.router_id, sequence, LsaBody::Router { flags: 0, links });
+        debug!("Originating OSPFv2 router LSA seq {:#x}", sequence as u32);
+        self.last_origination = Some(now);
+        self.origination_pending = false;
+        self.install(lsa.clone(), now);
+        self.flood(&lsa, None, now);
+    }
+
+    fn install(&mut self, lsa: Lsa, now: Instant) {
+        let key = lsa.header.key();
+        for port in &mut self.ports {
+            if let Some(adj) = port.neighbor.as_mut() {
+                adj.retransmit.remove(&key);
+            }
+        }
+        self.lsdb.insert(key, StoredLsa { lsa, received_at: now });
+        self.events.push(InteropEvent::LsdbChanged);
+    }
+
+    /// Inonde une instance vers les voisins en cours d'échange ou adjacents, sauf l'interface d'arrivée
+    fn flood(&mut self, lsa: &Lsa, except: Option<usize>, now: Instant) {
+        for index in 0..self.ports.len() {
+            if Some(index) == except {
+                continue;
+            }
+            let Some(adj) = self.ports[index].neighbor.as_mut().filter(|adj| adj.state >= NeighborState::Exchange) else {
+                continue;
+            };
+            adj.retransmit.insert(lsa.header.key(), lsa.header);
+            adj.retransmit_sent = now;
+            self.send(index, Body::LinkStateUpdate(vec![lsa.clone()]));
+        }
+    }
+
+    fn send(&mut self, interface: usize, body: Body) {
+        self.outbox.push(Outgoing {
+            interface,
+            packet: Packet { router_id: self.router_id, area_id: BACKBONE, body },
+        });
+    }
+
+    fn send_hello(&mut self, index: usize, now: Instant) {
+        let port = &mut self.ports[index];
+        port.last_hello = Some(now);
+        let hello = Hello {
+            network_mask: port.params.network.mask(),
+            hello_interval: port.params.hello_interval,
+            options: OPTION_E,
+            priority: 1,
+            dead_interval: port.params.dead_interval,
+            designated_router: Ipv4Addr::UNSPECIFIED,
+            backup_designated_router: Ipv4Addr::UNSPECIFIED,
+            neighbors: port.neighbor.iter().map(|adj| adj.router_id).collect(),
+        };
+        self.send(index, Body::Hello(hello));
+    }
+
+    fn receive_hello(&mut self, index: usize, source: Ipv4Addr, router_id: Ipv4Addr, hello: &Hello, now: Instant) {
+        let params = &self.ports[index].params;
+        if hello.hello_interval != params.hello_interval || hello.dead_interval != params.dead_interval {
+            warn!("OSPFv2 HELLO from {} on {}: intervals {}/{} do not match ours {}/{}",
+                  router_id, params.name, hello.hello_interval, hello.dead_interval, params.hello_interval, params.dead_interval);
+            return;
+        }
+        if hello.options & OPTION_E == 0 {
+            warn!("OSPFv2 HELLO from {} on {}: external routing capability (E bit) mismatch", router_id, params.name);
+            return;
+        }
+        if self.ports[index].neighbor.as_ref().is_some_and(|adj| adj.router_id != router_id) {
+            self.drop_neighbor(index, "replaced by another router", now);
+        }
+        let port = &mut self.ports[index];
+        let discovered = port.neighbor.is_none();
+        let adj = port.neighbor.get_or_insert_with(|| Adjacency {
+            router_id,
+            address: source,
+            state: NeighborState::Init,
+            last_hello: now,
+            master: false,
+            dd_sequence: 0,
+            summary: VecDeque::new(),
+            last_dd: None,
+            last_dd_sent: now,
+            requests: BTreeMap::new(),
+            requests_sent: now,
+            retransmit: BTreeMap::new(),
+            retransmit_sent: now,
+        });
+        adj.last_hello = now;
+        adj.address = source;
+        let state = adj.state;
+        if discovered {
+            info!("OSPFv2 neighbor {} ({}) seen on {}", router_id, source, port.params.name);
+            // Répondre sans attendre pour que le voisin nous voie dans la liste
+            self.send_hello(index, now);
+        }
+        if hello.neighbors.contains(&self.router_id) {
+            if state == NeighborState::Init {
+                self.start_exchange(index, now);
+            } else if state == NeighborState::Full {
+                let name = self.ports[index].params.name.clone();
+                self.events.push(InteropEvent::Refresh { interface: name, address: source });
+            }
+        } else if state > NeighborState::Init {
+            self.reset_adjacency(index, NeighborState::Init, "1-way HELLO received", now);
+        }
+    }
+
+    /// ExStart : on se déclare maître avec un DBD vide I|M|MS jusqu'à la négociation
+    fn start_exchange(&mut self, index: usize, now: Instant) {
+        let mtu = self.ports[index].params.mtu;
+        let Some(adj) = self.ports[index].neighbor.as_mut() else {
+            return;
+        };
+        adj.state = NeighborState::ExStart;
+        adj.master = true;
+        adj.dd_sequence = adj.dd_sequence.wrapping_add(rand::random::<u16>() as u32 + 1);
+        adj.summary.clear();
+        adj.requests.clear();
+        adj.retransmit.clear();
+        let dd = DatabaseDescription {
+            interface_mtu: mtu,
+            options: OPTION_E,
+            flags: DD_INIT | DD_MORE | DD_MASTER,
+            sequence: adj.dd_sequence,
+            headers: Vec::new(),
+        };
+        adj.last_dd = Some(dd.clone());
+        adj.last_dd_sent = now;
+        debug!("OSPFv2 neighbor {} -> ExStart", adj.router_id);
+        self.send(index, Body::DatabaseDescription(dd));
+    }
+
+    /// Repli d'une adjacence (voisin toujours entendu) ; signale sa perte si elle était en Full
+    fn reset_adjacency(&mut self, index: usize, state: NeighborState, reason: &str, now: Instant) {
+        let name = self.ports[index].params.name.clone();
+        let Some(adj) = self.ports[index].neighbor.as_mut() else {
+            return;
+        };
+        let was_full = adj.state == NeighborState::Full;
+        warn!("OSPFv2 neighbor {} on {}: {}", adj.router_id, name, reason);
+        let (router_id, address) = (adj.router_id, adj.address);
+        adj.state = state;
+        if was_full {
+            self.events.push(InteropEvent::AdjacencyDown { interface: name, address, router_id, reason: reason.to_string() });
+            self.request_origination(now);
+        }
+        if state == NeighborState::ExStart {
+            self.start_exchange(index, now);
+        }
+    }
+
+    fn drop_neighbor(&mut self, index: usize, reason: &str, now: Instant) {
+        self.reset_adjacency(index, NeighborState::Init, reason, now);
+        self.ports[index].neighbor = None;
+    }
+
+    fn next_dd(&mut self, index: usize, flags: u8, sequence: u32) -> DatabaseDescription {
+        let mtu = self.ports[index].params.mtu;
+        let capacity = (usize::from(mtu).saturating_sub(PACKET_OVERHEAD) / LSA_HEADER_LEN).max(1);
+        let adj = self.ports[index].neighbor.as_mut().expect("adjacency in exchange");
+        let count = capacity.min(adj.summary.len());
+        let headers: Vec<LsaHeader> = adj.summary.drain(..count).collect();
+        let more = if adj.summary.is_empty() { 0 } else { DD_MORE };
+        DatabaseDescription { interface_mtu: mtu, options: OPTION_E, flags: flags | more, sequence, headers }
+    }
+
+    fn send_dd(&mut self, index: usize, dd: DatabaseDescription, now: Instant) {
+        let adj = self.ports[index].neighbor.as_mut().expect("adjacency in exchange");
+        adj.last_dd = Some(dd.clone());
+        adj.last_dd_sent = now;
+        self.send(index, Body::DatabaseDescription(dd));
+    }
+
+    fn receive_dd(&mut self, index: usize, dd: DatabaseDescription, now: Instant) {
+        let params = &self.ports[index].params;
+        if dd.interface_mtu > params.mtu {
+            warn!("OSPFv2 DBD on {}: neighbor MTU {} above ours {}", params.name, dd.interface_mtu, params.mtu);
+            return;
+        }
+        let router_id = self.router_id;
+        let Some(adj) = self.ports[index].neighbor.as_mut() else {
+            return;
+        };
+        let neighbor_id = adj.router_id;
+        match adj.state {
+            NeighborState::Init => {}
+            NeighborState::ExStart => {
+                let init = dd.flags & (DD_INIT | DD_MORE | DD_MASTER) == DD_INIT | DD_MORE | DD_MASTER;
+                if init && dd.headers.is_empty() && neighbor_id > router_id {
+                    // Le voisin est maître : on adopte sa séquence et on répond en esclave
+                    adj.master = false;
+                    adj.dd_sequence = dd.sequence;
+                    self.negotiation_done(index, now);
+                    let reply = self.next_dd(index, 0, dd.sequence);
+                    let finished = reply.flags & DD_MORE == 0 && dd.flags & DD_MORE == 0;
+                    self.send_dd(index, reply, now);
+                    if finished {
+                        self.exchange_done(index, now);
+                    }
+                } else if dd.flags & (DD_INIT | DD_MASTER) == 0 && dd.sequence == adj.dd_sequence && neighbor_id < router_id {
+                    self.negotiation_done(index, now);
+                    self.process_dd(index, dd, now);
+                }
+            }
+            NeighborState::Exchange => {
+                let duplicate = if adj.master {
+                    dd.sequence.wrapping_add(1) == adj.dd_sequence
+                } else {
+                    dd.sequence == adj.dd_sequence
+                };
+                if duplicate {
+                    if !adj.master {
+                        if let Some(last) = adj.last_dd.clone() {
+                            self.send(index, Body::DatabaseDescription(last));
+                        }
+                    }
+                    return;
+                }
+                let master_flag = dd.flags & DD_MASTER != 0;
+                let expected = if adj.master { adj.dd_sequence } else { adj.dd_sequence.wrapping_add(1) };
+                if master_flag == adj.master || dd.flags & DD_INIT != 0 || dd.sequence != expected {
+                    self.reset_adjacency(index, NeighborState::ExStart, "DBD sequence mismatch", now);
+                    return;
+                }
+                self.process_dd(index, dd, now);
+            }
+            NeighborState::Loading | NeighborState::Full => {
+                if !adj.master && dd.sequence == adj.dd_sequence {
+                    if let Some(last) = adj.last_dd.clone() {
+                        self.send(index, Body::DatabaseDescription(last));
+                    }
+                } else if !(adj.master && dd.sequence.wrapping_add(1) == adj.dd_sequence) {
+                    self.reset_adjacency(index, NeighborState::ExStart, "unexpected DBD after exchange", now);
+                }
+            }
+        }
+    }
+
+    fn negotiation_done(&mut self, index: usize, now: Instant) {
+        let headers: VecDeque<LsaHeader> = self.lsdb.values().map(|stored| stored.header(now)).collect();
+        let adj = self.ports[index].neighbor.as_mut().expect("adjacency in ExStart");
+        adj.state = NeighborState::Exchange;
+        adj.summary = headers;
+        debug!("OSPFv2 neighbor {} -> Exchange ({})", adj.router_id, if adj.master { "master" } else { "slave" });
+    }
+
+    /// DBD accepté en Exchange : en-têtes plus récents que notre base à demander, puis
+    /// paquet suivant (maître) ou réponse (esclave)
+    fn process_dd(&mut self, index: usize, dd: DatabaseDescription, now: Instant) {
+        let wanted: Vec<LsaHeader> = dd.headers.iter()
+            .filter(|header| self.lsdb.get(&header.key()).is_none_or(|stored| header.is_newer_than(&stored.header(now))))
+            .copied()
+            .collect();
+        let adj = self.ports[index].neighbor.as_mut().expect("adjacency in exchange");
+        for header in wanted {
+            adj.requests.insert(header.key(), header);
+        }
+        let peer_more = dd.flags & DD_MORE != 0;
+        if adj.master {
+            let sent_more = adj.last_dd.as_ref().is_some_and(|last| last.flags & DD_MORE != 0);
+            if !sent_more && !peer_more {
+                self.exchange_done(index, now);
+                return;
+            }
+            adj.dd_sequence = adj.dd_sequence.wrapping_add(1);
+            let sequence = adj.dd_sequence;
+            let next = self.next_dd(index, DD_MASTER, sequence);
+            self.send_dd(index, next, now);
+        } else {
+            adj.dd_sequence = dd.sequence;
+            let reply = self.next_dd(index, 0, dd.sequence);
+            let finished = reply.flags & DD_MORE == 0 && !peer_more;
+            self.send_dd(index, reply, now);
+            if finished {
+                self.exchange_done(index, now);
+            }
+        }
+    }
+
+    fn exchange_done(&mut self, index: usize, now: Instant) {
+        let adj = self.ports[index].neighbor.as_mut().expect("adjacency in exchange");
+        if adj.requests.is_empty() {
+            self.adjacency_full(index, now);
+        } else {
+            adj.state = NeighborState::Loading;
+            debug!("OSPFv2 neighbor {} -> Loading ({} LSA to request)", adj.router_id, adj.requests.len());
+            self.send_requests(index, now);
+        }
+    }
+
+    fn adjacency_full(&mut self, index: usize, now: Instant) {
+        let name = self.ports[index].params.name.clone();
+        let adj = self.ports[index].neighbor.as_mut().expect("adjacency");
+        adj.state = NeighborState::Full;
+        info!("OSPFv2 adjacency with {} ({}) on {} is Full", adj.router_id, adj.address, name);
+        let event = InteropEvent::AdjacencyUp { interface: name, address: adj.address, router_id: adj.router_id };
+        self.events.push(event);
+        self.request_origination(now);
+    }
+
+    fn send_requests(&mut self, index: usize, now: Instant) {
+        let capacity = (usize::from(self.ports[index].params.mtu).saturating_sub(PACKET_OVERHEAD) / 12).max(1);
+        let adj = self.ports[index].neighbor.as_mut().expect("adjacency in loading");
+        adj.requests_sent = now;
+        let keys: Vec<LsaKey> = adj.requests.keys().take(capacity).copied().collect();
+        self.send(index, Body::LinkStateRequest(keys));
+    }
+
+    fn receive_request(&mut self, index: usize, keys: &[LsaKey], now: Instant) {
+        if self.ports[index].neighbor.as_ref().is_none_or(|adj| adj.state < NeighborState::Exchange) {
+            return;
+        }
+        let mut lsas = Vec::new();
+        for key in keys {
+            match self.lsdb.get(key) {
+                Some(stored) => lsas.push(stored.aged_copy(now)),
+                None => {
+                    self.reset_adjacency(index, NeighborState::ExStart, "request for an unknown LSA", now);
+                    return;
+                }
+            }
+        }
+        self.send_updates(index, lsas);
+    }
+
+    /// LSU découpés pour tenir dans la MTU de l'interface
+    fn send_updates(&mut self, index: usize, lsas: Vec<Lsa>) {
+        let budget = usize::from(self.ports[index].params.mtu).saturating_sub(PACKET_OVERHEAD);
+        let mut batch = Vec::new();
+        let mut size = 0;
+        for lsa in lsas {
+            let length = usize::from(lsa.header.length);
+            if !batch.is_empty() && size + length > budget {
+                self.send(index, Body::LinkStateUpdate(std::mem::take(&mut batch)));
+                size = 0;
+            }
+            size += length;
+            batch.push(lsa);
+        }
+        if !batch.is_empty() {
+            self.send(index, Body::LinkStateUpdate(batch));
+        }
+    }
+
+    /// Réception d'un LSU (RFC 2328, 13)
+    fn receive_update(&mut self, index: usize, lsas: Vec<Lsa>, now: Instant) {
+        if self.ports[index].neighbor.as_ref().is_none_or(|adj| adj.state < NeighborState::Exchange) {
+            return;
+        }
+        let mut acks = Vec::new();
+        for lsa in lsas {
+            let key = lsa.header.key();
+            let current = self.lsdb.get(&key).map(|stored| stored.header(now));
+            let adj = self.ports[index].neighbor.as_mut().expect("adjacency");
+            if current.is_none_or(|current| lsa.header.is_newer_than(&current)) {
+                acks.push(lsa.header);
+                adj.requests.remove(&key);
+                if key.advertising_router == self.router_id {
+                    self.receive_self_originated(lsa, now);
+                    continue;
+                }
+                if lsa.header.age >= MAX_AGE && current.is_none() {
+                    continue;
+                }
+                self.install(lsa.clone(), now);
+                self.flood(&lsa, Some(index), now);
+            } else if current.is_some_and(|current| !current.is_newer_than(&lsa.header)) {
+                // Même instance : acquittement implicite, sinon acquittement direct
+                if adj.retransmit.get(&key).is_some_and(|pending| pending.sequence == lsa.header.sequence) {
+                    adj.retransmit.remove(&key);
+                } else {
+                    acks.push(lsa.header);
+                }
+            } else if let Some(stored) = self.lsdb.get(&key) {
+                // Notre copie est plus récente : la renvoyer au voisin
+                let copy = stored.aged_copy(now);
+                self.send(index, Body::LinkStateUpdate(vec![copy]));
+            }
+        }
+        if !acks.is_empty() {
+            self.send(index, Body::LinkStateAck(acks));
+        }
+        let adj = self.ports[index].neighbor.as_ref().expect("adjacency");
+        if adj.state == NeighborState::Loading {
+            if adj.requests.is_empty() {
+                self.adjacency_full(index, now);
+            } else {
+                self.send_requests(index, now);
+            }
+        }
+    }
+
+    /// Copie d'un de nos LSA plus récente que la nôtre (instance d'avant un redémarrage) :
+    /// notre LSA routeur repart au-delà de sa séquence, les autres sont retirés
+    fn receive_self_originated(&mut self, mut lsa: Lsa, now: Instant) {
+        if lsa.header.key() == self.own_key() {
+            self.own_sequence = self.own_sequence.max(lsa.header.sequence.saturating_add(1));
+            self.originate(now);
+        } else if lsa.header.age < MAX_AGE {
+            lsa.header.age = MAX_AGE;
+            self.install(lsa.clone(), now);
+            self.flood(&lsa, None, now);
+        }
+    }
+
+    fn receive_ack(&mut self, index: usize, headers: &[LsaHeader]) {
+        let Some(adj) = self.ports[index].neighbor.as_mut().filter(|adj| adj.state >= NeighborState::Exchange) else {
+            return;
+        };
+        for header in headers {
+            let key = header.key();
+            if adj.retransmit.get(&key).is_some_and(|pending| pending.sequence == header.sequence && pending.checksum == header.checksum) {
+                adj.retransmit.remove(&key);
+            }
+        }
+    }
+
+    fn retransmit(&mut self, index: usize, now: Instant) {
+        let Some(adj) = self.ports[index].neighbor.as_mut() else {
+            return;
+        };
+        let due = |sent: Instant| now.saturating_duration_since(sent) >= RETRANSMIT_INTERVAL;
+        match adj.state {
+            NeighborState::ExStart | NeighborState::Exchange if (adj.state == NeighborState::ExStart || adj.master) && due(adj.last_dd_sent) => {
+                if let Some(last) = adj.last_dd.clone() {
+                    adj.last_dd_sent = now;
+                    self.send(index, Body::DatabaseDescription(last));
+                }
+            }
+            NeighborState::Loading if due(adj.requests_sent) => self.send_requests(index, now),
+            _ => {}
+        }
+        let adj = self.ports[index].neighbor.as_mut().expect("adjacency");
+        if adj.retransmit.is_empty() || !due(adj.retransmit_sent) {
+            return;
+        }
+        adj.retransmit_sent = now;
+        let pending: Vec<LsaKey> = adj.retransmit.keys().copied().collect();
+        let lsas = pending.iter()
+            .filter_map(|key| self.lsdb.get(key))
+            .map(|stored| stored.aged_copy(now))
+            .collect();
+        self.send_updates(index, lsas);
+    }
+
+    /// SPF sur la LSDB OSPFv2 : routes du domaine regroupées par voisin adjacent de premier saut
+    pub fn routes(&self, now: Instant) -> HashMap<Ipv4Addr, LearnedRoutes> {
+        let live = |key: &LsaKey| self.lsdb.get(key).filter(|stored| stored.age(now) < MAX_AGE).map(|stored| &stored.lsa);
+        let router_links = |router: Ipv4Addr| match live(&LsaKey { ls_type: ROUTER_LSA, link_state_id: router, advertising_router: router }) {
+            Some(Lsa { body: LsaBody::Router { links, .. }, .. }) => links.as_slice(),
+            _ => &[],
+        };
+        let network = |address: Ipv4Addr| self.lsdb.iter()
+            .find(|(key, stored)| key.ls_type == NETWORK_LSA && key.link_state_id == address && stored.age(now) < MAX_AGE)
+            .and_then(|(_, stored)| match &stored.lsa.body {
+                LsaBody::Network { network_mask, attached_routers } => Some((*network_mask, attached_routers.as_slice())),
+                _ => None,
+            });
+
+        // Dijkstra ; un lien n'est retenu que si l'autre extrémité l'annonce aussi
+        let root = Vertex::Router(self.router_id);
+        let mut distances: HashMap<Vertex, (u32, Option<Ipv4Addr>)> = HashMap::new();
+        let mut heap = BinaryHeap::from([Reverse((0u32, root, None::<Ipv4Addr>))]);
+        while let Some(Reverse((distance, vertex, first_hop))) = heap.pop() {
+            if distances.contains_key(&vertex) {
+                continue;
+            }
+            distances.insert(vertex, (distance, first_hop));
+            let edges: Vec<(Vertex, u32)> = match vertex {
+                Vertex::Router(router) => router_links(router).iter().filter_map(|link| match link.link_type {
+                    LINK_POINT_TO_POINT => router_links(link.link_id).iter()
+                        .any(|back| back.link_type == LINK_POINT_TO_POINT && back.link_id == router)
+                        .then_some((Vertex::Router(link.link_id), u32::from(link.metric))),
+                    LINK_TRANSIT => network(link.link_id)
+                        .filter(|(_, attached)| attached.contains(&router))
+                        .map(|_| (Vertex::Network(link.link_id), u32::from(link.metric))),
+                    _ => None,
+                }).collect(),
+                Vertex::Network(address) => network(address).map_or_else(Vec::new, |(_, attached)| {
+                    attached.iter()
+                        .filter(|router| router_links(**router).iter().any(|link| link.link_type == LINK_TRANSIT && link.link_id == address))
+                        .map(|router| (Vertex::Router(*router), 0))
+                        .collect()
+                }),
+            };
+            for (next, cost) in edges {
+                if distances.contains_key(&next) {
+                    continue;
+                }
+                // Premier saut : le routeur atteint depuis la racine, directement ou par un segment
+                let hop = match (first_hop, next) {
+                    (Some(hop), _) => Some(hop),
+                    (None, Vertex::Router(router)) => Some(router),
+                    (None, Vertex::Network(_)) => None,
+                };
+                heap.push(Reverse((distance.saturating_add(cost), next, hop)));
+            }
+        }
+
+        let mut networks: HashMap<Prefix, (u32, Ipv4Addr)> = HashMap::new();
+        let mut offer = |prefix: Prefix, cost: u32, hop: Ipv4Addr| {
+            if networks.get(&prefix).is_none_or(|(best, _)| cost < *best) {
+                networks.insert(prefix, (cost, hop));
+            }
+        };
+        for (vertex, (distance, hop)) in &distances {
+            let Some(hop) = hop else {
+                continue;
+            };
+            match vertex {
+                Vertex::Router(router) => {
+                    for link in router_links(*router).iter().filter(|link| link.link_type == LINK_STUB) {
+                        if let Some(prefix) = prefix(link.link_id, link.link_data) {
+                            offer(prefix, distance.saturating_add(u32::from(link.metric)), *hop);
+                        }
+                    }
+                }
+                Vertex::Network(address) => {
+                    if let Some(prefix) = network(*address).and_then(|(mask, _)| prefix(*address, mask)) {
+                        offer(prefix, *distance, *hop);
+                    }
+                }
+            }
+        }
+
+        let mut externals: HashMap<Prefix, ExternalChoice> = HashMap::new();
+        for (key, stored) in &self.lsdb {
+            let LsaBody::External { network_mask, type2, metric, .. } = &stored.lsa.body else {
+                continue;
+            };
+            if key.ls_type != EXTERNAL_LSA || stored.age(now) >= MAX_AGE || *metric >= 0x00ff_ffff {
+                continue;
+            }
+            let Some((distance, Some(hop))) = distances.get(&Vertex::Router(key.advertising_router)) else {
+                continue;
+            };
+            let Some(prefix) = prefix(key.link_state_id, *network_mask) else {
+                continue;
+            };
+            let (preference, metric_type) = if *type2 {
+                ((2, *metric, *distance), ExternalMetricType::Type2)
+            } else {
+                ((1, distance.saturating_add(*metric), 0), ExternalMetricType::Type1)
+            };
+            if externals.get(&prefix).is_none_or(|(best, ..)| preference < *best) {
+                externals.insert(prefix, (preference, *metric, metric_type, *hop));
+            }
+        }
+
+        let hop_distance = |hop: &Ipv4Addr| distances.get(&Vertex::Router(*hop)).map_or(0, |(distance, _)| *distance);
+        let mut learned: HashMap<Ipv4Addr, LearnedRoutes> = HashMap::new();
+        for (prefix, (cost, hop)) in networks {
+            learned.entry(hop).or_default().networks.insert(prefix, cost.saturating_sub(hop_distance(&hop)));
+        }
+        for (prefix, ((_, total, _), metric, metric_type, hop)) in externals {
+            let metric = match metric_type {
+                ExternalMetricType::Type1 => total.saturating_sub(hop_distance(&hop)),
+                ExternalMetricType::Type2 => metric,
+            };
+            learned.entry(hop).or_default().externals.insert(prefix, (metric, metric_type));
+        }
+        learned
+    }
+}
+
+/// Préfixe normalisé d'une adresse et de son masque
+fn prefix(address: Ipv4Addr, mask: Ipv4Addr) -> Option<Prefix> {
+    let network = Ipv4Network::with_netmask(address, mask).ok()?;
+    Ipv4Network::new(network.network(), network.prefix()).ok().map(Prefix::new)
+}
+
+/// Lien de bout annonçant un préfixe dans notre LSA routeur
+pub fn stub_link(prefix: &Prefix, metric: u32) -> RouterLink {
+    let network = prefix.network();
+    RouterLink {
+        link_id: network.network(),
+        link_data: network.mask(),
+        link_type: LINK_STUB,
+        metric: metric.min(u32::from(u16::MAX)) as u16,
+    }
+}
+
+/// Interfaces configurées en OSPFv2 et présentes sur le système
+pub async fn interop_interfaces(state: &crate::AppState) -> Vec<InteropInterface> {
+    let config = state.config().await;
+    config.interfaces.iter()
+        .filter(|iface| iface.protocol == crate::read_config::InterfaceProtocol::Ospfv2)
+        .filter_map(|iface| {
+            let local = state.interfaces.interfaces().into_iter().find(|local| local.name == iface.name)?;
+            Some(InteropInterface {
+                name: iface.name.clone(),
+                address: local.ip(),
+                network: local.network,
+                hello_interval: config.hello_interval_for(Some(&iface.name)).min(u64::from(u16::MAX)) as u16,
+                dead_interval: config.dead_interval_for(Some(&iface.name)).min(u64::from(u32::MAX)) as u32,
+                cost: iface.cost.unwrap_or_else(|| crate::dijkstra::calculate_ospf_cost(iface.capacity_mbps, true))
+                    .min(u32::from(u16::MAX)) as u16,
+                mtu: interface_mtu(&iface.name),
+            })
+        })
+        .collect()
+}
+
+/// MTU d'une interface (1500 si elle ne peut être lue)
+fn interface_mtu(name: &str) -> u16 {
+    std::fs::read_to_string(format!("/sys/class/net/{}/mtu", name))
+        .ok()
+        .and_then(|mtu| mtu.trim().parse().ok())
+        .unwrap_or(1500)
+}
+
+/// Liens de bout de notre LSA OSPFv2 : réseaux connectés hors OSPFv2 et routes apprises
+/// du domaine P-OSPF (pas celles qui viennent des voisins OSPFv2, pour éviter les boucles)
+async fn local_stubs(state: &crate::AppState, engine: &Engine) -> Vec<RouterLink> {
+    let config = state.config().await;
+    let mut stubs: Vec<RouterLink> = state.interfaces.interfaces().iter()
+        .filter(|iface| config.is_interface_configured(&iface.name) && !config.is_ospfv2_interface(Some(&iface.name)))
+        .map(|iface| {
+            let cost = config.cost_for(Some(&iface.name))
+                .unwrap_or_else(|| crate::dijkstra::calculate_ospf_cost(config.interface(&iface.name).map_or(100, |c| c.capacity_mbps), true));
+            stub_link(&Prefix::new(iface.network), cost)
+        })
+        .collect();
+    let interop_routers: Vec<String> = engine.neighbors().iter()
+        .map(|(_, router_id, _, _)| crate::types::RouterId::from(*router_id).to_string())
+        .collect();
+    let routing_table = state.routing_table.read().await;
+    let origins = state.route_origins.read().await;
+    for (prefix, (_, route_state)) in routing_table.iter() {
+        if origins.get(prefix).is_some_and(|origin| interop_routers.contains(&origin.originator)) {
+            continue;
+        }
+        if let Some(metric) = route_state.metric() {
+            stubs.push(stub_link(prefix, metric.max(1)));
+        }
+    }
+    stubs
+}
+
+/// Reporte les adjacences et routes OSPFv2 dans l'état du routeur : un voisin en Full
+/// devient un voisin P-OSPF, et ses routes un LSA routeur à son identifiant. `installed`
+/// retient, par voisin, le dernier numéro de séquence installé et si son LSA est encore
+/// annoncé : un voisin qui revient repart du numéro suivant.
+async fn apply_events(state: &std::sync::Arc<crate::AppState>, engine: &Engine, events: Vec<InteropEvent>, installed: &mut HashMap<Ipv4Addr, (u32, bool)>) {
+    let mut lsdb_changed = false;
+    for event in events {
+        match event {
+            InteropEvent::AdjacencyUp { interface, address, router_id } => {
+                let local_ip = engine.interfaces().find(|iface| iface.name == interface).map(|iface| iface.address.to_string());
+                crate::neighbor::update_neighbor(state, &address.to_string(), &local_ip.unwrap_or_default()).await;
+                if let Some(neighbor) = state.neighbors.write().await.get_mut(&address.to_string()) {
+                    neighbor.router_id = Some(crate::types::RouterId::from(router_id));
+                }
+                state.metrics.ospfv2_adjacencies.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                lsdb_changed = true;
+            }
+            InteropEvent::Refresh { interface, address } => {
+                let local_ip = engine.interfaces().find(|iface| iface.name == interface).map(|iface| iface.address.to_string());
+                crate::neighbor::update_neighbor(state, &address.to_string(), &local_ip.unwrap_or_default()).await;
+            }
+            InteropEvent::AdjacencyDown { address, reason, .. } => {
+                let mut neighbors = state.neighbors.write().await;
+                if let Some(neighbor) = neighbors.get_mut(&address.to_string()).filter(|neighbor| neighbor.link_up) {
+                    neighbor.link_up = false;
+                    drop(neighbors);
+                    crate::neighbor_stats::publish(state, crate::events::RouterEvent::NeighborDown { neighbor: address.to_string(), reason }).await;
+                }
+                state.metrics.ospfv2_adjacencies.fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+                lsdb_changed = true;
+            }
+            InteropEvent::LsdbChanged => lsdb_changed = true,
+        }
+    }
+    if !lsdb_changed {
+        return;
+    }
+
+    let mut learned = engine.routes(Instant::now());
+    let adjacent: Vec<(Ipv4Addr, Ipv4Addr)> = engine.neighbors().into_iter()
+        .filter(|(_, _, _, state)| *state == NeighborState::Full)
+        .map(|(_, router_id, address, _)| (router_id, address))
+        .collect();
+    let withdrawn: Vec<Ipv4Addr> = installed.iter()
+        .filter(|(router_id, (_, active))| *active && !adjacent.iter().any(|(id, _)| id == *router_id))
+        .map(|(router_id, _)| *router_id)
+        .collect();
+    let mut lsdb = state.lsdb.write().await;
+    for (router_id, address) in adjacent.iter().copied().chain(withdrawn.iter().map(|id| (*id, Ipv4Addr::UNSPECIFIED))) {
+        let routes = learned.remove(&router_id).unwrap_or_default();
+        let (seq_num, active) = installed.entry(router_id).or_insert((0, true));
+        *seq_num += 1;
+        *active = !withdrawn.contains(&router_id);
+        let message = crate::types::LSAMessage {
+            message_type: 2,
+            router_ip: address.to_string(),
+            last_hop: None,
+            originator: router_id.to_string(),
+            router_id: Some(crate::types::RouterId::from(router_id)),
+            seq_num: *seq_num,
+            neighbor_count: 0,
+            neighbors: Vec::new(),
+            routing_table: routes.networks.iter().map(|(prefix, metric)| (*prefix, crate::types::RouteState::Active(*metric))).collect(),
+            external_routes: routes.externals.iter().map(|(prefix, (metric, _))| (*prefix, *metric)).collect(),
+            external_metric_types: routes.externals.iter().map(|(prefix, (_, metric_type))| (*prefix, *metric_type)).collect(),
+            route_tags: HashMap::new(),
+            path: Vec::new(),
+            ttl: 0,
+            stub_only: false,
+            base_seq: None,
+            withdrawn: Vec::new(),
+            opaque: Vec::new(),
+            flush: false,
+            checksum: None,
+            part: None,
+        };
+        lsdb.install(&message);
+    }
+    drop(lsdb);
+    state.metrics.spf_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    state.spf.schedule();
+}
+
+/// Ouvre la socket brute OSPF (protocole IP 89) et rejoint AllSPFRouters sur chaque interface
+#[cfg(unix)]
+fn open_raw_socket(interfaces: &[InteropInterface]) -> Result<tokio::net::UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::RAW, Some(Protocol::from(i32::from(crate::ospfv2::IP_PROTOCOL))))?;
+    socket.set_multicast_ttl_v4(1)?;
+    socket.set_multicast_loop_v4(false)?;
+    socket.set_tos(0xc0)?;
+    for iface in interfaces {
+        socket.join_multicast_v4(&crate::ospfv2::ALL_SPF_ROUTERS, &iface.address)?;
+    }
+    socket.set_nonblocking(true)?;
+    // recvfrom/sendto sur la socket brute : seule l'API datagramme de tokio est utilisée
+    Ok(tokio::net::UdpSocket::from_std(std::net::UdpSocket::from(socket))?)
+}
+
+/// Adjacences OSPFv2 sur les interfaces configurées avec `protocol = "ospfv2"`
+#[cfg(unix)]
+pub fn spawn_interop_task(state: std::sync::Arc<crate::AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let interfaces = interop_interfaces(&state).await;
+        if interfaces.is_empty() {
+            warn!("No OSPFv2 interface found on this system");
+            return;
+        }
+        let socket = match open_raw_socket(&interfaces) {
+            Ok(socket) => socket,
+            Err(e) => {
+                tracing::error!("Failed to open the OSPFv2 raw socket (CAP_NET_RAW required): {}", e);
+                return;
+            }
+        };
+        let socket2 = socket2::SockRef::from(&socket);
+        info!("OSPFv2 interop on {:?}", interfaces.iter().map(|iface| &iface.name).collect::<Vec<_>>());
+        let mut engine = Engine::new(Ipv4Addr::from(state.router_id.0), interfaces);
+        let mut installed = HashMap::new();
+        let mut tick = tokio::time::interval(Duration::from_secs(1));
+        let mut buffer = vec![0u8; 65_535];
+        loop {
+            tokio::select! {
+                _ = tick.tick() => {
+                    if !state.is_enabled().await {
+                        continue;
+                    }
+                    let stubs = local_stubs(&state, &engine).await;
+                    let now = Instant::now();
+                    engine.set_stubs(stubs, now);
+                    engine.tick(now);
+                }
+                received = socket.recv_from(&mut buffer) => {
+                    let (len, source) = match received {
+                        Ok(received) => received,
+                        Err(e) => {
+                            warn!("OSPFv2 receive error: {}", e);
+                            continue;
+                        }
+                    };
+                    let std::net::IpAddr::V4(source) = source.ip() else {
+                        continue;
+                    };
+                    // La socket brute livre l'en-tête IP
+                    let header_len = usize::from(buffer[0] & 0x0f) * 4;
+                    let Some(index) = engine.interfaces().position(|iface| iface.network.contains(source) && iface.address != source) else {
+                        continue;
+                    };
+                    if len <= header_len || !state.is_enabled().await {
+                        continue;
+                    }
+                    if let Err(e) = engine.receive(index, source, &buffer[header_len..len], Instant::now()) {
+                        debug!("Dropping OSPFv2 packet from {}: {}", source, e);
+                        state.metrics.packets_rejected.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    }
+                }
+            }
+
+            let (packets, events) = engine.take_output();
+            for outgoing in packets {
+                let Some(iface) = engine.interfaces().nth(outgoing.interface) else {
+                    continue;
+                };
+                let target = std::net::SocketAddr::new(std::net::IpAddr::V4(crate::ospfv2::ALL_SPF_ROUTERS), 0);
+                let sent = match socket2.set_multicast_if_v4(&iface.address) {
+                    Ok(()) => socket.send_to(&outgoing.packet.encode(), target).await.map(|_| ()),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = sent {
+                    warn!("Failed to send OSPFv2 packet on {}: {}", iface.name, e);
+                }
+            }
+            apply_events(&state, &engine, events, &mut installed).await;
+        }
+    })
+}
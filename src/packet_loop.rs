@@ -1,230 +1,513 @@
-use log::{info, warn, debug, error};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::Ordering;
+use tokio::sync::mpsc;
+use tracing::{debug, Instrument};
 
-pub async fn main_loop(socket: std::sync::Arc<tokio::net::UdpSocket>, state: std::sync::Arc<crate::AppState>) -> crate::error::Result<()> {
-    let mut buf = [0u8; 4096];
-    let (size, src_addr) = socket.recv_from(&mut buf).await?;
+type LocalIps = std::collections::HashMap<std::net::IpAddr, (String, pnet::ipnetwork::IpNetwork)>;
 
-    let decrypted = match crate::net_utils::decrypt(&buf[..size], state.key.as_slice()) {
-        Ok(data) => data,
-        Err(e) => {
-            log::error!("Failed to decrypt message: {}", e);
-            return Err(e);
-        }
-    };
-
-    // Désérialisation du message JSON
-    let json: serde_json::Value = serde_json::from_slice(&decrypted)?;
-    let local_ips: std::collections::HashMap<std::net::IpAddr, (String, pnet::ipnetwork::IpNetwork)> = pnet::datalink::interfaces()
+/// Lit la socket et répartit les datagrammes entre les tâches de traitement : une
+/// installation de route ou un SPF lent ne bloque plus la réception. Une même source
+/// est toujours servie par la même tâche, ses messages restent donc dans l'ordre.
+pub async fn main_loop(socket: std::sync::Arc<crate::transport::Socket>, state: std::sync::Arc<crate::AppState>) -> crate::error::Result<()> {
+    let mut buf = vec![0u8; 65_536];
+    let local_ips: std::sync::Arc<LocalIps> = std::sync::Arc::new(state.interfaces.interfaces()
         .into_iter()
-        .flat_map(|iface| {
-            iface.ips.into_iter().filter_map(move |ip_network| {
-                if let std::net::IpAddr::V4(ipv4) = ip_network.ip() {
-                    if !ipv4.is_loopback() {
-                        Some((std::net::IpAddr::V4(ipv4), (ipv4.to_string(), ip_network)))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
+        .map(|iface| (std::net::IpAddr::V4(iface.ip()), (iface.ip().to_string(), pnet::ipnetwork::IpNetwork::V4(iface.network))))
+        .collect());
+    let receive = state.config().await.receive;
+    // Abandonnées avec la boucle de réception lorsque le démon s'arrête
+    let mut workers = tokio::task::JoinSet::new();
+    let queues: Vec<mpsc::Sender<crate::tcp_session::Frame>> = (0..receive.workers.max(1))
+        .map(|_| {
+            let (sender, receiver) = mpsc::channel(receive.queue_depth.max(1));
+            workers.spawn(worker(std::sync::Arc::clone(&socket), std::sync::Arc::clone(&state), std::sync::Arc::clone(&local_ips), receiver));
+            sender
         })
         .collect();
+    // Les commandes de contrôle, reconnues à l'en-tête de leur trame, ont leur propre file :
+    // une rafale de messages protocolaires ne prive pas l'opérateur du CLI
+    let control_queue = {
+        let (sender, receiver) = mpsc::channel(receive.queue_depth.max(1));
+        workers.spawn(worker(std::sync::Arc::clone(&socket), std::sync::Arc::clone(&state), std::sync::Arc::clone(&local_ips), receiver));
+        sender
+    };
+    // De même pour les HELLO : une rafale de LSA ou de fragments ne fait pas expirer les adjacences
+    let hello_queue = {
+        let (sender, receiver) = mpsc::channel(receive.queue_depth.max(1));
+        workers.spawn(worker(std::sync::Arc::clone(&socket), std::sync::Arc::clone(&state), std::sync::Arc::clone(&local_ips), receiver));
+        sender
+    };
+    // Trames des sessions TCP, servies comme les datagrammes de leur voisin
+    let mut inbound = state.stream_inbound.receiver().await;
     loop {
-        let (len, src_addr) = socket.recv_from(&mut buf).await?;
+        let (received, src_addr, tls) = tokio::select! {
+            datagram = socket.recv_from(&mut buf) => {
+                let (len, src_addr) = datagram?;
+                (buf[..len].to_vec(), src_addr, false)
+            }
+            Some(frame) = inbound.recv() => frame,
+        };
         if local_ips.contains_key(&src_addr.ip()) {
             continue;
         }
-        log::debug!("Received {} bytes from {}", len, src_addr);
-        
-        let decrypted = match crate::net_utils::decrypt(&buf[..len], state.key.as_slice()) {
-            Ok(data) => data,
-            Err(e) => {
-                log::error!("Failed to decrypt message: {}", e);
-                continue;
+        let queue = match crate::framing::peek_type(&received) {
+            Some(crate::framing::CONTROL) => &control_queue,
+            Some(crate::framing::HELLO) => &hello_queue,
+            _ => {
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                src_addr.ip().hash(&mut hasher);
+                &queues[hasher.finish() as usize % queues.len()]
             }
         };
-        
-        let (receiving_interface_ip, receiving_network) = match crate::net_utils::determine_receiving_interface(&src_addr.ip(), &local_ips) {
-            Ok((ip, network)) => (ip, network),
-            Err(e) => {
-                log::error!("Failed to determine receiving interface: {}", e);
-                continue;
+        #[cfg(feature = "fault-injection")]
+        let datagrams = crate::fault::apply(crate::fault::Direction::In, src_addr.ip(), &received);
+        #[cfg(not(feature = "fault-injection"))]
+        let datagrams = [(received, None::<std::time::Duration>)];
+        for (data, delay) in datagrams {
+            match delay {
+                None => enqueue(&state, queue, (data, src_addr, tls))?,
+                Some(delay) => {
+                    let (state, queue) = (std::sync::Arc::clone(&state), queue.clone());
+                    tokio::spawn(async move {
+                        tokio::time::sleep(delay).await;
+                        let _ = enqueue(&state, &queue, (data, src_addr, tls));
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Confie un datagramme à sa tâche de traitement ; perdu si la file est pleine
+fn enqueue(
+    state: &crate::AppState,
+    queue: &mpsc::Sender<crate::tcp_session::Frame>,
+    frame: crate::tcp_session::Frame,
+) -> crate::error::Result<()> {
+    let (len, src_addr) = (frame.0.len(), frame.1);
+    match queue.try_send(frame) {
+        Ok(()) => {
+            state.metrics.receive_queue_depth.fetch_add(1, Ordering::Relaxed);
+        }
+        Err(mpsc::error::TrySendError::Full(_)) => {
+            debug!("Worker queue full, dropping {} bytes from {}", len, src_addr);
+            state.metrics.receive_queue_dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        Err(mpsc::error::TrySendError::Closed(_)) => {
+            return Err(crate::error::AppError::NetworkError("packet worker stopped".to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Traite, dans l'ordre d'arrivée, les datagrammes d'une file de réception
+async fn worker(
+    socket: std::sync::Arc<crate::transport::Socket>,
+    state: std::sync::Arc<crate::AppState>,
+    local_ips: std::sync::Arc<LocalIps>,
+    mut queue: mpsc::Receiver<crate::tcp_session::Frame>,
+) {
+    while let Some((data, src_addr, tls)) = queue.recv().await {
+        state.metrics.receive_queue_depth.fetch_sub(1, Ordering::Relaxed);
+        let span = tracing::info_span!(
+            "packet",
+            src = %src_addr,
+            interface = tracing::field::Empty,
+            msg_type = tracing::field::Empty,
+            seq = tracing::field::Empty,
+        );
+        if let Err(e) = handle_datagram(&socket, &state, &local_ips, &data, src_addr, tls).instrument(span).await {
+            tracing::error!("Failed to process message from {}: {}", src_addr, e);
+        }
+    }
+}
+
+/// Étape du pipeline de réception à laquelle un datagramme a été écarté
+#[derive(Debug)]
+enum Rejection {
+    /// L'émetteur protège ses messages selon un autre mode que le segment de réception
+    AuthMismatch(crate::types::AuthMode),
+    /// Déchiffrement ou vérification du HMAC en échec (autre clé, datagramme altéré)
+    Undecryptable(crate::error::AppError),
+    /// Message mal formé, hors limites ou de type inconnu
+    Malformed(crate::error::AppError),
+    /// LSA sans somme de contrôle, ou dont la somme ne correspond pas au contenu (altéré
+    /// en route)
+    Checksum { originator: String, seq_num: u32, missing: bool },
+}
+
+/// Première étape : lit l'en-tête de la trame, puis déchiffre son corps ou vérifie son HMAC
+/// avec la protection du segment
+fn open(key: &crate::net_utils::SegmentKey, data: &[u8]) -> Result<(crate::framing::FrameHeader, Vec<u8>), Rejection> {
+    let (header, body) = crate::framing::split(data).map_err(Rejection::Malformed)?;
+    if header.auth != key.auth {
+        return Err(Rejection::AuthMismatch(header.auth));
+    }
+    let decrypted = key.open(body).map_err(Rejection::Undecryptable)?;
+    Ok((header, decrypted))
+}
+
+/// Étapes suivantes : analyse du message, réassemblage des fragments et vérification du
+/// contenu ; `None` tant qu'un message fragmenté reste incomplet
+async fn decode(
+    state: &crate::AppState,
+    key: &crate::net_utils::SegmentKey,
+    header: crate::framing::FrameHeader,
+    decrypted: &[u8],
+    src_addr: std::net::SocketAddr,
+) -> Result<Option<crate::packet::Packet>, Rejection> {
+    let parsed = match header.codec {
+        crate::framing::Codec::Json => crate::packet::parse_typed(header.message_type, decrypted),
+    };
+    let packet = match parsed.map_err(Rejection::Malformed)? {
+        crate::packet::Packet::Fragment(fragment) => {
+            let Some(message) = state.reassembly.lock().await.insert(src_addr, &fragment) else {
+                return Ok(None);
+            };
+            match crate::packet::parse(&message).map_err(Rejection::Malformed)? {
+                crate::packet::Packet::Fragment(_) => {
+                    return Err(Rejection::Malformed(crate::error::AppError::PacketError("fragment inside a reassembled message".to_string())));
+                }
+                packet => packet,
             }
+        }
+        packet => packet,
+    };
+    match &packet {
+        crate::packet::Packet::Hello(hello) => {
+            if let Some(auth) = hello.auth.filter(|auth| *auth != key.auth) {
+                return Err(Rejection::AuthMismatch(auth));
+            }
+        }
+        // LSA non scellé ou altéré en route : jamais traité, ni acquitté, ni installé dans la LSDB
+        crate::packet::Packet::Lsa(lsa) if !lsa.checksum_ok() => {
+            return Err(Rejection::Checksum { originator: lsa.originator.clone(), seq_num: lsa.seq_num, missing: lsa.checksum.is_none() });
+        }
+        _ => {}
+    }
+    // Partie d'un LSA découpé pour la MTU du lien : traité une fois toutes ses parties reçues
+    if let crate::packet::Packet::Lsa(lsa) = packet {
+        if lsa.part.is_none() {
+            return Ok(Some(crate::packet::Packet::Lsa(lsa)));
+        }
+        let Some(lsa) = state.lsa_parts.lock().await.insert(src_addr, *lsa) else {
+            return Ok(None);
         };
-        
-        log::debug!("Receiving interface IP: {}, Network: {}", receiving_interface_ip, receiving_network);
-        
-        match serde_json::from_slice::<serde_json::Value>(&decrypted) {
-            Ok(json) => {
-                if let Some(message_type) = json.get("message_type").and_then(|v| v.as_u64()) {
-                    log::debug!("Received message type: {}", message_type);
-                    match message_type {
-                        1 => {
-                            // Vérifier si le protocole OSPF est activé avant de traiter les HELLO
-                            if !state.is_enabled().await {
-                                debug!("OSPF disabled, ignoring HELLO message");
-                                continue;
-                            }
-                            
-                            if let Ok(hello) = serde_json::from_value::<crate::types::HelloMessage>(json) {
-                                log::info!("[RECV] HELLO from {} - {} (received on interface {})", 
-                                    hello.router_ip, src_addr, receiving_interface_ip);
-                                crate::neighbor::update_neighbor(&state, &hello.router_ip).await;
-                                // Utiliser le préfixe réseau de l'interface pour la table de routage
-                                let network_prefix = receiving_network.to_string();
-                                let broadcast_addr = crate::net_utils::calculate_broadcast_for_interface(&receiving_interface_ip, &receiving_network, crate::PORT)?;
-                                let seq_num = std::time::SystemTime::now()
-                                    .duration_since(std::time::UNIX_EPOCH)
-                                    .unwrap_or_else(|_| std::time::Duration::from_secs(0))
-                                    .as_secs() as u32;
-                                if let Err(e) = crate::lsa::send_lsa(&socket, &broadcast_addr, &network_prefix, 
-                                                        None, &network_prefix, std::sync::Arc::clone(&state), 
-                                                        seq_num, vec![network_prefix.clone()]).await {
-                                    log::error!("Failed to send LSA after HELLO: {}", e);
-                                }
-                            }
-                        }
-                        2 => {
-                            // Vérifier si le protocole OSPF est activé avant de traiter les LSA
-                            if !state.is_enabled().await {
-                                debug!("OSPF disabled, ignoring LSA message");
-                                continue;
-                            }
-                            
-                            if let Ok(lsa) = serde_json::from_value::<crate::types::LSAMessage>(json) {
-                                log::info!("[RECV] LSA from {} (originator: {}, last_hop: {:?}, seq: {}) on interface {}", 
-                                    src_addr, lsa.originator, lsa.last_hop, lsa.seq_num, receiving_interface_ip);
-                                let should_process = {
-                                    let mut processed = state.processed_lsa.lock().await;
-                                    let key = (lsa.originator.clone(), lsa.seq_num);
-                                    if !processed.contains(&key) {
-                                        processed.insert(key);
-                                        true
-                                    } else {
-                                        false
-                                    }
-                                };
-                                if should_process && lsa.ttl > 0 {
-                                    if lsa.originator != receiving_interface_ip {
-                                        let path_contains_us = lsa.path.contains(&receiving_interface_ip);
-                                        if !path_contains_us {
-                                            if let Err(e) = crate::lsa::update_routing_from_lsa(std::sync::Arc::clone(&state), &lsa, 
-                                                                                  &src_addr.ip().to_string(), &socket).await {
-                                                log::error!("Failed to update routing from LSA: {}", e);
-                                            }
-                                            if let Err(e) = crate::lsa::update_topology(std::sync::Arc::clone(&state), &lsa).await {
-                                                log::error!("Failed to update topology: {}", e);
-                                            }
-                                            let broadcast_addr = crate::net_utils::calculate_broadcast_for_interface(&receiving_interface_ip, &receiving_network, crate::PORT)?;
-                                            let mut new_path = lsa.path.clone();
-                                            new_path.push(receiving_interface_ip.clone());
-                                            if let Err(e) = crate::lsa::forward_lsa(&socket, &broadcast_addr, &receiving_interface_ip, 
-                                                                                   &lsa, new_path, &state).await {
-                                                log::error!("Failed to forward LSA: {}", e);
-                                            }
-                                        } else {
-                                            log::debug!("Not forwarding LSA as it would create a loop");
-                                        }
-                                    } else {
-                                        log::debug!("Not processing our own LSA");
-                                    }
-                                } else if !should_process {
-                                    log::debug!("Ignoring duplicate LSA (originator: {}, seq: {})", lsa.originator, lsa.seq_num);
-                                } else {
-                                    log::debug!("LSA TTL expired, not forwarding");
-                                }
-                            }
-                        }
-                        3 => {
-                            if let Some(command) = json.get("command").and_then(|v| v.as_str()) {
-                                log::info!("[CLI] Received control command from {}: {}", src_addr, command);
-                                match command {
-                                    "connexion" => {
-                                        log::info!("[CLI] New connection from {}", src_addr);
-                                        let response = "Connexion établie avec succès";
-                                        if let Err(e) = crate::net_utils::send_message(&socket, &src_addr, &response, state.key.as_slice(), "[CLI]").await {
-                                            log::warn!("{}", e);
-                                        }
-                                    },
-                                    "enable" => {
-                                        state.enable().await;
-                                        log::info!("[CLI] Protocole activé via commande réseau");
-                                        let response = "Protocole OSPF activé";
-                                        if let Err(e) = crate::net_utils::send_message(&socket, &src_addr, &response, state.key.as_slice(), "[CLI]").await {
-                                            log::warn!("{}", e);
-                                        }
-                                    },
-                                    "disable" => {
-                                        state.disable().await;
-                                        log::info!("[CLI] Protocole désactivé via commande réseau");
-                                        let response = "Protocole OSPF désactivé";
-                                        if let Err(e) = crate::net_utils::send_message(&socket, &src_addr, &response, state.key.as_slice(), "[CLI]").await {
-                                            log::warn!("{}", e);
-                                        }
-                                    },
-                                    "routing-table" => {
-                                        let routing_table = state.routing_table.lock().await;
-                                        let table_str = if routing_table.is_empty() {
-                                            "Table de routage vide".to_string()
-                                        } else {
-                                            routing_table.iter()
-                                                .map(|(key, (next_hop, state))| format!("{} -> {} ({:?})", key, next_hop, state))
-                                                .collect::<Vec<_>>()
-                                                .join("\n")
-                                        };
-                                        log::info!("[CLI] Routing table requested, sending to {}", src_addr);
-                                        if let Err(e) = crate::net_utils::send_message(&socket, &src_addr, &table_str, state.key.as_slice(), "[CLI]").await {
-                                            log::warn!("[CLI] Failed to send routing table: {}", e);
-                                        }
-                                    },
-                                    "neighbors" => {
-                                        let neighbors = state.neighbors.lock().await;
-                                        let neighbors_str = if neighbors.is_empty() {
-                                            "Aucun voisin détecté".to_string()
-                                        } else {
-                                            neighbors.iter()
-                                                .map(|(ip, neighbor)| {
-                                                    let current_time = std::time::SystemTime::now()
-                                                        .duration_since(std::time::UNIX_EPOCH)
-                                                        .unwrap_or_else(|_| std::time::Duration::from_secs(0))
-                                                        .as_secs();
-                                                    let age = current_time.saturating_sub(neighbor.last_seen);
-                                                    format!("{} (dernière activité: il y a {} secondes)", ip, age)
-                                                })
-                                                .collect::<Vec<_>>()
-                                                .join("\n")
-                                        };
-                                        log::info!("[CLI] Neighbors list requested, sending to {}", src_addr);
-                                        if let Err(e) = crate::net_utils::send_message(&socket, &src_addr, &neighbors_str, state.key.as_slice(), "[CLI]").await {
-                                            log::warn!("[CLI] Failed to send neighbors list: {}", e);
-                                        }
-                                    },
-                                    _ => {
-                                        log::warn!("[CLI] Commande de contrôle inconnue: {}", command);
-                                        let response = format!("Commande inconnue: '{}'. Utilisez 'help' pour voir les commandes disponibles.", command);
-                                        if let Err(e) = crate::net_utils::send_message(&socket, &src_addr, &response, state.key.as_slice(), "[CLI]").await {
-                                            log::warn!("[CLI] Failed to send error response: {}", e);
-                                        }
-                                    }
-                                }
-                            } else {
-                                log::warn!("[CLI] Message de contrôle sans champ 'command'");
-                                let response = "Erreur: message de contrôle sans commande";
-                                if let Err(e) = crate::net_utils::send_message(&socket, &src_addr, &response, state.key.as_slice(), "[CLI]").await {
-                                    log::warn!("[CLI] Failed to send error response: {}", e);
-                                }
-                            }
+        let packet = crate::packet::Packet::Lsa(Box::new(lsa));
+        crate::packet::validate(&packet).map_err(Rejection::Malformed)?;
+        return Ok(Some(packet));
+    }
+    Ok(Some(packet))
+}
+
+/// Journalise un datagramme écarté et le compte selon l'étape qui l'a rejeté
+async fn reject(
+    state: &crate::AppState,
+    rejection: Rejection,
+    src_addr: std::net::SocketAddr,
+    key: &crate::net_utils::SegmentKey,
+    receiving_interface_ip: &str,
+) {
+    match rejection {
+        // Un voisin protégeant autrement ses messages n'est jamais adjacent : on le signale
+        // plutôt que de rejeter silencieusement chacun de ses messages
+        Rejection::AuthMismatch(used) => {
+            tracing::warn!("[AUTH] {} uses auth = \"{}\" but \"{}\" is configured on {}: adjacency refused",
+                src_addr.ip(), used, key.auth, receiving_interface_ip);
+            state.metrics.auth_mode_mismatches.fetch_add(1, Ordering::Relaxed);
+        }
+        Rejection::Undecryptable(e) => {
+            tracing::error!("Failed to decrypt message from {}: {}", src_addr, e);
+            state.metrics.decrypt_failures.fetch_add(1, Ordering::Relaxed);
+            // Seuls les voisins connus sont comptés : une source quelconque ne crée pas d'entrée
+            let neighbor = crate::neighbor_stats::neighbor_for_source(state, src_addr.ip()).await;
+            if state.neighbors.read().await.contains_key(&neighbor) {
+                state.neighbor_stats.lock().await.entry(&neighbor).decrypt_failures += 1;
+            }
+        }
+        Rejection::Malformed(e) => {
+            tracing::warn!("Dropping message from {}: {}", src_addr, e);
+            state.metrics.packets_rejected.fetch_add(1, Ordering::Relaxed);
+        }
+        Rejection::Checksum { originator, seq_num, missing } => {
+            let reason = if missing { "missing checksum" } else { "checksum mismatch" };
+            tracing::warn!("Dropping LSA from {} (originator: {}, seq: {}): {}", src_addr, originator, seq_num, reason);
+            state.metrics.lsa_checksum_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Traite un datagramme reçu, dans le span « packet » qui porte sa source, l'interface
+/// de réception, le type de message et le numéro de séquence. `tls` : trame d'une session
+/// QUIC, déjà authentifiée par le TLS mutuel et donc transmise sans la clé du segment
+async fn handle_datagram(
+    socket: &std::sync::Arc<crate::transport::Socket>,
+    state: &std::sync::Arc<crate::AppState>,
+    local_ips: &LocalIps,
+    data: &[u8],
+    src_addr: std::net::SocketAddr,
+    tls: bool,
+) -> crate::error::Result<()> {
+    tracing::debug!("Received {} bytes from {}", data.len(), src_addr);
+    
+    // Voisin d'un lien point à point ou joint en unicast : son adresse peut être hors
+    // du sous-réseau local
+    let config = state.config().await;
+    let unicast_peer = match src_addr.ip() {
+        std::net::IpAddr::V4(ip) => crate::discovery::peer_interface(state, &config, ip).await,
+        std::net::IpAddr::V6(_) => None,
+    };
+    let point_to_point = match src_addr.ip() {
+        std::net::IpAddr::V4(ip) => config.point_to_point_interface(ip).map(|iface| iface.name.clone())
+            .or(unicast_peer.clone())
+            .and_then(|name| state.interfaces.addresses_of(&name).first().copied()),
+        std::net::IpAddr::V6(_) => None,
+    };
+    let receiving = match point_to_point.and_then(|ip| local_ips.get(&std::net::IpAddr::V4(ip))) {
+        Some(interface) => Ok(interface.clone()),
+        None => crate::net_utils::determine_receiving_interface(&src_addr.ip(), local_ips),
+    };
+    let (receiving_interface_ip, receiving_network) = match receiving {
+        Ok((ip, network)) => (ip, network),
+        Err(e) => {
+            tracing::error!("Failed to determine receiving interface: {}", e);
+            return Ok(());
+        }
+    };
+    
+    tracing::debug!("Receiving interface IP: {}, Network: {}", receiving_interface_ip, receiving_network);
+    let span = tracing::Span::current();
+    span.record("interface", state.interfaces.name_for_address(&receiving_interface_ip).unwrap_or_else(|| receiving_interface_ip.clone()));
+    // Clé du segment de réception : une clé d'accès compromise ne déchiffre pas le backbone
+    let key = if tls { crate::net_utils::SegmentKey::plaintext() } else { state.key_for(&receiving_interface_ip).await };
+    let decoded = match open(&key, data) {
+        Ok((header, decrypted)) => {
+            crate::trace::record(
+                crate::trace::Direction::Received,
+                src_addr,
+                state.interfaces.name_for_address(&receiving_interface_ip),
+                &decrypted,
+            );
+            decode(state, &key, header, &decrypted, src_addr).await
+        }
+        Err(rejection) => Err(rejection),
+    };
+    let packet = match decoded {
+        Ok(Some(packet)) => packet,
+        Ok(None) => return Ok(()),
+        Err(rejection) => {
+            reject(state, rejection, src_addr, &key, &receiving_interface_ip).await;
+            return Ok(());
+        }
+    };
+    tracing::debug!("Received message type: {}", packet.message_type());
+    span.record("msg_type", packet.message_type());
+    match packet {
+        crate::packet::Packet::Hello(hello) => {
+            // Vérifier si le protocole OSPF est activé avant de traiter les HELLO
+            if !state.is_enabled().await {
+                debug!("OSPF disabled, ignoring HELLO message");
+                return Ok(());
+            }
+            let flap_damping = state.config().await.flap_damping;
+            if state.damping.lock().await.is_quarantined(&hello.router_ip, &flap_damping) {
+                debug!("Neighbor {} quarantined, ignoring HELLO", hello.router_ip);
+                state.metrics.quarantined_hellos.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                return Ok(());
+            }
+
+            tracing::info!("[RECV] HELLO from {} - {} (received on interface {})", 
+                hello.router_ip, src_addr, receiving_interface_ip);
+            state.neighbor_stats.lock().await.entry(&hello.router_ip).hellos_rx += 1;
+            crate::hello::process_hello_timestamps(state, &hello, &receiving_interface_ip).await;
+            if let Err(e) = crate::auth::handle_hello(state, socket, &hello, &receiving_interface_ip).await {
+                tracing::warn!("Failed to handle HELLO from {}: {}", hello.router_ip, e);
+            }
+            crate::neighbor::set_identity(state, &hello).await;
+            if let std::net::IpAddr::V4(source) = src_addr.ip() {
+                state.neighbor_addresses.lock().await.insert(hello.router_ip.clone(), source);
+            }
+            if unicast_peer.is_some() {
+                state.neighbor_endpoints.lock().await.insert(hello.router_ip.clone(), src_addr);
+            }
+            let lsa_throttle = state.config().await.lsa_throttle;
+            if !state.damping.lock().await.allow_origination(&receiving_interface_ip, &lsa_throttle) {
+                debug!("Triggered LSA on {} throttled", receiving_interface_ip);
+                state.metrics.lsa_originations_throttled.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                return Ok(());
+            }
+            let point_to_point_peer = config.point_to_point_peer(state.interfaces.name_for_address(&receiving_interface_ip).as_deref());
+            let flood_addr = match point_to_point_peer {
+                Some(peer) => std::net::SocketAddr::new(std::net::IpAddr::V4(peer), state.port),
+                // Un voisin unicast ne reçoit pas nos diffusions : on lui répond directement,
+                // là d'où vient son HELLO (port éventuellement traduit par un NAT)
+                None if unicast_peer.is_some() => src_addr,
+                None => crate::net_utils::flood_address_for_interface(config.transport, &receiving_interface_ip, &receiving_network, state.port)?,
+            };
+            let sender = state.sender_for(&receiving_interface_ip, &flood_addr, socket).await;
+            let Some(seq_num) = crate::sequence::next_sequence(state).await else {
+                return Ok(());
+            };
+            match crate::lsa::send_lsa(&sender, &flood_addr, &receiving_interface_ip,
+                                    None, &receiving_interface_ip, std::sync::Arc::clone(state),
+                                    seq_num, vec![]).await {
+                Ok(()) => crate::neighbor_stats::record_sent(state, &receiving_interface_ip, &flood_addr, false).await,
+                Err(e) => tracing::error!("Failed to send LSA after HELLO: {}", e),
+            }
+            // Segment à accès multiple : son routeur désigné le décrit par un LSA réseau
+            if point_to_point_peer.is_none() && unicast_peer.is_none() {
+                if let Err(e) = crate::network_lsa::originate(state, &sender, &flood_addr, &receiving_interface_ip, receiving_network).await {
+                    tracing::error!("Failed to send network LSA for {}: {}", receiving_network, e);
+                }
+            }
+        }
+        crate::packet::Packet::Lsa(lsa) => {
+            span.record("seq", lsa.seq_num);
+            // Comme pour les échecs de déchiffrement, seuls les voisins connus sont comptés
+            let neighbor = crate::neighbor_stats::neighbor_for_source(state, src_addr.ip()).await;
+            if state.neighbors.read().await.contains_key(&neighbor) {
+                let mut stats = state.neighbor_stats.lock().await;
+                let stats = stats.entry(&neighbor);
+                stats.lsas_rx += 1;
+                stats.last_seq = Some(lsa.seq_num);
+            }
+            // Vérifier si le protocole OSPF est activé avant de traiter les LSA
+            if !state.is_enabled().await {
+                debug!("OSPF disabled, ignoring LSA message");
+                return Ok(());
+            }
+            
+            tracing::info!("[RECV] LSA from {} (originator: {}, last_hop: {:?}, seq: {}) on interface {}", 
+                src_addr, lsa.originator, lsa.last_hop, lsa.seq_num, receiving_interface_ip);
+            // Un retrait ne passe pas par le cache : il en efface l'originateur
+            let should_process = !lsa.flush && {
+                let mut processed = state.processed_lsa.lock().await;
+                let is_new = processed.insert(&lsa.originator, lsa.seq_num);
+                state.metrics.processed_lsa_entries.store(processed.len() as u64, std::sync::atomic::Ordering::Relaxed);
+                is_new
+            };
+            // Acquitter chaque instance reçue, même dupliquée : l'émetteur
+            // la retire de sa liste de retransmission
+            let sender = crate::flooding::NeighborKey::new(
+                state.interfaces.name_for_address(&receiving_interface_ip),
+                &src_addr.ip().to_string(),
+            );
+            let identity = crate::types::LsaIdentity { originator: lsa.originator.clone(), seq_num: lsa.seq_num };
+            state.flooding.lock().await.acknowledge(&sender, &identity);
+            let ack_addr = match unicast_peer {
+                Some(_) => src_addr,
+                None => std::net::SocketAddr::new(src_addr.ip(), state.port),
+            };
+            if let Err(e) = crate::flooding::send_ack(state, socket, &ack_addr, &receiving_interface_ip, &lsa, &key).await {
+                tracing::warn!("Failed to acknowledge LSA: {}", e);
+            }
+            if lsa.flush {
+                if let Err(e) = crate::lsa::apply_flush(state, socket, &receiving_interface_ip, &lsa, &sender).await {
+                    tracing::error!("Failed to forward LSA flush: {}", e);
+                }
+                return Ok(());
+            }
+            // Un LSA différentiel est reconstitué depuis la dernière instance complète
+            // reçue du même voisin
+            let lsa = if lsa.base_seq.is_some() {
+                let expanded = state.lsa_deltas.lock().await.expand(&src_addr.ip().to_string(), &lsa);
+                match expanded {
+                    Some(full) => Box::new(full),
+                    None => {
+                        if should_process {
+                            debug!("No base instance {:?} from {} for delta LSA of {} (seq: {})",
+                                   lsa.base_seq, src_addr.ip(), lsa.originator, lsa.seq_num);
+                            state.metrics.lsa_deltas_unusable.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
                         }
-                        _ => log::warn!("[CLI] Unknown message type: {}", message_type),
+                        return Ok(());
                     }
+                }
+            } else {
+                state.lsa_deltas.lock().await.record_received(&src_addr.ip().to_string(), &lsa);
+                lsa
+            };
+            // Comparé une fois reconstitué : un différentiel ne ressemble à aucune instance émise
+            if crate::collision::reject_foreign_own_lsa(state, &lsa, &src_addr.ip().to_string()).await {
+                return Ok(());
+            }
+            if should_process && lsa.ttl > 0 {
+                let lsa_throttle = state.config().await.lsa_throttle;
+                if !state.damping.lock().await.accept_arrival(&lsa.originator, &lsa_throttle) {
+                    debug!("LSA from originator {} arrived too soon, dropped (seq: {})", lsa.originator, lsa.seq_num);
+                    state.metrics.lsa_arrivals_throttled.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    return Ok(());
+                }
+                if !crate::lsa::accept_lsa_sequence(state, &lsa.originator, lsa.seq_num).await {
+                    return Ok(());
+                }
+                if lsa.originator == receiving_interface_ip {
+                    tracing::debug!("Not processing our own LSA");
+                } else if crate::collision::is_own_lsa(state, &lsa) {
+                    // Nos propres LSA revenus par une autre interface : gardés dans la LSDB,
+                    // mais ni source de routes ni relayés, nos voisins les tiennent de nous
+                    tracing::debug!("Own LSA {} came back through {}", lsa.originator, src_addr);
+                    if let Err(e) = crate::lsa::update_topology(std::sync::Arc::clone(state), &lsa).await {
+                        tracing::error!("Failed to update topology: {}", e);
+                    }
+                } else if state.config().await.role == crate::read_config::RouterRole::Access {
+                    // Routeur d'accès : ni LSDB du domaine ni relais, la route par défaut
+                    // ne dépend que des voisins directs
+                    tracing::debug!("Access router, LSA from originator {} not kept", lsa.originator);
                 } else {
-                    log::warn!("No message_type field in received JSON");
+                    if let Err(e) = crate::lsa::update_routing_from_lsa(std::sync::Arc::clone(state), &lsa, 
+                                                          &src_addr.ip().to_string(), socket).await {
+                        tracing::error!("Failed to update routing from LSA: {}", e);
+                    }
+                    if let Err(e) = crate::lsa::update_topology(std::sync::Arc::clone(state), &lsa).await {
+                        tracing::error!("Failed to update topology: {}", e);
+                    }
+                    if let Err(e) = crate::lsa::forward_lsa(socket, &receiving_interface_ip, 
+                                                           &lsa, &sender, state).await {
+                        tracing::error!("Failed to forward LSA: {}", e);
+                    }
                 }
+            } else if !should_process {
+                tracing::debug!("Ignoring duplicate LSA (originator: {}, seq: {})", lsa.originator, lsa.seq_num);
+            } else {
+                tracing::debug!("LSA TTL expired, not forwarding");
             }
-            Err(e) => {
-                log::error!("Failed to parse JSON: {}", e);
+        }
+        crate::packet::Packet::Control(control) => {
+            tracing::info!("[CLI] Received control command from {}: {}", src_addr, control.command);
+            // Hors de la file de contrôle : une commande qui attend d'autres routeurs (`ping`)
+            // ne retient pas les suivantes
+            let (state, socket) = (std::sync::Arc::clone(state), std::sync::Arc::clone(socket));
+            tokio::spawn(async move {
+                let response = crate::control_auth::execute_authorized(&state, &socket, &control, src_addr).await;
+                // Identifiant renvoyé au CLI qui en a fourni un ; le texte seul sinon
+                let sent = match control.request_id {
+                    Some(request_id) => {
+                        let response = crate::types::ControlResponse { request_id, response };
+                        crate::net_utils::send_message(&socket, &src_addr, &response, &key, "[CLI]").await
+                    }
+                    None => crate::net_utils::send_message(&socket, &src_addr, &response, &key, "[CLI]").await,
+                };
+                if let Err(e) = sent {
+                    tracing::warn!("[CLI] Failed to send response: {}", e);
+                }
+            });
+        }
+        crate::packet::Packet::AuthChallenge(challenge) => {
+            if let Err(e) = crate::auth::handle_challenge(state, socket, &challenge, &receiving_interface_ip).await {
+                tracing::warn!("Failed to answer challenge from {}: {}", challenge.router_ip, e);
             }
         }
+        crate::packet::Packet::AuthResponse(response) => {
+            crate::auth::handle_response(state, &response, &receiving_interface_ip).await;
+        }
+        crate::packet::Packet::LsaAck(ack) => {
+            crate::flooding::handle_ack(state, &src_addr.ip().to_string(), &receiving_interface_ip, &ack).await;
+        }
+        crate::packet::Packet::Echo(echo) => crate::probe::handle_echo(state, echo, &receiving_interface_ip, src_addr).await,
+        crate::packet::Packet::RouteRequest(request) => crate::route_query::handle_request(state, socket, request, src_addr, &key).await,
+        crate::packet::Packet::RouteResponse(response) => crate::route_query::handle_response(state, response).await,
+        // Réassemblé plus haut
+        crate::packet::Packet::Fragment(_) => {}
     }
-}
\ No newline at end of file
+    Ok(())
+}
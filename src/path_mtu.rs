@@ -0,0 +1,210 @@
+use crate::fragment::{self, DEFAULT_MTU, MIN_MTU};
+use crate::transport::Socket;
+use crate::types::EchoMessage;
+use crate::AppState;
+use pnet::ipnetwork::Ipv4Network;
+use rand::{rngs::OsRng, RngCore};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// MTU essayées vers chaque voisin, de la plus grande à la plus petite
+const CANDIDATES: [u16; 5] = [DEFAULT_MTU, 1400, 1280, 1024, MIN_MTU];
+/// Sondes envoyées pour une taille avant de la tenir pour trop grande
+const ATTEMPTS: usize = 2;
+/// En-têtes IPv4 et UDP, comptés dans la MTU
+const IP_UDP_HEADERS: usize = 28;
+
+#[derive(Debug, Clone, Copy)]
+struct Measure {
+    /// Sous-réseau partagé avec le voisin, pour les envois en diffusion
+    network: Ipv4Network,
+    mtu: u16,
+    measured: Instant,
+}
+
+/// MTU mesurées par ce routeur, par adresse de voisin
+#[derive(Debug, Default)]
+pub struct PathMtuTable {
+    measures: BTreeMap<Ipv4Addr, Measure>,
+}
+
+impl PathMtuTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// MTU vers une destination : celle du voisin s'il a été mesuré ; pour une diffusion,
+    /// la plus petite des voisins du sous-réseau (de tous les voisins en multicast)
+    pub fn mtu_for(&self, addr: IpAddr) -> Option<u16> {
+        let IpAddr::V4(addr) = addr else {
+            return None;
+        };
+        if let Some(measure) = self.measures.get(&addr) {
+            return Some(measure.mtu);
+        }
+        self.measures.values()
+            .filter(|measure| addr.is_multicast() || (measure.network.contains(addr) && addr == measure.network.broadcast()))
+            .map(|measure| measure.mtu)
+            .min()
+    }
+
+    /// Taille des morceaux d'un message découpé pour `addr` (voir `fragment::payload_for_mtu`)
+    pub fn max_payload(&self, addr: IpAddr) -> usize {
+        fragment::payload_for_mtu(self.mtu_for(addr).unwrap_or(DEFAULT_MTU))
+    }
+
+    /// Mesure enregistrée pour un voisin et son âge
+    pub fn measured(&self, neighbor: Ipv4Addr) -> Option<(u16, Duration)> {
+        self.measures.get(&neighbor).map(|measure| (measure.mtu, measure.measured.elapsed()))
+    }
+
+    /// Enregistre une mesure ; renvoie la MTU précédente du voisin
+    fn record(&mut self, neighbor: Ipv4Addr, network: Ipv4Network, mtu: u16) -> Option<u16> {
+        self.measures.insert(neighbor, Measure { network, mtu, measured: Instant::now() }).map(|previous| previous.mtu)
+    }
+}
+
+/// Envoie à un voisin direct, depuis `socket`, un écho bourré à `mtu` octets au plus,
+/// en-têtes compris ; vrai s'il répond avant `probe::PROBE_TIMEOUT`. Un envoi refusé
+/// (EMSGSIZE : plus grand que la MTU de l'interface) est un échec comme l'absence de réponse
+async fn probe(state: &Arc<AppState>, socket: &Socket, neighbor: Ipv4Addr, mtu: u16) -> bool {
+    let key = state.key_for(&neighbor.to_string()).await;
+    let router_id = state.router_id.to_string();
+    let id = OsRng.next_u64();
+    let mut echo = EchoMessage {
+        message_type: 8,
+        id,
+        source: router_id.clone(),
+        target: neighbor.to_string(),
+        reply: false,
+        path: vec![router_id],
+        ttl: 1,
+        signature: String::new(),
+        padding: String::new(),
+    };
+    if echo.sign(&key.key).is_err() {
+        return false;
+    }
+    // Bourrage réduit jusqu'à tenir dans la taille visée : le chiffrement arrondit au bloc
+    let target = mtu as usize - IP_UDP_HEADERS;
+    let mut padding = target;
+    let data = loop {
+        echo.padding = "x".repeat(padding);
+        let Ok(data) = serde_json::to_vec(&echo) else {
+            return false;
+        };
+        let Ok(sealed) = key.seal(&data) else {
+            return false;
+        };
+        let len = crate::framing::HEADER_LEN + sealed.len();
+        if len <= target || padding == 0 {
+            break data;
+        }
+        padding = padding.saturating_sub(len - target);
+    };
+
+    let receiver = state.probes.lock().await.register(id);
+    let addr = SocketAddr::new(neighbor.into(), state.port);
+    if let Err(e) = crate::net_utils::send_datagram(socket, &addr, echo.message_type, &data, &key).await {
+        debug!("[MTU] {} byte probe to {} not sent: {}", mtu, neighbor, e);
+        state.probes.lock().await.cancel(id);
+        return false;
+    }
+    match tokio::time::timeout(crate::probe::PROBE_TIMEOUT, receiver).await {
+        Ok(Ok(_)) => true,
+        _ => {
+            state.probes.lock().await.cancel(id);
+            false
+        }
+    }
+}
+
+/// Mesure la MTU vers un voisin : la plus grande taille candidate dont une sonde revient.
+/// Sans aucune réponse, le voisin est peut-être injoignable : rien n'est enregistré
+pub async fn discover(state: &Arc<AppState>, neighbor: Ipv4Addr) -> Option<u16> {
+    let (network, local_ip) = state.interfaces.interfaces().into_iter()
+        .map(|iface| (iface.network, iface.ip()))
+        .find(|(network, _)| network.contains(neighbor))?;
+    // Sans le bit DF, le noyau fragmenterait les sondes trop grandes et toutes
+    // reviendraient ; le simulateur, lui, perd déjà les datagrammes plus grands que le lien
+    let dedicated = match state.socket.as_udp() {
+        Some(_) => match crate::net_utils::create_probe_sender(local_ip, state.vrf.as_deref()) {
+            Ok(socket) => Some(Socket::Udp(socket)),
+            Err(e) => {
+                warn!("[MTU] Cannot open a probe socket on {}: {}", local_ip, e);
+                return None;
+            }
+        },
+        None => None,
+    };
+    let socket = dedicated.as_ref().unwrap_or(&state.socket);
+    for mtu in CANDIDATES {
+        for _ in 0..ATTEMPTS {
+            if probe(state, socket, neighbor, mtu).await {
+                let previous = state.path_mtu.lock().await.record(neighbor, network, mtu);
+                if previous != Some(mtu) {
+                    info!("[MTU] Path MTU to {} is {} bytes", neighbor, mtu);
+                }
+                return Some(mtu);
+            }
+        }
+        debug!("[MTU] No reply from {} to {} byte probes", neighbor, mtu);
+    }
+    None
+}
+
+/// Mesure périodiquement la MTU vers chaque voisin actif, dès son apparition
+pub fn spawn_mtu_discovery_task(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            tick.tick().await;
+            let config = state.config().await.mtu_discovery;
+            if !config.enabled || !state.is_enabled().await {
+                continue;
+            }
+            let interval = Duration::from_secs(config.interval_sec);
+            let neighbors: Vec<crate::types::Neighbor> = state.neighbors.read().await.values().cloned().collect();
+            for neighbor in neighbors {
+                let Ok(address) = neighbor.neighbor_ip.parse::<Ipv4Addr>() else {
+                    continue;
+                };
+                let measured = state.path_mtu.lock().await.measured(address);
+                if measured.is_some_and(|(_, age)| age < interval) || !crate::neighbor::is_usable(&state, &neighbor).await {
+                    continue;
+                }
+                discover(&state, address).await;
+            }
+        }
+    })
+}
+
+/// Commande `mtu` : MTU mesurée vers chaque voisin et taille des fragments qui en découle
+pub async fn report(state: &AppState) -> String {
+    let mut neighbors: Vec<crate::types::Neighbor> = state.neighbors.read().await.values().cloned().collect();
+    if neighbors.is_empty() {
+        return "Aucun voisin".to_string();
+    }
+    neighbors.sort_by(|a, b| a.neighbor_ip.cmp(&b.neighbor_ip));
+    let path_mtu = state.path_mtu.lock().await;
+    let mut lines = vec!["MTU par voisin :".to_string()];
+    for neighbor in neighbors {
+        let measure = neighbor.neighbor_ip.parse().ok().and_then(|address| path_mtu.measured(address));
+        let (mtu, origin) = match measure {
+            Some((mtu, age)) => (mtu, format!("mesurée il y a {}s", age.as_secs())),
+            None => (DEFAULT_MTU, "supposée".to_string()),
+        };
+        lines.push(format!(
+            "  {:<28} {} octets ({}), fragments de {} octets",
+            neighbor.display_name(),
+            mtu,
+            origin,
+            fragment::payload_for_mtu(mtu),
+        ));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,115 @@
+use crate::error::Result;
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Modification administrative d'une interface par rapport à la configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersistedInterface {
+    pub link_active: bool,
+    #[serde(default)]
+    pub cost: Option<u32>,
+}
+
+/// État administratif conservé d'un démarrage à l'autre (`state_file`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersistedState {
+    pub enabled: bool,
+    /// Interfaces dont l'état courant diffère de la configuration
+    #[serde(default)]
+    pub interfaces: BTreeMap<String, PersistedInterface>,
+    /// Numéro de séquence réservé : les LSA émis après redémarrage repartent au-delà
+    #[serde(default)]
+    pub lsa_sequence: Option<u32>,
+}
+
+impl Default for PersistedState {
+    fn default() -> Self {
+        Self { enabled: true, interfaces: BTreeMap::new(), lsa_sequence: None }
+    }
+}
+
+/// Lit le fichier d'état ; `None` s'il n'existe pas encore
+pub fn load(path: &Path) -> Result<Option<PersistedState>> {
+    match std::fs::read(path) {
+        Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Écrit le fichier d'état via un fichier temporaire renommé, pour ne jamais le laisser tronqué
+pub fn write(path: &Path, persisted: &PersistedState) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let temporary = path.with_extension("tmp");
+    std::fs::write(&temporary, serde_json::to_vec_pretty(persisted)?)?;
+    std::fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+/// Photographie l'état administratif courant du routeur
+pub async fn snapshot(state: &AppState) -> PersistedState {
+    let config = state.config().await;
+    let states = state.interface_states.read().await;
+    let interfaces = config.interfaces.iter()
+        .filter_map(|iface| {
+            let current = states.get(&iface.name)?;
+            (current.link_active != iface.link_active || current.cost != iface.cost).then(|| {
+                (iface.name.clone(), PersistedInterface { link_active: current.link_active, cost: current.cost })
+            })
+        })
+        .collect();
+    drop(states);
+    PersistedState {
+        enabled: state.is_enabled().await,
+        interfaces,
+        lsa_sequence: Some(state.own_sequence.reserved()),
+    }
+}
+
+/// Enregistre l'état administratif si un fichier d'état est configuré
+pub async fn save(state: &AppState) {
+    let Some(path) = state.config().await.state_file else {
+        return;
+    };
+    if let Err(e) = write(&path, &snapshot(state).await) {
+        warn!("Failed to save administrative state to {}: {}", path.display(), e);
+    }
+}
+
+/// Rétablit au démarrage l'état administratif enregistré (protocole désactivé, interfaces coupées)
+pub async fn restore(state: &AppState) {
+    let Some(path) = state.config().await.state_file else {
+        return;
+    };
+    let persisted = match load(&path) {
+        Ok(Some(persisted)) => persisted,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Ignoring unreadable state file {}: {}", path.display(), e);
+            return;
+        }
+    };
+    if let Some(seq_num) = persisted.lsa_sequence {
+        state.own_sequence.resume_after(seq_num);
+        info!("LSA sequence resumes after {:#010x}", seq_num);
+    }
+    if !persisted.enabled {
+        state.disable().await;
+        info!("Protocol disabled by operator before restart ({})", path.display());
+    }
+    let mut states = state.interface_states.write().await;
+    for (name, saved) in &persisted.interfaces {
+        let Some(current) = states.get_mut(name) else {
+            warn!("State file {} refers to unknown interface {}", path.display(), name);
+            continue;
+        };
+        current.set_link_state(saved.link_active);
+        current.cost = saved.cost;
+        info!("Restored interface {}: link {}, cost {:?}", name, if saved.link_active { "up" } else { "down" }, saved.cost);
+    }
+}
@@ -0,0 +1,115 @@
+use std::net::IpAddr;
+use std::sync::Mutex;
+use futures::future::BoxFuture;
+use pnet::ipnetwork::Ipv4Network;
+use crate::error::{AppError, Result};
+use crate::route_backend::{KernelRoute, RouteBackend, RTPROT_KERNEL, RTPROT_STATIC};
+
+/// Table de routage via l'API native du système (socket de routage BSD/macOS,
+/// IP Helper sous Windows). Ces API ne conservent pas de protocole d'origine :
+/// les routes installées par le démon sont retenues en mémoire pour être reconnues.
+#[derive(Debug, Default)]
+pub struct NetRouteBackend {
+    installed: Mutex<Vec<KernelRoute>>,
+}
+
+impl NetRouteBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn installed(&self) -> std::sync::MutexGuard<'_, Vec<KernelRoute>> {
+        self.installed.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn handle() -> Result<net_route::Handle> {
+    net_route::Handle::new()
+        .map_err(|e| AppError::RouteError(format!("Impossible d'ouvrir la table de routage: {}", e)))
+}
+
+fn to_system(route: &KernelRoute) -> net_route::Route {
+    let mut system = net_route::Route::new(IpAddr::V4(route.prefix.network()), route.prefix.prefix());
+    if let Some(gateway) = route.gateway {
+        system = system.with_gateway(IpAddr::V4(gateway));
+    }
+    if let Some(index) = route.output_interface {
+        system = system.with_ifindex(index);
+    }
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    if let Some(priority) = route.priority {
+        system = system.with_metric(priority);
+    }
+    system
+}
+
+fn from_system(route: &net_route::Route) -> Option<KernelRoute> {
+    let IpAddr::V4(destination) = route.destination else {
+        return None;
+    };
+    let prefix = Ipv4Network::new(destination, route.prefix).ok()?;
+    let gateway = match route.gateway {
+        Some(IpAddr::V4(gateway)) if !gateway.is_unspecified() => Some(gateway),
+        _ => None,
+    };
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    let priority = route.metric;
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let priority = None;
+    Some(KernelRoute {
+        prefix,
+        gateway,
+        output_interface: route.ifindex,
+        protocol: if gateway.is_some() { RTPROT_STATIC } else { RTPROT_KERNEL },
+        priority,
+    })
+}
+
+impl RouteBackend for NetRouteBackend {
+    fn name(&self) -> &'static str {
+        "net-route"
+    }
+
+    fn list_routes(&self) -> BoxFuture<'_, Result<Vec<KernelRoute>>> {
+        Box::pin(async move {
+            let listed = handle()?.list().await
+                .map_err(|e| AppError::RouteError(format!("Erreur lors de la lecture des routes: {}", e)))?;
+            let installed = self.installed();
+            Ok(listed.iter()
+                .filter_map(from_system)
+                .map(|route| {
+                    installed.iter()
+                        .find(|own| own.prefix == route.prefix && own.gateway == route.gateway)
+                        .cloned()
+                        .unwrap_or(route)
+                })
+                .collect())
+        })
+    }
+
+    fn install_route(&self, route: KernelRoute) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let handle = handle()?;
+            // Pas de remplacement atomique : retirer d'abord l'éventuelle route précédente
+            let previous = self.installed().iter().find(|own| own.prefix == route.prefix).cloned();
+            if let Some(previous) = previous {
+                let _ = handle.delete(&to_system(&previous)).await;
+            }
+            handle.add(&to_system(&route)).await
+                .map_err(|e| AppError::RouteError(format!("Erreur lors de l'ajout de {} via {:?}: {}", route.prefix, route.gateway, e)))?;
+            let mut installed = self.installed();
+            installed.retain(|own| own.prefix != route.prefix);
+            installed.push(route);
+            Ok(())
+        })
+    }
+
+    fn delete_route(&self, route: KernelRoute) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            handle()?.delete(&to_system(&route)).await
+                .map_err(|e| AppError::RouteError(format!("Erreur lors de la suppression de {}: {}", route.prefix, e)))?;
+            self.installed().retain(|own| *own != route);
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,183 @@
+use crate::error::{AppError, Result};
+use crate::types::EchoMessage;
+use crate::AppState;
+use rand::{rngs::OsRng, RngCore};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Délai d'attente de la réponse à un écho, inférieur à celui du CLI
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Sondes lancées par ce routeur, en attente de leur réponse
+#[derive(Debug, Default)]
+pub struct PendingProbes {
+    pending: HashMap<u64, oneshot::Sender<EchoMessage>>,
+}
+
+impl PendingProbes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, id: u64) -> oneshot::Receiver<EchoMessage> {
+        let (sender, receiver) = oneshot::channel();
+        self.pending.insert(id, sender);
+        receiver
+    }
+
+    fn complete(&mut self, reply: EchoMessage) -> bool {
+        match self.pending.remove(&reply.id) {
+            Some(sender) => sender.send(reply).is_ok(),
+            None => false,
+        }
+    }
+
+    pub fn cancel(&mut self, id: u64) {
+        self.pending.remove(&id);
+    }
+}
+
+/// Vrai si `identity` désigne ce routeur : son identifiant ou une de ses adresses
+fn is_local(state: &AppState, identity: &str) -> bool {
+    identity == state.router_id.to_string()
+        || state.interfaces.interfaces().iter().any(|iface| iface.ip().to_string() == identity)
+}
+
+/// Prochain saut vers un routeur : le voisin lui-même s'il est adjacent, sinon la route
+/// active la plus spécifique couvrant son adresse
+async fn next_hop_towards(state: &Arc<AppState>, target: &str) -> Option<Ipv4Addr> {
+    let neighbors: Vec<crate::types::Neighbor> = state.neighbors.read().await.values()
+        .filter(|neighbor| neighbor.neighbor_ip == target || neighbor.identity() == target)
+        .cloned()
+        .collect();
+    for neighbor in neighbors {
+        if crate::neighbor::is_usable(state, &neighbor).await {
+            return neighbor.neighbor_ip.parse().ok();
+        }
+    }
+    let address: Ipv4Addr = target.parse().ok()?;
+    let routing_table = state.routing_table.read().await;
+    routing_table.iter()
+        .filter(|(prefix, (_, route_state))| prefix.contains(address) && route_state.metric().is_some())
+        .max_by_key(|(prefix, _)| prefix.prefix_len())
+        .and_then(|(_, (next_hop, _))| next_hop.parse().ok())
+}
+
+/// Relaie un écho vers le prochain saut de `towards`, signé à nouveau
+async fn forward(state: &Arc<AppState>, mut echo: EchoMessage, towards: &str) -> Result<()> {
+    let Some(next_hop) = next_hop_towards(state, towards).await else {
+        return Err(AppError::NetworkError(format!("no route to {}", towards)));
+    };
+    let key = state.key_for(&next_hop.to_string()).await;
+    echo.sign(&key.key)?;
+    let addr = SocketAddr::new(next_hop.into(), state.port);
+    crate::net_utils::send_message(&state.socket, &addr, &echo, &key, "[PROBE]").await
+}
+
+/// Traite un écho reçu : relayé s'il ne nous est pas destiné, réponse s'il s'agit d'une
+/// requête pour nous, remise à la sonde en attente s'il s'agit de sa réponse
+pub async fn handle_echo(state: &Arc<AppState>, echo: EchoMessage, local_ip: &str, src_addr: SocketAddr) {
+    if !echo.verify(&state.key_for(local_ip).await.key) {
+        warn!("Dropping echo {} from {} to {}: bad signature", echo.id, echo.source, echo.target);
+        state.metrics.probes_rejected.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+    let router_id = state.router_id.to_string();
+    let destination = if echo.reply { echo.source.clone() } else { echo.target.clone() };
+    if is_local(state, &destination) {
+        if echo.reply {
+            if !state.probes.lock().await.complete(echo.clone()) {
+                debug!("Late or unknown echo reply {} from {}", echo.id, echo.target);
+            }
+            return;
+        }
+        let mut reply = echo;
+        reply.path.push(router_id);
+        reply.reply = true;
+        reply.ttl = crate::INITIAL_TTL;
+        let source = reply.source.clone();
+        // Sonde de MTU (voir `path_mtu`) : réponse sans bourrage, renvoyée directement à
+        // l'émetteur, dont l'adjacence n'est peut-être pas encore établie de notre côté
+        if !reply.padding.is_empty() {
+            reply.padding.clear();
+            let key = state.key_for(&src_addr.ip().to_string()).await;
+            let sent = match reply.sign(&key.key) {
+                Ok(()) => crate::net_utils::send_message(&state.socket, &SocketAddr::new(src_addr.ip(), state.port), &reply, &key, "[MTU]").await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = sent {
+                warn!("Failed to answer MTU probe from {}: {}", source, e);
+            }
+            return;
+        }
+        if let Err(e) = forward(state, reply, &source).await {
+            warn!("Failed to answer echo from {}: {}", source, e);
+        }
+        return;
+    }
+    let mut echo = echo;
+    if echo.ttl <= 1 {
+        debug!("Echo {} from {} to {} expired", echo.id, echo.source, echo.target);
+        return;
+    }
+    echo.ttl -= 1;
+    if !echo.reply {
+        echo.path.push(router_id);
+    }
+    if let Err(e) = forward(state, echo, &destination).await {
+        debug!("Echo towards {} not relayed: {}", destination, e);
+    }
+}
+
+/// Commande `ping <routeur>` (ou `probe`) : écho authentifié vers le routeur, relayé par
+/// les routes installées ; rapporte le temps aller-retour et le chemin aller
+pub async fn ping(state: &Arc<AppState>, target: &str) -> String {
+    if target.parse::<Ipv4Addr>().is_err() {
+        return format!("Argument invalide: '{}' n'est pas un identifiant de routeur", target);
+    }
+    let router_id = state.router_id.to_string();
+    if is_local(state, target) {
+        return format!("{} est ce routeur", target);
+    }
+    let id = OsRng.next_u64();
+    let echo = EchoMessage {
+        message_type: 8,
+        id,
+        source: router_id.clone(),
+        target: target.to_string(),
+        reply: false,
+        path: vec![router_id],
+        ttl: crate::INITIAL_TTL,
+        signature: String::new(),
+        padding: String::new(),
+    };
+    let receiver = state.probes.lock().await.register(id);
+    let started = Instant::now();
+    if let Err(e) = forward(state, echo, target).await {
+        state.probes.lock().await.cancel(id);
+        return format!("Échec: {}", e);
+    }
+    match tokio::time::timeout(PROBE_TIMEOUT, receiver).await {
+        Ok(Ok(reply)) => {
+            let rtt = started.elapsed();
+            info!("[PROBE] Reply from {} in {:?} via {:?}", target, rtt, reply.path);
+            format!(
+                "Réponse de {} : temps={:.1} ms, {} sauts, chemin: {}",
+                target,
+                rtt.as_secs_f64() * 1000.0,
+                reply.path.len().saturating_sub(1),
+                reply.path.join(" -> "),
+            )
+        }
+        _ => {
+            state.probes.lock().await.cancel(id);
+            format!("Échec: pas de réponse de {} en {} s", target, PROBE_TIMEOUT.as_secs())
+        }
+    }
+}
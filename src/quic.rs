@@ -0,0 +1,155 @@
+use crate::transport::Stream;
+use crate::read_config::QuicConfig;
+use quinn::crypto::rustls::{QuicClientConfig, QuicServerConfig};
+use quinn::{AsyncUdpSocket, Connection, Endpoint, EndpointConfig, IdleTimeout, Incoming, RecvStream, SendStream, TokioRuntime, TransportConfig, VarInt};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::RootCertStore;
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+
+/// Les sessions QUIC utilisent le port UDP qui suit celui du protocole
+pub const PORT_OFFSET: u16 = 1;
+
+/// Port des sessions QUIC d'un routeur dont le protocole écoute sur `port` ; aucun au-delà
+/// du dernier port UDP
+pub fn session_port(port: u16) -> Option<u16> {
+    port.checked_add(PORT_OFFSET)
+}
+/// Nom que doit porter le certificat de chaque routeur
+const SERVER_NAME: &str = "pospf";
+const ALPN: &[u8] = b"pospf";
+/// Premier octet écrit par l'initiateur : QUIC n'annonce un flux à l'autre extrémité
+/// qu'à sa première donnée
+const PREAMBLE: u8 = 0x50;
+/// Paquet de maintien envoyé sur une session inactive, bien avant son expiration
+const KEEP_ALIVE: Duration = Duration::from_secs(5);
+const IDLE_TIMEOUT_MS: u32 = 15_000;
+
+/// Flux bidirectionnel d'une connexion QUIC ; la connexion se ferme avec lui
+#[derive(Debug)]
+struct QuicStream {
+    _connection: Connection,
+    send: SendStream,
+    recv: RecvStream,
+}
+
+impl AsyncRead for QuicStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.recv).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for QuicStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        AsyncWrite::poll_write(Pin::new(&mut self.send), cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.send).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.send).poll_shutdown(cx)
+    }
+}
+
+fn transport_config() -> Arc<TransportConfig> {
+    let mut transport = TransportConfig::default();
+    transport.keep_alive_interval(Some(KEEP_ALIVE));
+    transport.max_idle_timeout(Some(IdleTimeout::from(VarInt::from_u32(IDLE_TIMEOUT_MS))));
+    Arc::new(transport)
+}
+
+fn invalid(path: &Path, error: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{}: {}", path.display(), error))
+}
+
+/// Certificat du routeur (suivi de sa chaîne), clé privée et autorités de confiance,
+/// lus depuis les fichiers PEM de `[quic]`
+fn credentials(config: &QuicConfig) -> io::Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>, RootCertStore)> {
+    let (Some(cert), Some(key), Some(ca)) = (&config.cert, &config.key, &config.ca) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "quic.cert, quic.key and quic.ca are required"));
+    };
+    let chain = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| invalid(cert, e))?;
+    let private_key = PrivateKeyDer::from_pem_file(key).map_err(|e| invalid(key, e))?;
+    let mut roots = RootCertStore::empty();
+    for authority in CertificateDer::pem_file_iter(ca).map_err(|e| invalid(ca, e))? {
+        roots.add(authority.map_err(|e| invalid(ca, e))?).map_err(|e| invalid(ca, e))?;
+    }
+    if chain.is_empty() || roots.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "quic.cert and quic.ca must hold at least one certificate"));
+    }
+    Ok((chain, private_key, roots))
+}
+
+/// Point de connexion QUIC sur `socket`, en TLS mutuel : chaque extrémité présente le
+/// certificat de `[quic]` et n'accepte que celui d'un voisin signé par l'une des autorités
+/// de `quic.ca` et portant le nom `pospf`
+pub fn endpoint(socket: Arc<dyn AsyncUdpSocket>, config: &QuicConfig) -> io::Result<Endpoint> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let (chain, key, roots) = credentials(config)?;
+    let roots = Arc::new(roots);
+
+    let client_verifier = WebPkiClientVerifier::builder_with_provider(Arc::clone(&roots), Arc::clone(&provider))
+        .build()
+        .map_err(io::Error::other)?;
+    let mut server_crypto = rustls::ServerConfig::builder_with_provider(Arc::clone(&provider))
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .map_err(io::Error::other)?
+        .with_client_cert_verifier(client_verifier)
+        .with_single_cert(chain.clone(), key.clone_key())
+        .map_err(io::Error::other)?;
+    server_crypto.alpn_protocols = vec![ALPN.to_vec()];
+    let mut server = quinn::ServerConfig::with_crypto(Arc::new(QuicServerConfig::try_from(server_crypto).map_err(io::Error::other)?));
+    server.transport_config(transport_config());
+
+    let mut client_crypto = rustls::ClientConfig::builder_with_provider(provider)
+        .with_protocol_versions(&[&rustls::version::TLS13])
+        .map_err(io::Error::other)?
+        .with_root_certificates(roots)
+        .with_client_auth_cert(chain, key)
+        .map_err(io::Error::other)?;
+    client_crypto.alpn_protocols = vec![ALPN.to_vec()];
+    let mut client = quinn::ClientConfig::new(Arc::new(QuicClientConfig::try_from(client_crypto).map_err(io::Error::other)?));
+    client.transport_config(transport_config());
+
+    let mut endpoint = Endpoint::new_with_abstract_socket(EndpointConfig::default(), Some(server), socket, Arc::new(TokioRuntime))?;
+    endpoint.set_default_client_config(client);
+    Ok(endpoint)
+}
+
+/// Ouvre une session vers le routeur dont le protocole écoute sur `target`
+pub async fn connect(endpoint: &Endpoint, target: SocketAddr) -> io::Result<Box<dyn Stream>> {
+    let port = session_port(target.port())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("{} has no QUIC session port", target)))?;
+    let target = SocketAddr::new(target.ip(), port);
+    let connection = endpoint.connect(target, SERVER_NAME).map_err(io::Error::other)?.await?;
+    let (mut send, recv) = connection.open_bi().await?;
+    send.write_all(&[PREAMBLE]).await?;
+    Ok(Box::new(QuicStream { _connection: connection, send, recv }))
+}
+
+/// Termine l'établissement d'une session entrante ; l'adresse rendue est celle du
+/// protocole de l'appelant
+pub async fn open(incoming: Incoming) -> io::Result<(Box<dyn Stream>, SocketAddr)> {
+    let connection = incoming.await?;
+    let (send, mut recv) = connection.accept_bi().await?;
+    if recv.read_u8().await? != PREAMBLE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected QUIC stream preamble"));
+    }
+    let remote = connection.remote_address();
+    let port = remote.port().checked_sub(PORT_OFFSET)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a QUIC session port", remote)))?;
+    let caller = SocketAddr::new(remote.ip(), port);
+    Ok((Box::new(QuicStream { _connection: connection, send, recv }), caller))
+}
@@ -1,58 +1,2075 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
 use crate::error::{AppError, Result};
+use crate::types::{AuthMode, ExternalMetricType};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+pub const DEFAULT_HELLO_INTERVAL_SEC: u64 = 5;
+pub const DEFAULT_LSA_INTERVAL_SEC: u64 = 10;
+pub const DEFAULT_DEAD_INTERVAL_SEC: u64 = 22;
+pub const DEFAULT_RETRANSMIT_INTERVAL_SEC: u64 = 5;
+pub const DEFAULT_PORT: u16 = 5000;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct InterfaceConfig {
     pub name: String,
     pub capacity_mbps: u32,
     #[serde(default = "default_link_active")]
     pub link_active: bool,
+    /// Intervalle HELLO propre à l'interface (sinon valeur globale)
+    #[serde(default)]
+    pub hello_interval_sec: Option<u64>,
+    /// Délai d'expiration des voisins propre à l'interface (sinon valeur globale)
+    #[serde(default)]
+    pub dead_interval_sec: Option<u64>,
+    /// Lien vers une zone stub : seule une route par défaut y est annoncée
+    #[serde(default)]
+    pub stub: bool,
+    /// Coût statique du lien, prioritaire sur le coût dérivé de la capacité
+    #[serde(default)]
+    pub cost: Option<u32>,
+    /// Type de lien : diffusion (par défaut) ou point à point vers `peer`
+    #[serde(default)]
+    pub link_type: LinkType,
+    /// Adresse du routeur d'en face sur un lien point à point (HELLO et LSA en unicast)
+    #[serde(default)]
+    pub peer: Option<String>,
+    /// Liste de préfixes appliquée aux routes apprises par cette interface
+    #[serde(default)]
+    pub filter_in: Option<String>,
+    /// Liste de préfixes appliquée aux routes annoncées sur cette interface
+    #[serde(default)]
+    pub filter_out: Option<String>,
+    /// Protocole parlé sur l'interface : P-OSPF, ou OSPFv2 standard pour les routeurs tiers
+    #[serde(default)]
+    pub protocol: InterfaceProtocol,
+    /// Priorité pour l'élection du routeur désigné du segment (0 : jamais élu)
+    #[serde(default = "crate::types::default_router_priority")]
+    pub priority: u8,
+    /// Annonce le sous-réseau connecté de l'interface
+    #[serde(default = "default_redistribute_connected")]
+    pub redistribute_connected: bool,
+    /// Préfixes annoncés en plus, tant que l'interface est présente
+    #[serde(default)]
+    pub advertise: Vec<String>,
+    /// Étiquette des réseaux annoncés par l'interface (sous-réseau connecté et `advertise`)
+    #[serde(default)]
+    pub route_tag: Option<u32>,
+    /// Clé propre au segment de l'interface (base64, 32 octets), à la place de `key`
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Protection des messages du segment : "none", "aes" ou "hmac" ; à défaut, AES si
+    /// `encryption` est activé, en clair sinon
+    #[serde(default)]
+    pub auth: Option<AuthMode>,
+    /// VRF de l'interface, déclarée dans `vrfs` (table principale si absente)
+    #[serde(default)]
+    pub vrf: Option<String>,
+    /// Transport des LSA et de leurs acquittements vers les voisins de l'interface
+    #[serde(default)]
+    pub transport: LsaTransport,
+}
+
+/// Erreur d'une clé AES-256 encodée en base64, le cas échéant
+fn key_error(field: &str, key: &str) -> Option<String> {
+    use base64::Engine;
+    match base64::engine::general_purpose::STANDARD.decode(key) {
+        Ok(bytes) if bytes.len() == 32 => None,
+        Ok(bytes) => Some(format!("{} must decode to 32 bytes for AES-256, got {}", field, bytes.len())),
+        Err(e) => Some(format!("{} is not valid base64: {}", field, e)),
+    }
+}
+
+fn default_redistribute_connected() -> bool {
+    true
+}
+
+impl InterfaceConfig {
+    /// Interface active de capacité donnée, les autres champs à leur valeur par défaut
+    pub fn new(name: &str, capacity_mbps: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            capacity_mbps,
+            link_active: true,
+            hello_interval_sec: None,
+            dead_interval_sec: None,
+            stub: false,
+            cost: None,
+            link_type: LinkType::default(),
+            peer: None,
+            filter_in: None,
+            filter_out: None,
+            protocol: InterfaceProtocol::default(),
+            priority: crate::types::default_router_priority(),
+            redistribute_connected: true,
+            advertise: Vec::new(),
+            route_tag: None,
+            key: None,
+            auth: None,
+            vrf: None,
+            transport: LsaTransport::default(),
+        }
+    }
+
+    /// État initial de l'interface, modifiable ensuite à chaud
+    pub fn initial_state(&self) -> crate::types::InterfaceState {
+        crate::types::InterfaceState {
+            cost: self.cost,
+            ..crate::types::InterfaceState::new(self.name.clone(), self.capacity_mbps, self.link_active)
+        }
+    }
+
+    /// Adresse du voisin d'un lien point à point
+    pub fn point_to_point_peer(&self) -> Option<Ipv4Addr> {
+        match self.link_type {
+            LinkType::PointToPoint => self.peer.as_deref().and_then(|peer| peer.parse().ok()),
+            LinkType::Broadcast => None,
+        }
+    }
+}
+
+/// Action d'une entrée de liste de préfixes
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    Permit,
+    Deny,
+}
+
+/// Entrée d'une liste de préfixes : sans `ge`/`le`, seul le préfixe exact correspond ;
+/// sinon tout préfixe couvert dont la longueur est comprise entre `ge` et `le`. Avec `tag`,
+/// seules les routes portant cette étiquette correspondent ; une entrée sans préfixe
+/// correspond alors à toutes les routes de l'étiquette
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct PrefixListEntry {
+    pub action: FilterAction,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub ge: Option<u8>,
+    #[serde(default)]
+    pub le: Option<u8>,
+    #[serde(default)]
+    pub tag: Option<u32>,
+}
+
+/// Filtres appliqués aux routes échangées avec un voisin donné
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct NeighborFilterConfig {
+    pub neighbor: String,
+    #[serde(default)]
+    pub filter_in: Option<String>,
+    #[serde(default)]
+    pub filter_out: Option<String>,
+}
+
+/// Type de lien d'une interface
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkType {
+    /// Segment partagé : HELLO et LSA en broadcast ou multicast
+    #[default]
+    Broadcast,
+    /// Lien vers un seul voisin (tunnel GRE/WireGuard, lien sans diffusion) : unicast vers `peer`
+    PointToPoint,
+}
+
+/// Protocole d'une interface
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InterfaceProtocol {
+    #[default]
+    Pospf,
+    /// OSPFv2 (RFC 2328) sur socket brute, en point à point, dans le backbone
+    Ospfv2,
+    /// RIPv2 (RFC 2453), pour des routeurs RIP d'ancienne génération
+    Rip,
 }
 
 fn default_link_active() -> bool {
     true
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct RouterConfig {
+fn default_hello_interval() -> u64 {
+    DEFAULT_HELLO_INTERVAL_SEC
+}
+
+fn default_lsa_interval() -> u64 {
+    DEFAULT_LSA_INTERVAL_SEC
+}
+
+fn default_dead_interval() -> u64 {
+    DEFAULT_DEAD_INTERVAL_SEC
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+fn default_neighbor_auth() -> bool {
+    true
+}
+
+fn default_encryption() -> bool {
+    true
+}
+
+fn default_retransmit_interval() -> u64 {
+    DEFAULT_RETRANSMIT_INTERVAL_SEC
+}
+
+/// Mode d'émission des HELLO et LSA vers les voisins
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TransportMode {
+    /// Broadcast sur l'adresse de diffusion de chaque sous-réseau
+    #[default]
+    Broadcast,
+    /// Multicast sur 224.0.0.5 (AllSPFRouters), TTL 1, comme OSPF
+    Multicast,
+}
+
+/// Transport des LSA vers les voisins d'une interface ; les HELLO restent en UDP
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LsaTransport {
+    /// Datagrammes UDP, retransmis tant qu'ils ne sont pas acquittés
+    #[default]
+    Udp,
+    /// Session TCP avec chaque voisin dès qu'il est actif
+    Tcp,
+    /// UDP, puis une session TCP avec un voisin dès que des pertes sont constatées
+    Auto,
+    /// Session QUIC (TLS 1.3) avec chaque voisin dès qu'il est actif, sur le port UDP qui
+    /// suit celui du protocole
+    Quic,
+}
+
+/// Rôle du routeur dans le domaine
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RouterRole {
+    /// Routeur complet : LSDB de tout le domaine et route vers chaque préfixe
+    #[default]
+    Standard,
+    /// Routeur d'accès totalement stub : les LSA reçus ne sont ni gardés ni relayés, seule
+    /// une route par défaut vers le voisin le plus proche est installée
+    Access,
+}
+
+/// Origine (protocole noyau) des routes pouvant être redistribuées
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RouteSource {
+    /// Routes ajoutées par l'opérateur (`ip route add`, proto static)
+    Static,
+    /// Routes des réseaux directement connectés (proto kernel)
+    Kernel,
+    /// Routes installées au démarrage (proto boot, ex. DHCP minimal, Docker)
+    Boot,
+}
+
+/// Redistribution des routes du noyau dans les LSA sous forme de routes externes
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RedistributeConfig {
     #[serde(default)]
-    pub interfaces: Vec<InterfaceConfig>,
+    pub enabled: bool,
+    #[serde(default = "default_redistribute_sources")]
+    pub sources: Vec<RouteSource>,
+    /// Métrique annoncée pour les routes redistribuées
+    #[serde(default = "default_redistribute_metric")]
+    pub metric: u32,
+    /// Type de métrique annoncé : `"type-1"` (coût interne ajouté) ou `"type-2"`
     #[serde(default)]
-    pub key: Option<String>,
+    pub metric_type: ExternalMetricType,
+    /// Préfixes autorisés (tous si vide) ; un préfixe plus spécifique est couvert
+    #[serde(default)]
+    pub permit: Vec<String>,
+    /// Préfixes refusés, prioritaires sur `permit`
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Redistribuer aussi la route par défaut du noyau
+    #[serde(default)]
+    pub include_default: bool,
+    /// Étiquette des routes redistribuées
+    #[serde(default)]
+    pub tag: Option<u32>,
+    #[serde(default = "default_redistribute_refresh")]
+    pub refresh_interval_sec: u64,
 }
 
-/// Lit la configuration du routeur basée sur le hostname
-pub fn read_router_config() -> Result<RouterConfig> {
-    let hostname = get_hostname()?;
-    let config_path = format!("src/conf/config_{}.toml", hostname);
-    
-    if !Path::new(&config_path).exists() {
-        return Err(AppError::ConfigError(format!(
-            "Config file not found: {}. Available configs: {}",
-            config_path,
-            list_available_configs()
-        )));
-    }
-    
-    let config_content = fs::read_to_string(&config_path)
-        .map_err(|e| AppError::ConfigError(format!("Failed to read config file {}: {}", config_path, e)))?;
-    
-    let config: RouterConfig = toml::from_str(&config_content)
-        .map_err(|e| AppError::ConfigError(format!("Failed to parse config file {}: {}", config_path, e)))?;
-    
-    log::info!("Loaded configuration for router: {}", hostname);
-    log::debug!("Config: {:?}", config);
-    
+impl Default for RedistributeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sources: default_redistribute_sources(),
+            metric: default_redistribute_metric(),
+            metric_type: ExternalMetricType::default(),
+            permit: Vec::new(),
+            deny: Vec::new(),
+            include_default: false,
+            tag: None,
+            refresh_interval_sec: default_redistribute_refresh(),
+        }
+    }
+}
+
+fn default_redistribute_sources() -> Vec<RouteSource> {
+    vec![RouteSource::Static]
+}
+
+fn default_redistribute_metric() -> u32 {
+    20
+}
+
+fn default_redistribute_refresh() -> u64 {
+    30
+}
+
+/// Table principale du noyau Linux
+pub const MAIN_ROUTE_TABLE: u32 = 254;
+
+/// VRF (VRF-lite) : ses interfaces forment un domaine de routage séparé, avec sa propre
+/// instance du protocole, sa LSDB, sa table de routage et sa table du noyau
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct VrfConfig {
+    /// Nom de la VRF, qui est aussi celui de son périphérique Linux (`ip link add blue type vrf`)
+    pub name: String,
+    /// Identifiant de la table du noyau associée au périphérique
+    pub table: u32,
+}
+
+/// Marquage et préséance des routes installées dans le noyau
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RouteInstallConfig {
+    /// Installer les routes calculées dans le noyau ; désactivé pour un routeur simulé
+    #[serde(default = "default_route_install_enabled")]
+    pub enabled: bool,
+    /// Numéro rtm_protocol apposé sur nos routes (188 = ospf dans /etc/iproute2/rt_protos)
+    #[serde(default = "default_route_protocol")]
+    pub protocol: u8,
+    /// Distance administrative, utilisée comme métrique noyau : une route statique
+    /// de métrique inférieure reste prioritaire sur la route apprise
+    #[serde(default = "default_route_distance")]
+    pub distance: u32,
+    /// Ne jamais installer de route sur un préfixe déjà présent avec un autre protocole
+    #[serde(default = "default_protect_foreign")]
+    pub protect_foreign: bool,
+    /// Période de réconciliation de nos routes du noyau avec la table de routage (0 : désactivée)
+    #[serde(default = "default_reconcile_interval")]
+    pub reconcile_interval_sec: u64,
+    /// Lire la table du système mais n'y rien écrire : les modifications sont seulement
+    /// journalisées (aussi activé par `--dry-run` ; pris en compte au démarrage)
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Tentatives d'une opération de route avant abandon (visible dans `pending-routes`)
+    #[serde(default = "default_route_max_attempts")]
+    pub max_attempts: u32,
+    /// Attente avant la première nouvelle tentative, doublée ensuite
+    #[serde(default = "default_route_retry_initial")]
+    pub retry_initial_ms: u64,
+    #[serde(default = "default_route_retry_max")]
+    pub retry_max_ms: u64,
+    /// Table de routage du noyau recevant nos routes (254 = main) ; celle de sa VRF pour
+    /// une instance VRF
+    #[serde(default = "default_route_table")]
+    pub table: u32,
+}
+
+impl Default for RouteInstallConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_route_install_enabled(),
+            protocol: default_route_protocol(),
+            distance: default_route_distance(),
+            protect_foreign: default_protect_foreign(),
+            reconcile_interval_sec: default_reconcile_interval(),
+            dry_run: false,
+            max_attempts: default_route_max_attempts(),
+            retry_initial_ms: default_route_retry_initial(),
+            retry_max_ms: default_route_retry_max(),
+            table: default_route_table(),
+        }
+    }
+}
+
+fn default_route_table() -> u32 {
+    MAIN_ROUTE_TABLE
+}
+
+fn default_route_install_enabled() -> bool {
+    true
+}
+
+fn default_route_protocol() -> u8 {
+    188
+}
+
+fn default_route_distance() -> u32 {
+    110
+}
+
+fn default_protect_foreign() -> bool {
+    true
+}
+
+fn default_reconcile_interval() -> u64 {
+    30
+}
+
+fn default_route_max_attempts() -> u32 {
+    5
+}
+
+fn default_route_retry_initial() -> u64 {
+    500
+}
+
+fn default_route_retry_max() -> u64 {
+    30_000
+}
+
+/// Condition d'annonce de la route par défaut
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum DefaultOriginateCondition {
+    /// Toujours annoncer 0.0.0.0/0
+    #[default]
+    Always,
+    /// Seulement si le noyau possède une route par défaut (hors routes du démon)
+    DefaultRoute,
+    /// Seulement si un réseau d'accès 192.168.0.0/16 est connecté (topologie de démonstration)
+    AccessNetwork,
+}
+
+pub use pospf_core::dijkstra::PathSelection;
+pub use pospf_core::spf_engine::SpfEngineKind;
+
+/// Annonce de la route par défaut dans les LSA
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct OriginateDefaultConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_originate_metric")]
+    pub metric: u32,
+    /// Type de métrique de la route par défaut annoncée (E2 par défaut)
+    #[serde(default)]
+    pub metric_type: ExternalMetricType,
+    #[serde(default)]
+    pub condition: DefaultOriginateCondition,
+}
+
+impl Default for OriginateDefaultConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            metric: default_originate_metric(),
+            metric_type: ExternalMetricType::default(),
+            condition: DefaultOriginateCondition::default(),
+        }
+    }
+}
+
+fn default_originate_metric() -> u32 {
+    20
+}
+
+/// Serveur HTTP de supervision (API JSON et tableau de bord)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct HttpConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Adresse d'écoute du serveur HTTP
+    #[serde(default = "default_http_listen")]
+    pub listen: String,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: default_http_listen(),
+        }
+    }
+}
+
+fn default_http_listen() -> String {
+    "0.0.0.0:8080".to_string()
+}
+
+/// Service gRPC de télémétrie (voir proto/telemetry.proto)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct GrpcConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_grpc_listen")]
+    pub listen: String,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: default_grpc_listen(),
+        }
+    }
+}
+
+fn default_grpc_listen() -> String {
+    "0.0.0.0:50051".to_string()
+}
+
+/// Temporisation du SPF : délai initial, puis attente doublée à chaque recalcul rapproché
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SpfThrottleConfig {
+    #[serde(default = "default_spf_initial_delay")]
+    pub initial_delay_ms: u64,
+    #[serde(default = "default_spf_hold")]
+    pub hold_ms: u64,
+    #[serde(default = "default_spf_max_hold")]
+    pub max_hold_ms: u64,
+}
+
+impl Default for SpfThrottleConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay_ms: default_spf_initial_delay(),
+            hold_ms: default_spf_hold(),
+            max_hold_ms: default_spf_max_hold(),
+        }
+    }
+}
+
+fn default_spf_initial_delay() -> u64 {
+    50
+}
+
+fn default_spf_hold() -> u64 {
+    200
+}
+
+fn default_spf_max_hold() -> u64 {
+    5000
+}
+
+/// Intervalles minimaux entre deux instances de LSA d'un même originateur
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct LsaThrottleConfig {
+    /// Délai minimal entre deux LSA acceptés d'un même originateur
+    #[serde(default = "default_min_lsa_arrival")]
+    pub min_arrival_ms: u64,
+    /// Délai minimal entre deux LSA déclenchés émis sur une même interface
+    #[serde(default = "default_min_lsa_origination")]
+    pub min_origination_ms: u64,
+}
+
+impl Default for LsaThrottleConfig {
+    fn default() -> Self {
+        Self {
+            min_arrival_ms: default_min_lsa_arrival(),
+            min_origination_ms: default_min_lsa_origination(),
+        }
+    }
+}
+
+fn default_min_lsa_arrival() -> u64 {
+    1000
+}
+
+fn default_min_lsa_origination() -> u64 {
+    5000
+}
+
+/// LSA différentiels : entre deux instances complètes, seuls les préfixes ajoutés,
+/// modifiés ou retirés sont annoncés. Tous les routeurs du réseau doivent le comprendre.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct LsaDeltaConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Intervalle maximal entre deux LSA complets sur une interface
+    #[serde(default = "default_full_refresh")]
+    pub full_refresh_sec: u64,
+}
+
+impl Default for LsaDeltaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            full_refresh_sec: default_full_refresh(),
+        }
+    }
+}
+
+fn default_full_refresh() -> u64 {
+    300
+}
+
+/// Retenue des routes annoncées injoignables : pendant `duration_sec`, le SPF ignore les
+/// annonces actives moins bonnes que la route perdue
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct HoldDownConfig {
+    #[serde(default = "default_hold_down_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_hold_down_duration")]
+    pub duration_sec: u64,
+}
+
+impl Default for HoldDownConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_hold_down_enabled(),
+            duration_sec: default_hold_down_duration(),
+        }
+    }
+}
+
+fn default_hold_down_enabled() -> bool {
+    true
+}
+
+fn default_hold_down_duration() -> u64 {
+    10
+}
+
+/// Seuils d'installation : une route calculée dont le chemin ne les respecte pas n'est pas
+/// installée (voir la commande `filtered-routes`)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct InstallPolicyConfig {
+    /// Capacité minimale du lien le plus faible du chemin
+    #[serde(default)]
+    pub min_bottleneck_mbps: Option<u32>,
+    /// Nombre maximal de sauts jusqu'au routeur annonçant le préfixe
+    #[serde(default)]
+    pub max_hop_count: Option<u32>,
+}
+
+/// HELLO adaptatifs : un lien stable espace peu à peu ses HELLO, un lien qui vient de
+/// basculer les rapproche
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct AdaptiveHelloConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Intervalle des HELLO sur une interface dont un voisin a basculé récemment
+    #[serde(default = "default_fast_hello_interval")]
+    pub fast_interval_sec: u64,
+    /// Intervalle maximal, au plus le tiers du délai d'expiration des voisins
+    #[serde(default = "default_max_hello_interval")]
+    pub max_interval_sec: u64,
+    /// Durée de stabilité au bout de laquelle l'intervalle double
+    #[serde(default = "default_hello_stable_after")]
+    pub stable_after_sec: u64,
+    /// Durée après un changement d'état pendant laquelle le lien reste en HELLO rapides
+    #[serde(default = "default_hello_flap_window")]
+    pub flap_window_sec: u64,
+}
+
+impl Default for AdaptiveHelloConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fast_interval_sec: default_fast_hello_interval(),
+            max_interval_sec: default_max_hello_interval(),
+            stable_after_sec: default_hello_stable_after(),
+            flap_window_sec: default_hello_flap_window(),
+        }
+    }
+}
+
+fn default_fast_hello_interval() -> u64 {
+    1
+}
+
+fn default_max_hello_interval() -> u64 {
+    DEFAULT_DEAD_INTERVAL_SEC / 3
+}
+
+fn default_hello_stable_after() -> u64 {
+    60
+}
+
+fn default_hello_flap_window() -> u64 {
+    60
+}
+
+/// Voisin joint en unicast au-delà d'un saut IP, que nos HELLO diffusés n'atteignent pas
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct StaticNeighborConfig {
+    pub address: Ipv4Addr,
+    /// Port du voisin (point d'accès public d'un overlay), à défaut celui du protocole
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Interface locale à laquelle les échanges avec ce voisin sont rattachés
+    pub interface: String,
+}
+
+/// Overlay unicast : adjacences uniquement avec les `static_neighbors`, sans diffusion,
+/// les HELLO servant aussi à maintenir ouvertes les associations NAT
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct OverlayConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Intervalle maximal entre deux HELLO vers un voisin, sous le délai d'expiration
+    /// des associations UDP des NAT traversés
+    #[serde(default = "default_overlay_keepalive")]
+    pub keepalive_sec: u64,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keepalive_sec: default_overlay_keepalive(),
+        }
+    }
+}
+
+fn default_overlay_keepalive() -> u64 {
+    25
+}
+
+/// Découverte des voisins par mDNS (service `_pospf._udp.local`) : les routeurs annoncés
+/// hors de nos sous-réseaux, par un relais mDNS par exemple, sont joints en unicast
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct MdnsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Intervalle entre deux requêtes ; un voisin découvert expire après trois intervalles
+    #[serde(default = "default_mdns_interval")]
+    pub interval_sec: u64,
+}
+
+impl Default for MdnsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_sec: default_mdns_interval(),
+        }
+    }
+}
+
+fn default_mdns_interval() -> u64 {
+    60
+}
+
+/// Passerelle RIPv2 des interfaces configurées avec `protocol = "rip"`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RipConfig {
+    /// Coût P-OSPF d'un saut RIP : une route de n sauts coûte n fois cette valeur, et une
+    /// route P-OSPF est annoncée en RIP avec son coût divisé par cette valeur (15 sauts au plus)
+    #[serde(default = "default_rip_cost_per_hop")]
+    pub cost_per_hop: u32,
+    #[serde(default = "default_rip_update_interval")]
+    pub update_interval_sec: u64,
+    /// Délai sans annonce au-delà duquel une route RIP est déclarée injoignable
+    #[serde(default = "default_rip_timeout")]
+    pub timeout_sec: u64,
+    /// Délai pendant lequel une route injoignable est encore annoncée avant d'être oubliée
+    #[serde(default = "default_rip_garbage")]
+    pub garbage_sec: u64,
+}
+
+impl Default for RipConfig {
+    fn default() -> Self {
+        Self {
+            cost_per_hop: default_rip_cost_per_hop(),
+            update_interval_sec: default_rip_update_interval(),
+            timeout_sec: default_rip_timeout(),
+            garbage_sec: default_rip_garbage(),
+        }
+    }
+}
+
+fn default_rip_cost_per_hop() -> u32 {
+    10
+}
+
+fn default_rip_update_interval() -> u64 {
+    30
+}
+
+fn default_rip_timeout() -> u64 {
+    180
+}
+
+fn default_rip_garbage() -> u64 {
+    120
+}
+
+/// Passerelle vers un domaine externe : sessions TCP simplifiées, à la manière d'eBGP,
+/// avec des routeurs d'autres systèmes autonomes
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct BgpConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Numéro de système autonome de notre domaine
+    #[serde(default)]
+    pub local_as: u32,
+    /// Adresse d'écoute des sessions ouvertes par les pairs passifs de notre côté
+    #[serde(default)]
+    pub listen: Option<String>,
+    #[serde(default)]
+    pub peers: Vec<BgpPeerConfig>,
+    /// Préfixes de notre domaine annoncés aux pairs, s'ils sont joignables
+    #[serde(default)]
+    pub export: Vec<String>,
+    /// Métrique des routes importées, augmentée de la longueur du chemin d'AS
+    #[serde(default = "default_redistribute_metric")]
+    pub metric: u32,
+    #[serde(default)]
+    pub metric_type: ExternalMetricType,
+    /// Délai sans message au-delà duquel une session est fermée ; KEEPALIVE au tiers
+    #[serde(default = "default_bgp_hold_time")]
+    pub hold_time_sec: u64,
+    /// Délai entre deux tentatives de connexion vers un pair
+    #[serde(default = "default_bgp_connect_retry")]
+    pub connect_retry_sec: u64,
+}
+
+impl Default for BgpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            local_as: 0,
+            listen: None,
+            peers: Vec::new(),
+            export: Vec::new(),
+            metric: default_redistribute_metric(),
+            metric_type: ExternalMetricType::default(),
+            hold_time_sec: default_bgp_hold_time(),
+            connect_retry_sec: default_bgp_connect_retry(),
+        }
+    }
+}
+
+fn default_bgp_hold_time() -> u64 {
+    90
+}
+
+fn default_bgp_connect_retry() -> u64 {
+    30
+}
+
+/// Pair externe d'une session de la passerelle
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct BgpPeerConfig {
+    /// Adresse et port TCP du pair
+    pub address: String,
+    pub remote_as: u32,
+    /// Attendre la connexion du pair sur `listen` au lieu de l'établir
+    #[serde(default)]
+    pub passive: bool,
+}
+
+/// Réception : une tâche lit la socket et répartit les datagrammes entre des tâches de
+/// traitement, chacune alimentée par une file bornée
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ReceiveConfig {
+    /// Nombre de tâches de traitement (HELLO, LSA, commandes)
+    #[serde(default = "default_receive_workers")]
+    pub workers: usize,
+    /// Datagrammes en attente par tâche au-delà desquels les suivants sont abandonnés
+    #[serde(default = "default_receive_queue_depth")]
+    pub queue_depth: usize,
+}
+
+impl Default for ReceiveConfig {
+    fn default() -> Self {
+        Self {
+            workers: default_receive_workers(),
+            queue_depth: default_receive_queue_depth(),
+        }
+    }
+}
+
+fn default_receive_workers() -> usize {
+    4
+}
+
+fn default_receive_queue_depth() -> usize {
+    256
+}
+
+/// Priorité du trafic protocolaire sur les liens congestionnés
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct QosConfig {
+    /// Valeur DSCP des datagrammes émis (CS6 par défaut, 0 pour ne pas marquer)
+    #[serde(default = "default_protocol_dscp")]
+    pub dscp: u8,
+}
+
+impl Default for QosConfig {
+    fn default() -> Self {
+        Self { dscp: default_protocol_dscp() }
+    }
+}
+
+fn default_protocol_dscp() -> u8 {
+    48
+}
+
+/// Amortissement exponentiel des voisins instables (pénalité par bascule, demi-vie)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct FlapDampingConfig {
+    #[serde(default = "default_flap_damping_enabled")]
+    pub enabled: bool,
+    /// Pénalité ajoutée à chaque changement d'état d'un voisin
+    #[serde(default = "default_flap_penalty")]
+    pub penalty: u32,
+    /// Au-delà de ce seuil, le lien est ignoré par le SPF et les LSA
+    #[serde(default = "default_flap_suppress_threshold")]
+    pub suppress_threshold: u32,
+    /// Le lien est réutilisé lorsque la pénalité redescend sous ce seuil
+    #[serde(default = "default_flap_reuse_threshold")]
+    pub reuse_threshold: u32,
+    #[serde(default = "default_flap_half_life")]
+    pub half_life_sec: u64,
+    /// Durée maximale de suppression, qui plafonne la pénalité
+    #[serde(default = "default_flap_max_suppress")]
+    pub max_suppress_sec: u64,
+    /// Quarantaine : les HELLO d'un voisin supprimé sont ignorés, son adjacence expire
+    #[serde(default)]
+    pub quarantine: bool,
+}
+
+impl Default for FlapDampingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_flap_damping_enabled(),
+            penalty: default_flap_penalty(),
+            suppress_threshold: default_flap_suppress_threshold(),
+            reuse_threshold: default_flap_reuse_threshold(),
+            half_life_sec: default_flap_half_life(),
+            max_suppress_sec: default_flap_max_suppress(),
+            quarantine: false,
+        }
+    }
+}
+
+fn default_flap_damping_enabled() -> bool {
+    true
+}
+
+fn default_flap_penalty() -> u32 {
+    1000
+}
+
+fn default_flap_suppress_threshold() -> u32 {
+    2000
+}
+
+fn default_flap_reuse_threshold() -> u32 {
+    750
+}
+
+fn default_flap_half_life() -> u64 {
+    15
+}
+
+fn default_flap_max_suppress() -> u64 {
+    60
+}
+
+/// Droits d'un utilisateur du canal de contrôle
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum ControlRole {
+    /// Consultation uniquement (tables, voisins, compteurs)
+    ReadOnly,
+    /// Toutes les commandes, y compris enable/disable et reload-config
+    Admin,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub struct ControlUser {
+    pub name: String,
+    /// Secret partagé avec le CLI, clé HMAC des commandes signées
+    pub token: String,
+    pub role: ControlRole,
+}
+
+/// Le jeton n'apparaît jamais dans les journaux
+impl std::fmt::Debug for ControlUser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ControlUser")
+            .field("name", &self.name)
+            .field("token", &"<redacted>")
+            .field("role", &self.role)
+            .finish()
+    }
+}
+
+/// Authentification et journal d'audit des commandes de contrôle
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct ControlConfig {
+    #[serde(default)]
+    pub users: Vec<ControlUser>,
+    /// Droits d'une commande non signée ; par défaut consultation seule sans utilisateur
+    /// déclaré, refusée sinon. Les droits administrateur ne s'obtiennent qu'explicitement
+    /// (`anonymous = "admin"`)
+    #[serde(default)]
+    pub anonymous: Option<ControlRole>,
+    /// Fichier JSON lignes recevant chaque commande exécutée ou refusée
+    #[serde(default)]
+    pub audit_log: Option<PathBuf>,
+    /// Socket Unix de contrôle local (Unix uniquement), sans clé ni exposition réseau
+    #[serde(default)]
+    pub socket: Option<PathBuf>,
+    /// Groupe (nom ou numéro) propriétaire de la socket locale, créée en mode 0660 : seuls
+    /// son propriétaire, root et les membres de ce groupe peuvent s'y connecter
+    #[serde(default)]
+    pub socket_group: Option<String>,
+}
+
+impl ControlConfig {
+    pub fn anonymous_role(&self) -> Option<ControlRole> {
+        match self.anonymous {
+            Some(role) => Some(role),
+            None if self.users.is_empty() => Some(ControlRole::ReadOnly),
+            None => None,
+        }
+    }
+
+    pub fn user(&self, name: &str) -> Option<&ControlUser> {
+        self.users.iter().find(|user| user.name == name)
+    }
+}
+
+/// Capture des messages protocolaires (commande `trace`)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct TraceConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Nombre de messages conservés dans le tampon circulaire
+    #[serde(default = "default_trace_capacity")]
+    pub capacity: usize,
+}
+
+impl Default for TraceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            capacity: default_trace_capacity(),
+        }
+    }
+}
+
+fn default_trace_capacity() -> usize {
+    crate::trace::DEFAULT_TRACE_CAPACITY
+}
+
+/// Journal des changements de la LSDB et de la table de routage (commande `history`)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct HistoryConfig {
+    /// Fichier du journal, une entrée JSON par ligne ; sans fichier, rien n'est journalisé
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Taille au-delà de laquelle le journal passe dans `<fichier>.1`
+    #[serde(default = "default_history_max_bytes")]
+    pub max_bytes: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            max_bytes: default_history_max_bytes(),
+        }
+    }
+}
+
+fn default_history_max_bytes() -> u64 {
+    crate::history::DEFAULT_HISTORY_MAX_BYTES
+}
+
+/// Certificats des sessions QUIC (interfaces `transport = "quic"`), en PEM
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct QuicConfig {
+    /// Certificat du routeur, suivi des certificats intermédiaires
+    #[serde(default)]
+    pub cert: Option<PathBuf>,
+    /// Clé privée du certificat
+    #[serde(default)]
+    pub key: Option<PathBuf>,
+    /// Autorités dont doit dépendre le certificat d'un voisin
+    #[serde(default)]
+    pub ca: Option<PathBuf>,
+}
+
+/// Mesure de la MTU du chemin vers chaque voisin (commande `mtu`)
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct MtuDiscoveryConfig {
+    /// Sans mesure, les messages sont découpés pour un lien Ethernet (1500 octets)
+    #[serde(default = "default_mtu_discovery_enabled")]
+    pub enabled: bool,
+    /// Intervalle entre deux mesures vers un même voisin
+    #[serde(default = "default_mtu_discovery_interval")]
+    pub interval_sec: u64,
+}
+
+impl Default for MtuDiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_mtu_discovery_enabled(),
+            interval_sec: default_mtu_discovery_interval(),
+        }
+    }
+}
+
+fn default_mtu_discovery_enabled() -> bool {
+    true
+}
+
+fn default_mtu_discovery_interval() -> u64 {
+    600
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RouterConfig {
+    #[serde(default)]
+    pub interfaces: Vec<InterfaceConfig>,
+    /// Nom annoncé aux voisins (par défaut, nom système)
+    #[serde(default)]
+    pub hostname: Option<String>,
+    /// Identifiant du routeur en notation pointée (par défaut, la plus grande adresse de ses interfaces)
+    #[serde(default)]
+    pub router_id: Option<crate::types::RouterId>,
+    #[serde(default)]
+    pub role: RouterRole,
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Chiffrement AES-256 des messages ; `false` pour échanger en clair (laboratoire)
+    #[serde(default = "default_encryption")]
+    pub encryption: bool,
+    /// Défi/réponse signé avec la clé avant d'accepter un nouveau voisin
+    #[serde(default = "default_neighbor_auth")]
+    pub neighbor_auth: bool,
+    #[serde(default)]
+    pub transport: TransportMode,
+    /// Port UDP du protocole, identique sur tous les routeurs
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default = "default_hello_interval")]
+    pub hello_interval_sec: u64,
+    #[serde(default = "default_lsa_interval")]
+    pub lsa_interval_sec: u64,
+    #[serde(default = "default_dead_interval")]
+    pub dead_interval_sec: u64,
+    /// Délai avant de réémettre un LSA relayé non acquitté par un voisin
+    #[serde(default = "default_retransmit_interval")]
+    pub retransmit_interval_sec: u64,
+    #[serde(default)]
+    pub redistribute: RedistributeConfig,
+    #[serde(default)]
+    pub originate_default: OriginateDefaultConfig,
+    #[serde(default)]
+    pub path_selection: PathSelection,
+    /// Implémentation du calcul SPF : `"native"` ou `"petgraph"`
+    #[serde(default)]
+    pub spf_engine: SpfEngineKind,
+    #[serde(default)]
+    pub route_install: RouteInstallConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    #[serde(default)]
+    pub grpc: GrpcConfig,
+    #[serde(default)]
+    pub trace: TraceConfig,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub mtu_discovery: MtuDiscoveryConfig,
+    #[serde(default)]
+    pub quic: QuicConfig,
+    #[serde(default)]
+    pub control: ControlConfig,
+    #[serde(default)]
+    pub spf_throttle: SpfThrottleConfig,
+    #[serde(default)]
+    pub lsa_throttle: LsaThrottleConfig,
+    #[serde(default)]
+    pub flap_damping: FlapDampingConfig,
+    #[serde(default)]
+    pub lsa_delta: LsaDeltaConfig,
+    #[serde(default)]
+    pub hold_down: HoldDownConfig,
+    #[serde(default)]
+    pub install_policy: InstallPolicyConfig,
+    #[serde(default)]
+    pub adaptive_hello: AdaptiveHelloConfig,
+    #[serde(default)]
+    pub receive: ReceiveConfig,
+    #[serde(default)]
+    pub qos: QosConfig,
+    #[serde(default)]
+    pub bgp: BgpConfig,
+    /// Voisins joints en unicast, en plus de ceux qui répondent à nos HELLO diffusés
+    #[serde(default)]
+    pub static_neighbors: Vec<StaticNeighborConfig>,
+    #[serde(default)]
+    pub mdns: MdnsConfig,
+    #[serde(default)]
+    pub overlay: OverlayConfig,
+    #[serde(default)]
+    pub rip: RipConfig,
+    /// TLV opaques ajoutés à nos LSA routeur, pour les extensions applicatives
+    #[serde(default)]
+    pub opaque: Vec<crate::types::OpaqueTlv>,
+    /// Métrique annoncée pour certains réseaux de ce routeur (connectés ou `advertise`), par
+    /// préfixe, à la place du coût de leur interface ; les clés sont lues comme préfixes au
+    /// chargement
+    #[serde(default)]
+    pub network_metrics: BTreeMap<crate::types::Prefix, u32>,
+    /// Listes de préfixes nommées, référencées par `filter_in` / `filter_out`
+    #[serde(default)]
+    pub prefix_lists: HashMap<String, Vec<PrefixListEntry>>,
+    #[serde(default)]
+    pub neighbor_filters: Vec<NeighborFilterConfig>,
+    /// Fichier conservant l'état administratif (enable/disable, set-link, set-cost) entre deux démarrages
+    #[serde(default)]
+    pub state_file: Option<PathBuf>,
+    /// Espace de noms réseau Linux du routeur : nom créé par `ip netns add` ou chemin absolu
+    #[serde(default)]
+    pub netns: Option<String>,
+    /// VRF auxquelles des interfaces sont rattachées par leur option `vrf`
+    #[serde(default)]
+    pub vrfs: Vec<VrfConfig>,
+}
+
+impl Default for RouterConfig {
+    fn default() -> Self {
+        Self {
+            interfaces: Vec::new(),
+            hostname: None,
+            router_id: None,
+            role: RouterRole::default(),
+            key: None,
+            encryption: default_encryption(),
+            neighbor_auth: default_neighbor_auth(),
+            transport: TransportMode::default(),
+            port: default_port(),
+            hello_interval_sec: default_hello_interval(),
+            lsa_interval_sec: default_lsa_interval(),
+            dead_interval_sec: default_dead_interval(),
+            retransmit_interval_sec: default_retransmit_interval(),
+            redistribute: RedistributeConfig::default(),
+            originate_default: OriginateDefaultConfig::default(),
+            path_selection: PathSelection::default(),
+            spf_engine: SpfEngineKind::default(),
+            route_install: RouteInstallConfig::default(),
+            http: HttpConfig::default(),
+            grpc: GrpcConfig::default(),
+            trace: TraceConfig::default(),
+            history: HistoryConfig::default(),
+            mtu_discovery: MtuDiscoveryConfig::default(),
+            quic: QuicConfig::default(),
+            control: ControlConfig::default(),
+            spf_throttle: SpfThrottleConfig::default(),
+            lsa_throttle: LsaThrottleConfig::default(),
+            flap_damping: FlapDampingConfig::default(),
+            lsa_delta: LsaDeltaConfig::default(),
+            hold_down: HoldDownConfig::default(),
+            install_policy: InstallPolicyConfig::default(),
+            adaptive_hello: AdaptiveHelloConfig::default(),
+            receive: ReceiveConfig::default(),
+            qos: QosConfig::default(),
+            bgp: BgpConfig::default(),
+            static_neighbors: Vec::new(),
+            mdns: MdnsConfig::default(),
+            overlay: OverlayConfig::default(),
+            rip: RipConfig::default(),
+            opaque: Vec::new(),
+            network_metrics: BTreeMap::new(),
+            prefix_lists: HashMap::new(),
+            neighbor_filters: Vec::new(),
+            state_file: None,
+            netns: None,
+            vrfs: Vec::new(),
+        }
+    }
+}
+
+impl RouterConfig {
+    /// Clé de chiffrement décodée depuis le base64 (vide si le chiffrement est désactivé)
+    pub fn decoded_key(&self) -> Vec<u8> {
+        use base64::Engine;
+
+        if !self.encryption {
+            return Vec::new();
+        }
+        self.key
+            .as_ref()
+            .and_then(|k| base64::engine::general_purpose::STANDARD.decode(k).ok())
+            .unwrap_or_default()
+    }
+
+    /// Configuration de l'instance de la VRF `vrf` (l'instance principale pour `None`) :
+    /// ses seules interfaces, et pour une VRF sa table du noyau, un fichier d'état et un journal propres ;
+    /// les services du démon (HTTP, gRPC, socket de contrôle, mDNS, sessions externes) restent
+    /// à l'instance principale
+    pub fn for_vrf(&self, vrf: Option<&str>) -> RouterConfig {
+        let mut config = self.clone();
+        config.interfaces.retain(|iface| iface.vrf.as_deref() == vrf);
+        config.static_neighbors.retain(|neighbor| config.interfaces.iter().any(|iface| iface.name == neighbor.interface));
+        let Some(vrf) = vrf.and_then(|name| self.vrfs.iter().find(|v| v.name == name)) else {
+            return config;
+        };
+        config.route_install.table = vrf.table;
+        config.http.enabled = false;
+        config.grpc.enabled = false;
+        config.control.socket = None;
+        config.mdns.enabled = false;
+        config.bgp.enabled = false;
+        let suffixed = |path: &PathBuf| {
+            let mut name = path.file_name().unwrap_or_default().to_os_string();
+            name.push(format!(".{}", vrf.name));
+            path.with_file_name(name)
+        };
+        config.state_file = self.state_file.as_ref().map(suffixed);
+        config.history.path = self.history.path.as_ref().map(suffixed);
+        config
+    }
+
+    /// Protection des messages sur une interface : son mode `auth`, à défaut celui découlant
+    /// de `encryption`
+    pub fn auth_for(&self, interface: Option<&str>) -> AuthMode {
+        interface
+            .and_then(|name| self.interface(name))
+            .and_then(|iface| iface.auth)
+            .unwrap_or(if self.encryption { AuthMode::Aes } else { AuthMode::None })
+    }
+
+    /// Protection propre aux interfaces déclarant une clé ou un mode `auth` ; les autres
+    /// utilisent la clé globale
+    pub fn interface_keys(&self) -> HashMap<String, crate::net_utils::SegmentKey> {
+        use base64::Engine;
+
+        self.interfaces.iter()
+            .filter(|iface| iface.auth.is_some() || (self.encryption && iface.key.is_some()))
+            .map(|iface| {
+                let auth = self.auth_for(Some(&iface.name));
+                let key = match auth {
+                    AuthMode::None => Vec::new(),
+                    _ => iface.key.as_ref().or(self.key.as_ref())
+                        .and_then(|k| base64::engine::general_purpose::STANDARD.decode(k).ok())
+                        .unwrap_or_default(),
+                };
+                (iface.name.clone(), crate::net_utils::SegmentKey { auth, key })
+            })
+            .collect()
+    }
+
+    /// Indique si une interface participe au protocole (toutes si aucune n'est configurée)
+    pub fn is_interface_configured(&self, name: &str) -> bool {
+        self.interfaces.is_empty() || self.interface(name).is_some()
+    }
+
+    /// Configuration d'une interface nommée
+    pub fn interface(&self, name: &str) -> Option<&InterfaceConfig> {
+        self.interfaces.iter().find(|iface| iface.name == name)
+    }
+
+    /// Métrique imposée par `network_metrics` pour un réseau annoncé : même adresse de
+    /// réseau (bits d'hôte ignorés) et même longueur
+    pub fn network_metric(&self, prefix: &crate::types::Prefix) -> Option<u32> {
+        let network = prefix.network();
+        self.network_metrics.iter()
+            .find(|(candidate, _)| {
+                let candidate = candidate.network();
+                candidate.network() == network.network() && candidate.prefix() == network.prefix()
+            })
+            .map(|(_, metric)| *metric)
+    }
+
+    /// Intervalle HELLO effectif pour une interface
+    pub fn hello_interval_for(&self, interface: Option<&str>) -> u64 {
+        interface
+            .and_then(|name| self.interface(name))
+            .and_then(|iface| iface.hello_interval_sec)
+            .unwrap_or(self.hello_interval_sec)
+    }
+
+    /// Délai d'expiration effectif des voisins vus sur une interface
+    pub fn dead_interval_for(&self, interface: Option<&str>) -> u64 {
+        interface
+            .and_then(|name| self.interface(name))
+            .and_then(|iface| iface.dead_interval_sec)
+            .unwrap_or(self.dead_interval_sec)
+    }
+
+    /// Priorité d'élection du routeur désigné sur une interface
+    pub fn priority_for(&self, interface: Option<&str>) -> u8 {
+        interface
+            .and_then(|name| self.interface(name))
+            .map_or_else(crate::types::default_router_priority, |iface| iface.priority)
+    }
+
+    /// Coût statique configuré pour une interface
+    pub fn cost_for(&self, interface: Option<&str>) -> Option<u32> {
+        interface
+            .and_then(|name| self.interface(name))
+            .and_then(|iface| iface.cost)
+    }
+
+    /// Protocole parlé sur une interface (P-OSPF si elle n'est pas configurée)
+    pub fn interface_protocol(&self, interface: Option<&str>) -> InterfaceProtocol {
+        interface
+            .and_then(|name| self.interface(name))
+            .map(|iface| iface.protocol)
+            .unwrap_or_default()
+    }
+
+    /// Interface parlant OSPFv2 plutôt que P-OSPF
+    pub fn is_ospfv2_interface(&self, interface: Option<&str>) -> bool {
+        self.interface_protocol(interface) == InterfaceProtocol::Ospfv2
+    }
+
+    /// Voisin configuré d'une interface point à point
+    pub fn point_to_point_peer(&self, interface: Option<&str>) -> Option<Ipv4Addr> {
+        interface
+            .and_then(|name| self.interface(name))
+            .and_then(InterfaceConfig::point_to_point_peer)
+    }
+
+    /// Interface point à point dont le voisin configuré est `peer`
+    pub fn point_to_point_interface(&self, peer: Ipv4Addr) -> Option<&InterfaceConfig> {
+        self.interfaces.iter().find(|iface| iface.point_to_point_peer() == Some(peer))
+    }
+
+    /// L'interface mène-t-elle à une zone stub ?
+    pub fn is_stub_interface(&self, interface: Option<&str>) -> bool {
+        interface
+            .and_then(|name| self.interface(name))
+            .is_some_and(|iface| iface.stub)
+    }
+
+    /// Routeur de bordure : au moins une interface stub et une interface de backbone
+    pub fn is_stub_border(&self) -> bool {
+        self.interfaces.iter().any(|iface| iface.stub) && self.interfaces.iter().any(|iface| !iface.stub)
+    }
+
+    /// Routeur interne à une zone stub (toutes ses interfaces sont stub)
+    pub fn is_stub_internal(&self) -> bool {
+        !self.interfaces.is_empty() && self.interfaces.iter().all(|iface| iface.stub)
+    }
+
+    /// Plus petit délai d'expiration configuré, pour cadencer la vérification des voisins
+    pub fn min_dead_interval(&self) -> u64 {
+        self.interfaces.iter()
+            .filter_map(|iface| iface.dead_interval_sec)
+            .fold(self.dead_interval_sec, u64::min)
+    }
+
+    /// Plus grand délai d'expiration configuré : au-delà, tout voisin muet a disparu
+    pub fn max_dead_interval(&self) -> u64 {
+        self.interfaces.iter()
+            .filter_map(|iface| iface.dead_interval_sec)
+            .fold(self.dead_interval_sec, u64::max)
+    }
+
+    /// Vérifie la configuration et renvoie la liste de toutes les erreurs trouvées
+    pub fn validation_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.router_id.is_some_and(|router_id| router_id.0 == 0) {
+            errors.push("router_id: 0.0.0.0 is not a valid router id".to_string());
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for iface in &self.interfaces {
+            if iface.name.trim().is_empty() {
+                errors.push("interface with an empty name".to_string());
+            } else if !seen.insert(iface.name.as_str()) {
+                errors.push(format!("interface {}: declared more than once", iface.name));
+            }
+            if iface.capacity_mbps == 0 {
+                errors.push(format!("interface {}: capacity_mbps must be greater than 0", iface.name));
+            }
+            if iface.cost.is_some_and(|cost| cost == 0 || cost == u32::MAX) {
+                errors.push(format!("interface {}: cost must be between 1 and {}", iface.name, u32::MAX - 1));
+            }
+            match (iface.link_type, iface.peer.as_deref()) {
+                (LinkType::PointToPoint, None) => {
+                    errors.push(format!("interface {}: point-to-point link requires a peer address", iface.name));
+                }
+                (LinkType::PointToPoint, Some(peer)) if peer.parse::<Ipv4Addr>().is_err() => {
+                    errors.push(format!("interface {}: peer '{}' is not an IPv4 address", iface.name, peer));
+                }
+                (LinkType::Broadcast, Some(_)) => {
+                    errors.push(format!("interface {}: peer is only used with link_type = \"point-to-point\"", iface.name));
+                }
+                _ => {}
+            }
+            for entry in &iface.advertise {
+                if entry.parse::<crate::types::Prefix>().is_err() {
+                    errors.push(format!("interface {}: advertise '{}' is not a valid IPv4 CIDR", iface.name, entry));
+                }
+            }
+            if iface.protocol == InterfaceProtocol::Ospfv2 {
+                if iface.cost.is_some_and(|cost| cost > u32::from(u16::MAX)) {
+                    errors.push(format!("interface {}: OSPFv2 costs are limited to {}", iface.name, u16::MAX));
+                }
+                if self.hello_interval_for(Some(&iface.name)) > u64::from(u16::MAX) {
+                    errors.push(format!("interface {}: OSPFv2 hello interval is limited to {} seconds", iface.name, u16::MAX));
+                }
+            }
+        }
+
+        if self.hostname.as_deref().is_some_and(|hostname| !crate::packet::is_valid_hostname(hostname)) {
+            errors.push("hostname: 1 to 63 letters, digits, '-', '_' or '.'".to_string());
+        }
+
+        if self.encryption {
+            match &self.key {
+                None => errors.push("key is required when encryption is enabled (or set encryption = false)".to_string()),
+                Some(key) => errors.extend(key_error("key", key)),
+            }
+        }
+        for iface in &self.interfaces {
+            let auth = self.auth_for(Some(&iface.name));
+            match (&iface.key, &self.key) {
+                (Some(key), _) if auth.needs_key() => errors.extend(key_error(&format!("interface {}: key", iface.name), key)),
+                (None, None) if auth.needs_key() => errors.push(format!("interface {}: auth = \"{}\" requires a key", iface.name, auth)),
+                // La clé globale n'est vérifiée ci-dessus qu'avec `encryption`
+                (None, Some(key)) if auth.needs_key() && !self.encryption => {
+                    errors.extend(key_error(&format!("key (used by interface {})", iface.name), key));
+                }
+                _ => {}
+            }
+        }
+
+        if self.redistribute.enabled && self.is_stub_internal() {
+            errors.push("redistribute: external routes cannot be injected inside a stub area".to_string());
+        }
+
+        for (field, list) in [("redistribute.permit", &self.redistribute.permit), ("redistribute.deny", &self.redistribute.deny)] {
+            for entry in list {
+                if entry.parse::<pnet::ipnetwork::Ipv4Network>().is_err() {
+                    errors.push(format!("{}: '{}' is not a valid IPv4 CIDR", field, entry));
+                }
+            }
+        }
+
+        let mut networks = HashMap::new();
+        for (network, metric) in &self.network_metrics {
+            if *metric == u32::MAX {
+                errors.push(format!("network_metrics: metric of {} must be below {}", network, u32::MAX));
+            }
+            // « 192.168.2.1/24 » et « 192.168.2.0/24 » désignent le même réseau
+            if let Some(other) = networks.insert(network.network(), network) {
+                errors.push(format!("network_metrics: {} and {} are the same network", other, network));
+            }
+        }
+
+        errors.extend(self.filter_errors());
+
+        for (index, neighbor) in self.static_neighbors.iter().enumerate() {
+            if !self.is_interface_configured(&neighbor.interface) || self.interface_protocol(Some(&neighbor.interface)) != InterfaceProtocol::Pospf {
+                errors.push(format!("static_neighbors[{}]: interface {} is not a configured P-OSPF interface", index, neighbor.interface));
+            }
+            if self.static_neighbors[..index].iter().any(|other| other.address == neighbor.address) {
+                errors.push(format!("static_neighbors[{}]: duplicate address {}", index, neighbor.address));
+            }
+            if neighbor.port == Some(0) {
+                errors.push(format!("static_neighbors[{}]: port must be greater than 0", index));
+            }
+        }
+        if self.overlay.enabled {
+            if self.static_neighbors.is_empty() {
+                errors.push("overlay mode requires at least one static_neighbors endpoint".to_string());
+            }
+            if self.overlay.keepalive_sec == 0 {
+                errors.push("overlay.keepalive_sec must be greater than 0".to_string());
+            }
+        }
+        if self.route_install.max_attempts == 0 {
+            errors.push("route_install.max_attempts must be greater than 0".to_string());
+        }
+        if self.route_install.retry_initial_ms > self.route_install.retry_max_ms {
+            errors.push("route_install.retry_initial_ms must not exceed retry_max_ms".to_string());
+        }
+        if let Some(netns) = &self.netns {
+            if !crate::netns::is_valid_name(netns) {
+                errors.push(format!("netns: '{}' is neither a namespace name nor an absolute path", netns));
+            }
+        }
+        if self.mdns.enabled && self.mdns.interval_sec == 0 {
+            errors.push("mdns.interval_sec must be greater than 0".to_string());
+        }
+        if self.mtu_discovery.enabled && self.mtu_discovery.interval_sec == 0 {
+            errors.push("mtu_discovery.interval_sec must be greater than 0".to_string());
+        }
+
+        if self.rip.cost_per_hop == 0 {
+            errors.push("rip.cost_per_hop must be greater than 0".to_string());
+        }
+        if self.rip.update_interval_sec == 0 || self.rip.timeout_sec <= self.rip.update_interval_sec {
+            errors.push("rip: update_interval_sec must be greater than 0 and shorter than timeout_sec".to_string());
+        }
+
+        if self.bgp.enabled {
+            if self.bgp.local_as == 0 {
+                errors.push("bgp.local_as must be set".to_string());
+            }
+            if self.is_stub_internal() {
+                errors.push("bgp: external routes cannot be injected inside a stub area".to_string());
+            }
+            if self.bgp.hold_time_sec < 3 {
+                errors.push("bgp.hold_time_sec must be at least 3".to_string());
+            }
+            if let Some(listen) = &self.bgp.listen {
+                if listen.parse::<std::net::SocketAddr>().is_err() {
+                    errors.push(format!("bgp.listen: '{}' is not a valid address:port", listen));
+                }
+            }
+            for (index, peer) in self.bgp.peers.iter().enumerate() {
+                if peer.address.parse::<std::net::SocketAddr>().is_err() {
+                    errors.push(format!("bgp.peers[{}]: '{}' is not a valid address:port", index, peer.address));
+                }
+                if peer.remote_as == 0 || peer.remote_as == self.bgp.local_as {
+                    errors.push(format!("bgp.peers[{}]: remote_as must be set and differ from local_as", index));
+                }
+                if peer.passive && self.bgp.listen.is_none() {
+                    errors.push(format!("bgp.peers[{}]: a passive peer requires bgp.listen", index));
+                }
+            }
+            for entry in &self.bgp.export {
+                if entry.parse::<crate::types::Prefix>().is_err() {
+                    errors.push(format!("bgp.export: '{}' is not a valid IPv4 CIDR", entry));
+                }
+            }
+        }
+
+        for (index, user) in self.control.users.iter().enumerate() {
+            if user.name.is_empty() || user.token.is_empty() {
+                errors.push(format!("control.users[{}]: name and token must not be empty", index));
+            }
+            if self.control.users[..index].iter().any(|other| other.name == user.name) {
+                errors.push(format!("control.users[{}]: duplicate user '{}'", index, user.name));
+            }
+        }
+
+        if self.opaque.len() > crate::packet::MAX_OPAQUE_TLVS {
+            errors.push(format!("opaque: at most {} TLVs", crate::packet::MAX_OPAQUE_TLVS));
+        }
+        for (index, tlv) in self.opaque.iter().enumerate() {
+            if tlv.value.len() > crate::packet::MAX_OPAQUE_LEN {
+                errors.push(format!("opaque[{}]: value exceeds {} bytes", index, crate::packet::MAX_OPAQUE_LEN));
+            }
+            if self.opaque[..index].iter().any(|other| other.tlv_type == tlv.tlv_type) {
+                errors.push(format!("opaque[{}]: duplicate tlv_type {}", index, tlv.tlv_type));
+            }
+        }
+
+        if self.port == 0 {
+            errors.push("port must be greater than 0".to_string());
+        }
+
+        if self.http.enabled && self.http.listen.parse::<std::net::SocketAddr>().is_err() {
+            errors.push(format!("http.listen: '{}' is not a valid address:port", self.http.listen));
+        }
+        if self.grpc.enabled && self.grpc.listen.parse::<std::net::SocketAddr>().is_err() {
+            errors.push(format!("grpc.listen: '{}' is not a valid address:port", self.grpc.listen));
+        }
+
+        let damping = &self.flap_damping;
+        if damping.enabled {
+            if damping.half_life_sec == 0 {
+                errors.push("flap_damping.half_life_sec must be greater than 0".to_string());
+            }
+            if damping.reuse_threshold == 0 || damping.reuse_threshold >= damping.suppress_threshold {
+                errors.push(format!(
+                    "flap_damping.reuse_threshold ({}) must be between 1 and suppress_threshold ({})",
+                    damping.reuse_threshold, damping.suppress_threshold
+                ));
+            }
+        }
+
+        if self.hold_down.enabled && self.hold_down.duration_sec == 0 {
+            errors.push("hold_down.duration_sec must be greater than 0".to_string());
+        }
+        if self.install_policy.max_hop_count == Some(0) {
+            errors.push("install_policy.max_hop_count must be greater than 0".to_string());
+        }
+
+        let adaptive = &self.adaptive_hello;
+        if adaptive.enabled {
+            if adaptive.fast_interval_sec == 0 || adaptive.stable_after_sec == 0 {
+                errors.push("adaptive_hello.fast_interval_sec and stable_after_sec must be greater than 0".to_string());
+            }
+            let dead_intervals = std::iter::once(self.dead_interval_sec)
+                .chain(self.interfaces.iter().map(|iface| self.dead_interval_for(Some(&iface.name))));
+            if let Some(dead) = dead_intervals.min().filter(|dead| adaptive.max_interval_sec.saturating_mul(3) > *dead) {
+                errors.push(format!(
+                    "adaptive_hello.max_interval_sec ({}) must be at most a third of the dead interval ({} s)",
+                    adaptive.max_interval_sec, dead
+                ));
+            }
+        }
+
+        if self.receive.workers == 0 {
+            errors.push("receive.workers must be greater than 0".to_string());
+        }
+        if self.receive.queue_depth == 0 {
+            errors.push("receive.queue_depth must be greater than 0".to_string());
+        }
+        if self.qos.dscp > 63 {
+            errors.push(format!("qos.dscp ({}) must be at most 63", self.qos.dscp));
+        }
+
+        let mut vrf_names = std::collections::HashSet::new();
+        let mut vrf_tables = std::collections::HashSet::new();
+        for vrf in &self.vrfs {
+            if vrf.name.trim().is_empty() || vrf.name.contains(char::is_whitespace) {
+                errors.push(format!("vrf '{}': name must be a single non-empty word", vrf.name));
+            } else if !vrf_names.insert(vrf.name.as_str()) {
+                errors.push(format!("vrf {}: declared more than once", vrf.name));
+            }
+            // 0 : non spécifiée, 253 à 255 : default, main et local
+            if vrf.table == 0 || (253..=255).contains(&vrf.table) {
+                errors.push(format!("vrf {}: table {} is reserved", vrf.name, vrf.table));
+            } else if !vrf_tables.insert(vrf.table) {
+                errors.push(format!("vrf {}: table {} is already used by another vrf", vrf.name, vrf.table));
+            }
+            if !self.interfaces.iter().any(|iface| iface.vrf.as_deref() == Some(vrf.name.as_str())) {
+                errors.push(format!("vrf {}: no interface is attached to it", vrf.name));
+            }
+        }
+        for iface in &self.interfaces {
+            if let Some(vrf) = iface.vrf.as_deref().filter(|vrf| !vrf_names.contains(vrf)) {
+                errors.push(format!("interface {}: vrf '{}' is not declared in vrfs", iface.name, vrf));
+            }
+        }
+
+        let quic = self.interfaces.iter().filter(|iface| iface.transport == LsaTransport::Quic).map(|iface| iface.name.as_str()).collect::<Vec<_>>();
+        if !quic.is_empty() && (self.quic.cert.is_none() || self.quic.key.is_none() || self.quic.ca.is_none()) {
+            errors.push(format!("interfaces {} use transport quic: quic.cert, quic.key and quic.ca are required", quic.join(", ")));
+        }
+        if !quic.is_empty() && crate::quic::session_port(self.port).is_none() {
+            errors.push(format!("interfaces {} use transport quic: port {} leaves no UDP port for QUIC sessions", quic.join(", "), self.port));
+        }
+
+        errors.extend(self.timer_errors());
+        errors
+    }
+
+    /// Valide la configuration, en agrégeant toutes les erreurs dans un seul message
+    pub fn validate(&self) -> Result<()> {
+        let errors = self.validation_errors();
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(AppError::ConfigError(format!(
+            "{} problem(s) found:\n  - {}",
+            errors.len(),
+            errors.join("\n  - ")
+        )))
+    }
+
+    /// Vérifie les listes de préfixes et les filtres qui y font référence
+    fn filter_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (name, entries) in &self.prefix_lists {
+            for entry in entries {
+                if entry.prefix.is_empty() {
+                    if entry.tag.is_none() {
+                        errors.push(format!("prefix_lists.{}: an entry needs a prefix or a tag", name));
+                    } else if entry.ge.is_some() || entry.le.is_some() {
+                        errors.push(format!("prefix_lists.{}: ge and le require a prefix", name));
+                    }
+                    continue;
+                }
+                let Ok(prefix) = entry.prefix.parse::<pnet::ipnetwork::Ipv4Network>() else {
+                    errors.push(format!("prefix_lists.{}: '{}' is not a valid IPv4 CIDR", name, entry.prefix));
+                    continue;
+                };
+                let ge = entry.ge.unwrap_or(prefix.prefix());
+                let le = entry.le.unwrap_or(if entry.ge.is_some() { 32 } else { prefix.prefix() });
+                if ge < prefix.prefix() || ge > le || le > 32 {
+                    errors.push(format!(
+                        "prefix_lists.{}: {} requires {} <= ge <= le <= 32 (ge {}, le {})",
+                        name, entry.prefix, prefix.prefix(), ge, le
+                    ));
+                }
+            }
+        }
+
+        let references = self.interfaces.iter()
+            .flat_map(|iface| [(&iface.name, &iface.filter_in), (&iface.name, &iface.filter_out)])
+            .chain(self.neighbor_filters.iter()
+                .flat_map(|filter| [(&filter.neighbor, &filter.filter_in), (&filter.neighbor, &filter.filter_out)]));
+        for (owner, list) in references {
+            if let Some(list) = list.as_ref().filter(|list| !self.prefix_lists.contains_key(*list)) {
+                errors.push(format!("{}: unknown prefix list '{}'", owner, list));
+            }
+        }
+        for filter in &self.neighbor_filters {
+            if filter.neighbor.parse::<Ipv4Addr>().is_err() {
+                errors.push(format!("neighbor_filters: '{}' is not an IPv4 address", filter.neighbor));
+            }
+        }
+        errors
+    }
+
+    /// Vérifie la cohérence des temporisations globales et par interface
+    fn timer_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.lsa_interval_sec == 0 {
+            errors.push("lsa_interval_sec must be greater than 0".to_string());
+        }
+        if self.retransmit_interval_sec == 0 {
+            errors.push("retransmit_interval_sec must be greater than 0".to_string());
+        }
+        let spf = &self.spf_throttle;
+        if spf.hold_ms == 0 || spf.max_hold_ms < spf.hold_ms {
+            errors.push(format!(
+                "spf_throttle: hold_ms ({}) must be greater than 0 and not exceed max_hold_ms ({})",
+                spf.hold_ms, spf.max_hold_ms
+            ));
+        }
+        let mut scopes = vec![("global".to_string(), self.hello_interval_sec, self.dead_interval_sec)];
+        for iface in &self.interfaces {
+            scopes.push((
+                format!("interface {}", iface.name),
+                self.hello_interval_for(Some(&iface.name)),
+                self.dead_interval_for(Some(&iface.name)),
+            ));
+        }
+        for (scope, hello, dead) in scopes {
+            if hello == 0 {
+                errors.push(format!("{}: hello_interval_sec must be greater than 0", scope));
+            } else if dead <= hello {
+                errors.push(format!(
+                    "{}: dead_interval_sec ({}) must be greater than hello_interval_sec ({})",
+                    scope, dead, hello
+                ));
+            }
+        }
+        errors
+    }
+}
+
+/// Préfixe des variables d'environnement surchargeant la configuration
+pub const ENV_PREFIX: &str = "ROUTER_";
+
+/// Extensions reconnues pour les fichiers de configuration, par ordre de priorité
+const CONFIG_EXTENSIONS: &[&str] = &["toml", "yaml", "yml"];
+
+/// Analyse le contenu d'un fichier de configuration selon son extension (TOML ou YAML)
+pub fn parse_config(content: &str, path: &str) -> Result<RouterConfig> {
+    let is_yaml = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml");
+    if is_yaml {
+        serde_yaml::from_str(content)
+            .map_err(|e| AppError::ConfigError(format!("Failed to parse config file {}: {}", path, e)))
+    } else {
+        toml::from_str(content)
+            .map_err(|e| AppError::ConfigError(format!("Failed to parse config file {}: {}", path, e)))
+    }
+}
+
+fn parse_env<T: std::str::FromStr>(name: &str, value: &str, errors: &mut Vec<String>) -> Option<T> {
+    match value.trim().parse() {
+        Ok(parsed) => Some(parsed),
+        Err(_) => {
+            errors.push(format!("{}: invalid value '{}'", name, value));
+            None
+        }
+    }
+}
+
+/// Applique les surcharges `ROUTER_*` (clé, port, temporisations, interfaces...)
+pub fn apply_env_overrides<I>(config: &mut RouterConfig, vars: I) -> Result<()>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut errors = Vec::new();
+    for (name, value) in vars {
+        let Some(field) = name.strip_prefix(ENV_PREFIX) else { continue };
+        match field {
+            "KEY" => config.key = Some(value),
+            "ENCRYPTION" => config.encryption = parse_env(&name, &value, &mut errors).unwrap_or(config.encryption),
+            "PORT" => config.port = parse_env(&name, &value, &mut errors).unwrap_or(config.port),
+            "HELLO_INTERVAL" => config.hello_interval_sec = parse_env(&name, &value, &mut errors).unwrap_or(config.hello_interval_sec),
+            "LSA_INTERVAL" => config.lsa_interval_sec = parse_env(&name, &value, &mut errors).unwrap_or(config.lsa_interval_sec),
+            "DEAD_INTERVAL" => config.dead_interval_sec = parse_env(&name, &value, &mut errors).unwrap_or(config.dead_interval_sec),
+            "TRANSPORT" => match value.trim() {
+                "broadcast" => config.transport = TransportMode::Broadcast,
+                "multicast" => config.transport = TransportMode::Multicast,
+                _ => errors.push(format!("{}: expected 'broadcast' or 'multicast', got '{}'", name, value)),
+            },
+            "HTTP_LISTEN" => {
+                config.http.enabled = true;
+                config.http.listen = value;
+            }
+            "GRPC_LISTEN" => {
+                config.grpc.enabled = true;
+                config.grpc.listen = value;
+            }
+            // Liste « nom[:capacité_mbps] » séparée par des virgules, remplaçant les interfaces du fichier
+            "INTERFACES" => {
+                let mut interfaces = Vec::new();
+                for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+                    let (iface, capacity) = entry.split_once(':').unwrap_or((entry, "100"));
+                    if let Some(capacity_mbps) = parse_env(&name, capacity, &mut errors) {
+                        interfaces.push(InterfaceConfig::new(iface, capacity_mbps));
+                    }
+                }
+                config.interfaces = interfaces;
+            }
+            // Variables ROUTER_* propres au déploiement (ROUTER_CONFIG...) : ignorées ici
+            _ => {}
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::ConfigError(format!("Invalid environment overrides:\n  - {}", errors.join("\n  - "))))
+    }
+}
+
+fn has_env_overrides() -> bool {
+    std::env::vars().any(|(name, _)| name.starts_with(ENV_PREFIX) && ![CONFIG_PATH_ENV, LOG_FORMAT_ENV, CONTROL_TOKEN_ENV].contains(&name.as_str()))
+}
+
+/// Variable d'environnement désignant explicitement le fichier de configuration
+pub const CONFIG_PATH_ENV: &str = "ROUTER_CONFIG";
+
+/// Format des journaux : `json` pour une sortie structurée, texte sinon
+pub const LOG_FORMAT_ENV: &str = "ROUTER_LOG_FORMAT";
+
+/// Secret de l'utilisateur du CLI (`--user`), jamais passé en argument
+pub const CONTROL_TOKEN_ENV: &str = "ROUTER_CONTROL_TOKEN";
+
+/// Répertoire de configuration système
+const SYSTEM_CONFIG_DIR: &str = "/etc/pospf";
+
+/// Répertoire des configurations d'exemple du dépôt (relatif au répertoire courant)
+const REPO_CONFIG_DIR: &str = "src/conf";
+
+/// Extrait le chemin passé via `--config <fichier>` ou `--config=<fichier>`
+pub fn config_path_from_args<I: IntoIterator<Item = String>>(args: I) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+/// Vrai si `--dry-run` figure parmi les arguments
+pub fn dry_run_from_args<I: IntoIterator<Item = String>>(args: I) -> bool {
+    args.into_iter().any(|arg| arg == "--dry-run")
+}
+
+/// Valeur d'une option `--nom <valeur>` ou `--nom=<valeur>`
+pub fn option_from_args<I: IntoIterator<Item = String>>(args: I, name: &str) -> Option<String> {
+    let mut args = args.into_iter();
+    let inline = format!("{}=", name);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(&inline) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+fn xdg_config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("pospf"))
+}
+
+/// Emplacements candidats, par ordre de priorité : /etc/pospf, répertoire XDG,
+/// puis src/conf. Les répertoires système acceptent aussi un `config.<ext>` générique.
+pub fn config_search_paths(hostname: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let system_dirs = std::iter::once(PathBuf::from(SYSTEM_CONFIG_DIR)).chain(xdg_config_dir());
+    for dir in system_dirs {
+        for ext in CONFIG_EXTENSIONS {
+            paths.push(dir.join(format!("config_{}.{}", hostname, ext)));
+        }
+        for ext in CONFIG_EXTENSIONS {
+            paths.push(dir.join(format!("config.{}", ext)));
+        }
+    }
+    for ext in CONFIG_EXTENSIONS {
+        paths.push(Path::new(REPO_CONFIG_DIR).join(format!("config_{}.{}", hostname, ext)));
+    }
+    paths
+}
+
+fn load_file(path: &Path) -> Result<RouterConfig> {
+    let display = path.display().to_string();
+    let config_content = fs::read_to_string(path)
+        .map_err(|e| AppError::ConfigError(format!("Failed to read config file {}: {}", display, e)))?;
+    parse_config(&config_content, &display)
+}
+
+/// Lit la configuration du routeur par couches : valeurs par défaut, fichier
+/// (chemin explicite, ROUTER_CONFIG ou chemins de recherche ; facultatif si des
+/// variables ROUTER_* sont définies), puis surcharges par l'environnement
+pub fn read_router_config(explicit_path: Option<&Path>) -> Result<RouterConfig> {
+    let hostname = get_hostname()?;
+    let explicit_path = explicit_path
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::var_os(CONFIG_PATH_ENV).filter(|p| !p.is_empty()).map(PathBuf::from));
+
+    let (mut config, source) = match explicit_path {
+        Some(path) => (load_file(&path)?, path.display().to_string()),
+        None => {
+            let search_paths = config_search_paths(&hostname);
+            match search_paths.iter().find(|path| path.exists()) {
+                Some(path) => (load_file(path)?, path.display().to_string()),
+                None if has_env_overrides() => (RouterConfig::default(), "environment".to_string()),
+                None => {
+                    let searched: Vec<String> = search_paths.iter().map(|p| p.display().to_string()).collect();
+                    return Err(AppError::ConfigError(format!(
+                        "Config file not found for {} (searched: {}). Available configs in {}: {}",
+                        hostname,
+                        searched.join(", "),
+                        REPO_CONFIG_DIR,
+                        list_available_configs()
+                    )));
+                }
+            }
+        }
+    };
+
+    apply_env_overrides(&mut config, std::env::vars())?;
+
+    if let Err(AppError::ConfigError(problems)) = config.validate() {
+        return Err(AppError::ConfigError(format!("Invalid configuration from {}: {}", source, problems)));
+    }
+
+    tracing::info!("Loaded configuration for router: {} ({})", hostname, source);
+    // Pas de dump de la configuration : elle contient clés et jetons
+    tracing::debug!(
+        "Config: {} interfaces, transport {:?}, port {}, encryption {}",
+        config.interfaces.len(),
+        config.transport,
+        config.port,
+        config.encryption
+    );
+
     Ok(config)
 }
 
-fn get_hostname() -> Result<String> {
+/// Nom court du système (avant le premier point)
+pub fn get_hostname() -> Result<String> {
     hostname::get()
         .map_err(|e| AppError::ConfigError(format!("Failed to get hostname: {}", e)))?
         .to_string_lossy()
-        .to_string()
         .split('.')
         .next()
         .ok_or_else(|| AppError::ConfigError("Invalid hostname".to_string()))
@@ -60,13 +2077,12 @@ fn get_hostname() -> Result<String> {
 }
 
 fn list_available_configs() -> String {
-    let config_dir = "src/conf";
-    if let Ok(entries) = fs::read_dir(config_dir) {
+    if let Ok(entries) = fs::read_dir(REPO_CONFIG_DIR) {
         let configs: Vec<String> = entries
             .filter_map(|entry| {
                 let entry = entry.ok()?;
                 let path = entry.path();
-                if path.extension()? == "toml" {
+                if CONFIG_EXTENSIONS.iter().any(|ext| path.extension().is_some_and(|e| e == *ext)) {
                     path.file_name()?.to_str().map(|s| s.to_string())
                 } else {
                     None
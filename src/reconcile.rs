@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use tracing::{debug, info, warn};
+use pnet::ipnetwork::Ipv4Network;
+use crate::AppState;
+use crate::error::Result;
+use crate::nexthop::NextHop;
+use crate::types::{Prefix, RouteState};
+
+/// Corrections appliquées par une passe de réconciliation
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReconcileReport {
+    /// Routes orphelines ou divergentes retirées du noyau
+    pub removed: usize,
+    /// Routes manquantes (ré)installées
+    pub installed: usize,
+}
+
+/// Routes que le noyau devrait porter sous notre protocole : entrées actives de la table,
+/// hors réseaux connectés, préfixes déjà tenus par un autre protocole (si protégés)
+/// et prochains sauts irrésolubles
+async fn desired_routes(state: &AppState, foreign: &[Ipv4Network], protect_foreign: bool) -> HashMap<Ipv4Network, NextHop> {
+    let connected: Vec<Ipv4Network> = state.interfaces.interfaces()
+        .iter()
+        .filter_map(|iface| Ipv4Network::new(iface.network.network(), iface.network.prefix()).ok())
+        .collect();
+    let table = state.routing_table.read().await.clone();
+    let mut desired = HashMap::new();
+    for (prefix, (next_hop, route_state)) in table {
+        if matches!(route_state, RouteState::Unreachable) {
+            continue;
+        }
+        let network = prefix.network();
+        if connected.contains(&network) || (protect_foreign && foreign.contains(&network)) {
+            continue;
+        }
+        if let Some(resolved) = crate::nexthop::resolve(state, &next_hop).await {
+            desired.insert(network, resolved);
+        }
+    }
+    desired
+}
+
+/// Compare nos routes du noyau à la table de routage et corrige les écarts : route
+/// supprimée par un tiers ou modifiée (réinstallée), route laissée par une exécution
+/// précédente ou vers un préfixe disparu (retirée)
+pub async fn reconcile_routes(state: &Arc<AppState>) -> Result<ReconcileReport> {
+    let install = state.config().await.route_install;
+    let mut report = ReconcileReport::default();
+    if !install.enabled {
+        return Ok(report);
+    }
+
+    let (own, foreign): (Vec<_>, Vec<_>) = state.routes.list_routes().await?
+        .into_iter()
+        .partition(|route| route.protocol == install.protocol);
+    let foreign: Vec<Ipv4Network> = foreign.iter().map(|route| route.prefix).collect();
+    let desired = desired_routes(state, &foreign, install.protect_foreign).await;
+
+    let mut in_sync = Vec::new();
+    for route in own {
+        let up_to_date = desired.get(&route.prefix).is_some_and(|next_hop| {
+            route.gateway == next_hop.gateway && route.priority == Some(install.distance)
+        });
+        if up_to_date && !in_sync.contains(&route.prefix) {
+            in_sync.push(route.prefix);
+            continue;
+        }
+        let (prefix, gateway) = (route.prefix, route.gateway);
+        match state.routes.delete_route(route).await {
+            Ok(()) => {
+                info!("[RECONCILE] Removed stale route to {} via {:?}", prefix, gateway);
+                report.removed += 1;
+            }
+            Err(e) => warn!("[RECONCILE] Failed to remove stale route to {}: {}", prefix, e),
+        }
+    }
+
+    for (prefix, next_hop) in desired.iter().filter(|(prefix, _)| !in_sync.contains(prefix)) {
+        match crate::lsa::update_routing_table_safe(state.routes.as_ref(), &state.interfaces, &Prefix::new(*prefix), next_hop, &install).await {
+            Ok(false) => {}
+            Ok(true) => {
+                info!("[RECONCILE] Reinstalled missing route to {} via {}", prefix, next_hop);
+                report.installed += 1;
+            }
+            Err(e) => warn!("[RECONCILE] Failed to reinstall route to {}: {}", prefix, e),
+        }
+    }
+
+    let repairs = (report.removed + report.installed) as u64;
+    state.metrics.route_repairs.fetch_add(repairs, Ordering::Relaxed);
+    if repairs == 0 {
+        debug!("[RECONCILE] Kernel routes in sync ({} route(s))", in_sync.len());
+    }
+    Ok(report)
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, info};
+use pnet::ipnetwork::Ipv4Network;
+use crate::AppState;
+use crate::types::Prefix;
+use crate::error::Result;
+use crate::route_backend::{KernelRoute, RTPROT_BOOT, RTPROT_KERNEL, RTPROT_STATIC};
+use crate::read_config::{RedistributeConfig, RouteSource};
+
+fn route_source(route: &KernelRoute) -> Option<RouteSource> {
+    match route.protocol {
+        RTPROT_STATIC => Some(RouteSource::Static),
+        RTPROT_KERNEL => Some(RouteSource::Kernel),
+        RTPROT_BOOT => Some(RouteSource::Boot),
+        _ => None,
+    }
+}
+
+fn covers(list: &[String], prefix: &Ipv4Network) -> bool {
+    list.iter()
+        .filter_map(|entry| entry.parse::<Ipv4Network>().ok())
+        .any(|net| net.prefix() <= prefix.prefix() && net.contains(prefix.network()))
+}
+
+/// Applique la politique de redistribution à une route du noyau
+pub fn is_redistributable(config: &RedistributeConfig, route: &KernelRoute) -> bool {
+    let Some(source) = route_source(route) else {
+        return false;
+    };
+    if !config.sources.contains(&source) {
+        return false;
+    }
+    if route.prefix.prefix() == 0 && !config.include_default {
+        return false;
+    }
+    if route.prefix.ip().is_loopback() || route.prefix.ip().is_link_local() || route.prefix.ip().is_multicast() {
+        return false;
+    }
+    if covers(&config.deny, &route.prefix) {
+        return false;
+    }
+    config.permit.is_empty() || covers(&config.permit, &route.prefix)
+}
+
+/// Calcule l'ensemble des routes externes à annoncer et le stocke dans l'état partagé.
+/// Les routes installées par le protocole lui-même (marquées de notre numéro de
+/// protocole) ne sont jamais réinjectées.
+pub async fn refresh_redistributed_routes(state: &Arc<AppState>) -> Result<()> {
+    let config = state.config().await.redistribute;
+    let kernel_routes = state.routes.list_routes().await?;
+
+    let mut external = HashMap::new();
+    for route in kernel_routes {
+        if !is_redistributable(&config, &route) {
+            continue;
+        }
+        external.insert(Prefix::new(route.prefix), config.metric);
+    }
+
+    let mut redistributed = state.redistributed_routes.write().await;
+    if *redistributed != external {
+        info!("Redistributing {} external route(s): {:?}", external.len(), external.keys().collect::<Vec<_>>());
+    } else {
+        debug!("Redistributed routes unchanged ({})", external.len());
+    }
+    *redistributed = external;
+    Ok(())
+}
@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::transport::Socket;
+use tracing::{info, warn};
+use crate::AppState;
+use crate::error::Result;
+use crate::read_config::{self, RouterConfig, TransportMode};
+
+/// Différences entre l'ancienne et la nouvelle configuration
+#[derive(Debug, Default)]
+pub struct ConfigDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    pub key_changed: bool,
+    pub timers_changed: bool,
+    pub transport_changed: bool,
+    /// Vrai dès qu'un paramètre quelconque diffère (redistribution, etc.)
+    pub settings_changed: bool,
+}
+
+impl ConfigDiff {
+    pub fn compute(old: &RouterConfig, new: &RouterConfig) -> Self {
+        let added = new.interfaces.iter()
+            .filter(|iface| !old.interfaces.iter().any(|o| o.name == iface.name))
+            .map(|iface| iface.name.clone())
+            .collect();
+        let removed = old.interfaces.iter()
+            .filter(|iface| !new.interfaces.iter().any(|n| n.name == iface.name))
+            .map(|iface| iface.name.clone())
+            .collect();
+        let modified = new.interfaces.iter()
+            .filter(|iface| old.interfaces.iter().any(|o| o.name == iface.name && o != *iface))
+            .map(|iface| iface.name.clone())
+            .collect();
+
+        Self {
+            added,
+            removed,
+            modified,
+            key_changed: old.decoded_key() != new.decoded_key() || old.interface_keys() != new.interface_keys(),
+            timers_changed: old.hello_interval_sec != new.hello_interval_sec
+                || old.lsa_interval_sec != new.lsa_interval_sec
+                || old.dead_interval_sec != new.dead_interval_sec
+                || old.retransmit_interval_sec != new.retransmit_interval_sec,
+            transport_changed: old.transport != new.transport,
+            settings_changed: old != new,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.settings_changed
+    }
+
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "Configuration rechargée: aucun changement".to_string();
+        }
+        let mut parts = vec![
+            format!("{} interface(s) ajoutée(s) {:?}", self.added.len(), self.added),
+            format!("{} supprimée(s) {:?}", self.removed.len(), self.removed),
+            format!("{} modifiée(s) {:?}", self.modified.len(), self.modified),
+        ];
+        if self.key_changed {
+            parts.push("clé modifiée".to_string());
+        }
+        if self.timers_changed {
+            parts.push("temporisations modifiées".to_string());
+        }
+        if self.transport_changed {
+            parts.push("mode de transport modifié".to_string());
+        }
+        format!("Configuration rechargée: {}", parts.join(", "))
+    }
+}
+
+/// Relit la configuration et applique les changements sans redémarrer le processus.
+/// Un chemin explicite remplace, en cas de succès, celui utilisé pour les rechargements suivants.
+pub async fn reload_config(state: &Arc<AppState>, socket: &Socket, override_path: Option<PathBuf>) -> Result<String> {
+    let config_path = match override_path {
+        Some(path) => Some(path),
+        None => state.config_path.lock().await.clone(),
+    };
+    // Chaque instance ne reprend que la part de la configuration qui concerne sa VRF
+    let new_config = read_config::read_router_config(config_path.as_deref())?.for_vrf(state.vrf.as_deref());
+    *state.config_path.lock().await = config_path;
+    let old_config = state.config().await;
+    let diff = ConfigDiff::compute(&old_config, &new_config);
+    if new_config.port != state.port {
+        tracing::warn!("Port change ({} -> {}) requires a restart, keeping {}", state.port, new_config.port, state.port);
+    }
+    if new_config.receive != old_config.receive {
+        tracing::warn!("Receive worker settings change requires a restart");
+    }
+    let protocols = |config: &read_config::RouterConfig| -> Vec<(String, read_config::InterfaceProtocol)> {
+        config.interfaces.iter().map(|iface| (iface.name.clone(), iface.protocol)).collect()
+    };
+    if protocols(&new_config) != protocols(&old_config) {
+        tracing::warn!("Interface protocol change (P-OSPF/OSPFv2/RIP) requires a restart");
+    }
+    let sessions = |config: &read_config::RouterConfig| {
+        (config.bgp.enabled, config.bgp.local_as, config.bgp.listen.clone(), config.bgp.peers.clone())
+    };
+    if sessions(&new_config) != sessions(&old_config) {
+        tracing::warn!("External gateway sessions (bgp peers, AS, listen address) change requires a restart");
+    }
+    if new_config.vrfs != old_config.vrfs {
+        tracing::warn!("VRF list change requires a restart");
+    }
+    if new_config.netns != old_config.netns {
+        tracing::warn!("Network namespace change requires a restart");
+    }
+    if new_config.mdns != old_config.mdns {
+        tracing::warn!("mDNS discovery change requires a restart");
+    }
+    if new_config.router_id.is_some_and(|router_id| router_id != state.router_id) {
+        tracing::warn!("Router id change requires a restart, keeping {}", state.router_id);
+    }
+
+    if diff.is_empty() {
+        return Ok(diff.summary());
+    }
+
+    // Rejoindre le groupe multicast sur les interfaces qui en ont besoin
+    if new_config.transport == TransportMode::Multicast {
+        let interfaces: Vec<String> = if diff.transport_changed {
+            new_config.interfaces.iter().map(|iface| iface.name.clone()).collect()
+        } else {
+            diff.added.clone()
+        };
+        if let Some(socket) = socket.as_udp() {
+            for ip in interfaces.iter().flat_map(|name| state.interfaces.addresses_of(name)) {
+                if let Err(e) = crate::net_utils::join_multicast_on(socket, ip) {
+                    warn!("{}", e);
+                }
+            }
+        }
+    }
+    let dscp_changed = new_config.qos.dscp != old_config.qos.dscp;
+    if let Some(socket) = socket.as_udp().filter(|_| dscp_changed) {
+        crate::net_utils::set_dscp(socket, new_config.qos.dscp)?;
+    }
+    if socket.as_udp().is_some() && (diff.transport_changed || dscp_changed || !diff.added.is_empty() || !diff.removed.is_empty()) {
+        let flood_sockets = crate::init::init_flood_sockets(
+            new_config.transport,
+            new_config.qos.dscp,
+            state.interfaces.local_ipv4_addresses(),
+            state.vrf.as_deref(),
+        )?;
+        *state.flood_sockets.write().await = flood_sockets;
+    }
+
+    if diff.key_changed {
+        *state.key.write().await = new_config.decoded_key();
+        *state.interface_keys.write().await = new_config.interface_keys();
+    }
+    if new_config.trace != old_config.trace {
+        crate::trace::configure(new_config.trace.enabled, new_config.trace.capacity);
+    }
+    if new_config.history != old_config.history {
+        *state.history.lock().await = crate::history::open(&new_config.history);
+    }
+    let new_path_selection = new_config.path_selection;
+    let new_role = new_config.role;
+    crate::neighbor::sync_interface_states(state, &new_config, &diff).await;
+    *state.config.write().await = new_config;
+
+    // Rompre les adjacences apprises sur les interfaces supprimées
+    let mut topology_changed = !diff.modified.is_empty() || old_config.path_selection != new_path_selection
+        || old_config.role != new_role;
+    if !diff.removed.is_empty() {
+        let mut neighbors = state.neighbors.write().await;
+        neighbors.retain(|ip, neighbor| {
+            let interface = neighbor.interface.clone().or_else(|| state.interfaces.name_for_address(ip));
+            let keep = interface.as_ref().is_none_or(|name| !diff.removed.contains(name));
+            if !keep {
+                warn!("Neighbor {} removed: interface {:?} no longer configured", ip, interface);
+                topology_changed = true;
+            }
+            keep
+        });
+    }
+
+    if topology_changed {
+        if let Err(e) = crate::dijkstra::calculate_and_update_optimal_routes(Arc::clone(state)).await {
+            warn!("Échec du recalcul des routes après rechargement: {}", e);
+        }
+    }
+
+    let summary = diff.summary();
+    info!("{}", summary);
+    Ok(summary)
+}
@@ -0,0 +1,34 @@
+use routing_project::history;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: replay <journal> [instant]   (instant : secondes Unix ou HH:MM[:SS] UTC)";
+
+/// Relit le journal d'un routeur (section `[history]`) : sans instant, toutes ses entrées ;
+/// avec un instant, la LSDB et la table de routage reconstruites à ce moment
+fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let (path, instant) = match &args[..] {
+        [path] => (PathBuf::from(path), None),
+        [path, instant] => (PathBuf::from(path), Some(instant)),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let records = history::read(&path)?;
+    let Some(instant) = instant else {
+        println!("{}", history::recent_report(&records, records.len()));
+        return Ok(());
+    };
+    let Some(at_ms) = history::parse_time(instant, &records) else {
+        eprintln!("Instant invalide: '{}'\n{}", instant, USAGE);
+        std::process::exit(1);
+    };
+    println!("{}", history::snapshot_report(&history::replay(&records, at_ms)));
+    Ok(())
+}
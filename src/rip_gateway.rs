@@ -0,0 +1,489 @@
+use crate::error::{AppError, Result};
+use crate::rip::{Command, RipEntry, RipPacket, INFINITY, MAX_ENTRIES};
+use crate::types::{Prefix, RouteState};
+use pnet::ipnetwork::Ipv4Network;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Interface parlant RIPv2
+#[derive(Debug, Clone, PartialEq)]
+pub struct RipInterface {
+    pub name: String,
+    pub address: Ipv4Addr,
+    pub network: Ipv4Network,
+}
+
+/// Temporisations RIP (RFC 2453, 3.8)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RipTimers {
+    pub update: Duration,
+    pub timeout: Duration,
+    pub garbage: Duration,
+}
+
+impl From<&crate::read_config::RipConfig> for RipTimers {
+    fn from(config: &crate::read_config::RipConfig) -> Self {
+        Self {
+            update: Duration::from_secs(config.update_interval_sec),
+            timeout: Duration::from_secs(config.timeout_sec),
+            garbage: Duration::from_secs(config.garbage_sec),
+        }
+    }
+}
+
+/// Message à émettre sur une interface, vers RIP_ROUTERS ou en unicast
+#[derive(Debug, Clone)]
+pub struct RipOutgoing {
+    pub interface: usize,
+    pub target: Option<SocketAddrV4>,
+    pub packet: RipPacket,
+}
+
+/// Changement à répercuter sur l'état du routeur
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RipEvent {
+    NeighborUp { interface: String, address: Ipv4Addr },
+    NeighborDown { interface: String, address: Ipv4Addr },
+    RoutesChanged,
+}
+
+#[derive(Debug, Clone)]
+struct LearnedRoute {
+    metric: u32,
+    /// Routeur RIP ayant annoncé la route (le prochain saut explicite n'est pas utilisé)
+    router: Ipv4Addr,
+    interface: usize,
+    updated_at: Instant,
+    /// Instant où la route est devenue injoignable, avant son retrait
+    expired_at: Option<Instant>,
+}
+
+/// Routes apprises des routeurs RIP et routes du domaine P-OSPF à leur annoncer, sans
+/// entrées/sorties (la tâche `spawn_rip_task` les assure)
+#[derive(Debug)]
+pub struct RipGateway {
+    interfaces: Vec<RipInterface>,
+    timers: RipTimers,
+    routes: BTreeMap<Prefix, LearnedRoute>,
+    /// Routes du domaine P-OSPF en nombre de sauts
+    exported: BTreeMap<Prefix, u32>,
+    /// Routes du domaine retirées, annoncées injoignables à la prochaine mise à jour
+    withdrawn: BTreeSet<Prefix>,
+    neighbors: BTreeMap<Ipv4Addr, (usize, Instant)>,
+    last_update: Option<Instant>,
+    triggered: bool,
+    outbox: Vec<RipOutgoing>,
+    events: Vec<RipEvent>,
+}
+
+impl RipGateway {
+    pub fn new(interfaces: Vec<RipInterface>, timers: RipTimers) -> Self {
+        Self {
+            interfaces,
+            timers,
+            routes: BTreeMap::new(),
+            exported: BTreeMap::new(),
+            withdrawn: BTreeSet::new(),
+            neighbors: BTreeMap::new(),
+            last_update: None,
+            triggered: false,
+            outbox: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn interfaces(&self) -> impl Iterator<Item = &RipInterface> {
+        self.interfaces.iter()
+    }
+
+    /// Routeurs RIP entendus depuis moins de `timeout` (interface, adresse)
+    pub fn neighbors(&self) -> Vec<(String, Ipv4Addr)> {
+        self.neighbors.iter()
+            .map(|(address, (interface, _))| (self.interfaces[*interface].name.clone(), *address))
+            .collect()
+    }
+
+    /// Messages à émettre et événements produits depuis le dernier appel
+    pub fn take_output(&mut self) -> (Vec<RipOutgoing>, Vec<RipEvent>) {
+        (std::mem::take(&mut self.outbox), std::mem::take(&mut self.events))
+    }
+
+    /// Remplace les routes du domaine P-OSPF annoncées, en nombre de sauts
+    pub fn set_exported(&mut self, exported: BTreeMap<Prefix, u32>) {
+        if exported == self.exported {
+            return;
+        }
+        self.withdrawn.extend(self.exported.keys().filter(|prefix| !exported.contains_key(prefix)));
+        self.withdrawn.retain(|prefix| !exported.contains_key(prefix));
+        self.exported = exported;
+        self.triggered = true;
+    }
+
+    /// Traite un message reçu sur une interface
+    pub fn receive(&mut self, interface: usize, source: SocketAddrV4, data: &[u8], now: Instant) -> Result<()> {
+        let packet = RipPacket::decode(data)?;
+        let Some(params) = self.interfaces.get(interface) else {
+            return Err(AppError::NetworkError(format!("unknown RIP interface {}", interface)));
+        };
+        if *source.ip() == params.address || !params.network.contains(*source.ip()) {
+            return Ok(());
+        }
+        match packet.command {
+            Command::Request => {
+                let entries = if packet.entries.is_empty() {
+                    self.advertised(interface)
+                } else {
+                    packet.entries.into_iter()
+                        .map(|entry| RipEntry { metric: self.metric_for(&entry.prefix, interface), ..entry })
+                        .collect()
+                };
+                self.queue(interface, Some(source), entries);
+            }
+            // Seules les réponses émises depuis le port RIP font foi (RFC 2453, 3.9.2)
+            Command::Response if source.port() == crate::rip::PORT => {
+                let router = *source.ip();
+                if self.neighbors.insert(router, (interface, now)).is_none() {
+                    info!("RIP router {} heard on {}", router, params.name);
+                    self.events.push(RipEvent::NeighborUp { interface: params.name.clone(), address: router });
+                }
+                for entry in packet.entries {
+                    self.learn(interface, router, entry, now);
+                }
+            }
+            Command::Response => debug!("Ignoring RIP response from port {}", source.port()),
+        }
+        Ok(())
+    }
+
+    fn learn(&mut self, interface: usize, router: Ipv4Addr, entry: RipEntry, now: Instant) {
+        let prefix = Prefix::new(entry.prefix.network());
+        // Nos réseaux connectés ne s'apprennent pas
+        if self.interfaces.iter().any(|iface| Prefix::new(iface.network).network() == prefix.network()) {
+            return;
+        }
+        let metric = (entry.metric + 1).min(INFINITY);
+        let changed = match self.routes.get_mut(&prefix) {
+            Some(route) if route.router == router => {
+                let changed = route.metric != metric;
+                if metric < INFINITY {
+                    route.updated_at = now;
+                    route.expired_at = None;
+                } else if route.metric < INFINITY {
+                    route.expired_at = Some(now);
+                }
+                route.metric = metric;
+                changed
+            }
+            Some(route) if metric >= route.metric => false,
+            _ if metric >= INFINITY => false,
+            _ => {
+                self.routes.insert(prefix, LearnedRoute { metric, router, interface, updated_at: now, expired_at: None });
+                true
+            }
+        };
+        if changed {
+            self.triggered = true;
+            self.events.push(RipEvent::RoutesChanged);
+        }
+    }
+
+    /// Temporisations : expiration des routes et des routeurs, mises à jour périodiques
+    /// et déclenchées
+    pub fn tick(&mut self, now: Instant) {
+        for route in self.routes.values_mut() {
+            if route.metric < INFINITY && now.saturating_duration_since(route.updated_at) > self.timers.timeout {
+                route.metric = INFINITY;
+                route.expired_at = Some(now);
+                self.triggered = true;
+                self.events.push(RipEvent::RoutesChanged);
+            }
+        }
+        let garbage = self.timers.garbage;
+        self.routes.retain(|_, route| route.expired_at.is_none_or(|expired| now.saturating_duration_since(expired) < garbage));
+        let timeout = self.timers.timeout;
+        let interfaces = &self.interfaces;
+        let events = &mut self.events;
+        self.neighbors.retain(|address, (interface, heard)| {
+            let alive = now.saturating_duration_since(*heard) <= timeout;
+            if !alive {
+                warn!("RIP router {} silent for {:?}", address, timeout);
+                events.push(RipEvent::NeighborDown { interface: interfaces[*interface].name.clone(), address: *address });
+            }
+            alive
+        });
+
+        let periodic = self.last_update.is_none_or(|sent| now.saturating_duration_since(sent) >= self.timers.update);
+        if periodic || self.triggered {
+            if periodic {
+                self.last_update = Some(now);
+            }
+            for interface in 0..self.interfaces.len() {
+                let entries = self.advertised(interface);
+                self.queue(interface, None, entries);
+            }
+            self.withdrawn.clear();
+            self.triggered = false;
+        }
+    }
+
+    /// Métrique annoncée sur une interface (horizon partagé : jamais vers l'interface
+    /// d'où vient la route)
+    fn metric_for(&self, prefix: &Prefix, interface: usize) -> u32 {
+        let prefix = Prefix::new(prefix.network());
+        if let Some(hops) = self.exported.get(&prefix) {
+            return *hops;
+        }
+        self.routes.get(&prefix)
+            .filter(|route| route.interface != interface)
+            .map_or(INFINITY, |route| route.metric)
+    }
+
+    /// Table annoncée sur une interface : routes du domaine P-OSPF, routes RIP apprises sur
+    /// les autres interfaces (horizon partagé) et retraits en attente
+    fn advertised(&self, interface: usize) -> Vec<RipEntry> {
+        let entry = |prefix: &Prefix, metric: u32| RipEntry { prefix: *prefix, tag: 0, next_hop: Ipv4Addr::UNSPECIFIED, metric };
+        let mut entries: Vec<RipEntry> = self.exported.iter().map(|(prefix, hops)| entry(prefix, *hops)).collect();
+        entries.extend(self.routes.iter()
+            .filter(|(prefix, route)| route.interface != interface && !self.exported.contains_key(prefix))
+            .map(|(prefix, route)| entry(prefix, route.metric)));
+        entries.extend(self.withdrawn.iter().map(|prefix| entry(prefix, INFINITY)));
+        entries
+    }
+
+    fn queue(&mut self, interface: usize, target: Option<SocketAddrV4>, entries: Vec<RipEntry>) {
+        for chunk in entries.chunks(MAX_ENTRIES) {
+            self.outbox.push(RipOutgoing {
+                interface,
+                target,
+                packet: RipPacket { command: Command::Response, entries: chunk.to_vec() },
+            });
+        }
+    }
+
+    /// Routes joignables apprises de chaque routeur RIP, en nombre de sauts depuis ce routeur
+    pub fn routes(&self) -> HashMap<Ipv4Addr, HashMap<Prefix, u32>> {
+        let mut routes: HashMap<Ipv4Addr, HashMap<Prefix, u32>> = HashMap::new();
+        for (prefix, route) in self.routes.iter().filter(|(_, route)| route.metric < INFINITY) {
+            routes.entry(route.router).or_default().insert(*prefix, route.metric - 1);
+        }
+        routes
+    }
+}
+
+/// Nombre de sauts RIP annoncé pour un coût P-OSPF (arrondi au saut supérieur, de 1 à 15)
+pub fn hops_for_cost(cost: u32, cost_per_hop: u32) -> u32 {
+    cost.div_ceil(cost_per_hop.max(1)).clamp(1, INFINITY - 1)
+}
+
+/// Interfaces configurées en RIP et présentes sur le système
+pub async fn rip_interfaces(state: &crate::AppState) -> Vec<RipInterface> {
+    let config = state.config().await;
+    config.interfaces.iter()
+        .filter(|iface| iface.protocol == crate::read_config::InterfaceProtocol::Rip)
+        .filter_map(|iface| {
+            let local = state.interfaces.interfaces().into_iter().find(|local| local.name == iface.name)?;
+            Some(RipInterface { name: iface.name.clone(), address: local.ip(), network: local.network })
+        })
+        .collect()
+}
+
+/// Routes du domaine annoncées aux routeurs RIP : réseaux connectés hors RIP et routes
+/// de la table, sauf celles apprises des routeurs RIP eux-mêmes
+async fn exported_routes(state: &crate::AppState, gateway: &RipGateway) -> BTreeMap<Prefix, u32> {
+    let config = state.config().await;
+    let mut exported: BTreeMap<Prefix, u32> = state.interfaces.interfaces().iter()
+        .filter(|iface| config.is_interface_configured(&iface.name) && config.interface_protocol(Some(&iface.name)) != crate::read_config::InterfaceProtocol::Rip)
+        .map(|iface| (Prefix::new(Prefix::new(iface.network).network()), 1))
+        .collect();
+    let rip_routers: Vec<String> = gateway.neighbors.keys().map(|address| address.to_string()).collect();
+    let routing_table = state.routing_table.read().await;
+    let origins = state.route_origins.read().await;
+    for (prefix, (_, route_state)) in routing_table.iter() {
+        if origins.get(prefix).is_some_and(|origin| rip_routers.contains(&origin.originator)) {
+            continue;
+        }
+        if let Some(metric) = route_state.metric() {
+            exported.entry(Prefix::new(prefix.network())).or_insert(hops_for_cost(metric, config.rip.cost_per_hop));
+        }
+    }
+    exported
+}
+
+/// Reporte les routeurs et routes RIP dans l'état du routeur : un routeur RIP devient un
+/// voisin P-OSPF, et ses routes un LSA routeur à son adresse. `installed` retient, par
+/// routeur, le dernier numéro de séquence installé et si son LSA est encore annoncé.
+async fn apply_events(state: &std::sync::Arc<crate::AppState>, gateway: &RipGateway, events: Vec<RipEvent>, installed: &mut HashMap<Ipv4Addr, (u32, bool)>) {
+    let mut changed = false;
+    for event in events {
+        match event {
+            RipEvent::NeighborUp { interface, address } => {
+                let local_ip = gateway.interfaces().find(|iface| iface.name == interface).map(|iface| iface.address.to_string());
+                crate::neighbor::update_neighbor(state, &address.to_string(), &local_ip.unwrap_or_default()).await;
+                state.metrics.rip_neighbors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                changed = true;
+            }
+            RipEvent::NeighborDown { address, .. } => {
+                let mut neighbors = state.neighbors.write().await;
+                if let Some(neighbor) = neighbors.get_mut(&address.to_string()).filter(|neighbor| neighbor.link_up) {
+                    neighbor.link_up = false;
+                    drop(neighbors);
+                    crate::neighbor_stats::publish(state, crate::events::RouterEvent::NeighborDown {
+                        neighbor: address.to_string(),
+                        reason: "RIP timeout".to_string(),
+                    }).await;
+                }
+                state.metrics.rip_neighbors.fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+                changed = true;
+            }
+            RipEvent::RoutesChanged => changed = true,
+        }
+    }
+
+    // Les routeurs RIP entendus restent vivants côté P-OSPF
+    let now = crate::clock::unix_secs();
+    let alive = gateway.neighbors();
+    let mut neighbors = state.neighbors.write().await;
+    for (_, address) in &alive {
+        if let Some(neighbor) = neighbors.get_mut(&address.to_string()) {
+            neighbor.last_seen = now;
+        }
+    }
+    drop(neighbors);
+    if !changed {
+        return;
+    }
+
+    let cost_per_hop = state.config().await.rip.cost_per_hop;
+    let mut learned = gateway.routes();
+    let routers: BTreeSet<Ipv4Addr> = alive.iter().map(|(_, address)| *address)
+        .chain(installed.iter().filter(|(_, (_, active))| *active).map(|(router, _)| *router))
+        .collect();
+    let mut lsdb = state.lsdb.write().await;
+    for router in &routers {
+        let routes = learned.remove(router).unwrap_or_default();
+        let (seq_num, active) = installed.entry(*router).or_insert((0, true));
+        *seq_num += 1;
+        *active = alive.iter().any(|(_, address)| address == router);
+        let message = crate::types::LSAMessage {
+            message_type: 2,
+            router_ip: router.to_string(),
+            last_hop: None,
+            originator: router.to_string(),
+            router_id: None,
+            seq_num: *seq_num,
+            neighbor_count: 0,
+            neighbors: Vec::new(),
+            routing_table: routes.iter().map(|(prefix, hops)| (*prefix, RouteState::Active(hops * cost_per_hop))).collect(),
+            external_routes: HashMap::new(),
+            external_metric_types: HashMap::new(),
+            route_tags: HashMap::new(),
+            path: Vec::new(),
+            ttl: 0,
+            stub_only: false,
+            base_seq: None,
+            withdrawn: Vec::new(),
+            opaque: Vec::new(),
+            flush: false,
+            checksum: None,
+            part: None,
+        };
+        lsdb.install(&message);
+    }
+    drop(lsdb);
+    state.metrics.spf_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    state.spf.schedule();
+}
+
+/// Socket UDP du port RIP, abonnée à RIP_ROUTERS sur chaque interface
+fn open_socket(interfaces: &[RipInterface]) -> Result<tokio::net::UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.bind(&std::net::SocketAddr::from((Ipv4Addr::UNSPECIFIED, crate::rip::PORT)).into())?;
+    socket.set_multicast_ttl_v4(1)?;
+    socket.set_multicast_loop_v4(false)?;
+    socket.set_tos(0xc0)?;
+    for iface in interfaces {
+        socket.join_multicast_v4(&crate::rip::RIP_ROUTERS, &iface.address)?;
+    }
+    socket.set_nonblocking(true)?;
+    Ok(tokio::net::UdpSocket::from_std(std::net::UdpSocket::from(socket))?)
+}
+
+/// Passerelle RIPv2 sur les interfaces configurées avec `protocol = "rip"`
+pub fn spawn_rip_task(state: std::sync::Arc<crate::AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let interfaces = rip_interfaces(&state).await;
+        if interfaces.is_empty() {
+            warn!("No RIP interface found on this system");
+            return;
+        }
+        let socket = match open_socket(&interfaces) {
+            Ok(socket) => socket,
+            Err(e) => {
+                tracing::error!("Failed to open the RIP socket on port {}: {}", crate::rip::PORT, e);
+                return;
+            }
+        };
+        let socket2 = socket2::SockRef::from(&socket);
+        info!("RIP gateway on {:?}", interfaces.iter().map(|iface| &iface.name).collect::<Vec<_>>());
+        let mut gateway = RipGateway::new(interfaces, RipTimers::from(&state.config().await.rip));
+        let mut installed = HashMap::new();
+        let mut tick = tokio::time::interval(Duration::from_secs(1));
+        let mut buffer = vec![0u8; 65_535];
+        loop {
+            tokio::select! {
+                _ = tick.tick() => {
+                    if !state.is_enabled().await {
+                        continue;
+                    }
+                    let exported = exported_routes(&state, &gateway).await;
+                    gateway.set_exported(exported);
+                    gateway.tick(Instant::now());
+                }
+                received = socket.recv_from(&mut buffer) => {
+                    let (len, source) = match received {
+                        Ok(received) => received,
+                        Err(e) => {
+                            warn!("RIP receive error: {}", e);
+                            continue;
+                        }
+                    };
+                    let std::net::SocketAddr::V4(source) = source else {
+                        continue;
+                    };
+                    let Some(index) = gateway.interfaces().position(|iface| iface.network.contains(*source.ip())) else {
+                        continue;
+                    };
+                    if !state.is_enabled().await {
+                        continue;
+                    }
+                    if let Err(e) = gateway.receive(index, source, &buffer[..len], Instant::now()) {
+                        debug!("Dropping RIP message from {}: {}", source, e);
+                        state.metrics.packets_rejected.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    }
+                }
+            }
+
+            let (messages, events) = gateway.take_output();
+            for outgoing in messages {
+                let Some(iface) = gateway.interfaces().nth(outgoing.interface) else {
+                    continue;
+                };
+                let target = outgoing.target.unwrap_or(SocketAddrV4::new(crate::rip::RIP_ROUTERS, crate::rip::PORT));
+                let sent = match socket2.set_multicast_if_v4(&iface.address) {
+                    Ok(()) => socket.send_to(&outgoing.packet.encode(), target).await.map(|_| ()),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = sent {
+                    warn!("Failed to send RIP message on {}: {}", iface.name, e);
+                }
+            }
+            apply_events(&state, &gateway, events, &mut installed).await;
+        }
+    })
+}
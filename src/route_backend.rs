@@ -0,0 +1,135 @@
+use std::fmt::Debug;
+use std::net::Ipv4Addr;
+use std::sync::{Arc, Mutex};
+use futures::future::BoxFuture;
+use tracing::info;
+use pnet::ipnetwork::Ipv4Network;
+use crate::error::Result;
+
+/// Origine d'une route (rtm_protocol de Linux) : les autres systèmes s'y ramènent
+pub const RTPROT_KERNEL: u8 = 2;
+pub const RTPROT_BOOT: u8 = 3;
+pub const RTPROT_STATIC: u8 = 4;
+
+/// Route IPv4 de la table principale du système
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KernelRoute {
+    pub prefix: Ipv4Network,
+    pub gateway: Option<Ipv4Addr>,
+    /// Index de l'interface de sortie
+    pub output_interface: Option<u32>,
+    /// Protocole d'origine : static, kernel, boot ou celui d'un démon
+    pub protocol: u8,
+    pub priority: Option<u32>,
+}
+
+/// Accès à la table de routage du système
+pub trait RouteBackend: Debug + Send + Sync {
+    /// Nom affiché dans les journaux
+    fn name(&self) -> &'static str;
+
+    /// Routes IPv4 de la table principale
+    fn list_routes(&self) -> BoxFuture<'_, Result<Vec<KernelRoute>>>;
+
+    /// Installe ou remplace une route (même préfixe, protocole et priorité)
+    fn install_route(&self, route: KernelRoute) -> BoxFuture<'_, Result<()>>;
+
+    /// Supprime une route précédemment listée
+    fn delete_route(&self, route: KernelRoute) -> BoxFuture<'_, Result<()>>;
+}
+
+/// Table `table` du système courant : netlink sous Linux, API de routage native ailleurs
+/// (table principale seulement)
+pub fn system_backend(table: u32) -> Arc<dyn RouteBackend> {
+    #[cfg(target_os = "linux")]
+    {
+        Arc::new(crate::kernel_routes::NetlinkBackend::new(table))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        if table != crate::read_config::MAIN_ROUTE_TABLE {
+            tracing::warn!("Routing table {} is only supported on Linux, using the main table", table);
+        }
+        Arc::new(crate::portable_routes::NetRouteBackend::new())
+    }
+}
+
+/// Table fictive : les modifications sont journalisées et appliquées à une copie en
+/// mémoire, sans jamais toucher au système dont les routes restent lisibles si `system` est fourni
+#[derive(Debug, Default)]
+pub struct DryRunBackend {
+    system: Option<Arc<dyn RouteBackend>>,
+    overlay: Mutex<Overlay>,
+}
+
+#[derive(Debug, Default)]
+struct Overlay {
+    installed: Vec<KernelRoute>,
+    /// Routes du système que l'on aurait supprimées
+    removed: Vec<KernelRoute>,
+}
+
+fn same_slot(a: &KernelRoute, b: &KernelRoute) -> bool {
+    a.prefix == b.prefix && a.protocol == b.protocol && a.priority == b.priority
+}
+
+impl DryRunBackend {
+    /// Table entièrement en mémoire (tests, routeurs simulés)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lit les routes de `system` mais n'y écrit jamais
+    pub fn over(system: Arc<dyn RouteBackend>) -> Self {
+        Self { system: Some(system), overlay: Mutex::default() }
+    }
+
+    fn overlay(&self) -> std::sync::MutexGuard<'_, Overlay> {
+        self.overlay.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl RouteBackend for DryRunBackend {
+    fn name(&self) -> &'static str {
+        "dry-run"
+    }
+
+    fn list_routes(&self) -> BoxFuture<'_, Result<Vec<KernelRoute>>> {
+        Box::pin(async move {
+            let mut routes = match &self.system {
+                Some(system) => system.list_routes().await?,
+                None => Vec::new(),
+            };
+            let overlay = self.overlay();
+            routes.retain(|route| {
+                !overlay.removed.contains(route) && !overlay.installed.iter().any(|own| same_slot(own, route))
+            });
+            routes.extend(overlay.installed.iter().cloned());
+            Ok(routes)
+        })
+    }
+
+    fn install_route(&self, route: KernelRoute) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            info!("[DRY-RUN] Would install route to {} via {:?} (dev {:?}, proto {}, metric {:?})",
+                  route.prefix, route.gateway, route.output_interface, route.protocol, route.priority);
+            let mut overlay = self.overlay();
+            overlay.installed.retain(|existing| !same_slot(existing, &route));
+            overlay.installed.push(route);
+            Ok(())
+        })
+    }
+
+    fn delete_route(&self, route: KernelRoute) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            info!("[DRY-RUN] Would remove route to {} via {:?} (proto {})", route.prefix, route.gateway, route.protocol);
+            let mut overlay = self.overlay();
+            let installed = overlay.installed.len();
+            overlay.installed.retain(|existing| *existing != route);
+            if overlay.installed.len() == installed {
+                overlay.removed.push(route);
+            }
+            Ok(())
+        })
+    }
+}
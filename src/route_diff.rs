@@ -0,0 +1,90 @@
+use crate::nexthop::NextHop;
+use crate::read_config::RouteInstallConfig;
+use crate::types::{Prefix, RouteState};
+use std::collections::HashMap;
+
+/// Écart entre la table de routage précédente et celle issue d'un calcul SPF
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RouteDiff {
+    pub added: Vec<Prefix>,
+    pub removed: Vec<Prefix>,
+    /// Préfixes dont le prochain saut ou la métrique a changé
+    pub modified: Vec<Prefix>,
+}
+
+impl RouteDiff {
+    pub fn between(old: &HashMap<Prefix, (String, RouteState)>, new: &HashMap<Prefix, (String, RouteState)>) -> Self {
+        let mut diff = Self::default();
+        for (prefix, route) in new {
+            match old.get(prefix) {
+                None => diff.added.push(*prefix),
+                Some(previous) if previous != route => diff.modified.push(*prefix),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old.keys().filter(|prefix| !new.contains_key(*prefix)).copied().collect();
+        diff.added.sort();
+        diff.removed.sort();
+        diff.modified.sort();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    /// Nombre de préfixes ajoutés, retirés ou modifiés
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.modified.len()
+    }
+
+    /// Résumé journalisé après chaque calcul : « 3 added, 1 removed, 2 modified »
+    pub fn summary(&self) -> String {
+        format!("{} added, {} removed, {} modified", self.added.len(), self.removed.len(), self.modified.len())
+    }
+}
+
+/// Paramètres qui déterminent la forme des routes du noyau (activation, protocole,
+/// métrique, protection des routes tierces, mode simulé)
+type Placement = (bool, u8, u32, bool, bool);
+
+fn placement(install: &RouteInstallConfig) -> Placement {
+    (install.enabled, install.protocol, install.distance, install.protect_foreign, install.dry_run)
+}
+
+/// Routes déjà programmées dans le noyau avec leur prochain saut résolu : un calcul SPF
+/// ne reprogramme que celles dont le prochain saut a changé
+#[derive(Debug, Default)]
+pub struct ProgrammedRoutes {
+    /// Paramètres d'installation sous lesquels les routes ont été programmées
+    install: Option<Placement>,
+    routes: HashMap<Prefix, NextHop>,
+}
+
+impl ProgrammedRoutes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Routes programmées sous `install` ; tout est à reprogrammer si la configuration
+    /// d'installation a changé depuis (activation, protocole, distance...)
+    pub fn snapshot(&mut self, install: &RouteInstallConfig) -> HashMap<Prefix, NextHop> {
+        if self.install != Some(placement(install)) {
+            self.install = Some(placement(install));
+            self.routes.clear();
+        }
+        self.routes.clone()
+    }
+
+    pub fn record(&mut self, prefix: Prefix, next_hop: NextHop) {
+        self.routes.insert(prefix, next_hop);
+    }
+
+    pub fn forget(&mut self, prefix: &Prefix) {
+        self.routes.remove(prefix);
+    }
+
+    pub fn clear(&mut self) {
+        self.routes.clear();
+    }
+}
@@ -0,0 +1,148 @@
+use crate::error::{AppError, Result};
+use crate::types::{Prefix, RouteRequest, RouteResponse, RouteState};
+use crate::AppState;
+use rand::{rngs::OsRng, RngCore};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::{debug, info, warn};
+
+/// Délai d'attente de la réponse d'un routeur interrogé
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Requêtes de route envoyées par ce routeur, en attente de leur réponse
+#[derive(Debug, Default)]
+pub struct PendingQueries {
+    pending: HashMap<u64, oneshot::Sender<RouteResponse>>,
+}
+
+impl PendingQueries {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn register(&mut self, id: u64) -> oneshot::Receiver<RouteResponse> {
+        let (sender, receiver) = oneshot::channel();
+        self.pending.insert(id, sender);
+        receiver
+    }
+
+    fn complete(&mut self, response: RouteResponse) -> bool {
+        match self.pending.remove(&response.id) {
+            Some(sender) => sender.send(response).is_ok(),
+            None => false,
+        }
+    }
+
+    fn cancel(&mut self, id: u64) {
+        self.pending.remove(&id);
+    }
+}
+
+/// Route de ce routeur vers `destination` : réseau directement connecté, sinon route la
+/// plus spécifique de la table de routage
+pub async fn lookup(state: &Arc<AppState>, id: u64, destination: Prefix) -> RouteResponse {
+    let router_id = state.router_id.to_string();
+    let mut response = RouteResponse {
+        message_type: 11,
+        id,
+        router_id: router_id.clone(),
+        destination,
+        prefix: None,
+        next_hop: None,
+        metric: None,
+        path: Vec::new(),
+        reachable: false,
+    };
+    if let Some(iface) = state.interfaces.interfaces().into_iter().find(|iface| Prefix::new(iface.network).covers(&destination)) {
+        response.prefix = Some(Prefix::new(iface.network));
+        response.metric = Some(0);
+        response.path = vec![router_id];
+        response.reachable = true;
+        return response;
+    }
+    let routing_table = state.routing_table.read().await;
+    let best = routing_table.iter()
+        .filter(|(prefix, _)| prefix.covers(&destination))
+        .max_by(|a, b| a.0.prefix_len().cmp(&b.0.prefix_len()).then(b.0.cmp(a.0)));
+    if let Some((prefix, (next_hop, route_state))) = best {
+        response.prefix = Some(*prefix);
+        response.next_hop = Some(next_hop.clone());
+        response.metric = route_state.metric();
+        response.reachable = !matches!(route_state, RouteState::Unreachable);
+        response.path = state.route_origins.read().await.get(prefix).map(|origin| origin.path.clone()).unwrap_or_default();
+    }
+    response
+}
+
+/// Répond à une requête de route reçue de `src_addr`, si la configuration `[control]`
+/// autorise les consultations anonymes
+pub async fn handle_request(
+    state: &Arc<AppState>,
+    socket: &crate::transport::Socket,
+    request: RouteRequest,
+    src_addr: SocketAddr,
+    key: &crate::net_utils::SegmentKey,
+) {
+    if state.config().await.control.anonymous_role().is_none() {
+        debug!("[QUERY] Route request from {} ignored: anonymous queries are disabled", src_addr);
+        return;
+    }
+    let response = lookup(state, request.id, request.destination).await;
+    if let Err(e) = crate::net_utils::send_message(socket, &src_addr, &response, key, "[QUERY]").await {
+        warn!("[QUERY] Failed to answer route request from {}: {}", src_addr, e);
+    }
+}
+
+/// Remet une réponse reçue à la requête en attente
+pub async fn handle_response(state: &Arc<AppState>, response: RouteResponse) {
+    if !state.route_queries.lock().await.complete(response.clone()) {
+        debug!("[QUERY] Late or unknown route response {} from {}", response.id, response.router_id);
+    }
+}
+
+/// Interroge le routeur d'adresse `router` sur sa route vers `destination`
+pub async fn query(state: &Arc<AppState>, router: Ipv4Addr, destination: Prefix) -> Result<RouteResponse> {
+    let id = OsRng.next_u64();
+    let request = RouteRequest { message_type: 10, id, destination };
+    let receiver = state.route_queries.lock().await.register(id);
+    let key = state.key_for(&router.to_string()).await;
+    let addr = SocketAddr::new(router.into(), state.port);
+    if let Err(e) = crate::net_utils::send_message(&state.socket, &addr, &request, &key, "[QUERY]").await {
+        state.route_queries.lock().await.cancel(id);
+        return Err(e);
+    }
+    match tokio::time::timeout(QUERY_TIMEOUT, receiver).await {
+        Ok(Ok(response)) => {
+            info!("[QUERY] {} answered for {}: {:?} via {:?}", router, destination, response.prefix, response.next_hop);
+            Ok(response)
+        }
+        _ => {
+            state.route_queries.lock().await.cancel(id);
+            Err(AppError::NetworkError(format!("no answer from {} within {} s", router, QUERY_TIMEOUT.as_secs())))
+        }
+    }
+}
+
+/// Réponse d'un routeur présentée par la commande `path <destination> <routeur>`
+pub fn describe(response: &RouteResponse) -> String {
+    let Some(prefix) = response.prefix else {
+        return format!("{} n'a aucune route vers {}", response.router_id, response.destination);
+    };
+    if !response.reachable {
+        return format!("{} : route vers {} injoignable", response.router_id, prefix);
+    }
+    let Some(next_hop) = &response.next_hop else {
+        return format!("{} : {} est directement connecté", response.router_id, prefix);
+    };
+    format!(
+        "{} : {} via {} (métrique {}), chemin: {}",
+        response.router_id,
+        prefix,
+        next_hop,
+        response.metric.map_or("?".to_string(), |metric| metric.to_string()),
+        response.path.join(" -> "),
+    )
+}
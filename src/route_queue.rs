@@ -0,0 +1,262 @@
+use crate::nexthop::NextHop;
+use crate::read_config::RouteInstallConfig;
+use crate::types::Prefix;
+use crate::AppState;
+use crate::events::RouterEvent;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, MutexGuard, Notify};
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Opération à appliquer à la table de routage du système
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteOp {
+    Install { next_hop: NextHop },
+    Remove,
+}
+
+impl std::fmt::Display for RouteOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RouteOp::Install { next_hop } => write!(f, "install via {}", next_hop),
+            RouteOp::Remove => write!(f, "remove"),
+        }
+    }
+}
+
+/// Opération en attente d'exécution ou de nouvel essai
+#[derive(Debug, Clone)]
+pub struct PendingOp {
+    pub op: RouteOp,
+    /// Essais déjà effectués
+    pub attempts: u32,
+    /// Prochain essai ; `None` une fois les essais épuisés
+    pub next_attempt: Option<Instant>,
+    pub last_error: Option<String>,
+    /// Distingue une opération de celle qui l'a remplacée pendant son exécution
+    generation: u64,
+}
+
+/// File des opérations de routes, au plus une par préfixe : une opération plus récente
+/// remplace celle en attente, une opération identique conserve ses essais et son attente
+#[derive(Debug, Default)]
+pub struct RouteQueue {
+    pending: BTreeMap<Prefix, PendingOp>,
+    generation: u64,
+}
+
+impl RouteQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Met en file une opération ; renvoie faux si la même opération était déjà en attente
+    pub fn push(&mut self, prefix: Prefix, op: RouteOp) -> bool {
+        if self.pending.get(&prefix).is_some_and(|pending| pending.op == op) {
+            return false;
+        }
+        self.generation += 1;
+        self.pending.insert(prefix, PendingOp {
+            op,
+            attempts: 0,
+            next_attempt: Some(Instant::now()),
+            last_error: None,
+            generation: self.generation,
+        });
+        true
+    }
+
+    /// Abandonne l'opération en attente pour `prefix` (route déjà dans l'état voulu)
+    pub fn cancel(&mut self, prefix: &Prefix) -> bool {
+        self.pending.remove(prefix).is_some()
+    }
+
+    /// Opérations dont l'essai est dû, avec leur génération
+    pub fn due(&self, now: Instant) -> Vec<(Prefix, RouteOp, u64)> {
+        self.pending.iter()
+            .filter(|(_, pending)| pending.next_attempt.is_some_and(|at| at <= now))
+            .map(|(prefix, pending)| (*prefix, pending.op.clone(), pending.generation))
+            .collect()
+    }
+
+    /// Prochain essai prévu parmi les opérations non épuisées
+    pub fn next_due(&self) -> Option<Instant> {
+        self.pending.values().filter_map(|pending| pending.next_attempt).min()
+    }
+
+    /// Retire une opération réussie, sauf si elle a été remplacée entre-temps
+    pub fn succeeded(&mut self, prefix: &Prefix, generation: u64) {
+        if self.pending.get(prefix).is_some_and(|pending| pending.generation == generation) {
+            self.pending.remove(prefix);
+        }
+    }
+
+    /// Note un échec et planifie le nouvel essai : attente doublée à chaque échec depuis
+    /// `retry_initial_ms`, plafonnée à `retry_max_ms`, jusqu'à `max_attempts` essais.
+    /// Renvoie l'attente avant le prochain essai, `None` si les essais sont épuisés.
+    pub fn failed(&mut self, prefix: &Prefix, generation: u64, error: String, install: &RouteInstallConfig) -> Option<Duration> {
+        let pending = self.pending.get_mut(prefix).filter(|pending| pending.generation == generation)?;
+        pending.attempts += 1;
+        pending.last_error = Some(error);
+        if pending.attempts >= install.max_attempts {
+            pending.next_attempt = None;
+            return None;
+        }
+        let factor = 1u64 << (pending.attempts - 1).min(20);
+        let delay = Duration::from_millis(install.retry_initial_ms.saturating_mul(factor).min(install.retry_max_ms));
+        pending.next_attempt = Some(Instant::now() + delay);
+        Some(delay)
+    }
+
+    pub fn get(&self, prefix: &Prefix) -> Option<&PendingOp> {
+        self.pending.get(prefix)
+    }
+
+    /// Opérations en attente, par préfixe
+    pub fn entries(&self) -> impl Iterator<Item = (&Prefix, &PendingOp)> {
+        self.pending.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+/// File des opérations de routes partagée entre le calcul SPF et la tâche
+/// d'installation de `spawn_route_install_task`
+#[derive(Debug, Default)]
+pub struct RouteOps {
+    queue: Mutex<RouteQueue>,
+    notify: Notify,
+}
+
+impl RouteOps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Met en file une opération et réveille la tâche d'installation
+    pub async fn push(&self, prefix: Prefix, op: RouteOp) {
+        if self.queue.lock().await.push(prefix, op) {
+            self.notify.notify_one();
+        }
+    }
+
+    pub async fn cancel(&self, prefix: &Prefix) {
+        self.queue.lock().await.cancel(prefix);
+    }
+
+    pub async fn queue(&self) -> MutexGuard<'_, RouteQueue> {
+        self.queue.lock().await
+    }
+}
+
+/// Exécute une opération avec la configuration d'installation courante
+async fn apply(state: &AppState, prefix: &Prefix, op: &RouteOp, install: &RouteInstallConfig) -> crate::error::Result<bool> {
+    match op {
+        RouteOp::Install { next_hop } => crate::lsa::update_routing_table_safe(state.routes.as_ref(), &state.interfaces, prefix, next_hop, install).await,
+        RouteOp::Remove => crate::lsa::remove_own_route(state.routes.as_ref(), prefix, install).await.map(|()| true),
+    }
+}
+
+/// Rapport `pending-routes`
+pub fn pending_report(queue: &RouteQueue, now: Instant) -> String {
+    if queue.is_empty() {
+        return "Aucune opération de route en attente".to_string();
+    }
+    let mut lines = vec![format!("{} opération(s) de route en attente:", queue.len())];
+    for (prefix, pending) in queue.entries() {
+        let retry = match pending.next_attempt {
+            Some(at) => format!("prochain essai dans {} ms", at.saturating_duration_since(now).as_millis()),
+            None => "abandonnée".to_string(),
+        };
+        lines.push(format!(
+            "  {} {} - {} essai(s), {} ({})",
+            prefix,
+            pending.op,
+            pending.attempts,
+            retry,
+            pending.last_error.as_deref().unwrap_or("pas encore essayée"),
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Applique les opérations de la file au noyau hors du calcul SPF ; une opération
+/// échouée (droits insuffisants, interface absente...) est retentée avec une attente
+/// croissante puis laissée visible par `pending-routes`
+pub fn spawn_route_install_task(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let (due, next_due) = {
+                let queue = state.route_ops.queue().await;
+                (queue.due(Instant::now()), queue.next_due())
+            };
+            if due.is_empty() {
+                // Réveil par une nouvelle opération ou à l'échéance du prochain essai
+                match next_due {
+                    Some(at) => {
+                        tokio::select! {
+                            _ = state.route_ops.notify.notified() => {}
+                            _ = tokio::time::sleep_until(at) => {}
+                        }
+                    }
+                    None => state.route_ops.notify.notified().await,
+                }
+                continue;
+            }
+
+            let install = state.config().await.route_install;
+            for (prefix, op, generation) in due {
+                let result = apply(&state, &prefix, &op, &install).await;
+                let mut queue = state.route_ops.queue().await;
+                match result {
+                    Ok(changed) => {
+                        queue.succeeded(&prefix, generation);
+                        drop(queue);
+                        let mut programmed = state.programmed_routes.lock().await;
+                        match &op {
+                            RouteOp::Install { next_hop } => {
+                                programmed.record(prefix, next_hop.clone());
+                                drop(programmed);
+                                if changed {
+                                    info!("Route {} installed via {}", prefix, next_hop);
+                                    state.events.emit(RouterEvent::RouteInstalled { prefix: prefix.to_string(), next_hop: next_hop.to_string() });
+                                }
+                            }
+                            RouteOp::Remove => {
+                                programmed.forget(&prefix);
+                                drop(programmed);
+                                if changed {
+                                    state.events.emit(RouterEvent::RouteRemoved { prefix: prefix.to_string() });
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let retry = queue.failed(&prefix, generation, e.to_string(), &install);
+                        drop(queue);
+                        match retry {
+                            Some(delay) => warn!("Route operation {} {} failed, retrying in {} ms: {}", prefix, op, delay.as_millis(), e),
+                            None => warn!("Route operation {} {} failed after {} attempts, giving up: {}", prefix, op, install.max_attempts, e),
+                        }
+                        if let RouteOp::Install { next_hop } = &op {
+                            state.events.emit(RouterEvent::RouteFailed { prefix: prefix.to_string(), next_hop: next_hop.to_string(), error: e.to_string() });
+                        }
+                    }
+                }
+            }
+            state.metrics.pending_route_ops.store(state.route_ops.queue().await.len() as u64, std::sync::atomic::Ordering::Relaxed);
+        }
+    })
+}
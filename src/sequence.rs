@@ -0,0 +1,139 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use crate::AppState;
+use crate::flooding::NeighborKey;
+use crate::types::{LSAMessage, LsaIdentity};
+
+pub use pospf_core::sequence::*;
+
+/// Numéro de séquence du prochain LSA émis par ce routeur ; enregistre une nouvelle
+/// réservation dans le fichier d'état lorsque la précédente est épuisée. `None` une fois
+/// MAX émis : le premier appel lance le retrait de nos LSA (`wrap`), aucun LSA n'est émis
+/// avant qu'il ne s'achève
+pub async fn next_sequence(state: &Arc<AppState>) -> Option<u32> {
+    let Some((seq_num, reserve)) = state.own_sequence.advance() else {
+        if state.own_sequence.begin_wrap() {
+            tokio::spawn(wrap(Arc::clone(state)));
+        }
+        return None;
+    };
+    if reserve {
+        crate::persist::save(state).await;
+    }
+    Some(seq_num)
+}
+
+/// Vrai si l'originateur désigne ce routeur (adresse ou segment d'une de ses interfaces)
+pub fn is_own_originator(state: &AppState, originator: &str) -> bool {
+    state.interfaces.interfaces().iter()
+        .any(|iface| iface.ip().to_string() == originator || crate::network_lsa::segment_id(iface.network) == originator)
+}
+
+/// LSA de retrait de l'instance MAX d'un de nos originateurs, émis depuis `router_ip`
+fn flush_message(state: &AppState, originator: &str, router_ip: &str) -> LSAMessage {
+    let mut message = LSAMessage {
+        message_type: 2,
+        router_ip: router_ip.to_string(),
+        last_hop: None,
+        originator: originator.to_string(),
+        router_id: Some(state.router_id),
+        seq_num: MAX_SEQUENCE,
+        neighbor_count: 0,
+        neighbors: Vec::new(),
+        routing_table: std::collections::HashMap::new(),
+        external_routes: std::collections::HashMap::new(),
+        external_metric_types: std::collections::HashMap::new(),
+        route_tags: std::collections::HashMap::new(),
+        path: Vec::new(),
+        ttl: crate::INITIAL_TTL,
+        stub_only: false,
+        base_seq: None,
+        withdrawn: Vec::new(),
+        opaque: Vec::new(),
+        flush: true,
+        checksum: None,
+        part: None,
+    };
+    message.seal();
+    message
+}
+
+/// Retour du compteur à `INITIAL_SEQUENCE` (procédure de MaxSequenceNumber d'OSPF) : nos
+/// instances MAX sont retirées chez chaque voisin adjacent, qui relaie le retrait, et le
+/// compteur ne repart qu'une fois chaque retrait acquitté ou les voisins muets expirés.
+/// Sans cela, un routeur resté à MAX-1 rejetterait INITIAL comme plus ancien. Future
+/// explicitement `Send` : elle réémet nos LSA, donc rappelle `next_sequence`
+fn wrap(state: Arc<AppState>) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        let state = &state;
+        let config = state.config().await;
+        let mut originators: Vec<String> = state.interfaces.interfaces().iter().map(|iface| iface.ip().to_string()).collect();
+        let router_id = state.router_id.to_string();
+        originators.extend(state.lsdb.read().await.network_lsas()
+            .filter(|lsa| lsa.advertising_router == router_id)
+            .map(|lsa| lsa.prefix.clone()));
+        warn!("LSA sequence reached {:#x}: flushing {} own LSA(s) before restarting at {:#x}",
+              MAX_SEQUENCE, originators.len(), INITIAL_SEQUENCE);
+
+        let neighbors: Vec<NeighborKey> = state.neighbors.read().await.values()
+            .filter(|neighbor| neighbor.link_up)
+            .map(|neighbor| NeighborKey::for_neighbor(&state.interfaces, neighbor))
+            .collect();
+        {
+            let mut flooding = state.flooding.lock().await;
+            for neighbor in &neighbors {
+                let local_ip = state.interfaces.addresses_of(&neighbor.interface).first().map(|ip| ip.to_string()).unwrap_or_default();
+                for originator in &originators {
+                    flooding.push(neighbor.clone(), flush_message(state, originator, &local_ip));
+                }
+            }
+        }
+        for (local_ip, addr) in crate::discovery::flood_targets(state, &config).await {
+            let sender = state.sender_for(&local_ip, &addr, &state.socket).await;
+            let key = state.key_for(&local_ip).await;
+            for originator in &originators {
+                let message = flush_message(state, originator, &local_ip);
+                if let Err(e) = crate::tcp_session::send_message(state, &sender, &local_ip, &addr, &message, &key, "[FLUSH]").await {
+                    warn!("Failed to flush LSA {} on {}: {}", originator, local_ip, e);
+                }
+            }
+        }
+
+        // Les retraits non acquittés sont réémis par `retransmit_pending` ; un voisin muet
+        // au-delà du délai d'expiration n'est plus attendu
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(config.max_dead_interval());
+        loop {
+            let pending = {
+                let flooding = state.flooding.lock().await;
+                neighbors.iter().any(|neighbor| originators.iter().any(|originator| {
+                    flooding.is_pending(neighbor, &LsaIdentity { originator: originator.clone(), seq_num: MAX_SEQUENCE })
+                }))
+            };
+            if !pending {
+                break;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                warn!("LSA flush not acknowledged by every neighbor, restarting the sequence anyway");
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+
+        {
+            let mut lsdb = state.lsdb.write().await;
+            let mut processed = state.processed_lsa.lock().await;
+            let mut sequences = state.lsa_sequences.lock().await;
+            let mut deltas = state.lsa_deltas.lock().await;
+            for originator in &originators {
+                lsdb.flush(&flush_message(state, originator, originator));
+                sequences.remove(originator);
+                processed.forget(originator);
+                deltas.forget_originator(originator);
+            }
+        }
+        state.own_sequence.restart();
+        info!("Own LSAs flushed, sequence restarts at {:#x}", INITIAL_SEQUENCE);
+        crate::neighbor::originate_lsas(state, "sequence wrap").await;
+    })
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use crate::transport::Socket;
+use tracing::{error, info, warn};
+use crate::AppState;
+use crate::types::Prefix;
+
+/// Attend SIGINT ou SIGTERM
+pub async fn wait_for_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => info!("SIGINT received, shutting down"),
+                    _ = terminate.recv() => info!("SIGTERM received, shutting down"),
+                }
+            }
+            Err(e) => {
+                warn!("Failed to install SIGTERM handler: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                info!("SIGINT received, shutting down");
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        info!("Ctrl-C received, shutting down");
+    }
+}
+
+/// Arrêt propre : empoisonne les préfixes originés localement puis retire
+/// du noyau toutes les routes installées par le démon
+pub async fn graceful_shutdown(socket: &Arc<Socket>, state: &Arc<AppState>) {
+    let config = state.config().await;
+
+    // Numéro de séquence strictement supérieur au dernier LSA périodique ; aucun pendant
+    // le retrait de nos instances MAX, qui retire déjà nos préfixes
+    if let Some(seq_num) = crate::sequence::next_sequence(state).await {
+        let mut redistributed: Vec<Prefix> = state.redistributed_routes.read().await.keys().copied().collect();
+        redistributed.extend(state.bgp.read().await.best().into_keys());
+        for (local_ip, addr) in crate::discovery::flood_targets(state, &config).await {
+            let mut poisoned: Vec<Prefix> = crate::lsa::originated_networks(&config, &state.interfaces, &*state.interface_states.read().await, &local_ip).into_keys().collect();
+            poisoned.extend(redistributed.iter().copied());
+            if config.originate_default.enabled || config.is_stub_border() {
+                poisoned.push(Prefix::default_route());
+            }
+            let sender = state.sender_for(&local_ip, &addr, socket).await;
+            if let Err(e) = crate::lsa::send_poisoned_route(&sender, &addr, &local_ip, &poisoned, seq_num, vec![], state).await {
+                error!("Failed to send poisoned LSA on {}: {}", local_ip, e);
+            }
+        }
+    }
+
+    // Les opérations en attente ne doivent pas réinstaller ce qui va être retiré
+    state.route_ops.queue().await.clear();
+    // Rien à retirer si le démon n'installe pas de routes (routeur simulé)
+    if config.route_install.enabled {
+        match state.routes.list_routes().await {
+            Ok(routes) => {
+                let own: Vec<_> = routes.into_iter()
+                    .filter(|route| route.protocol == config.route_install.protocol)
+                    .collect();
+                info!("Removing {} route(s) installed by the daemon", own.len());
+                for route in own {
+                    let prefix = route.prefix;
+                    if let Err(e) = state.routes.delete_route(route).await {
+                        warn!("Failed to remove route to {}: {}", prefix, e);
+                    }
+                }
+            }
+            Err(e) => error!("Failed to list kernel routes during shutdown: {}", e),
+        }
+    }
+
+    state.routing_table.write().await.clear();
+    state.programmed_routes.lock().await.clear();
+    state.route_origins.write().await.clear();
+    info!("Shutdown complete");
+}
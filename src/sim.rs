@@ -0,0 +1,431 @@
+use crate::daemon::{RouterDaemon, RouterHandle};
+use crate::error::{AppError, Result};
+use crate::interfaces::{InterfaceProvider, LocalInterface};
+use crate::read_config::{InterfaceConfig, RouterConfig, TransportMode};
+use crate::route_backend::DryRunBackend;
+use crate::transport::Socket;
+use crate::types::{Prefix, RouteState};
+use pnet::ipnetwork::Ipv4Network;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, IoSliceMut};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::{Arc, Mutex};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::DuplexStream;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+type Datagram = (Vec<u8>, SocketAddr);
+/// Connexion entrante d'une session : extrémité du tube et adresse de l'appelant
+type Connection = (DuplexStream, SocketAddr);
+
+/// Capacité de chaque sens d'une connexion virtuelle
+const STREAM_BUFFER: usize = 64 * 1024;
+
+/// Caractéristiques d'un lien virtuel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkProfile {
+    pub up: bool,
+    /// Probabilité de perte de chaque datagramme (0.0 à 1.0)
+    pub loss: f64,
+    /// Délai de propagation appliqué à chaque datagramme
+    pub delay: Duration,
+    /// MTU du lien : un datagramme plus grand, en-têtes IPv4 et UDP compris, est perdu
+    pub mtu: Option<usize>,
+}
+
+impl Default for LinkProfile {
+    fn default() -> Self {
+        Self {
+            up: true,
+            loss: 0.0,
+            delay: Duration::ZERO,
+            mtu: None,
+        }
+    }
+}
+
+/// Compteurs du réseau virtuel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NetworkStats {
+    pub delivered: u64,
+    pub dropped: u64,
+}
+
+#[derive(Debug)]
+struct Segment {
+    network: Ipv4Network,
+    link: LinkProfile,
+    members: Vec<Ipv4Addr>,
+}
+
+#[derive(Debug)]
+struct NetworkInner {
+    segments: BTreeMap<String, Segment>,
+    inboxes: HashMap<(Ipv4Addr, u16), UnboundedSender<Datagram>>,
+    listeners: HashMap<(Ipv4Addr, u16), UnboundedSender<Connection>>,
+    rng: StdRng,
+    stats: NetworkStats,
+}
+
+/// Réseau en mémoire reliant des routeurs simulés : chaque segment est un domaine
+/// de diffusion dont les pertes sont tirées d'un générateur à graine fixe
+#[derive(Debug, Clone)]
+pub struct VirtualNetwork {
+    inner: Arc<Mutex<NetworkInner>>,
+}
+
+impl VirtualNetwork {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(NetworkInner {
+                segments: BTreeMap::new(),
+                inboxes: HashMap::new(),
+                listeners: HashMap::new(),
+                rng: StdRng::seed_from_u64(seed),
+                stats: NetworkStats::default(),
+            })),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, NetworkInner> {
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Déclare un segment (sous-réseau partagé) sous un nom
+    pub fn add_segment(&self, name: &str, network: Ipv4Network) {
+        self.lock().segments.insert(name.to_string(), Segment {
+            network,
+            link: LinkProfile::default(),
+            members: Vec::new(),
+        });
+    }
+
+    /// Raccorde une adresse à un segment et renvoie l'adresse avec le préfixe du segment
+    pub fn attach(&self, segment: &str, address: Ipv4Addr) -> Result<Ipv4Network> {
+        let mut inner = self.lock();
+        let segment = inner.segments.get_mut(segment)
+            .ok_or_else(|| AppError::ConfigError(format!("Unknown segment {}", segment)))?;
+        if !segment.network.contains(address) {
+            return Err(AppError::ConfigError(format!("{} is not in segment {}", address, segment.network)));
+        }
+        segment.members.push(address);
+        Ipv4Network::new(address, segment.network.prefix())
+            .map_err(|e| AppError::ConfigError(format!("Invalid address {}: {}", address, e)))
+    }
+
+    pub fn link(&self, segment: &str) -> Option<LinkProfile> {
+        self.lock().segments.get(segment).map(|segment| segment.link)
+    }
+
+    pub fn set_link(&self, segment: &str, link: LinkProfile) {
+        if let Some(segment) = self.lock().segments.get_mut(segment) {
+            segment.link = link;
+        }
+    }
+
+    pub fn stats(&self) -> NetworkStats {
+        self.lock().stats
+    }
+
+    /// Socket recevant les datagrammes adressés à l'une des adresses du routeur, sur `port`
+    pub fn socket(&self, addresses: Vec<Ipv4Addr>, port: u16) -> VirtualSocket {
+        let (sender, inbox) = unbounded_channel();
+        let mut inner = self.lock();
+        for address in &addresses {
+            inner.inboxes.insert((*address, port), sender.clone());
+        }
+        VirtualSocket {
+            network: self.clone(),
+            addresses,
+            port,
+            inbox: tokio::sync::Mutex::new(inbox),
+        }
+    }
+
+    /// Destinataires d'un datagramme : tous les membres du segment pour une diffusion
+    /// (broadcast du segment ou multicast), le seul propriétaire de l'adresse sinon, chacun
+    /// sur le port `target`
+    fn route(&self, from: &[Ipv4Addr], port: u16, target: SocketAddrV4, len: usize) -> Vec<(UnboundedSender<Datagram>, SocketAddr, Duration)> {
+        let mut inner = self.lock();
+        let NetworkInner { segments, inboxes, rng, stats, .. } = &mut *inner;
+        let mut deliveries = Vec::new();
+        let (target_port, target) = (target.port(), *target.ip());
+        for segment in segments.values() {
+            let Some(&source) = segment.members.iter().find(|member| from.contains(member)) else {
+                continue;
+            };
+            let recipients: Vec<Ipv4Addr> = if target == segment.network.broadcast() || target.is_multicast() {
+                segment.members.iter().filter(|member| !from.contains(member)).copied().collect()
+            } else if segment.members.contains(&target) && !from.contains(&target) {
+                vec![target]
+            } else {
+                continue;
+            };
+            for recipient in recipients {
+                let oversized = segment.link.mtu.is_some_and(|mtu| len + 28 > mtu);
+                if !segment.link.up || oversized || rng.gen::<f64>() < segment.link.loss {
+                    stats.dropped += 1;
+                    continue;
+                }
+                if let Some(inbox) = inboxes.get(&(recipient, target_port)) {
+                    stats.delivered += 1;
+                    deliveries.push((inbox.clone(), SocketAddr::new(IpAddr::V4(source), port), segment.link.delay));
+                }
+            }
+        }
+        deliveries
+    }
+
+    /// Ouvre une connexion vers `target` depuis l'une des adresses `from` : les deux
+    /// extrémités partagent un segment actif et `target` écoute. Une fois établie, la
+    /// connexion est fiable, sans perte ni délai
+    fn connect(&self, from: &[Ipv4Addr], port: u16, target: SocketAddr) -> io::Result<DuplexStream> {
+        let IpAddr::V4(target_ip) = target.ip() else {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "IPv6 not supported on virtual links"));
+        };
+        let inner = self.lock();
+        let source = inner.segments.values()
+            .filter(|segment| segment.link.up && segment.members.contains(&target_ip))
+            .find_map(|segment| segment.members.iter().find(|member| from.contains(member)).copied())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::HostUnreachable, format!("{} is unreachable", target)))?;
+        let listener = inner.listeners.get(&(target_ip, target.port()))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::ConnectionRefused, format!("{} is not listening", target)))?;
+        let (local, remote) = tokio::io::duplex(STREAM_BUFFER);
+        listener.send((remote, SocketAddr::new(IpAddr::V4(source), port)))
+            .map_err(|_| io::Error::new(io::ErrorKind::ConnectionRefused, format!("{} is not listening", target)))?;
+        Ok(local)
+    }
+}
+
+/// Écoute des connexions virtuelles adressées à un routeur simulé
+#[derive(Debug)]
+pub struct VirtualListener {
+    inbox: UnboundedReceiver<Connection>,
+}
+
+impl VirtualListener {
+    pub async fn accept(&mut self) -> io::Result<Connection> {
+        self.inbox.recv().await
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "virtual network closed"))
+    }
+}
+
+/// Extrémité d'un routeur simulé sur le réseau virtuel
+#[derive(Debug)]
+pub struct VirtualSocket {
+    network: VirtualNetwork,
+    addresses: Vec<Ipv4Addr>,
+    port: u16,
+    inbox: tokio::sync::Mutex<UnboundedReceiver<Datagram>>,
+}
+
+impl VirtualSocket {
+    pub fn send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        let IpAddr::V4(target_ip) = target.ip() else {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "IPv6 not supported on virtual links"));
+        };
+        for (inbox, source, delay) in self.network.route(&self.addresses, self.port, SocketAddrV4::new(target_ip, target.port()), buf.len()) {
+            let datagram = (buf.to_vec(), source);
+            if delay.is_zero() {
+                let _ = inbox.send(datagram);
+            } else {
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    let _ = inbox.send(datagram);
+                });
+            }
+        }
+        Ok(buf.len())
+    }
+
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let (data, source) = self.inbox.lock().await.recv().await
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "virtual network closed"))?;
+        let len = data.len().min(buf.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        Ok((len, source))
+    }
+
+    /// Écoute les connexions sur le port du routeur, pour chacune de ses adresses
+    pub fn listen(&self) -> VirtualListener {
+        let (sender, inbox) = unbounded_channel();
+        let mut inner = self.network.lock();
+        for address in &self.addresses {
+            inner.listeners.insert((*address, self.port), sender.clone());
+        }
+        VirtualListener { inbox }
+    }
+
+    pub fn connect(&self, target: SocketAddr) -> io::Result<DuplexStream> {
+        self.network.connect(&self.addresses, self.port, target)
+    }
+
+    /// Autre socket sur les mêmes adresses, à l'écoute de `port`
+    pub fn with_port(&self, port: u16) -> VirtualSocket {
+        self.network.socket(self.addresses.clone(), port)
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        let ip = self.addresses.first().copied().unwrap_or(Ipv4Addr::UNSPECIFIED);
+        SocketAddr::new(IpAddr::V4(ip), self.port)
+    }
+}
+
+/// Un `VirtualSocket` porte aussi les sessions QUIC du simulateur : l'envoi n'est jamais
+/// bloquant et chaque datagramme est reçu seul
+impl quinn::AsyncUdpSocket for VirtualSocket {
+    fn create_io_poller(self: Arc<Self>) -> Pin<Box<dyn quinn::UdpPoller>> {
+        Box::pin(AlwaysWritable)
+    }
+
+    fn try_send(&self, transmit: &quinn::udp::Transmit) -> io::Result<()> {
+        self.send_to(transmit.contents, transmit.destination).map(|_| ())
+    }
+
+    fn poll_recv(&self, cx: &mut Context, bufs: &mut [IoSliceMut<'_>], meta: &mut [quinn::udp::RecvMeta]) -> Poll<io::Result<usize>> {
+        let Ok(mut inbox) = self.inbox.try_lock() else {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        };
+        match inbox.poll_recv(cx) {
+            Poll::Ready(Some((data, source))) => {
+                let len = data.len().min(bufs[0].len());
+                bufs[0][..len].copy_from_slice(&data[..len]);
+                meta[0] = quinn::udp::RecvMeta { addr: source, len, stride: len, ecn: None, dst_ip: None };
+                Poll::Ready(Ok(1))
+            }
+            Poll::Ready(None) => Poll::Ready(Err(io::Error::new(io::ErrorKind::BrokenPipe, "virtual network closed"))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(VirtualSocket::local_addr(self))
+    }
+}
+
+#[derive(Debug)]
+struct AlwaysWritable;
+
+impl quinn::UdpPoller for AlwaysWritable {
+    fn poll_writable(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Routeurs `RouterDaemon` reliés par un `VirtualNetwork`, sans socket ni privilège
+pub struct Simulation {
+    network: VirtualNetwork,
+    routers: BTreeMap<String, RouterDaemon>,
+}
+
+impl Simulation {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            network: VirtualNetwork::new(seed),
+            routers: BTreeMap::new(),
+        }
+    }
+
+    /// Configuration adaptée à un routeur simulé : messages en clair, diffusion broadcast,
+    /// aucune route installée dans le noyau
+    pub fn router_config() -> RouterConfig {
+        let mut config = RouterConfig {
+            encryption: false,
+            transport: TransportMode::Broadcast,
+            ..RouterConfig::default()
+        };
+        config.route_install.enabled = false;
+        config
+    }
+
+    pub fn network(&self) -> &VirtualNetwork {
+        &self.network
+    }
+
+    /// Déclare un segment, par exemple `add_segment("r1-r2", "10.0.12.0/24")`
+    pub fn add_segment(&mut self, name: &str, cidr: &str) -> Result<()> {
+        let network: Ipv4Network = cidr.parse()
+            .map_err(|e| AppError::ConfigError(format!("Invalid segment {}: {}", cidr, e)))?;
+        self.network.add_segment(name, network);
+        Ok(())
+    }
+
+    /// Ajoute un routeur raccordé aux segments donnés par (interface, segment, adresse).
+    /// Les interfaces absentes de la configuration y sont ajoutées avec les valeurs par défaut,
+    /// et le routeur s'annonce sous `name` à défaut d'un nom configuré.
+    pub fn add_router(&mut self, name: &str, attachments: &[(&str, &str, &str)], mut config: RouterConfig) -> Result<()> {
+        config.hostname.get_or_insert_with(|| name.to_string());
+        let mut interfaces = Vec::new();
+        for (interface, segment, address) in attachments {
+            let address: Ipv4Addr = address.parse()
+                .map_err(|e| AppError::ConfigError(format!("Invalid address {}: {}", address, e)))?;
+            let network = self.network.attach(segment, address)?;
+            interfaces.push(LocalInterface::new(interface, network));
+            if !config.interfaces.iter().any(|iface| iface.name == *interface) {
+                config.interfaces.push(InterfaceConfig::new(interface, 1000));
+            }
+        }
+        let addresses = interfaces.iter().map(LocalInterface::ip).collect();
+        let socket = Arc::new(Socket::Virtual(self.network.socket(addresses, config.port)));
+        let daemon = RouterDaemon::with_socket(config, None, socket, InterfaceProvider::Static(interfaces), Arc::new(DryRunBackend::new()), HashMap::new())?;
+        self.routers.insert(name.to_string(), daemon);
+        Ok(())
+    }
+
+    pub async fn start(&mut self) -> Result<()> {
+        for daemon in self.routers.values_mut() {
+            daemon.start().await?;
+        }
+        Ok(())
+    }
+
+    pub async fn stop(&mut self) {
+        for daemon in self.routers.values_mut() {
+            daemon.stop().await;
+        }
+    }
+
+    pub fn handle(&self, name: &str) -> Option<RouterHandle> {
+        self.routers.get(name).map(RouterDaemon::handle)
+    }
+
+    pub fn set_link_up(&self, segment: &str, up: bool) {
+        if let Some(link) = self.network.link(segment) {
+            self.network.set_link(segment, LinkProfile { up, ..link });
+        }
+    }
+
+    /// Laisse s'écouler du temps (simulé si l'horloge de tokio est suspendue)
+    pub async fn run_for(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+
+    /// Tables de routage de tous les routeurs, par nom
+    pub async fn routing_tables(&self) -> BTreeMap<String, HashMap<Prefix, (String, RouteState)>> {
+        let mut tables = BTreeMap::new();
+        for (name, daemon) in &self.routers {
+            tables.insert(name.clone(), daemon.handle().routing_table().await);
+        }
+        tables
+    }
+
+    /// Avance par pas de `tick` jusqu'à ce que `converged` soit vrai sur les tables de routage ;
+    /// renvoie le nombre de pas nécessaires, ou `None` au-delà de `max_ticks`
+    pub async fn run_until<F>(&self, tick: Duration, max_ticks: u32, mut converged: F) -> Option<u32>
+    where
+        F: FnMut(&BTreeMap<String, HashMap<Prefix, (String, RouteState)>>) -> bool,
+    {
+        for ticks in 0..=max_ticks {
+            if converged(&self.routing_tables().await) {
+                return Some(ticks);
+            }
+            self.run_for(tick).await;
+        }
+        None
+    }
+}
@@ -0,0 +1,66 @@
+use crate::AppState;
+use tracing::{debug, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio::sync::Notify;
+
+/// Demandes de recalcul SPF, regroupées par la tâche de `spawn_spf_task`
+#[derive(Debug, Default)]
+pub struct SpfScheduler {
+    pending: AtomicBool,
+    notify: Notify,
+}
+
+impl SpfScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Demande un recalcul ; les demandes rapprochées n'en déclenchent qu'un seul
+    pub fn schedule(&self) {
+        self.pending.store(true, Ordering::Release);
+        self.notify.notify_one();
+    }
+}
+
+/// Exécute les recalculs demandés après un délai initial, puis avec une attente
+/// doublée à chaque recalcul rapproché, plafonnée à `max_hold_ms`
+pub fn spawn_spf_task(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last_run: Option<Instant> = None;
+        let mut hold = Duration::ZERO;
+        loop {
+            state.spf.notify.notified().await;
+            if !state.spf.pending.load(Ordering::Acquire) {
+                continue;
+            }
+
+            let throttle = state.config().await.spf_throttle;
+            let initial_delay = Duration::from_millis(throttle.initial_delay_ms);
+            let max_hold = Duration::from_millis(throttle.max_hold_ms);
+            // Après une période calme, on repart du délai initial et de l'attente minimale
+            let delay = match last_run {
+                Some(run) if run.elapsed() < max_hold * 2 => {
+                    let wait = hold.saturating_sub(run.elapsed()).max(initial_delay);
+                    hold = (hold * 2).min(max_hold);
+                    wait
+                }
+                _ => {
+                    hold = Duration::from_millis(throttle.hold_ms);
+                    initial_delay
+                }
+            };
+            debug!("SPF scheduled in {} ms (hold {} ms)", delay.as_millis(), hold.as_millis());
+            tokio::time::sleep(delay).await;
+
+            state.spf.pending.store(false, Ordering::Release);
+            last_run = Some(Instant::now());
+            state.metrics.spf_runs.fetch_add(1, Ordering::Relaxed);
+            if let Err(e) = crate::dijkstra::calculate_and_update_optimal_routes(Arc::clone(&state)).await {
+                warn!("Échec du calcul des routes: {}", e);
+            }
+        }
+    })
+}
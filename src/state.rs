@@ -0,0 +1,173 @@
+use crate::read_config::RouterConfig;
+use crate::types::{Neighbor, Prefix, RouteState};
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::interfaces::InterfaceProvider;
+use crate::transport::Socket;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{Mutex, RwLock};
+
+/// État partagé d'une instance de routeur
+///
+/// Les tables lues à chaque paquet ou par les commandes (LSDB, voisins, table de routage,
+/// configuration) sont des `RwLock` : les lecteurs ne se bloquent pas entre eux. Quand
+/// plusieurs verrous sont tenus ensemble, ils sont pris dans l'ordre des champs
+/// ci-dessous (`lsdb`, `neighbors`, `routing_table`, `route_origins`, puis les autres),
+/// jamais pendant une installation de route ni un envoi réseau, et un même verrou n'est
+/// jamais repris en lecture alors qu'il est déjà tenu.
+pub struct AppState {
+    pub lsdb: RwLock<crate::lsdb::Lsdb>,
+    pub neighbors: RwLock<HashMap<String, Neighbor>>,
+    pub routing_table: RwLock<HashMap<Prefix, (String, RouteState)>>,
+    /// Provenance de chaque route de `routing_table`
+    pub route_origins: RwLock<HashMap<Prefix, crate::types::RouteOrigin>>,
+    /// Routes programmées dans le noyau par le dernier calcul SPF
+    pub programmed_routes: Mutex<crate::route_diff::ProgrammedRoutes>,
+    /// Installations et retraits de routes en attente d'exécution ou de nouvel essai
+    pub route_ops: crate::route_queue::RouteOps,
+    /// Routes écartées par les seuils d'installation lors du dernier calcul SPF
+    pub filtered_routes: Mutex<Vec<crate::install_policy::FilteredRoute>>,
+    /// Préfixes annoncés injoignables, dont les annonces moins bonnes sont ignorées un temps
+    pub hold_down: Mutex<crate::hold_down::HoldDown>,
+    pub processed_lsa: Mutex<crate::lsa_cache::ProcessedLsaCache>,
+    /// Messages fragmentés en cours de réassemblage
+    pub reassembly: Mutex<crate::fragment::Reassembler>,
+    /// LSA découpés pour la MTU dont toutes les parties ne sont pas encore reçues
+    pub lsa_parts: Mutex<crate::lsa_part::PartAssembler>,
+    pub lsa_sequences: Mutex<HashMap<String, u32>>,
+    /// Numéros de séquence des LSA émis par ce routeur
+    pub own_sequence: crate::sequence::SequenceCounter,
+    /// Instances émises et collisions d'identifiant détectées
+    pub router_id_guard: Mutex<crate::collision::RouterIdGuard>,
+    pub redistributed_routes: RwLock<HashMap<Prefix, u32>>,
+    /// Sessions et routes apprises de la passerelle externe
+    pub bgp: RwLock<crate::bgp_lite::BgpRib>,
+    pub local_ip: String,
+    /// Identifiant du routeur, annoncé dans les HELLO et les LSA
+    pub router_id: crate::types::RouterId,
+    /// Nom annoncé aux voisins dans les HELLO
+    pub hostname: Option<String>,
+    /// VRF servie par cette instance (`None` : instance principale)
+    pub vrf: Option<String>,
+    /// Socket principale du protocole (réception et réponses unicast)
+    pub socket: Arc<Socket>,
+    /// Interfaces du routeur (système, ou fixées par le simulateur)
+    pub interfaces: InterfaceProvider,
+    /// Table de routage du système (netlink, API native ou simulation)
+    pub routes: Arc<dyn crate::route_backend::RouteBackend>,
+    /// Port UDP du protocole, fixé au démarrage
+    pub port: u16,
+    pub enabled: AtomicBool,
+    /// État courant des interfaces configurées (configuration et modifications à chaud)
+    pub interface_states: RwLock<HashMap<String, crate::types::InterfaceState>>,
+    pub config: RwLock<RouterConfig>,
+    /// Fichier de configuration explicite (--config ou reload-config <fichier>)
+    pub config_path: Mutex<Option<std::path::PathBuf>>,
+    pub key: RwLock<Vec<u8>>,
+    /// Protection propre à certaines interfaces, prioritaire sur `key` sur leur segment
+    pub interface_keys: RwLock<HashMap<String, crate::net_utils::SegmentKey>>,
+    pub flood_sockets: RwLock<HashMap<String, Arc<Socket>>>,
+    pub flooding: Mutex<crate::flooding::FloodingState>,
+    /// Instances complètes de référence des LSA différentiels, émises et reçues
+    pub lsa_deltas: Mutex<crate::lsa_delta::DeltaState>,
+    pub neighbor_auth: Mutex<crate::auth::NeighborAuth>,
+    pub control_replay: Mutex<crate::control_auth::ControlReplay>,
+    /// Sondes `ping` et sondes de MTU en attente de leur réponse
+    pub probes: Mutex<crate::probe::PendingProbes>,
+    /// MTU mesurée vers chaque voisin
+    pub path_mtu: Mutex<crate::path_mtu::PathMtuTable>,
+    /// Sessions TCP ou QUIC avec les voisins des interfaces `transport = "tcp"`, `"auto"` ou `"quic"`
+    pub stream_sessions: Mutex<crate::tcp_session::StreamSessions>,
+    /// Trames reçues sur ces sessions, traitées par la boucle de réception
+    pub stream_inbound: crate::tcp_session::Inbound,
+    /// Requêtes de route (`path <destination> <routeur>`) en attente de réponse
+    pub route_queries: Mutex<crate::route_query::PendingQueries>,
+    pub damping: Mutex<crate::damping::LsaDamping>,
+    /// Journal des changements de la LSDB et de la table de routage, s'il est configuré
+    pub history: Mutex<Option<crate::history::Journal>>,
+    pub spf: crate::spf::SpfScheduler,
+    /// Dernier horodatage HELLO reçu de chaque voisin et instant de réception
+    pub hello_timestamps: Mutex<HashMap<String, (u64, tokio::time::Instant)>>,
+    /// Adresse source des HELLO de chaque voisin, pour résoudre les prochains sauts
+    pub neighbor_addresses: Mutex<HashMap<String, std::net::Ipv4Addr>>,
+    /// Voisins hors de nos sous-réseaux découverts par mDNS
+    pub discovered_peers: Mutex<crate::discovery::DiscoveredPeers>,
+    /// Adresse et port sources observés des HELLO de chaque voisin unicast (après NAT)
+    pub neighbor_endpoints: Mutex<HashMap<String, std::net::SocketAddr>>,
+    /// Compteurs de trafic et changements d'état de chaque voisin
+    pub neighbor_stats: Mutex<crate::neighbor_stats::NeighborStatsTable>,
+    /// Instances des VRF, joignables par les commandes `vrf <nom> ...` de l'instance principale
+    pub vrf_instances: RwLock<HashMap<String, crate::RouterHandle>>,
+    pub metrics: crate::metrics::Metrics,
+    pub events: crate::events::EventBus,
+}
+
+impl AppState {
+    pub async fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+    
+    pub async fn disable(&self) {
+        self.enabled.store(false, Ordering::Relaxed);
+    }
+    
+    pub async fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// État courant d'une interface configurée
+    pub async fn interface_state(&self, name: &str) -> Option<crate::types::InterfaceState> {
+        self.interface_states.read().await.get(name).cloned()
+    }
+
+    /// Copie de la configuration courante (rechargeable à chaud)
+    pub async fn config(&self) -> RouterConfig {
+        self.config.read().await.clone()
+    }
+
+    /// Copie de la clé de chiffrement courante
+    pub async fn key(&self) -> Vec<u8> {
+        self.key.read().await.clone()
+    }
+
+    /// Protection du segment de l'interface `interface`, à défaut la clé globale
+    pub async fn interface_key(&self, interface: Option<&str>) -> crate::net_utils::SegmentKey {
+        let own = match interface {
+            Some(name) => self.interface_keys.read().await.get(name).cloned(),
+            None => None,
+        };
+        match own {
+            Some(key) => key,
+            None => crate::net_utils::SegmentKey::from_global(self.key().await),
+        }
+    }
+
+    /// Protection du segment contenant `address` (adresse locale ou voisin), à défaut la clé
+    /// globale
+    pub async fn key_for(&self, address: &str) -> crate::net_utils::SegmentKey {
+        self.interface_key(self.interfaces.name_for_address(address).as_deref()).await
+    }
+
+    /// Taille des fragments d'un message pour `addr`, selon la MTU mesurée (voir `path_mtu`)
+    pub async fn max_payload(&self, addr: &std::net::SocketAddr) -> usize {
+        self.path_mtu.lock().await.max_payload(addr.ip())
+    }
+
+    /// Socket d'émission vers `addr` : la socket multicast de l'interface pour une diffusion,
+    /// la socket principale en unicast (le voisin répond, et un NAT associe, son port)
+    pub async fn sender_for(&self, local_ip: &str, addr: &std::net::SocketAddr, default: &Arc<Socket>) -> Arc<Socket> {
+        if addr.ip().is_multicast() {
+            self.flood_socket(local_ip, default).await
+        } else {
+            Arc::clone(default)
+        }
+    }
+
+    /// Socket à utiliser pour diffuser depuis l'interface `local_ip` (socket multicast dédiée si disponible)
+    pub async fn flood_socket(&self, local_ip: &str, default: &Arc<Socket>) -> Arc<Socket> {
+        self.flood_sockets.read().await
+            .get(local_ip)
+            .cloned()
+            .unwrap_or_else(|| Arc::clone(default))
+    }
+}
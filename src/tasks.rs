@@ -1,53 +1,145 @@
-pub fn spawn_hello_and_lsa_tasks(socket: std::sync::Arc<tokio::net::UdpSocket>, state: std::sync::Arc<crate::AppState>) {
+pub fn spawn_hello_and_lsa_tasks(socket: std::sync::Arc<crate::transport::Socket>, state: std::sync::Arc<crate::AppState>) -> tokio::task::JoinHandle<()> {
     let socket_clone = std::sync::Arc::clone(&socket);
     let state_clone = std::sync::Arc::clone(&state);
     tokio::spawn(async move {
-        let mut hello_interval = tokio::time::interval(std::time::Duration::from_secs(crate::HELLO_INTERVAL_SEC));
-        let mut lsa_interval = tokio::time::interval(std::time::Duration::from_secs(crate::LSA_INTERVAL_SEC));
+        // Les intervalles dépendent de la configuration (par interface, rechargeable à chaud) :
+        // on cadence à la seconde et on n'émet que lorsque l'échéance est atteinte
+        let mut tick = tokio::time::interval(std::time::Duration::from_secs(1));
+        let mut last_hello: std::collections::HashMap<std::net::SocketAddr, tokio::time::Instant> = std::collections::HashMap::new();
+        let mut last_lsa: Option<tokio::time::Instant> = None;
         loop {
-            tokio::select! {
-                _ = hello_interval.tick() => {
-                    // Vérifier si le protocole OSPF est activé avant d'envoyer des HELLO
-                    if !state_clone.is_enabled().await {
-                        continue;
-                    }
-                    
-                    let broadcast_addrs = crate::net_utils::get_broadcast_addresses(crate::PORT);
-                    for (local_ip, addr) in &broadcast_addrs {
-                        if let Err(e) = crate::hello::send_hello(&socket_clone, addr, local_ip, state.key.as_slice()).await {
-                            log::error!("Failed to send hello to {}: {}", addr, e);
-                        }
-                    }
+            tick.tick().await;
+
+            // Vérifier si le protocole OSPF est activé avant d'envoyer des HELLO/LSA
+            if !state_clone.is_enabled().await {
+                continue;
+            }
+
+            let config = state_clone.config().await;
+            let flood_addrs = crate::discovery::flood_targets(&state_clone, &config).await;
+
+            for (local_ip, addr) in &flood_addrs {
+                let interface = state_clone.interfaces.name_for_address(local_ip);
+                let mut hello_interval = std::time::Duration::from_secs(crate::adaptive_hello::interface_interval(&state_clone, &config, interface.as_deref()).await);
+                // En overlay, les HELLO maintiennent aussi les associations NAT ouvertes
+                if config.overlay.enabled {
+                    hello_interval = hello_interval.min(std::time::Duration::from_secs(config.overlay.keepalive_sec));
+                }
+                if last_hello.get(addr).is_some_and(|sent| sent.elapsed() < hello_interval) {
+                    continue;
                 }
-                _ = lsa_interval.tick() => {
-                    // Vérifier si le protocole OSPF est activé avant d'envoyer des LSA
-                    if !state_clone.is_enabled().await {
-                        continue;
-                    }
-                    
-                    let broadcast_addrs = crate::net_utils::get_broadcast_addresses(crate::PORT);
-                    for (local_ip, addr) in &broadcast_addrs {
-                        let seq_num = std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_else(|_| std::time::Duration::from_secs(0))
-                            .as_secs() as u32;
-                        if let Err(e) = crate::lsa::send_lsa(&socket_clone, addr, local_ip, None, local_ip, std::sync::Arc::clone(&state_clone), seq_num, vec![]).await {
-                            log::error!("Failed to send LSA: {}", e);
-                        }
-                    }
+                last_hello.insert(*addr, tokio::time::Instant::now());
+                let sender = state_clone.sender_for(local_ip, addr, &socket_clone).await;
+                let echoes = crate::hello::hello_echoes(&state_clone, local_ip).await;
+                let priority = config.priority_for(interface.as_deref());
+                let key = state_clone.interface_key(interface.as_deref()).await;
+                match crate::hello::send_hello(&sender, addr, local_ip, state_clone.router_id, state_clone.hostname.as_deref(), priority, echoes, &key).await {
+                    Ok(()) => crate::neighbor_stats::record_sent(&state_clone, local_ip, addr, true).await,
+                    Err(e) => tracing::error!("Failed to send hello to {}: {}", addr, e),
+                }
+            }
+
+            let lsa_interval = std::time::Duration::from_secs(config.lsa_interval_sec);
+            if last_lsa.is_some_and(|sent| sent.elapsed() < lsa_interval) {
+                continue;
+            }
+            last_lsa = Some(tokio::time::Instant::now());
+            for (local_ip, addr) in &flood_addrs {
+                state_clone.damping.lock().await.record_origination(local_ip);
+                let sender = state_clone.sender_for(local_ip, addr, &socket_clone).await;
+                let Some(seq_num) = crate::sequence::next_sequence(&state_clone).await else {
+                    break;
+                };
+                match crate::lsa::send_lsa(&sender, addr, local_ip, None, local_ip, std::sync::Arc::clone(&state_clone), seq_num, vec![]).await {
+                    Ok(()) => crate::neighbor_stats::record_sent(&state_clone, local_ip, addr, false).await,
+                    Err(e) => tracing::error!("Failed to send LSA: {}", e),
                 }
             }
         }
-    });
+    })
 }
 
-pub fn spawn_neighbor_timeout_task(state: std::sync::Arc<crate::AppState>) {
+pub fn spawn_neighbor_timeout_task(state: std::sync::Arc<crate::AppState>) -> tokio::task::JoinHandle<()> {
     let state_clone = std::sync::Arc::clone(&state);
     tokio::spawn(async move {
-        let mut interval = tokio::time::interval(std::time::Duration::from_secs(crate::NEIGHBOR_TIMEOUT_SEC / 2));
         loop {
-            interval.tick().await;
+            let check_period = (state_clone.config().await.min_dead_interval() / 2).max(1);
+            tokio::time::sleep(std::time::Duration::from_secs(check_period)).await;
             crate::neighbor::check_neighbor_timeouts(&state_clone).await;
         }
-    });
-}
\ No newline at end of file
+    })
+}
+
+/// Recharge la configuration à la réception de SIGHUP
+#[cfg(unix)]
+pub fn spawn_sighup_task(socket: std::sync::Arc<crate::transport::Socket>, state: std::sync::Arc<crate::AppState>) -> tokio::task::JoinHandle<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    tokio::spawn(async move {
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(e) => {
+                tracing::error!("Failed to install SIGHUP handler: {}", e);
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            tracing::info!("SIGHUP received, reloading configuration");
+            match crate::reload::reload_config(&state, &socket, None).await {
+                Ok(summary) => tracing::info!("{}", summary),
+                Err(e) => tracing::error!("Configuration reload failed: {}", e),
+            }
+        }
+    })
+}
+
+/// Relit périodiquement les routes du noyau à redistribuer dans les LSA
+pub fn spawn_redistribution_task(state: std::sync::Arc<crate::AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let config = state.config().await.redistribute;
+            if config.enabled {
+                if let Err(e) = crate::redistribute::refresh_redistributed_routes(&state).await {
+                    tracing::warn!("Failed to read kernel routes for redistribution: {}", e);
+                }
+            } else {
+                state.redistributed_routes.write().await.clear();
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(config.refresh_interval_sec.max(1))).await;
+        }
+    })
+}
+
+/// Répare périodiquement l'écart entre nos routes du noyau et la table de routage
+pub fn spawn_route_reconciliation_task(state: std::sync::Arc<crate::AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let install = state.config().await.route_install;
+            // Intervalle nul : vérifier seulement de temps en temps si la réconciliation a été activée
+            let interval = match install.reconcile_interval_sec {
+                0 => 60,
+                interval => interval,
+            };
+            tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+            if install.reconcile_interval_sec == 0 || !state.is_enabled().await {
+                continue;
+            }
+            if let Err(e) = crate::reconcile::reconcile_routes(&state).await {
+                tracing::warn!("Kernel route reconciliation failed: {}", e);
+            }
+        }
+    })
+}
+
+/// Réémet périodiquement les LSA relayés que les voisins n'ont pas acquittés
+pub fn spawn_retransmission_task(socket: std::sync::Arc<crate::transport::Socket>, state: std::sync::Arc<crate::AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(std::time::Duration::from_secs(1));
+        loop {
+            tick.tick().await;
+            if state.is_enabled().await {
+                crate::flooding::retransmit_pending(&socket, &state).await;
+            }
+        }
+    })
+}
@@ -0,0 +1,511 @@
+use crate::error::{AppError, Result};
+use crate::flooding::NeighborKey;
+use crate::net_utils::SegmentKey;
+use crate::read_config::LsaTransport;
+use crate::transport::{Socket, Stream};
+use crate::AppState;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::mpsc::{channel, error::TrySendError, Receiver, Sender};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// Retransmissions vers un voisin, dans `LOSS_WINDOW`, au-delà desquelles une interface
+/// `auto` ouvre une session TCP
+const LOSS_THRESHOLD: usize = 3;
+const LOSS_WINDOW: Duration = Duration::from_secs(60);
+/// Délai d'établissement d'une session
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// Corps le plus grand envoyé en une trame, marge de chiffrement comprise ; au-delà, le
+/// message est découpé comme en UDP
+const MAX_STREAM_PAYLOAD: usize = crate::framing::MAX_BODY_LEN - 128;
+/// Trames en attente d'écriture sur une session ; au-delà, le message part en UDP
+const WRITE_QUEUE_DEPTH: usize = 256;
+/// Trames reçues sur les sessions en attente de la boucle de réception ; au-delà, elles
+/// sont abandonnées comme un datagramme perdu
+const INBOUND_QUEUE_DEPTH: usize = 1024;
+
+/// Trame reçue, adresse du voisin et vrai si elle vient d'une session QUIC : le TLS mutuel
+/// l'authentifie déjà, elle n'est pas protégée par la clé du segment
+pub type Frame = (Vec<u8>, SocketAddr, bool);
+
+/// Trames reçues sur les sessions, remises à la boucle de réception avec les datagrammes
+#[derive(Debug)]
+pub struct Inbound {
+    sender: Sender<Frame>,
+    receiver: Mutex<Receiver<Frame>>,
+}
+
+impl Inbound {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel(INBOUND_QUEUE_DEPTH);
+        Self { sender, receiver: Mutex::new(receiver) }
+    }
+
+    /// File de réception, tenue par la boucle de réception tant qu'elle tourne
+    pub async fn receiver(&self) -> tokio::sync::MutexGuard<'_, Receiver<Frame>> {
+        self.receiver.lock().await
+    }
+}
+
+impl Default for Inbound {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+struct Session {
+    id: u64,
+    interface: String,
+    /// `Tcp` ou `Quic`
+    transport: LsaTransport,
+    /// Extrémité qui a ouvert la connexion
+    initiator: Ipv4Addr,
+    outgoing: bool,
+    writer: Sender<Vec<u8>>,
+    tasks: [JoinHandle<()>; 2],
+    established: Instant,
+    frames_tx: u64,
+    frames_rx: Arc<AtomicU64>,
+    /// Trames abandonnées, file d'écriture ou de réception pleine
+    frames_dropped: Arc<AtomicU64>,
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Sessions TCP ou QUIC ouvertes avec les voisins, par adresse, et pertes constatées vers
+/// chacun
+#[derive(Debug, Default)]
+pub struct StreamSessions {
+    sessions: BTreeMap<Ipv4Addr, Session>,
+    retransmits: HashMap<Ipv4Addr, VecDeque<Instant>>,
+    /// Voisins vers lesquels une connexion est en cours d'établissement
+    connecting: HashSet<Ipv4Addr>,
+    next_id: u64,
+    /// Point de connexion QUIC, ouvert si une interface est en `quic`
+    endpoint: Option<quinn::Endpoint>,
+}
+
+impl StreamSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn has_session(&self, peer: Ipv4Addr) -> bool {
+        self.sessions.contains_key(&peer)
+    }
+
+    /// Compte une retransmission vers `peer` ; vrai si les pertes justifient une session
+    pub fn record_retransmit(&mut self, peer: Ipv4Addr) -> bool {
+        let now = Instant::now();
+        let times = self.retransmits.entry(peer).or_default();
+        times.push_back(now);
+        while times.front().is_some_and(|at| now.duration_since(*at) > LOSS_WINDOW) {
+            times.pop_front();
+        }
+        times.len() >= LOSS_THRESHOLD
+    }
+
+    fn lossy(&self, peer: Ipv4Addr) -> bool {
+        self.retransmits.get(&peer).is_some_and(|times| times.len() >= LOSS_THRESHOLD)
+    }
+
+    /// Garde une seule session par voisin : si les deux routeurs se connectent en même
+    /// temps, celle ouverte par la plus grande adresse l'emporte des deux côtés
+    fn register(&mut self, peer: Ipv4Addr, local: Ipv4Addr, session: Session) -> bool {
+        let preferred = local.max(peer);
+        if self.sessions.get(&peer).is_some_and(|existing| existing.initiator == preferred && session.initiator != preferred) {
+            return false;
+        }
+        self.sessions.insert(peer, session);
+        true
+    }
+
+    /// Ferme la session `id` avec `peer`, si c'est toujours la session courante
+    fn close(&mut self, peer: Ipv4Addr, id: u64) -> bool {
+        if self.sessions.get(&peer).is_some_and(|session| session.id == id) {
+            self.sessions.remove(&peer);
+            return true;
+        }
+        false
+    }
+
+    /// Ferme la session d'un voisin et oublie ses pertes ; rend le transport de la session
+    fn forget(&mut self, peer: Ipv4Addr) -> Option<LsaTransport> {
+        self.retransmits.remove(&peer);
+        self.sessions.remove(&peer).map(|session| session.transport)
+    }
+
+    fn allocate_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+/// Transport configuré sur l'interface d'un voisin
+fn transport_for(config: &crate::read_config::RouterConfig, interface: &str) -> LsaTransport {
+    config.interface(interface).map(|iface| iface.transport).unwrap_or_default()
+}
+
+/// Transport des sessions d'une interface : `Tcp` pour `tcp` et `auto`, `Quic` pour `quic`
+fn session_transport(configured: LsaTransport) -> Option<LsaTransport> {
+    match configured {
+        LsaTransport::Udp => None,
+        LsaTransport::Tcp | LsaTransport::Auto => Some(LsaTransport::Tcp),
+        LsaTransport::Quic => Some(LsaTransport::Quic),
+    }
+}
+
+/// Préfixe des journaux d'une session
+fn label(transport: LsaTransport) -> &'static str {
+    match transport {
+        LsaTransport::Quic => "[QUIC]",
+        _ => "[TCP]",
+    }
+}
+
+/// Adresse locale du sous-réseau partagé avec `peer`
+fn local_address(state: &AppState, peer: Ipv4Addr) -> Option<(Ipv4Addr, String)> {
+    state.interfaces.interfaces().into_iter()
+        .find(|iface| iface.network.contains(peer))
+        .map(|iface| (iface.ip(), iface.name.clone()))
+}
+
+/// Lance les tâches de lecture et d'écriture d'une session et l'enregistre
+async fn establish(state: &Arc<AppState>, peer: Ipv4Addr, stream: Box<dyn Stream>, transport: LsaTransport, outgoing: bool) {
+    let Some((local, interface)) = local_address(state, peer) else {
+        debug!("{} No interface shares a network with {}, session refused", label(transport), peer);
+        return;
+    };
+    let mut sessions = state.stream_sessions.lock().await;
+    let id = sessions.allocate_id();
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let (sender, mut outbox) = channel::<Vec<u8>>(WRITE_QUEUE_DEPTH);
+    let frames_rx = Arc::new(AtomicU64::new(0));
+    let frames_dropped = Arc::new(AtomicU64::new(0));
+
+    let write_task = tokio::spawn(async move {
+        while let Some(frame) = outbox.recv().await {
+            if let Err(e) = writer.write_all(&frame).await {
+                debug!("{} Write to {} failed: {}", label(transport), peer, e);
+                break;
+            }
+        }
+    });
+    let read_task = {
+        let (state, frames_rx, frames_dropped) = (Arc::clone(state), Arc::clone(&frames_rx), Arc::clone(&frames_dropped));
+        tokio::spawn(async move {
+            let source = SocketAddr::new(IpAddr::V4(peer), state.port);
+            let reason = loop {
+                let mut header = [0u8; crate::framing::HEADER_LEN];
+                if let Err(e) = reader.read_exact(&mut header).await {
+                    break e.to_string();
+                }
+                let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+                let mut frame = header.to_vec();
+                frame.resize(crate::framing::HEADER_LEN + length, 0);
+                if let Err(e) = reader.read_exact(&mut frame[crate::framing::HEADER_LEN..]).await {
+                    break e.to_string();
+                }
+                frames_rx.fetch_add(1, Ordering::Relaxed);
+                match state.stream_inbound.sender.try_send((frame, source, transport == LsaTransport::Quic)) {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => {
+                        debug!("{} Receive queue full, dropping a frame from {}", label(transport), peer);
+                        frames_dropped.fetch_add(1, Ordering::Relaxed);
+                        state.metrics.stream_frames_dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(TrySendError::Closed(_)) => break "receive loop stopped".to_string(),
+                }
+            };
+            if state.stream_sessions.lock().await.close(peer, id) {
+                info!("{} Session with {} closed: {}", label(transport), peer, reason);
+            }
+        })
+    };
+
+    let session = Session {
+        id,
+        interface,
+        transport,
+        initiator: if outgoing { local } else { peer },
+        outgoing,
+        writer: sender,
+        tasks: [read_task, write_task],
+        established: Instant::now(),
+        frames_tx: 0,
+        frames_rx,
+        frames_dropped,
+    };
+    if sessions.register(peer, local, session) {
+        info!("{} Session with {} established ({})", label(transport), peer, if outgoing { "outgoing" } else { "incoming" });
+    } else {
+        debug!("{} Concurrent session with {} dropped", label(transport), peer);
+    }
+}
+
+/// Ouvre une session vers un voisin
+async fn connect(state: &Arc<AppState>, peer: Ipv4Addr, transport: LsaTransport) {
+    let target = SocketAddr::new(IpAddr::V4(peer), state.port);
+    let connected = match transport {
+        LsaTransport::Quic => {
+            let Some(endpoint) = state.stream_sessions.lock().await.endpoint.clone() else {
+                return;
+            };
+            tokio::time::timeout(CONNECT_TIMEOUT, crate::quic::connect(&endpoint, target)).await
+        }
+        _ => tokio::time::timeout(CONNECT_TIMEOUT, state.socket.connect_stream(target)).await,
+    };
+    match connected {
+        Ok(Ok(stream)) => establish(state, peer, stream, transport, true).await,
+        Ok(Err(e)) => debug!("{} Connection to {} failed: {}", label(transport), target, e),
+        Err(_) => debug!("{} Connection to {} timed out", label(transport), target),
+    }
+}
+
+/// Accepte une session entrante si l'appelant est un voisin d'une interface dont c'est le
+/// transport (`tcp` ou `auto` pour TCP, `quic` pour QUIC)
+async fn accept(state: &Arc<AppState>, stream: Box<dyn Stream>, caller: SocketAddr, transport: LsaTransport) {
+    let IpAddr::V4(peer) = caller.ip() else {
+        return;
+    };
+    let config = state.config().await;
+    let interface = local_address(state, peer).map(|(_, interface)| interface);
+    let known = state.neighbors.read().await.values().any(|neighbor| neighbor.neighbor_ip == peer.to_string());
+    match interface {
+        Some(interface) if known && session_transport(transport_for(&config, &interface)) == Some(transport) => {
+            establish(state, peer, stream, transport, false).await;
+        }
+        _ => debug!("{} Session from {} refused", label(transport), caller),
+    }
+}
+
+/// Ouvre les sessions voulues (interfaces `tcp`, interfaces `auto` après des pertes) et
+/// ferme celles des voisins perdus ou des interfaces repassées en UDP
+async fn maintain(state: &Arc<AppState>) {
+    let config = state.config().await;
+    let neighbors: Vec<crate::types::Neighbor> = state.neighbors.read().await.values().cloned().collect();
+    let mut wanted = Vec::new();
+    for neighbor in &neighbors {
+        let Ok(peer) = neighbor.neighbor_ip.parse::<Ipv4Addr>() else {
+            continue;
+        };
+        if !crate::neighbor::is_usable(state, neighbor).await {
+            continue;
+        }
+        let interface = NeighborKey::for_neighbor(&state.interfaces, neighbor).interface;
+        let configured = transport_for(&config, &interface);
+        let wants = match configured {
+            LsaTransport::Udp => false,
+            LsaTransport::Tcp | LsaTransport::Quic => true,
+            // Une session ouverte sur pertes est gardée tant que le voisin est actif
+            LsaTransport::Auto => {
+                let sessions = state.stream_sessions.lock().await;
+                sessions.has_session(peer) || sessions.lossy(peer)
+            }
+        };
+        if let Some(transport) = session_transport(configured).filter(|_| wants) {
+            wanted.push((peer, transport));
+        }
+    }
+
+    let stale: Vec<Ipv4Addr> = {
+        let sessions = state.stream_sessions.lock().await;
+        sessions.sessions.iter()
+            .filter(|(peer, session)| !wanted.contains(&(**peer, session.transport)))
+            .map(|(peer, _)| peer)
+            .chain(sessions.retransmits.keys().filter(|peer| !neighbors.iter().any(|neighbor| neighbor.neighbor_ip == peer.to_string())))
+            .copied()
+            .collect()
+    };
+    for peer in stale {
+        if let Some(transport) = state.stream_sessions.lock().await.forget(peer) {
+            info!("{} Session with {} closed: neighbor lost or transport changed", label(transport), peer);
+        }
+    }
+
+    // Chaque connexion s'établit à part : une poignée de main QUIC dure plusieurs allers-
+    // retours, pendant lesquels les sessions entrantes doivent rester acceptées
+    for (peer, transport) in wanted {
+        let mut sessions = state.stream_sessions.lock().await;
+        if sessions.has_session(peer) || !sessions.connecting.insert(peer) {
+            continue;
+        }
+        let state = Arc::clone(state);
+        tokio::spawn(async move {
+            connect(&state, peer, transport).await;
+            state.stream_sessions.lock().await.connecting.remove(&peer);
+        });
+    }
+}
+
+/// Point de connexion QUIC du routeur, si l'une de ses interfaces est en `quic`
+async fn open_endpoint(state: &Arc<AppState>) -> Option<quinn::Endpoint> {
+    if !state.config().await.interfaces.iter().any(|iface| iface.transport == LsaTransport::Quic) {
+        return None;
+    }
+    let quic = state.config().await.quic;
+    match state.socket.quic_socket().and_then(|socket| crate::quic::endpoint(socket, &quic)) {
+        Ok(endpoint) => {
+            state.stream_sessions.lock().await.endpoint = Some(endpoint.clone());
+            Some(endpoint)
+        }
+        Err(e) => {
+            warn!("[QUIC] Cannot open an endpoint next to port {}: {}, no QUIC session is possible", state.port, e);
+            None
+        }
+    }
+}
+
+/// Écoute les sessions entrantes (TCP, et QUIC si une interface est en `quic`) et
+/// entretient les sessions sortantes, chaque seconde
+pub fn spawn_tcp_session_task(state: Arc<AppState>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut listener = match state.socket.listen_stream().await {
+            Ok(listener) => Some(listener),
+            Err(e) => {
+                warn!("[TCP] Cannot listen on port {}: {}, only outgoing sessions are possible", state.port, e);
+                None
+            }
+        };
+        let endpoint = open_endpoint(&state).await;
+        let mut tick = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            tokio::select! {
+                accepted = async { listener.as_mut()?.accept().await.ok() }, if listener.is_some() => {
+                    if let Some((stream, caller)) = accepted {
+                        accept(&state, stream, caller, LsaTransport::Tcp).await;
+                    }
+                }
+                incoming = async { endpoint.as_ref()?.accept().await }, if endpoint.is_some() => {
+                    // La poignée de main se poursuit à part, sans retenir les autres sessions
+                    if let Some(incoming) = incoming {
+                        let state = Arc::clone(&state);
+                        tokio::spawn(async move {
+                            match tokio::time::timeout(CONNECT_TIMEOUT, crate::quic::open(incoming)).await {
+                                Ok(Ok((stream, caller))) => accept(&state, stream, caller, LsaTransport::Quic).await,
+                                Ok(Err(e)) => debug!("[QUIC] Incoming session failed: {}", e),
+                                Err(_) => debug!("[QUIC] Incoming session timed out"),
+                            }
+                        });
+                    }
+                }
+                _ = tick.tick() => {
+                    if state.is_enabled().await {
+                        maintain(&state).await;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Trames d'un message pour une session : une seule, sauf pour un message trop grand
+fn frames<T: crate::framing::Framed>(message: &T, serialized: &[u8], key: &SegmentKey) -> Result<Vec<Vec<u8>>> {
+    let frame = |message_type: u8, data: &[u8]| {
+        crate::framing::frame(message_type, crate::framing::Codec::Json, key.auth, &key.seal(data)?)
+    };
+    if serialized.len() <= MAX_STREAM_PAYLOAD {
+        return Ok(vec![frame(message.message_type(), serialized)?]);
+    }
+    crate::fragment::split(serialized).unwrap_or_default().iter()
+        .map(|fragment| frame(crate::framing::FRAGMENT, &serde_json::to_vec(fragment).map_err(AppError::SerializationError)?))
+        .collect()
+}
+
+/// Émet un message protocolaire (LSA, acquittement) depuis l'interface `local_ip` : par la
+/// session du voisin s'il en a une, en UDP sinon. Une diffusion part en UDP et aussi sur
+/// chaque session de l'interface
+pub async fn send_message<T: crate::framing::Framed>(
+    state: &AppState,
+    socket: &Socket,
+    local_ip: &str,
+    addr: &SocketAddr,
+    message: &T,
+    key: &SegmentKey,
+    log_prefix: &str,
+) -> Result<()> {
+    let peer = match addr.ip() {
+        IpAddr::V4(ip) => ip,
+        IpAddr::V6(_) => return crate::net_utils::send_message_within(socket, addr, message, key, state.max_payload(addr).await, log_prefix).await,
+    };
+    let serialized = serde_json::to_vec(message).map_err(AppError::SerializationError)?;
+    let mut sent_over_session = false;
+    {
+        let mut sessions = state.stream_sessions.lock().await;
+        if !sessions.sessions.is_empty() {
+            let interface = state.interfaces.name_for_address(local_ip);
+            let broadcast = peer.is_multicast() || state.interfaces.interfaces().iter().any(|iface| iface.network.broadcast() == peer);
+            // Sur QUIC, le TLS mutuel tient lieu de la clé du segment
+            let sealed = frames(message, &serialized, key)?;
+            let clear = frames(message, &serialized, &SegmentKey::plaintext())?;
+            for (address, session) in sessions.sessions.iter_mut() {
+                let chosen = *address == peer || (broadcast && Some(&session.interface) == interface.as_ref());
+                let frames = if session.transport == LsaTransport::Quic { &clear } else { &sealed };
+                if !chosen {
+                    continue;
+                }
+                // File d'écriture pleine : le message part en UDP vers ce voisin
+                if session.writer.capacity() < frames.len() {
+                    debug!("{} Write queue to {} full, {} frame(s) dropped", log_prefix, address, frames.len());
+                    session.frames_dropped.fetch_add(frames.len() as u64, Ordering::Relaxed);
+                    state.metrics.stream_frames_dropped.fetch_add(frames.len() as u64, Ordering::Relaxed);
+                    continue;
+                }
+                if frames.iter().all(|frame| session.writer.try_send(frame.clone()).is_ok()) {
+                    session.frames_tx += frames.len() as u64;
+                    debug!("{} Sent over {} to {}", log_prefix, label(session.transport), address);
+                    sent_over_session |= *address == peer;
+                }
+            }
+        }
+    }
+    if sent_over_session {
+        if crate::trace::is_enabled() {
+            crate::trace::record(crate::trace::Direction::Sent, *addr, state.interfaces.name_for_address(local_ip), &serialized);
+        }
+        return Ok(());
+    }
+    crate::net_utils::send_message_within(socket, addr, message, key, state.max_payload(addr).await, log_prefix).await
+}
+
+/// Commande `sessions` : sessions TCP et QUIC ouvertes avec les voisins
+pub async fn report(state: &AppState) -> String {
+    // Noms relevés avant de prendre les sessions : `neighbors` précède `stream_sessions`
+    let names: HashMap<String, String> = state.neighbors.read().await.iter()
+        .map(|(ip, neighbor)| (ip.clone(), neighbor.display_name()))
+        .collect();
+    let sessions = state.stream_sessions.lock().await;
+    if sessions.sessions.is_empty() {
+        return "Aucune session TCP ni QUIC".to_string();
+    }
+    let mut lines = vec!["Sessions :".to_string()];
+    for (peer, session) in &sessions.sessions {
+        let name = names.get(&peer.to_string()).cloned().unwrap_or_else(|| peer.to_string());
+        lines.push(format!(
+            "  {:<28} {:<4} sur {}, {} il y a {}s, {} trames émises, {} reçues, {} abandonnées",
+            name,
+            if session.transport == LsaTransport::Quic { "QUIC" } else { "TCP" },
+            session.interface,
+            if session.outgoing { "ouverte" } else { "acceptée" },
+            session.established.elapsed().as_secs(),
+            session.frames_tx,
+            session.frames_rx.load(Ordering::Relaxed),
+            session.frames_dropped.load(Ordering::Relaxed),
+        ));
+    }
+    lines.join("\n")
+}
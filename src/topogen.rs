@@ -0,0 +1,48 @@
+use routing_project::read_config;
+use routing_project::topology::{self, LabFormat, TopologySpec};
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: topogen <topologie.toml> [--out <répertoire>] [--containerlab]";
+
+/// Génère un lab prêt à lancer : fichier compose (ou containerlab) et configuration de
+/// chaque routeur, avec une clé partagée
+fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let out = PathBuf::from(read_config::option_from_args(args.iter().cloned(), "--out").unwrap_or_else(|| "lab".to_string()));
+    let format = if args.iter().any(|arg| arg == "--containerlab") {
+        LabFormat::Containerlab
+    } else {
+        LabFormat::Compose
+    };
+    let mut positional = Vec::new();
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        if arg == "--out" {
+            args_iter.next();
+        } else if !arg.starts_with("--") {
+            positional.push(arg);
+        }
+    }
+    let [spec_path] = positional[..] else {
+        eprintln!("{}", USAGE);
+        std::process::exit(1);
+    };
+
+    let spec = TopologySpec::parse(&std::fs::read_to_string(spec_path)?)?;
+    let key = spec.key.clone().unwrap_or_else(topology::generate_key);
+    let files = spec.generate(format, &key)?;
+    std::fs::create_dir_all(&out)?;
+    for file in &files {
+        std::fs::write(out.join(&file.path), &file.content)?;
+        println!("{}", out.join(&file.path).display());
+    }
+    match format {
+        LabFormat::Compose => println!("Lancement: docker compose -f {} up -d", out.join("compose.yaml").display()),
+        LabFormat::Containerlab => println!("Lancement: sudo containerlab deploy -t {}", out.join(&files[0].path).display()),
+    }
+    Ok(())
+}
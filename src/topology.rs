@@ -0,0 +1,310 @@
+use crate::error::{AppError, Result};
+use pnet::ipnetwork::Ipv4Network;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::net::Ipv4Addr;
+
+/// Capacité des liens sans capacité explicite
+const DEFAULT_CAPACITY_MBPS: u32 = 100;
+
+/// Description d'un réseau de test lue par `topogen`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TopologySpec {
+    /// Nom du lab (projet compose, topologie containerlab)
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// Image des routeurs (`docker build -t pospf .`)
+    #[serde(default = "default_image")]
+    pub image: String,
+    /// Clé partagée, en base64 ; générée si absente
+    #[serde(default)]
+    pub key: Option<String>,
+    pub routers: Vec<RouterSpec>,
+    #[serde(default)]
+    pub links: Vec<LinkSpec>,
+}
+
+fn default_name() -> String {
+    "pospf-lab".to_string()
+}
+
+fn default_image() -> String {
+    "pospf".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RouterSpec {
+    /// Nom d'hôte du routeur, qui choisit aussi son fichier `config_<nom>.toml`
+    pub name: String,
+    /// Réseaux d'accès du routeur, qui y prend la première adresse
+    #[serde(default)]
+    pub lans: Vec<Ipv4Network>,
+}
+
+/// Segment entre deux routeurs ou plus
+#[derive(Debug, Clone, Deserialize)]
+pub struct LinkSpec {
+    pub routers: Vec<String>,
+    #[serde(default)]
+    pub capacity_mbps: Option<u32>,
+    /// Sous-réseau du segment ; 10.<n>.0.0/24 pour le n-ième lien si absent
+    #[serde(default)]
+    pub subnet: Option<Ipv4Network>,
+}
+
+/// Format du fichier de lab produit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabFormat {
+    Compose,
+    Containerlab,
+}
+
+/// Interface d'un routeur dans le lab généré
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabInterface {
+    pub name: String,
+    /// Réseau compose ou lien containerlab auquel l'interface est attachée
+    pub network: String,
+    pub address: Ipv4Network,
+    pub capacity_mbps: u32,
+    /// Réseau d'accès (sans autre routeur)
+    pub lan: bool,
+}
+
+/// Fichier produit : chemin relatif au répertoire de sortie et contenu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedFile {
+    pub path: String,
+    pub content: String,
+}
+
+fn invalid(message: impl Into<String>) -> AppError {
+    AppError::ConfigError(format!("topology: {}", message.into()))
+}
+
+impl TopologySpec {
+    pub fn parse(content: &str) -> Result<Self> {
+        let spec: Self = toml::from_str(content).map_err(|e| invalid(e.to_string()))?;
+        spec.check()?;
+        Ok(spec)
+    }
+
+    fn check(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for router in &self.routers {
+            if !crate::packet::is_valid_hostname(&router.name) || router.name.contains('.') {
+                return Err(invalid(format!("'{}' is not a valid router name", router.name)));
+            }
+            if !names.insert(router.name.as_str()) {
+                return Err(invalid(format!("router {} is declared twice", router.name)));
+            }
+        }
+        if self.routers.is_empty() {
+            return Err(invalid("no router declared"));
+        }
+        let mut subnets: Vec<Ipv4Network> = self.routers.iter().flat_map(|router| router.lans.iter().copied()).collect();
+        for (index, link) in self.links.iter().enumerate() {
+            if link.routers.len() < 2 {
+                return Err(invalid(format!("links[{}] needs at least two routers", index)));
+            }
+            if let Some(unknown) = link.routers.iter().find(|name| !names.contains(name.as_str())) {
+                return Err(invalid(format!("links[{}]: unknown router {}", index, unknown)));
+            }
+            if link.routers.iter().collect::<HashSet<_>>().len() != link.routers.len() {
+                return Err(invalid(format!("links[{}] lists a router twice", index)));
+            }
+            if link.capacity_mbps == Some(0) {
+                return Err(invalid(format!("links[{}]: capacity_mbps must be greater than 0", index)));
+            }
+            subnets.push(self.link_subnet(index)?);
+        }
+        for (index, subnet) in subnets.iter().enumerate() {
+            // Adresses des routeurs de .1 à .n, passerelle du pont en .254
+            if subnet.prefix() > 24 || subnet.prefix() == 0 {
+                return Err(invalid(format!("subnet {} must be between /1 and /24", subnet)));
+            }
+            if subnets[..index].iter().any(|other| other.contains(subnet.network()) || subnet.contains(other.network())) {
+                return Err(invalid(format!("subnet {} overlaps another network", subnet)));
+            }
+        }
+        Ok(())
+    }
+
+    fn link_subnet(&self, index: usize) -> Result<Ipv4Network> {
+        if let Some(subnet) = self.links[index].subnet {
+            return Ipv4Network::new(subnet.network(), subnet.prefix()).map_err(|e| invalid(e.to_string()));
+        }
+        let octet = u8::try_from(index + 1).map_err(|_| invalid("more than 255 links need explicit subnets"))?;
+        Ok(Ipv4Network::new(Ipv4Addr::new(10, octet, 0, 0), 24).expect("valid /24"))
+    }
+
+    /// Interfaces de chaque routeur, dans l'ordre des liens puis des réseaux d'accès.
+    /// Sous compose elles sont nommées eth0, eth1... ; sous containerlab eth0 est réservée
+    /// à la gestion, les liens commencent à eth1 et les réseaux d'accès sont des
+    /// interfaces `lan<n>` locales au routeur.
+    pub fn interfaces(&self, router: &str, format: LabFormat) -> Result<Vec<LabInterface>> {
+        let mut interfaces = Vec::new();
+        let first = match format {
+            LabFormat::Compose => 0,
+            LabFormat::Containerlab => 1,
+        };
+        for (index, link) in self.links.iter().enumerate() {
+            let Some(position) = link.routers.iter().position(|name| name == router) else {
+                continue;
+            };
+            let subnet = self.link_subnet(index)?;
+            interfaces.push(LabInterface {
+                name: format!("eth{}", first + interfaces.len()),
+                network: format!("N_L{}", index + 1),
+                address: host(subnet, position as u32 + 1)?,
+                capacity_mbps: link.capacity_mbps.unwrap_or(DEFAULT_CAPACITY_MBPS),
+                lan: false,
+            });
+        }
+        let spec = self.routers.iter().find(|spec| spec.name == router).ok_or_else(|| invalid(format!("unknown router {}", router)))?;
+        for (index, lan) in spec.lans.iter().enumerate() {
+            let name = match format {
+                LabFormat::Compose => format!("eth{}", first + interfaces.len()),
+                LabFormat::Containerlab => format!("lan{}", index),
+            };
+            interfaces.push(LabInterface {
+                name,
+                network: format!("N_{}_LAN{}", router, index + 1),
+                address: host(*lan, 1)?,
+                capacity_mbps: DEFAULT_CAPACITY_MBPS,
+                lan: true,
+            });
+        }
+        Ok(interfaces)
+    }
+
+    /// Fichier de lab puis `config_<routeur>.toml` de chaque routeur
+    pub fn generate(&self, format: LabFormat, key: &str) -> Result<Vec<GeneratedFile>> {
+        let mut files = vec![match format {
+            LabFormat::Compose => GeneratedFile { path: "compose.yaml".to_string(), content: self.compose()? },
+            LabFormat::Containerlab => GeneratedFile { path: format!("{}.clab.yml", self.name), content: self.containerlab()? },
+        }];
+        for router in &self.routers {
+            let path = format!("config_{}.toml", router.name);
+            let content = router_config(key, &self.interfaces(&router.name, format)?);
+            // Le routeur doit pouvoir démarrer avec la configuration produite
+            crate::read_config::parse_config(&content, &path)?.validate()?;
+            files.push(GeneratedFile { path, content });
+        }
+        Ok(files)
+    }
+
+    fn compose(&self) -> Result<String> {
+        let mut out = format!("name: {}\n\nservices:\n", self.name);
+        for router in &self.routers {
+            let service = router.name.to_lowercase().replace('_', "-");
+            let _ = writeln!(out, "  {}:", service);
+            let _ = writeln!(out, "    image: {}", self.image);
+            let _ = writeln!(out, "    container_name: {}", router.name);
+            let _ = writeln!(out, "    hostname: {}", router.name);
+            let _ = writeln!(out, "    volumes:");
+            let _ = writeln!(out, "      - ./config_{0}.toml:/etc/pospf/config_{0}.toml:ro", router.name);
+            let _ = writeln!(out, "    networks:");
+            // La priorité fixe l'ordre des interfaces, et donc leurs noms eth0, eth1...
+            for (index, iface) in self.interfaces(&router.name, LabFormat::Compose)?.iter().enumerate() {
+                let _ = writeln!(out, "      {}:", iface.network);
+                let _ = writeln!(out, "        ipv4_address: {}", iface.address.ip());
+                let _ = writeln!(out, "        priority: {}", 1000 - 10 * index);
+            }
+            let _ = writeln!(out, "    cap_add:\n      - NET_ADMIN\n");
+        }
+        let _ = writeln!(out, "networks:");
+        let mut networks: Vec<(String, Ipv4Network)> = Vec::new();
+        for index in 0..self.links.len() {
+            networks.push((format!("N_L{}", index + 1), self.link_subnet(index)?));
+        }
+        for router in &self.routers {
+            for (index, lan) in router.lans.iter().enumerate() {
+                networks.push((format!("N_{}_LAN{}", router.name, index + 1), *lan));
+            }
+        }
+        for (name, subnet) in networks {
+            let network = Ipv4Network::new(subnet.network(), subnet.prefix()).map_err(|e| invalid(e.to_string()))?;
+            let _ = writeln!(out, "  {}:", name);
+            let _ = writeln!(out, "    driver: bridge");
+            let _ = writeln!(out, "    ipam:\n      config:");
+            let _ = writeln!(out, "        - subnet: {}", network);
+            let _ = writeln!(out, "          gateway: {}", host(network, 254)?.ip());
+        }
+        Ok(out)
+    }
+
+    fn containerlab(&self) -> Result<String> {
+        let mut out = format!("name: {}\n\ntopology:\n  nodes:\n", self.name);
+        for router in &self.routers {
+            let _ = writeln!(out, "    {}:", router.name);
+            let _ = writeln!(out, "      kind: linux");
+            let _ = writeln!(out, "      image: {}", self.image);
+            let _ = writeln!(out, "      binds:");
+            let _ = writeln!(out, "        - config_{0}.toml:/etc/pospf/config_{0}.toml:ro", router.name);
+            // Les liens sont branchés après le démarrage du conteneur : le démon n'est
+            // lancé qu'une fois ses interfaces présentes et adressées
+            let interfaces = self.interfaces(&router.name, LabFormat::Containerlab)?;
+            let mut script = Vec::new();
+            if let Some(last) = interfaces.iter().rfind(|iface| !iface.lan) {
+                script.push(format!("until ip link show {} >/dev/null 2>&1; do sleep 1; done", last.name));
+            }
+            for iface in &interfaces {
+                if iface.lan {
+                    script.push(format!("ip link add {} type dummy", iface.name));
+                    script.push(format!("ip link set {} up", iface.name));
+                }
+                script.push(format!("ip addr add {} dev {}", iface.address, iface.name));
+            }
+            script.push("exec routing".to_string());
+            let _ = writeln!(out, "      cmd: 'sh -c \"{}\"'", script.join("; "));
+        }
+        let _ = writeln!(out, "\n  links:");
+        for (index, link) in self.links.iter().enumerate() {
+            let [a, b] = &link.routers[..] else {
+                return Err(invalid(format!("links[{}]: containerlab links join exactly two routers", index)));
+            };
+            let endpoint = |router: &str| -> Result<String> {
+                let iface = self.interfaces(router, LabFormat::Containerlab)?
+                    .into_iter()
+                    .find(|iface| iface.network == format!("N_L{}", index + 1))
+                    .expect("router is on the link");
+                Ok(format!("\"{}:{}\"", router, iface.name))
+            };
+            let _ = writeln!(out, "    - endpoints: [{}, {}]", endpoint(a)?, endpoint(b)?);
+        }
+        Ok(out)
+    }
+}
+
+/// `n`-ième adresse du sous-réseau, avec son préfixe
+fn host(subnet: Ipv4Network, n: u32) -> Result<Ipv4Network> {
+    let address = Ipv4Addr::from(u32::from(subnet.network()) + n);
+    if !subnet.contains(address) || address == subnet.broadcast() {
+        return Err(invalid(format!("subnet {} has no room for address .{}", subnet, n)));
+    }
+    Ipv4Network::new(address, subnet.prefix()).map_err(|e| invalid(e.to_string()))
+}
+
+/// Configuration d'un routeur du lab
+pub fn router_config(key: &str, interfaces: &[LabInterface]) -> String {
+    let mut out = format!("key = \"{}\"\n", key);
+    for iface in interfaces {
+        let _ = writeln!(out, "\n[[interfaces]]");
+        let _ = writeln!(out, "name = \"{}\"", iface.name);
+        let _ = writeln!(out, "capacity_mbps = {}  # {} {}", iface.capacity_mbps, iface.network, iface.address);
+        let _ = writeln!(out, "link_active = true");
+    }
+    out
+}
+
+/// Nouvelle clé AES-256 en base64
+pub fn generate_key() -> String {
+    use base64::Engine;
+    use rand::RngCore;
+
+    let mut key = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut key);
+    base64::engine::general_purpose::STANDARD.encode(key)
+}
@@ -0,0 +1,149 @@
+use crate::error::Result;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+
+/// Capacité par défaut du tampon de traces
+pub const DEFAULT_TRACE_CAPACITY: usize = 1000;
+
+/// Longueur maximale du contenu affiché par entrée dans `trace`
+const DISPLAY_PAYLOAD_LEN: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// Message protocolaire émis ou reçu, tel qu'il circule une fois déchiffré
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceEntry {
+    /// Horodatage en millisecondes depuis l'epoch
+    pub timestamp_ms: u64,
+    pub direction: Direction,
+    pub peer: SocketAddr,
+    pub interface: Option<String>,
+    pub message_type: u64,
+    pub payload: String,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self.direction {
+            Direction::Sent => "SEND",
+            Direction::Received => "RECV",
+        };
+        let kind = match self.message_type {
+            1 => "HELLO",
+            2 => "LSA",
+            4 => "ACK",
+            5 => "CHAL",
+            6 => "RESP",
+            8 => "ECHO",
+            _ => "?",
+        };
+        let payload: String = self.payload.chars().take(DISPLAY_PAYLOAD_LEN).collect();
+        let ellipsis = if self.payload.chars().count() > DISPLAY_PAYLOAD_LEN { "..." } else { "" };
+        write!(
+            f,
+            "{}.{:03} {} {:<5} {} [{}] {}{}",
+            self.timestamp_ms / 1000,
+            self.timestamp_ms % 1000,
+            direction,
+            kind,
+            self.peer,
+            self.interface.as_deref().unwrap_or("-"),
+            payload,
+            ellipsis
+        )
+    }
+}
+
+struct TraceBuffer {
+    enabled: bool,
+    capacity: usize,
+    entries: VecDeque<TraceEntry>,
+}
+
+static TRACE: Mutex<TraceBuffer> = Mutex::new(TraceBuffer {
+    enabled: false,
+    capacity: DEFAULT_TRACE_CAPACITY,
+    entries: VecDeque::new(),
+});
+
+fn buffer() -> std::sync::MutexGuard<'static, TraceBuffer> {
+    TRACE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Applique la configuration de la capture (activation et taille du tampon)
+pub fn configure(enabled: bool, capacity: usize) {
+    let mut trace = buffer();
+    trace.enabled = enabled;
+    trace.capacity = capacity.max(1);
+    while trace.entries.len() > trace.capacity {
+        trace.entries.pop_front();
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    buffer().enabled = enabled;
+}
+
+pub fn is_enabled() -> bool {
+    buffer().enabled
+}
+
+/// Enregistre un message protocolaire (HELLO, LSA, acquittement, authentification) ; les échanges de contrôle ne sont pas tracés
+pub fn record(direction: Direction, peer: SocketAddr, interface: Option<String>, payload: &[u8]) {
+    if !is_enabled() {
+        return;
+    }
+    let message_type = serde_json::from_slice::<serde_json::Value>(payload)
+        .ok()
+        .and_then(|json| json.get("message_type").and_then(|v| v.as_u64()));
+    let Some(message_type) = message_type.filter(|t| matches!(t, 1 | 2 | 4 | 5 | 6)) else {
+        return;
+    };
+    let timestamp_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_else(|_| std::time::Duration::from_secs(0))
+        .as_millis() as u64;
+    let mut trace = buffer();
+    if trace.entries.len() >= trace.capacity {
+        trace.entries.pop_front();
+    }
+    trace.entries.push_back(TraceEntry {
+        timestamp_ms,
+        direction,
+        peer,
+        interface,
+        message_type,
+        payload: String::from_utf8_lossy(payload).into_owned(),
+    });
+}
+
+/// Les `limit` dernières entrées, de la plus ancienne à la plus récente
+pub fn recent(limit: usize) -> Vec<TraceEntry> {
+    let trace = buffer();
+    let skip = trace.entries.len().saturating_sub(limit);
+    trace.entries.iter().skip(skip).cloned().collect()
+}
+
+pub fn clear() {
+    buffer().entries.clear();
+}
+
+/// Écrit tout le tampon dans un fichier, une entrée JSON par ligne
+pub fn save(path: &str) -> Result<usize> {
+    let entries = recent(usize::MAX);
+    let mut file = std::fs::File::create(path)?;
+    for entry in &entries {
+        serde_json::to_writer(&mut file, entry)?;
+        file.write_all(b"\n")?;
+    }
+    Ok(entries.len())
+}
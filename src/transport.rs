@@ -0,0 +1,112 @@
+use crate::sim::{VirtualListener, VirtualSocket};
+use quinn::Runtime;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+
+/// Flux d'une session avec un voisin : connexion TCP, flux QUIC ou tube en mémoire du
+/// simulateur
+pub trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+/// Écoute des sessions TCP sur le port du protocole
+#[derive(Debug)]
+pub enum StreamListener {
+    Tcp(TcpListener),
+    Virtual(VirtualListener),
+}
+
+impl StreamListener {
+    pub async fn accept(&mut self) -> io::Result<(Box<dyn Stream>, SocketAddr)> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, peer) = listener.accept().await?;
+                stream.set_nodelay(true)?;
+                Ok((Box::new(stream), peer))
+            }
+            Self::Virtual(listener) => {
+                let (stream, peer) = listener.accept().await?;
+                Ok((Box::new(stream), peer))
+            }
+        }
+    }
+}
+
+/// Point d'émission/réception du protocole : socket UDP réelle ou lien virtuel du simulateur
+#[derive(Debug)]
+pub enum Socket {
+    Udp(UdpSocket),
+    Virtual(VirtualSocket),
+}
+
+impl From<UdpSocket> for Socket {
+    fn from(socket: UdpSocket) -> Self {
+        Self::Udp(socket)
+    }
+}
+
+impl Socket {
+    pub async fn send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        match self {
+            Self::Udp(socket) => socket.send_to(buf, target).await,
+            Self::Virtual(socket) => socket.send_to(buf, target),
+        }
+    }
+
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        match self {
+            Self::Udp(socket) => socket.recv_from(buf).await,
+            Self::Virtual(socket) => socket.recv_from(buf).await,
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Self::Udp(socket) => socket.local_addr(),
+            Self::Virtual(socket) => Ok(socket.local_addr()),
+        }
+    }
+
+    /// Écoute TCP sur l'adresse et le port de la socket
+    pub async fn listen_stream(&self) -> io::Result<StreamListener> {
+        match self {
+            Self::Udp(socket) => Ok(StreamListener::Tcp(TcpListener::bind(socket.local_addr()?).await?)),
+            Self::Virtual(socket) => Ok(StreamListener::Virtual(socket.listen())),
+        }
+    }
+
+    /// Ouvre une session TCP vers `target`
+    pub async fn connect_stream(&self, target: SocketAddr) -> io::Result<Box<dyn Stream>> {
+        match self {
+            Self::Udp(_) => {
+                let stream = TcpStream::connect(target).await?;
+                stream.set_nodelay(true)?;
+                Ok(Box::new(stream))
+            }
+            Self::Virtual(socket) => Ok(Box::new(socket.connect(target)?)),
+        }
+    }
+
+    /// Socket des sessions QUIC, sur le port UDP qui suit celui de la socket
+    pub fn quic_socket(&self) -> io::Result<Arc<dyn quinn::AsyncUdpSocket>> {
+        let mut address = self.local_addr()?;
+        let port = crate::quic::session_port(address.port())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("no UDP port after {} for QUIC sessions", address.port())))?;
+        address.set_port(port);
+        match self {
+            Self::Udp(_) => quinn::TokioRuntime.wrap_udp_socket(std::net::UdpSocket::bind(address)?),
+            Self::Virtual(socket) => Ok(Arc::new(socket.with_port(port))),
+        }
+    }
+
+    /// Socket UDP sous-jacente (options multicast), absente pour un lien virtuel
+    pub fn as_udp(&self) -> Option<&UdpSocket> {
+        match self {
+            Self::Udp(socket) => Some(socket),
+            Self::Virtual(_) => None,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use crate::error::Result;
+use crate::read_config::RouterConfig;
+use crate::{AppState, RouterDaemon};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::info;
+
+/// Ouvre et démarre une instance par VRF configurée, joignable ensuite par les commandes
+/// `vrf <nom> ...` de l'instance principale `main`
+pub async fn start_instances(config: &RouterConfig, config_path: Option<PathBuf>, main: &RouterDaemon) -> Result<Vec<RouterDaemon>> {
+    let mut instances = Vec::new();
+    for vrf in &config.vrfs {
+        let mut instance = RouterDaemon::new_in_vrf(config, config_path.clone(), vrf).await?;
+        instance.start().await?;
+        main.handle().attach_vrf(&vrf.name, instance.handle()).await;
+        info!("VRF {} started (table {})", vrf.name, vrf.table);
+        instances.push(instance);
+    }
+    Ok(instances)
+}
+
+/// Commande `vrf` : seule, liste les VRF ; `vrf <nom> <commande>` exécute la commande dans
+/// l'instance de la VRF
+pub async fn execute(state: &Arc<AppState>, arguments: &str) -> String {
+    let config = state.config().await;
+    let arguments = arguments.trim();
+    if arguments.is_empty() {
+        if config.vrfs.is_empty() {
+            return "Aucune VRF configurée".to_string();
+        }
+        let instances = state.vrf_instances.read().await;
+        let mut lines = vec!["VRF:".to_string()];
+        for vrf in &config.vrfs {
+            let interfaces = match instances.get(&vrf.name) {
+                Some(instance) => instance.state().config().await.interfaces.iter().map(|iface| iface.name.clone()).collect::<Vec<_>>().join(", "),
+                None => "non démarrée".to_string(),
+            };
+            lines.push(format!("  {} : table {}, interfaces {}", vrf.name, vrf.table, interfaces));
+        }
+        return lines.join("\n");
+    }
+    let (name, command) = arguments.split_once(char::is_whitespace).unwrap_or((arguments, ""));
+    if command.trim().is_empty() {
+        return "Usage: vrf <nom> <commande>".to_string();
+    }
+    let Some(instance) = state.vrf_instances.read().await.get(name).cloned() else {
+        return format!("Échec: VRF '{}' inconnue", name);
+    };
+    // La commande de l'instance peut elle-même être une commande `vrf`
+    Box::pin(instance.command(command.trim())).await
+}
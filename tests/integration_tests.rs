@@ -0,0 +1,2624 @@
+use routing_project::control::{command_from_args, response_for};
+use routing_project::control_auth::signed_command;
+use routing_project::read_config::{ControlRole, ControlUser, FilterAction, InterfaceConfig, LinkType, LsaTransport, PrefixListEntry, QuicConfig, ReceiveConfig, RouterConfig, RouterRole, StaticNeighborConfig};
+use routing_project::sim::{LinkProfile, Simulation};
+use routing_project::read_config::DefaultOriginateCondition;
+use routing_project::types::{AuthMode, ExternalMetricType, HelloMessage, OpaqueTlv, Prefix, RouteState, RouterId};
+use routing_project::route_backend::{DryRunBackend, KernelRoute, RouteBackend};
+use routing_project::grpc_api::proto::{telemetry_client::TelemetryClient, Empty};
+use routing_project::events::RouterEvent;
+use routing_project::persist::{PersistedInterface, PersistedState};
+use routing_project::quic;
+use routing_project::sequence::{self, SequenceCounter};
+use routing_project::transport::Socket;
+use routing_project::types::{ControlMessage, RouteRequest, RouteResponse};
+use routing_project::framing;
+use routing_project::net_utils::SegmentKey;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+const TICK: Duration = Duration::from_secs(1);
+
+type Tables = BTreeMap<String, HashMap<Prefix, (String, RouteState)>>;
+
+/// Anneau de `n` routeurs : r<i> relie r<i+1> par le segment 10.0.<i><i+1>.0/24
+/// (adresse .<i>) et porte un réseau local 192.168.<i>.0/24
+struct Ring {
+    n: usize,
+}
+
+impl Ring {
+    fn segment(&self, i: usize) -> String {
+        format!("r{}-r{}", i, self.next(i))
+    }
+
+    fn next(&self, i: usize) -> usize {
+        i % self.n + 1
+    }
+
+    fn prev(&self, i: usize) -> usize {
+        (i + self.n - 2) % self.n + 1
+    }
+
+    /// Adresse de r<i> sur le segment qui le relie à r<j>
+    fn address(&self, i: usize, j: usize) -> String {
+        let (a, b) = if self.next(i) == j { (i, j) } else { (j, i) };
+        format!("10.0.{}.{}", a * 10 + b, i)
+    }
+
+    fn lan(&self, i: usize) -> Prefix {
+        format!("192.168.{}.1/24", i).parse().unwrap()
+    }
+
+    async fn build(&self, seed: u64) -> Simulation {
+        self.build_with(seed, |_| Simulation::router_config()).await
+    }
+
+    /// Anneau dont la configuration de chaque routeur r<i> est donnée par `config(i)`
+    async fn build_with(&self, seed: u64, config: impl Fn(usize) -> RouterConfig) -> Simulation {
+        let mut sim = Simulation::new(seed);
+        for i in 1..=self.n {
+            sim.add_segment(&self.segment(i), &format!("10.0.{}.0/24", i * 10 + self.next(i))).unwrap();
+            sim.add_segment(&format!("lan{}", i), &format!("192.168.{}.0/24", i)).unwrap();
+        }
+        for i in 1..=self.n {
+            let (next, prev) = (self.next(i), self.prev(i));
+            sim.add_router(
+                &format!("r{}", i),
+                &[
+                    ("eth0", &self.segment(i), &self.address(i, next)),
+                    ("eth1", &self.segment(prev), &self.address(i, prev)),
+                    ("eth2", &format!("lan{}", i), &format!("192.168.{}.1", i)),
+                ],
+                config(i),
+            )
+            .unwrap();
+        }
+        sim.start().await.unwrap();
+        sim
+    }
+
+    /// Prochains sauts acceptables de r<from> vers r<to> (plus courts chemins en
+    /// nombre de sauts), les liens de `cut` étant coupés
+    fn expected_next_hops(&self, from: usize, to: usize, cut: &[(usize, usize)]) -> Vec<String> {
+        let linked = |a: usize, b: usize| !cut.contains(&(a, b)) && !cut.contains(&(b, a));
+        let mut distance = HashMap::from([(to, 0)]);
+        let mut queue = VecDeque::from([to]);
+        while let Some(node) = queue.pop_front() {
+            for neighbor in [self.next(node), self.prev(node)] {
+                if linked(node, neighbor) && !distance.contains_key(&neighbor) {
+                    distance.insert(neighbor, distance[&node] + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        [self.next(from), self.prev(from)]
+            .into_iter()
+            .filter(|&neighbor| linked(from, neighbor) && distance.get(&neighbor) == Some(&(distance[&from] - 1)))
+            .map(|neighbor| self.address(neighbor, from))
+            .collect()
+    }
+
+    /// Vrai si chaque routeur joint chaque réseau local distant par un plus court chemin
+    fn converged(&self, tables: &Tables, cut: &[(usize, usize)]) -> bool {
+        (1..=self.n).all(|from| {
+            let table = &tables[&format!("r{}", from)];
+            (1..=self.n).filter(|&to| to != from).all(|to| {
+                matches!(
+                    table.get(&self.lan(to)),
+                    Some((next_hop, RouteState::Active(_))) if self.expected_next_hops(from, to, cut).contains(next_hop)
+                )
+            })
+        })
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn ring_converges_to_shortest_next_hops() {
+    let ring = Ring { n: 6 };
+    let mut sim = ring.build(1).await;
+
+    let ticks = sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await;
+    assert!(ticks.is_some(), "no convergence: {:#?}", sim.routing_tables().await);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn spf_topology_spans_routers_beyond_direct_neighbors() {
+    let ring = Ring { n: 6 };
+    let mut sim = ring.build(5).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let r1 = sim.handle("r1").unwrap();
+    let state = r1.state();
+
+    let topology = routing_project::dijkstra::build_network_topology(std::sync::Arc::clone(state)).await;
+    let paths = topology.calculate_shortest_paths(&state.local_ip, routing_project::read_config::PathSelection::MinCost);
+    // r4, à l'opposé de l'anneau, n'est connu que par les LSA : identifiant 192.168.4.1
+    let r4 = paths.get("192.168.4.1").expect("r4 missing from the SPF tree");
+    assert_eq!(r4.hop_count, 3);
+    assert!(ring.expected_next_hops(1, 4, &[]).contains(&r4.next_hop), "{:?}", r4);
+    for far in ["192.168.3.1", "192.168.5.1"] {
+        assert_eq!(paths.get(far).map(|route| route.hop_count), Some(2), "{}", far);
+    }
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn shared_segment_is_a_star_around_its_designated_router() {
+    let mut sim = Simulation::new(6);
+    sim.add_segment("core", "10.0.0.0/24").unwrap();
+    for i in 1..=3 {
+        sim.add_segment(&format!("lan{}", i), &format!("192.168.{}.0/24", i)).unwrap();
+    }
+    for i in 1..=3 {
+        let mut config = Simulation::router_config();
+        // r1 a le plus petit identifiant mais la plus grande priorité : il est élu
+        let mut core = InterfaceConfig::new("eth0", 1000);
+        core.priority = if i == 1 { 10 } else { 1 };
+        config.interfaces.push(core);
+        sim.add_router(
+            &format!("r{}", i),
+            &[("eth0", "core", &format!("10.0.0.{}", i)), ("eth1", &format!("lan{}", i), &format!("192.168.{}.1", i))],
+            config,
+        )
+        .unwrap();
+    }
+    sim.start().await.unwrap();
+    sim.run_for(Duration::from_secs(30)).await;
+
+    let r2 = sim.handle("r2").unwrap();
+    let table = r2.routing_table().await;
+    for i in [1, 3] {
+        let lan: Prefix = format!("192.168.{}.1/24", i).parse().unwrap();
+        assert_eq!(table.get(&lan).map(|(next_hop, _)| next_hop.clone()), Some(format!("10.0.0.{}", i)), "{:#?}", table);
+    }
+    {
+        let lsdb = r2.state().lsdb.read().await;
+        let networks: Vec<_> = lsdb.network_lsas().collect();
+        assert_eq!(networks.len(), 1);
+        assert_eq!(networks[0].prefix, "10.0.0.0/24");
+        assert_eq!(networks[0].advertising_router, "192.168.1.1");
+        let mut attached = networks[0].attached_routers.clone();
+        attached.sort();
+        assert_eq!(attached, vec!["192.168.1.1", "192.168.2.1", "192.168.3.1"]);
+    }
+
+    // Les adjacences de r1 et r3 sur le segment passent par le pseudo-nœud
+    let topology = routing_project::dijkstra::build_network_topology(std::sync::Arc::clone(r2.state())).await;
+    assert!(topology.find_link("10.0.0.1", "10.0.0.3").is_none());
+    assert!(topology.find_link("10.0.0.1", "10.0.0.0/24").is_some());
+    assert_eq!(topology.find_link("10.0.0.0/24", "10.0.0.3").map(|link| (link.cost, link.hop_count)), Some((0, 0)));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn ring_reroutes_around_failed_link() {
+    let ring = Ring { n: 6 };
+    let mut sim = ring.build(2).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    sim.set_link_up(&ring.segment(1), false);
+    let cut = [(1, 2)];
+    // Intervalle de mort des voisins (22 s) puis propagation le long de l'anneau
+    let ticks = sim.run_until(TICK, 120, |tables| ring.converged(tables, &cut)).await;
+    assert!(ticks.is_some(), "no reconvergence: {:#?}", sim.routing_tables().await);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn routes_through_a_dead_neighbor_are_poisoned() {
+    let ring = Ring { n: 6 };
+    let mut sim = ring.build(4).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let r1 = sim.handle("r1").unwrap();
+    let dead = ring.address(2, 1);
+    let via_dead = r1.routing_table().await.values().filter(|(next_hop, _)| *next_hop == dead).count();
+    assert!(via_dead > 0);
+
+    sim.set_link_up(&ring.segment(1), false);
+    // Intervalle de mort (22 s) plus une période de vérification (11 s) : plus aucune
+    // route active par r2
+    sim.run_for(Duration::from_secs(35)).await;
+    let table = r1.routing_table().await;
+    assert!(
+        table.values().all(|(next_hop, route_state)| *next_hop != dead || matches!(route_state, RouteState::Unreachable)),
+        "{:#?}", table,
+    );
+    let metrics = r1.command("metrics").await;
+    let poisoned: u64 = metrics.lines()
+        .find_map(|line| line.strip_prefix("pospf_routes_poisoned_total "))
+        .and_then(|value| value.parse().ok())
+        .unwrap();
+    // Les routes déjà basculées par les LSA de l'autre côté de l'anneau ne sont plus à empoisonner
+    assert!(poisoned > 0);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn held_down_prefixes_ignore_worse_advertisements_until_expiry() {
+    use routing_project::hold_down::HoldDown;
+
+    let prefix: Prefix = "192.168.2.0/24".parse().unwrap();
+    let now = tokio::time::Instant::now();
+    let mut hold_down = HoldDown::new();
+    assert!(hold_down.start(prefix, "10.0.12.2", 2, Duration::from_secs(10), now));
+    // Une annonce injoignable répétée ne prolonge pas la retenue
+    assert!(!hold_down.start(prefix, "10.0.16.6", 7, Duration::from_secs(10), now + Duration::from_secs(5)));
+    assert_eq!(hold_down.get(&prefix).unwrap().metric, 2);
+
+    assert!(!hold_down.admits(&prefix, "10.0.16.6", &RouteState::Active(5), now));
+    assert!(hold_down.admits(&prefix, "10.0.16.6", &RouteState::Active(2), now));
+    assert!(hold_down.admits(&prefix, "10.0.12.2", &RouteState::Active(9), now));
+    assert!(hold_down.admits(&"192.168.3.0/24".parse().unwrap(), "10.0.16.6", &RouteState::Active(5), now));
+
+    let later = now + Duration::from_secs(10);
+    assert!(hold_down.admits(&prefix, "10.0.16.6", &RouteState::Active(5), later));
+    hold_down.expire(later);
+    assert!(hold_down.is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn ring_recovers_when_link_comes_back() {
+    let ring = Ring { n: 5 };
+    let mut sim = ring.build(3).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    sim.set_link_up(&ring.segment(3), false);
+    assert!(sim.run_until(TICK, 120, |tables| ring.converged(tables, &[(3, 4)])).await.is_some());
+
+    sim.set_link_up(&ring.segment(3), true);
+    let ticks = sim.run_until(TICK, 120, |tables| ring.converged(tables, &[])).await;
+    assert!(ticks.is_some(), "no recovery: {:#?}", sim.routing_tables().await);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn lossy_links_still_converge() {
+    let ring = Ring { n: 8 };
+    let mut sim = ring.build(4).await;
+    for i in 1..=ring.n {
+        sim.network().set_link(&ring.segment(i), LinkProfile {
+            loss: 0.1,
+            delay: Duration::from_millis(20),
+            ..LinkProfile::default()
+        });
+    }
+
+    let ticks = sim.run_until(TICK, 120, |tables| ring.converged(tables, &[])).await;
+    assert!(ticks.is_some(), "no convergence: {:#?}", sim.routing_tables().await);
+    assert!(sim.network().stats().dropped > 0);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn neighbors_are_named_after_their_hostname() {
+    let ring = Ring { n: 5 };
+    let mut sim = ring.build(5).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    let neighbors = sim.handle("r1").unwrap().neighbors().await;
+    let mut names: Vec<String> = neighbors.values().map(|neighbor| neighbor.display_name()).collect();
+    names.sort();
+    assert_eq!(names, vec!["r2 (10.0.12.2)".to_string(), "r5 (10.0.51.5)".to_string()]);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn neighbors_use_the_capacity_of_their_interface() {
+    let ring = Ring { n: 5 };
+    let mut sim = ring
+        .build_with(6, |i| {
+            let mut config = Simulation::router_config();
+            // r1 : lien lent vers r2 (eth0), rapide vers r5 (eth1)
+            if i == 1 {
+                for (name, capacity_mbps) in [("eth0", 10), ("eth1", 1000), ("eth2", 1000)] {
+                    config.interfaces.push(InterfaceConfig::new(name, capacity_mbps));
+                }
+            }
+            config
+        })
+        .await;
+    // Le lien lent (coût 10) est évité : lan2 est joint par r5 (coût 4, plus 1 pour
+    // l'interface de lan2 sur r2)
+    let fast = ring.address(5, 1);
+    let ticks = sim.run_until(TICK, 60, |tables| {
+        matches!(tables["r1"].get(&ring.lan(2)), Some((next_hop, RouteState::Active(5))) if *next_hop == fast)
+    }).await;
+    assert!(ticks.is_some(), "{:#?}", sim.routing_tables().await);
+
+    let neighbors = sim.handle("r1").unwrap().neighbors().await;
+    let r2 = &neighbors[&ring.address(2, 1)];
+    let r5 = &neighbors[&ring.address(5, 1)];
+    assert_eq!((r2.interface.as_deref(), r2.capacity), (Some("eth0"), 10));
+    assert_eq!((r5.interface.as_deref(), r5.capacity), (Some("eth1"), 1000));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn network_metrics_override_the_advertised_metric() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(91, |i| {
+            let mut config = Simulation::router_config();
+            if i == 2 {
+                config.network_metrics.insert("192.168.2.0/24".parse().unwrap(), 20);
+            }
+            config
+        })
+        .await;
+    // lan2 : lien r1-r2 (coût 1) plus la métrique imposée par r2 ; lan3 : coût de l'interface
+    let ticks = sim.run_until(TICK, 60, |tables| {
+        matches!(tables["r1"].get(&ring.lan(2)), Some((_, RouteState::Active(21))))
+            && matches!(tables["r1"].get(&ring.lan(3)), Some((_, RouteState::Active(2))))
+    }).await;
+    assert!(ticks.is_some(), "{:#?}", sim.routing_tables().await);
+
+    sim.stop().await;
+}
+
+#[test]
+fn network_metrics_match_the_prefix_length() {
+    use routing_project::read_config::parse_config;
+
+    let config = parse_config("interfaces = []\nencryption = false\n[network_metrics]\n\"192.168.2.0/24\" = 20\n\"192.168.2.0/25\" = 30", "r2.toml").unwrap();
+    assert!(config.validation_errors().is_empty(), "{:?}", config.validation_errors());
+    assert_eq!(config.network_metric(&"192.168.2.0/24".parse().unwrap()), Some(20));
+    assert_eq!(config.network_metric(&"192.168.2.1/25".parse().unwrap()), Some(30));
+    assert_eq!(config.network_metric(&"192.168.2.0/23".parse().unwrap()), None);
+
+    // Clé qui n'est pas un préfixe : refusée au chargement
+    assert!(parse_config("interfaces = []\nencryption = false\n[network_metrics]\n\"192.168.2.0\" = 20", "r2.toml").is_err());
+    // Deux clés pour le même réseau
+    let config = parse_config("interfaces = []\nencryption = false\n[network_metrics]\n\"192.168.2.0/24\" = 20\n\"192.168.2.7/24\" = 30", "r2.toml").unwrap();
+    assert!(config.validation_errors().iter().any(|error| error.contains("are the same network")), "{:?}", config.validation_errors());
+}
+
+#[tokio::test(start_paused = true)]
+async fn overridden_metrics_are_installed_through_the_simulated_interfaces() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(109, |i| {
+            let mut config = Simulation::router_config();
+            config.route_install.enabled = i == 1;
+            if i == 2 {
+                config.network_metrics.insert("192.168.2.0/24".parse().unwrap(), 20);
+            }
+            config
+        })
+        .await;
+    let ticks = sim.run_until(TICK, 60, |tables| matches!(tables["r1"].get(&ring.lan(2)), Some((_, RouteState::Active(21))))).await;
+    assert!(ticks.is_some(), "{:#?}", sim.routing_tables().await);
+    sim.run_for(Duration::from_secs(5)).await;
+
+    // Interface de sortie et réseaux locaux pris parmi les interfaces simulées de r1
+    let r1 = sim.handle("r1").unwrap();
+    let state = r1.state();
+    let routes = state.routes.list_routes().await.unwrap();
+    let lan2 = routes.iter().find(|route| route.prefix == ring.lan(2).network()).unwrap_or_else(|| panic!("{:#?}", routes));
+    assert_eq!(lan2.gateway, Some(ring.address(2, 1).parse().unwrap()));
+    assert_eq!(lan2.output_interface, state.interfaces.index_of("eth0"));
+    assert_eq!(state.interfaces.index_of("eth0"), Some(1));
+    let local: Vec<_> = state.interfaces.interfaces().iter().map(|iface| Prefix::new(iface.network).network()).collect();
+    assert!(routes.iter().all(|route| !local.contains(&route.prefix)), "{:#?}", routes);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn point_to_point_links_use_unicast_hellos() {
+    let ring = Ring { n: 5 };
+    let mut sim = ring
+        .build_with(7, |i| {
+            let mut config = Simulation::router_config();
+            // Lien r1-r2 en point à point, chaque extrémité visant l'autre
+            let (interface, peer) = match i {
+                1 => ("eth0", ring.address(2, 1)),
+                2 => ("eth1", ring.address(1, 2)),
+                _ => return config,
+            };
+            let mut iface = InterfaceConfig::new(interface, 1000);
+            iface.link_type = LinkType::PointToPoint;
+            iface.peer = Some(peer);
+            config.interfaces.push(iface);
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    let neighbors = sim.handle("r1").unwrap().neighbors().await;
+    let r2 = &neighbors[&ring.address(2, 1)];
+    assert_eq!((r2.interface.as_deref(), r2.link_up), (Some("eth0"), true));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn overlay_mode_builds_adjacencies_over_unicast_endpoints_only() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(44, |i| {
+            let mut config = Simulation::router_config();
+            // Aucune diffusion : chaque routeur ne connaît que les points d'accès de ses voisins
+            config.overlay.enabled = true;
+            config.overlay.keepalive_sec = 2;
+            config.static_neighbors = [("eth0", ring.next(i)), ("eth1", ring.prev(i))]
+                .into_iter()
+                .map(|(interface, j)| StaticNeighborConfig {
+                    address: ring.address(j, i).parse().unwrap(),
+                    port: None,
+                    interface: interface.to_string(),
+                })
+                .collect();
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    let state = std::sync::Arc::clone(sim.handle("r1").unwrap().state());
+    let neighbors = state.neighbors.read().await.clone();
+    let r2 = &neighbors[&ring.address(2, 1)];
+    assert_eq!((r2.interface.as_deref(), r2.link_up), (Some("eth0"), true));
+    let endpoints = state.neighbor_endpoints.lock().await.clone();
+    assert_eq!(endpoints[&ring.address(3, 1)].ip().to_string(), ring.address(3, 1));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn type_1_external_routes_are_preferred_over_type_2() {
+    let ring = Ring { n: 5 };
+    let mut sim = ring
+        .build_with(8, |i| {
+            let mut config = Simulation::router_config();
+            // r1 annonce une route par défaut E2 de faible métrique, r3 une route E1 plus chère
+            let metric_type = match i {
+                1 => ExternalMetricType::Type2,
+                3 => ExternalMetricType::Type1,
+                _ => return config,
+            };
+            config.originate_default.enabled = true;
+            config.originate_default.condition = DefaultOriginateCondition::Always;
+            config.originate_default.metric = if i == 1 { 1 } else { 50 };
+            config.originate_default.metric_type = metric_type;
+            config
+        })
+        .await;
+
+    // r2, voisin des deux annonceurs, préfère la route E1 malgré sa métrique plus élevée
+    let via_r3 = |tables: &Tables| {
+        matches!(
+            tables["r2"].get(&Prefix::default_route()),
+            Some((next_hop, RouteState::External { metric_type: ExternalMetricType::Type1, metric: 50, cost: 1 }))
+                if *next_hop == ring.address(3, 2)
+        )
+    };
+    let ticks = sim.run_until(TICK, 60, |tables| ring.converged(tables, &[]) && via_r3(tables)).await;
+    assert!(ticks.is_some(), "default route not via r3: {:#?}", sim.routing_tables().await);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn inbound_prefix_filter_forces_the_longer_path() {
+    let ring = Ring { n: 5 };
+    let mut sim = ring
+        .build_with(9, |i| {
+            let mut config = Simulation::router_config();
+            // r2 refuse le réseau local de r3 sur son lien direct vers r3 (eth0)
+            if i == 2 {
+                config.prefix_lists.insert("no-lan3".to_string(), vec![
+                    PrefixListEntry { action: FilterAction::Deny, prefix: "192.168.3.0/24".to_string(), ge: None, le: None, tag: None },
+                    PrefixListEntry { action: FilterAction::Permit, prefix: "0.0.0.0/0".to_string(), ge: None, le: Some(32), tag: None },
+                ]);
+                let mut eth0 = InterfaceConfig::new("eth0", 1000);
+                eth0.filter_in = Some("no-lan3".to_string());
+                config.interfaces.push(eth0);
+            }
+            config
+        })
+        .await;
+
+    let filtered = |tables: &Tables| {
+        matches!(tables["r2"].get(&ring.lan(3)), Some((next_hop, RouteState::Active(_))) if *next_hop == ring.address(1, 2))
+            && matches!(tables["r2"].get(&ring.lan(4)), Some((next_hop, RouteState::Active(_))) if *next_hop == ring.address(3, 2))
+    };
+    let ticks = sim.run_until(TICK, 60, filtered).await;
+    assert!(ticks.is_some(), "filter not applied: {:#?}", sim.routing_tables().await["r2"]);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn interfaces_advertise_configured_networks() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(11, |i| {
+            let mut config = Simulation::router_config();
+            // r3 annonce un agrégat à la place de son réseau local
+            if i == 3 {
+                let mut eth2 = InterfaceConfig::new("eth2", 1000);
+                eth2.redistribute_connected = false;
+                eth2.advertise = vec!["172.16.3.0/24".to_string()];
+                config.interfaces.push(eth2);
+            }
+            config
+        })
+        .await;
+
+    let aggregate: Prefix = "172.16.3.0/24".parse().unwrap();
+    let advertised = |tables: &Tables| {
+        (1..=2).all(|i| {
+            let table = &tables[&format!("r{}", i)];
+            matches!(table.get(&aggregate), Some((_, RouteState::Active(_)))) && table.contains_key(&ring.lan(3 - i))
+        })
+    };
+    let ticks = sim.run_until(TICK, 60, advertised).await;
+    assert!(ticks.is_some(), "aggregate not learned: {:#?}", sim.routing_tables().await);
+    let tables = sim.routing_tables().await;
+    assert!(!tables["r1"].contains_key(&ring.lan(3)));
+    assert!(!tables["r2"].contains_key(&ring.lan(3)));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn route_tags_survive_readvertisement_and_drive_filters() {
+    let ring = Ring { n: 4 };
+    let mut sim = ring
+        .build_with(71, |i| {
+            let mut config = Simulation::router_config();
+            // r1 étiquette son réseau local, r3 refuse l'étiquette sur ses deux liens
+            if i == 1 {
+                config.interfaces.push(InterfaceConfig { route_tag: Some(100), ..InterfaceConfig::new("eth2", 1000) });
+            }
+            if i == 3 {
+                config.prefix_lists.insert("no-tag-100".to_string(), vec![
+                    PrefixListEntry { action: FilterAction::Deny, prefix: String::new(), ge: None, le: None, tag: Some(100) },
+                    PrefixListEntry { action: FilterAction::Permit, prefix: "0.0.0.0/0".to_string(), ge: None, le: Some(32), tag: None },
+                ]);
+                for name in ["eth0", "eth1"] {
+                    config.interfaces.push(InterfaceConfig { filter_in: Some("no-tag-100".to_string()), ..InterfaceConfig::new(name, 1000) });
+                }
+            }
+            config
+        })
+        .await;
+
+    let learned = |tables: &Tables| {
+        [2, 4].iter().all(|&i| tables[&format!("r{}", i)].contains_key(&ring.lan(1)))
+            && [2, 4].iter().all(|&i| tables["r3"].contains_key(&ring.lan(i)))
+    };
+    assert!(sim.run_until(TICK, 60, learned).await.is_some(), "{:#?}", sim.routing_tables().await);
+    sim.run_for(Duration::from_secs(10)).await;
+
+    // r2 et r4 réannoncent le réseau de r1 avec son étiquette : r3 ne l'apprend d'aucun des deux
+    assert!(!sim.routing_tables().await["r3"].contains_key(&ring.lan(1)));
+    let routes: serde_json::Value = serde_json::from_str(&sim.handle("r2").unwrap().command("routing-table --json").await).unwrap();
+    let tag_of = |prefix: &str| routes.as_array().unwrap().iter().find(|route| route["prefix"] == prefix).map(|route| route["origin"]["tag"].clone());
+    assert_eq!(tag_of("192.168.1.1/24"), Some(serde_json::json!(100)));
+    assert_eq!(tag_of("192.168.4.1/24"), Some(serde_json::Value::Null));
+    assert!(sim.handle("r4").unwrap().command("routing-table").await.contains("étiquette 100"));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn interface_keys_isolate_their_segment() {
+    use base64::Engine;
+    let encode = |byte: u8| base64::engine::general_purpose::STANDARD.encode([byte; 32]);
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(13, |i| {
+            let mut config = Simulation::router_config();
+            config.encryption = true;
+            config.key = Some(encode(1));
+            // Le segment r1-r2 a sa propre clé des deux côtés ; sur r3-r1, seul r3 en a une
+            let own = match i {
+                1 => Some(("eth0", 2)),
+                2 => Some(("eth1", 2)),
+                3 => Some(("eth0", 3)),
+                _ => None,
+            };
+            if let Some((name, byte)) = own {
+                let mut iface = InterfaceConfig::new(name, 1000);
+                iface.key = Some(encode(byte));
+                config.interfaces.push(iface);
+            }
+            config
+        })
+        .await;
+
+    // r1 et r3 ne se comprennent pas : tout passe par r2
+    let ticks = sim.run_until(TICK, 60, |tables| ring.converged(tables, &[(3, 1)])).await;
+    assert!(ticks.is_some(), "not converged around r3-r1: {:#?}", sim.routing_tables().await);
+    let r1 = sim.handle("r1").unwrap();
+    assert!(!r1.state().neighbors.read().await.contains_key(&ring.address(3, 1)));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn mismatched_auth_modes_refuse_adjacency() {
+    use base64::Engine;
+    let key = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(17, |i| {
+            let mut config = Simulation::router_config();
+            // r1-r2 en clair authentifié des deux côtés ; r3 chiffre seul le segment r3-r1
+            let auth = match i {
+                1 => Some(("eth0", AuthMode::Hmac)),
+                2 => Some(("eth1", AuthMode::Hmac)),
+                3 => Some(("eth0", AuthMode::Aes)),
+                _ => None,
+            };
+            if let Some((name, auth)) = auth {
+                let mut iface = InterfaceConfig::new(name, 1000);
+                iface.auth = Some(auth);
+                iface.key = Some(key.clone());
+                config.interfaces.push(iface);
+            }
+            config
+        })
+        .await;
+
+    let ticks = sim.run_until(TICK, 60, |tables| ring.converged(tables, &[(3, 1)])).await;
+    assert!(ticks.is_some(), "not converged around r3-r1: {:#?}", sim.routing_tables().await);
+    for (router, peer) in [("r1", ring.address(3, 1)), ("r3", ring.address(1, 3))] {
+        let handle = sim.handle(router).unwrap();
+        let state = handle.state();
+        assert!(!state.neighbors.read().await.contains_key(&peer), "{} adjacent to {}", router, peer);
+        assert!(state.metrics.auth_mode_mismatches.load(std::sync::atomic::Ordering::Relaxed) > 0, "{} did not report the mismatch", router);
+    }
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn receive_pipeline_counts_each_failure_class() {
+    use base64::Engine;
+    let key = [4u8; 32];
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(19, |_| {
+            let mut config = Simulation::router_config();
+            config.encryption = true;
+            config.key = Some(base64::engine::general_purpose::STANDARD.encode(key));
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    let address: Ipv4Addr = "192.168.1.62".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let sender = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let router: SocketAddr = "192.168.1.1:5000".parse().unwrap();
+    let message = ControlMessage { message_type: 3, command: "routes".to_string(), user: None, timestamp: None, signature: None, request_id: None };
+    // Autre clé, contenu chiffré illisible, message en clair sur un segment chiffré
+    routing_project::net_utils::send_message(&sender, &router, &message, &SegmentKey::from_global(vec![5u8; 32]), "[TEST]").await.unwrap();
+    let garbage = routing_project::net_utils::encrypt(b"{not json", &key).unwrap();
+    let garbage = framing::frame(3, framing::Codec::Json, AuthMode::Aes, &garbage).unwrap();
+    sender.send_to(&garbage, router).await.unwrap();
+    routing_project::net_utils::send_message(&sender, &router, &message, &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+    sim.run_for(Duration::from_secs(1)).await;
+
+    let r1 = sim.handle("r1").unwrap();
+    let metrics = &r1.state().metrics;
+    for (name, counter) in [
+        ("decrypt", &metrics.decrypt_failures),
+        ("malformed", &metrics.packets_rejected),
+        ("auth mode", &metrics.auth_mode_mismatches),
+    ] {
+        assert_eq!(counter.load(std::sync::atomic::Ordering::Relaxed), 1, "{} failures", name);
+    }
+    assert!(r1.command("metrics").await.contains("pospf_decrypt_failures_total 1"));
+
+    sim.stop().await;
+}
+
+#[tokio::test]
+async fn dry_run_backend_records_changes_without_a_system_table() {
+    let backend = DryRunBackend::new();
+    let route = KernelRoute {
+        prefix: "192.168.5.0/24".parse().unwrap(),
+        gateway: Some("10.0.45.5".parse().unwrap()),
+        output_interface: None,
+        protocol: 89,
+        priority: Some(20),
+    };
+    backend.install_route(route.clone()).await.unwrap();
+    // Même emplacement (préfixe, protocole, métrique) : la route est remplacée
+    let moved = KernelRoute { gateway: Some("10.0.34.3".parse().unwrap()), ..route.clone() };
+    backend.install_route(moved.clone()).await.unwrap();
+    assert_eq!(backend.list_routes().await.unwrap(), vec![moved.clone()]);
+
+    backend.delete_route(moved).await.unwrap();
+    assert!(backend.list_routes().await.unwrap().is_empty());
+}
+
+#[test]
+fn spf_results_are_diffed_and_only_changed_routes_reprogrammed() {
+    use routing_project::nexthop::NextHop;
+    use routing_project::read_config::RouteInstallConfig;
+    use routing_project::route_diff::{ProgrammedRoutes, RouteDiff};
+
+    let prefix = |i: u8| -> Prefix { format!("192.168.{}.0/24", i).parse().unwrap() };
+    let route = |next_hop: &str, metric: u32| (next_hop.to_string(), RouteState::Active(metric));
+    let old = HashMap::from([
+        (prefix(1), route("10.0.12.2", 2)),
+        (prefix(2), route("10.0.12.2", 3)),
+        (prefix(3), route("10.0.13.3", 2)),
+        (prefix(4), route("10.0.13.3", 4)),
+    ]);
+    let new = HashMap::from([
+        (prefix(1), route("10.0.12.2", 2)),
+        (prefix(2), route("10.0.13.3", 3)),
+        (prefix(3), route("10.0.13.3", 5)),
+        (prefix(5), route("10.0.12.2", 2)),
+        (prefix(6), route("10.0.12.2", 3)),
+        (prefix(7), route("10.0.13.3", 3)),
+    ]);
+    let diff = RouteDiff::between(&old, &new);
+    assert_eq!(diff.added, vec![prefix(5), prefix(6), prefix(7)]);
+    assert_eq!(diff.removed, vec![prefix(4)]);
+    assert_eq!(diff.modified, vec![prefix(2), prefix(3)]);
+    assert_eq!(diff.summary(), "3 added, 1 removed, 2 modified");
+    assert!(RouteDiff::between(&new, &new).is_empty());
+
+    // Les routes programmées sont oubliées si la configuration d'installation change
+    let install = RouteInstallConfig::default();
+    let mut programmed = ProgrammedRoutes::new();
+    assert!(programmed.snapshot(&install).is_empty());
+    let next_hop = NextHop { gateway: Some("10.0.12.2".parse().unwrap()), interface: Some("eth0".to_string()) };
+    programmed.record(prefix(1), next_hop.clone());
+    assert_eq!(programmed.snapshot(&install).get(&prefix(1)), Some(&next_hop));
+    let moved = RouteInstallConfig { distance: install.distance + 1, ..install };
+    assert!(programmed.snapshot(&moved).is_empty());
+}
+
+#[test]
+fn topology_generator_emits_lab_and_router_configs() {
+    use routing_project::topology::{LabFormat, TopologySpec};
+
+    let spec = TopologySpec::parse(r#"
+        name = "triangle"
+        [[routers]]
+        name = "R_1"
+        lans = ["192.168.1.0/24"]
+        [[routers]]
+        name = "R_2"
+        [[routers]]
+        name = "R_3"
+        [[links]]
+        routers = ["R_1", "R_2"]
+        capacity_mbps = 1000
+        [[links]]
+        routers = ["R_2", "R_3"]
+        [[links]]
+        routers = ["R_3", "R_1"]
+        subnet = "172.16.0.0/24"
+    "#).unwrap();
+    let key = routing_project::topology::generate_key();
+    let files = spec.generate(LabFormat::Compose, &key).unwrap();
+    let paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+    assert_eq!(paths, ["compose.yaml", "config_R_1.toml", "config_R_2.toml", "config_R_3.toml"]);
+
+    let compose: serde_yaml::Value = serde_yaml::from_str(&files[0].content).unwrap();
+    let r1 = &compose["services"]["r-1"];
+    assert_eq!(r1["hostname"].as_str(), Some("R_1"));
+    assert_eq!(r1["networks"]["N_L1"]["ipv4_address"].as_str(), Some("10.1.0.1"));
+    assert_eq!(r1["networks"]["N_L3"]["ipv4_address"].as_str(), Some("172.16.0.2"));
+    assert_eq!(compose["networks"]["N_R_1_LAN1"]["ipam"]["config"][0]["subnet"].as_str(), Some("192.168.1.0/24"));
+
+    let config = routing_project::read_config::parse_config(&files[1].content, &files[1].path).unwrap();
+    assert_eq!(config.key.as_deref(), Some(key.as_str()));
+    let interfaces: Vec<(&str, u32)> = config.interfaces.iter().map(|iface| (iface.name.as_str(), iface.capacity_mbps)).collect();
+    assert_eq!(interfaces, [("eth0", 1000), ("eth1", 100), ("eth2", 100)]);
+
+    let clab = spec.generate(LabFormat::Containerlab, &key).unwrap();
+    let lab: serde_yaml::Value = serde_yaml::from_str(&clab[0].content).unwrap();
+    assert_eq!(lab["topology"]["links"][0]["endpoints"][0].as_str(), Some("R_1:eth1"));
+
+    // Sous-réseaux qui se chevauchent, routeur inconnu, segment partagé sous containerlab
+    assert!(TopologySpec::parse("[[routers]]\nname = \"A\"\nlans = [\"10.1.0.0/16\"]\n[[routers]]\nname = \"B\"\n[[links]]\nrouters = [\"A\", \"B\"]").is_err());
+    assert!(TopologySpec::parse("[[routers]]\nname = \"A\"\n[[links]]\nrouters = [\"A\", \"B\"]").is_err());
+    let shared = TopologySpec::parse("[[routers]]\nname = \"A\"\n[[routers]]\nname = \"B\"\n[[routers]]\nname = \"C\"\n[[links]]\nrouters = [\"A\", \"B\", \"C\"]").unwrap();
+    assert!(shared.generate(LabFormat::Compose, &key).is_ok());
+    assert!(shared.generate(LabFormat::Containerlab, &key).is_err());
+}
+
+#[cfg(feature = "fault-injection")]
+#[test]
+fn fault_injection_drops_duplicates_and_corrupts_per_neighbor() {
+    use rand::SeedableRng;
+    use routing_project::fault::{self, Direction, FaultRule};
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+    let rule = FaultRule { drop_pct: 30, ..FaultRule::default() };
+    let dropped = (0..1000).filter(|_| rule.decide(&mut rng).drop).count();
+    assert!((250..350).contains(&dropped), "{} drops out of 1000", dropped);
+    assert_eq!(FaultRule::default().decide(&mut rng), fault::Verdict::default());
+
+    // Adresse de documentation : aucun autre test ne l'emploie
+    let peer: std::net::IpAddr = "203.0.113.7".parse().unwrap();
+    let other: std::net::IpAddr = "203.0.113.8".parse().unwrap();
+    let data = b"datagram".to_vec();
+    assert!(fault::handle_command("fault 203.0.113.7 in drop 100").starts_with("Fautes injectées"));
+    assert!(fault::apply(Direction::In, peer, &data).is_empty());
+    assert_eq!(fault::apply(Direction::Out, peer, &data), vec![(data.clone(), None)]);
+    assert_eq!(fault::apply(Direction::In, other, &data), vec![(data.clone(), None)]);
+
+    fault::handle_command("fault 203.0.113.7 both drop 0 duplicate 100 corrupt 100 delay 100 50");
+    let copies = fault::apply(Direction::Out, peer, &data);
+    assert_eq!(copies.len(), 2);
+    assert!(copies.iter().all(|(copy, delay)| copy != &data && copy.len() == data.len() && *delay == Some(Duration::from_millis(50))));
+    assert!(fault::handle_command("fault").contains("203.0.113.7: both drop 0% delay 100% 50 ms duplicate 100% corrupt 100%"));
+
+    assert!(fault::handle_command("fault 203.0.113.7 drop 101").starts_with("Argument invalide"));
+    assert!(fault::handle_command("fault 203.0.113.7 clear").starts_with("Aucune faute"));
+    assert_eq!(fault::apply(Direction::In, peer, &data), vec![(data, None)]);
+}
+
+#[test]
+fn network_namespaces_are_named_or_given_by_path() {
+    use routing_project::netns;
+
+    assert_eq!(netns::path("r1"), std::path::PathBuf::from("/run/netns/r1"));
+    assert_eq!(netns::path("/proc/1/ns/net"), std::path::PathBuf::from("/proc/1/ns/net"));
+    let mut config = Simulation::router_config();
+    config.netns = Some("r1".to_string());
+    assert!(config.validation_errors().is_empty());
+    for invalid in ["", "..", "lab/r1"] {
+        config.netns = Some(invalid.to_string());
+        assert!(config.validation_errors().iter().any(|error| error.starts_with("netns")), "{:?} accepted", invalid);
+    }
+    // Sans espace à ce chemin, le démon refuse de démarrer plutôt que de tourner hors de l'espace
+    assert!(netns::is_current("pospf-test-missing").is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn vrf_instances_keep_separate_tables_and_answer_scoped_commands() {
+    use routing_project::read_config::VrfConfig;
+
+    let blue = VrfConfig { name: "blue".to_string(), table: 100 };
+    let mut config = Simulation::router_config();
+    config.vrfs = vec![blue.clone()];
+    config.interfaces = vec![InterfaceConfig::new("eth0", 1000), InterfaceConfig { vrf: Some("blue".to_string()), ..InterfaceConfig::new("eth3", 1000) }];
+    config.http.enabled = true;
+    assert!(config.validation_errors().is_empty(), "{:?}", config.validation_errors());
+    let main = config.for_vrf(None);
+    assert_eq!(main.interfaces.iter().map(|iface| iface.name.as_str()).collect::<Vec<_>>(), ["eth0"]);
+    assert_eq!(main.route_install.table, 254);
+    let in_blue = config.for_vrf(Some("blue"));
+    assert_eq!(in_blue.interfaces.iter().map(|iface| iface.name.as_str()).collect::<Vec<_>>(), ["eth3"]);
+    assert_eq!(in_blue.route_install.table, 100);
+    assert!(!in_blue.http.enabled);
+    config.interfaces[1].vrf = Some("red".to_string());
+    assert!(config.validation_errors().iter().any(|error| error.contains("vrf 'red' is not declared")));
+
+    // L'instance VRF de r1 et son voisin forment un domaine séparé de l'anneau
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(61, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.vrfs = vec![blue.clone()];
+            }
+            config
+        })
+        .await;
+    sim.add_segment("blue", "172.16.1.0/24").unwrap();
+    sim.add_segment("blue-lan", "172.16.2.0/24").unwrap();
+    let mut in_blue = Simulation::router_config();
+    in_blue.route_install.table = blue.table;
+    sim.add_router("r1-blue", &[("eth3", "blue", "172.16.1.1")], in_blue).unwrap();
+    sim.add_router("peer-blue", &[("eth0", "blue", "172.16.1.2"), ("eth1", "blue-lan", "172.16.2.1")], Simulation::router_config()).unwrap();
+    sim.start().await.unwrap();
+    let r1 = sim.handle("r1").unwrap();
+    r1.attach_vrf("blue", sim.handle("r1-blue").unwrap()).await;
+
+    let blue_lan: Prefix = "172.16.2.1/24".parse().unwrap();
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[]) && tables["r1-blue"].contains_key(&blue_lan)).await.is_some());
+    assert!(!r1.routing_table().await.contains_key(&blue_lan));
+    let routes: serde_json::Value = serde_json::from_str(&r1.command("vrf blue routing-table --json").await).unwrap();
+    let prefixes: Vec<&str> = routes.as_array().unwrap().iter().filter_map(|route| route["prefix"].as_str()).collect();
+    assert!(prefixes.contains(&"172.16.2.1/24"), "{:?}", prefixes);
+    assert!(!prefixes.contains(&"192.168.2.1/24"), "{:?}", prefixes);
+
+    assert!(r1.command("vrf").await.contains("blue : table 100, interfaces eth3"));
+    assert!(r1.command("vrf red routing-table").await.starts_with("Échec"));
+    assert!(r1.command("vrf blue").await.starts_with("Usage"));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn failed_route_operations_are_retried_with_backoff_then_given_up() {
+    use routing_project::nexthop::NextHop;
+    use routing_project::read_config::RouteInstallConfig;
+    use routing_project::route_queue::{RouteOp, RouteQueue};
+    use tokio::time::Instant;
+
+    let install = RouteInstallConfig { max_attempts: 3, retry_initial_ms: 500, retry_max_ms: 800, ..RouteInstallConfig::default() };
+    let prefix: Prefix = "192.168.5.0/24".parse().unwrap();
+    let via = |gateway: &str| RouteOp::Install {
+        next_hop: NextHop { gateway: Some(gateway.parse().unwrap()), interface: Some("eth0".to_string()) },
+    };
+    let mut queue = RouteQueue::new();
+    assert!(queue.push(prefix, via("10.0.12.2")));
+    assert!(!queue.push(prefix, via("10.0.12.2")), "an identical operation is not queued twice");
+
+    let [(_, _, generation)] = queue.due(Instant::now())[..] else { panic!("operation not due") };
+    assert_eq!(queue.failed(&prefix, generation, "Operation not permitted".to_string(), &install), Some(Duration::from_millis(500)));
+    assert!(queue.due(Instant::now()).is_empty());
+    // Une opération identique remise en file garde ses essais et son attente
+    assert!(!queue.push(prefix, via("10.0.12.2")));
+    assert_eq!(queue.get(&prefix).unwrap().attempts, 1);
+
+    tokio::time::advance(Duration::from_millis(500)).await;
+    assert_eq!(queue.due(Instant::now()).len(), 1);
+    assert_eq!(queue.failed(&prefix, generation, "Operation not permitted".to_string(), &install), Some(Duration::from_millis(800)));
+    tokio::time::advance(Duration::from_millis(800)).await;
+    assert_eq!(queue.failed(&prefix, generation, "Operation not permitted".to_string(), &install), None);
+    let given_up = queue.get(&prefix).unwrap();
+    assert_eq!((given_up.attempts, given_up.next_attempt), (3, None));
+    assert_eq!(given_up.last_error.as_deref(), Some("Operation not permitted"));
+    assert!(queue.next_due().is_none());
+    assert!(routing_project::route_queue::pending_report(&queue, Instant::now()).contains("abandonnée"));
+
+    // Une opération plus récente remplace l'abandonnée ; la réussite de l'ancienne ne la retire pas
+    assert!(queue.push(prefix, RouteOp::Remove));
+    queue.succeeded(&prefix, generation);
+    assert_eq!(queue.get(&prefix).unwrap().op, RouteOp::Remove);
+    let [(_, _, remove)] = queue.due(Instant::now())[..] else { panic!("removal not due") };
+    queue.succeeded(&prefix, remove);
+    assert!(queue.is_empty());
+}
+
+#[tokio::test]
+async fn grpc_telemetry_reports_interface_states() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(11, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.grpc.enabled = true;
+                config.grpc.listen = format!("127.0.0.1:{}", port);
+            }
+            config
+        })
+        .await;
+
+    let mut client = None;
+    for _ in 0..50 {
+        match TelemetryClient::connect(format!("http://127.0.0.1:{}", port)).await {
+            Ok(connected) => {
+                client = Some(connected);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+    let mut client = client.expect("gRPC server not reachable");
+
+    let interfaces = client.get_interfaces(Empty {}).await.unwrap().into_inner().interfaces;
+    let names: Vec<&str> = interfaces.iter().map(|iface| iface.name.as_str()).collect();
+    assert_eq!(names, ["eth0", "eth1", "eth2"]);
+    assert_eq!(interfaces[2].address, ring.lan(1).to_string());
+    assert!(interfaces.iter().all(|iface| iface.link_active && iface.link_type == "broadcast"));
+
+    let neighbors = client.get_neighbors(Empty {}).await.unwrap().into_inner();
+    // Identifiant automatique : la plus grande adresse des interfaces de r1
+    assert_eq!(neighbors.router_id, "192.168.1.1");
+    assert!(client.get_lsdb(Empty {}).await.is_ok());
+    assert!(client.get_spf_stats(Empty {}).await.is_ok());
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn event_bus_reports_neighbors_and_spf_runs() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring.build(13).await;
+    let mut events = sim.handle("r1").unwrap().subscribe();
+
+    let mut neighbors_up = Vec::new();
+    let mut spf_runs = 0;
+    let collect = async {
+        while neighbors_up.len() < 2 || spf_runs == 0 {
+            match events.recv().await.unwrap() {
+                RouterEvent::NeighborUp { neighbor, .. } => neighbors_up.push(neighbor),
+                RouterEvent::SpfCompleted { .. } => spf_runs += 1,
+                _ => {}
+            }
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(30), collect).await.expect("events not received");
+
+    neighbors_up.sort();
+    assert_eq!(neighbors_up, [ring.address(2, 1), ring.address(3, 1)]);
+
+    sim.stop().await;
+}
+
+#[tokio::test]
+async fn websocket_streams_router_events_as_json() {
+    use futures::StreamExt;
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(14, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.http.enabled = true;
+                config.http.listen = format!("127.0.0.1:{}", port);
+            }
+            config
+        })
+        .await;
+
+    let mut socket = None;
+    for _ in 0..50 {
+        match tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}/ws", port)).await {
+            Ok((connected, _)) => {
+                socket = Some(connected);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+    let mut socket = socket.expect("WebSocket endpoint not reachable");
+
+    // Abonné dès la poignée de main : les événements suivants arrivent dans l'ordre, en JSON
+    let r1 = sim.handle("r1").unwrap();
+    let bus = &r1.state().events;
+    bus.emit(RouterEvent::NeighborDown { neighbor: ring.address(2, 1), reason: "test".to_string() });
+    bus.emit(RouterEvent::RouteRemoved { prefix: ring.lan(2).to_string() });
+    let mut streamed = Vec::new();
+    let collect = async {
+        while streamed.len() < 2 {
+            let message = socket.next().await.unwrap().unwrap();
+            let event: serde_json::Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+            // Les événements de la convergence en cours s'intercalent
+            if matches!(event["event"].as_str(), Some("neighbor_down" | "route_removed")) {
+                streamed.push(event);
+            }
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), collect).await.expect("events not streamed");
+    assert_eq!(streamed[0], serde_json::json!({ "event": "neighbor_down", "neighbor": ring.address(2, 1), "reason": "test" }));
+    assert_eq!(streamed[1], serde_json::json!({ "event": "route_removed", "prefix": ring.lan(2).to_string() }));
+
+    sim.stop().await;
+}
+
+/// Envoie une commande de contrôle à r1 depuis un poste du réseau local lan1 et renvoie sa réponse
+async fn control_from_lan(client: &Socket, message: &ControlMessage) -> String {
+    let router: SocketAddr = "192.168.1.1:5000".parse().unwrap();
+    routing_project::net_utils::send_message(client, &router, message, &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+    let mut buf = vec![0u8; 65_536];
+    // Le poste reçoit aussi les HELLO et LSA diffusés sur le segment : seule la réponse a le
+    // type des réponses de contrôle
+    loop {
+        let (len, _) = tokio::time::timeout(Duration::from_secs(5), client.recv_from(&mut buf)).await.unwrap().unwrap();
+        let (header, body) = framing::split(&buf[..len]).unwrap();
+        if header.message_type == framing::CONTROL_RESPONSE {
+            return serde_json::from_slice(body).unwrap();
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn control_commands_require_a_role() {
+    // Les jetons n'apparaissent pas dans les journaux
+    let ops = ControlUser { name: "ops".to_string(), token: "ops-secret".to_string(), role: ControlRole::ReadOnly };
+    assert!(!format!("{:?}", ops).contains("ops-secret"));
+
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(17, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.control.users = vec![
+                    ops.clone(),
+                    ControlUser { name: "admin".to_string(), token: "admin-secret".to_string(), role: ControlRole::Admin },
+                ];
+            }
+            config
+        })
+        .await;
+    let address: Ipv4Addr = "192.168.1.50".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let client = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let now = routing_project::clock::unix_secs();
+    let unsigned = ControlMessage { message_type: 3, command: "disable".to_string(), user: None, timestamp: None, signature: None, request_id: None };
+
+    assert!(control_from_lan(&client, &unsigned).await.starts_with("Accès refusé"));
+    let read_only = signed_command("ops", "ops-secret", "disable", now).unwrap();
+    assert!(control_from_lan(&client, &read_only).await.contains("administrateur"));
+    let forged = signed_command("admin", "ops-secret", "disable", now).unwrap();
+    assert!(control_from_lan(&client, &forged).await.contains("signature invalide"));
+    let neighbors = signed_command("ops", "ops-secret", "neighbors", now).unwrap();
+    assert!(!control_from_lan(&client, &neighbors).await.starts_with("Accès refusé"));
+
+    let r1 = sim.handle("r1").unwrap();
+    assert!(r1.state().is_enabled().await);
+    let admin = signed_command("admin", "admin-secret", "disable", now).unwrap();
+    assert_eq!(control_from_lan(&client, &admin).await, "Protocole OSPF désactivé");
+    assert!(!r1.state().is_enabled().await);
+    assert!(control_from_lan(&client, &admin).await.contains("rejouée"));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn unsigned_commands_are_read_only_by_default() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring.build(113).await;
+    let address: Ipv4Addr = "192.168.1.50".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let client = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let unsigned = |command: &str| ControlMessage { message_type: 3, command: command.to_string(), user: None, timestamp: None, signature: None, request_id: None };
+
+    // Aucun utilisateur déclaré ni `anonymous` : consultation seule
+    assert_eq!(control_from_lan(&client, &unsigned("disable")).await, "Accès refusé: droits administrateur requis");
+    assert!(sim.handle("r1").unwrap().state().is_enabled().await);
+    assert!(!control_from_lan(&client, &unsigned("neighbors")).await.starts_with("Accès refusé"));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn late_control_replies_are_discarded_by_request_id() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring.build(127).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let address: Ipv4Addr = "192.168.1.50".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let client = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let router: SocketAddr = "192.168.1.1:5000".parse().unwrap();
+    let tagged = |command: &str, request_id: u64| ControlMessage { message_type: 3, command: command.to_string(), user: None, timestamp: None, signature: None, request_id: Some(request_id) };
+
+    // La réponse à « neighbors » arrive en retard, alors que le client attend celle de « lsdb »
+    for message in [tagged("neighbors", 1), tagged("lsdb", 2)] {
+        routing_project::net_utils::send_message(&client, &router, &message, &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+    }
+    let mut buf = vec![0u8; 65_536];
+    let mut discarded = 0;
+    let response = loop {
+        let (len, _) = tokio::time::timeout(Duration::from_secs(5), client.recv_from(&mut buf)).await.unwrap().unwrap();
+        let (header, body) = framing::split(&buf[..len]).unwrap();
+        if header.message_type != framing::CONTROL_RESPONSE {
+            continue;
+        }
+        match response_for(2, body).unwrap() {
+            Some(response) => break response,
+            None => discarded += 1,
+        }
+    };
+    assert_eq!(discarded, 1);
+    assert!(response.starts_with("Type"), "{response}");
+
+    // Un démon sans identifiant de requête répond par une chaîne nue, acceptée telle quelle
+    let bare = serde_json::to_vec("Commande inconnue").unwrap();
+    assert_eq!(response_for(2, &bare).unwrap().as_deref(), Some("Commande inconnue"));
+
+    sim.stop().await;
+}
+
+#[cfg(unix)]
+#[tokio::test(start_paused = true)]
+async fn control_socket_runs_local_commands() {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let path = std::env::temp_dir().join(format!("pospf-control-{}.sock", std::process::id()));
+    // Groupe principal du test, seul groupe auquel il peut attribuer la socket
+    let probe = std::env::temp_dir().join(format!("pospf-group-{}", std::process::id()));
+    std::fs::write(&probe, b"").unwrap();
+    let gid = std::fs::metadata(&probe).unwrap().gid();
+    let _ = std::fs::remove_file(&probe);
+    // Fichier ordinaire à l'emplacement de la socket de r2 : jamais supprimé
+    let occupied = std::env::temp_dir().join(format!("pospf-occupied-{}", std::process::id()));
+    std::fs::write(&occupied, b"keep").unwrap();
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(19, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.control.socket = Some(path.clone());
+                config.control.socket_group = Some(gid.to_string());
+            }
+            if i == 2 {
+                config.control.socket = Some(occupied.clone());
+            }
+            config
+        })
+        .await;
+    sim.run_for(Duration::from_secs(1)).await;
+    assert_eq!(std::fs::read(&occupied).unwrap(), b"keep");
+    let _ = std::fs::remove_file(&occupied);
+    assert!(path.exists());
+    let metadata = std::fs::metadata(&path).unwrap();
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o660);
+    assert_eq!(metadata.gid(), gid);
+
+    // Le propriétaire de la socket (l'utilisateur du test) a les droits administrateur
+    let neighbors = routing_project::control_socket::send_command(&path, "neighbors").await.unwrap();
+    assert!(!neighbors.starts_with("Accès refusé"));
+    let r1 = sim.handle("r1").unwrap();
+    let response = routing_project::control_socket::send_command(&path, "disable").await.unwrap();
+    assert_eq!(response, "Protocole OSPF désactivé");
+    assert!(!r1.state().is_enabled().await);
+
+    sim.stop().await;
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(unix)]
+#[tokio::test(start_paused = true)]
+async fn control_commands_inspect_the_router() {
+    let path = std::env::temp_dir().join(format!("pospf-json-{}.sock", std::process::id()));
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(23, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.control.socket = Some(path.clone());
+            }
+            config
+        })
+        .await;
+    sim.run_for(Duration::from_secs(60)).await;
+
+    let response = routing_project::control_socket::send_command(&path, "routing-table --json").await.unwrap();
+    let routes: Vec<serde_json::Value> = serde_json::from_str(&response).unwrap();
+    let lan3 = routes.iter().find(|route| route["prefix"] == ring.lan(3).to_string()).unwrap();
+    // Provenance : LSA routeur de r3, atteint par un chemin partant de r1
+    let hops = lan3["origin"]["path"].as_array().unwrap();
+    assert_eq!(lan3["origin"]["lsa_type"], "router");
+    assert_eq!(hops.last(), Some(&lan3["origin"]["originator"]));
+    assert!(hops.len() >= 2);
+    let response = routing_project::control_socket::send_command(&path, "neighbors --json").await.unwrap();
+    let neighbors: Vec<serde_json::Value> = serde_json::from_str(&response).unwrap();
+    assert_eq!(neighbors.len(), 2);
+    let lsdb = routing_project::control_socket::send_command(&path, "lsdb").await.unwrap();
+    assert!(lsdb.starts_with("Type"));
+    assert!(lsdb.lines().filter(|line| line.starts_with("router ")).count() >= 3);
+    let hops = routing_project::control_socket::send_command(&path, "path 192.168.3.10").await.unwrap();
+    assert!(hops.starts_with(&format!("Chemin vers {}", ring.lan(3))), "{}", hops);
+    assert!(hops.lines().count() >= 3);
+    let response = routing_project::control_socket::send_command(&path, "bogus --json").await.unwrap();
+    let rejected: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(rejected["ok"], false);
+
+    sim.stop().await;
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test(start_paused = true)]
+async fn set_link_down_reroutes_without_restart() {
+    let ring = Ring { n: 4 };
+    let mut sim = ring
+        .build_with(29, |_| {
+            let mut config = Simulation::router_config();
+            config.control.anonymous = Some(ControlRole::Admin);
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let address: Ipv4Addr = "192.168.1.50".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let client = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let command = |command: &str| ControlMessage { message_type: 3, command: command.to_string(), user: None, timestamp: None, signature: None, request_id: None };
+
+    // r1 coupe administrativement eth0 (vers r2) : lan2 est joint par r4 puis r3
+    let response = control_from_lan(&client, &command("set-link eth0 down")).await;
+    assert_eq!(response, "Interface eth0: lien inactif, coût ∞");
+    let via_r4 = ring.address(4, 1);
+    let rerouted = sim.run_until(TICK, 30, |tables| {
+        matches!(tables["r1"].get(&ring.lan(2)), Some((next_hop, RouteState::Active(_))) if *next_hop == via_r4)
+    }).await;
+    assert!(rerouted.is_some(), "no reroute: {:#?}", sim.routing_tables().await);
+
+    assert!(control_from_lan(&client, &command("set-cost eth1 500")).await.ends_with("coût 500"));
+    assert!(control_from_lan(&client, &command("set-link eth9 up")).await.starts_with("Échec"));
+    control_from_lan(&client, &command("set-link eth0 up")).await;
+    let via_r2 = ring.address(2, 1);
+    let restored = sim.run_until(TICK, 30, |tables| {
+        matches!(tables["r1"].get(&ring.lan(2)), Some((next_hop, RouteState::Active(_))) if *next_hop == via_r2)
+    }).await;
+    assert!(restored.is_some(), "no recovery: {:#?}", sim.routing_tables().await);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn ping_reaches_a_remote_router_through_the_protocol_port() {
+    let ring = Ring { n: 5 };
+    let mut sim = ring.build(37).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let address: Ipv4Addr = "192.168.1.50".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let client = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let command = |command: &str| ControlMessage { message_type: 3, command: command.to_string(), user: None, timestamp: None, signature: None, request_id: None };
+
+    // r1 -> r2 -> r3, réponse relayée en sens inverse ; la commande reçue sur le port du
+    // protocole attend une réponse qui arrive par ce même port
+    let response = control_from_lan(&client, &command("ping 192.168.3.1")).await;
+    assert!(response.starts_with("Réponse de 192.168.3.1"), "{}", response);
+    assert!(response.ends_with("2 sauts, chemin: 192.168.1.1 -> 192.168.2.1 -> 192.168.3.1"), "{}", response);
+
+    let r1 = sim.handle("r1").unwrap();
+    assert!(r1.command("ping 192.168.9.1").await.starts_with("Échec"));
+    assert!(r1.command("ping r3").await.starts_with("Argument invalide"));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn route_requests_are_answered_from_the_routing_table() {
+    let ring = Ring { n: 4 };
+    let mut sim = ring.build(73).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let address: Ipv4Addr = "192.168.1.50".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let client = Socket::Virtual(sim.network().socket(vec![address], 5000));
+
+    // Un hôte du réseau local de r1 l'interroge directement
+    let request = RouteRequest { message_type: 10, id: 7, destination: "192.168.2.9/32".parse().unwrap() };
+    let router: SocketAddr = "192.168.1.1:5000".parse().unwrap();
+    routing_project::net_utils::send_message(&client, &router, &request, &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+    let mut buf = vec![0u8; 65_536];
+    let response: RouteResponse = loop {
+        let (len, _) = tokio::time::timeout(Duration::from_secs(5), client.recv_from(&mut buf)).await.unwrap().unwrap();
+        let (header, body) = framing::split(&buf[..len]).unwrap();
+        if header.message_type == framing::ROUTE_RESPONSE {
+            break serde_json::from_slice(body).unwrap();
+        }
+    };
+    assert_eq!(response.id, 7);
+    assert_eq!(response.prefix, Some(ring.lan(2)));
+    assert_eq!(response.next_hop.as_deref(), Some("10.0.12.2"));
+    assert!(response.reachable && response.metric.is_some());
+    assert_eq!(response.path.first().map(String::as_str), Some("192.168.1.1"));
+
+    // `path <destination> <routeur>` : r1 pose la question à r2
+    let r1 = sim.handle("r1").unwrap();
+    let answer = r1.command("path 192.168.3.9 10.0.12.2").await;
+    assert!(answer.starts_with("192.168.2.1 : 192.168.3.1/24 via 10.0.23.3"), "{}", answer);
+    assert!(r1.command("path 192.168.1.9 10.0.12.2").await.contains("via 10.0.12.1"));
+    assert_eq!(r1.command("path 10.9.9.9 10.0.12.2").await, "192.168.2.1 n'a aucune route vers 10.9.9.9/32");
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn paths_lists_alternatives_and_disjoint_backups() {
+    let ring = Ring { n: 4 };
+    let mut sim = ring.build(79).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let r1 = sim.handle("r1").unwrap();
+
+    // Deux chemins seulement dans l'anneau, par r2 et par r4
+    let answer = r1.command("paths 192.168.3.9 --k 3").await;
+    assert!(answer.starts_with(&format!("2 meilleur(s) chemin(s) vers {}", ring.lan(3))), "{}", answer);
+    assert!(answer.contains("-> 10.0.12.2 ->") && answer.contains("-> 10.0.41.4 ->"), "{}", answer);
+    let answer = r1.command("paths 192.168.3.9 --disjoint").await;
+    assert!(answer.lines().nth(2).is_some_and(|line| line.starts_with("  secours : coût 2") && line.ends_with("(aucun routeur commun)")), "{}", answer);
+    // Le détour par r4 et r3 coûte plus que le lien direct vers r2
+    let answer = r1.command("paths 192.168.2.9").await;
+    assert!(answer.lines().nth(1).is_some_and(|line| line.starts_with("  1 : coût 1, 1 sauts")), "{}", answer);
+    assert!(answer.lines().nth(2).is_some_and(|line| line.starts_with("  2 : coût 3, 3 sauts")), "{}", answer);
+    assert!(r1.command("paths 192.168.3.9 --k 0").await.starts_with("Argument invalide"));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn batch_mode_keeps_the_options_of_the_command() {
+    let args = |line: &str| line.split_whitespace().map(String::from).collect::<Vec<_>>();
+    // Seules les options du CLI sont retirées, celles de la commande restent à leur place
+    let k = command_from_args(args("--server 10.0.12.1 paths 192.168.3.9 --k 3")).unwrap();
+    assert_eq!(k, "paths 192.168.3.9 --k 3");
+    let disjoint = command_from_args(args("--config=/etc/pospf.toml paths 192.168.3.9 --disjoint --json")).unwrap();
+    assert_eq!(disjoint, "paths 192.168.3.9 --disjoint --json");
+    assert_eq!(command_from_args(args("--user admin --vrf blue")), None);
+
+    let ring = Ring { n: 4 };
+    let mut sim = ring.build(79).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let r1 = sim.handle("r1").unwrap();
+    let answer = r1.command(&k).await;
+    assert!(answer.starts_with(&format!("2 meilleur(s) chemin(s) vers {}", ring.lan(3))), "{}", answer);
+    let answer = r1.command(&disjoint).await;
+    assert!(answer.contains("Chemins disjoints vers"), "{}", answer);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn spof_reports_cut_routers_and_links() {
+    let ring = Ring { n: 4 };
+    let mut sim = ring.build(83).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let r1 = sim.handle("r1").unwrap();
+    assert!(r1.command("spof").await.starts_with("Aucun point unique de défaillance"));
+
+    // Sans le lien r2-r3, l'anneau devient une chaîne r2 - r1 - r4 - r3
+    sim.set_link_up(&ring.segment(2), false);
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[(2, 3)])).await.is_some());
+    let report = r1.command("spof").await;
+    assert!(report.contains("Routeurs : 10.0.12.1, segment 10.0.34.0/24, 10.0.41.4"), "{}", report);
+    let analysis: serde_json::Value = serde_json::from_str(&r1.command("spof --json").await).unwrap();
+    assert_eq!(analysis["links"].as_array().map(Vec::len), Some(4), "{}", analysis);
+    assert!(analysis["links"].as_array().unwrap().contains(&serde_json::json!(["10.0.12.1", "10.0.12.2"])));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn history_journal_replays_past_routing_state() {
+    let path = std::env::temp_dir().join(format!("pospf-history-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(routing_project::history::rotated_path(&path));
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(89, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.history.path = Some(path.clone());
+                config.history.max_bytes = 64 * 1024;
+            }
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let r1 = sim.handle("r1").unwrap();
+    let recent = r1.command("history 200").await;
+    assert!(recent.contains(&format!("route {} via {}", ring.lan(2), ring.address(2, 1))), "{}", recent);
+    assert!(recent.lines().any(|line| line.contains("LSA Router")), "{}", recent);
+
+    // r1 perd son lien direct vers r2 : lan2 passe par r3
+    let before_cut = routing_project::clock::unix_secs();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    sim.set_link_up(&ring.segment(1), false);
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[(1, 2)])).await.is_some());
+
+    let past = r1.command(&format!("history at {}", before_cut)).await;
+    assert!(past.contains(&format!("  {} via {}", ring.lan(2), ring.address(2, 1))), "{}", past);
+    let records = routing_project::history::read(&path).unwrap();
+    let now = routing_project::history::replay(&records, u64::MAX);
+    assert_eq!(now.routes.get(&ring.lan(2)).map(|(next_hop, _)| next_hop.clone()), Some(ring.address(3, 1)));
+    assert!(now.lsdb.router_lsas().count() >= 3);
+    assert!(r1.command("history at 25:00").await.starts_with("Argument invalide"));
+
+    sim.stop().await;
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(routing_project::history::rotated_path(&path));
+}
+
+#[tokio::test]
+async fn history_journal_rotates_past_its_size_limit() {
+    use routing_project::history::{self, HistoryRecord, Journal};
+
+    let path = std::env::temp_dir().join(format!("pospf-rotation-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(history::rotated_path(&path));
+    let mut journal = Journal::open(&path, 512).unwrap();
+    for at_ms in 0..20 {
+        let record = HistoryRecord::Route { at_ms, prefix: "10.1.0.0/16".parse().unwrap(), route: None };
+        journal.append(&[record]).await.unwrap();
+    }
+    // Le fichier courant reste sous la limite, le précédent garde les entrées plus anciennes
+    assert!(std::fs::metadata(&path).unwrap().len() <= 512);
+    assert!(history::rotated_path(&path).exists());
+    let records = history::read(&path).unwrap();
+    assert_eq!(records.last().map(HistoryRecord::at_ms), Some(19));
+    assert!(records.len() < 20);
+
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(history::rotated_path(&path));
+}
+
+#[tokio::test(start_paused = true)]
+async fn administrative_state_survives_restart() {
+    let path = std::env::temp_dir().join(format!("pospf-state-{}.json", std::process::id()));
+    let saved = PersistedState {
+        enabled: false,
+        interfaces: BTreeMap::from([("eth0".to_string(), PersistedInterface { link_active: false, cost: None })]),
+        lsa_sequence: None,
+    };
+    routing_project::persist::write(&path, &saved).unwrap();
+
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(31, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.state_file = Some(path.clone());
+                config.control.anonymous = Some(ControlRole::Admin);
+            }
+            config
+        })
+        .await;
+    let r1 = sim.handle("r1").unwrap();
+    assert!(!r1.state().is_enabled().await);
+    assert!(!r1.state().interface_state("eth0").await.unwrap().link_active);
+    assert!(r1.state().interface_state("eth1").await.unwrap().link_active);
+
+    // Réactivé par l'opérateur : le fichier ne garde que la coupure d'eth0
+    let address: Ipv4Addr = "192.168.1.50".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let client = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let enable = ControlMessage { message_type: 3, command: "enable".to_string(), user: None, timestamp: None, signature: None, request_id: None };
+    assert_eq!(control_from_lan(&client, &enable).await, "Protocole OSPF activé");
+    let reloaded = routing_project::persist::load(&path).unwrap().unwrap();
+    assert!(reloaded.enabled);
+    assert_eq!(reloaded.interfaces, saved.interfaces);
+
+    sim.stop().await;
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn lsa_sequence_rolls_over_without_the_clock() {
+    let counter = SequenceCounter::starting_at(sequence::MAX_SEQUENCE - 1);
+    assert_eq!(counter.advance(), Some((sequence::MAX_SEQUENCE, true)));
+    // MaxSequenceNumber atteint : plus aucun numéro tant que l'instance MAX n'est pas retirée
+    assert_eq!(counter.advance(), None);
+    assert!(counter.begin_wrap());
+    assert!(!counter.begin_wrap());
+    assert_eq!(counter.advance(), None);
+    counter.restart();
+    let (seq_num, reserve) = counter.advance().unwrap();
+    assert_eq!(seq_num, sequence::INITIAL_SEQUENCE);
+    assert!(reserve);
+    // Sans retrait, INITIAL reste plus ancien que MAX
+    assert!(!sequence::is_newer(sequence::INITIAL_SEQUENCE, sequence::MAX_SEQUENCE));
+    assert!(sequence::is_newer(sequence::INITIAL_SEQUENCE + 1, sequence::INITIAL_SEQUENCE));
+    assert!(!sequence::is_newer(sequence::INITIAL_SEQUENCE, 1_700_000_000));
+
+    // Une de nos anciennes instances plus récente que le compteur : on la dépasse
+    let counter = SequenceCounter::starting_at(10);
+    assert!(counter.observe(500));
+    assert!(!counter.observe(20));
+    assert_eq!(counter.advance().unwrap().0, 501);
+    counter.resume_after(42);
+    assert_eq!(counter.advance(), Some((43, true)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn lsa_sequence_wrap_flushes_the_max_instance() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring.build(107).await;
+    sim.run_for(Duration::from_secs(30)).await;
+    let (r1, r2) = (sim.handle("r1").unwrap(), sim.handle("r2").unwrap());
+
+    // Originateur du lan1 dont r1 et r2 ont retenu l'instance MAX-1 et manqué l'instance MAX
+    let address: Ipv4Addr = "192.168.1.62".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let originator = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let router: SocketAddr = "192.168.1.1:5000".parse().unwrap();
+    let send = |seq_num: u32, prefix: &str, flush: bool| {
+        let mut lsa = routing_project::types::LSAMessage {
+            message_type: 2,
+            router_ip: address.to_string(),
+            last_hop: None,
+            originator: "10.99.0.2".to_string(),
+            router_id: None,
+            seq_num,
+            neighbor_count: 0,
+            neighbors: Vec::new(),
+            routing_table: HashMap::from([(prefix.parse().unwrap(), RouteState::Active(1))]),
+            external_routes: HashMap::new(),
+            external_metric_types: HashMap::new(),
+            route_tags: HashMap::new(),
+            path: Vec::new(),
+            ttl: 8,
+            stub_only: false,
+            base_seq: None,
+            withdrawn: Vec::new(),
+            opaque: Vec::new(),
+            flush,
+            checksum: None,
+            part: None,
+        };
+        lsa.seal();
+        let originator = &originator;
+        async move { routing_project::net_utils::send_message(originator, &router, &lsa, &SegmentKey::plaintext(), "[TEST]").await.unwrap() }
+    };
+    let installed = |handle: &routing_project::RouterHandle| {
+        let state = std::sync::Arc::clone(handle.state());
+        async move { state.lsdb.read().await.router_lsa("10.99.0.2").map(|lsa| lsa.seq_num) }
+    };
+
+    send(sequence::MAX_SEQUENCE - 1, "172.18.0.0/16", false).await;
+    sim.run_for(Duration::from_secs(2)).await;
+    assert_eq!(installed(&r1).await, Some(sequence::MAX_SEQUENCE - 1));
+    assert_eq!(installed(&r2).await, Some(sequence::MAX_SEQUENCE - 1));
+    // Sans retrait préalable, INITIAL reste plus ancien que MAX-1
+    send(sequence::INITIAL_SEQUENCE, "172.19.0.0/16", false).await;
+    sim.run_for(Duration::from_secs(2)).await;
+    assert_eq!(installed(&r1).await, Some(sequence::MAX_SEQUENCE - 1));
+    // Seule l'instance MAX se retire
+    send(sequence::MAX_SEQUENCE - 1, "172.18.0.0/16", true).await;
+    sim.run_for(Duration::from_secs(2)).await;
+    assert_eq!(installed(&r1).await, Some(sequence::MAX_SEQUENCE - 1));
+    assert_eq!(installed(&r2).await, Some(sequence::MAX_SEQUENCE - 1));
+
+    // Retrait de l'instance MAX, relayé de r1 à r2, puis retour à INITIAL
+    send(sequence::MAX_SEQUENCE, "172.18.0.0/16", true).await;
+    sim.run_for(Duration::from_secs(2)).await;
+    assert_eq!(installed(&r1).await, None);
+    assert_eq!(installed(&r2).await, None);
+    send(sequence::INITIAL_SEQUENCE, "172.19.0.0/16", false).await;
+    sim.run_for(Duration::from_secs(2)).await;
+    assert_eq!(installed(&r1).await, Some(sequence::INITIAL_SEQUENCE));
+    assert_eq!(installed(&r2).await, Some(sequence::INITIAL_SEQUENCE));
+    let lsa = r2.state().lsdb.read().await.router_lsa("10.99.0.2").unwrap().clone();
+    assert!(lsa.networks.contains_key(&"172.19.0.0/16".parse().unwrap()));
+
+    // Compteur de r1 lui-même au bout de l'espace : ses voisins le suivent après le retour
+    r1.state().own_sequence.resume_after(sequence::MAX_SEQUENCE - 2);
+    let router_id = r1.state().router_id.to_string();
+    let r3 = sim.handle("r3").unwrap();
+    let restarted = |seq_num: u32| (seq_num as i32) < 0;
+    let mut wrapped = false;
+    for _ in 0..120 {
+        sim.run_for(Duration::from_secs(1)).await;
+        wrapped = restarted(r1.state().own_sequence.current());
+        for handle in [&r2, &r3] {
+            wrapped &= handle.state().lsdb.read().await.router_lsa(&router_id).is_some_and(|lsa| restarted(lsa.seq_num));
+        }
+        if wrapped {
+            break;
+        }
+    }
+    assert!(wrapped, "{}", r2.command("lsdb").await);
+    let ticks = sim.run_until(TICK, 60, |tables| tables["r2"].contains_key(&ring.lan(1)) && tables["r3"].contains_key(&ring.lan(1))).await;
+    assert!(ticks.is_some(), "{:#?}", sim.routing_tables().await);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn corrupted_lsas_are_counted_and_kept_out_of_the_lsdb() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring.build(31).await;
+    sim.run_for(Duration::from_secs(30)).await;
+    let r1 = sim.handle("r1").unwrap();
+
+    let address: Ipv4Addr = "192.168.1.61".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let sender = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let router: SocketAddr = "192.168.1.1:5000".parse().unwrap();
+    let mut lsa = routing_project::types::LSAMessage {
+        message_type: 2,
+        router_ip: address.to_string(),
+        last_hop: None,
+        originator: "10.99.0.1".to_string(),
+        router_id: None,
+        seq_num: 1,
+        neighbor_count: 0,
+        neighbors: Vec::new(),
+        routing_table: HashMap::from([("172.17.0.0/16".parse().unwrap(), RouteState::Active(1))]),
+        external_routes: HashMap::new(),
+        external_metric_types: HashMap::new(),
+        route_tags: HashMap::new(),
+        path: Vec::new(),
+        ttl: 8,
+        stub_only: false,
+        base_seq: None,
+        withdrawn: Vec::new(),
+        opaque: Vec::new(),
+        flush: false,
+        checksum: None,
+        part: None,
+    };
+    // LSA non scellé : rejeté et compté comme un LSA altéré
+    assert!(!lsa.checksum_ok());
+    routing_project::net_utils::send_message(&sender, &router, &lsa, &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+    sim.run_for(Duration::from_secs(2)).await;
+    assert!(r1.command("metrics").await.contains("pospf_lsa_checksum_failures_total 1"));
+    assert!(!r1.command("lsdb").await.contains("10.99.0.1"));
+
+    lsa.seal();
+    assert!(lsa.checksum_ok());
+
+    // Métrique altérée après le calcul de la somme de contrôle
+    let mut tampered = lsa.clone();
+    tampered.routing_table.insert("172.17.0.0/16".parse().unwrap(), RouteState::Active(2));
+    assert!(!tampered.checksum_ok());
+    routing_project::net_utils::send_message(&sender, &router, &tampered, &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+    sim.run_for(Duration::from_secs(2)).await;
+    assert!(r1.command("metrics").await.contains("pospf_lsa_checksum_failures_total 2"));
+    assert!(!r1.command("lsdb").await.contains("10.99.0.1"));
+
+    routing_project::net_utils::send_message(&sender, &router, &lsa, &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+    sim.run_for(Duration::from_secs(2)).await;
+    assert!(r1.command("metrics").await.contains("pospf_lsa_checksum_failures_total 2"));
+    assert!(r1.command("lsdb").await.contains("10.99.0.1"));
+    // L'émetteur n'est pas un voisin : aucun compteur par voisin n'est créé pour lui
+    assert!(r1.state().neighbor_stats.lock().await.get(&address.to_string()).is_none());
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn router_id_collision_is_reported() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring.build(29).await;
+    sim.run_for(Duration::from_secs(30)).await;
+    let r1 = sim.handle("r1").unwrap();
+    let mut events = r1.subscribe();
+
+    // Un autre routeur du lan1 annonce sous l'adresse LAN de r1, avec ses propres numéros
+    let address: Ipv4Addr = "192.168.1.60".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let impostor = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let router: SocketAddr = "192.168.1.1:5000".parse().unwrap();
+    for k in 0..3 {
+        let mut lsa = routing_project::types::LSAMessage {
+            message_type: 2,
+            router_ip: address.to_string(),
+            last_hop: None,
+            originator: "192.168.1.1".to_string(),
+            router_id: None,
+            seq_num: 0x7000_0000 + k * 1000,
+            neighbor_count: 0,
+            neighbors: Vec::new(),
+            routing_table: HashMap::from([("172.16.0.0/16".parse().unwrap(), RouteState::Active(1))]),
+            external_routes: HashMap::new(),
+            external_metric_types: HashMap::new(),
+            route_tags: HashMap::new(),
+            path: Vec::new(),
+            ttl: 8,
+            stub_only: false,
+            base_seq: None,
+            withdrawn: Vec::new(),
+            opaque: Vec::new(),
+            flush: false,
+            checksum: None,
+            part: None,
+        };
+        lsa.seal();
+        routing_project::net_utils::send_message(&impostor, &router, &lsa, &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+        sim.run_for(Duration::from_secs(2)).await;
+    }
+
+    assert!(r1.command("metrics").await.contains("pospf_router_id_conflicts_total 1"));
+    let conflicts: Vec<serde_json::Value> = serde_json::from_str(&r1.command("conflicts --json").await).unwrap();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0]["originator"], "192.168.1.1");
+    assert_eq!(conflicts[0]["source"], address.to_string());
+    let conflict = std::iter::from_fn(|| events.try_recv().ok())
+        .find(|event| matches!(event, RouterEvent::RouterIdConflict { .. }));
+    assert!(conflict.is_some());
+    // Le préfixe de l'imposteur n'est pas installé
+    assert!(!r1.routing_table().await.contains_key(&"172.16.0.0/16".parse().unwrap()));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn lsdb_identifies_routers_by_router_id() {
+    let router_id: RouterId = "2.2.2.2".parse().unwrap();
+    assert_eq!(router_id, RouterId(0x0202_0202));
+    assert_eq!(serde_json::to_string(&router_id).unwrap(), "\"2.2.2.2\"");
+    assert!(serde_json::from_str::<RouterId>("\"2.2.2\"").is_err());
+    let addresses = ["10.0.12.2".parse().unwrap(), "192.168.2.1".parse().unwrap()];
+    assert_eq!(RouterId::from_addresses(addresses).unwrap().to_string(), "192.168.2.1");
+
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(31, |i| {
+            let mut config = Simulation::router_config();
+            if i == 2 {
+                config.router_id = Some(router_id);
+            }
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    // Les LSA émis par r2 sur chacune de ses interfaces n'occupent qu'une entrée, à son identifiant
+    let r1 = sim.handle("r1").unwrap();
+    let lsdb = r1.command("lsdb").await;
+    let rows: Vec<&str> = lsdb.lines().filter(|line| line.starts_with("router ")).collect();
+    assert_eq!(rows.iter().filter(|row| row.contains("2.2.2.2")).count(), 1, "{}", lsdb);
+    assert!(!rows.iter().any(|row| row.split_whitespace().nth(1) == Some(ring.address(2, 1).as_str())), "{}", lsdb);
+    let routes: Vec<serde_json::Value> = serde_json::from_str(&r1.command("routing-table --json").await).unwrap();
+    let lan2 = routes.iter().find(|route| route["prefix"] == ring.lan(2).to_string()).unwrap();
+    assert_eq!(lan2["origin"]["originator"], "2.2.2.2");
+    let neighbors: Vec<serde_json::Value> = serde_json::from_str(&r1.command("neighbors --json").await).unwrap();
+    assert!(neighbors.iter().any(|neighbor| neighbor["router_id"] == "2.2.2.2" && neighbor["address"] == ring.address(2, 1).as_str()));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn adaptive_hello_backs_off_on_stable_links_and_speeds_up_after_a_flap() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(47, |_| {
+            let mut config = Simulation::router_config();
+            config.adaptive_hello.enabled = true;
+            config.adaptive_hello.stable_after_sec = 10;
+            config.adaptive_hello.flap_window_sec = 30;
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    sim.run_for(Duration::from_secs(20)).await;
+
+    let r1 = sim.handle("r1").unwrap();
+    let interval = |neighbor: String| {
+        let r1 = &r1;
+        async move {
+            let detail: serde_json::Value = serde_json::from_str(&r1.command(&format!("neighbor-detail {} --json", neighbor)).await).unwrap();
+            detail["hello_interval_sec"].as_u64().unwrap()
+        }
+    };
+    // Intervalle configuré (5 s) doublé, plafonné au tiers du délai d'expiration (22 s)
+    assert_eq!(interval(ring.address(2, 1)).await, 7);
+
+    sim.set_link_up(&ring.segment(1), false);
+    sim.run_until(TICK, 60, |_| false).await;
+    sim.set_link_up(&ring.segment(1), true);
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    assert_eq!(interval(ring.address(2, 1)).await, 1);
+    assert_eq!(interval(ring.address(3, 1)).await, 7);
+    assert!(r1.command(&format!("neighbor-detail {}", ring.address(2, 1))).await.contains("intervalle effectif: 1 s"));
+
+    sim.run_for(Duration::from_secs(40)).await;
+    assert_eq!(interval(ring.address(2, 1)).await, 7);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn install_policy_filters_weak_or_long_paths() {
+    use routing_project::dijkstra::{PathSelection, RouteInfo};
+    use routing_project::install_policy;
+    use routing_project::read_config::InstallPolicyConfig;
+
+    let policy = InstallPolicyConfig { min_bottleneck_mbps: Some(10), max_hop_count: Some(4) };
+    let route = |hop_count: u32, bottleneck_capacity: u32| RouteInfo {
+        destination: "10.0.0.9".to_string(),
+        next_hop: "10.0.0.2".to_string(),
+        total_cost: hop_count,
+        hop_count,
+        bottleneck_capacity,
+        total_latency_us: 0,
+        policy: PathSelection::MinCost,
+        path: Vec::new(),
+        is_reachable: true,
+    };
+    assert_eq!(install_policy::violation(&policy, &route(4, 10)), None);
+    assert_eq!(install_policy::violation(&policy, &route(5, 100)).as_deref(), Some("5 sauts > 4"));
+    assert_eq!(install_policy::violation(&policy, &route(1, 5)).as_deref(), Some("capacité du chemin 5 Mbps < 10 Mbps"));
+
+    // Tous les liens de r2 sont à 1000 Mbps : aucune route apprise n'atteint le seuil
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(67, |i| {
+            let mut config = Simulation::router_config();
+            if i == 2 {
+                config.install_policy.min_bottleneck_mbps = Some(2000);
+            }
+            config
+        })
+        .await;
+    let others_converged = |tables: &Tables| {
+        [1, 3].iter().all(|&from| [1, 2, 3].iter().filter(|&&to| to != from).all(|&to| tables[&format!("r{}", from)].contains_key(&ring.lan(to))))
+    };
+    assert!(sim.run_until(TICK, 60, others_converged).await.is_some());
+    sim.run_for(Duration::from_secs(10)).await;
+
+    let tables = sim.routing_tables().await;
+    assert!([1, 3].iter().all(|&i| !tables["r2"].contains_key(&ring.lan(i))));
+    let r2 = sim.handle("r2").unwrap();
+    let filtered: serde_json::Value = serde_json::from_str(&r2.command("filtered-routes --json").await).unwrap();
+    let prefixes: Vec<&str> = filtered.as_array().unwrap().iter().filter_map(|route| route["prefix"].as_str()).collect();
+    assert!(prefixes.contains(&"192.168.1.1/24") && prefixes.contains(&"192.168.3.1/24"), "{}", filtered);
+    let report = r2.command("filtered-routes").await;
+    assert!(report.contains("capacité du chemin 1000 Mbps < 2000 Mbps"), "{}", report);
+    assert!(sim.handle("r1").unwrap().command("filtered-routes").await.starts_with("Aucune route"));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn flapping_neighbor_is_quarantined_until_its_penalty_decays() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(53, |_| {
+            let mut config = Simulation::router_config();
+            config.flap_damping.quarantine = true;
+            config.flap_damping.suppress_threshold = 1500;
+            config.flap_damping.half_life_sec = 20;
+            config.flap_damping.max_suppress_sec = 120;
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    // Perte puis rétablissement rapide du lien r1-r2 : la seconde bascule dépasse le seuil
+    sim.set_link_up(&ring.segment(1), false);
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[(1, 2)])).await.is_some());
+    sim.set_link_up(&ring.segment(1), true);
+    sim.run_for(Duration::from_secs(10)).await;
+
+    let r1 = sim.handle("r1").unwrap();
+    assert!(ring.converged(&sim.routing_tables().await, &[(1, 2)]));
+    assert!(r1.state().metrics.quarantined_hellos.load(std::sync::atomic::Ordering::Relaxed) > 0);
+    let r2 = ring.address(2, 1);
+    let detail: serde_json::Value = serde_json::from_str(&r1.command(&format!("neighbor-detail {} --json", r2)).await).unwrap();
+    assert_eq!(detail["damping"]["quarantined"], true, "{}", detail);
+    assert!(r1.command(&format!("neighbor-detail {}", r2)).await.contains("en quarantaine"));
+
+    // La pénalité décroît sous le seuil de réutilisation : l'adjacence revient sans nouvelle peine
+    assert!(sim.run_until(TICK, 90, |tables| ring.converged(tables, &[])).await.is_some());
+    sim.run_for(Duration::from_secs(10)).await;
+    assert!(ring.converged(&sim.routing_tables().await, &[]));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn neighbor_detail_reports_per_neighbor_counters_and_state_changes() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring.build(45).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    // Lien r1-r2 coupé jusqu'à l'expiration du voisin, puis rétabli
+    sim.set_link_up(&ring.segment(1), false);
+    sim.run_until(TICK, 60, |_| false).await;
+    sim.set_link_up(&ring.segment(1), true);
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    let r1 = sim.handle("r1").unwrap();
+    let r2 = ring.address(2, 1);
+    let detail: serde_json::Value = serde_json::from_str(&r1.command(&format!("neighbor-detail {} --json", r2)).await).unwrap();
+    let stats = &detail["stats"];
+    for counter in ["hellos_rx", "hellos_tx", "lsas_rx", "lsas_tx"] {
+        assert!(stats[counter].as_u64().unwrap() > 0, "{}: {}", counter, detail);
+    }
+    assert!(stats["last_seq"].as_u64().is_some(), "{}", detail);
+    let changes: Vec<bool> = stats["state_changes"].as_array().unwrap().iter().map(|change| change["up"].as_bool().unwrap()).collect();
+    assert_eq!(changes, vec![true, false, true], "{}", detail);
+
+    assert!(r1.command(&format!("neighbor-detail {}", r2)).await.contains("HELLO reçus/émis"));
+    assert!(r1.command("neighbor-detail 10.9.9.9").await.starts_with("Échec"));
+    let rendered = r1.state().neighbor_stats.lock().await.render();
+    assert!(rendered.contains(&format!("pospf_neighbor_state_changes_total{{neighbor=\"{}\"}} 3", r2)), "{}", rendered);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn full_receive_queue_drops_and_counts_datagrams() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(31, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.receive = ReceiveConfig { workers: 1, queue_depth: 8 };
+            }
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let address: Ipv4Addr = "192.168.1.70".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let flooder = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let router: SocketAddr = "192.168.1.1:5000".parse().unwrap();
+    for _ in 0..50 {
+        flooder.send_to(b"not a message", router).await.unwrap();
+    }
+    sim.run_for(Duration::from_secs(2)).await;
+
+    let r1 = sim.handle("r1").unwrap();
+    let metrics = &r1.state().metrics;
+    let dropped = metrics.receive_queue_dropped.load(std::sync::atomic::Ordering::Relaxed);
+    let rejected = metrics.packets_rejected.load(std::sync::atomic::Ordering::Relaxed);
+    assert!(dropped > 0 && rejected > 0, "dropped {} rejected {}", dropped, rejected);
+    assert!(dropped + rejected >= 50);
+    assert_eq!(metrics.receive_queue_depth.load(std::sync::atomic::Ordering::Relaxed), 0);
+    // Le routeur continue de traiter les messages suivants
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn hellos_are_not_dropped_behind_a_full_receive_queue() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(59, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.receive = ReceiveConfig { workers: 1, queue_depth: 8 };
+            }
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let address: Ipv4Addr = "192.168.1.71".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let peer = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let router: SocketAddr = "192.168.1.1:5000".parse().unwrap();
+    // Rafale de LSA illisibles qui remplit la file de traitement, suivie d'un HELLO
+    let garbage = framing::frame(2, framing::Codec::Json, AuthMode::None, b"not a message").unwrap();
+    for _ in 0..50 {
+        peer.send_to(&garbage, router).await.unwrap();
+    }
+    let hello = HelloMessage {
+        message_type: 1,
+        router_ip: address.to_string(),
+        router_id: None,
+        timestamp_us: 0,
+        echoes: Vec::new(),
+        hostname: None,
+        priority: 1,
+        auth: Some(AuthMode::None),
+    };
+    routing_project::net_utils::send_message(&peer, &router, &hello, &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+    sim.run_for(Duration::from_secs(2)).await;
+
+    let r1 = sim.handle("r1").unwrap();
+    assert!(r1.state().metrics.receive_queue_dropped.load(std::sync::atomic::Ordering::Relaxed) > 0);
+    let hellos = r1.state().neighbor_stats.lock().await.get(&address.to_string()).map(|stats| stats.hellos_rx);
+    assert_eq!(hellos, Some(1));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn pending_challenges_expire_and_stay_bounded() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring.build(139).await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    let address: Ipv4Addr = "192.168.1.72".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    let peer = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let router: SocketAddr = "192.168.1.1:5000".parse().unwrap();
+    // Chaque HELLO annonce une autre adresse : un défi par adresse, jamais plus que la limite
+    let hello = |k: u32| HelloMessage {
+        message_type: 1,
+        router_ip: Ipv4Addr::from(0x0ac8_0000 + k).to_string(),
+        router_id: None,
+        timestamp_us: 0,
+        echoes: Vec::new(),
+        hostname: None,
+        priority: 1,
+        auth: Some(AuthMode::None),
+    };
+    for k in 0..300 {
+        routing_project::net_utils::send_message(&peer, &router, &hello(k), &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+        sim.run_for(Duration::from_millis(10)).await;
+    }
+    let r1 = sim.handle("r1").unwrap();
+    assert_eq!(r1.state().neighbor_auth.lock().await.len(), 256);
+
+    // Défis expirés oubliés au suivant
+    sim.run_for(Duration::from_secs(11)).await;
+    routing_project::net_utils::send_message(&peer, &router, &hello(300), &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+    sim.run_for(Duration::from_secs(1)).await;
+    assert_eq!(r1.state().neighbor_auth.lock().await.len(), 1);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn delta_lsas_carry_only_changes() {
+    let ring = Ring { n: 4 };
+    let mut sim = ring
+        .build_with(37, |_| {
+            let mut config = Simulation::router_config();
+            config.lsa_delta.enabled = true;
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+    sim.run_for(Duration::from_secs(120)).await;
+
+    let r1 = sim.handle("r1").unwrap();
+    let metrics = r1.command("metrics").await;
+    assert!(!metrics.contains("pospf_lsa_delta_sent_total 0"), "{}", metrics);
+    assert!(ring.converged(&sim.routing_tables().await, &[]));
+
+    // Les changements de routes voyagent dans les différentiels
+    sim.set_link_up(&ring.segment(1), false);
+    let rerouted = sim.run_until(TICK, 120, |tables| ring.converged(tables, &[(1, 2)])).await;
+    assert!(rerouted.is_some(), "no reroute: {:#?}", sim.routing_tables().await);
+    for name in ["r1", "r2", "r3", "r4"] {
+        let metrics = sim.handle(name).unwrap().command("metrics").await;
+        assert!(metrics.contains("pospf_lsa_delta_unusable_total 0"), "{}: {}", name, metrics);
+    }
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn opaque_tlvs_are_flooded_without_interpretation() {
+    let tlv = OpaqueTlv { tlv_type: 40_000, value: b"service=dns".to_vec() };
+    assert_eq!(serde_json::to_value(&tlv).unwrap(), serde_json::json!({ "tlv_type": 40_000, "value": "c2VydmljZT1kbnM=" }));
+
+    let ring = Ring { n: 4 };
+    let mut sim = ring
+        .build_with(41, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.opaque = vec![tlv.clone()];
+            }
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    // r3 n'est pas adjacent à r1 : le TLV lui parvient par relais, sans effet sur ses routes
+    let originator = sim.handle("r1").unwrap().state().router_id.to_string();
+    let r3 = sim.handle("r3").unwrap();
+    let lsdb = r3.state().lsdb.read().await;
+    let opaque = lsdb.opaque_lsa(&originator).expect("opaque LSA of r1");
+    assert_eq!(opaque.tlv(40_000), Some(b"service=dns".as_slice()));
+    assert_eq!(lsdb.opaque_lsas().count(), 1);
+    drop(lsdb);
+    assert!(r3.command("lsdb").await.lines().any(|line| line.starts_with("opaque ") && line.contains("TLV 40000 (11 octets)")));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn ospfv2_engines_reach_full_and_exchange_stub_networks() {
+    use routing_project::ospfv2_interop::{stub_link, Engine, InteropInterface, NeighborState};
+
+    let interface = |address: [u8; 4]| InteropInterface {
+        name: "eth1".to_string(),
+        address: Ipv4Addr::from(address),
+        network: "10.0.0.0/30".parse().unwrap(),
+        hello_interval: 10,
+        dead_interval: 40,
+        cost: 10,
+        mtu: 1500,
+    };
+    let ids = [Ipv4Addr::new(1, 1, 1, 1), Ipv4Addr::new(2, 2, 2, 2)];
+    let mut engines = [Engine::new(ids[0], vec![interface([10, 0, 0, 1])]), Engine::new(ids[1], vec![interface([10, 0, 0, 2])])];
+    let lan: Prefix = "192.168.2.0/24".parse().unwrap();
+    engines[1].set_stubs(vec![stub_link(&lan, 5)], tokio::time::Instant::now());
+
+    let full = |engines: &[Engine; 2]| engines.iter()
+        .all(|engine| engine.neighbors().iter().any(|(_, _, _, state)| *state == NeighborState::Full));
+    // Adjacence, puis nouvelles instances des LSA routeurs (MinLSInterval)
+    for _ in 0..60 {
+        let now = tokio::time::Instant::now();
+        for side in 0..2 {
+            engines[side].tick(now);
+            let (outgoing, _) = engines[side].take_output();
+            let source = engines[side].interfaces().next().unwrap().address;
+            for out in outgoing {
+                engines[1 - side].receive(0, source, &out.packet.encode(), now).unwrap();
+            }
+        }
+        tokio::time::advance(TICK).await;
+    }
+    assert!(full(&engines), "adjacency did not reach Full: {:?}", engines[0].neighbors());
+    assert_eq!(engines[0].lsdb_len(), 2);
+
+    // Le réseau de r2 est appris via r2, métrique relative à r2
+    let routes = engines[0].routes(tokio::time::Instant::now());
+    assert_eq!(routes[&ids[1]].networks.get(&lan), Some(&5));
+}
+
+#[tokio::test(start_paused = true)]
+async fn bgp_gateway_imports_loop_free_routes_as_externals() {
+    use routing_project::bgp_lite::{self, BgpMessage, BgpRoute};
+    use routing_project::read_config::BgpPeerConfig;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(43, |i| {
+            let mut config = Simulation::router_config();
+            if i == 1 {
+                config.bgp.enabled = true;
+                config.bgp.local_as = 65001;
+                config.bgp.export = vec!["192.168.3.0/24".to_string()];
+            }
+            config
+        })
+        .await;
+    assert!(sim.run_until(TICK, 60, |tables| ring.converged(tables, &[])).await.is_some());
+
+    // Le test joue le routeur externe de l'AS 65002
+    let peer = BgpPeerConfig { address: "198.51.100.1:179".to_string(), remote_as: 65002, passive: false };
+    let (local, remote) = tokio::io::duplex(64 * 1024);
+    let state = std::sync::Arc::clone(sim.handle("r1").unwrap().state());
+    let session = tokio::spawn(async move { bgp_lite::run_session(&state, &peer, local).await });
+    let (reader, mut writer) = tokio::io::split(remote);
+    let mut lines = BufReader::new(reader).lines();
+    let send = |message: BgpMessage| format!("{}\n", serde_json::to_string(&message).unwrap());
+    let open = serde_json::from_str::<BgpMessage>(&lines.next_line().await.unwrap().unwrap()).unwrap();
+    assert!(matches!(open, BgpMessage::Open { asn: 65001, .. }));
+    writer.write_all(send(BgpMessage::Open { asn: 65002, router_id: "198.51.100.1".to_string(), hold_time_sec: 90 }).as_bytes()).await.unwrap();
+    let external: Prefix = "203.0.113.0/24".parse().unwrap();
+    let looped: Prefix = "198.51.100.0/24".parse().unwrap();
+    writer.write_all(send(BgpMessage::Update {
+        announce: vec![
+            BgpRoute { prefix: external, as_path: vec![65002, 64999] },
+            BgpRoute { prefix: looped, as_path: vec![65002, 65001] },
+        ],
+        withdraw: Vec::new(),
+    }).as_bytes()).await.unwrap();
+
+    // Seul le LAN de r3, joignable dans le domaine, est exporté
+    let update = serde_json::from_str::<BgpMessage>(&lines.next_line().await.unwrap().unwrap()).unwrap();
+    let BgpMessage::Update { announce, .. } = update else {
+        panic!("expected an UPDATE, received {:?}", update);
+    };
+    assert_eq!(announce, vec![BgpRoute { prefix: "192.168.3.0/24".parse().unwrap(), as_path: vec![65001] }]);
+
+    let imported = sim.run_until(TICK, 60, |tables| {
+        matches!(tables["r3"].get(&external), Some((_, RouteState::External { .. })))
+    }).await;
+    assert!(imported.is_some(), "external route not learned by r3");
+    assert!(!sim.routing_tables().await["r3"].contains_key(&looped));
+    let r1 = sim.handle("r1").unwrap();
+    assert_eq!(r1.state().metrics.bgp_routes_rejected.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+    // Fermeture de la session : les routes importées sont retirées
+    drop(writer);
+    drop(lines);
+    assert!(session.await.unwrap().is_err());
+    assert!(r1.state().bgp.read().await.best().is_empty());
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn rip_gateway_learns_routes_with_split_horizon_and_timeouts() {
+    use routing_project::rip::{Command, RipEntry, RipPacket, INFINITY, PORT};
+    use routing_project::rip_gateway::{hops_for_cost, RipGateway, RipInterface, RipTimers};
+    use std::net::SocketAddrV4;
+
+    let timers = RipTimers { update: Duration::from_secs(30), timeout: Duration::from_secs(180), garbage: Duration::from_secs(120) };
+    let interface = RipInterface { name: "eth2".to_string(), address: Ipv4Addr::new(10, 0, 9, 1), network: "10.0.9.0/24".parse().unwrap() };
+    let mut gateway = RipGateway::new(vec![interface], timers);
+    let legacy: Prefix = "172.16.5.0/24".parse().unwrap();
+    let domain: Prefix = "192.168.1.0/24".parse().unwrap();
+    gateway.set_exported(BTreeMap::from([(domain, hops_for_cost(25, 10))]));
+
+    let response = RipPacket {
+        command: Command::Response,
+        entries: vec![RipEntry { prefix: legacy, tag: 0, next_hop: Ipv4Addr::UNSPECIFIED, metric: 2 }],
+    };
+    let bytes = response.encode();
+    assert_eq!(RipPacket::decode(&bytes).unwrap(), response);
+    let router = SocketAddrV4::new(Ipv4Addr::new(10, 0, 9, 2), PORT);
+    let now = tokio::time::Instant::now();
+    gateway.receive(0, router, &bytes, now).unwrap();
+    assert_eq!(gateway.routes()[router.ip()].get(&legacy), Some(&2));
+
+    // Horizon partagé : la route RIP n'est pas renvoyée sur son interface, la route du
+    // domaine y est annoncée en sauts (coût 25, 10 par saut)
+    gateway.tick(now);
+    let (messages, _) = gateway.take_output();
+    let advertised: Vec<(Prefix, u32)> = messages.iter()
+        .flat_map(|message| message.packet.entries.iter().map(|entry| (entry.prefix, entry.metric)))
+        .collect();
+    assert_eq!(advertised, vec![(domain, 3)]);
+
+    // Sans nouvelle annonce, la route expire puis le routeur silencieux est déclaré perdu
+    gateway.tick(now + Duration::from_secs(181));
+    assert!(gateway.routes().is_empty());
+    gateway.tick(now + Duration::from_secs(302));
+    let (_, events) = gateway.take_output();
+    assert!(events.iter().any(|event| matches!(event, routing_project::rip_gateway::RipEvent::NeighborDown { .. })));
+    assert_eq!(hops_for_cost(1_000, 10), INFINITY - 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn access_router_keeps_only_a_default_route() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(97, |i| {
+            let mut config = Simulation::router_config();
+            if i == 3 {
+                config.role = RouterRole::Access;
+            }
+            config
+        })
+        .await;
+    // r3 n'apprend que la route par défaut ; son LAN reste annoncé aux autres routeurs
+    let ticks = sim.run_until(TICK, 60, |tables| {
+        tables["r3"].len() == 1
+            && tables["r3"].contains_key(&Prefix::default_route())
+            && matches!(tables["r1"].get(&ring.lan(3)), Some((_, RouteState::Active(2))))
+            && matches!(tables["r2"].get(&ring.lan(3)), Some((_, RouteState::Active(2))))
+            && matches!(tables["r1"].get(&ring.lan(2)), Some((_, RouteState::Active(2))))
+    }).await;
+    assert!(ticks.is_some(), "{:#?}", sim.routing_tables().await);
+
+    // Le routeur d'accès ne garde aucun LSA de ses voisins
+    let lsdb = sim.handle("r3").unwrap().command("lsdb").await;
+    assert!(lsdb.lines().skip(1).all(|row| row.split_whitespace().nth(1) == Some("192.168.3.1")), "{}", lsdb);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn lsas_are_fragmented_for_the_measured_path_mtu() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(101, |i| {
+            let mut config = Simulation::router_config();
+            // LSA de r2 plus grand qu'un datagramme
+            if i == 2 {
+                config.opaque.push(OpaqueTlv { tlv_type: 40_000, value: vec![7; 1024] });
+            }
+            config
+        })
+        .await;
+    // Les LSA de r2 ne parviennent à r1 que par le lien r1-r2, de MTU 1100 : les fragments
+    // dimensionnés pour Ethernet y sont perdus
+    sim.set_link_up(&ring.segment(2), false);
+    sim.network().set_link(&ring.segment(1), LinkProfile { mtu: Some(1100), ..LinkProfile::default() });
+    let ticks = sim.run_until(TICK, 90, |tables| tables["r1"].contains_key(&ring.lan(2))).await;
+    assert!(ticks.is_some(), "{:#?}", sim.routing_tables().await);
+
+    let report = sim.handle("r2").unwrap().command("mtu").await;
+    let line = report.lines().find(|line| line.contains(&ring.address(1, 2))).unwrap_or_default();
+    assert!(line.contains("1024 octets (mesurée"), "{}", report);
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn oversized_lsas_are_split_into_parts_for_the_path_mtu() {
+    use routing_project::framing::Framed;
+    let ring = Ring { n: 3 };
+    let mut sim = ring.build(137).await;
+    sim.run_for(Duration::from_secs(30)).await;
+    let r1 = sim.handle("r1").unwrap();
+
+    // Un routeur du lan1, de MTU 1280, annonce 200 réseaux : chaque partie tient dans un
+    // datagramme, sans fragment
+    let address: Ipv4Addr = "192.168.1.62".parse().unwrap();
+    sim.network().attach("lan1", address).unwrap();
+    sim.network().set_link("lan1", LinkProfile { mtu: Some(1280), ..LinkProfile::default() });
+    let sender = Socket::Virtual(sim.network().socket(vec![address], 5000));
+    let router: SocketAddr = "192.168.1.1:5000".parse().unwrap();
+    let mut lsa = routing_project::types::LSAMessage {
+        message_type: 2,
+        router_ip: address.to_string(),
+        last_hop: None,
+        originator: "10.99.0.2".to_string(),
+        router_id: None,
+        seq_num: 1,
+        neighbor_count: 0,
+        neighbors: Vec::new(),
+        routing_table: (0..200).map(|i| (format!("172.18.{}.0/24", i).parse().unwrap(), RouteState::Active(1))).collect(),
+        external_routes: HashMap::new(),
+        external_metric_types: HashMap::new(),
+        route_tags: HashMap::new(),
+        path: Vec::new(),
+        ttl: 8,
+        stub_only: false,
+        base_seq: None,
+        withdrawn: Vec::new(),
+        opaque: Vec::new(),
+        flush: false,
+        checksum: None,
+        part: None,
+    };
+    lsa.seal();
+    let max_payload = routing_project::fragment::payload_for_mtu(1280);
+    let parts = lsa.split_within(max_payload).unwrap();
+    assert!(parts.iter().all(|part| serde_json::to_vec(part).unwrap().len() <= max_payload));
+
+    // Tant qu'une partie manque, le LSA n'est pas installé
+    let (first, others) = parts.split_first().unwrap();
+    for part in others {
+        routing_project::net_utils::send_message(&sender, &router, part, &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+    }
+    sim.run_for(Duration::from_secs(2)).await;
+    assert!(r1.state().lsdb.read().await.router_lsa("10.99.0.2").is_none());
+    routing_project::net_utils::send_message(&sender, &router, first, &SegmentKey::plaintext(), "[TEST]").await.unwrap();
+    sim.run_for(Duration::from_secs(2)).await;
+    let networks = r1.state().lsdb.read().await.router_lsa("10.99.0.2").map(|lsa| lsa.networks.len());
+    assert_eq!(networks, Some(200));
+    assert!(r1.command("metrics").await.contains("pospf_lsa_checksum_failures_total 0"));
+
+    sim.stop().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn lossy_links_switch_lsas_to_a_tcp_session() {
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(103, |i| {
+            let mut config = Simulation::router_config();
+            if i != 3 {
+                config.interfaces = ["eth0", "eth1", "eth2"].into_iter()
+                    .map(|name| InterfaceConfig { transport: LsaTransport::Auto, ..InterfaceConfig::new(name, 1000) })
+                    .collect();
+            }
+            config
+        })
+        .await;
+    // r1 et r2 ne se parlent que par un lien perdant un datagramme sur trois : les
+    // retransmissions de LSA y ouvrent une session TCP
+    sim.set_link_up(&ring.segment(2), false);
+    sim.network().set_link(&ring.segment(1), LinkProfile { loss: 0.3, ..LinkProfile::default() });
+    let has_session = |report: &str| report.lines().any(|line| line.contains("trames émises"));
+    let mut report = String::new();
+    for _ in 0..120 {
+        report = sim.handle("r1").unwrap().command("sessions").await;
+        if has_session(&report) {
+            break;
+        }
+        sim.run_for(TICK).await;
+    }
+    assert!(has_session(&report), "{}", report);
+
+    let ticks = sim.run_until(TICK, 60, |tables| tables["r1"].contains_key(&ring.lan(2)) && tables["r2"].contains_key(&ring.lan(1))).await;
+    assert!(ticks.is_some(), "{:#?}", sim.routing_tables().await);
+    let report = sim.handle("r2").unwrap().command("sessions").await;
+    assert!(has_session(&report), "{}", report);
+    // Files bornées, jamais pleines à ce débit
+    assert!(report.contains(" 0 abandonnées"), "{}", report);
+    assert!(sim.handle("r2").unwrap().command("metrics").await.contains("pospf_stream_frames_dropped_total 0"));
+
+    sim.stop().await;
+}
+
+/// Autorité de test et certificats qu'elle signe, écrits en PEM dans un dossier temporaire
+struct TestPki {
+    dir: std::path::PathBuf,
+    ca: rcgen::Certificate,
+    ca_key: rcgen::KeyPair,
+}
+
+impl TestPki {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("pospf-pki-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut params = rcgen::CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        params.key_usages = vec![rcgen::KeyUsagePurpose::KeyCertSign];
+        let ca_key = rcgen::KeyPair::generate().unwrap();
+        let ca = params.self_signed(&ca_key).unwrap();
+        std::fs::write(dir.join("ca.pem"), ca.pem()).unwrap();
+        Self { dir, ca, ca_key }
+    }
+
+    /// Certificat `pospf` d'un routeur, signé par l'autorité
+    fn issue(&self, router: &str) -> QuicConfig {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let cert = rcgen::CertificateParams::new(vec!["pospf".to_string()]).unwrap()
+            .signed_by(&key, &self.ca, &self.ca_key)
+            .unwrap();
+        let (cert_path, key_path) = (self.dir.join(format!("{router}.pem")), self.dir.join(format!("{router}.key")));
+        std::fs::write(&cert_path, cert.pem()).unwrap();
+        std::fs::write(&key_path, key.serialize_pem()).unwrap();
+        QuicConfig { cert: Some(cert_path), key: Some(key_path), ca: Some(self.dir.join("ca.pem")) }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn quic_interfaces_carry_lsas_over_a_quic_session() {
+    use base64::Engine;
+    let pki = TestPki::new("ring");
+    let ring = Ring { n: 3 };
+    let mut sim = ring
+        .build_with(131, |i| {
+            let mut config = Simulation::router_config();
+            // Les trames des sessions QUIC ne sont pas scellées par la clé du segment
+            config.encryption = true;
+            config.key = Some(base64::engine::general_purpose::STANDARD.encode([7u8; 32]));
+            if i != 3 {
+                config.interfaces = ["eth0", "eth1", "eth2"].into_iter()
+                    .map(|name| InterfaceConfig { transport: LsaTransport::Quic, ..InterfaceConfig::new(name, 1000) })
+                    .collect();
+                config.quic = pki.issue(&format!("r{i}"));
+            }
+            config
+        })
+        .await;
+    // r1 et r2 ne se parlent que par un lien perdant un datagramme sur cinq : QUIC
+    // retransmet lui-même les paquets perdus de la session
+    sim.set_link_up(&ring.segment(2), false);
+    sim.network().set_link(&ring.segment(1), LinkProfile { loss: 0.2, ..LinkProfile::default() });
+    let quic_session = |report: &str| report.lines().any(|line| line.contains(" QUIC ") && line.contains("trames émises"));
+    let mut report = String::new();
+    for _ in 0..120 {
+        report = sim.handle("r1").unwrap().command("sessions").await;
+        if quic_session(&report) {
+            break;
+        }
+        sim.run_for(TICK).await;
+    }
+    assert!(quic_session(&report), "{}", report);
+
+    let ticks = sim.run_until(TICK, 60, |tables| tables["r1"].contains_key(&ring.lan(2)) && tables["r2"].contains_key(&ring.lan(1))).await;
+    assert!(ticks.is_some(), "{:#?}", sim.routing_tables().await);
+    let report = sim.handle("r2").unwrap().command("sessions").await;
+    assert!(quic_session(&report), "{}", report);
+    // r3 reste en UDP : aucune session avec lui
+    assert_eq!(sim.handle("r3").unwrap().command("sessions").await, "Aucune session TCP ni QUIC");
+
+    sim.stop().await;
+}
+
+#[test]
+fn quic_interfaces_require_certificates() {
+    let mut config = Simulation::router_config();
+    config.interfaces = vec![InterfaceConfig { transport: LsaTransport::Quic, ..InterfaceConfig::new("eth0", 1000) }];
+    let errors = config.validation_errors();
+    assert!(errors.iter().any(|error| error.contains("quic.cert, quic.key and quic.ca are required")), "{errors:?}");
+    config.quic = TestPki::new("validation").issue("r1");
+    assert!(config.validation_errors().is_empty());
+    // Le port des sessions suit celui du protocole : il n'y en a pas après le dernier
+    config.port = u16::MAX;
+    let errors = config.validation_errors();
+    assert!(errors.iter().any(|error| error.contains("port 65535 leaves no UDP port for QUIC sessions")), "{errors:?}");
+}
+
+#[tokio::test]
+async fn quic_sessions_run_over_udp_sockets() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let pki = TestPki::new("loopback");
+    let server = Socket::Udp(tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap());
+    let client = Socket::Udp(tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap());
+    let server_endpoint = quic::endpoint(server.quic_socket().unwrap(), &pki.issue("server")).unwrap();
+    let client_endpoint = quic::endpoint(client.quic_socket().unwrap(), &pki.issue("client")).unwrap();
+    assert_eq!(server_endpoint.local_addr().unwrap().port(), server.local_addr().unwrap().port() + quic::PORT_OFFSET);
+
+    let accepting = {
+        let server_endpoint = server_endpoint.clone();
+        tokio::spawn(async move { quic::open(server_endpoint.accept().await.unwrap()).await.unwrap() })
+    };
+    let mut outgoing = quic::connect(&client_endpoint, server.local_addr().unwrap()).await.unwrap();
+    let (mut incoming, caller) = tokio::time::timeout(Duration::from_secs(5), accepting).await.unwrap().unwrap();
+    // L'appelant est identifié par l'adresse de son protocole, pas par celle de QUIC
+    assert_eq!(caller, client.local_addr().unwrap());
+
+    let mut buf = [0u8; 5];
+    outgoing.write_all(b"hello").await.unwrap();
+    incoming.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"hello");
+    incoming.write_all(b"ack!!").await.unwrap();
+    outgoing.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"ack!!");
+
+    // Fermer une extrémité ferme le flux de l'autre
+    drop(outgoing);
+    assert!(tokio::time::timeout(Duration::from_secs(5), incoming.read_u8()).await.unwrap().is_err());
+
+    // Un routeur dont le certificat vient d'une autre autorité est refusé dans les deux sens
+    let rogue_socket = Socket::Udp(tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap());
+    let rogue = quic::endpoint(rogue_socket.quic_socket().unwrap(), &TestPki::new("rogue").issue("rogue")).unwrap();
+    let accepting = {
+        let server_endpoint = server_endpoint.clone();
+        tokio::spawn(async move { quic::open(server_endpoint.accept().await.unwrap()).await })
+    };
+    assert!(tokio::time::timeout(Duration::from_secs(5), quic::connect(&rogue, server.local_addr().unwrap())).await.unwrap().is_err());
+    assert!(tokio::time::timeout(Duration::from_secs(5), accepting).await.unwrap().unwrap().is_err());
+    let accepting = tokio::spawn(async move { quic::open(rogue.accept().await.unwrap()).await });
+    assert!(tokio::time::timeout(Duration::from_secs(5), quic::connect(&client_endpoint, rogue_socket.local_addr().unwrap())).await.unwrap().is_err());
+    accepting.abort();
+}
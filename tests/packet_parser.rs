@@ -0,0 +1,288 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use routing_project::fragment;
+use routing_project::framing;
+use routing_project::packet::{self, Packet, MAX_PATH_LEN, MAX_PREFIXES};
+use routing_project::types::{AuthMode, Prefix};
+use serde_json::{json, Value};
+
+fn lsa() -> Value {
+    json!({
+        "message_type": 2,
+        "router_ip": "10.0.12.1",
+        "last_hop": null,
+        "originator": "10.0.12.1",
+        "seq_num": 42,
+        "neighbor_count": 1,
+        "neighbors": [{ "neighbor_ip": "10.0.12.2", "link_up": true, "capacity": 1000, "last_seen": 0 }],
+        "routing_table": { "192.168.1.1/24": { "Active": 0 }, "0.0.0.0/0": "Unreachable" },
+        "path": ["10.0.12.1"],
+        "ttl": 15
+    })
+}
+
+fn hello() -> Value {
+    json!({
+        "message_type": 1,
+        "router_ip": "10.0.12.1",
+        "timestamp_us": 1,
+        "echoes": [{ "router_ip": "10.0.12.2", "timestamp_us": 1, "held_us": 2 }]
+    })
+}
+
+fn parse(value: &Value) -> routing_project::error::Result<Packet> {
+    packet::parse(&serde_json::to_vec(value).unwrap())
+}
+
+#[test]
+fn accepts_valid_messages() {
+    assert!(matches!(parse(&lsa()), Ok(Packet::Lsa(_))));
+    assert!(matches!(parse(&hello()), Ok(Packet::Hello(_))));
+    assert!(matches!(parse(&json!({ "message_type": 3, "command": "show" })), Ok(Packet::Control(_))));
+    assert!(matches!(
+        parse(&json!({ "message_type": 4, "router_ip": "10.0.12.2", "acks": [{ "originator": "10.0.12.1", "seq_num": 42 }] })),
+        Ok(Packet::LsaAck(_))
+    ));
+}
+
+#[test]
+fn rejects_malformed_and_oversized_messages() {
+    let mutations: Vec<(&str, Value)> = vec![
+        ("ttl", json!(200)),
+        ("originator", json!("not-an-address")),
+        ("neighbor_count", json!(5)),
+        ("seq_num", json!(-1)),
+        ("path", json!(vec!["10.0.0.1"; MAX_PATH_LEN + 1])),
+        ("routing_table", json!({ "192.168.1.1": { "Active": 0 } })),
+        ("message_type", json!(9)),
+    ];
+    for (field, value) in mutations {
+        let mut message = lsa();
+        message[field] = value;
+        assert!(parse(&message).is_err(), "accepted invalid {}", field);
+    }
+
+    let mut message = lsa();
+    message["routing_table"] = (0..=MAX_PREFIXES)
+        .map(|i| (format!("10.{}.{}.0/24", i / 256, i % 256), json!({ "Active": 1 })))
+        .collect::<serde_json::Map<_, _>>()
+        .into();
+    assert!(parse(&message).is_err());
+
+    let mut message = hello();
+    message.as_object_mut().unwrap().remove("router_ip");
+    assert!(parse(&message).is_err());
+
+    assert!(packet::parse(b"").is_err());
+    assert!(packet::parse(b"[1, 2, 3]").is_err());
+    assert!(packet::parse(&vec![b' '; packet::MAX_PACKET_LEN + 1]).is_err());
+}
+
+/// Mutations aléatoires (graine fixe) de messages valides : l'analyseur ne doit jamais paniquer
+#[test]
+fn frames_are_checked_before_their_body() {
+    let body = serde_json::to_vec(&hello()).unwrap();
+    let datagram = framing::frame(1, framing::Codec::Json, AuthMode::Hmac, &body).unwrap();
+    assert_eq!(framing::peek_type(&datagram), Some(1));
+    let (header, framed) = framing::split(&datagram).unwrap();
+    assert_eq!((header.message_type, header.auth, header.length as usize), (1, AuthMode::Hmac, body.len()));
+    assert!(matches!(packet::parse_typed(header.message_type, framed), Ok(Packet::Hello(_))));
+
+    // Type de la trame différent de celui du message, longueur erronée, version ou codec inconnus
+    assert!(packet::parse_typed(2, framed).is_err());
+    assert!(framing::split(&datagram[..datagram.len() - 1]).is_err());
+    for (index, value) in [(0, 2), (1, 0), (1, 42), (2, 7), (3, 9)] {
+        let mut altered = datagram.clone();
+        altered[index] = value;
+        assert!(framing::split(&altered).is_err(), "byte {} = {}", index, value);
+    }
+    assert!(framing::split(&[framing::VERSION, 1]).is_err());
+    assert!(framing::frame(2, framing::Codec::Json, AuthMode::None, &vec![b' '; framing::MAX_BODY_LEN + 1]).is_err());
+}
+
+#[test]
+fn survives_random_mutations() {
+    let mut rng = StdRng::seed_from_u64(0x05bf);
+    let seeds: Vec<Vec<u8>> = [lsa(), hello()].iter().map(|value| serde_json::to_vec(value).unwrap()).collect();
+    for _ in 0..20_000 {
+        let mut data = seeds[rng.gen_range(0..seeds.len())].clone();
+        for _ in 0..rng.gen_range(1..8) {
+            let position = rng.gen_range(0..data.len());
+            match rng.gen_range(0..3) {
+                0 => data[position] = rng.gen(),
+                1 => {
+                    data.remove(position);
+                }
+                _ => data.insert(position, rng.gen()),
+            }
+            if data.is_empty() {
+                break;
+            }
+        }
+        packet::fuzz_parse(&data);
+    }
+}
+
+#[test]
+fn large_messages_are_fragmented_and_reassembled() {
+    let mut message = lsa();
+    message["routing_table"] = (0..600)
+        .map(|i| (format!("10.{}.{}.0/24", i / 256, i % 256), json!({ "Active": 3 })))
+        .collect::<serde_json::Map<_, _>>()
+        .into();
+    let serialized = serde_json::to_vec(&message).unwrap();
+    let mut fragments = fragment::split(&serialized).expect("message should be fragmented");
+    assert!(fragments.len() > 1);
+
+    // Ordre d'arrivée quelconque, doublons compris
+    fragments.reverse();
+    fragments.push(fragments[0].clone());
+    let source = "10.0.12.1:5000".parse().unwrap();
+    let mut reassembler = fragment::Reassembler::new();
+    let mut reassembled = None;
+    for fragment in &fragments {
+        let datagram = serde_json::to_vec(fragment).unwrap();
+        assert!(datagram.len() < 1400);
+        let Ok(Packet::Fragment(parsed)) = packet::parse(&datagram) else {
+            panic!("fragment rejected by the parser");
+        };
+        if let Some(message) = reassembler.insert(source, &parsed) {
+            reassembled = Some(message);
+        }
+    }
+    assert_eq!(reassembled.as_deref(), Some(serialized.as_slice()));
+    assert!(matches!(packet::parse(&serialized), Ok(Packet::Lsa(lsa)) if lsa.routing_table.len() == 600));
+}
+
+#[test]
+fn fragments_fit_the_path_mtu() {
+    let mut message = lsa();
+    message["routing_table"] = (0..600)
+        .map(|i| (format!("10.{}.{}.0/24", i / 256, i % 256), json!({ "Active": 3 })))
+        .collect::<serde_json::Map<_, _>>()
+        .into();
+    let serialized = serde_json::to_vec(&message).unwrap();
+    assert_eq!(fragment::payload_for_mtu(fragment::DEFAULT_MTU), fragment::MAX_FRAGMENT_PAYLOAD);
+    for mtu in [fragment::MIN_MTU, 1024, 1280] {
+        let fragments = fragment::split_to(&serialized, fragment::payload_for_mtu(mtu)).unwrap();
+        assert!(fragments.len() <= fragment::MAX_FRAGMENTS as usize);
+        for fragment in &fragments {
+            // En-têtes IPv4, UDP et de trame, et surcoût du chiffrement compris
+            let datagram = serde_json::to_vec(fragment).unwrap();
+            assert!(datagram.len() + 28 + 6 + 32 <= mtu as usize, "{} bytes for MTU {}", datagram.len(), mtu);
+            assert!(matches!(packet::parse(&datagram), Ok(Packet::Fragment(_))));
+        }
+    }
+}
+
+#[test]
+fn prefixes_are_typed_and_installed_without_host_bits() {
+    let Ok(Packet::Lsa(lsa)) = parse(&lsa()) else {
+        panic!("valid LSA rejected");
+    };
+    let lan: Prefix = "192.168.1.1/24".parse().unwrap();
+    assert!(lsa.routing_table.contains_key(&lan));
+    assert!(lsa.routing_table.contains_key(&Prefix::default_route()));
+    assert_eq!(lan.network().to_string(), "192.168.1.0/24");
+    assert!(lan.covers(&"192.168.1.128/25".parse().unwrap()));
+    assert!("192.168.1.1".parse::<Prefix>().is_err());
+    assert_eq!(serde_json::to_value(lan).unwrap(), json!("192.168.1.1/24"));
+}
+
+#[test]
+fn ospfv2_packets_round_trip_and_checksums_are_verified() {
+    use routing_project::ospfv2::{self, Body, Hello, Lsa, LsaBody, RouterLink, INITIAL_SEQUENCE, LINK_STUB, ROUTER_LSA};
+    use std::net::Ipv4Addr;
+
+    let router_id = Ipv4Addr::new(10, 0, 0, 1);
+    let hello = ospfv2::Packet {
+        router_id,
+        area_id: Ipv4Addr::UNSPECIFIED,
+        body: Body::Hello(Hello {
+            network_mask: Ipv4Addr::new(255, 255, 255, 252),
+            hello_interval: 10,
+            options: ospfv2::OPTION_E,
+            priority: 1,
+            dead_interval: 40,
+            designated_router: Ipv4Addr::UNSPECIFIED,
+            backup_designated_router: Ipv4Addr::UNSPECIFIED,
+            neighbors: vec![Ipv4Addr::new(10, 0, 0, 2)],
+        }),
+    };
+    assert_eq!(ospfv2::Packet::decode(&hello.encode()).unwrap(), hello);
+
+    let lsa = Lsa::originate(ROUTER_LSA, router_id, router_id, INITIAL_SEQUENCE, LsaBody::Router {
+        flags: 0,
+        links: vec![RouterLink {
+            link_id: Ipv4Addr::new(192, 168, 1, 0),
+            link_data: Ipv4Addr::new(255, 255, 255, 0),
+            link_type: LINK_STUB,
+            metric: 10,
+        }],
+    });
+    assert!(ospfv2::fletcher_valid(&lsa.encode()[2..]));
+    let update = ospfv2::Packet { router_id, area_id: Ipv4Addr::UNSPECIFIED, body: Body::LinkStateUpdate(vec![lsa]) };
+    let mut bytes = update.encode();
+    assert_eq!(ospfv2::Packet::decode(&bytes).unwrap(), update);
+
+    // Somme de contrôle du paquet, puis celle du LSA (paquet recalculé), corrompues
+    bytes[ospfv2::HEADER_LEN + 4 + 16] ^= 0xff;
+    assert!(ospfv2::Packet::decode(&bytes).is_err());
+    bytes[12..14].fill(0);
+    let checksum = ospfv2::internet_checksum(&bytes);
+    bytes[12..14].copy_from_slice(&checksum.to_be_bytes());
+    assert!(ospfv2::Packet::decode(&bytes).is_err());
+}
+
+#[test]
+fn mdns_announcements_round_trip_and_follow_compression() {
+    use routing_project::mdns::{self, Announcement, SERVICE};
+    use std::net::Ipv4Addr;
+
+    let query = mdns::decode(&mdns::encode_query(SERVICE), SERVICE).unwrap();
+    assert_eq!(query.queries, vec![SERVICE.to_string()]);
+    assert!(query.announcements.is_empty());
+
+    let announcement = Announcement {
+        instance: "pospf-10-0-0-1".to_string(),
+        port: 5000,
+        addresses: vec![Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 1, 0, 1)],
+        ttl: 180,
+    };
+    let response = mdns::decode(&mdns::encode_response(SERVICE, &announcement), SERVICE).unwrap();
+    assert!(response.queries.is_empty());
+    assert_eq!(response.announcements, vec![announcement.clone()]);
+
+    // Réponse compressée : le PTR désigne l'instance par un pointeur vers le nom du service
+    let mut data = vec![0, 0, 0x84, 0, 0, 0, 0, 3, 0, 0, 0, 0];
+    let service_offset = data.len() as u8;
+    for label in ["_pospf", "_udp", "local"] {
+        data.push(label.len() as u8);
+        data.extend_from_slice(label.as_bytes());
+    }
+    data.push(0);
+    data.extend_from_slice(&[0, 12, 0, 1, 0, 0, 0, 60, 0, 10]);
+    let instance_offset = data.len() as u8;
+    data.extend_from_slice(&[7]);
+    data.extend_from_slice(b"POSPF-B");
+    data.extend_from_slice(&[0xc0, service_offset]);
+    data.extend_from_slice(&[0xc0, instance_offset, 0, 33, 0x80, 1, 0, 0, 0, 60, 0, 10, 0, 0, 0, 0, 0x13, 0x88]);
+    let host_offset = data.len() as u8;
+    data.extend_from_slice(&[1, b'b', 0xc0, service_offset + 12]);
+    data.extend_from_slice(&[0xc0, host_offset, 0, 1, 0x80, 1, 0, 0, 0, 60, 0, 4, 172, 16, 0, 2]);
+    let compressed = mdns::decode(&data, SERVICE).unwrap();
+    assert_eq!(compressed.announcements, vec![Announcement {
+        instance: "pospf-b".to_string(),
+        port: 5000,
+        addresses: vec![Ipv4Addr::new(172, 16, 0, 2)],
+        ttl: 60,
+    }]);
+
+    // Boucle de pointeurs et messages tronqués : rejetés sans paniquer
+    let looping = [0, 0, 0x84, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0xc0, 12];
+    assert!(mdns::decode(&looping, SERVICE).is_err());
+    let encoded = mdns::encode_response(SERVICE, &announcement);
+    for len in 0..encoded.len() {
+        assert!(mdns::decode(&encoded[..len], SERVICE).is_err());
+    }
+}